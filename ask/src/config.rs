@@ -0,0 +1,257 @@
+// ask-specific configuration
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Post-processing applied to a response before it is printed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OutputConfig {
+    /// Remove markdown code fences, keeping the code inside them.
+    /// Defaults to on in shell mode, where the response is a command that is
+    /// copied to the clipboard, and off in general mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strip_fences: Option<bool>,
+
+    /// Wrap prose to the terminal width (code blocks are left as-is)
+    #[serde(default)]
+    pub wrap: bool,
+
+    /// Color fenced code blocks
+    #[serde(default)]
+    pub highlight: bool,
+
+    /// Shell command the response is piped through for display, e.g. "bat -p -l md"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pipe: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Outputs {
+    /// Shell mode (the default)
+    #[serde(default)]
+    pub shell: OutputConfig,
+
+    /// General mode (`--general`)
+    #[serde(default)]
+    pub general: OutputConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// Record each question and answer in ~/.config/cli-programs/ask-history.jsonl
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// Price of a model in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+}
+
+impl ModelPrice {
+    /// Estimated cost in USD of a request with this many tokens
+    pub fn cost(&self, input_tokens: u32, output_tokens: u32) -> f64 {
+        (input_tokens as f64 * self.input + output_tokens as f64 * self.output) / 1_000_000.0
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatsConfig {
+    /// Print model, tokens, estimated cost, and latency after each answer
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Prices used for the cost estimate, keyed by model name or prefix
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub prices: HashMap<String, ModelPrice>,
+}
+
+impl StatsConfig {
+    /// Price for a model, matching the longest key the model name starts
+    /// with so that "claude-sonnet-4-5" also prices dated model IDs
+    pub fn price_for(&self, model: &str) -> Option<&ModelPrice> {
+        self.prices
+            .iter()
+            .filter(|(key, _)| model.starts_with(key.as_str()))
+            .max_by_key(|(key, _)| key.len())
+            .map(|(_, price)| price)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExecConfig {
+    /// Offer to run the suggested command in shell mode, as with `--exec`
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AskConfig {
+    /// Preset to retry once with when a response is empty or a refusal.
+    /// Defaults to the preset's `fallback` in llm.toml.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secondary_preset: Option<String>,
+
+    /// Output post-processing per mode
+    #[serde(default)]
+    pub output: Outputs,
+
+    /// Question and answer history
+    #[serde(default)]
+    pub history: HistoryConfig,
+
+    /// Usage footer shown after each answer
+    #[serde(default)]
+    pub stats: StatsConfig,
+
+    /// Running suggested commands
+    #[serde(default)]
+    pub exec: ExecConfig,
+}
+
+impl AskConfig {
+    /// Get the config file path: ~/.config/cli-programs/ask.toml
+    pub fn config_path() -> Result<PathBuf> {
+        let home = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE"))?;
+        Ok(PathBuf::from(home)
+            .join(".config")
+            .join("cli-programs")
+            .join("ask.toml"))
+    }
+
+    /// Load config from file, returning default if file doesn't exist
+    pub fn load() -> Result<Self> {
+        let path = Self::config_path()?;
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Output settings for shell or general mode
+    pub fn output_for(&self, general: bool) -> &OutputConfig {
+        if general {
+            &self.output.general
+        } else {
+            &self.output.shell
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = AskConfig::default();
+        assert_eq!(config.output_for(false), &OutputConfig::default());
+        assert_eq!(config.output_for(true), &OutputConfig::default());
+        assert!(config.history.enabled);
+        assert_eq!(config.stats, StatsConfig::default());
+        assert!(!config.exec.enabled);
+    }
+
+    #[test]
+    fn test_parse_exec() {
+        let config: AskConfig = toml::from_str("[exec]\nenabled = true\n").unwrap();
+        assert!(config.exec.enabled);
+    }
+
+    #[test]
+    fn test_parse_output_config() {
+        let config: AskConfig = toml::from_str(
+            r#"
+            [output.general]
+            wrap = true
+            highlight = true
+            pipe = "bat -p -l md"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.output.shell, OutputConfig::default());
+        let general = config.output_for(true);
+        assert!(general.wrap);
+        assert!(general.highlight);
+        assert_eq!(general.strip_fences, None);
+        assert_eq!(general.pipe.as_deref(), Some("bat -p -l md"));
+    }
+
+    #[test]
+    fn test_parse_secondary_preset() {
+        let config: AskConfig = toml::from_str("secondary_preset = \"sonnet\"\n").unwrap();
+        assert_eq!(config.secondary_preset.as_deref(), Some("sonnet"));
+        assert_eq!(AskConfig::default().secondary_preset, None);
+    }
+
+    #[test]
+    fn test_parse_strip_fences_override() {
+        let config: AskConfig = toml::from_str("[output.shell]\nstrip_fences = false\n").unwrap();
+        assert_eq!(config.output.shell.strip_fences, Some(false));
+    }
+
+    #[test]
+    fn test_parse_history_opt_out() {
+        let config: AskConfig = toml::from_str("[history]\nenabled = false\n").unwrap();
+        assert!(!config.history.enabled);
+
+        let config: AskConfig = toml::from_str("[history]\n").unwrap();
+        assert!(config.history.enabled);
+    }
+
+    #[test]
+    fn test_parse_stats() {
+        let config: AskConfig = toml::from_str(
+            r#"
+            [stats]
+            enabled = true
+
+            [stats.prices]
+            "claude-sonnet-4" = { input = 3.0, output = 15.0 }
+            "claude-sonnet-4-5" = { input = 3.5, output = 16.0 }
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.stats.enabled);
+        let price = config
+            .stats
+            .price_for("claude-sonnet-4-5-20250929")
+            .unwrap();
+        assert_eq!(price.input, 3.5);
+        assert_eq!(
+            config.stats.price_for("claude-sonnet-4-1").unwrap().input,
+            3.0
+        );
+        assert!(config.stats.price_for("gpt-5").is_none());
+    }
+
+    #[test]
+    fn test_model_price_cost() {
+        let price = ModelPrice {
+            input: 3.0,
+            output: 15.0,
+        };
+        assert!((price.cost(1_000, 200) - 0.006).abs() < 1e-9);
+    }
+}
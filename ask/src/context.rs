@@ -0,0 +1,131 @@
+//! Environment context for shell mode
+//!
+//! Gathers lightweight details about where the question is being asked
+//! (OS, directory, shell, project type) so suggested commands fit the project.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Marker files used to detect the kind of project in the current directory
+const PROJECT_MARKERS: &[(&str, &str)] = &[
+    ("Cargo.toml", "Rust (cargo)"),
+    ("package.json", "Node.js (npm)"),
+    ("pyproject.toml", "Python"),
+    ("requirements.txt", "Python (pip)"),
+    ("go.mod", "Go"),
+    ("Gemfile", "Ruby (bundler)"),
+    ("pom.xml", "Java (maven)"),
+    ("build.gradle", "Java/Kotlin (gradle)"),
+    ("Makefile", "make"),
+    ("Dockerfile", "Docker"),
+];
+
+/// Snapshot of the user's shell environment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvironmentContext {
+    pub os: Option<String>,
+    pub cwd: Option<String>,
+    pub shell: Option<String>,
+    pub project_types: Vec<&'static str>,
+}
+
+impl EnvironmentContext {
+    /// Detect context from the current process environment
+    pub fn detect() -> Self {
+        let cwd = std::env::current_dir().ok();
+
+        Self {
+            os: detect_os(),
+            cwd: cwd.as_ref().map(|p| p.display().to_string()),
+            shell: std::env::var("SHELL").ok().and_then(|s| {
+                Path::new(&s)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+            }),
+            project_types: cwd.as_deref().map(detect_project_types).unwrap_or_default(),
+        }
+    }
+
+    /// Render as a short block to append to the system prompt
+    pub fn to_prompt_section(&self) -> String {
+        let mut lines = vec!["User environment:".to_string()];
+
+        if let Some(os) = &self.os {
+            lines.push(format!("- OS: {}", os));
+        }
+        if let Some(shell) = &self.shell {
+            lines.push(format!("- Shell: {}", shell));
+        }
+        if let Some(cwd) = &self.cwd {
+            lines.push(format!("- Current directory: {}", cwd));
+        }
+        if !self.project_types.is_empty() {
+            lines.push(format!("- Project type: {}", self.project_types.join(", ")));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Detect project types from marker files in a directory
+pub fn detect_project_types(dir: &Path) -> Vec<&'static str> {
+    PROJECT_MARKERS
+        .iter()
+        .filter(|(file, _)| dir.join(file).exists())
+        .map(|(_, kind)| *kind)
+        .collect()
+}
+
+/// Describe the OS, preferring the macOS product version when available
+fn detect_os() -> Option<String> {
+    let run = |cmd: &str, args: &[&str]| {
+        Command::new(cmd)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+
+    if cfg!(target_os = "macos")
+        && let Some(version) = run("sw_vers", &["-productVersion"])
+    {
+        return Some(format!("macOS {}", version));
+    }
+
+    run("uname", &["-sr"])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_project_types() {
+        let dir = std::env::temp_dir().join(format!("ask-context-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Cargo.toml"), "").unwrap();
+        std::fs::write(dir.join("Makefile"), "").unwrap();
+
+        let types = detect_project_types(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(types, vec!["Rust (cargo)", "make"]);
+    }
+
+    #[test]
+    fn test_prompt_section_skips_missing_fields() {
+        let context = EnvironmentContext {
+            os: Some("macOS 15.1".to_string()),
+            cwd: None,
+            shell: Some("zsh".to_string()),
+            project_types: vec!["Rust (cargo)"],
+        };
+
+        assert_eq!(
+            context.to_prompt_section(),
+            "User environment:\n- OS: macOS 15.1\n- Shell: zsh\n- Project type: Rust (cargo)"
+        );
+    }
+}
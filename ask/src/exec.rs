@@ -0,0 +1,183 @@
+// Running a suggested command after confirmation
+
+use anyhow::{Context, Result};
+use llm_client::{RegexFilter, ResponseFilter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::Command;
+use std::sync::LazyLock;
+
+/// Patterns for commands that need an explicit "y" to run, described by
+/// what they do
+static DESTRUCTIVE: LazyLock<RegexFilter> = LazyLock::new(|| {
+    RegexFilter::described(&[
+        (
+            r"\brm\s(?:[^|;&]*\s)?-(?:[a-zA-Z]*[rRf][a-zA-Z]*|-recursive|-force)\b",
+            "removes files recursively or without asking",
+        ),
+        (r"\bfind\b[^|;&]*\s-delete\b", "deletes the files find matches"),
+        (r"\bsudo\b", "runs with root privileges"),
+        (
+            r"\b(?:mkfs(?:\.\w+)?|fdisk|wipefs|diskutil\s+(?:erase\w*|partition\w*|zero\w*))\b",
+            "erases or partitions a disk",
+        ),
+        (r"\bdd\b[^|;&]*\bof=", "writes raw data with dd"),
+        (r">\s*/dev/(?:sd|disk|nvme|hd)", "overwrites a disk device"),
+        (
+            r"\bgit\s+(?:push\b[^|;&]*\s(?:--force\b|--force-with-lease\b|-f\b)|reset\s+--hard\b|clean\s+-[a-zA-Z]*f)",
+            "discards git history or local changes",
+        ),
+        (
+            r"\bch(?:mod|own)\s+(?:-[a-zA-Z]*R|--recursive)\b",
+            "changes ownership or permissions recursively",
+        ),
+        (r":\(\)\s*\{", "defines a fork bomb"),
+        (r"\b(?:shutdown|reboot|halt)\b", "shuts down the machine"),
+        (
+            r"\b(?:curl|wget)\b[^|;&]*\|\s*(?:sudo\s+)?(?:ba|z)?sh\b",
+            "runs a downloaded script",
+        ),
+    ])
+    .unwrap()
+});
+
+/// What makes a command look destructive; empty if nothing does
+pub fn destructive_reasons(command: &str) -> Vec<String> {
+    DESTRUCTIVE.check(command)
+}
+
+/// Whether an answer to the confirmation prompt means yes; an empty answer
+/// takes the default
+fn is_yes(answer: &str, default: bool) -> bool {
+    match answer.trim().to_lowercase().as_str() {
+        "" => default,
+        "y" | "yes" => true,
+        _ => false,
+    }
+}
+
+/// Ask on the terminal whether to run the command, showing it exactly as it
+/// will run. Destructive commands are listed with a warning and default to no.
+///
+/// The terminal is read directly, since stdin may have been piped input.
+fn confirm(command: &str, reasons: &[String]) -> Result<bool> {
+    eprintln!("$ {}", command);
+    if !reasons.is_empty() {
+        eprintln!("Warning: this command looks destructive:");
+        for reason in reasons {
+            eprintln!("  - {}", reason);
+        }
+    }
+    let default = reasons.is_empty();
+    eprint!("Run it? {} ", if default { "[Y/n]" } else { "[y/N]" });
+    io::stderr().flush().ok();
+
+    let tty = File::open("/dev/tty").context("--exec needs a terminal to confirm")?;
+    let mut answer = String::new();
+    BufReader::new(tty)
+        .read_line(&mut answer)
+        .context("Failed to read confirmation")?;
+    Ok(is_yes(&answer, default))
+}
+
+/// Confirm and run a suggested command through the user's shell, returning
+/// its exit status, or `None` if the user declined
+pub fn confirm_and_run(command: &str) -> Result<Option<i32>> {
+    let command = command.trim();
+    if command.is_empty() {
+        anyhow::bail!("No command to run");
+    }
+
+    if !confirm(command, &destructive_reasons(command))? {
+        eprintln!("Not run.");
+        return Ok(None);
+    }
+
+    // The command is passed as a single argument, so the shell parses it
+    // exactly as if it had been typed
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+    let status = Command::new(&shell)
+        .arg("-c")
+        .arg(command)
+        .status()
+        .with_context(|| format!("Failed to run {}", shell))?;
+
+    // Killed by a signal
+    Ok(Some(status.code().unwrap_or(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_destructive_commands() {
+        for command in [
+            "rm -rf ~/tmp/build",
+            "rm -r old/",
+            "rm --force *.log",
+            "find . -name '*.pyc' -delete",
+            "sudo apt-get update",
+            "mkfs.ext4 /dev/sdb1",
+            "diskutil eraseDisk APFS Backup disk2",
+            "dd if=image.iso of=/dev/disk2 bs=4m",
+            "cat zeros > /dev/sda",
+            "git push --force origin main",
+            "git push -f",
+            "git reset --hard HEAD~3",
+            "git clean -fdx",
+            "chmod -R 777 .",
+            ":(){ :|:& };:",
+            "curl -fsSL https://example.com/install.sh | bash",
+        ] {
+            assert!(
+                !destructive_reasons(command).is_empty(),
+                "{} should be flagged",
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn test_safe_commands() {
+        for command in [
+            "ls -la",
+            "rm notes.txt",
+            "rm my-draft.txt",
+            "find . -name '*.rs' | xargs wc -l",
+            "git push origin feature-branch",
+            "git reset --soft HEAD~1",
+            "chmod +x script.sh",
+            "curl -o install.sh https://example.com/install.sh",
+            "grep -rf patterns.txt src",
+        ] {
+            assert!(
+                destructive_reasons(command).is_empty(),
+                "{} should not be flagged: {:?}",
+                command,
+                destructive_reasons(command)
+            );
+        }
+    }
+
+    #[test]
+    fn test_destructive_reasons_are_listed() {
+        assert_eq!(
+            destructive_reasons("sudo rm -rf /var/cache"),
+            vec![
+                "removes files recursively or without asking",
+                "runs with root privileges"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_yes() {
+        assert!(is_yes("\n", true));
+        assert!(!is_yes("\n", false));
+        assert!(is_yes("Y\n", false));
+        assert!(is_yes("yes", false));
+        assert!(!is_yes("n", true));
+        assert!(!is_yes("nope", true));
+    }
+}
@@ -0,0 +1,219 @@
+// Question and answer history
+//
+// Each answered question is appended to a JSON Lines file so that an answer
+// from last week can be found again with `ask history <query>` instead of
+// asking (and paying) again. Entries are numbered so that `--rerun <id>` can
+// send the same question again when a fresh answer is wanted.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// One question and the answer it got
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: u64,
+    pub timestamp: DateTime<Local>,
+    /// The question as sent, including any piped input
+    pub prompt: String,
+    /// Asked in general mode rather than shell mode
+    pub general: bool,
+    /// Files attached to the question
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<PathBuf>,
+    /// The response, before output post-processing
+    pub answer: String,
+}
+
+impl HistoryEntry {
+    /// Whether every whitespace-separated term in `query` appears in the
+    /// question or the answer, ignoring case
+    pub fn matches(&self, query: &str) -> bool {
+        let prompt = self.prompt.to_lowercase();
+        let answer = self.answer.to_lowercase();
+        query.split_whitespace().all(|term| {
+            let term = term.to_lowercase();
+            prompt.contains(&term) || answer.contains(&term)
+        })
+    }
+
+    /// The entry as listed by `ask history`
+    pub fn render(&self) -> String {
+        let mode = if self.general { "general" } else { "shell" };
+        let mut text = format!(
+            "#{}  {}  ({})\n",
+            self.id,
+            self.timestamp.format("%Y-%m-%d %H:%M"),
+            mode
+        );
+        text.push_str(&format!("Q: {}\n", first_line(&self.prompt)));
+        for (i, line) in self.answer.trim_end().lines().enumerate() {
+            let prefix = if i == 0 { "A: " } else { "   " };
+            text.push_str(&format!("{}{}\n", prefix, line));
+        }
+        text
+    }
+}
+
+/// The first line of a multi-line prompt (e.g. a question followed by piped
+/// input), marked as shortened
+fn first_line(prompt: &str) -> String {
+    let prompt = prompt.trim();
+    match prompt.split_once('\n') {
+        Some((first, _)) => format!("{} ...", first.trim_end()),
+        None => prompt.to_string(),
+    }
+}
+
+pub struct History;
+
+impl History {
+    /// Get the history file path: ~/.config/cli-programs/ask-history.jsonl
+    pub fn history_path() -> Result<PathBuf> {
+        let home = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE"))?;
+        Ok(PathBuf::from(home)
+            .join(".config")
+            .join("cli-programs")
+            .join("ask-history.jsonl"))
+    }
+
+    /// Read every entry, oldest first. Lines that can't be parsed are skipped.
+    pub fn load() -> Result<Vec<HistoryEntry>> {
+        let path = Self::history_path()?;
+
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read history: {}", path.display()))?;
+        Ok(parse(&content))
+    }
+
+    /// Append a question and its answer, returning the new entry's id
+    pub fn append(prompt: &str, general: bool, files: &[PathBuf], answer: &str) -> Result<u64> {
+        let id = Self::load()?.last().map_or(1, |entry| entry.id + 1);
+        let entry = HistoryEntry {
+            id,
+            timestamp: Local::now(),
+            prompt: prompt.to_string(),
+            general,
+            files: files.to_vec(),
+            answer: answer.to_string(),
+        };
+
+        let path = Self::history_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open history: {}", path.display()))?;
+
+        let line = serde_json::to_string(&entry).context("Failed to serialize history entry")?;
+        writeln!(file, "{}", line).context("Failed to write history entry")?;
+
+        Ok(id)
+    }
+
+    /// Find an entry by id
+    pub fn get(id: u64) -> Result<HistoryEntry> {
+        Self::load()?
+            .into_iter()
+            .find(|entry| entry.id == id)
+            .with_context(|| format!("No history entry #{}", id))
+    }
+}
+
+fn parse(content: &str) -> Vec<HistoryEntry> {
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// The newest `limit` entries matching `query` (all entries if it is empty),
+/// newest first
+pub fn search<'a>(entries: &'a [HistoryEntry], query: &str, limit: usize) -> Vec<&'a HistoryEntry> {
+    entries
+        .iter()
+        .rev()
+        .filter(|entry| entry.matches(query))
+        .take(limit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(id: u64, prompt: &str, answer: &str) -> HistoryEntry {
+        HistoryEntry {
+            id,
+            timestamp: Local.with_ymd_and_hms(2026, 10, 11, 14, 2, 0).unwrap(),
+            prompt: prompt.to_string(),
+            general: false,
+            files: Vec::new(),
+            answer: answer.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_history_path() {
+        let path = History::history_path().unwrap();
+        assert!(path.ends_with("cli-programs/ask-history.jsonl"));
+    }
+
+    #[test]
+    fn test_matches_every_term() {
+        let entry = entry(1, "How do I list open ports", "lsof -i -P -n | grep LISTEN");
+        assert!(entry.matches("ports"));
+        assert!(entry.matches("PORTS lsof"));
+        assert!(entry.matches(""));
+        assert!(!entry.matches("ports netstat"));
+    }
+
+    #[test]
+    fn test_search_newest_first() {
+        let entries = vec![
+            entry(1, "list open ports", "lsof -i"),
+            entry(2, "disk usage by folder", "du -sh *"),
+            entry(3, "open ports on macOS", "lsof -i -P"),
+        ];
+        let ids = |found: Vec<&HistoryEntry>| found.iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(ids(search(&entries, "ports", 10)), vec![3, 1]);
+        assert_eq!(ids(search(&entries, "", 2)), vec![3, 2]);
+        assert!(search(&entries, "docker", 10).is_empty());
+    }
+
+    #[test]
+    fn test_parse_skips_bad_lines() {
+        let line = serde_json::to_string(&entry(4, "q", "a")).unwrap();
+        let content = format!("{}\nnot json\n", line);
+        assert_eq!(parse(&content), vec![entry(4, "q", "a")]);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut entry = entry(
+            7,
+            "summarize this\n\nlong piped log",
+            "Line one\nLine two\n",
+        );
+        entry.general = true;
+        assert_eq!(
+            entry.render(),
+            "#7  2026-10-11 14:02  (general)
+Q: summarize this ...
+A: Line one
+   Line two
+"
+        );
+    }
+}
@@ -3,33 +3,94 @@
 //! Provides a simplified interface to the llm-client crate.
 
 use anyhow::{Context, Result};
-use llm_client::{Config, FileAttachment, LlmProvider, LlmRequest, get_provider};
+use llm_client::{
+    Config, FileAttachment, LlmProvider, LlmRequest, LlmResponse, get_provider, profiles,
+    with_rate_limits,
+};
 use serde_json::Value;
 use std::path::Path;
 
+/// Phrases that open a refusal rather than an answer
+const REFUSAL_PREFIXES: &[&str] = &[
+    "i'm sorry, but i can't",
+    "i'm sorry, but i cannot",
+    "i'm sorry, i can't",
+    "i can't help with",
+    "i cannot help with",
+    "i can't assist with",
+    "i cannot assist with",
+    "i'm not able to help",
+    "i'm unable to help",
+    "i won't be able to help",
+];
+
+/// Refusals are short; longer responses that start apologetically usually go
+/// on to answer
+const MAX_REFUSAL_LEN: usize = 300;
+
+/// Why a response is not worth showing, if it isn't
+pub fn unusable_reason(content: &str) -> Option<&'static str> {
+    let content = content.trim();
+    if content.is_empty() {
+        return Some("empty response");
+    }
+
+    let lower = content.to_lowercase().replace('\u{2019}', "'");
+    if content.len() <= MAX_REFUSAL_LEN && REFUSAL_PREFIXES.iter().any(|p| lower.starts_with(p)) {
+        return Some("refusal");
+    }
+
+    None
+}
+
 /// Wrapper around LLM providers for ask
 pub struct LlmClient {
     provider: Box<dyn LlmProvider>,
+    config: Config,
+    /// Preset to retry once with when the response is empty or a refusal
+    retry_preset: Option<String>,
     debug: bool,
 }
 
+/// Initialize the provider for a preset, with rate limits applied
+fn build_provider(config: &Config, preset_name: &str) -> Result<Box<dyn LlmProvider>> {
+    let preset = config
+        .get_preset(preset_name)
+        .context(format!("Unknown preset: {}", preset_name))?;
+
+    let provider_config = config.get_provider_config(&preset.provider);
+    let provider = get_provider(preset, provider_config).context(format!(
+        "Failed to initialize provider '{}' for preset '{}'",
+        preset.provider, preset_name
+    ))?;
+    with_rate_limits(config, &preset.provider, provider).context("Failed to set up rate limits")
+}
+
 impl LlmClient {
     /// Create a new LLM client
     ///
-    /// If preset_name is None, uses the default preset from config.
-    pub fn new(preset_name: Option<&str>, debug: bool) -> Result<Self> {
+    /// If preset_name is None, uses ask's default preset from config, or else
+    /// the preset serving the cheap-fast profile.
+    /// `secondary_preset` (from ask.toml) is retried when a response is empty
+    /// or a refusal; without it, the preset's `fallback` is used.
+    pub fn new(
+        preset_name: Option<&str>,
+        secondary_preset: Option<&str>,
+        debug: bool,
+    ) -> Result<Self> {
         let config = Config::load().context("Failed to load LLM configuration")?;
 
-        let preset_name = preset_name.unwrap_or_else(|| config.get_default_for_program("ask"));
-        let preset = config
-            .get_preset(preset_name)
-            .context(format!("Unknown preset: {}", preset_name))?;
+        let preset_name = preset_name
+            .or(config.defaults.get("ask").map(String::as_str))
+            .unwrap_or_else(|| config.preset_for_profile(profiles::CHEAP_FAST))
+            .to_string();
+        let provider = build_provider(&config, &preset_name)?;
+        let preset = config.get_preset(&preset_name)?;
 
-        let provider_config = config.get_provider_config(&preset.provider);
-        let provider = get_provider(preset, provider_config).context(format!(
-            "Failed to initialize provider '{}' for preset '{}'",
-            preset.provider, preset_name
-        ))?;
+        let retry_preset = secondary_preset
+            .map(String::from)
+            .or_else(|| preset.fallback.clone())
+            .filter(|name| *name != preset_name);
 
         if debug {
             eprintln!(
@@ -37,9 +98,17 @@ impl LlmClient {
                 provider.name(),
                 preset.model
             );
+            if let Some(retry) = &retry_preset {
+                eprintln!("Retry preset for empty or refused responses: {}", retry);
+            }
         }
 
-        Ok(Self { provider, debug })
+        Ok(Self {
+            provider,
+            config,
+            retry_preset,
+            debug,
+        })
     }
 
     /// Send a completion request to the LLM
@@ -47,13 +116,15 @@ impl LlmClient {
     /// System prompt is optional - used in shell mode, not in general mode.
     /// Files are optional - used for multimodal requests (e.g., audio/image analysis).
     /// json_schema is optional - used for structured output with OpenAI-compatible providers.
+    ///
+    /// An empty or refused response is retried once with the retry preset.
     pub async fn complete(
         &self,
         prompt: &str,
         system_prompt: Option<&str>,
         file_paths: &[impl AsRef<Path>],
         json_schema: Option<Value>,
-    ) -> Result<String> {
+    ) -> Result<LlmResponse> {
         // Load files and determine MIME types
         let files = file_paths
             .iter()
@@ -69,8 +140,38 @@ impl LlmClient {
             json_schema,
         };
 
+        let response = self.send(self.provider.as_ref(), request.clone()).await?;
+
+        let (Some(reason), Some(retry_preset)) =
+            (unusable_reason(&response.content), &self.retry_preset)
+        else {
+            return Ok(response);
+        };
+
+        eprintln!("... retrying with \"{}\" ({})", retry_preset, reason);
+        let provider = build_provider(&self.config, retry_preset)?;
+        let retried = self
+            .send(provider.as_ref(), request)
+            .await
+            .context(format!("Retry with preset '{}' failed", retry_preset))?;
+
+        if self.debug {
+            match unusable_reason(&retried.content) {
+                Some(reason) => eprintln!(
+                    "{} also gave an unusable response ({})",
+                    provider.name(),
+                    reason
+                ),
+                None => eprintln!("Answered by {} after retry", provider.name()),
+            }
+        }
+
+        Ok(retried)
+    }
+
+    async fn send(&self, provider: &dyn LlmProvider, request: LlmRequest) -> Result<LlmResponse> {
         if self.debug {
-            eprintln!("Sending request to {}", self.provider.name());
+            eprintln!("Sending request to {}", provider.name());
             if !request.files.is_empty() {
                 eprintln!("  with {} file attachment(s)", request.files.len());
             }
@@ -79,13 +180,22 @@ impl LlmClient {
             }
         }
 
-        let response = self
-            .provider
-            .complete(request)
-            .await
-            .context("LLM request failed")?;
+        let response = match provider.complete(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                if let Some(hint) = e.remediation() {
+                    eprintln!("Hint: {}", hint);
+                }
+                return Err(e).context("LLM request failed");
+            }
+        };
 
         if self.debug {
+            eprintln!(
+                "Model: {} ({:.1}s)",
+                response.model,
+                response.latency.as_secs_f64()
+            );
             if let Some(usage) = &response.usage {
                 eprintln!(
                     "Tokens: {} in, {} out",
@@ -94,14 +204,23 @@ impl LlmClient {
             }
         }
 
-        Ok(response.content)
+        if response.is_truncated()
+            && let Some(reason) = &response.finish_reason
+        {
+            eprintln!(
+                "Warning: response from {} was cut off ({}); output may be incomplete",
+                response.model, reason
+            );
+        }
+
+        Ok(response)
     }
 }
 
 /// Load a file and determine its MIME type
 fn load_file_attachment(path: &Path) -> Result<FileAttachment> {
-    let data = std::fs::read(path)
-        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+    let data =
+        std::fs::read(path).with_context(|| format!("Failed to read file: {}", path.display()))?;
 
     let mime_type = mime_type_from_extension(path);
 
@@ -129,3 +248,30 @@ fn mime_type_from_extension(path: &Path) -> String {
         _ => "application/octet-stream".to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unusable_reason() {
+        assert_eq!(unusable_reason(""), Some("empty response"));
+        assert_eq!(unusable_reason("  \n"), Some("empty response"));
+        assert_eq!(
+            unusable_reason("I'm sorry, but I can't help with that request."),
+            Some("refusal")
+        );
+        assert_eq!(
+            unusable_reason("I\u{2019}m sorry, but I cannot assist with that."),
+            Some("refusal")
+        );
+        assert_eq!(unusable_reason("ls -la"), None);
+
+        // A long answer that opens with an apology is still an answer
+        let long = format!(
+            "I can't help with sudo here, but {}",
+            "try this. ".repeat(40)
+        );
+        assert_eq!(unusable_reason(&long), None);
+    }
+}
@@ -1,9 +1,19 @@
+mod config;
+mod context;
+mod exec;
+mod history;
 mod llm;
+mod output;
+mod stats;
 
 use anyhow::{Context, Result};
 use clap::{CommandFactory, Parser, Subcommand};
+use config::AskConfig;
+use context::EnvironmentContext;
+use history::History;
 use llm::LlmClient;
 use llm_client::{Config, ModelPreset};
+use output::{OutputFlags, Pipeline};
 use std::io::{self, BufRead, IsTerminal, Read, Write};
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
@@ -38,6 +48,47 @@ struct Args {
     #[arg(short, long, value_name = "FILE_OR_JSON")]
     json: Option<String>,
 
+    /// Don't include environment context (OS, directory, shell, project type) in shell mode
+    #[arg(long)]
+    no_context: bool,
+
+    /// Print the response as-is, skipping all output post-processing
+    #[arg(long, conflicts_with_all = ["strip_fences", "wrap", "highlight", "pipe"])]
+    raw: bool,
+
+    /// Remove markdown code fences from the response
+    #[arg(long)]
+    strip_fences: bool,
+
+    /// Wrap the response to the terminal width
+    #[arg(long)]
+    wrap: bool,
+
+    /// Color code blocks in the response
+    #[arg(long)]
+    highlight: bool,
+
+    /// Pipe the response through a shell command for display (e.g. "bat -p -l md")
+    #[arg(long, value_name = "COMMAND")]
+    pipe: Option<String>,
+
+    /// Show the model, tokens, estimated cost, and latency after the answer
+    #[arg(long)]
+    stats: bool,
+
+    /// Offer to run the suggested command after confirming (shell mode)
+    #[arg(
+        short = 'x',
+        long,
+        conflicts_with = "general",
+        overrides_with = "no_exec"
+    )]
+    exec: bool,
+
+    /// Don't offer to run the suggested command, even if enabled in ask.toml
+    #[arg(long, overrides_with = "exec")]
+    no_exec: bool,
+
     /// Configuration subcommand
     #[command(subcommand)]
     command: Option<Commands>,
@@ -59,6 +110,20 @@ enum Commands {
         #[command(subcommand)]
         action: Option<SetupAction>,
     },
+    /// Search past questions and answers
+    History {
+        /// Keywords that must all appear in the question or answer
+        #[arg(trailing_var_arg = true)]
+        query: Vec<String>,
+
+        /// Maximum number of entries to show
+        #[arg(short = 'n', long, default_value_t = 10)]
+        limit: usize,
+
+        /// Ask the question from this entry again
+        #[arg(long, value_name = "ID", conflicts_with = "query")]
+        rerun: Option<u64>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -98,9 +163,7 @@ fn handle_config_command(action: &ConfigAction) -> Result<()> {
     match action {
         ConfigAction::SetDefault { preset } => {
             let mut config = Config::load()?;
-            // Verify preset exists
-            config.get_preset(preset)?;
-            config.defaults.insert("ask".to_string(), preset.clone());
+            config.set_default(preset, Some("ask"))?;
             config.save()?;
             println!("Default preset for ask set to: {}", preset);
         }
@@ -108,16 +171,8 @@ fn handle_config_command(action: &ConfigAction) -> Result<()> {
             let config = Config::load()?;
             let current_default = config.get_default_for_program("ask");
             println!("Available presets:");
-            for (name, preset) in &config.presets {
-                let default_marker = if name == current_default {
-                    " (default)"
-                } else {
-                    ""
-                };
-                println!(
-                    "  {} - {} / {}{}",
-                    name, preset.provider, preset.model, default_marker
-                );
+            for (name, preset) in config.sorted_presets() {
+                println!("  {}", preset.describe(name, name == current_default));
             }
         }
         ConfigAction::Show => {
@@ -133,17 +188,13 @@ fn handle_config_command(action: &ConfigAction) -> Result<()> {
             model,
         } => {
             let mut config = Config::load()?;
-            config.presets.insert(
-                name.clone(),
-                ModelPreset {
-                    provider: provider.clone(),
-                    model: model.clone(),
-                    fallback: None,
-                    api_key_env: None,
-                },
-            );
+            let replaced = config.add_preset(name, ModelPreset::new(provider, model))?;
             config.save()?;
-            println!("Added preset: {}", name);
+            if replaced {
+                println!("Replaced preset: {}", name);
+            } else {
+                println!("Added preset: {}", name);
+            }
         }
     }
     Ok(())
@@ -289,6 +340,30 @@ fn handle_setup_command(action: Option<&SetupAction>) -> Result<()> {
     Ok(())
 }
 
+/// List history entries matching a query, newest first
+fn handle_history_command(query: &[String], limit: usize) -> Result<()> {
+    let entries = History::load()?;
+    let query = query.join(" ");
+    let found = history::search(&entries, &query, limit);
+
+    if found.is_empty() {
+        if entries.is_empty() {
+            println!("No history yet.");
+        } else {
+            println!("No questions or answers match \"{}\".", query);
+        }
+        return Ok(());
+    }
+
+    for (i, entry) in found.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        print!("{}", entry.render());
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
@@ -303,6 +378,19 @@ async fn main() -> Result<()> {
         return handle_setup_command(action.as_ref());
     }
 
+    if let Some(Commands::History {
+        query,
+        limit,
+        rerun,
+    }) = &args.command
+    {
+        let Some(id) = rerun else {
+            return handle_history_command(query, *limit);
+        };
+        let entry = History::get(*id)?;
+        return ask(&args, &entry.prompt, None, entry.general, &entry.files).await;
+    }
+
     // Get the question from args
     let question = args.question.join(" ");
 
@@ -328,15 +416,51 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    ask(
+        &args,
+        &question,
+        piped_input.as_deref(),
+        args.general,
+        &args.files,
+    )
+    .await
+}
+
+/// Ask a question, print the answer, and record both in the history
+async fn ask(
+    args: &Args,
+    question: &str,
+    piped_input: Option<&str>,
+    general: bool,
+    files: &[PathBuf],
+) -> Result<()> {
     // Validate files exist
-    for file in &args.files {
+    for file in files {
         if !file.exists() {
             anyhow::bail!("File not found: {}", file.display());
         }
     }
 
+    let ask_config = AskConfig::load()?;
+    let pipeline = Pipeline::resolve(
+        ask_config.output_for(general),
+        general,
+        OutputFlags {
+            raw: args.raw,
+            strip_fences: args.strip_fences,
+            wrap: args.wrap,
+            highlight: args.highlight,
+            pipe: args.pipe.clone(),
+        },
+        io::stdout().is_terminal(),
+    );
+
     // Initialize LLM client with selected preset
-    let llm = LlmClient::new(args.model.as_deref(), args.debug)?;
+    let llm = LlmClient::new(
+        args.model.as_deref(),
+        ask_config.secondary_preset.as_deref(),
+        args.debug,
+    )?;
 
     // Parse JSON schema if provided
     let json_schema = match &args.json {
@@ -344,24 +468,63 @@ async fn main() -> Result<()> {
         None => None,
     };
 
+    // Gather environment context for shell mode unless disabled
+    let env_context = if general || args.no_context {
+        None
+    } else {
+        Some(EnvironmentContext::detect())
+    };
+
+    if args.debug
+        && let Some(ctx) = &env_context
+    {
+        eprintln!("{}", ctx.to_prompt_section());
+    }
+
     // Build the prompt and optional system prompt
-    let (prompt, system_prompt) = build_prompt(&question, piped_input.as_deref(), args.general);
+    let (prompt, system_prompt) =
+        build_prompt(question, piped_input, general, env_context.as_ref());
 
     // Call LLM
     let response = llm
-        .complete(&prompt, system_prompt, &args.files, json_schema)
+        .complete(&prompt, system_prompt.as_deref(), files, json_schema)
         .await?;
 
-    if response.is_empty() {
+    if response.content.is_empty() {
         anyhow::bail!("Empty response from LLM");
     }
 
+    // A failure to record history shouldn't lose the answer
+    if ask_config.history.enabled {
+        match History::append(&prompt, general, files, &response.content) {
+            Ok(id) if args.debug => eprintln!("Saved to history as #{}", id),
+            Ok(_) => {}
+            Err(e) => eprintln!("Warning: Failed to save history: {}", e),
+        }
+    }
+
     // Display the response
-    println!("{}", response.trim());
+    let content = pipeline.content(&response.content);
+    pipeline.print(&content)?;
+
+    // On stderr, so it isn't piped or copied with the answer
+    if args.stats || ask_config.stats.enabled {
+        eprintln!("{}", stats::footer(&response, &ask_config.stats));
+    }
 
     // Copy to clipboard if not general mode (macOS only)
-    if !args.general {
-        copy_to_clipboard(&response)?;
+    if !general {
+        copy_to_clipboard(&content)?;
+    }
+
+    // Run the suggested command, exiting with its status
+    if !general
+        && !args.no_exec
+        && (args.exec || ask_config.exec.enabled)
+        && let Some(code) = exec::confirm_and_run(&output::strip_fences(&response.content))?
+        && code != 0
+    {
+        std::process::exit(code);
     }
 
     Ok(())
@@ -369,11 +532,12 @@ async fn main() -> Result<()> {
 
 /// Build prompt and optional system prompt based on mode
 /// Returns (user_prompt, Option<system_prompt>)
-fn build_prompt<'a>(
+fn build_prompt(
     question: &str,
     piped_input: Option<&str>,
     is_general: bool,
-) -> (String, Option<&'a str>) {
+    env_context: Option<&EnvironmentContext>,
+) -> (String, Option<String>) {
     let user_content = match piped_input {
         Some(piped_data) if !question.is_empty() => {
             format!("{}\n\n{}", question, piped_data)
@@ -386,8 +550,12 @@ fn build_prompt<'a>(
         // General mode: no system prompt
         (user_content, None)
     } else {
-        // Shell mode: use shell system prompt
-        (user_content, Some(SHELL_SYSTEM_PROMPT))
+        // Shell mode: use shell system prompt, with environment context if available
+        let system_prompt = match env_context {
+            Some(ctx) => format!("{}\n\n{}", SHELL_SYSTEM_PROMPT, ctx.to_prompt_section()),
+            None => SHELL_SYSTEM_PROMPT.to_string(),
+        };
+        (user_content, Some(system_prompt))
     }
 }
 
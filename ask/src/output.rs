@@ -0,0 +1,329 @@
+// Response post-processing
+//
+// Responses go through an optional pipeline before they are printed: strip
+// markdown fences, wrap prose to the terminal width, color code blocks, and
+// finally pipe through a user command such as `bat`. Only fence stripping
+// changes what is copied to the clipboard; the other steps are display-only.
+
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::config::OutputConfig;
+
+const DEFAULT_WIDTH: usize = 80;
+const CODE_COLOR: &str = "\x1b[36m";
+const FENCE_COLOR: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Command-line overrides for the configured output settings
+#[derive(Debug, Default)]
+pub struct OutputFlags {
+    pub raw: bool,
+    pub strip_fences: bool,
+    pub wrap: bool,
+    pub highlight: bool,
+    pub pipe: Option<String>,
+}
+
+/// The post-processing steps to run for one response
+#[derive(Debug, PartialEq)]
+pub struct Pipeline {
+    strip_fences: bool,
+    /// Width to wrap to, if wrapping
+    wrap: Option<usize>,
+    highlight: bool,
+    pipe: Option<String>,
+}
+
+impl Pipeline {
+    /// Combine the configured settings for the mode with command-line flags.
+    ///
+    /// Wrapping and highlighting only apply when printing to a terminal, so
+    /// redirected output stays plain.
+    pub fn resolve(config: &OutputConfig, general: bool, flags: OutputFlags, tty: bool) -> Self {
+        if flags.raw {
+            return Self {
+                strip_fences: false,
+                wrap: None,
+                highlight: false,
+                pipe: None,
+            };
+        }
+
+        let wrap = (config.wrap || flags.wrap) && tty;
+        Self {
+            strip_fences: flags.strip_fences || config.strip_fences.unwrap_or(!general),
+            wrap: wrap.then(terminal_width),
+            highlight: (config.highlight || flags.highlight) && tty,
+            pipe: flags.pipe.or_else(|| config.pipe.clone()),
+        }
+    }
+
+    /// The response text to copy to the clipboard
+    pub fn content(&self, response: &str) -> String {
+        let response = response.trim();
+        if self.strip_fences {
+            strip_fences(response)
+        } else {
+            response.to_string()
+        }
+    }
+
+    /// Format content for display, then print it or pipe it through the configured command
+    pub fn print(&self, content: &str) -> Result<()> {
+        let mut text = content.to_string();
+        if let Some(width) = self.wrap {
+            text = wrap_text(&text, width);
+        }
+        if self.highlight {
+            text = highlight_code_blocks(&text);
+        }
+
+        match &self.pipe {
+            Some(command) => pipe_through(command, &text),
+            None => {
+                println!("{}", text);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn is_fence(line: &str) -> bool {
+    line.trim_start().starts_with("```")
+}
+
+/// Remove markdown code fence lines, keeping the code inside them
+pub fn strip_fences(text: &str) -> String {
+    text.lines()
+        .filter(|line| !is_fence(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Wrap a single line at word boundaries, keeping its indentation on continuation lines
+fn wrap_line(line: &str, width: usize, out: &mut Vec<String>) {
+    let indent_len = line.len() - line.trim_start().len();
+    let indent = &line[..indent_len];
+    let mut current = indent.to_string();
+
+    for word in line.split_whitespace() {
+        let has_words = current.len() > indent_len;
+        if has_words && current.chars().count() + 1 + word.chars().count() > width {
+            out.push(std::mem::replace(&mut current, indent.to_string()));
+        }
+        if current.len() > indent_len {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+
+    out.push(current);
+}
+
+/// Wrap prose lines to the given width; code blocks are left untouched
+pub fn wrap_text(text: &str, width: usize) -> String {
+    let mut out = Vec::new();
+    let mut in_code = false;
+
+    for line in text.lines() {
+        if is_fence(line) {
+            in_code = !in_code;
+            out.push(line.to_string());
+        } else if in_code || line.chars().count() <= width {
+            out.push(line.to_string());
+        } else {
+            wrap_line(line, width, &mut out);
+        }
+    }
+
+    out.join("\n")
+}
+
+/// Color the contents of fenced code blocks, dimming the fences themselves
+pub fn highlight_code_blocks(text: &str) -> String {
+    let mut out = Vec::new();
+    let mut in_code = false;
+
+    for line in text.lines() {
+        if is_fence(line) {
+            in_code = !in_code;
+            out.push(format!("{}{}{}", FENCE_COLOR, line, RESET));
+        } else if in_code {
+            out.push(format!("{}{}{}", CODE_COLOR, line, RESET));
+        } else {
+            out.push(line.to_string());
+        }
+    }
+
+    out.join("\n")
+}
+
+/// Terminal width from $COLUMNS or `tput cols`, falling back to 80
+fn terminal_width() -> usize {
+    if let Some(width) = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|cols| cols.parse().ok())
+    {
+        return width;
+    }
+
+    Command::new("tput")
+        .arg("cols")
+        .stderr(Stdio::inherit())
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+        .filter(|width| *width > 0)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Run a shell command with the text on stdin, letting it write to the terminal
+fn pipe_through(command: &str, text: &str) -> Result<()> {
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run output pipe: {}", command))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A pager may exit before reading everything; that's not an error
+        let _ = writeln!(stdin, "{}", text);
+    }
+
+    let status = child
+        .wait()
+        .with_context(|| format!("Failed to wait for output pipe: {}", command))?;
+    if !status.success() {
+        anyhow::bail!("Output pipe failed ({}): {}", status, command);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(strip_fences: Option<bool>, wrap: bool, pipe: Option<&str>) -> OutputConfig {
+        OutputConfig {
+            strip_fences,
+            wrap,
+            highlight: false,
+            pipe: pipe.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_strip_fences() {
+        assert_eq!(strip_fences("```bash\nls -la\n```"), "ls -la");
+        assert_eq!(
+            strip_fences("Try this:\n```\nfind . -name '*.rs'\n```\nDone."),
+            "Try this:\nfind . -name '*.rs'\nDone."
+        );
+        assert_eq!(strip_fences("echo ok"), "echo ok");
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        let text = "one two three four five\n  - six seven eight nine\n```\na very long code line that stays\n```";
+        assert_eq!(
+            wrap_text(text, 14),
+            "one two three\nfour five\n  - six seven\n  eight nine\n```\na very long code line that stays\n```"
+        );
+    }
+
+    #[test]
+    fn test_wrap_text_long_word() {
+        assert_eq!(
+            wrap_text("a supercalifragilistic b", 5),
+            "a\nsupercalifragilistic\nb"
+        );
+    }
+
+    #[test]
+    fn test_highlight_code_blocks() {
+        let highlighted = highlight_code_blocks("Run:\n```sh\nls\n```");
+        assert_eq!(
+            highlighted,
+            format!(
+                "Run:\n{f}```sh{r}\n{c}ls{r}\n{f}```{r}",
+                f = FENCE_COLOR,
+                c = CODE_COLOR,
+                r = RESET
+            )
+        );
+    }
+
+    #[test]
+    fn test_resolve_mode_defaults() {
+        let shell = Pipeline::resolve(
+            &OutputConfig::default(),
+            false,
+            OutputFlags::default(),
+            true,
+        );
+        assert!(shell.strip_fences);
+        assert_eq!(shell.wrap, None);
+
+        let general =
+            Pipeline::resolve(&OutputConfig::default(), true, OutputFlags::default(), true);
+        assert!(!general.strip_fences);
+    }
+
+    #[test]
+    fn test_resolve_flags_override_config() {
+        let config = config(Some(false), false, Some("bat"));
+        let flags = OutputFlags {
+            strip_fences: true,
+            pipe: Some("less -R".to_string()),
+            ..Default::default()
+        };
+        let pipeline = Pipeline::resolve(&config, false, flags, true);
+        assert!(pipeline.strip_fences);
+        assert_eq!(pipeline.pipe.as_deref(), Some("less -R"));
+
+        let raw = OutputFlags {
+            raw: true,
+            ..Default::default()
+        };
+        let pipeline = Pipeline::resolve(&config, false, raw, true);
+        assert!(!pipeline.strip_fences);
+        assert_eq!(pipeline.pipe, None);
+    }
+
+    #[test]
+    fn test_resolve_display_steps_need_tty() {
+        let config = OutputConfig {
+            wrap: true,
+            highlight: true,
+            ..Default::default()
+        };
+        let pipeline = Pipeline::resolve(&config, true, OutputFlags::default(), false);
+        assert_eq!(pipeline.wrap, None);
+        assert!(!pipeline.highlight);
+    }
+
+    #[test]
+    fn test_content_trims_and_strips() {
+        let pipeline = Pipeline::resolve(
+            &OutputConfig::default(),
+            false,
+            OutputFlags::default(),
+            false,
+        );
+        assert_eq!(
+            pipeline.content("\n```zsh\ngit status\n```\n"),
+            "git status"
+        );
+    }
+
+    #[test]
+    fn test_pipe_through() {
+        assert!(pipe_through("cat > /dev/null", "hello").is_ok());
+        assert!(pipe_through("exit 3", "hello").is_err());
+    }
+}
@@ -0,0 +1,83 @@
+// One-line usage footer shown after an answer
+
+use llm_client::LlmResponse;
+
+use crate::config::StatsConfig;
+
+/// Model, tokens, estimated cost, and latency of a response, e.g.
+/// "claude-sonnet-4-5 | 1200 in, 85 out | ~$0.0049 | 2.3s"
+///
+/// Tokens are left out when the provider doesn't report usage, and the cost
+/// when the model has no price in `ask.toml`.
+pub fn footer(response: &LlmResponse, config: &StatsConfig) -> String {
+    let mut parts = vec![response.model.clone()];
+    if let Some(usage) = &response.usage {
+        parts.push(format!(
+            "{} in, {} out",
+            usage.input_tokens, usage.output_tokens
+        ));
+        if let Some(price) = config.price_for(&response.model) {
+            let cost = price.cost(usage.input_tokens, usage.output_tokens);
+            parts.push(format!("~${:.4}", cost));
+        }
+    }
+    parts.push(format!("{:.1}s", response.latency.as_secs_f64()));
+    parts.join(" | ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModelPrice;
+    use llm_client::TokenUsage;
+    use std::time::Duration;
+
+    fn response(usage: Option<TokenUsage>) -> LlmResponse {
+        LlmResponse {
+            content: "ls -la".to_string(),
+            model: "claude-sonnet-4-5-20250929".to_string(),
+            usage,
+            finish_reason: None,
+            latency: Duration::from_millis(2340),
+            served_by: None,
+        }
+    }
+
+    #[test]
+    fn test_footer() {
+        let mut config = StatsConfig::default();
+        config.prices.insert(
+            "claude-sonnet-4-5".to_string(),
+            ModelPrice {
+                input: 3.0,
+                output: 15.0,
+            },
+        );
+        let usage = TokenUsage {
+            input_tokens: 1200,
+            output_tokens: 85,
+        };
+        assert_eq!(
+            footer(&response(Some(usage)), &config),
+            "claude-sonnet-4-5-20250929 | 1200 in, 85 out | ~$0.0049 | 2.3s"
+        );
+    }
+
+    #[test]
+    fn test_footer_without_usage_or_price() {
+        let config = StatsConfig::default();
+        assert_eq!(
+            footer(&response(None), &config),
+            "claude-sonnet-4-5-20250929 | 2.3s"
+        );
+
+        let usage = TokenUsage {
+            input_tokens: 10,
+            output_tokens: 2,
+        };
+        assert_eq!(
+            footer(&response(Some(usage)), &config),
+            "claude-sonnet-4-5-20250929 | 10 in, 2 out | 2.3s"
+        );
+    }
+}
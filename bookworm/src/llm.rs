@@ -3,7 +3,7 @@
 //! Provides a simplified interface to the llm-client crate.
 
 use anyhow::{Context, Result};
-use llm_client::{Config, LlmProvider, LlmRequest, get_provider};
+use llm_client::{Config, LlmProvider, LlmRequest, get_provider, with_rate_limits};
 
 /// Wrapper around LLM providers for bookworm
 pub struct LlmClient {
@@ -28,6 +28,8 @@ impl LlmClient {
             "Failed to initialize provider '{}' for preset '{}'",
             preset.provider, preset_name
         ))?;
+        let provider = with_rate_limits(&config, &preset.provider, provider)
+            .context("Failed to set up rate limits")?;
 
         if debug {
             eprintln!(
@@ -55,13 +57,22 @@ impl LlmClient {
             eprintln!("Sending request to {}", self.provider.name());
         }
 
-        let response = self
-            .provider
-            .complete(request)
-            .await
-            .context("LLM request failed")?;
+        let response = match self.provider.complete(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                if let Some(hint) = e.remediation() {
+                    eprintln!("Hint: {}", hint);
+                }
+                return Err(e).context("LLM request failed");
+            }
+        };
 
         if self.debug {
+            eprintln!(
+                "Model: {} ({:.1}s)",
+                response.model,
+                response.latency.as_secs_f64()
+            );
             if let Some(usage) = &response.usage {
                 eprintln!(
                     "Tokens: {} in, {} out",
@@ -70,6 +81,15 @@ impl LlmClient {
             }
         }
 
+        if response.is_truncated()
+            && let Some(reason) = &response.finish_reason
+        {
+            eprintln!(
+                "Warning: response from {} was cut off ({}); output may be incomplete",
+                response.model, reason
+            );
+        }
+
         Ok(response.content)
     }
 }
@@ -134,6 +134,9 @@ fn handle_config_command(action: &ConfigAction) -> Result<()> {
                     model: model.clone(),
                     fallback: None,
                     api_key_env: None,
+                    requires: Vec::new(),
+                    context_tokens: None,
+                    tags: Vec::new(),
                 },
             );
             config.save()?;
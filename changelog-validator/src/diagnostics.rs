@@ -0,0 +1,210 @@
+//! Structured validation results
+//!
+//! [`validate_all_content`] reads the whole changelog and collects every
+//! problem as a [`Diagnostic`], instead of stopping at the first one like
+//! [`crate::validate_content`].
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::parser::{Event, ParseError, ValidationOptions, parse_events_with_options};
+use crate::{Changelog, VersionBuilder};
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single problem found in a changelog
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// 1-based line number
+    pub line: usize,
+    pub severity: Severity,
+    /// Stable identifier for the rule that was violated, e.g. "invalid-semver"
+    pub rule: &'static str,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn from_parse_error(line: usize, error: &ParseError) -> Self {
+        Self {
+            line,
+            severity: error.severity(),
+            rule: error.rule(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Line {}: {}: {} [{}]",
+            self.line, self.severity, self.message, self.rule
+        )
+    }
+}
+
+impl ParseError {
+    /// Stable rule identifier for this error
+    pub fn rule(&self) -> &'static str {
+        match self {
+            Self::MissingHeader => "missing-header",
+            Self::UnreleasedSection => "unreleased-section",
+            Self::MisplacedUnreleased => "misplaced-unreleased",
+            Self::ContentBeforeFirstVersion => "content-before-first-version",
+            Self::InvalidSemver(_) => "invalid-semver",
+            Self::InvalidDate { .. } => "invalid-date",
+            Self::InvalidSection(_) => "invalid-section",
+            Self::EmptySection { .. } => "empty-section",
+            Self::EmptyVersion(_) => "empty-version",
+            Self::VersionOutOfOrder { .. } => "version-order",
+            Self::DateOutOfOrder { .. } => "date-order",
+            Self::NoVersions => "no-versions",
+            Self::DuplicateVersion { .. } => "duplicate-version",
+            Self::DuplicateSection { .. } => "duplicate-section",
+            Self::MissingLink(_) => "missing-link",
+            Self::UnusedLink(_) => "unused-link",
+            Self::Io(_) => "io",
+        }
+    }
+
+    /// Severity of this error. Every parser rule is currently an error.
+    pub fn severity(&self) -> Severity {
+        Severity::Error
+    }
+}
+
+/// Every problem found in one changelog, plus whatever could be parsed
+#[derive(Debug)]
+pub struct ValidationReport {
+    pub path: PathBuf,
+    /// Versions parsed from the content; may be partial if there were errors
+    pub changelog: Changelog,
+    /// Diagnostics in the order they were found
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    /// True if there are no error-level diagnostics
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Warning)
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    /// One `path: Line N: severity: message [rule]` line per diagnostic
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diagnostic in &self.diagnostics {
+            writeln!(f, "{}: {}", self.path.display(), diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+/// Validates changelog content, collecting every problem instead of stopping at the first
+pub fn validate_all_content(content: &str, path: &Path) -> ValidationReport {
+    validate_all_content_with_options(content, path, ValidationOptions::default())
+}
+
+/// Like [`validate_all_content`], applying optional rules
+pub fn validate_all_content_with_options(
+    content: &str,
+    path: &Path,
+    options: ValidationOptions,
+) -> ValidationReport {
+    let mut builder = VersionBuilder::default();
+    let mut diagnostics = Vec::new();
+
+    for event in parse_events_with_options(content, options) {
+        if let Event::Error { line, error } = &event {
+            diagnostics.push(Diagnostic::from_parse_error(*line, error));
+        }
+        builder.push(event);
+    }
+
+    ValidationReport {
+        path: path.to_path_buf(),
+        changelog: builder.build(content),
+        diagnostics,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collects_every_problem() {
+        let content = "\
+# Changelog
+
+## [1.0] - someday
+
+### Stuff
+- entry
+
+## [0.1.0] - 2025-01-01
+
+### Added
+- Initial release
+";
+        let report = validate_all_content(content, Path::new("CHANGELOG.md"));
+        assert!(!report.is_valid());
+
+        let rules: Vec<(usize, &str)> = report.errors().map(|d| (d.line, d.rule)).collect();
+        assert_eq!(
+            rules,
+            vec![
+                (3, "invalid-semver"),
+                (3, "invalid-date"),
+                (5, "invalid-section")
+            ]
+        );
+        assert_eq!(report.changelog.versions.len(), 2);
+        assert_eq!(report.warnings().count(), 0);
+    }
+
+    #[test]
+    fn test_valid_report() {
+        let content = "# Changelog\n\n## [1.0.0] - TBD\n\n### Added\n- Thing\n";
+        let report = validate_all_content(content, Path::new("CHANGELOG.md"));
+        assert!(report.is_valid());
+        assert!(report.diagnostics.is_empty());
+        assert_eq!(report.to_string(), "");
+    }
+
+    #[test]
+    fn test_report_display() {
+        let report = validate_all_content("# Changelog\n", Path::new("gc/CHANGELOG.md"));
+        assert_eq!(
+            report.to_string(),
+            "gc/CHANGELOG.md: Line 1: error: Must have at least one versioned release [no-versions]\n"
+        );
+    }
+}
@@ -0,0 +1,472 @@
+//! Programmatic changelog editing
+//!
+//! Edits are applied to the changelog text line by line, so everything an
+//! edit doesn't touch (separators, blank lines, multi-line entries) is kept
+//! exactly as it was, and [`Changelog::to_markdown`] returns the original
+//! text plus the edits. `versions` is re-parsed after every edit.
+
+use anyhow::{Context, Result, bail};
+use std::path::Path;
+
+use crate::parser::{
+    DATE_PATTERN, Event, UNRELEASED, VALID_SECTIONS, ValidationOptions, parse_events_with_options,
+};
+use crate::semver::SemVer;
+use crate::{Changelog, VersionBuilder};
+
+/// Accepts anything the editor can safely locate, including an
+/// `## [Unreleased]` section and versions out of order
+const LENIENT: ValidationOptions = ValidationOptions {
+    allow_unreleased: true,
+    allow_out_of_order: true,
+    require_links: false,
+};
+
+/// Line positions (0-based) of one version block
+struct VersionLines {
+    version: String,
+    header: usize,
+    /// First line of the next version, or the end of the file
+    end: usize,
+    sections: Vec<SectionLines>,
+}
+
+struct SectionLines {
+    name: String,
+    header: usize,
+}
+
+impl Changelog {
+    /// Parse changelog text for editing, without validating it.
+    ///
+    /// Use [`crate::validate_content`] first if the text must be valid.
+    pub fn parse(content: &str) -> Changelog {
+        let mut builder = VersionBuilder::default();
+        for event in parse_events_with_options(content, LENIENT) {
+            builder.push(event);
+        }
+        builder.build(content)
+    }
+
+    /// Read and parse a changelog file for editing
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Changelog> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read changelog at {}", path.display()))?;
+        Ok(Self::parse(&content))
+    }
+
+    /// The changelog text, including any edits
+    pub fn to_markdown(&self) -> String {
+        self.content.clone()
+    }
+
+    /// Add a `- text` entry to a section of `version`, creating the section
+    /// (in Keep a Changelog order) if the version doesn't have it yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version doesn't exist or the section name
+    /// isn't one of [`VALID_SECTIONS`].
+    pub fn add_entry(&mut self, version: &str, section: &str, text: &str) -> Result<()> {
+        if !VALID_SECTIONS.contains(&section) {
+            bail!(
+                "Invalid section '{}' (expected one of: {})",
+                section,
+                VALID_SECTIONS.join(", ")
+            );
+        }
+        let entry = if text.starts_with("- ") {
+            text.to_string()
+        } else {
+            format!("- {}", text)
+        };
+
+        let mut lines = self.lines();
+        let layout = self.find_version(&lines, version)?;
+
+        if let Some(i) = layout.sections.iter().position(|s| s.name == section) {
+            let end = layout
+                .sections
+                .get(i + 1)
+                .map_or(layout.end, |next| next.header);
+            let last = last_content_line(&lines, layout.sections[i].header, end);
+            lines.insert(last + 1, entry);
+        } else {
+            let rank = |name: &str| VALID_SECTIONS.iter().position(|s| *s == name);
+            let before = layout
+                .sections
+                .iter()
+                .find(|s| rank(&s.name) > rank(section))
+                .map(|s| s.header);
+            match before {
+                Some(header) => {
+                    let block = [format!("### {}", section), entry, String::new()];
+                    lines.splice(header..header, block);
+                }
+                None => {
+                    let last = last_content_line(&lines, layout.header, layout.end);
+                    let block = [String::new(), format!("### {}", section), entry];
+                    lines.splice(last + 1..last + 1, block);
+                }
+            }
+        }
+
+        self.set_lines(lines);
+        Ok(())
+    }
+
+    /// Add an empty `## [version] - date` block, placed so versions stay
+    /// newest first. Follows the file's `---` separator style.
+    ///
+    /// The new version has no sections, which validation rejects until an
+    /// entry is added with [`Changelog::add_entry`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version or date is malformed, or the version
+    /// already exists.
+    pub fn add_version(&mut self, version: &str, date: &str) -> Result<()> {
+        let Some(semver) = SemVer::parse(version) else {
+            bail!("Invalid semver format '{}'", version);
+        };
+        if !DATE_PATTERN.is_match(date) {
+            bail!(
+                "Invalid date format '{}' (expected YYYY-MM-DD or TBD)",
+                date
+            );
+        }
+        if self.versions.iter().any(|v| v.version == version) {
+            bail!("Version {} already exists", version);
+        }
+
+        let mut lines = self.lines();
+        let separators = lines.iter().any(|l| l.trim() == "---");
+        let header = format!("## [{}] - {}", version, date);
+
+        // Before the first released version this one is newer than
+        let before = match self
+            .versions
+            .iter()
+            .find(|v| v.semver.as_ref().is_some_and(|s| semver.is_newer_than(s)))
+        {
+            Some(v) => Some(self.find_version(&lines, &v.version)?.header),
+            None => None,
+        };
+
+        match before {
+            Some(at) => {
+                let mut block = vec![header, String::new()];
+                if separators {
+                    block.extend(["---".to_string(), String::new()]);
+                }
+                lines.splice(at..at, block);
+            }
+            None => {
+                while lines.last().is_some_and(|l| l.trim().is_empty()) {
+                    lines.pop();
+                }
+                let mut block = vec![String::new()];
+                if separators && !self.versions.is_empty() {
+                    block.extend(["---".to_string(), String::new()]);
+                }
+                block.push(header);
+                lines.extend(block);
+            }
+        }
+
+        self.set_lines(lines);
+        Ok(())
+    }
+
+    /// Release the newest version dated `TBD` by giving it `date`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the date is malformed or no version is dated
+    /// `TBD`, and for an `## [Unreleased]` section, which has no version
+    /// number to release under.
+    pub fn release_unreleased(&mut self, date: &str) -> Result<()> {
+        if !DATE_PATTERN.is_match(date) || date == "TBD" {
+            bail!("Invalid release date '{}' (expected YYYY-MM-DD)", date);
+        }
+        if self.versions.first().is_some_and(|v| v.is_unreleased()) {
+            bail!(
+                "[{}] has no version number; add a version with add_version and move its entries there",
+                UNRELEASED
+            );
+        }
+        let Some(version) = self.versions.iter().find(|v| v.date == "TBD") else {
+            bail!("No version is dated TBD");
+        };
+
+        let header = format!("## [{}] - {}", version.version, date);
+        let mut lines = self.lines();
+        let at = self.find_version(&lines, &version.version)?.header;
+        lines[at] = header;
+
+        self.set_lines(lines);
+        Ok(())
+    }
+
+    /// Add a `[version]: url` link definition for every version without one,
+    /// pointing at compare views on a GitHub-style `repo_url` with tags named
+    /// `vX.Y.Z`. Returns the number of links added.
+    ///
+    /// New links go after the existing link definitions, or at the end of the
+    /// file, in version order.
+    pub fn add_missing_links(&mut self, repo_url: &str) -> usize {
+        let repo = repo_url.trim_end_matches('/').trim_end_matches(".git");
+        let has_link = |label: &str| {
+            self.links
+                .iter()
+                .any(|l| l.label.eq_ignore_ascii_case(label))
+        };
+
+        let mut new_links = Vec::new();
+        for (i, version) in self.versions.iter().enumerate() {
+            if has_link(&version.version) {
+                continue;
+            }
+            let older = self.versions[i + 1..]
+                .iter()
+                .find(|v| !v.is_unreleased())
+                .map(|v| &v.version);
+            let url = match (version.is_unreleased(), older) {
+                (true, Some(older)) => format!("{}/compare/v{}...HEAD", repo, older),
+                (true, None) => format!("{}/commits/HEAD", repo),
+                (false, Some(older)) => {
+                    format!("{}/compare/v{}...v{}", repo, older, version.version)
+                }
+                (false, None) => format!("{}/releases/tag/v{}", repo, version.version),
+            };
+            new_links.push(format!("[{}]: {}", version.version, url));
+        }
+        if new_links.is_empty() {
+            return 0;
+        }
+
+        let added = new_links.len();
+        let mut lines = self.lines();
+        match self.links.last() {
+            Some(last) => {
+                lines.splice(last.line..last.line, new_links);
+            }
+            None => {
+                while lines.last().is_some_and(|l| l.trim().is_empty()) {
+                    lines.pop();
+                }
+                lines.push(String::new());
+                lines.extend(new_links);
+            }
+        }
+
+        self.set_lines(lines);
+        added
+    }
+
+    fn lines(&self) -> Vec<String> {
+        self.content.lines().map(str::to_string).collect()
+    }
+
+    fn set_lines(&mut self, lines: Vec<String>) {
+        let mut content = lines.join("\n");
+        if self.content.ends_with('\n') || self.content.is_empty() {
+            content.push('\n');
+        }
+        self.content = content;
+        self.reparse();
+    }
+
+    fn reparse(&mut self) {
+        *self = Self::parse(&self.content);
+    }
+
+    fn find_version(&self, lines: &[String], version: &str) -> Result<VersionLines> {
+        let mut blocks: Vec<VersionLines> = Vec::new();
+        for event in parse_events_with_options(&self.content, LENIENT) {
+            match event {
+                Event::VersionStart { line, version, .. } => {
+                    if let Some(last) = blocks.last_mut() {
+                        last.end = line - 1;
+                    }
+                    blocks.push(VersionLines {
+                        version,
+                        header: line - 1,
+                        end: lines.len(),
+                        sections: Vec::new(),
+                    });
+                }
+                Event::Section { line, name } => {
+                    if let Some(last) = blocks.last_mut() {
+                        last.sections.push(SectionLines {
+                            name,
+                            header: line - 1,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        blocks
+            .into_iter()
+            .find(|b| b.version == version)
+            .ok_or_else(|| anyhow::anyhow!("Version {} not found", version))
+    }
+}
+
+/// The last line in `start..end` that isn't blank or a `---` separator
+fn last_content_line(lines: &[String], start: usize, end: usize) -> usize {
+    (start..end)
+        .rev()
+        .find(|&i| {
+            let trimmed = lines[i].trim();
+            !trimmed.is_empty() && trimmed != "---"
+        })
+        .unwrap_or(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate_content;
+
+    const CHANGELOG: &str = "# Changelog
+
+## [1.1.0] - TBD
+
+### Added
+- Streaming parser
+  that spans two lines
+
+### Fixed
+- Crash on empty input
+
+---
+
+## [1.0.0] - 2025-10-17
+
+### Added
+- Initial release
+";
+
+    #[test]
+    fn test_untouched_round_trip() {
+        assert_eq!(Changelog::parse(CHANGELOG).to_markdown(), CHANGELOG);
+    }
+
+    #[test]
+    fn test_add_entry_to_existing_section() {
+        let mut changelog = Changelog::parse(CHANGELOG);
+        changelog
+            .add_entry("1.1.0", "Added", "JSON output")
+            .unwrap();
+        changelog
+            .add_entry("1.0.0", "Added", "- Command line")
+            .unwrap();
+
+        let expected = CHANGELOG
+            .replace("two lines\n", "two lines\n- JSON output\n")
+            .replace("- Initial release\n", "- Initial release\n- Command line\n");
+        assert_eq!(changelog.to_markdown(), expected);
+        assert_eq!(changelog.versions[0].sections[0].entries.len(), 2);
+    }
+
+    #[test]
+    fn test_add_entry_creates_section_in_order() {
+        let mut changelog = Changelog::parse(CHANGELOG);
+        changelog.add_entry("1.1.0", "Changed", "Faster").unwrap();
+        changelog.add_entry("1.1.0", "Security", "Escape").unwrap();
+
+        let expected = CHANGELOG.replace(
+            "### Fixed\n- Crash on empty input\n",
+            "### Changed\n- Faster\n\n### Fixed\n- Crash on empty input\n\n### Security\n- Escape\n",
+        );
+        assert_eq!(changelog.to_markdown(), expected);
+        validate_content(&changelog.to_markdown(), Path::new("test.md")).unwrap();
+    }
+
+    #[test]
+    fn test_add_entry_errors() {
+        let mut changelog = Changelog::parse(CHANGELOG);
+        let err = changelog.add_entry("9.9.9", "Added", "x").unwrap_err();
+        assert!(err.to_string().contains("Version 9.9.9 not found"));
+        let err = changelog.add_entry("1.1.0", "Misc", "x").unwrap_err();
+        assert!(err.to_string().contains("Invalid section 'Misc'"));
+        assert_eq!(changelog.to_markdown(), CHANGELOG);
+    }
+
+    #[test]
+    fn test_add_version() {
+        let mut changelog = Changelog::parse(CHANGELOG);
+        changelog.add_version("1.2.0", "TBD").unwrap();
+        changelog.add_entry("1.2.0", "Fixed", "Thing").unwrap();
+
+        let expected = CHANGELOG.replace(
+            "## [1.1.0] - TBD\n",
+            "## [1.2.0] - TBD\n\n### Fixed\n- Thing\n\n---\n\n## [1.1.0] - TBD\n",
+        );
+        assert_eq!(changelog.to_markdown(), expected);
+        assert_eq!(changelog.versions[0].version, "1.2.0");
+        validate_content(&changelog.to_markdown(), Path::new("test.md")).unwrap();
+
+        // Older versions go further down, without separators if the file has none
+        let mut plain =
+            Changelog::parse("# Changelog\n\n## [1.0.0] - 2025-10-17\n\n### Added\n- A\n");
+        plain.add_version("0.9.0", "2025-09-01").unwrap();
+        plain.add_entry("0.9.0", "Added", "B").unwrap();
+        assert_eq!(
+            plain.to_markdown(),
+            "# Changelog\n\n## [1.0.0] - 2025-10-17\n\n### Added\n- A\n\n## [0.9.0] - 2025-09-01\n\n### Added\n- B\n"
+        );
+
+        assert!(plain.add_version("1.0.0", "TBD").is_err());
+        assert!(plain.add_version("1.0", "TBD").is_err());
+        assert!(plain.add_version("2.0.0", "soon").is_err());
+    }
+
+    #[test]
+    fn test_release_unreleased() {
+        let mut changelog = Changelog::parse(CHANGELOG);
+        changelog.release_unreleased("2025-11-01").unwrap();
+        assert_eq!(
+            changelog.to_markdown(),
+            CHANGELOG.replace("## [1.1.0] - TBD", "## [1.1.0] - 2025-11-01")
+        );
+        assert_eq!(changelog.versions[0].date, "2025-11-01");
+
+        let err = changelog.release_unreleased("2025-11-02").unwrap_err();
+        assert!(err.to_string().contains("No version is dated TBD"));
+        assert!(changelog.release_unreleased("TBD").is_err());
+
+        let mut unreleased = Changelog::parse("# Changelog\n\n## [Unreleased]\n\n### Added\n- A\n");
+        assert!(unreleased.release_unreleased("2025-11-01").is_err());
+    }
+
+    #[test]
+    fn test_add_missing_links() {
+        let repo = "https://github.com/owner/repo.git";
+        let mut changelog = Changelog::parse(CHANGELOG);
+        assert_eq!(changelog.add_missing_links(repo), 2);
+        assert_eq!(
+            changelog.to_markdown(),
+            format!(
+                "{}\n[1.1.0]: https://github.com/owner/repo/compare/v1.0.0...v1.1.0\n[1.0.0]: https://github.com/owner/repo/releases/tag/v1.0.0\n",
+                CHANGELOG
+            )
+        );
+        assert_eq!(changelog.links.len(), 2);
+        assert_eq!(changelog.add_missing_links(repo), 0);
+        validate_content(&changelog.to_markdown(), Path::new("test.md")).unwrap();
+
+        // Links are added after existing ones, and Unreleased compares with HEAD
+        let mut partial = Changelog::parse(
+            "# Changelog\n\n## [Unreleased]\n\n## [1.0.0] - 2025-10-17\n\n### Added\n- A\n\n[docs]: https://example.com/docs\n[1.0.0]: https://example.com/v1\n\nTrailing text\n",
+        );
+        assert_eq!(partial.add_missing_links(repo), 1);
+        assert!(partial.to_markdown().ends_with(
+            "[docs]: https://example.com/docs\n[1.0.0]: https://example.com/v1\n[Unreleased]: https://github.com/owner/repo/compare/v1.0.0...HEAD\n\nTrailing text\n"
+        ));
+    }
+}
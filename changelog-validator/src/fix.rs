@@ -0,0 +1,480 @@
+//! Automatic repair of mechanical problems
+//!
+//! [`fix_content`] rewrites the parts of a changelog that have exactly one
+//! correct form: header spacing, date separators, section order, empty
+//! sections, and runs of blank lines. Everything else, such as an invalid
+//! version number or an unknown section, is left for validation to report.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fmt;
+
+use crate::parser::{LINK_PATTERN, VALID_SECTIONS};
+
+/// A version header with any spacing, and an optional `-`, `–`, or `—`
+/// before the date
+static LOOSE_VERSION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^##\s*\[([^\]]+)\]\s*(?:[-–—]\s*)?(.*?)\s*$").unwrap());
+static LOOSE_SECTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^###\s*([^\s#].*?)\s*$").unwrap());
+static LOOSE_DATE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\d{4})[-/.](\d{2})[-/.](\d{2})$").unwrap());
+
+/// A change made by [`fix_content`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixApplied {
+    /// 1-based line in the original text
+    pub line: usize,
+    pub description: String,
+}
+
+impl fmt::Display for FixApplied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.description)
+    }
+}
+
+/// A line of the changelog, with its 1-based line number in the original text
+#[derive(Debug, Clone)]
+struct Line {
+    number: usize,
+    text: String,
+}
+
+impl Line {
+    fn is_blank(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+
+    /// Blank lines, `---` separators, and link definitions, which belong
+    /// between sections and versions rather than to the section above them
+    fn is_separator(&self) -> bool {
+        let trimmed = self.text.trim();
+        trimmed.is_empty() || trimmed == "---" || LINK_PATTERN.is_match(trimmed)
+    }
+}
+
+/// A `### Name` section: its header and content, then the separator lines
+/// that follow it
+struct SectionBlock {
+    name: String,
+    content: Vec<Line>,
+    gap: Vec<Line>,
+}
+
+/// Repair the mechanical problems in changelog text, returning the fixed
+/// text and what was changed, in line order.
+///
+/// Fixing is idempotent, and text without mechanical problems is returned
+/// unchanged. Run validation on the result for the problems that remain.
+pub fn fix_content(content: &str) -> (String, Vec<FixApplied>) {
+    let mut fixes = Vec::new();
+    let mut lines: Vec<Line> = content
+        .lines()
+        .enumerate()
+        .map(|(i, text)| Line {
+            number: i + 1,
+            text: text.to_string(),
+        })
+        .collect();
+
+    for line in &mut lines {
+        let Some((text, descriptions)) = normalize_header(&line.text) else {
+            continue;
+        };
+        line.text = text;
+        for description in descriptions {
+            fixes.push(FixApplied {
+                line: line.number,
+                description,
+            });
+        }
+    }
+    let lines = fix_sections(lines, &mut fixes);
+    let lines = collapse_blank_lines(lines, &mut fixes);
+    fixes.sort_by_key(|fix| fix.line);
+
+    let mut fixed = lines
+        .iter()
+        .map(|line| line.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if content.ends_with('\n') {
+        fixed.push('\n');
+    }
+    (fixed, fixes)
+}
+
+/// The canonical form of a version or section header, and what was wrong
+/// with it; None for other lines and headers that are already canonical
+fn normalize_header(line: &str) -> Option<(String, Vec<String>)> {
+    let trimmed = line.trim();
+
+    if let Some(caps) = LOOSE_VERSION.captures(trimmed) {
+        let (version, date) = (&caps[1], &caps[2]);
+        let header = |date: &str| {
+            if date.is_empty() {
+                format!("## [{}]", version)
+            } else {
+                format!("## [{}] - {}", version, date)
+            }
+        };
+
+        let mut descriptions = Vec::new();
+        if header(date) != line {
+            descriptions.push(format!("Normalized spacing in the {} header", version));
+        }
+        let fixed_date = match LOOSE_DATE.captures(date) {
+            Some(parts) => format!("{}-{}-{}", &parts[1], &parts[2], &parts[3]),
+            None => date.to_string(),
+        };
+        if fixed_date != date {
+            descriptions.push(format!(
+                "Changed the {} date from {} to {}",
+                version, date, fixed_date
+            ));
+        }
+        return (!descriptions.is_empty()).then(|| (header(&fixed_date), descriptions));
+    }
+
+    if let Some(caps) = LOOSE_SECTION.captures(trimmed) {
+        let header = format!("### {}", &caps[1]);
+        if header != line {
+            let description = format!("Normalized spacing in the {} header", &caps[1]);
+            return Some((header, vec![description]));
+        }
+    }
+
+    None
+}
+
+/// Remove empty sections and put the rest in canonical order, version by
+/// version. Expects headers to be normalized already.
+fn fix_sections(lines: Vec<Line>, fixes: &mut Vec<FixApplied>) -> Vec<Line> {
+    let mut fixed = Vec::with_capacity(lines.len());
+    let mut block = Vec::new();
+
+    for line in lines {
+        if line.text.starts_with("## [") {
+            flush_version(&mut block, &mut fixed, fixes);
+        }
+        block.push(line);
+    }
+    flush_version(&mut block, &mut fixed, fixes);
+
+    fixed
+}
+
+/// Fix one version block and append it to `fixed`. Lines before the first
+/// version header are passed through.
+fn flush_version(block: &mut Vec<Line>, fixed: &mut Vec<Line>, fixes: &mut Vec<FixApplied>) {
+    let mut lines = std::mem::take(block).into_iter().peekable();
+    let Some(header) = lines.next_if(|line| line.text.starts_with("## [")) else {
+        fixed.extend(lines);
+        return;
+    };
+    let version = LOOSE_VERSION
+        .captures(&header.text)
+        .map_or_else(|| header.text.clone(), |caps| caps[1].to_string());
+    fixed.push(header.clone());
+
+    // Anything between the version header and its first section stays put
+    while let Some(line) = lines.next_if(|line| !line.text.starts_with("### ")) {
+        fixed.push(line);
+    }
+
+    let mut sections: Vec<SectionBlock> = Vec::new();
+    for line in lines {
+        if let Some(name) = line.text.strip_prefix("### ") {
+            sections.push(SectionBlock {
+                name: name.to_string(),
+                content: vec![line.clone()],
+                gap: Vec::new(),
+            });
+            continue;
+        }
+        let Some(section) = sections.last_mut() else {
+            continue;
+        };
+        if line.is_separator() {
+            section.gap.push(line);
+        } else {
+            // Separators followed by more content belong to the section
+            section.content.append(&mut section.gap);
+            section.content.push(line);
+        }
+    }
+    if sections.is_empty() {
+        return;
+    }
+
+    // Separators stay where they were; sections move between them
+    let mut gaps: Vec<Vec<Line>> = sections
+        .iter_mut()
+        .map(|section| std::mem::take(&mut section.gap))
+        .collect();
+    let trailer = gaps.pop().unwrap_or_default();
+
+    sections.retain(|section| {
+        let empty = section.content.len() == 1;
+        if empty {
+            fixes.push(FixApplied {
+                line: section.content[0].number,
+                description: format!(
+                    "Removed the empty {} section from {}",
+                    section.name, version
+                ),
+            });
+        }
+        !empty
+    });
+
+    let rank = |name: &str| VALID_SECTIONS.iter().position(|s| *s == name);
+    // Unknown sections have no canonical place, so their version is left as is
+    if sections.iter().all(|section| rank(&section.name).is_some()) {
+        let before: Vec<usize> = sections.iter().map(|s| s.content[0].number).collect();
+        sections.sort_by_key(|section| rank(&section.name));
+        if sections
+            .iter()
+            .map(|s| s.content[0].number)
+            .ne(before.iter().copied())
+        {
+            let order: Vec<&str> = sections.iter().map(|s| s.name.as_str()).collect();
+            fixes.push(FixApplied {
+                line: header.number,
+                description: format!(
+                    "Reordered the sections of {} to {}",
+                    version,
+                    order.join(", ")
+                ),
+            });
+        }
+    }
+
+    let count = sections.len();
+    let mut gaps = gaps.into_iter();
+    for (i, section) in sections.into_iter().enumerate() {
+        fixed.extend(section.content);
+        if i + 1 < count {
+            fixed.extend(gaps.next().unwrap_or_default());
+        }
+    }
+    fixed.extend(trailer);
+}
+
+/// Reduce every run of blank lines to a single blank line
+fn collapse_blank_lines(lines: Vec<Line>, fixes: &mut Vec<FixApplied>) -> Vec<Line> {
+    let mut fixed: Vec<Line> = Vec::with_capacity(lines.len());
+    let mut run: Option<(usize, usize)> = None;
+
+    let mut report = |run: &mut Option<(usize, usize)>| {
+        if let Some((line, removed)) = run.take() {
+            fixes.push(FixApplied {
+                line,
+                description: format!("Removed {} extra blank line(s)", removed),
+            });
+        }
+    };
+
+    for line in lines {
+        if line.is_blank() && fixed.last().is_some_and(Line::is_blank) {
+            let (_, removed) = run.get_or_insert((line.number, 0));
+            *removed += 1;
+            continue;
+        }
+        report(&mut run);
+        fixed.push(line);
+    }
+    report(&mut run);
+
+    fixed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate_all_content;
+    use std::path::Path;
+
+    fn fixed(content: &str) -> String {
+        fix_content(content).0
+    }
+
+    #[test]
+    fn test_valid_changelog_is_unchanged() {
+        let content = include_str!("../CHANGELOG.md");
+        let (text, fixes) = fix_content(content);
+        assert!(fixes.is_empty(), "unexpected fixes: {:?}", fixes);
+        assert_eq!(text, content);
+    }
+
+    #[test]
+    fn test_normalizes_headers() {
+        let content = "# Changelog\n\n##[1.1.0]-2025/10/17\n\n###Added\n- New\n\n##  [1.0.0] — 2025.01.02\n\n###   Fixed  \n- Bug\n\n## [Unreleased]  \n";
+        let (text, fixes) = fix_content(content);
+        assert_eq!(
+            text,
+            "# Changelog\n\n## [1.1.0] - 2025-10-17\n\n### Added\n- New\n\n## [1.0.0] - 2025-01-02\n\n### Fixed\n- Bug\n\n## [Unreleased]\n"
+        );
+        let lines: Vec<usize> = fixes.iter().map(|fix| fix.line).collect();
+        assert_eq!(lines, vec![3, 3, 5, 8, 8, 10, 13]);
+        assert_eq!(
+            fixes[1].description,
+            "Changed the 1.1.0 date from 2025/10/17 to 2025-10-17"
+        );
+
+        // Dates that aren't just mis-separated are left for validation
+        assert_eq!(
+            fixed("## [1.0.0] - Oct 17, 2025\n"),
+            "## [1.0.0] - Oct 17, 2025\n"
+        );
+        assert_eq!(fixed("#### Notes\n"), "#### Notes\n");
+    }
+
+    #[test]
+    fn test_reorders_sections() {
+        let content = "\
+## [1.0.0] - 2025-10-17
+
+### Fixed
+- Bug
+
+### Added
+- Feature
+  continued
+
+---
+
+## [0.1.0] - 2025-01-01
+
+### Changed
+- Thing
+### Added
+- Other
+
+[1.0.0]: https://example.com/compare/v0.1.0...v1.0.0
+";
+        let (text, fixes) = fix_content(content);
+        assert_eq!(
+            text,
+            "\
+## [1.0.0] - 2025-10-17
+
+### Added
+- Feature
+  continued
+
+### Fixed
+- Bug
+
+---
+
+## [0.1.0] - 2025-01-01
+
+### Added
+- Other
+### Changed
+- Thing
+
+[1.0.0]: https://example.com/compare/v0.1.0...v1.0.0
+"
+        );
+        assert_eq!(
+            fixes,
+            vec![
+                FixApplied {
+                    line: 1,
+                    description: "Reordered the sections of 1.0.0 to Added, Fixed".to_string()
+                },
+                FixApplied {
+                    line: 12,
+                    description: "Reordered the sections of 0.1.0 to Added, Changed".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_leaves_unknown_sections_in_place() {
+        let content = "## [1.0.0] - 2025-10-17\n\n### Fixed\n- Bug\n\n### Notes\n- Text\n\n### Added\n- New\n";
+        assert_eq!(fixed(content), content);
+    }
+
+    #[test]
+    fn test_removes_empty_sections() {
+        let content = "\
+## [1.0.0] - 2025-10-17
+
+### Added
+
+### Fixed
+- Bug
+
+### Security
+
+---
+
+## [0.1.0] - 2025-01-01
+
+### Changed
+A note, but no entries
+";
+        let (text, fixes) = fix_content(content);
+        assert_eq!(
+            text,
+            "\
+## [1.0.0] - 2025-10-17
+
+### Fixed
+- Bug
+
+---
+
+## [0.1.0] - 2025-01-01
+
+### Changed
+A note, but no entries
+"
+        );
+        let lines: Vec<usize> = fixes.iter().map(|fix| fix.line).collect();
+        assert_eq!(lines, vec![3, 8]);
+        assert_eq!(
+            fixes[0].description,
+            "Removed the empty Added section from 1.0.0"
+        );
+    }
+
+    #[test]
+    fn test_collapses_blank_lines() {
+        let content = "# Changelog\n\n\n\n## [1.0.0] - 2025-10-17\n\n### Added\n- New\n\n\n";
+        let (text, fixes) = fix_content(content);
+        assert_eq!(
+            text,
+            "# Changelog\n\n## [1.0.0] - 2025-10-17\n\n### Added\n- New\n\n"
+        );
+        assert_eq!(
+            fixes,
+            vec![
+                FixApplied {
+                    line: 3,
+                    description: "Removed 2 extra blank line(s)".to_string()
+                },
+                FixApplied {
+                    line: 10,
+                    description: "Removed 1 extra blank line(s)".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fix_is_idempotent_and_leaves_semantic_problems() {
+        let content = "# Changelog\n\n## [1.0] - 2025/1/5\n\n### Fixed\n- Bug\n### Added\n\n\n### Added\n- New\n";
+        let (text, fixes) = fix_content(content);
+        assert!(!fixes.is_empty());
+        assert_eq!(fix_content(&text), (text.clone(), Vec::new()));
+
+        let report = validate_all_content(&text, Path::new("CHANGELOG.md"));
+        let rules: Vec<&str> = report.diagnostics.iter().map(|d| d.rule).collect();
+        assert_eq!(rules, vec!["invalid-semver", "invalid-date"]);
+    }
+}
@@ -0,0 +1,232 @@
+//! Changelog generation from Conventional Commits
+//!
+//! Commit messages like `feat(parser): add streaming` are mapped to Keep a
+//! Changelog sections and collected into a new version block. Commits that
+//! aren't conventional, or whose type has no place in a changelog (`docs`,
+//! `test`, `chore`, ...), are skipped.
+
+use git_conventional::Commit;
+
+use crate::parser::VALID_SECTIONS;
+use crate::semver::SemVer;
+use crate::{Changelog, Section, Version, render};
+
+/// Keep a Changelog section for a commit type, if it belongs in a changelog
+pub fn section_for(commit_type: &str) -> Option<&'static str> {
+    match commit_type.to_ascii_lowercase().as_str() {
+        "feat" => Some("Added"),
+        "fix" => Some("Fixed"),
+        "perf" | "refactor" | "revert" => Some("Changed"),
+        "deprecate" => Some("Deprecated"),
+        "remove" => Some("Removed"),
+        "security" | "sec" => Some("Security"),
+        _ => None,
+    }
+}
+
+/// The section and entry text for one commit message
+fn classify(message: &str) -> Option<(&'static str, String)> {
+    let commit = Commit::parse(message.trim()).ok()?;
+    let section = match section_for(commit.type_().as_str()) {
+        Some(section) => section,
+        // A breaking change always matters to readers, whatever its type
+        None if commit.breaking() => "Changed",
+        None => return None,
+    };
+
+    let mut text = capitalize(commit.description());
+    if let Some(scope) = commit.scope() {
+        text = format!("{}: {}", scope.as_str(), text);
+    }
+    if commit.breaking() {
+        text = format!("**Breaking:** {}", text);
+    }
+
+    Some((section, format!("- {}", text)))
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Group commit messages into sections, in Keep a Changelog order.
+/// Duplicate entries are listed once.
+pub fn sections_from_commits<I, S>(messages: I) -> Vec<Section>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut sections: Vec<Section> = VALID_SECTIONS
+        .iter()
+        .map(|name| Section {
+            name: name.to_string(),
+            entries: Vec::new(),
+        })
+        .collect();
+
+    for message in messages {
+        if let Some((name, entry)) = classify(message.as_ref())
+            && let Some(section) = sections.iter_mut().find(|s| s.name == name)
+            && !section.entries.contains(&entry)
+        {
+            section.entries.push(entry);
+        }
+    }
+
+    sections.retain(|s| !s.entries.is_empty());
+    sections
+}
+
+/// The next version after `previous` for these commits: major for breaking
+/// changes (minor while still 0.x), minor for features, patch otherwise.
+/// A prerelease such as `1.0.0-rc.1` is followed by its release, `1.0.0`.
+pub fn next_version<I, S>(previous: &SemVer, messages: I) -> SemVer
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let (mut breaking, mut feature) = (false, false);
+    for message in messages {
+        if let Ok(commit) = Commit::parse(message.as_ref().trim()) {
+            breaking |= commit.breaking();
+            feature |= commit.type_() == git_conventional::Type::FEAT;
+        }
+    }
+
+    let (major, minor, patch) = (previous.major, previous.minor, previous.patch);
+    let (major, minor, patch) = if !previous.pre.is_empty() {
+        (major, minor, patch)
+    } else if breaking && major > 0 {
+        (major + 1, 0, 0)
+    } else if breaking || feature {
+        (major, minor + 1, 0)
+    } else {
+        (major, minor, patch + 1)
+    };
+
+    SemVer {
+        major,
+        minor,
+        patch,
+        pre: Vec::new(),
+        build: Vec::new(),
+    }
+}
+
+impl Changelog {
+    /// Build a changelog with a single version from Conventional Commit messages.
+    ///
+    /// The version has no sections if none of the commits belong in a changelog.
+    /// Its `content` is the rendered changelog, parsed back so it can be
+    /// edited; use [`crate::render_version`] for just the version block.
+    pub fn from_commits<I, S>(version: &str, date: &str, messages: I) -> Changelog
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let versions = vec![Version {
+            version: version.to_string(),
+            date: date.to_string(),
+            semver: SemVer::parse(version),
+            sections: sections_from_commits(messages),
+            line: 0,
+        }];
+
+        Changelog::parse(&render(&versions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{render_version, validate_content};
+    use std::path::Path;
+
+    const COMMITS: &[&str] = &[
+        "feat(parser): add streaming input",
+        "fix: handle empty files",
+        "docs: update README",
+        "Merge branch 'main'",
+        "perf: cache compiled patterns",
+        "fix: handle empty files",
+        "chore!: drop support for the old config format",
+    ];
+
+    #[test]
+    fn test_from_commits() {
+        let changelog = Changelog::from_commits("1.2.0", "TBD", COMMITS);
+        let version = &changelog.versions[0];
+
+        let sections: Vec<(&str, Vec<&str>)> = version
+            .sections
+            .iter()
+            .map(|s| {
+                (
+                    s.name.as_str(),
+                    s.entries.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                ("Added", vec!["- parser: Add streaming input"]),
+                (
+                    "Changed",
+                    vec![
+                        "- Cache compiled patterns",
+                        "- **Breaking:** Drop support for the old config format"
+                    ]
+                ),
+                ("Fixed", vec!["- Handle empty files"]),
+            ]
+        );
+
+        // The generated changelog passes validation
+        validate_content(&changelog.content, Path::new("generated.md")).unwrap();
+        assert!(render_version(version).starts_with("## [1.2.0] - TBD\n\n### Added\n"));
+    }
+
+    #[test]
+    fn test_edit_generated_changelog() {
+        let mut changelog = Changelog::from_commits("1.2.0", "TBD", COMMITS);
+        assert_eq!(changelog.versions[0].line, 3);
+
+        changelog.release_unreleased("2026-10-18").unwrap();
+        changelog.add_version("1.3.0", "TBD").unwrap();
+        changelog.add_entry("1.3.0", "Added", "Watch mode").unwrap();
+
+        let versions: Vec<_> = changelog
+            .versions
+            .iter()
+            .map(|v| (v.version.as_str(), v.date.as_str()))
+            .collect();
+        assert_eq!(versions, vec![("1.3.0", "TBD"), ("1.2.0", "2026-10-18")]);
+        validate_content(&changelog.to_markdown(), Path::new("generated.md")).unwrap();
+    }
+
+    #[test]
+    fn test_next_version() {
+        let v = |s: &str| SemVer::parse(s).unwrap();
+        assert_eq!(next_version(&v("1.2.3"), ["fix: a"]), v("1.2.4"));
+        assert_eq!(next_version(&v("1.2.3"), ["fix: a", "feat: b"]), v("1.3.0"));
+        assert_eq!(next_version(&v("1.2.3"), ["feat!: b"]), v("2.0.0"));
+        assert_eq!(next_version(&v("0.4.1"), ["feat!: b"]), v("0.5.0"));
+        assert_eq!(
+            next_version(&v("1.0.0-rc.1"), ["fix: a"]),
+            v("1.0.0"),
+            "a prerelease is followed by its release"
+        );
+    }
+
+    #[test]
+    fn test_section_for() {
+        assert_eq!(section_for("feat"), Some("Added"));
+        assert_eq!(section_for("FIX"), Some("Fixed"));
+        assert_eq!(section_for("chore"), None);
+    }
+}
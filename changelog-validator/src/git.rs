@@ -0,0 +1,52 @@
+//! Reading commit history for `changelog-validator generate`
+
+use anyhow::{Context, Result, bail};
+use std::path::Path;
+use std::process::Command;
+
+/// Separates commit messages in `git log` output (ASCII record separator)
+const RECORD_SEPARATOR: char = '\x1e';
+
+fn git(repo: &Path, args: &[&str]) -> Result<std::process::Output> {
+    Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .context("Failed to run git")
+}
+
+/// The most recent tag reachable from `rev`, if there is one
+pub fn latest_tag(repo: &Path, rev: &str) -> Result<Option<String>> {
+    let output = git(repo, &["describe", "--tags", "--abbrev=0", rev])?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let tag = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((!tag.is_empty()).then_some(tag))
+}
+
+/// Full messages of the non-merge commits in `from..to`, newest first.
+/// Without `from`, every commit reachable from `to` is included.
+pub fn commit_messages(repo: &Path, from: Option<&str>, to: &str) -> Result<Vec<String>> {
+    let range = match from {
+        Some(from) => format!("{}..{}", from, to),
+        None => to.to_string(),
+    };
+    let format = format!("--format=%B{}", RECORD_SEPARATOR);
+    let output = git(repo, &["log", "--no-merges", &format, &range])?;
+    if !output.status.success() {
+        bail!(
+            "git log {} failed: {}",
+            range,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split(RECORD_SEPARATOR)
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .map(str::to_string)
+        .collect())
+}
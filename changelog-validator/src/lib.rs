@@ -3,44 +3,80 @@
 //! This library provides validation for CHANGELOG.md files following the
 //! [Keep a Changelog](https://keepachangelog.com/) format.
 
+pub mod diagnostics;
+mod edit;
+mod fix;
+pub mod generate;
+pub mod parser;
+pub mod semver;
+pub mod workspace;
+
 use anyhow::{Context, Result, bail};
-use once_cell::sync::Lazy;
-use regex::Regex;
+use std::cmp::Ordering;
 use std::path::Path;
 
-/// Valid section headers according to Keep a Changelog
-const VALID_SECTIONS: &[&str] = &[
-    "Added",
-    "Changed",
-    "Deprecated",
-    "Removed",
-    "Fixed",
-    "Security",
-];
-
-/// Regex patterns for validation
-static VERSION_PATTERN: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^## \[([^\]]+)\] - (.+)$").unwrap());
-static DATE_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}$|^TBD$").unwrap());
-static SECTION_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^### (.+)$").unwrap());
+pub use diagnostics::{
+    Diagnostic, Severity, ValidationReport, validate_all_content, validate_all_content_with_options,
+};
+pub use fix::{FixApplied, fix_content};
+pub use generate::{next_version, section_for, sections_from_commits};
+pub use parser::{
+    Event, EventParser, ParseError, UNRELEASED, VALID_SECTIONS, ValidationOptions, parse_events,
+    parse_events_with_options, parse_reader,
+};
+pub use semver::{Identifier, SemVer};
+pub use workspace::{
+    CrateReport, WorkspaceReport, validate_workspace, validate_workspace_with_options,
+};
 
 /// Represents a parsed changelog
 #[derive(Debug)]
 pub struct Changelog {
     pub content: String,
     pub versions: Vec<Version>,
+    /// `[label]: url` link reference definitions, in file order
+    pub links: Vec<LinkDefinition>,
+}
+
+/// A `[label]: url` link reference definition, such as
+/// `[1.2.0]: https://github.com/owner/repo/compare/v1.1.0...v1.2.0`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkDefinition {
+    pub label: String,
+    pub url: String,
+    /// 1-based line of the definition; 0 for links not parsed from a file
+    pub line: usize,
 }
 
 /// Represents a version entry in the changelog
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Version {
+    /// Version number, or [`UNRELEASED`] for an `## [Unreleased]` section
     pub version: String,
+    /// Release date; empty for Unreleased
     pub date: String,
+    /// Parsed version number; None for Unreleased or an invalid version
+    pub semver: Option<SemVer>,
     pub sections: Vec<Section>,
+    /// 1-based line of the `## [...]` header; 0 for versions not parsed from a file
+    pub line: usize,
+}
+
+impl Version {
+    /// Whether this is the `## [Unreleased]` section
+    pub fn is_unreleased(&self) -> bool {
+        self.version == UNRELEASED
+    }
+
+    /// Compare two versions by SemVer precedence. Returns None unless both
+    /// have valid version numbers.
+    pub fn cmp_precedence(&self, other: &Version) -> Option<Ordering> {
+        Some(self.semver.as_ref()?.cmp_precedence(other.semver.as_ref()?))
+    }
 }
 
 /// Represents a section within a version
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Section {
     pub name: String,
     pub entries: Vec<String>,
@@ -65,202 +101,141 @@ pub fn validate_changelog<P: AsRef<Path>>(path: P) -> Result<Changelog> {
     validate_content(&content, path)
 }
 
-/// Validates changelog content
-pub fn validate_content(content: &str, path: &Path) -> Result<Changelog> {
-    let lines: Vec<&str> = content.lines().collect();
-
-    // Validate header
-    if lines.is_empty() || !lines[0].starts_with("# Changelog") {
-        bail!("{}: Must start with '# Changelog' header", path.display());
-    }
-
-    // Check for [Unreleased] section (disallowed)
-    if content.contains("## [Unreleased]") {
-        bail!("{}: [Unreleased] sections are not allowed", path.display());
-    }
-
-    // Validate that only blank lines appear between header and first version
-    validate_header_format(&lines, path)?;
-
-    // Parse and validate versions
-    let versions = parse_versions(&lines, path)?;
-
-    if versions.is_empty() {
-        bail!(
-            "{}: Must have at least one versioned release",
-            path.display()
-        );
-    }
+/// Validates a changelog file at the given path, collecting every problem
+///
+/// # Errors
+///
+/// Returns an error only if the file cannot be read; validation problems are
+/// reported in the returned [`ValidationReport`].
+pub fn validate_all<P: AsRef<Path>>(path: P) -> Result<ValidationReport> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read changelog at {}", path.display()))?;
 
-    Ok(Changelog {
-        content: content.to_string(),
-        versions,
-    })
+    Ok(validate_all_content(&content, path))
 }
 
-/// Validates that only blank lines appear between the header and first version
-fn validate_header_format(lines: &[&str], path: &Path) -> Result<()> {
-    let mut found_header = false;
-
-    for (i, line) in lines.iter().enumerate() {
-        let trimmed = line.trim();
-
-        // Found the header
-        if trimmed.starts_with("# Changelog") {
-            found_header = true;
-            continue;
-        }
-
-        // After header, check for non-blank lines before first version
-        if found_header {
-            // If we hit a version header, we're done
-            if VERSION_PATTERN.is_match(trimmed) {
-                break;
-            }
+/// Validates changelog content, failing on the first problem found.
+///
+/// Use [`validate_all_content`] to get every problem at once.
+pub fn validate_content(content: &str, path: &Path) -> Result<Changelog> {
+    validate_content_with_options(content, path, ValidationOptions::default())
+}
 
-            // If we find a non-blank line that's not a version header
-            if !trimmed.is_empty() {
-                bail!(
-                    "{}: Line {}: Found content between '# Changelog' header and first version section. Only blank lines are allowed.",
-                    path.display(),
-                    i + 1
-                );
-            }
+/// Like [`validate_content`], applying optional rules
+pub fn validate_content_with_options(
+    content: &str,
+    path: &Path,
+    options: ValidationOptions,
+) -> Result<Changelog> {
+    let mut builder = VersionBuilder::default();
+
+    for event in parse_events_with_options(content, options) {
+        if let Event::Error { line, error } = event {
+            bail!("{}: Line {}: {}", path.display(), line, error);
         }
+        builder.push(event);
     }
 
-    Ok(())
+    Ok(builder.build(content))
 }
 
-/// Parses version entries from changelog lines
-fn parse_versions(lines: &[&str], path: &Path) -> Result<Vec<Version>> {
-    let mut versions = Vec::new();
-    let mut current_version: Option<Version> = None;
-    let mut current_section: Option<Section> = None;
-
-    for line in lines {
-        let trimmed = line.trim();
-
-        // Check for version header
-        if let Some(caps) = VERSION_PATTERN.captures(trimmed) {
-            // Save previous version if exists
-            if let Some(mut ver) = current_version.take() {
-                if let Some(sec) = current_section.take() {
-                    ver.sections.push(sec);
-                }
-                versions.push(ver);
-            }
-
-            let version = caps.get(1).unwrap().as_str().to_string();
-            let date = caps.get(2).unwrap().as_str().to_string();
-
-            // Validate semver format
-            if !is_valid_semver(&version) {
-                bail!(
-                    "{}: Invalid semver format '{}' (expected X.Y.Z)",
-                    path.display(),
-                    version
-                );
-            }
-
-            // Validate date format
-            if !DATE_PATTERN.is_match(&date) {
-                bail!(
-                    "{}: Invalid date format '{}' for version {} (expected YYYY-MM-DD or TBD)",
-                    path.display(),
-                    date,
-                    version
-                );
-            }
+/// Assembles versions from a stream of parse events, ignoring errors
+#[derive(Debug, Default)]
+pub struct VersionBuilder {
+    versions: Vec<Version>,
+    links: Vec<LinkDefinition>,
+}
 
-            current_version = Some(Version {
+impl VersionBuilder {
+    pub fn push(&mut self, event: Event) {
+        match event {
+            Event::VersionStart {
+                line,
+                version,
+                date,
+            } => self.versions.push(Version {
+                semver: SemVer::parse(&version),
                 version,
                 date,
                 sections: Vec::new(),
-            });
-        }
-        // Check for section header
-        else if let Some(caps) = SECTION_PATTERN.captures(trimmed) {
-            // Save previous section if exists
-            if let Some(sec) = current_section.take() {
-                if let Some(ref mut ver) = current_version {
-                    ver.sections.push(sec);
+                line,
+            }),
+            Event::Section { name, .. } => {
+                if let Some(version) = self.versions.last_mut() {
+                    version.sections.push(Section {
+                        name,
+                        entries: Vec::new(),
+                    });
                 }
             }
-
-            let section_name = caps.get(1).unwrap().as_str();
-
-            // Validate section name
-            if !VALID_SECTIONS.contains(&section_name) {
-                bail!(
-                    "{}: Invalid section '{}' (expected one of: {})",
-                    path.display(),
-                    section_name,
-                    VALID_SECTIONS.join(", ")
-                );
+            Event::Entry { text, .. } => {
+                if let Some(section) = self.versions.last_mut().and_then(|v| v.sections.last_mut())
+                {
+                    section.entries.push(text);
+                }
             }
-
-            current_section = Some(Section {
-                name: section_name.to_string(),
-                entries: Vec::new(),
-            });
-        }
-        // Check for section entry (list item)
-        else if trimmed.starts_with("- ") {
-            if let Some(ref mut sec) = current_section {
-                sec.entries.push(trimmed.to_string());
+            Event::LinkDefinition { line, label, url } => {
+                self.links.push(LinkDefinition { label, url, line })
             }
+            Event::Error { .. } => {}
         }
     }
 
-    // Save final version and section
-    if let Some(sec) = current_section {
-        if let Some(ref mut ver) = current_version {
-            ver.sections.push(sec);
-        }
-    }
-    if let Some(ver) = current_version {
-        versions.push(ver);
+    pub fn finish(self) -> Vec<Version> {
+        self.versions
     }
 
-    // Validate that each version has content
-    for version in &versions {
-        if version.sections.is_empty() {
-            bail!(
-                "{}: Version {} has no sections",
-                path.display(),
-                version.version
-            );
+    /// Assemble a [`Changelog`] with the versions and link definitions seen
+    pub fn build(self, content: &str) -> Changelog {
+        Changelog {
+            content: content.to_string(),
+            versions: self.versions,
+            links: self.links,
         }
+    }
+}
 
-        for section in &version.sections {
-            if section.entries.is_empty() {
-                bail!(
-                    "{}: Section '{}' in version {} is empty",
-                    path.display(),
-                    section.name,
-                    version.version
-                );
-            }
-        }
+/// Render versions back into changelog text
+pub fn render(versions: &[Version]) -> String {
+    let mut out = String::from("# Changelog\n");
+
+    for version in versions {
+        out.push('\n');
+        out.push_str(&render_version(version));
     }
 
-    Ok(versions)
+    out
 }
 
-/// Validates semver format (X.Y.Z where X, Y, Z are numbers)
-fn is_valid_semver(version: &str) -> bool {
-    let parts: Vec<&str> = version.split('.').collect();
-    if parts.len() != 3 {
-        return false;
+/// Render a single version block, starting at its `## [...]` header
+pub fn render_version(version: &Version) -> String {
+    let mut out = if version.is_unreleased() {
+        format!("## [{}]\n", UNRELEASED)
+    } else {
+        format!("## [{}] - {}\n", version.version, version.date)
+    };
+    for section in &version.sections {
+        out.push_str(&format!("\n### {}\n", section.name));
+        for entry in &section.entries {
+            out.push_str(entry);
+            out.push('\n');
+        }
     }
-    parts.iter().all(|p| p.parse::<u32>().is_ok())
+
+    out
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use crate::semver::SemVer;
+
+    fn is_valid_semver(version: &str) -> bool {
+        SemVer::parse(version).is_some()
+    }
+
     #[test]
     fn test_valid_semver() {
         assert!(is_valid_semver("1.0.0"));
@@ -269,6 +244,35 @@ mod tests {
         assert!(!is_valid_semver("1.0"));
         assert!(!is_valid_semver("1.0.0.0"));
         assert!(!is_valid_semver("1.0.x"));
+        assert!(is_valid_semver("1.2.0-rc.1"));
+        assert!(is_valid_semver("2.0.0+build.5"));
+        assert!(!is_valid_semver("01.0.0"));
+    }
+
+    #[test]
+    fn test_prerelease_versions_parsed() {
+        let content = r#"# Changelog
+
+## [2.0.0-rc.1+build.5] - TBD
+
+### Added
+- Release candidate
+
+## [1.0.0] - 2025-10-17
+
+### Added
+- Initial release
+"#;
+        let changelog = validate_content(content, Path::new("test.md")).unwrap();
+        let rc = &changelog.versions[0];
+        let semver = rc.semver.as_ref().unwrap();
+        assert_eq!(semver.major, 2);
+        assert!(semver.is_prerelease());
+        assert_eq!(semver.build, vec!["build", "5"]);
+        assert_eq!(
+            rc.cmp_precedence(&changelog.versions[1]),
+            Some(Ordering::Greater)
+        );
     }
 
     #[test]
@@ -328,6 +332,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_unreleased_section_with_option() {
+        let content = r#"# Changelog
+
+## [Unreleased]
+
+### Added
+- Upcoming feature
+
+## [1.0.0] - 2025-10-17
+
+### Added
+- Initial release
+"#;
+        let options = ValidationOptions {
+            allow_unreleased: true,
+            ..Default::default()
+        };
+        let changelog =
+            validate_content_with_options(content, Path::new("test.md"), options).unwrap();
+        assert_eq!(changelog.versions.len(), 2);
+        assert!(changelog.versions[0].is_unreleased());
+        assert_eq!(
+            changelog.versions[0].sections[0].entries,
+            vec!["- Upcoming feature"]
+        );
+        assert!(!changelog.versions[1].is_unreleased());
+        assert_eq!(render(&changelog.versions), content);
+    }
+
     #[test]
     fn test_content_after_header_disallowed() {
         let content = r#"# Changelog
@@ -406,6 +440,32 @@ and this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0
         assert!(result.unwrap_err().to_string().contains("is empty"));
     }
 
+    #[test]
+    fn test_render() {
+        let content = r#"# Changelog
+
+## [1.1.0] - TBD
+
+### Added
+- New thing
+
+---
+
+## [1.0.0] - 2025-10-17
+
+### Added
+- Initial release
+
+### Fixed
+- Bug fix
+"#;
+        let changelog = validate_content(content, Path::new("test.md")).unwrap();
+        assert_eq!(
+            render(&changelog.versions),
+            "# Changelog\n\n## [1.1.0] - TBD\n\n### Added\n- New thing\n\n## [1.0.0] - 2025-10-17\n\n### Added\n- Initial release\n\n### Fixed\n- Bug fix\n"
+        );
+    }
+
     #[test]
     fn test_tbd_date() {
         let content = r#"# Changelog
@@ -0,0 +1,369 @@
+// changelog-validator - Validate CHANGELOG.md files against Keep a Changelog
+
+mod git;
+
+use anyhow::{Context, Result, bail};
+use changelog_validator::{
+    Changelog, SemVer, ValidationOptions, ValidationReport, fix_content, next_version,
+    render_version, validate_all_content_with_options,
+};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Exit status when every changelog is valid
+const EXIT_VALID: u8 = 0;
+/// Exit status when at least one changelog has errors
+const EXIT_INVALID: u8 = 1;
+/// Exit status when a changelog could not be read
+const EXIT_UNREADABLE: u8 = 2;
+
+#[derive(Parser, Debug)]
+#[command(name = "changelog-validator")]
+#[command(about = "Validate CHANGELOG.md files against Keep a Changelog", long_about = None)]
+#[command(version)]
+#[command(args_conflicts_with_subcommands = true)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Changelog files, or directories containing a CHANGELOG.md
+    #[arg(default_value = "CHANGELOG.md")]
+    paths: Vec<PathBuf>,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Print nothing; report the result through the exit status only
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Accept an `## [Unreleased]` section before the first release
+    #[arg(long)]
+    allow_unreleased: bool,
+
+    /// Don't require versions to be listed newest first
+    #[arg(long)]
+    allow_out_of_order: bool,
+
+    /// Require a `[X.Y.Z]: url` link definition for every version
+    #[arg(long)]
+    require_links: bool,
+
+    /// Add missing version link definitions pointing at this repository, then validate
+    #[arg(long, value_name = "REPO_URL")]
+    add_links: Option<String>,
+
+    /// Fix mechanical problems (section order, header spacing, empty sections,
+    /// extra blank lines, date separators) in place, then validate
+    #[arg(long)]
+    fix: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate a version block from Conventional Commits between two revisions
+    Generate {
+        /// Start of the range, exclusive (default: the latest tag before --to)
+        #[arg(long)]
+        from: Option<String>,
+
+        /// End of the range, inclusive
+        #[arg(long, default_value = "HEAD")]
+        to: String,
+
+        /// Version for the new block (default: bumped from the --from tag)
+        #[arg(long)]
+        version: Option<String>,
+
+        /// Release date for the new block
+        #[arg(long, default_value = "TBD")]
+        date: String,
+
+        /// Repository to read history from
+        #[arg(long, default_value = ".")]
+        repo: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+/// The outcome for one path
+enum Outcome {
+    Checked(ValidationReport),
+    Unreadable { path: PathBuf, error: String },
+}
+
+impl Outcome {
+    fn is_valid(&self) -> bool {
+        matches!(self, Self::Checked(report) if report.is_valid())
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            Self::Checked(report) => json!({
+                "path": report.path,
+                "valid": report.is_valid(),
+                "versions": report.changelog.versions.len(),
+                "diagnostics": report.diagnostics.iter().map(|d| json!({
+                    "line": d.line,
+                    "severity": d.severity.to_string(),
+                    "rule": d.rule,
+                    "message": d.message,
+                })).collect::<Vec<_>>(),
+            }),
+            Self::Unreadable { path, error } => json!({
+                "path": path,
+                "valid": false,
+                "error": error,
+            }),
+        }
+    }
+
+    fn print_text(&self) {
+        match self {
+            Self::Checked(report) if report.is_valid() => {
+                print!("{}", report);
+                println!(
+                    "{}: ok ({} version(s))",
+                    report.path.display(),
+                    report.changelog.versions.len()
+                );
+            }
+            Self::Checked(report) => print!("{}", report),
+            Self::Unreadable { path, error } => {
+                println!("{}: error: {}", path.display(), error)
+            }
+        }
+    }
+}
+
+/// A directory stands for the CHANGELOG.md inside it
+fn changelog_path(path: &Path) -> PathBuf {
+    if path.is_dir() {
+        path.join("CHANGELOG.md")
+    } else {
+        path.to_path_buf()
+    }
+}
+
+fn check(path: &Path, options: ValidationOptions) -> Outcome {
+    let path = changelog_path(path);
+    match std::fs::read_to_string(&path) {
+        Ok(content) => {
+            Outcome::Checked(validate_all_content_with_options(&content, &path, options))
+        }
+        Err(e) => Outcome::Unreadable {
+            path,
+            error: format!("Failed to read changelog: {}", e),
+        },
+    }
+}
+
+/// Exit status for a set of outcomes; unreadable files take precedence
+fn exit_status(outcomes: &[Outcome]) -> u8 {
+    if outcomes
+        .iter()
+        .any(|o| matches!(o, Outcome::Unreadable { .. }))
+    {
+        EXIT_UNREADABLE
+    } else if outcomes.iter().all(Outcome::is_valid) {
+        EXIT_VALID
+    } else {
+        EXIT_INVALID
+    }
+}
+
+/// Version number from a tag such as `v1.2.0` or `gc-v1.2.0`
+fn tag_version(tag: &str) -> Option<SemVer> {
+    let start = tag.find(|c: char| c.is_ascii_digit())?;
+    SemVer::parse(&tag[start..])
+}
+
+fn cmd_generate(
+    repo: &Path,
+    from: Option<String>,
+    to: &str,
+    version: Option<String>,
+    date: &str,
+) -> Result<()> {
+    let from = match from {
+        Some(from) => Some(from),
+        None => git::latest_tag(repo, to)?,
+    };
+    let messages = git::commit_messages(repo, from.as_deref(), to)?;
+
+    let version = match version {
+        Some(version) => version,
+        None => {
+            let previous = from.as_deref().and_then(tag_version).with_context(|| {
+                format!(
+                    "Can't infer the next version from {}; pass --version",
+                    from.as_deref().unwrap_or("a repository without tags")
+                )
+            })?;
+            next_version(&previous, &messages).to_string()
+        }
+    };
+
+    let changelog = Changelog::from_commits(&version, date, &messages);
+    let block = &changelog.versions[0];
+    if block.sections.is_empty() {
+        bail!(
+            "None of the {} commit(s) in range are Conventional Commits with a changelog section",
+            messages.len()
+        );
+    }
+
+    print!("{}", render_version(block));
+    Ok(())
+}
+
+fn main() -> Result<ExitCode> {
+    let args = Args::parse();
+    if let Some(Command::Generate {
+        from,
+        to,
+        version,
+        date,
+        repo,
+    }) = args.command
+    {
+        cmd_generate(&repo, from, &to, version, &date)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let options = ValidationOptions {
+        allow_unreleased: args.allow_unreleased,
+        allow_out_of_order: args.allow_out_of_order,
+        require_links: args.require_links,
+    };
+
+    if args.fix {
+        for path in &args.paths {
+            let path = changelog_path(path);
+            // Unreadable files are reported by the check below
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let (fixed, fixes) = fix_content(&content);
+            if fixes.is_empty() {
+                continue;
+            }
+            std::fs::write(&path, fixed)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            if !args.quiet && args.format == Format::Text {
+                for fix in &fixes {
+                    println!("{}: {}", path.display(), fix);
+                }
+                println!("{}: fixed {} issue(s)", path.display(), fixes.len());
+            }
+        }
+    }
+
+    if let Some(repo_url) = &args.add_links {
+        for path in &args.paths {
+            let path = changelog_path(path);
+            // Unreadable files are reported by the check below
+            let Ok(mut changelog) = Changelog::load(&path) else {
+                continue;
+            };
+            let added = changelog.add_missing_links(repo_url);
+            if added > 0 {
+                std::fs::write(&path, changelog.to_markdown())
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                if !args.quiet && args.format == Format::Text {
+                    println!("{}: added {} link definition(s)", path.display(), added);
+                }
+            }
+        }
+    }
+
+    let outcomes: Vec<Outcome> = args.paths.iter().map(|p| check(p, options)).collect();
+    let status = exit_status(&outcomes);
+
+    if !args.quiet {
+        match args.format {
+            Format::Json => {
+                let output = json!({
+                    "valid": status == EXIT_VALID,
+                    "files": outcomes.iter().map(Outcome::to_json).collect::<Vec<_>>(),
+                });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            }
+            Format::Text => {
+                for outcome in &outcomes {
+                    outcome.print_text();
+                }
+                let failed = outcomes.iter().filter(|o| !o.is_valid()).count();
+                if failed > 0 {
+                    println!(
+                        "\n{} of {} changelog(s) failed validation",
+                        failed,
+                        outcomes.len()
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(ExitCode::from(status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checked(content: &str) -> Outcome {
+        Outcome::Checked(validate_all_content_with_options(
+            content,
+            Path::new("CHANGELOG.md"),
+            ValidationOptions::default(),
+        ))
+    }
+
+    #[test]
+    fn test_tag_version() {
+        assert_eq!(tag_version("v1.2.0"), SemVer::parse("1.2.0"));
+        assert_eq!(tag_version("gc-v0.4.1-rc.1"), SemVer::parse("0.4.1-rc.1"));
+        assert_eq!(tag_version("latest"), None);
+    }
+
+    #[test]
+    fn test_exit_status() {
+        let valid = || checked("# Changelog\n\n## [1.0.0] - TBD\n\n### Added\n- Thing\n");
+        let invalid = || checked("# Changelog\n");
+        let unreadable = || Outcome::Unreadable {
+            path: PathBuf::from("missing.md"),
+            error: "not found".to_string(),
+        };
+
+        assert_eq!(exit_status(&[valid(), valid()]), EXIT_VALID);
+        assert_eq!(exit_status(&[valid(), invalid()]), EXIT_INVALID);
+        assert_eq!(exit_status(&[invalid(), unreadable()]), EXIT_UNREADABLE);
+    }
+
+    #[test]
+    fn test_json_lists_every_diagnostic() {
+        let outcome = checked("# Changelog\n\n## [1.0] - someday\n\n### Added\n- Thing\n");
+        let json = outcome.to_json();
+
+        assert_eq!(json["valid"], false);
+        assert_eq!(json["versions"], 1);
+        let rules: Vec<&str> = json["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|d| d["rule"].as_str().unwrap())
+            .collect();
+        assert_eq!(rules, vec!["invalid-semver", "invalid-date"]);
+        assert_eq!(json["diagnostics"][0]["line"], 3);
+        assert_eq!(json["diagnostics"][0]["severity"], "error");
+    }
+}
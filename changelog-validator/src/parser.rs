@@ -0,0 +1,972 @@
+//! Line-based changelog parser
+//!
+//! The parser is a small state machine that consumes one line at a time and
+//! yields [`Event`]s. Problems are reported as [`Event::Error`] and parsing
+//! continues, so arbitrarily large or malformed input is always read to the end.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::BufRead;
+
+use crate::semver::SemVer;
+
+/// Valid section headers according to Keep a Changelog
+pub const VALID_SECTIONS: &[&str] = &[
+    "Added",
+    "Changed",
+    "Deprecated",
+    "Removed",
+    "Fixed",
+    "Security",
+];
+
+/// Version name used for the `## [Unreleased]` section when it is allowed
+pub const UNRELEASED: &str = "Unreleased";
+
+/// Optional validation rules
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ValidationOptions {
+    /// Accept a Keep a Changelog style `## [Unreleased]` section (with no
+    /// date) as the first version instead of reporting it as an error
+    pub allow_unreleased: bool,
+    /// Skip checking that versions are listed newest first, with dates that
+    /// never increase going down the file
+    pub allow_out_of_order: bool,
+    /// Require a `[X.Y.Z]: url` link definition for every version, even in a
+    /// changelog with no link definitions. Changelogs that have any version
+    /// links are always checked.
+    pub require_links: bool,
+}
+
+static VERSION_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^## \[([^\]]+)\] - (.+)$").unwrap());
+pub(crate) static DATE_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}$|^TBD$").unwrap());
+static SECTION_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^### (.+)$").unwrap());
+pub(crate) static LINK_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\[([^\]]+)\]:\s+(\S+)$").unwrap());
+
+/// A parse event. Line numbers are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A `## [X.Y.Z] - DATE` header, or `## [Unreleased]` (with an empty
+    /// date) when [`ValidationOptions::allow_unreleased`] is set
+    VersionStart {
+        line: usize,
+        version: String,
+        date: String,
+    },
+    /// A `### Name` header within a version
+    Section { line: usize, name: String },
+    /// A `- item` list entry within a section (stored with its `- ` marker)
+    Entry { line: usize, text: String },
+    /// A `[label]: url` link reference definition, usually at the end of the file
+    LinkDefinition {
+        line: usize,
+        label: String,
+        url: String,
+    },
+    /// A problem at the given line; parsing continues afterwards
+    Error { line: usize, error: ParseError },
+}
+
+/// Problems the parser can report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    MissingHeader,
+    UnreleasedSection,
+    MisplacedUnreleased,
+    ContentBeforeFirstVersion,
+    InvalidSemver(String),
+    InvalidDate {
+        version: String,
+        date: String,
+    },
+    InvalidSection(String),
+    EmptySection {
+        version: String,
+        section: String,
+    },
+    EmptyVersion(String),
+    VersionOutOfOrder {
+        version: String,
+        previous: String,
+    },
+    DateOutOfOrder {
+        version: String,
+        date: String,
+        previous: String,
+        previous_date: String,
+    },
+    NoVersions,
+    /// A version number that already appeared at `first_line`
+    DuplicateVersion {
+        version: String,
+        first_line: usize,
+    },
+    /// A section header that already appeared in the same version at `first_line`
+    DuplicateSection {
+        version: String,
+        section: String,
+        first_line: usize,
+    },
+    /// A version header without a `[version]: url` link definition
+    MissingLink(String),
+    /// A link definition for a version that isn't in the changelog
+    UnusedLink(String),
+    Io(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHeader => write!(f, "Must start with '# Changelog' header"),
+            Self::UnreleasedSection => write!(f, "[Unreleased] sections are not allowed"),
+            Self::MisplacedUnreleased => {
+                write!(f, "[Unreleased] must come before all released versions")
+            }
+            Self::ContentBeforeFirstVersion => write!(
+                f,
+                "Found content between '# Changelog' header and first version section. Only blank lines are allowed."
+            ),
+            Self::InvalidSemver(version) => {
+                write!(
+                    f,
+                    "Invalid semver format '{}' (expected X.Y.Z, optionally with -prerelease and +build metadata)",
+                    version
+                )
+            }
+            Self::InvalidDate { version, date } => write!(
+                f,
+                "Invalid date format '{}' for version {} (expected YYYY-MM-DD or TBD)",
+                date, version
+            ),
+            Self::InvalidSection(name) => write!(
+                f,
+                "Invalid section '{}' (expected one of: {})",
+                name,
+                VALID_SECTIONS.join(", ")
+            ),
+            Self::EmptySection { version, section } => {
+                write!(f, "Section '{}' in version {} is empty", section, version)
+            }
+            Self::EmptyVersion(version) => write!(f, "Version {} has no sections", version),
+            Self::VersionOutOfOrder { version, previous } => write!(
+                f,
+                "Version {} is listed below {} but is not older (versions must be newest first)",
+                version, previous
+            ),
+            Self::DateOutOfOrder {
+                version,
+                date,
+                previous,
+                previous_date,
+            } => write!(
+                f,
+                "Version {} is dated {}, after {} ({}) listed above it",
+                version, date, previous, previous_date
+            ),
+            Self::NoVersions => write!(f, "Must have at least one versioned release"),
+            Self::DuplicateVersion {
+                version,
+                first_line,
+            } => write!(
+                f,
+                "Version {} is listed twice (first on line {})",
+                version, first_line
+            ),
+            Self::DuplicateSection {
+                version,
+                section,
+                first_line,
+            } => write!(
+                f,
+                "Section '{}' appears twice in version {} (first on line {})",
+                section, version, first_line
+            ),
+            Self::MissingLink(version) => {
+                write!(
+                    f,
+                    "Version {} has no link definition ([{}]: url)",
+                    version, version
+                )
+            }
+            Self::UnusedLink(label) => write!(
+                f,
+                "Link definition [{}] does not match any version in the changelog",
+                label
+            ),
+            Self::Io(message) => write!(f, "Failed to read line: {}", message),
+        }
+    }
+}
+
+/// Where the parser is in the document
+#[derive(Debug)]
+enum State {
+    /// Before the `# Changelog` line
+    Start,
+    /// After the header, before the first version
+    Preamble { reported: bool },
+    /// Inside a version, tracking the open section (if any)
+    InVersion {
+        version: String,
+        line: usize,
+        /// Name and header line of each section seen so far
+        sections: Vec<(String, usize)>,
+        section: Option<OpenSection>,
+    },
+}
+
+/// The last released version seen, for ordering checks
+#[derive(Debug)]
+struct PreviousVersion {
+    version: String,
+    semver: Option<SemVer>,
+    /// None if the date was invalid
+    date: Option<String>,
+}
+
+#[derive(Debug)]
+struct OpenSection {
+    name: String,
+    line: usize,
+    entries: usize,
+}
+
+/// Streaming event parser over any source of lines
+pub struct EventParser<I> {
+    lines: I,
+    line_no: usize,
+    state: State,
+    versions_seen: usize,
+    previous: Option<PreviousVersion>,
+    options: ValidationOptions,
+    /// Version headers seen so far, for matching against link definitions
+    headers: Vec<(usize, String)>,
+    /// Link definitions labelled with a version number or `Unreleased`
+    version_links: Vec<(usize, String)>,
+    pending: VecDeque<Event>,
+    finished: bool,
+}
+
+/// Parse changelog text into events
+pub fn parse_events(
+    content: &str,
+) -> EventParser<impl Iterator<Item = Result<String, String>> + '_> {
+    EventParser::new(content.lines().map(|l| Ok(l.to_string())))
+}
+
+/// Parse changelog text into events, applying optional rules
+pub fn parse_events_with_options(
+    content: &str,
+    options: ValidationOptions,
+) -> EventParser<impl Iterator<Item = Result<String, String>> + '_> {
+    EventParser::with_options(content.lines().map(|l| Ok(l.to_string())), options)
+}
+
+/// Parse a changelog from a reader without loading it into memory.
+///
+/// Read failures are reported as [`ParseError::Io`] events.
+pub fn parse_reader<R: BufRead>(
+    reader: R,
+) -> EventParser<impl Iterator<Item = Result<String, String>>> {
+    EventParser::new(reader.lines().map(|l| l.map_err(|e| e.to_string())))
+}
+
+impl<I> EventParser<I>
+where
+    I: Iterator<Item = Result<String, String>>,
+{
+    pub fn new(lines: I) -> Self {
+        Self::with_options(lines, ValidationOptions::default())
+    }
+
+    pub fn with_options(lines: I, options: ValidationOptions) -> Self {
+        Self {
+            lines,
+            line_no: 0,
+            state: State::Start,
+            versions_seen: 0,
+            previous: None,
+            options,
+            headers: Vec::new(),
+            version_links: Vec::new(),
+            pending: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    fn error(&mut self, line: usize, error: ParseError) {
+        self.pending.push_back(Event::Error { line, error });
+    }
+
+    /// Close the open section, reporting it if it had no entries
+    fn close_section(&mut self) {
+        if let State::InVersion {
+            version, section, ..
+        } = &mut self.state
+            && let Some(open) = section.take()
+            && open.entries == 0
+        {
+            let error = ParseError::EmptySection {
+                version: version.clone(),
+                section: open.name,
+            };
+            self.pending.push_back(Event::Error {
+                line: open.line,
+                error,
+            });
+        }
+    }
+
+    /// Close the open version, reporting it if it had no sections.
+    /// An empty Unreleased section is fine; there may be nothing pending.
+    fn close_version(&mut self) {
+        self.close_section();
+        if let State::InVersion {
+            version,
+            line,
+            sections,
+            ..
+        } = &self.state
+            && sections.is_empty()
+            && version != UNRELEASED
+        {
+            let (line, error) = (*line, ParseError::EmptyVersion(version.clone()));
+            self.error(line, error);
+        }
+    }
+
+    fn start_version(&mut self, line: usize, version: String, date: String) {
+        self.versions_seen += 1;
+        self.headers.push((line, version.clone()));
+        self.state = State::InVersion {
+            version: version.clone(),
+            line,
+            sections: Vec::new(),
+            section: None,
+        };
+        self.pending.push_back(Event::VersionStart {
+            line,
+            version,
+            date,
+        });
+    }
+
+    /// Report a version that isn't older than, or is dated after, the one above it
+    fn check_order(&mut self, line: usize, current: &PreviousVersion) {
+        let Some(previous) = &self.previous else {
+            return;
+        };
+
+        let mut errors = Vec::new();
+        if let (Some(semver), Some(prev)) = (&current.semver, &previous.semver)
+            && !prev.is_newer_than(semver)
+        {
+            errors.push(ParseError::VersionOutOfOrder {
+                version: current.version.clone(),
+                previous: previous.version.clone(),
+            });
+        }
+        if let (Some(date), Some(prev)) = (&current.date, &previous.date)
+            && is_later_date(date, prev)
+        {
+            errors.push(ParseError::DateOutOfOrder {
+                version: current.version.clone(),
+                date: date.clone(),
+                previous: previous.version.clone(),
+                previous_date: prev.clone(),
+            });
+        }
+
+        for error in errors {
+            self.error(line, error);
+        }
+    }
+
+    fn handle_line(&mut self, line: &str) {
+        let n = self.line_no;
+        let trimmed = line.trim();
+
+        if matches!(self.state, State::Start) {
+            self.state = State::Preamble { reported: false };
+            if trimmed.starts_with("# Changelog") {
+                return;
+            }
+            self.error(n, ParseError::MissingHeader);
+        }
+
+        if trimmed.starts_with("## [Unreleased]") {
+            if !self.options.allow_unreleased {
+                self.error(n, ParseError::UnreleasedSection);
+                return;
+            }
+
+            self.close_version();
+            if self.versions_seen > 0 {
+                self.error(n, ParseError::MisplacedUnreleased);
+            }
+            self.start_version(n, UNRELEASED.to_string(), String::new());
+            return;
+        }
+
+        if let Some(caps) = VERSION_PATTERN.captures(trimmed) {
+            let version = caps[1].to_string();
+            let date = caps[2].to_string();
+
+            self.close_version();
+
+            let semver = SemVer::parse(&version);
+            if semver.is_none() {
+                self.error(n, ParseError::InvalidSemver(version.clone()));
+            }
+            let valid_date = DATE_PATTERN.is_match(&date);
+            if !valid_date {
+                let error = ParseError::InvalidDate {
+                    version: version.clone(),
+                    date: date.clone(),
+                };
+                self.error(n, error);
+            }
+
+            let current = PreviousVersion {
+                version: version.clone(),
+                semver,
+                date: valid_date.then(|| date.clone()),
+            };
+            let first = self
+                .headers
+                .iter()
+                .find(|(_, v)| *v == version)
+                .map(|(line, _)| *line);
+            if let Some(first_line) = first {
+                // Reported instead of as out of order, which would be confusing
+                let error = ParseError::DuplicateVersion {
+                    version: version.clone(),
+                    first_line,
+                };
+                self.error(n, error);
+            } else if !self.options.allow_out_of_order {
+                self.check_order(n, &current);
+            }
+            self.previous = Some(current);
+
+            self.start_version(n, version, date);
+            return;
+        }
+
+        if let State::Preamble { reported } = &mut self.state {
+            if !trimmed.is_empty() && !*reported {
+                *reported = true;
+                self.error(n, ParseError::ContentBeforeFirstVersion);
+            }
+            return;
+        }
+
+        if let Some(caps) = LINK_PATTERN.captures(trimmed) {
+            let label = caps[1].to_string();
+            if label.eq_ignore_ascii_case(UNRELEASED) || SemVer::parse(&label).is_some() {
+                self.version_links.push((n, label.clone()));
+            }
+            self.pending.push_back(Event::LinkDefinition {
+                line: n,
+                label,
+                url: caps[2].to_string(),
+            });
+            return;
+        }
+
+        if let Some(caps) = SECTION_PATTERN.captures(trimmed) {
+            let name = caps[1].to_string();
+
+            self.close_section();
+
+            if !VALID_SECTIONS.contains(&name.as_str()) {
+                self.error(n, ParseError::InvalidSection(name.clone()));
+            }
+
+            let mut duplicate = None;
+            if let State::InVersion {
+                version,
+                sections,
+                section,
+                ..
+            } = &mut self.state
+            {
+                if let Some((_, first_line)) = sections.iter().find(|(s, _)| *s == name) {
+                    duplicate = Some(ParseError::DuplicateSection {
+                        version: version.clone(),
+                        section: name.clone(),
+                        first_line: *first_line,
+                    });
+                }
+                sections.push((name.clone(), n));
+                *section = Some(OpenSection {
+                    name: name.clone(),
+                    line: n,
+                    entries: 0,
+                });
+            }
+            if let Some(error) = duplicate {
+                self.error(n, error);
+            }
+            self.pending.push_back(Event::Section { line: n, name });
+            return;
+        }
+
+        if trimmed.starts_with("- ")
+            && let State::InVersion {
+                section: Some(open),
+                ..
+            } = &mut self.state
+        {
+            open.entries += 1;
+            self.pending.push_back(Event::Entry {
+                line: n,
+                text: trimmed.to_string(),
+            });
+        }
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+        match self.state {
+            State::Start => self.error(1, ParseError::MissingHeader),
+            State::InVersion { .. } => self.close_version(),
+            State::Preamble { .. } => {}
+        }
+        if self.versions_seen == 0 {
+            self.error(self.line_no.max(1), ParseError::NoVersions);
+        }
+        if self.options.require_links || !self.version_links.is_empty() {
+            self.check_links();
+        }
+    }
+
+    /// Report version headers without a link definition, and version link
+    /// definitions without a header
+    fn check_links(&mut self) {
+        let same = |a: &str, b: &str| a.eq_ignore_ascii_case(b);
+
+        let missing: Vec<(usize, ParseError)> = self
+            .headers
+            .iter()
+            .filter(|(_, version)| !self.version_links.iter().any(|(_, l)| same(l, version)))
+            .map(|(line, version)| (*line, ParseError::MissingLink(version.clone())))
+            .collect();
+        let unused: Vec<(usize, ParseError)> = self
+            .version_links
+            .iter()
+            .filter(|(_, label)| !self.headers.iter().any(|(_, v)| same(v, label)))
+            .map(|(line, label)| (*line, ParseError::UnusedLink(label.clone())))
+            .collect();
+
+        for (line, error) in missing.into_iter().chain(unused) {
+            self.error(line, error);
+        }
+    }
+}
+
+impl<I> Iterator for EventParser<I>
+where
+    I: Iterator<Item = Result<String, String>>,
+{
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            if self.finished {
+                return None;
+            }
+
+            match self.lines.next() {
+                Some(Ok(line)) => {
+                    self.line_no += 1;
+                    self.handle_line(&line);
+                }
+                Some(Err(message)) => {
+                    self.line_no += 1;
+                    self.error(self.line_no, ParseError::Io(message));
+                }
+                None => self.finish(),
+            }
+        }
+    }
+}
+
+/// Whether `date` is after `other`. Both are YYYY-MM-DD or TBD, and TBD
+/// (not yet released) is later than any real date.
+fn is_later_date(date: &str, other: &str) -> bool {
+    match (date == "TBD", other == "TBD") {
+        (true, false) => true,
+        (false, false) => date > other,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(content: &str) -> Vec<(usize, ParseError)> {
+        parse_events(content)
+            .filter_map(|e| match e {
+                Event::Error { line, error } => Some((line, error)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_event_sequence() {
+        let content = "# Changelog\n\n## [1.0.0] - 2025-10-17\n\n### Added\n- One\n- Two\n";
+        let events: Vec<Event> = parse_events(content).collect();
+        assert_eq!(
+            events,
+            vec![
+                Event::VersionStart {
+                    line: 3,
+                    version: "1.0.0".to_string(),
+                    date: "2025-10-17".to_string()
+                },
+                Event::Section {
+                    line: 5,
+                    name: "Added".to_string()
+                },
+                Event::Entry {
+                    line: 6,
+                    text: "- One".to_string()
+                },
+                Event::Entry {
+                    line: 7,
+                    text: "- Two".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_continues_after_errors() {
+        let content = "\
+# Changelog
+
+## [1.0] - someday
+
+### Stuff
+- entry
+
+### Added
+
+## [0.1.0] - 2025-01-01
+";
+        assert_eq!(
+            errors(content),
+            vec![
+                (3, ParseError::InvalidSemver("1.0".to_string())),
+                (
+                    3,
+                    ParseError::InvalidDate {
+                        version: "1.0".to_string(),
+                        date: "someday".to_string()
+                    }
+                ),
+                (5, ParseError::InvalidSection("Stuff".to_string())),
+                (
+                    8,
+                    ParseError::EmptySection {
+                        version: "1.0".to_string(),
+                        section: "Added".to_string()
+                    }
+                ),
+                (10, ParseError::EmptyVersion("0.1.0".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_input() {
+        assert_eq!(
+            errors(""),
+            vec![(1, ParseError::MissingHeader), (1, ParseError::NoVersions)]
+        );
+    }
+
+    #[test]
+    fn test_preamble_reported_once() {
+        let content = "# Changelog\nintro\nmore intro\n## [1.0.0] - TBD\n### Added\n- x\n";
+        assert_eq!(
+            errors(content),
+            vec![(2, ParseError::ContentBeforeFirstVersion)]
+        );
+    }
+
+    #[test]
+    fn test_unreleased_allowed_with_option() {
+        let content = "# Changelog\n\n## [Unreleased]\n\n### Added\n- Soon\n\n## [1.0.0] - 2025-10-17\n\n### Added\n- First\n";
+        let options = ValidationOptions {
+            allow_unreleased: true,
+            ..Default::default()
+        };
+        let events: Vec<Event> = parse_events_with_options(content, options).collect();
+        assert_eq!(
+            events[0],
+            Event::VersionStart {
+                line: 3,
+                version: UNRELEASED.to_string(),
+                date: String::new()
+            }
+        );
+        assert!(!events.iter().any(|e| matches!(e, Event::Error { .. })));
+
+        assert_eq!(errors(content)[0], (3, ParseError::UnreleasedSection));
+    }
+
+    #[test]
+    fn test_empty_unreleased_allowed() {
+        let content =
+            "# Changelog\n\n## [Unreleased]\n\n## [1.0.0] - 2025-10-17\n\n### Added\n- First\n";
+        let options = ValidationOptions {
+            allow_unreleased: true,
+            ..Default::default()
+        };
+        assert!(
+            !parse_events_with_options(content, options).any(|e| matches!(e, Event::Error { .. }))
+        );
+    }
+
+    #[test]
+    fn test_unreleased_must_be_first() {
+        let content =
+            "# Changelog\n\n## [1.0.0] - 2025-10-17\n\n### Added\n- First\n\n## [Unreleased]\n";
+        let options = ValidationOptions {
+            allow_unreleased: true,
+            ..Default::default()
+        };
+        let errors: Vec<ParseError> = parse_events_with_options(content, options)
+            .filter_map(|e| match e {
+                Event::Error { error, .. } => Some(error),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(errors, vec![ParseError::MisplacedUnreleased]);
+    }
+
+    #[test]
+    fn test_versions_out_of_order() {
+        let content = "\
+# Changelog
+
+## [1.0.0] - 2025-03-01
+
+### Added
+- Newest
+
+## [1.1.0] - 2025-02-01
+
+### Added
+- Misplaced
+
+## [0.9.0] - 2025-04-01
+
+### Added
+- Dated after 1.1.0
+";
+        assert_eq!(
+            errors(content),
+            vec![
+                (
+                    8,
+                    ParseError::VersionOutOfOrder {
+                        version: "1.1.0".to_string(),
+                        previous: "1.0.0".to_string()
+                    }
+                ),
+                (
+                    13,
+                    ParseError::DateOutOfOrder {
+                        version: "0.9.0".to_string(),
+                        date: "2025-04-01".to_string(),
+                        previous: "1.1.0".to_string(),
+                        previous_date: "2025-02-01".to_string()
+                    }
+                ),
+            ]
+        );
+
+        let options = ValidationOptions {
+            allow_out_of_order: true,
+            ..Default::default()
+        };
+        assert!(
+            !parse_events_with_options(content, options).any(|e| matches!(e, Event::Error { .. }))
+        );
+    }
+
+    #[test]
+    fn test_ordering_prerelease_and_tbd() {
+        let content = "\
+# Changelog
+
+## [2.0.0] - TBD
+
+### Added
+- Release
+
+## [2.0.0-rc.1] - TBD
+
+### Added
+- Candidate
+
+## [1.0.0] - 2025-01-01
+
+### Added
+- First
+
+## [0.9.0] - TBD
+
+### Added
+- Unreleased below a release
+";
+        assert_eq!(
+            errors(content),
+            vec![(
+                18,
+                ParseError::DateOutOfOrder {
+                    version: "0.9.0".to_string(),
+                    date: "TBD".to_string(),
+                    previous: "1.0.0".to_string(),
+                    previous_date: "2025-01-01".to_string()
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let content = "\
+# Changelog
+
+## [1.0.0] - 2025-02-01
+
+### Added
+- First
+
+### Fixed
+- Bug
+
+### Added
+- Second
+
+## [1.0.0] - 2025-01-01
+
+### Added
+- Again
+";
+        assert_eq!(
+            errors(content),
+            vec![
+                (
+                    11,
+                    ParseError::DuplicateSection {
+                        version: "1.0.0".to_string(),
+                        section: "Added".to_string(),
+                        first_line: 5
+                    }
+                ),
+                (
+                    14,
+                    ParseError::DuplicateVersion {
+                        version: "1.0.0".to_string(),
+                        first_line: 3
+                    }
+                ),
+            ]
+        );
+
+        // Still reported when ordering isn't checked
+        let options = ValidationOptions {
+            allow_out_of_order: true,
+            ..Default::default()
+        };
+        let duplicates = parse_events_with_options(content, options)
+            .filter(|e| matches!(e, Event::Error { .. }))
+            .count();
+        assert_eq!(duplicates, 2);
+    }
+
+    #[test]
+    fn test_link_definitions() {
+        let content = "\
+# Changelog
+
+## [1.1.0] - TBD
+
+### Added
+- Thing, see [docs]
+
+## [1.0.0] - 2025-01-01
+
+### Added
+- First
+
+[1.1.0]: https://github.com/owner/repo/compare/v1.0.0...v1.1.0
+[0.9.0]: https://github.com/owner/repo/releases/tag/v0.9.0
+[docs]: https://example.com/docs
+";
+        let links: Vec<(usize, String)> = parse_events(content)
+            .filter_map(|e| match e {
+                Event::LinkDefinition { line, label, .. } => Some((line, label)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                (13, "1.1.0".to_string()),
+                (14, "0.9.0".to_string()),
+                (15, "docs".to_string())
+            ]
+        );
+
+        // Links to labels other than versions, like [docs], are not checked
+        assert_eq!(
+            errors(content),
+            vec![
+                (8, ParseError::MissingLink("1.0.0".to_string())),
+                (14, ParseError::UnusedLink("0.9.0".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_links_required_only_with_option() {
+        let content = "# Changelog\n\n## [1.0.0] - 2025-01-01\n\n### Added\n- First\n";
+        assert!(errors(content).is_empty());
+
+        let options = ValidationOptions {
+            require_links: true,
+            ..Default::default()
+        };
+        let errors: Vec<ParseError> = parse_events_with_options(content, options)
+            .filter_map(|e| match e {
+                Event::Error { error, .. } => Some(error),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(errors, vec![ParseError::MissingLink("1.0.0".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_reader_matches_parse_events() {
+        let content = "# Changelog\n\n## [1.0.0] - TBD\n\n### Fixed\n- Bug\n";
+        let from_reader: Vec<Event> = parse_reader(content.as_bytes()).collect();
+        let from_str: Vec<Event> = parse_events(content).collect();
+        assert_eq!(from_reader, from_str);
+    }
+}
@@ -0,0 +1,273 @@
+//! Semantic Versioning 2.0.0 parsing and precedence
+//!
+//! Versions are `MAJOR.MINOR.PATCH`, optionally followed by `-prerelease`
+//! and `+build` metadata, e.g. `1.2.0-rc.1+build.5`. See
+//! <https://semver.org/spec/v2.0.0.html>.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A prerelease identifier
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Identifier {
+    Numeric(u64),
+    AlphaNumeric(String),
+}
+
+impl Identifier {
+    fn parse(s: &str) -> Option<Self> {
+        if !is_identifier(s) {
+            return None;
+        }
+        if s.bytes().all(|b| b.is_ascii_digit()) {
+            // Numeric identifiers must not include leading zeros
+            if s.len() > 1 && s.starts_with('0') {
+                return None;
+            }
+            return s.parse().ok().map(Self::Numeric);
+        }
+        Some(Self::AlphaNumeric(s.to_string()))
+    }
+}
+
+impl Ord for Identifier {
+    /// Numeric identifiers compare numerically and sort before alphanumeric ones,
+    /// which compare in ASCII order
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Numeric(a), Self::Numeric(b)) => a.cmp(b),
+            (Self::Numeric(_), Self::AlphaNumeric(_)) => Ordering::Less,
+            (Self::AlphaNumeric(_), Self::Numeric(_)) => Ordering::Greater,
+            (Self::AlphaNumeric(a), Self::AlphaNumeric(b)) => a.cmp(b),
+        }
+    }
+}
+
+impl PartialOrd for Identifier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Numeric(n) => write!(f, "{}", n),
+            Self::AlphaNumeric(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// A parsed semantic version
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SemVer {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// Prerelease identifiers, e.g. `["rc", 1]` for `-rc.1`
+    pub pre: Vec<Identifier>,
+    /// Build metadata identifiers, e.g. `["build", "5"]` for `+build.5`
+    pub build: Vec<String>,
+}
+
+/// Non-empty and made of ASCII alphanumerics and hyphens
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+/// A MAJOR, MINOR, or PATCH number: digits without leading zeros
+fn parse_number(s: &str) -> Option<u64> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) || (s.len() > 1 && s.starts_with('0'))
+    {
+        return None;
+    }
+    s.parse().ok()
+}
+
+impl SemVer {
+    /// Parse a version string, returning None if it isn't valid SemVer 2.0.0
+    pub fn parse(version: &str) -> Option<Self> {
+        let (rest, build) = match version.split_once('+') {
+            Some((rest, build)) => (rest, Some(build)),
+            None => (version, None),
+        };
+        let (core, pre) = match rest.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (rest, None),
+        };
+
+        let mut numbers = core.split('.').map(parse_number);
+        let (major, minor, patch) = (numbers.next()??, numbers.next()??, numbers.next()??);
+        if numbers.next().is_some() {
+            return None;
+        }
+
+        let pre = match pre {
+            Some(pre) => pre
+                .split('.')
+                .map(Identifier::parse)
+                .collect::<Option<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+
+        let build = match build {
+            Some(build) => {
+                let ids: Vec<&str> = build.split('.').collect();
+                if !ids.iter().all(|id| is_identifier(id)) {
+                    return None;
+                }
+                ids.into_iter().map(str::to_string).collect()
+            }
+            None => Vec::new(),
+        };
+
+        Some(Self {
+            major,
+            minor,
+            patch,
+            pre,
+            build,
+        })
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    /// Compare by SemVer precedence, which ignores build metadata:
+    /// `1.0.0-alpha < 1.0.0-alpha.1 < 1.0.0-beta < 1.0.0 < 1.0.1`
+    pub fn cmp_precedence(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                // A prerelease has lower precedence than the release itself
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            })
+    }
+
+    /// Whether this version has higher precedence than `other`
+    pub fn is_newer_than(&self, other: &Self) -> bool {
+        self.cmp_precedence(other) == Ordering::Greater
+    }
+}
+
+impl Ord for SemVer {
+    /// Precedence order, with build metadata as a tie-breaker so the ordering
+    /// is consistent with equality
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_precedence(other)
+            .then_with(|| self.build.cmp(&other.build))
+    }
+}
+
+impl PartialOrd for SemVer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for SemVer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            let pre: Vec<String> = self.pre.iter().map(Identifier::to_string).collect();
+            write!(f, "-{}", pre.join("."))?;
+        }
+        if !self.build.is_empty() {
+            write!(f, "+{}", self.build.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(s: &str) -> SemVer {
+        SemVer::parse(s).unwrap_or_else(|| panic!("{} should parse", s))
+    }
+
+    #[test]
+    fn test_parse_components() {
+        let version = v("1.2.3-rc.1+build.05");
+        assert_eq!((version.major, version.minor, version.patch), (1, 2, 3));
+        assert_eq!(
+            version.pre,
+            vec![
+                Identifier::AlphaNumeric("rc".to_string()),
+                Identifier::Numeric(1)
+            ]
+        );
+        assert_eq!(version.build, vec!["build", "05"]);
+        assert!(version.is_prerelease());
+        assert_eq!(version.to_string(), "1.2.3-rc.1+build.05");
+    }
+
+    #[test]
+    fn test_parse_valid() {
+        for s in [
+            "0.0.0",
+            "10.20.30",
+            "1.0.0-alpha",
+            "1.0.0-alpha-1.0",
+            "1.0.0-0.3.7",
+            "1.0.0-x.7.z.92",
+            "2.0.0+build.5",
+            "1.0.0-beta+exp.sha.5114f85",
+        ] {
+            assert_eq!(v(s).to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for s in [
+            "1.0",
+            "1.0.0.0",
+            "1.0.x",
+            "01.0.0",
+            "1.0.0-",
+            "1.0.0-01",
+            "1.0.0-alpha..1",
+            "1.0.0+",
+            "1.0.0+build..1",
+            "1.0.0-beta_1",
+            "v1.0.0",
+            "",
+        ] {
+            assert!(SemVer::parse(s).is_none(), "{} should not parse", s);
+        }
+    }
+
+    #[test]
+    fn test_precedence() {
+        let ordered = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.0.1",
+            "1.1.0",
+            "2.0.0",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(v(pair[1]).is_newer_than(&v(pair[0])), "{:?}", pair);
+            assert!(v(pair[0]) < v(pair[1]));
+        }
+    }
+
+    #[test]
+    fn test_build_metadata_ignored_for_precedence() {
+        assert_eq!(v("1.0.0+a").cmp_precedence(&v("1.0.0+b")), Ordering::Equal);
+        assert!(!v("1.0.0+b").is_newer_than(&v("1.0.0+a")));
+        assert_ne!(v("1.0.0+a"), v("1.0.0+b"));
+    }
+}
@@ -0,0 +1,332 @@
+//! Validation of every changelog in a cargo workspace
+//!
+//! [`validate_workspace`] reads the workspace members from the root
+//! `Cargo.toml`, validates each member's `CHANGELOG.md`, and checks that the
+//! newest released version matches the version in the member's manifest.
+
+use anyhow::{Context, Result, bail};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Table;
+
+use crate::diagnostics::{
+    Diagnostic, Severity, ValidationReport, validate_all_content_with_options,
+};
+use crate::parser::ValidationOptions;
+use crate::{Changelog, Version};
+
+/// Validation results for one workspace member
+#[derive(Debug)]
+pub struct CrateReport {
+    /// Member directory
+    pub dir: PathBuf,
+    /// `package.version` from the member's Cargo.toml, if it has one
+    pub manifest_version: Option<String>,
+    /// Diagnostics for the member's CHANGELOG.md, including version mismatches
+    pub report: ValidationReport,
+}
+
+/// Validation results for a whole workspace, keyed by crate name
+#[derive(Debug)]
+pub struct WorkspaceReport {
+    pub root: PathBuf,
+    pub crates: BTreeMap<String, CrateReport>,
+}
+
+impl WorkspaceReport {
+    /// True if no crate has an error-level diagnostic
+    pub fn is_valid(&self) -> bool {
+        self.crates.values().all(|c| c.report.is_valid())
+    }
+
+    /// Crates with at least one error
+    pub fn failing(&self) -> impl Iterator<Item = (&str, &CrateReport)> {
+        self.crates
+            .iter()
+            .filter(|(_, c)| !c.report.is_valid())
+            .map(|(name, c)| (name.as_str(), c))
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.crates
+            .values()
+            .map(|c| c.report.errors().count())
+            .sum()
+    }
+}
+
+impl fmt::Display for WorkspaceReport {
+    /// Every diagnostic, grouped by crate in name order
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for crate_report in self.crates.values() {
+            write!(f, "{}", crate_report.report)?;
+        }
+        Ok(())
+    }
+}
+
+/// Validates every workspace member's changelog under `root`
+///
+/// # Errors
+///
+/// Returns an error if the root or a member manifest can't be read or parsed,
+/// or if the root manifest has no `[workspace]` table. Problems with the
+/// changelogs themselves are reported in the returned [`WorkspaceReport`].
+pub fn validate_workspace(root: &Path) -> Result<WorkspaceReport> {
+    validate_workspace_with_options(root, ValidationOptions::default())
+}
+
+/// Like [`validate_workspace`], applying optional rules to every changelog
+pub fn validate_workspace_with_options(
+    root: &Path,
+    options: ValidationOptions,
+) -> Result<WorkspaceReport> {
+    let root_manifest = read_manifest(&root.join("Cargo.toml"))?;
+    let Some(workspace) = root_manifest.get("workspace").and_then(|w| w.as_table()) else {
+        bail!(
+            "{} has no [workspace] table",
+            root.join("Cargo.toml").display()
+        );
+    };
+    let workspace_version = workspace
+        .get("package")
+        .and_then(|p| p.get("version"))
+        .and_then(|v| v.as_str());
+
+    let mut crates = BTreeMap::new();
+    for dir in member_dirs(root, workspace, &root_manifest)? {
+        let manifest = read_manifest(&dir.join("Cargo.toml"))?;
+        let package = manifest.get("package");
+        let name = package
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| dir_name(&dir));
+        let manifest_version = package
+            .and_then(|p| p.get("version"))
+            .and_then(|v| match v {
+                toml::Value::String(version) => Some(version.as_str()),
+                // `version.workspace = true`
+                _ => workspace_version,
+            })
+            .map(str::to_string);
+
+        let report = validate_member(&dir, manifest_version.as_deref(), options)?;
+        crates.insert(
+            name,
+            CrateReport {
+                dir,
+                manifest_version,
+                report,
+            },
+        );
+    }
+
+    Ok(WorkspaceReport {
+        root: root.to_path_buf(),
+        crates,
+    })
+}
+
+fn read_manifest(path: &Path) -> Result<Table> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    content
+        .parse()
+        .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn dir_name(dir: &Path) -> String {
+    dir.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Member directories from `workspace.members`, minus `workspace.exclude`.
+///
+/// Supports plain paths and a trailing `/*` glob; the root itself is included
+/// when it is also a package.
+fn member_dirs(root: &Path, workspace: &Table, root_manifest: &Table) -> Result<Vec<PathBuf>> {
+    let strings = |key: &str| -> Vec<&str> {
+        workspace
+            .get(key)
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default()
+    };
+    let excluded: Vec<PathBuf> = strings("exclude").iter().map(|e| root.join(e)).collect();
+
+    let mut dirs = Vec::new();
+    if root_manifest.contains_key("package") {
+        dirs.push(root.to_path_buf());
+    }
+
+    for member in strings("members") {
+        if let Some(parent) = member.strip_suffix("/*") {
+            let parent = root.join(parent);
+            let entries = fs::read_dir(&parent)
+                .with_context(|| format!("Failed to read {}", parent.display()))?;
+            let mut matched: Vec<PathBuf> = entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.join("Cargo.toml").is_file())
+                .collect();
+            matched.sort();
+            dirs.extend(matched);
+        } else {
+            dirs.push(root.join(member));
+        }
+    }
+
+    dirs.retain(|d| !excluded.contains(d));
+    Ok(dirs)
+}
+
+/// Validate a member's changelog and compare its newest release to the manifest
+fn validate_member(
+    dir: &Path,
+    manifest_version: Option<&str>,
+    options: ValidationOptions,
+) -> Result<ValidationReport> {
+    let path = dir.join("CHANGELOG.md");
+    if !path.exists() {
+        return Ok(ValidationReport {
+            diagnostics: vec![Diagnostic {
+                line: 1,
+                severity: Severity::Error,
+                rule: "missing-changelog",
+                message: "No CHANGELOG.md found".to_string(),
+            }],
+            path,
+            changelog: Changelog {
+                content: String::new(),
+                versions: Vec::new(),
+                links: Vec::new(),
+            },
+        });
+    }
+
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut report = validate_all_content_with_options(&content, &path, options);
+
+    let latest = report
+        .changelog
+        .versions
+        .iter()
+        .find(|v| !v.is_unreleased());
+    if let (Some(expected), Some(latest)) = (manifest_version, latest)
+        && let Some(diagnostic) = version_mismatch(latest, expected)
+    {
+        report.diagnostics.push(diagnostic);
+    }
+
+    Ok(report)
+}
+
+fn version_mismatch(latest: &Version, manifest_version: &str) -> Option<Diagnostic> {
+    (latest.version != manifest_version).then(|| Diagnostic {
+        line: latest.line,
+        severity: Severity::Error,
+        rule: "version-mismatch",
+        message: format!(
+            "Latest changelog version {} does not match Cargo.toml version {}",
+            latest.version, manifest_version
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANGELOG: &str = "# Changelog\n\n## [1.1.0] - TBD\n\n### Added\n- Thing\n";
+
+    struct TempWorkspace(PathBuf);
+
+    impl TempWorkspace {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir().join(format!(
+                "changelog-validator-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).unwrap();
+            Self(root)
+        }
+
+        fn write(&self, path: &str, content: &str) {
+            let path = self.0.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        fn package(&self, dir: &str, name: &str, version: &str) {
+            self.write(
+                &format!("{}/Cargo.toml", dir),
+                &format!("[package]\nname = \"{}\"\nversion = {}\n", name, version),
+            );
+        }
+    }
+
+    impl Drop for TempWorkspace {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_validates_members() {
+        let ws = TempWorkspace::new("members");
+        ws.write(
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"good\", \"tools/*\"]\nexclude = [\"tools/skipped\"]\n\n[workspace.package]\nversion = \"1.1.0\"\n",
+        );
+        ws.package("good", "good", "\"1.1.0\"");
+        ws.write("good/CHANGELOG.md", CHANGELOG);
+        ws.package("tools/shared", "shared-version", "{ workspace = true }");
+        ws.write("tools/shared/CHANGELOG.md", CHANGELOG);
+        ws.package("tools/stale", "stale", "\"1.2.0\"");
+        ws.write("tools/stale/CHANGELOG.md", CHANGELOG);
+        ws.package("tools/undocumented", "undocumented", "\"0.1.0\"");
+        ws.package("tools/skipped", "skipped", "\"0.1.0\"");
+
+        let report = validate_workspace(&ws.0).unwrap();
+        let names: Vec<&str> = report.crates.keys().map(String::as_str).collect();
+        assert_eq!(
+            names,
+            vec!["good", "shared-version", "stale", "undocumented"]
+        );
+
+        assert_eq!(
+            report.crates["shared-version"].manifest_version.as_deref(),
+            Some("1.1.0")
+        );
+
+        let failing: Vec<(&str, &str)> = report
+            .failing()
+            .map(|(name, c)| (name, c.report.diagnostics[0].rule))
+            .collect();
+        assert_eq!(
+            failing,
+            vec![
+                ("stale", "version-mismatch"),
+                ("undocumented", "missing-changelog")
+            ]
+        );
+        assert_eq!(report.crates["stale"].report.diagnostics[0].line, 3);
+        assert_eq!(report.error_count(), 2);
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn test_requires_workspace_table() {
+        let ws = TempWorkspace::new("not-a-workspace");
+        ws.package(".", "solo", "\"1.0.0\"");
+        let err = validate_workspace(&ws.0).unwrap_err();
+        assert!(err.to_string().contains("has no [workspace] table"));
+    }
+}
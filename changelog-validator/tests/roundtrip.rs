@@ -0,0 +1,164 @@
+//! Property-based tests for the changelog grammar
+//!
+//! Generated changelogs must survive render → parse → render unchanged, and
+//! arbitrary input must always be parsed to the end without panicking.
+
+use changelog_validator::{
+    Event, Section, SemVer, VALID_SECTIONS, Version, VersionBuilder, parse_events, render,
+    validate_content,
+};
+use proptest::prelude::*;
+use std::path::Path;
+
+fn entry() -> impl Strategy<Value = String> {
+    "[A-Za-z0-9`]([A-Za-z0-9 ,.()`/-]{0,40}[A-Za-z0-9.)`])?".prop_map(|text| format!("- {}", text))
+}
+
+fn section() -> impl Strategy<Value = Section> {
+    (
+        prop::sample::select(VALID_SECTIONS),
+        prop::collection::vec(entry(), 1..5),
+    )
+        .prop_map(|(name, entries)| Section {
+            name: name.to_string(),
+            entries,
+        })
+}
+
+fn date() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just("TBD".to_string()),
+        (2000u32..2100, 1u32..13, 1u32..29)
+            .prop_map(|(y, m, d)| format!("{:04}-{:02}-{:02}", y, m, d)),
+    ]
+}
+
+fn version_number() -> impl Strategy<Value = String> {
+    (
+        (0u32..100, 0u32..100, 0u32..100),
+        prop::option::of(prop_oneof![
+            "(alpha|beta|rc)(\\.[1-9][0-9]{0,2})?",
+            "[a-z][0-9A-Za-z-]{0,7}\\.[a-z]{1,4}",
+        ]),
+        prop::option::of("[0-9A-Za-z-]{1,8}(\\.[0-9A-Za-z-]{1,8}){0,2}"),
+    )
+        .prop_map(|((major, minor, patch), pre, build)| {
+            let mut version = format!("{}.{}.{}", major, minor, patch);
+            if let Some(pre) = pre {
+                version.push_str(&format!("-{}", pre));
+            }
+            if let Some(build) = build {
+                version.push_str(&format!("+{}", build));
+            }
+            version
+        })
+}
+
+fn version() -> impl Strategy<Value = Version> {
+    (
+        version_number(),
+        date(),
+        prop::collection::vec(section(), 1..4),
+    )
+        .prop_map(|(version, date, mut sections)| {
+            // Each section may appear only once per version
+            let mut seen = Vec::new();
+            sections.retain(|s| {
+                let first = !seen.contains(&s.name);
+                seen.push(s.name.clone());
+                first
+            });
+            Version {
+                semver: SemVer::parse(&version),
+                version,
+                date,
+                sections,
+                line: 0,
+            }
+        })
+}
+
+/// Versions newest first with dates that never increase, as the validator requires
+fn versions() -> impl Strategy<Value = Vec<Version>> {
+    prop::collection::vec(version(), 1..5).prop_map(|mut versions| {
+        let precedence = |v: &Version| v.semver.clone().unwrap();
+        versions.sort_by(|a, b| precedence(b).cmp_precedence(&precedence(a)));
+        versions.dedup_by(|a, b| precedence(a).cmp_precedence(&precedence(b)).is_eq());
+
+        // "TBD" sorts after digits, so a reverse sort puts it first
+        let mut dates: Vec<String> = versions.iter().map(|v| v.date.clone()).collect();
+        dates.sort_by(|a, b| b.cmp(a));
+        for (version, date) in versions.iter_mut().zip(dates) {
+            version.date = date;
+        }
+        versions
+    })
+}
+
+proptest! {
+    #[test]
+    fn render_parse_render_roundtrip(versions in versions()) {
+        let rendered = render(&versions);
+        let changelog = validate_content(&rendered, Path::new("generated.md"))
+            .map_err(|e| TestCaseError::fail(e.to_string()))?;
+
+        prop_assert!(changelog.versions.iter().all(|v| v.semver.is_some()));
+        let lines: Vec<&str> = rendered.lines().collect();
+        prop_assert!(changelog.versions.iter().all(|v| lines[v.line - 1].starts_with("## [")));
+
+        let parsed: Vec<Version> = changelog
+            .versions
+            .iter()
+            .map(|v| Version { line: 0, ..v.clone() })
+            .collect();
+        prop_assert_eq!(&parsed, &versions);
+        prop_assert_eq!(render(&changelog.versions), rendered);
+    }
+
+    #[test]
+    fn arbitrary_input_is_parsed_to_the_end(content in ".{0,400}") {
+        let line_count = content.lines().count().max(1);
+        let mut builder = VersionBuilder::default();
+
+        for event in parse_events(&content) {
+            let line = match &event {
+                Event::VersionStart { line, .. }
+                | Event::Section { line, .. }
+                | Event::Entry { line, .. }
+                | Event::LinkDefinition { line, .. }
+                | Event::Error { line, .. } => *line,
+            };
+            prop_assert!(line >= 1 && line <= line_count);
+            builder.push(event);
+        }
+
+        let _ = builder.finish();
+        let _ = validate_content(&content, Path::new("fuzz.md"));
+    }
+
+    #[test]
+    fn line_soup_is_parsed_to_the_end(lines in prop::collection::vec(
+        prop_oneof![
+            Just("# Changelog".to_string()),
+            Just("## [Unreleased]".to_string()),
+            Just(String::new()),
+            Just("---".to_string()),
+            "## \\[[0-9a-z.]{0,8}\\] - [0-9A-Z-]{0,12}",
+            "### [A-Za-z]{0,10}",
+            "- [a-z ]{0,20}",
+        ],
+        0..60,
+    )) {
+        let content = lines.join("\n");
+        let events: Vec<Event> = parse_events(&content).collect();
+        let versions = events
+            .iter()
+            .filter(|e| matches!(e, Event::VersionStart { .. }))
+            .count();
+        let no_versions_reported = events.iter().any(|e| matches!(
+            e,
+            Event::Error { error: changelog_validator::ParseError::NoVersions, .. }
+        ));
+        prop_assert_eq!(versions == 0, no_versions_reported);
+    }
+}
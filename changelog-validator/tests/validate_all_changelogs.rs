@@ -3,7 +3,7 @@
 //! This test automatically discovers and validates all CHANGELOG.md files
 //! in the workspace, ensuring they conform to the Keep a Changelog format.
 
-use changelog_validator::validate_changelog;
+use changelog_validator::{fix_content, validate_changelog, validate_content, validate_workspace};
 use std::path::PathBuf;
 
 /// Get the workspace root directory
@@ -52,7 +52,7 @@ fn all_workspace_changelogs_are_valid() {
 
     for changelog_path in &changelogs {
         let relative_path = changelog_path
-            .strip_prefix(&workspace_root())
+            .strip_prefix(workspace_root())
             .unwrap_or(changelog_path);
 
         print!("  - {}... ", relative_path.display());
@@ -93,3 +93,36 @@ fn changelog_validator_has_changelog() {
     validate_changelog(&changelog_path)
         .expect("changelog-validator's own CHANGELOG.md must be valid");
 }
+
+#[test]
+fn workspace_changelogs_match_manifest_versions() {
+    let report = validate_workspace(&workspace_root()).expect("workspace manifests must parse");
+
+    assert!(
+        report.crates.contains_key("changelog-validator"),
+        "workspace members should include changelog-validator"
+    );
+    assert!(
+        report.is_valid(),
+        "\n{} problem(s) found:\n{}",
+        report.error_count(),
+        report
+    );
+}
+
+#[test]
+fn fixing_workspace_changelogs_keeps_them_valid() {
+    for changelog_path in find_all_changelogs() {
+        let content = std::fs::read_to_string(&changelog_path).unwrap();
+        let (fixed, _) = fix_content(&content);
+
+        validate_content(&fixed, &changelog_path)
+            .unwrap_or_else(|e| panic!("{}: {}", changelog_path.display(), e));
+        assert_eq!(
+            fix_content(&fixed),
+            (fixed.clone(), Vec::new()),
+            "{}: fixing again changed the result",
+            changelog_path.display()
+        );
+    }
+}
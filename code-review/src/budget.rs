@@ -0,0 +1,231 @@
+// Budget-constrained review planning
+//
+// When a diff is larger than the configured token limit, files are split into
+// those that fit in the budget (reviewed in full) and those that are only
+// described by a skeleton of their hunk headers (reviewed superficially).
+
+/// Estimate token count using a simple heuristic (~4 chars per token)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len() / 4
+}
+
+/// A single file's portion of a unified diff
+#[derive(Debug, Clone)]
+pub struct FileDiff {
+    pub path: String,
+    pub text: String,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+impl FileDiff {
+    /// Condensed view of the file: path, change counts, and hunk headers
+    pub fn skeleton(&self) -> String {
+        let mut out = format!("{} (+{} -{})\n", self.path, self.additions, self.deletions);
+        for line in self.text.lines().filter(|l| l.starts_with("@@")) {
+            out.push_str("  ");
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Split a unified diff into per-file chunks
+pub fn split_diff(diff: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+
+    for line in diff.lines() {
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            let path = rest
+                .split_once(" b/")
+                .map(|(_, b)| b.to_string())
+                .unwrap_or_else(|| rest.to_string());
+            files.push(FileDiff {
+                path,
+                text: String::new(),
+                additions: 0,
+                deletions: 0,
+            });
+        }
+
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+
+        if line.starts_with('+') && !line.starts_with("+++") {
+            file.additions += 1;
+        } else if line.starts_with('-') && !line.starts_with("---") {
+            file.deletions += 1;
+        }
+        file.text.push_str(line);
+        file.text.push('\n');
+    }
+
+    files
+}
+
+/// Which files get a full review and which are only skimmed
+#[derive(Debug)]
+pub struct ReviewPlan {
+    pub full: Vec<FileDiff>,
+    pub superficial: Vec<FileDiff>,
+}
+
+/// Partition files so the full diffs plus skeletons fit within `limit` tokens.
+///
+/// Every file is guaranteed at least a skeleton. The remaining budget is spent
+/// on full diffs, smallest first, so as many files as possible are reviewed
+/// in full. Files keep their original diff order within each group.
+pub fn plan_review(files: Vec<FileDiff>, limit: usize) -> ReviewPlan {
+    let skeleton_cost: usize = files.iter().map(|f| estimate_tokens(&f.skeleton())).sum();
+    let mut remaining = limit.saturating_sub(skeleton_cost);
+
+    let mut by_size: Vec<usize> = (0..files.len()).collect();
+    by_size.sort_by_key(|&i| files[i].text.len());
+
+    let mut include_full = vec![false; files.len()];
+    for i in by_size {
+        let file = &files[i];
+        // A full diff replaces the file's skeleton, so only the difference counts
+        let extra = estimate_tokens(&file.text).saturating_sub(estimate_tokens(&file.skeleton()));
+        if extra <= remaining {
+            remaining -= extra;
+            include_full[i] = true;
+        }
+    }
+
+    let mut plan = ReviewPlan {
+        full: Vec::new(),
+        superficial: Vec::new(),
+    };
+    for (file, full) in files.into_iter().zip(include_full) {
+        if full {
+            plan.full.push(file);
+        } else {
+            plan.superficial.push(file);
+        }
+    }
+    plan
+}
+
+/// Build the prompt for a summarized review
+pub fn build_summarized_prompt(plan: &ReviewPlan, user_prompt: Option<&str>) -> String {
+    let mut prompt = String::from(
+        "Review the following code changes. The full change set exceeded the review budget, \
+         so some files are shown in full and others only as skeletons (file path, line counts, \
+         and hunk headers). Review the full diffs in detail. For skeleton-only files, comment \
+         only on risks that are apparent from their structure, and do not speculate about \
+         code you cannot see.\n\n",
+    );
+
+    if let Some(p) = user_prompt {
+        prompt.push_str(&format!("Additional review instructions:\n{}\n\n", p));
+    }
+
+    if !plan.full.is_empty() {
+        prompt.push_str("## Files reviewed in full\n\n");
+        for file in &plan.full {
+            prompt.push_str(&file.text);
+            prompt.push('\n');
+        }
+    }
+
+    if !plan.superficial.is_empty() {
+        prompt.push_str("## Files shown as skeletons only\n\n");
+        for file in &plan.superficial {
+            prompt.push_str(&file.skeleton());
+        }
+    }
+
+    prompt
+}
+
+/// Footer appended to the review listing files that were only skimmed
+pub fn superficial_notice(plan: &ReviewPlan) -> Option<String> {
+    if plan.superficial.is_empty() {
+        return None;
+    }
+
+    let mut notice = format!(
+        "---\nReviewed superficially (over budget, skeleton only): {} of {} files\n",
+        plan.superficial.len(),
+        plan.superficial.len() + plan.full.len()
+    );
+    for file in &plan.superficial {
+        notice.push_str(&format!("  - {}\n", file.path));
+    }
+    Some(notice)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/small.rs b/src/small.rs
+index 111..222 100644
+--- a/src/small.rs
++++ b/src/small.rs
+@@ -1,2 +1,2 @@ fn small()
+-old
++new
+diff --git a/src/big.rs b/src/big.rs
+index 333..444 100644
+--- a/src/big.rs
++++ b/src/big.rs
+@@ -10,3 +10,40 @@ impl Big
++aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa
++bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb
++cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc
+";
+
+    #[test]
+    fn test_split_diff() {
+        let files = split_diff(DIFF);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "src/small.rs");
+        assert_eq!(files[0].additions, 1);
+        assert_eq!(files[0].deletions, 1);
+        assert_eq!(files[1].path, "src/big.rs");
+        assert_eq!(files[1].additions, 3);
+        assert_eq!(files[1].deletions, 0);
+    }
+
+    #[test]
+    fn test_skeleton_contains_hunk_headers_only() {
+        let files = split_diff(DIFF);
+        let skeleton = files[1].skeleton();
+        assert!(skeleton.starts_with("src/big.rs (+3 -0)"));
+        assert!(skeleton.contains("@@ -10,3 +10,40 @@ impl Big"));
+        assert!(!skeleton.contains("aaaa"));
+    }
+
+    #[test]
+    fn test_plan_review_prefers_small_files() {
+        let files = split_diff(DIFF);
+        let small_cost = estimate_tokens(&files[0].text);
+        let skeleton_cost: usize = files.iter().map(|f| estimate_tokens(&f.skeleton())).sum();
+
+        let plan = plan_review(files, skeleton_cost + small_cost);
+        assert_eq!(plan.full.len(), 1);
+        assert_eq!(plan.full[0].path, "src/small.rs");
+        assert_eq!(plan.superficial.len(), 1);
+        assert_eq!(plan.superficial[0].path, "src/big.rs");
+    }
+
+    #[test]
+    fn test_plan_review_everything_fits() {
+        let plan = plan_review(split_diff(DIFF), 100_000);
+        assert_eq!(plan.full.len(), 2);
+        assert!(superficial_notice(&plan).is_none());
+    }
+
+    #[test]
+    fn test_superficial_notice_lists_files() {
+        let plan = plan_review(split_diff(DIFF), 0);
+        let notice = superficial_notice(&plan).unwrap();
+        assert!(notice.contains("2 of 2 files"));
+        assert!(notice.contains("  - src/small.rs"));
+        assert!(notice.contains("  - src/big.rs"));
+    }
+}
@@ -0,0 +1,124 @@
+// code-review-specific configuration
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Default price used to convert a `--budget` into a token cap (USD per million input tokens)
+const DEFAULT_COST_PER_MILLION_TOKENS: f64 = 1.25;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeReviewConfig {
+    /// Maximum estimated tokens of diff to send for a full review
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+
+    /// Maximum spend per review in USD
+    #[serde(default)]
+    pub budget: Option<f64>,
+
+    /// Price per million input tokens, used to convert `budget` into tokens
+    #[serde(default = "default_cost_per_million_tokens")]
+    pub cost_per_million_tokens: f64,
+}
+
+fn default_cost_per_million_tokens() -> f64 {
+    DEFAULT_COST_PER_MILLION_TOKENS
+}
+
+impl Default for CodeReviewConfig {
+    fn default() -> Self {
+        Self {
+            max_tokens: None,
+            budget: None,
+            cost_per_million_tokens: DEFAULT_COST_PER_MILLION_TOKENS,
+        }
+    }
+}
+
+impl CodeReviewConfig {
+    /// Get the config file path: ~/.config/cli-programs/code-review.toml
+    pub fn config_path() -> Result<PathBuf> {
+        let home = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE"))?;
+        Ok(PathBuf::from(home)
+            .join(".config")
+            .join("cli-programs")
+            .join("code-review.toml"))
+    }
+
+    /// Load config from file, returning default if file doesn't exist
+    pub fn load() -> Result<Self> {
+        let path = Self::config_path()?;
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        let config: CodeReviewConfig = toml::from_str(&content)?;
+        Ok(config)
+    }
+
+    /// Resolve the effective token limit from CLI overrides and config defaults.
+    ///
+    /// When both a token cap and a budget apply, the stricter of the two wins.
+    /// Returns `None` when no limit is configured.
+    pub fn token_limit(&self, max_tokens: Option<usize>, budget: Option<f64>) -> Option<usize> {
+        let max_tokens = max_tokens.or(self.max_tokens);
+        let budget_tokens = budget
+            .or(self.budget)
+            .map(|usd| (usd / self.cost_per_million_tokens * 1_000_000.0).max(0.0) as usize);
+
+        match (max_tokens, budget_tokens) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = CodeReviewConfig::default();
+        assert_eq!(config.max_tokens, None);
+        assert_eq!(config.budget, None);
+        assert_eq!(config.token_limit(None, None), None);
+    }
+
+    #[test]
+    fn test_config_path() {
+        let path = CodeReviewConfig::config_path().unwrap();
+        assert!(path.ends_with("cli-programs/code-review.toml"));
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let toml_str = r#"
+max_tokens = 20000
+budget = 0.05
+cost_per_million_tokens = 2.5
+"#;
+        let config: CodeReviewConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.max_tokens, Some(20000));
+        assert_eq!(config.budget, Some(0.05));
+        assert_eq!(config.cost_per_million_tokens, 2.5);
+    }
+
+    #[test]
+    fn test_token_limit_uses_stricter_value() {
+        let config = CodeReviewConfig {
+            max_tokens: Some(50000),
+            budget: None,
+            cost_per_million_tokens: 1.0,
+        };
+        // $0.01 at $1/M tokens = 10,000 tokens, stricter than 50,000
+        assert_eq!(config.token_limit(None, Some(0.01)), Some(10000));
+        // CLI max tokens overrides config
+        assert_eq!(config.token_limit(Some(5000), Some(0.01)), Some(5000));
+        assert_eq!(config.token_limit(None, None), Some(50000));
+    }
+}
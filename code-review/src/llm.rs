@@ -0,0 +1,83 @@
+//! LLM client wrapper for code-review
+//!
+//! Reviews go through codex, which reads the repository itself. Release
+//! notes only need the prompt, so they go through the llm-client crate and
+//! the presets in llm.toml.
+
+use anyhow::{Context, Result};
+use llm_client::{
+    Config, FallbackProvider, LlmProvider, LlmRequest, get_provider_with_fallback, profiles,
+};
+
+/// Wrapper around the LLM provider chain for code-review
+pub struct LlmClient {
+    provider: FallbackProvider,
+}
+
+impl LlmClient {
+    /// Create a new LLM client with fallback chain support
+    ///
+    /// If preset_name is None, uses code-review's default preset from
+    /// config, or else the preset serving the long-context profile, since
+    /// the prompt carries the diff.
+    pub fn new(preset_name: Option<&str>) -> Result<Self> {
+        let config = Config::load().context("Failed to load LLM configuration")?;
+
+        let preset_name = preset_name.or(config.defaults.get("code-review").map(String::as_str));
+        let provider = match preset_name {
+            Some(preset_name) => {
+                get_provider_with_fallback(&config, preset_name).context(format!(
+                    "Failed to initialize provider chain for preset '{}'",
+                    preset_name
+                ))?
+            }
+            None => config.for_profile(profiles::LONG_CONTEXT).context(format!(
+                "Failed to initialize provider chain for profile '{}'",
+                profiles::LONG_CONTEXT
+            ))?,
+        };
+        let provider = provider.with_fallback_callback(|next_name| {
+            eprintln!("... falling back to \"{}\"", next_name);
+        });
+
+        Ok(Self { provider })
+    }
+
+    /// Name of the preset tried first
+    pub fn name(&self) -> &str {
+        self.provider.primary_name()
+    }
+
+    /// Send a completion request to the LLM
+    pub async fn complete(&self, prompt: &str) -> Result<String> {
+        let request = LlmRequest {
+            prompt: prompt.to_string(),
+            system_prompt: None,
+            max_tokens: None,
+            temperature: None,
+            files: vec![],
+            json_schema: None,
+        };
+
+        let response = match self.provider.complete(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                if let Some(hint) = e.remediation() {
+                    eprintln!("Hint: {}", hint);
+                }
+                return Err(e).context("LLM request failed");
+            }
+        };
+
+        if response.is_truncated()
+            && let Some(reason) = &response.finish_reason
+        {
+            eprintln!(
+                "Warning: response from {} was cut off ({}); output may be incomplete",
+                response.model, reason
+            );
+        }
+
+        Ok(response.content)
+    }
+}
@@ -1,7 +1,16 @@
+mod budget;
+mod config;
+mod llm;
+mod patches;
+mod rereview;
+mod summarize;
+
 use anyhow::{Context, Result};
 use chrono::Local;
-use clap::Parser;
-use std::path::PathBuf;
+use clap::{Parser, Subcommand};
+use config::CodeReviewConfig;
+use llm::LlmClient;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 const EXAMPLES: &str = r#"
@@ -23,6 +32,21 @@ EXAMPLES:
 
     # Review a specific commit with custom prompt
     code-review --commit abc123 "Check for breaking changes"
+
+    # Cap the review at ~20k tokens of diff (larger diffs get a summarized review)
+    code-review --max-tokens 20000
+
+    # Cap the review at roughly five cents of input tokens
+    code-review --budget 0.05
+
+    # Ask for patches for selected findings and apply them one by one
+    code-review --suggest-patches
+
+    # Review everything again, even if the branch was reviewed before
+    code-review --full
+
+    # Draft release notes for the changes since a tag
+    code-review summarize --base v1.2.0
 "#;
 
 #[derive(Parser, Debug)]
@@ -31,6 +55,9 @@ EXAMPLES:
 #[command(version)]
 #[command(after_help = EXAMPLES)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// Custom review instructions
     #[arg(value_name = "PROMPT")]
     prompt: Option<String>,
@@ -42,6 +69,40 @@ struct Args {
     /// Review a specific commit
     #[arg(long, value_name = "SHA")]
     commit: Option<String>,
+
+    /// Maximum estimated diff tokens for a full review (overrides config)
+    #[arg(long, value_name = "TOKENS", global = true)]
+    max_tokens: Option<usize>,
+
+    /// Maximum spend per review in USD (overrides config)
+    #[arg(long, value_name = "USD", global = true)]
+    budget: Option<f64>,
+
+    /// After the review, request unified diffs for selected findings and offer to apply them
+    #[arg(long)]
+    suggest_patches: bool,
+
+    /// Review the whole change set even if this branch was reviewed before
+    #[arg(long)]
+    full: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Summarize the changes since a ref as release notes instead of reviewing them
+    Summarize {
+        /// Tag, branch, or commit to summarize from
+        #[arg(long, value_name = "REF")]
+        base: String,
+
+        /// Additional instructions for the summary
+        #[arg(value_name = "PROMPT")]
+        prompt: Option<String>,
+
+        /// LLM preset from llm.toml to draft the notes with
+        #[arg(short, long, value_name = "PRESET")]
+        model: Option<String>,
+    },
 }
 
 #[derive(Debug)]
@@ -85,7 +146,9 @@ fn get_main_branch() -> Result<String> {
     if git(&["show-ref", "--verify", "--quiet", "refs/heads/master"]).is_ok() {
         return Ok("master".to_string());
     }
-    anyhow::bail!("Could not find 'main' or 'master' branch. Use --uncommitted or --commit instead.")
+    anyhow::bail!(
+        "Could not find 'main' or 'master' branch. Use --uncommitted or --commit instead."
+    )
 }
 
 fn determine_mode(args: &Args) -> Result<ReviewMode> {
@@ -105,6 +168,14 @@ fn determine_mode(args: &Args) -> Result<ReviewMode> {
     Ok(ReviewMode::Committed)
 }
 
+fn get_diff(mode: &ReviewMode, main_branch: &str) -> Result<String> {
+    match mode {
+        ReviewMode::Uncommitted => git(&["diff", "HEAD"]),
+        ReviewMode::Committed => git(&["diff", &format!("{}...HEAD", main_branch)]),
+        ReviewMode::SpecificCommit(sha) => git(&["show", "--format=", sha]),
+    }
+}
+
 fn run_codex(mode: &ReviewMode, main_branch: &str, prompt: Option<&str>) -> Result<String> {
     let mut args: Vec<&str> = match mode {
         ReviewMode::Uncommitted => {
@@ -124,8 +195,37 @@ fn run_codex(mode: &ReviewMode, main_branch: &str, prompt: Option<&str>) -> Resu
 
     eprintln!("Running: codex {}", args.join(" "));
 
+    invoke_codex(&args)
+}
+
+/// Key for the branch's entry in the review history, or None when the
+/// review isn't of a branch (a specific commit or a detached HEAD).
+/// Uncommitted and committed reviews diff different things, so each mode
+/// gets its own entry.
+fn history_key(mode: &ReviewMode) -> Option<String> {
+    let mode = match mode {
+        ReviewMode::Uncommitted => "uncommitted",
+        ReviewMode::Committed => "committed",
+        ReviewMode::SpecificCommit(_) => return None,
+    };
+    let root = git(&["rev-parse", "--show-toplevel"]).ok()?;
+    let branch = git(&["rev-parse", "--abbrev-ref", "HEAD"]).ok()?;
+    let branch = branch.trim();
+    if branch == "HEAD" {
+        return None;
+    }
+    Some(format!("{}:{}:{}", root.trim(), branch, mode))
+}
+
+fn run_codex_exec(prompt: &str) -> Result<String> {
+    eprintln!("Running: codex exec <review prompt>");
+
+    invoke_codex(&["exec", prompt])
+}
+
+fn invoke_codex(args: &[&str]) -> Result<String> {
     let output = Command::new("codex")
-        .args(&args)
+        .args(args)
         .output()
         .context("Failed to execute codex command")?;
 
@@ -173,6 +273,77 @@ fn parse_codex_output(output: &str) -> Result<String> {
     Ok(review_content)
 }
 
+/// Ask codex for patches for selected findings and offer to apply each one
+fn suggest_patches(review: &str) -> Result<()> {
+    let findings = patches::parse_findings(review);
+    if findings.is_empty() {
+        eprintln!("\nNo findings to suggest patches for.");
+        return Ok(());
+    }
+
+    let selected = patches::select_findings(&findings)?;
+    if selected.is_empty() {
+        return Ok(());
+    }
+    let selected: Vec<&patches::Finding> = selected.iter().map(|&i| &findings[i]).collect();
+
+    eprintln!("Running: codex exec <patch request>");
+    let output = invoke_codex(&["exec", &patches::build_patch_prompt(&selected)])?;
+    let response = parse_codex_output(&output).or_else(|e| {
+        let log_path = log_codex_output(&output)?;
+        eprintln!("Full output logged to: {}", log_path.display());
+        Err(e)
+    })?;
+
+    let suggested = patches::extract_patches(&response);
+    if suggested.is_empty() {
+        eprintln!("No patches were suggested.");
+        return Ok(());
+    }
+
+    patches::offer_patches(Path::new("."), &suggested, &selected)
+}
+
+/// Draft release notes for the commits since `base`
+async fn summarize(
+    base: &str,
+    prompt: Option<&str>,
+    model: Option<&str>,
+    token_limit: Option<usize>,
+) -> Result<()> {
+    let summary = summarize::RangeSummary::load(base)?;
+    let plan = summarize::plan_diff(base, token_limit.unwrap_or(usize::MAX))?;
+    eprintln!(
+        "Summarizing {} commits and {} changed files since {}",
+        summary.commit_count,
+        plan.full.len() + plan.superficial.len(),
+        base
+    );
+
+    let llm = LlmClient::new(model)?;
+    eprintln!("Running: {} <summary prompt>", llm.name());
+    let notes = llm
+        .complete(&summarize::build_prompt(&summary, &plan, prompt))
+        .await?;
+    let notes = notes.trim();
+    if notes.is_empty() {
+        anyhow::bail!("The LLM returned empty release notes");
+    }
+
+    println!("{}", notes);
+    let unknown = summarize::unknown_sections(notes);
+    if !unknown.is_empty() {
+        eprintln!(
+            "\nWarning: not Keep a Changelog sections: {}",
+            unknown.join(", ")
+        );
+    }
+    if let Some(notice) = budget::superficial_notice(&plan) {
+        println!("\n{}", notice);
+    }
+    Ok(())
+}
+
 fn log_codex_output(output: &str) -> Result<PathBuf> {
     let logs_dir = PathBuf::from("logs");
     std::fs::create_dir_all(&logs_dir).context("Failed to create logs directory")?;
@@ -184,7 +355,8 @@ fn log_codex_output(output: &str) -> Result<PathBuf> {
     Ok(log_file)
 }
 
-fn main() -> Result<()> {
+#[tokio::main]
+async fn main() -> Result<()> {
     let args = Args::parse();
 
     // Validate we're in a git repo
@@ -192,17 +364,105 @@ fn main() -> Result<()> {
         anyhow::bail!("Not in a git repository");
     }
 
+    let config = CodeReviewConfig::load().context("Failed to load code-review config")?;
+    let token_limit = config.token_limit(args.max_tokens, args.budget);
+
+    if let Some(Commands::Summarize {
+        base,
+        prompt,
+        model,
+    }) = &args.command
+    {
+        return summarize(base, prompt.as_deref(), model.as_deref(), token_limit).await;
+    }
+
     // Determine review mode
     let mode = determine_mode(&args)?;
     let main_branch = get_main_branch()?;
 
+    let diff = get_diff(&mode, &main_branch)?;
+    let key = history_key(&mode);
+    let mut history = rereview::ReviewHistory::load();
+    let previous = key
+        .as_ref()
+        .filter(|_| !args.full)
+        .and_then(|key| history.reviews.get(key))
+        .cloned();
+
+    // Re-review only the hunks that changed since the branch was last reviewed
+    let mut rereview_prompt = None;
+    let mut plan = None;
+    if let Some(previous) = &previous {
+        let changes = rereview::changes_since(&diff, previous);
+        if changes.is_empty() {
+            println!(
+                "No changes since the last review of this branch ({}). Use --full to review it again.",
+                previous.reviewed_at.format("%Y-%m-%d %H:%M")
+            );
+            return Ok(());
+        }
+        eprintln!(
+            "Re-reviewing {} changed hunks in {} files since the last review ({}); use --full for a complete review",
+            changes.hunk_count(),
+            changes.files.len(),
+            previous.reviewed_at.format("%Y-%m-%d %H:%M")
+        );
+
+        let changed_plan =
+            budget::plan_review(changes.files.clone(), token_limit.unwrap_or(usize::MAX));
+        rereview_prompt = Some(rereview::build_prompt(
+            &changed_plan,
+            &changes,
+            previous,
+            args.prompt.as_deref(),
+        ));
+        plan = Some(changed_plan);
+    }
+
+    // Fall back to a summarized review when the diff exceeds the budget
+    if let Some(limit) = token_limit.filter(|_| rereview_prompt.is_none()) {
+        let estimated_tokens = budget::estimate_tokens(&diff);
+        if estimated_tokens > limit {
+            let review_plan = budget::plan_review(budget::split_diff(&diff), limit);
+            eprintln!(
+                "Diff is ~{} tokens (limit: {}); reviewing {} files in full and {} superficially",
+                estimated_tokens,
+                limit,
+                review_plan.full.len(),
+                review_plan.superficial.len()
+            );
+            plan = Some(review_plan);
+        }
+    }
+
     // Run codex review
-    let output = run_codex(&mode, &main_branch, args.prompt.as_deref())?;
+    let output = match (&rereview_prompt, &plan) {
+        (Some(prompt), _) => run_codex_exec(prompt)?,
+        (None, Some(plan)) => run_codex_exec(&budget::build_summarized_prompt(
+            plan,
+            args.prompt.as_deref(),
+        ))?,
+        (None, None) => run_codex(&mode, &main_branch, args.prompt.as_deref())?,
+    };
 
     // Parse output
     match parse_codex_output(&output) {
         Ok(review) => {
             println!("{}", review);
+            if let Some(key) = key {
+                let record =
+                    rereview::ReviewRecord::new(&diff, plan.as_ref(), &review, previous.as_ref());
+                history.reviews.insert(key, record);
+                if let Err(e) = history.save() {
+                    eprintln!("Warning: {}", e);
+                }
+            }
+            if let Some(notice) = plan.as_ref().and_then(budget::superficial_notice) {
+                println!("\n{}", notice);
+            }
+            if args.suggest_patches {
+                suggest_patches(&review)?;
+            }
             Ok(())
         }
         Err(e) => {
@@ -278,10 +538,12 @@ tokens used
 
         let result = parse_codex_output(input);
         assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("Could not find 'codex' section"));
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Could not find 'codex' section")
+        );
     }
 
     #[test]
@@ -293,9 +555,11 @@ tokens used
 
         let result = parse_codex_output(input);
         assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("Codex review section was empty"));
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Codex review section was empty")
+        );
     }
 }
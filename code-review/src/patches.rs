@@ -0,0 +1,389 @@
+// Patch suggestions for review findings
+//
+// After a review, findings can be sent back to codex to get unified diffs.
+// Each diff is checked with `git apply --check` before the user is offered
+// the chance to apply it to the working tree.
+
+use anyhow::{Context, Result};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// A single review finding, e.g. "- [P1] Title — path:10-20" plus its description
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub title: String,
+    pub body: String,
+}
+
+/// A suggested patch for one finding
+#[derive(Debug, Clone, PartialEq)]
+pub struct Patch {
+    /// 1-based index into the findings sent to the backend
+    pub finding: usize,
+    pub diff: String,
+}
+
+/// Extract findings from review text.
+///
+/// Findings are list items that start with a priority tag (`- [P1] ...`);
+/// indented lines that follow are treated as the finding's description.
+pub fn parse_findings(review: &str) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = Vec::new();
+    let mut in_finding = false;
+
+    for line in review.lines() {
+        let trimmed = line.trim_start();
+        if let Some(rest) = trimmed.strip_prefix("- [P")
+            && rest.chars().next().is_some_and(|c| c.is_ascii_digit())
+        {
+            findings.push(Finding {
+                title: trimmed.trim_start_matches("- ").trim().to_string(),
+                body: String::new(),
+            });
+            in_finding = true;
+            continue;
+        }
+
+        if !in_finding {
+            continue;
+        }
+
+        if line.starts_with(char::is_whitespace) && !trimmed.is_empty() {
+            let finding = findings.last_mut().expect("in_finding implies a finding");
+            if !finding.body.is_empty() {
+                finding.body.push('\n');
+            }
+            finding.body.push_str(trimmed);
+        } else if !trimmed.is_empty() {
+            in_finding = false;
+        }
+    }
+
+    findings
+}
+
+/// Parse a selection like "1,3", "2-4", or "a" into 0-based indices.
+///
+/// Returns None for invalid input; an empty selection means "none".
+pub fn parse_selection(input: &str, count: usize) -> Option<Vec<usize>> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("a") || input.eq_ignore_ascii_case("all") {
+        return Some((0..count).collect());
+    }
+
+    let mut selected = Vec::new();
+    for part in input.split([',', ' ']).filter(|p| !p.is_empty()) {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+            None => {
+                let n: usize = part.parse().ok()?;
+                (n, n)
+            }
+        };
+        if start == 0 || end > count || start > end {
+            return None;
+        }
+        for n in start..=end {
+            if !selected.contains(&(n - 1)) {
+                selected.push(n - 1);
+            }
+        }
+    }
+
+    Some(selected)
+}
+
+/// Build the prompt asking codex for one unified diff per finding
+pub fn build_patch_prompt(findings: &[&Finding]) -> String {
+    let mut prompt = String::from(
+        "You previously reviewed the changes in this repository and reported the findings below.\n\
+         For each finding, propose a minimal fix against the current working tree.\n\n\
+         Output format, for each finding:\n\
+         - A line `### Finding N` (N is the finding number below)\n\
+         - Exactly one ```diff fenced block containing a unified diff in `git diff` format, \
+         with `a/` and `b/` path prefixes relative to the repository root\n\
+         If a finding has no concrete code fix, output `### Finding N` followed by `No patch`.\n\
+         Do not modify any files yourself.\n\n\
+         FINDINGS:\n",
+    );
+
+    for (i, finding) in findings.iter().enumerate() {
+        prompt.push_str(&format!("\n{}. {}\n", i + 1, finding.title));
+        if !finding.body.is_empty() {
+            prompt.push_str(&finding.body);
+            prompt.push('\n');
+        }
+    }
+
+    prompt
+}
+
+/// Extract `### Finding N` headed ```diff blocks from the backend's response
+pub fn extract_patches(output: &str) -> Vec<Patch> {
+    let mut patches = Vec::new();
+    let mut finding = None;
+    let mut diff: Option<String> = None;
+
+    for line in output.lines() {
+        if let Some(current) = diff.as_mut() {
+            if line.trim_start().starts_with("```") {
+                if let Some(finding) = finding.take() {
+                    patches.push(Patch {
+                        finding,
+                        diff: diff.take().unwrap_or_default(),
+                    });
+                }
+                diff = None;
+            } else {
+                current.push_str(line);
+                current.push('\n');
+            }
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if let Some(n) = trimmed.strip_prefix("### Finding ") {
+            finding = n.trim().parse().ok();
+        } else if trimmed.starts_with("```diff") || trimmed.starts_with("```patch") {
+            diff = Some(String::new());
+        }
+    }
+
+    patches
+}
+
+/// Run `git apply` in `repo` with the diff on stdin
+fn git_apply(repo: &Path, diff: &str, check_only: bool) -> Result<()> {
+    let mut args = vec!["apply"];
+    if check_only {
+        args.push("--check");
+    }
+    args.push("-");
+
+    let mut child = Command::new("git")
+        .args(&args)
+        .current_dir(repo)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to execute git apply")?;
+
+    child
+        .stdin
+        .take()
+        .context("Failed to open git apply stdin")?
+        .write_all(diff.as_bytes())
+        .context("Failed to write patch to git apply")?;
+
+    let output = child.wait_with_output().context("git apply failed")?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(())
+}
+
+/// Check that a patch applies cleanly to the working tree
+pub fn check_patch(repo: &Path, diff: &str) -> Result<()> {
+    git_apply(repo, diff, true)
+}
+
+/// Apply a patch to the working tree
+pub fn apply_patch(repo: &Path, diff: &str) -> Result<()> {
+    git_apply(repo, diff, false)
+}
+
+fn prompt(message: &str) -> Result<String> {
+    eprint!("{}", message);
+    io::stderr().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+/// Ask which findings should get patches; returns an empty list to skip
+pub fn select_findings(findings: &[Finding]) -> Result<Vec<usize>> {
+    eprintln!("\nFindings:");
+    for (i, finding) in findings.iter().enumerate() {
+        eprintln!("  {}. {}", i + 1, finding.title);
+    }
+
+    loop {
+        let input =
+            prompt("\nRequest patches for (e.g. 1,3 or 2-4, 'a' for all, Enter to skip): ")?;
+        match parse_selection(&input, findings.len()) {
+            Some(selected) => return Ok(selected),
+            None => eprintln!("Invalid selection: {}", input),
+        }
+    }
+}
+
+/// Check each patch and offer to apply it, one at a time
+pub fn offer_patches(repo: &Path, patches: &[Patch], findings: &[&Finding]) -> Result<()> {
+    let mut applied = 0;
+
+    for patch in patches {
+        let title = findings
+            .get(patch.finding.wrapping_sub(1))
+            .map_or("unknown finding", |f| f.title.as_str());
+
+        eprintln!("\n=== Patch for finding {}: {}", patch.finding, title);
+
+        if let Err(e) = check_patch(repo, &patch.diff) {
+            eprintln!("Skipping: patch does not apply cleanly ({})", e);
+            continue;
+        }
+
+        println!("{}", patch.diff.trim_end());
+
+        let answer = prompt("Apply this patch? [y/N]: ")?;
+        if answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes") {
+            apply_patch(repo, &patch.diff)
+                .with_context(|| format!("Failed to apply patch for finding {}", patch.finding))?;
+            eprintln!("Applied.");
+            applied += 1;
+        }
+    }
+
+    eprintln!("\nApplied {} of {} patches", applied, patches.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REVIEW: &str = "\
+Queue daemon writes outputs based solely on the EPUB basename.
+
+Review comment:
+
+- [P1] Daemon overwrites outputs — /path/to/file.py:145-154
+  Two inputs with the same basename write to the same output.
+  Include the parent directory in the output name.
+- [P2] Missing timeout — /path/to/client.py:30
+  Requests can hang forever.
+
+Overall the change looks reasonable.
+";
+
+    #[test]
+    fn test_parse_findings() {
+        let findings = parse_findings(REVIEW);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(
+            findings[0].title,
+            "[P1] Daemon overwrites outputs — /path/to/file.py:145-154"
+        );
+        assert_eq!(
+            findings[0].body,
+            "Two inputs with the same basename write to the same output.\n\
+             Include the parent directory in the output name."
+        );
+        assert_eq!(
+            findings[1].title,
+            "[P2] Missing timeout — /path/to/client.py:30"
+        );
+        assert_eq!(findings[1].body, "Requests can hang forever.");
+    }
+
+    #[test]
+    fn test_parse_findings_none() {
+        assert!(parse_findings("Looks good to me.\n- [note] not a finding").is_empty());
+    }
+
+    #[test]
+    fn test_parse_selection() {
+        assert_eq!(parse_selection("", 3), Some(vec![]));
+        assert_eq!(parse_selection("a", 3), Some(vec![0, 1, 2]));
+        assert_eq!(parse_selection("1,3", 3), Some(vec![0, 2]));
+        assert_eq!(parse_selection("2-3 1", 3), Some(vec![1, 2, 0]));
+        assert_eq!(parse_selection("1,1", 3), Some(vec![0]));
+        assert_eq!(parse_selection("0", 3), None);
+        assert_eq!(parse_selection("4", 3), None);
+        assert_eq!(parse_selection("3-2", 3), None);
+        assert_eq!(parse_selection("x", 3), None);
+    }
+
+    #[test]
+    fn test_build_patch_prompt_numbers_findings() {
+        let findings = parse_findings(REVIEW);
+        let prompt = build_patch_prompt(&[&findings[1]]);
+        assert!(prompt.contains("### Finding N"));
+        assert!(prompt.contains("\n1. [P2] Missing timeout"));
+        assert!(prompt.contains("Requests can hang forever."));
+        assert!(!prompt.contains("Daemon overwrites"));
+    }
+
+    #[test]
+    fn test_extract_patches() {
+        let output = "\
+### Finding 1
+```diff
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1 +1 @@
+-old
++new
+```
+
+### Finding 2
+No patch
+
+### Finding 3
+```diff
+--- a/b.txt
++++ b/b.txt
+@@ -1 +1 @@
+-x
++y
+```
+";
+        let patches = extract_patches(output);
+        assert_eq!(patches.len(), 2);
+        assert_eq!(patches[0].finding, 1);
+        assert!(patches[0].diff.starts_with("diff --git a/src/lib.rs"));
+        assert!(patches[0].diff.ends_with("+new\n"));
+        assert_eq!(patches[1].finding, 3);
+    }
+
+    #[test]
+    fn test_extract_patches_ignores_unheaded_blocks() {
+        let output = "```diff\n-a\n+b\n```\n";
+        assert!(extract_patches(output).is_empty());
+    }
+
+    #[test]
+    fn test_check_and_apply_patch() {
+        let dir = std::env::temp_dir().join(format!("code-review-patch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let run = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(&dir)
+                .output()
+                .unwrap()
+        };
+        run(&["init", "-q"]);
+        std::fs::write(dir.join("a.txt"), "old\n").unwrap();
+
+        let diff = "--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-old\n+new\n";
+        let bad = "--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-missing\n+new\n";
+
+        let checked = check_patch(&dir, diff);
+        let rejected = check_patch(&dir, bad);
+        let applied = apply_patch(&dir, diff);
+
+        let content = std::fs::read_to_string(dir.join("a.txt")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(checked.is_ok());
+        assert!(rejected.is_err());
+        assert!(applied.is_ok());
+        assert_eq!(content, "new\n");
+    }
+}
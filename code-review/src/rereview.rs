@@ -0,0 +1,417 @@
+// Incremental re-review of a branch
+//
+// After each review, a fingerprint of every diff hunk is stored per branch
+// along with the review's findings. The next review of the same branch sends
+// only the hunks whose fingerprints are new, plus those findings as context,
+// instead of the whole change set.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::budget::{self, FileDiff, ReviewPlan};
+use crate::patches;
+
+/// Findings kept from earlier reviews of a branch
+const MAX_SUMMARY_FINDINGS: usize = 20;
+
+/// What was reviewed last time on one branch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewRecord {
+    pub reviewed_at: DateTime<Local>,
+    /// Fingerprints of the hunks that were reviewed in full
+    pub hunks: HashSet<String>,
+    /// Finding titles from this and earlier reviews, newest first
+    pub findings: Vec<String>,
+}
+
+impl ReviewRecord {
+    /// Record a review of `diff`, carrying forward findings from `previous`.
+    ///
+    /// Hunks of files the plan only skimmed aren't recorded, unless they were
+    /// reviewed before, so the next review sends them again.
+    pub fn new(
+        diff: &str,
+        plan: Option<&ReviewPlan>,
+        review: &str,
+        previous: Option<&ReviewRecord>,
+    ) -> Self {
+        let mut findings: Vec<String> = patches::parse_findings(review)
+            .into_iter()
+            .map(|finding| finding.title)
+            .collect();
+        for title in previous.into_iter().flat_map(|p| &p.findings) {
+            if !findings.contains(title) {
+                findings.push(title.clone());
+            }
+        }
+        findings.truncate(MAX_SUMMARY_FINDINGS);
+
+        let skimmed: HashSet<&str> = plan
+            .into_iter()
+            .flat_map(|plan| &plan.superficial)
+            .map(|file| file.path.as_str())
+            .collect();
+        let hunks = split_hunks(diff)
+            .into_iter()
+            .filter(|hunk| {
+                !skimmed.contains(hunk.path.as_str())
+                    || previous.is_some_and(|p| p.hunks.contains(&hunk.fingerprint))
+            })
+            .map(|hunk| hunk.fingerprint)
+            .collect();
+
+        Self {
+            reviewed_at: Local::now(),
+            hunks,
+            findings,
+        }
+    }
+}
+
+/// Last review of each branch, keyed by `<repo root>:<branch>:<mode>`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReviewHistory {
+    #[serde(default)]
+    pub reviews: HashMap<String, ReviewRecord>,
+}
+
+impl ReviewHistory {
+    /// Get the history file path: ~/.config/cli-programs/code-review-history.json
+    pub fn history_path() -> Result<PathBuf> {
+        let home = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE"))?;
+        Ok(PathBuf::from(home)
+            .join(".config")
+            .join("cli-programs")
+            .join("code-review-history.json"))
+    }
+
+    /// Load the history, starting fresh if the file is missing or unreadable
+    pub fn load() -> Self {
+        Self::history_path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::history_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write review history: {}", path.display()))
+    }
+}
+
+/// One hunk of a file's diff
+#[derive(Debug, Clone)]
+pub struct Hunk {
+    pub path: String,
+    /// The file's `diff --git`, index, and `---`/`+++` lines
+    pub file_header: String,
+    /// The `@@` line and the hunk body
+    pub text: String,
+    pub fingerprint: String,
+}
+
+/// Split a unified diff into hunks. Files without hunks (binary files, pure
+/// renames, and mode changes) become a single hunk with an empty body.
+pub fn split_hunks(diff: &str) -> Vec<Hunk> {
+    let mut hunks = Vec::new();
+
+    for file in budget::split_diff(diff) {
+        let mut file_header = String::new();
+        let mut bodies: Vec<String> = Vec::new();
+        for line in file.text.lines() {
+            if line.starts_with("@@") {
+                bodies.push(String::new());
+            }
+            let target = bodies.last_mut().unwrap_or(&mut file_header);
+            target.push_str(line);
+            target.push('\n');
+        }
+        if bodies.is_empty() {
+            bodies.push(String::new());
+        }
+
+        for text in bodies {
+            let fingerprint = fingerprint(&file.path, &file_header, &text);
+            hunks.push(Hunk {
+                path: file.path.clone(),
+                file_header: file_header.clone(),
+                text,
+                fingerprint,
+            });
+        }
+    }
+
+    hunks
+}
+
+/// Hash of a hunk's content. The `@@` line is left out so that hunks shifted
+/// by changes elsewhere in the file keep their fingerprint; for files without
+/// hunks the header (rename, mode, binary) is what identifies the change.
+fn fingerprint(path: &str, file_header: &str, text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(path.as_bytes());
+    hasher.update([0]);
+    match text.split_once('\n') {
+        Some((_, body)) => hasher.update(body.as_bytes()),
+        None => hasher.update(file_header.as_bytes()),
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// The part of the change set that wasn't in the last review
+#[derive(Debug)]
+pub struct Changes {
+    /// New or changed hunks, grouped back into per-file diffs
+    pub files: Vec<FileDiff>,
+    /// Files whose hunks were all reviewed before, with their hunk counts
+    pub unchanged: Vec<(String, usize)>,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn hunk_count(&self) -> usize {
+        self.files
+            .iter()
+            .map(|file| {
+                file.text
+                    .lines()
+                    .filter(|l| l.starts_with("@@"))
+                    .count()
+                    .max(1)
+            })
+            .sum()
+    }
+}
+
+/// Compare `diff` against the hunks reviewed last time
+pub fn changes_since(diff: &str, previous: &ReviewRecord) -> Changes {
+    let mut by_file: BTreeMap<String, (String, Vec<Hunk>, usize)> = BTreeMap::new();
+    let mut order = Vec::new();
+
+    for hunk in split_hunks(diff) {
+        let entry = by_file.entry(hunk.path.clone()).or_insert_with(|| {
+            order.push(hunk.path.clone());
+            (hunk.file_header.clone(), Vec::new(), 0)
+        });
+        if previous.hunks.contains(&hunk.fingerprint) {
+            entry.2 += 1;
+        } else {
+            entry.1.push(hunk);
+        }
+    }
+
+    let mut changed_diff = String::new();
+    let mut unchanged = Vec::new();
+    for path in order {
+        let (file_header, new_hunks, reviewed) = &by_file[&path];
+        if new_hunks.is_empty() {
+            unchanged.push((path, *reviewed));
+            continue;
+        }
+        changed_diff.push_str(file_header);
+        for hunk in new_hunks {
+            changed_diff.push_str(&hunk.text);
+        }
+    }
+
+    Changes {
+        files: budget::split_diff(&changed_diff),
+        unchanged,
+    }
+}
+
+/// Build the prompt for re-reviewing only what changed since `previous`
+pub fn build_prompt(
+    plan: &ReviewPlan,
+    changes: &Changes,
+    previous: &ReviewRecord,
+    user_prompt: Option<&str>,
+) -> String {
+    let mut prompt = format!(
+        "Re-review a change set that was already reviewed on {}. Only the hunks that are new \
+         or changed since that review are shown below; everything else is unchanged and was \
+         reviewed then. Review the new hunks in detail. Repeat an earlier finding only if the \
+         new hunks show it is still present, and say so when they fix one.\n\n",
+        previous.reviewed_at.format("%Y-%m-%d %H:%M")
+    );
+
+    if let Some(p) = user_prompt {
+        prompt.push_str(&format!("Additional review instructions:\n{}\n\n", p));
+    }
+
+    if !previous.findings.is_empty() {
+        prompt.push_str("## Findings from earlier reviews\n\n");
+        for finding in &previous.findings {
+            prompt.push_str(&format!("- {}\n", finding));
+        }
+        prompt.push('\n');
+    }
+
+    if !changes.unchanged.is_empty() {
+        prompt.push_str("## Files unchanged since the last review\n\n");
+        for (path, hunks) in &changes.unchanged {
+            prompt.push_str(&format!("{} ({} hunks)\n", path, hunks));
+        }
+        prompt.push('\n');
+    }
+
+    if !plan.full.is_empty() {
+        prompt.push_str("## Changed hunks\n\n");
+        for file in &plan.full {
+            prompt.push_str(&file.text);
+            prompt.push('\n');
+        }
+    }
+
+    if !plan.superficial.is_empty() {
+        prompt.push_str("## Changed files shown as skeletons only (over the review budget)\n\n");
+        for file in &plan.superficial {
+            prompt.push_str(&file.skeleton());
+        }
+    }
+
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs
+index 111..222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,2 +1,2 @@ fn first()
+-old
++new
+@@ -20,2 +20,3 @@ fn second()
+ context
++added
+diff --git a/logo.png b/logo.png
+index 333..444 100644
+Binary files a/logo.png and b/logo.png differ
+";
+
+    fn record(diff: &str) -> ReviewRecord {
+        ReviewRecord::new(
+            diff,
+            None,
+            "- [P2] Missing bounds check — src/lib.rs:3",
+            None,
+        )
+    }
+
+    #[test]
+    fn test_split_hunks() {
+        let hunks = split_hunks(DIFF);
+        assert_eq!(hunks.len(), 3);
+        assert_eq!(hunks[0].path, "src/lib.rs");
+        assert!(hunks[0].text.starts_with("@@ -1,2 +1,2 @@"));
+        assert!(hunks[1].file_header.starts_with("diff --git a/src/lib.rs"));
+        assert_eq!(hunks[2].path, "logo.png");
+        assert!(hunks[2].text.is_empty());
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_line_numbers() {
+        let shifted = DIFF.replace("@@ -20,2 +20,3 @@", "@@ -25,2 +25,3 @@");
+        let changes = changes_since(&shifted, &record(DIFF));
+        assert!(changes.is_empty());
+        assert_eq!(
+            changes.unchanged,
+            vec![("src/lib.rs".to_string(), 2), ("logo.png".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_changes_since_keeps_only_new_hunks() {
+        let edited = DIFF.replace("+added", "+added\n+more");
+        let changes = changes_since(&edited, &record(DIFF));
+
+        assert_eq!(changes.files.len(), 1);
+        assert_eq!(changes.hunk_count(), 1);
+        let text = &changes.files[0].text;
+        assert!(text.starts_with("diff --git a/src/lib.rs b/src/lib.rs\n"));
+        assert!(text.contains("+++ b/src/lib.rs\n@@ -20,2 +20,3 @@"));
+        assert!(text.contains("+more"));
+        assert!(!text.contains("+new"));
+        assert_eq!(changes.unchanged, vec![("logo.png".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_record_carries_findings_forward() {
+        let first = record(DIFF);
+        let second = ReviewRecord::new(
+            DIFF,
+            None,
+            "- [P1] Panic on empty input — src/lib.rs:21\n- [P2] Missing bounds check — src/lib.rs:3",
+            Some(&first),
+        );
+        assert_eq!(
+            second.findings,
+            vec![
+                "[P1] Panic on empty input — src/lib.rs:21",
+                "[P2] Missing bounds check — src/lib.rs:3"
+            ]
+        );
+        assert_eq!(second.hunks, first.hunks);
+    }
+
+    #[test]
+    fn test_skimmed_files_are_reviewed_again() {
+        // Budget for every skeleton plus logo.png in full, leaving src/lib.rs skimmed
+        let files = budget::split_diff(DIFF);
+        let skeletons: usize = files
+            .iter()
+            .map(|f| budget::estimate_tokens(&f.skeleton()))
+            .sum();
+        let logo = &files[1];
+        let limit = skeletons + budget::estimate_tokens(&logo.text)
+            - budget::estimate_tokens(&logo.skeleton());
+        let plan = budget::plan_review(files, limit);
+        assert_eq!(plan.superficial[0].path, "src/lib.rs");
+
+        let first = ReviewRecord::new(DIFF, Some(&plan), "", None);
+        assert_eq!(first.hunks.len(), 1);
+
+        let changes = changes_since(DIFF, &first);
+        assert_eq!(changes.files.len(), 1);
+        assert_eq!(changes.files[0].path, "src/lib.rs");
+        assert_eq!(changes.hunk_count(), 2);
+        assert_eq!(changes.unchanged, vec![("logo.png".to_string(), 1)]);
+
+        // Once the re-review covers src/lib.rs in full, nothing is left
+        let replan = budget::plan_review(changes.files.clone(), 100_000);
+        let second = ReviewRecord::new(DIFF, Some(&replan), "", Some(&first));
+        assert!(changes_since(DIFF, &second).is_empty());
+    }
+
+    #[test]
+    fn test_build_prompt() {
+        let previous = record(DIFF);
+        let edited = DIFF.replace("+added", "+added\n+more");
+        let changes = changes_since(&edited, &previous);
+        let plan = budget::plan_review(changes.files.clone(), 100_000);
+
+        let prompt = build_prompt(&plan, &changes, &previous, Some("Focus on errors"));
+        assert!(prompt.contains("Additional review instructions:\nFocus on errors"));
+        assert!(prompt.contains("- [P2] Missing bounds check"));
+        assert!(prompt.contains("## Files unchanged since the last review\n\nlogo.png (1 hunks)"));
+        assert!(prompt.contains("+more"));
+        assert!(!prompt.contains("skeletons only"));
+    }
+}
@@ -0,0 +1,215 @@
+// Release-note summaries of a commit range
+//
+// Commits since a ref are sorted into Keep a Changelog sections with the same
+// Conventional Commit mapping that changelog-validator uses to generate
+// changelogs. That draft, the commits it couldn't place, and the diff are sent
+// to codex, which writes the final notes in the same section structure.
+
+use anyhow::{Context, Result};
+use changelog_validator::{Section, VALID_SECTIONS, sections_from_commits};
+use git_conventional::Commit;
+
+use crate::budget::{self, ReviewPlan};
+use crate::git;
+
+/// Commits and changes between a ref and HEAD
+#[derive(Debug)]
+pub struct RangeSummary {
+    pub base: String,
+    pub commit_count: usize,
+    /// Entries for the conventional commits, in Keep a Changelog order
+    pub sections: Vec<Section>,
+    /// Subjects of the commits that aren't conventional
+    pub unclassified: Vec<String>,
+}
+
+impl RangeSummary {
+    /// Collect the commits in `base..HEAD`
+    pub fn load(base: &str) -> Result<Self> {
+        git(&[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{}^{{commit}}", base),
+        ])
+        .with_context(|| format!("Unknown ref '{}'", base))?;
+        let log = git(&["log", "--format=%B%x00", &format!("{}..HEAD", base)])?;
+        let messages = parse_log(&log);
+        if messages.is_empty() {
+            anyhow::bail!("No commits since {}", base);
+        }
+        Ok(Self::from_messages(base, &messages))
+    }
+
+    fn from_messages(base: &str, messages: &[String]) -> Self {
+        let unclassified = messages
+            .iter()
+            .filter(|message| Commit::parse(message).is_err())
+            .filter_map(|message| message.lines().next())
+            .map(|subject| subject.to_string())
+            .collect();
+        Self {
+            base: base.to_string(),
+            commit_count: messages.len(),
+            sections: sections_from_commits(messages),
+            unclassified,
+        }
+    }
+
+    /// The draft as Markdown, with `### Section` headings
+    pub fn draft(&self) -> String {
+        let mut out = String::new();
+        for section in &self.sections {
+            out.push_str(&format!("### {}\n", section.name));
+            for entry in &section.entries {
+                out.push_str(entry);
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Commit messages from `git log --format=%B%x00`, oldest first
+fn parse_log(log: &str) -> Vec<String> {
+    let mut messages: Vec<String> = log
+        .split('\0')
+        .map(str::trim)
+        .filter(|message| !message.is_empty())
+        .map(str::to_string)
+        .collect();
+    messages.reverse();
+    messages
+}
+
+/// Build the prompt asking for release notes, with the diff cut down to `plan`
+pub fn build_prompt(
+    summary: &RangeSummary,
+    plan: &ReviewPlan,
+    user_prompt: Option<&str>,
+) -> String {
+    let mut prompt = format!(
+        "Draft release notes for the {} commits since {}. Do not review the code.\n\n\
+         Use only these Keep a Changelog sections, in this order, and leave out empty ones: {}.\n\
+         Write each section as a `### <Section>` heading followed by `- ` entries written for \
+         users of the software. Start an entry with its scope and a colon (e.g. `- parser: ...`) \
+         when the change belongs to one, and keep entries with the same scope together.\n\n\
+         Start from the draft below, which was generated from the Conventional Commit messages. \
+         Merge duplicate or related entries, place the commits that couldn't be classified, drop \
+         changes that are purely internal, and use the diff to catch user-visible changes the \
+         commit messages don't mention. Output only the release notes.\n\n",
+        summary.commit_count,
+        summary.base,
+        VALID_SECTIONS.join(", ")
+    );
+
+    if let Some(p) = user_prompt {
+        prompt.push_str(&format!("Additional instructions:\n{}\n\n", p));
+    }
+
+    prompt.push_str("## Draft from commit messages\n\n");
+    if summary.sections.is_empty() {
+        prompt.push_str("(no commits mapped to a section)\n\n");
+    } else {
+        prompt.push_str(&summary.draft());
+    }
+
+    if !summary.unclassified.is_empty() {
+        prompt.push_str("## Commits that couldn't be classified\n\n");
+        for subject in &summary.unclassified {
+            prompt.push_str(&format!("- {}\n", subject));
+        }
+        prompt.push('\n');
+    }
+
+    if !plan.full.is_empty() {
+        prompt.push_str("## Diff\n\n");
+        for file in &plan.full {
+            prompt.push_str(&file.text);
+            prompt.push('\n');
+        }
+    }
+
+    if !plan.superficial.is_empty() {
+        prompt.push_str("## Files shown as skeletons only (over budget)\n\n");
+        for file in &plan.superficial {
+            prompt.push_str(&file.skeleton());
+        }
+    }
+
+    prompt
+}
+
+/// Split the range's diff so it fits in `limit` tokens
+pub fn plan_diff(base: &str, limit: usize) -> Result<ReviewPlan> {
+    let diff = git(&["diff", base, "HEAD"])?;
+    Ok(budget::plan_review(budget::split_diff(&diff), limit))
+}
+
+/// `### ` headings in the notes that aren't Keep a Changelog sections
+pub fn unknown_sections(notes: &str) -> Vec<&str> {
+    notes
+        .lines()
+        .filter_map(|line| line.strip_prefix("### "))
+        .map(str::trim)
+        .filter(|name| !VALID_SECTIONS.contains(name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(messages: &[&str]) -> Vec<String> {
+        messages.iter().map(|m| m.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let log = "feat: newest\n\nbody\n\0\nfix: oldest\n\0\n";
+        assert_eq!(parse_log(log), vec!["fix: oldest", "feat: newest\n\nbody"]);
+    }
+
+    #[test]
+    fn test_from_messages() {
+        let summary = RangeSummary::from_messages(
+            "v1.0.0",
+            &messages(&[
+                "feat(parser): add streaming",
+                "fix: crash on empty input",
+                "Update the logo\n\nNew colors",
+                "docs: typo",
+            ]),
+        );
+        assert_eq!(summary.commit_count, 4);
+        assert_eq!(summary.unclassified, vec!["Update the logo"]);
+        assert_eq!(
+            summary.draft(),
+            "### Added\n- parser: Add streaming\n\n### Fixed\n- Crash on empty input\n\n"
+        );
+    }
+
+    #[test]
+    fn test_build_prompt() {
+        let summary =
+            RangeSummary::from_messages("v1.0.0", &messages(&["feat: add export", "Tweak things"]));
+        let files = budget::split_diff("diff --git a/src/a.rs b/src/a.rs\n+new line\n");
+        let plan = budget::plan_review(files, usize::MAX);
+
+        let prompt = build_prompt(&summary, &plan, Some("Mention the MSRV"));
+        assert!(prompt.contains("the 2 commits since v1.0.0"));
+        assert!(prompt.contains("Added, Changed, Deprecated, Removed, Fixed, Security"));
+        assert!(prompt.contains("Additional instructions:\nMention the MSRV"));
+        assert!(prompt.contains("### Added\n- Add export\n"));
+        assert!(prompt.contains("couldn't be classified\n\n- Tweak things\n"));
+        assert!(prompt.contains("## Diff\n\ndiff --git a/src/a.rs b/src/a.rs\n+new line\n"));
+        assert!(!prompt.contains("skeletons only"));
+    }
+
+    #[test]
+    fn test_unknown_sections() {
+        let notes = "### Added\n- A\n\n### Improvements\n- B\n\n### Fixed\n- C\n";
+        assert_eq!(unknown_sections(notes), vec!["Improvements"]);
+    }
+}
@@ -0,0 +1,99 @@
+// Amending the last commit
+
+use anyhow::Result;
+
+use crate::git;
+
+/// Git's well-known empty tree, used as the diff base when amending a root commit
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// The commit being amended
+#[derive(Debug, Clone)]
+pub struct AmendTarget {
+    /// Message of the current HEAD commit
+    pub message: String,
+    /// What to diff the index against so the diff covers the previous
+    /// commit's changes plus anything newly staged
+    pub diff_base: String,
+    /// Remote branches that already contain HEAD
+    pub pushed_to: Vec<String>,
+}
+
+impl AmendTarget {
+    /// Load the HEAD commit, failing if there is nothing to amend
+    pub fn load() -> Result<Self> {
+        if git(&["rev-parse", "--verify", "--quiet", "HEAD"]).is_err() {
+            anyhow::bail!("Nothing to amend: the repository has no commits yet");
+        }
+
+        let message = git(&["log", "-1", "--format=%B"])?.trim().to_string();
+        let diff_base = git(&["rev-parse", "--verify", "--quiet", "HEAD~1"])
+            .map(|parent| parent.trim().to_string())
+            .unwrap_or_else(|_| EMPTY_TREE.to_string());
+        let pushed_to = parse_branch_list(&git(&["branch", "-r", "--contains", "HEAD"])?);
+
+        Ok(Self {
+            message,
+            diff_base,
+            pushed_to,
+        })
+    }
+
+    pub fn is_pushed(&self) -> bool {
+        !self.pushed_to.is_empty()
+    }
+
+    /// Refuse to rewrite a pushed commit unless forced
+    pub fn check_pushed(&self, force: bool) -> Result<()> {
+        if self.is_pushed() && !force {
+            anyhow::bail!(
+                "The last commit has already been pushed ({}). Amending it rewrites published history; re-run with --force to amend anyway.",
+                self.pushed_to.join(", ")
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Parse `git branch -r` output, skipping symbolic refs like `origin/HEAD -> origin/main`
+fn parse_branch_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.contains(" -> "))
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(pushed_to: &[&str]) -> AmendTarget {
+        AmendTarget {
+            message: "feat: add login".to_string(),
+            diff_base: EMPTY_TREE.to_string(),
+            pushed_to: pushed_to.iter().map(|b| b.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_branch_list() {
+        let output = "  origin/HEAD -> origin/main\n  origin/feature\n  upstream/feature\n";
+        assert_eq!(
+            parse_branch_list(output),
+            vec!["origin/feature", "upstream/feature"]
+        );
+        assert!(parse_branch_list("").is_empty());
+    }
+
+    #[test]
+    fn test_check_pushed() {
+        assert!(target(&[]).check_pushed(false).is_ok());
+        assert!(target(&["origin/feature"]).check_pushed(true).is_ok());
+
+        let err = target(&["origin/feature"]).check_pushed(false).unwrap_err();
+        assert!(err.to_string().contains("origin/feature"));
+        assert!(err.to_string().contains("--force"));
+    }
+}
@@ -5,24 +5,54 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::policy::PolicyConfig;
+
 /// Default maximum tokens for diff content before switching to summary mode
 const DEFAULT_MAX_DIFF_TOKENS: usize = 30000;
 
+/// Default width for wrapping the message body
+const DEFAULT_BODY_WIDTH: usize = 72;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GcConfig {
     /// Maximum estimated tokens for diff before prompting for context
     #[serde(default = "default_max_diff_tokens")]
     pub max_diff_tokens: usize,
+
+    /// Shell commands to run before pushing (e.g. "cargo fmt --check");
+    /// the push is skipped if any of them fails
+    #[serde(default)]
+    pub pre_push_checks: Vec<String>,
+
+    /// Commit message content policies (`[policy]`)
+    #[serde(default)]
+    pub policy: PolicyConfig,
+
+    /// Always add provenance trailers, as if `--trailer` were passed
+    #[serde(default)]
+    pub trailer: bool,
+
+    /// Wrap body lines longer than this many characters (0 disables wrapping)
+    #[serde(default = "default_body_width")]
+    pub body_width: usize,
 }
 
 fn default_max_diff_tokens() -> usize {
     DEFAULT_MAX_DIFF_TOKENS
 }
 
+fn default_body_width() -> usize {
+    DEFAULT_BODY_WIDTH
+}
+
 impl Default for GcConfig {
     fn default() -> Self {
         Self {
             max_diff_tokens: DEFAULT_MAX_DIFF_TOKENS,
+            pre_push_checks: Vec::new(),
+            policy: PolicyConfig::default(),
+            trailer: false,
+            body_width: DEFAULT_BODY_WIDTH,
         }
     }
 }
@@ -83,5 +113,42 @@ max_diff_tokens = 50000
         let toml_str = "";
         let config: GcConfig = toml::from_str(toml_str).unwrap();
         assert_eq!(config.max_diff_tokens, 30000); // default
+        assert!(config.pre_push_checks.is_empty());
+        assert!(!config.trailer);
+        assert_eq!(config.body_width, 72);
+    }
+
+    #[test]
+    fn test_parse_body_width() {
+        let config: GcConfig = toml::from_str("body_width = 0").unwrap();
+        assert_eq!(config.body_width, 0);
+    }
+
+    #[test]
+    fn test_parse_pre_push_checks() {
+        let toml_str = r#"
+pre_push_checks = ["cargo fmt --check", "cargo clippy -- -D warnings"]
+"#;
+        let config: GcConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config.pre_push_checks,
+            vec!["cargo fmt --check", "cargo clippy -- -D warnings"]
+        );
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        let toml_str = r#"
+[policy]
+emoji = false
+banned_patterns = ["(?i)wip"]
+allowed_domains = ["example.com"]
+"#;
+        let config: GcConfig = toml::from_str(toml_str).unwrap();
+        assert!(!config.policy.emoji);
+        assert!(config.policy.email && config.policy.url);
+        assert_eq!(config.policy.banned_patterns, vec!["(?i)wip"]);
+        assert_eq!(config.policy.allowed_domains, vec!["example.com"]);
+        assert!(config.policy.allowed_words.is_empty());
     }
 }
@@ -0,0 +1,263 @@
+// Commit hook detection and committing through hooks
+
+use anyhow::{Context, Result};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::git;
+
+/// Hooks that run during `git commit` and can reject or rewrite the message
+const COMMIT_HOOKS: &[&str] = &["pre-commit", "prepare-commit-msg", "commit-msg"];
+
+/// Tools that install and manage git hooks for a repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookManager {
+    Husky,
+    Lefthook,
+    PreCommit,
+}
+
+impl fmt::Display for HookManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Husky => "husky",
+            Self::Lefthook => "lefthook",
+            Self::PreCommit => "pre-commit",
+        })
+    }
+}
+
+/// Commit hooks installed in a repository
+#[derive(Debug, Clone, Default)]
+pub struct HookSetup {
+    pub managers: Vec<HookManager>,
+    /// Names of the commit hooks that will run, e.g. `commit-msg`
+    pub active: Vec<&'static str>,
+}
+
+impl HookSetup {
+    /// Detect the commit hooks for the repository in the current directory
+    pub fn detect() -> Result<Self> {
+        let root = PathBuf::from(git(&["rev-parse", "--show-toplevel"])?.trim());
+        // Honors core.hooksPath, which husky and some other managers set
+        let hooks_dir = PathBuf::from(git(&["rev-parse", "--git-path", "hooks"])?.trim());
+        Ok(Self::detect_in(&root, &hooks_dir))
+    }
+
+    pub fn detect_in(root: &Path, hooks_dir: &Path) -> Self {
+        Self {
+            managers: detect_managers(root, hooks_dir),
+            active: active_hooks(hooks_dir),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.active.is_empty()
+    }
+
+    /// One-line description such as `pre-commit, commit-msg (husky)`
+    pub fn describe(&self) -> String {
+        let hooks = self.active.join(", ");
+        if self.managers.is_empty() {
+            hooks
+        } else {
+            let managers: Vec<String> = self.managers.iter().map(|m| m.to_string()).collect();
+            format!("{} ({})", hooks, managers.join(", "))
+        }
+    }
+}
+
+fn detect_managers(root: &Path, hooks_dir: &Path) -> Vec<HookManager> {
+    let mut managers = Vec::new();
+
+    let in_husky_dir = hooks_dir.components().any(|c| c.as_os_str() == ".husky");
+    if root.join(".husky").is_dir() || in_husky_dir {
+        managers.push(HookManager::Husky);
+    }
+
+    let lefthook_configs = [
+        "lefthook.yml",
+        ".lefthook.yml",
+        "lefthook.yaml",
+        ".lefthook.yaml",
+        "lefthook.toml",
+        "lefthook.json",
+    ];
+    if lefthook_configs.iter().any(|f| root.join(f).is_file()) {
+        managers.push(HookManager::Lefthook);
+    }
+
+    if root.join(".pre-commit-config.yaml").is_file() {
+        managers.push(HookManager::PreCommit);
+    }
+
+    managers
+}
+
+fn active_hooks(hooks_dir: &Path) -> Vec<&'static str> {
+    use std::os::unix::fs::PermissionsExt;
+
+    COMMIT_HOOKS
+        .iter()
+        .copied()
+        .filter(|hook| {
+            // git skips hooks that aren't executable
+            hooks_dir
+                .join(hook)
+                .metadata()
+                .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        })
+        .collect()
+}
+
+/// Build the arguments for `git commit`
+pub fn commit_args(message: &str, no_verify: bool, amend: bool) -> Vec<String> {
+    let mut args = vec!["commit".to_string()];
+    if amend {
+        args.push("--amend".to_string());
+    }
+    if no_verify {
+        args.push("--no-verify".to_string());
+    }
+    args.push("-m".to_string());
+    args.push(message.to_string());
+    args
+}
+
+/// Commit staged changes, passing hook output through on failure.
+///
+/// Hooks report problems on stdout as often as stderr, so both are included
+/// in the error.
+pub fn commit(message: &str, no_verify: bool, amend: bool) -> Result<()> {
+    let output = Command::new("git")
+        .args(commit_args(message, no_verify, amend))
+        .output()
+        .context("Failed to execute git command")?;
+
+    if !output.status.success() {
+        let mut details = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !stdout.trim().is_empty() {
+            if !details.is_empty() {
+                details.push('\n');
+            }
+            details.push_str(stdout.trim());
+        }
+        anyhow::bail!("git commit failed:\n{}", details);
+    }
+
+    Ok(())
+}
+
+/// Normalize a message the way `git commit` cleans it up: comment lines and
+/// trailing whitespace removed, surrounding blank lines trimmed
+fn normalize(message: &str) -> String {
+    message
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// The committed message if it differs from the one gc wrote, e.g. because a
+/// `commit-msg` or `prepare-commit-msg` hook rewrote it
+pub fn rewritten_message(sent: &str, committed: &str) -> Option<String> {
+    let committed = normalize(committed);
+    (committed != normalize(sent)).then_some(committed)
+}
+
+/// Full message of the HEAD commit
+pub fn head_message() -> Result<String> {
+    git(&["log", "-1", "--format=%B"])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write_hook(dir: &Path, name: &str, mode: u32) {
+        use std::os::unix::fs::PermissionsExt;
+
+        fs::create_dir_all(dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[test]
+    fn test_detect_husky() {
+        let root = TempDir::new().unwrap();
+        let hooks_dir = root.path().join(".husky/_");
+        write_hook(&hooks_dir, "commit-msg", 0o755);
+        write_hook(&hooks_dir, "pre-commit", 0o755);
+
+        let setup = HookSetup::detect_in(root.path(), &hooks_dir);
+        assert_eq!(setup.managers, vec![HookManager::Husky]);
+        assert_eq!(setup.active, vec!["pre-commit", "commit-msg"]);
+        assert_eq!(setup.describe(), "pre-commit, commit-msg (husky)");
+    }
+
+    #[test]
+    fn test_detect_lefthook_and_pre_commit() {
+        let root = TempDir::new().unwrap();
+        fs::write(root.path().join("lefthook.yml"), "").unwrap();
+        fs::write(root.path().join(".pre-commit-config.yaml"), "").unwrap();
+        let hooks_dir = root.path().join(".git/hooks");
+        write_hook(&hooks_dir, "commit-msg", 0o755);
+        // Sample hooks and non-executable files don't run
+        write_hook(&hooks_dir, "pre-commit.sample", 0o755);
+        write_hook(&hooks_dir, "prepare-commit-msg", 0o644);
+
+        let setup = HookSetup::detect_in(root.path(), &hooks_dir);
+        assert_eq!(
+            setup.managers,
+            vec![HookManager::Lefthook, HookManager::PreCommit]
+        );
+        assert_eq!(setup.active, vec!["commit-msg"]);
+    }
+
+    #[test]
+    fn test_detect_no_hooks() {
+        let root = TempDir::new().unwrap();
+        let setup = HookSetup::detect_in(root.path(), &root.path().join(".git/hooks"));
+        assert!(setup.is_empty());
+        assert!(setup.managers.is_empty());
+    }
+
+    #[test]
+    fn test_commit_args() {
+        assert_eq!(
+            commit_args("feat: x", false, false),
+            vec!["commit", "-m", "feat: x"]
+        );
+        assert_eq!(
+            commit_args("feat: x", true, false),
+            vec!["commit", "--no-verify", "-m", "feat: x"]
+        );
+        assert_eq!(
+            commit_args("feat: x", false, true),
+            vec!["commit", "--amend", "-m", "feat: x"]
+        );
+    }
+
+    #[test]
+    fn test_rewritten_message() {
+        let sent = "feat: add login\n\nBody text  \n";
+        assert_eq!(
+            rewritten_message(sent, "feat: add login\n\nBody text\n\n"),
+            None
+        );
+
+        let rewritten = rewritten_message(sent, "feat: add login\n\nBody text\n\nRefs: ABC-12\n");
+        assert_eq!(
+            rewritten.as_deref(),
+            Some("feat: add login\n\nBody text\n\nRefs: ABC-12")
+        );
+    }
+}
@@ -4,33 +4,44 @@
 //! automatic fallback support.
 
 use anyhow::{Context, Result};
-use llm_client::{Config, FallbackProvider, LlmError, LlmProvider, LlmRequest, get_provider_with_fallback};
+use llm_client::{Config, LlmError, LlmProvider, LlmRequest, PromptTemplates, ResponseFilters, get_provider_with_fallback, profiles};
+use std::sync::Mutex;
 use std::time::Duration;
 
+use crate::prompts;
+
 /// Constants for retry logic
 const MAX_RETRIES: u32 = 3;
 const INITIAL_BACKOFF_MS: u64 = 1000;
 
 /// Wrapper around LLM providers for gc
 pub struct LlmClient {
-    provider: FallbackProvider,
+    provider: Box<dyn LlmProvider>,
     debug: bool,
+    /// Model that answered the most recent request
+    last_model: Mutex<Option<String>>,
+    /// gc's prompts, with the user's overrides applied
+    templates: PromptTemplates,
 }
 
 impl LlmClient {
     /// Create a new LLM client with fallback chain support
     ///
-    /// If preset_name is None, uses the default preset from config.
+    /// If preset_name is None, uses gc's default preset from config, or else
+    /// the preset serving the cheap-fast profile.
     /// The fallback chain is automatically built from the preset's `fallback` field.
     pub fn new(preset_name: Option<&str>, debug: bool) -> Result<Self> {
         let config = Config::load().context("Failed to load LLM configuration")?;
 
-        let preset_name = preset_name
-            .map(String::from)
-            .unwrap_or_else(|| config.get_default_for_program("gc").to_string());
-
-        let provider = get_provider_with_fallback(&config, &preset_name)
-            .context(format!("Failed to initialize provider chain for preset '{}'", preset_name))?
+        let provider = match preset_name.or(config.defaults.get("gc").map(String::as_str)) {
+            Some(preset_name) => get_provider_with_fallback(&config, preset_name)
+                .context(format!("Failed to initialize provider chain for preset '{}'", preset_name))?,
+            None => config.for_profile(profiles::CHEAP_FAST).context(format!(
+                "Failed to initialize provider chain for profile '{}'",
+                profiles::CHEAP_FAST
+            ))?,
+        };
+        let provider = provider
             .with_debug(debug)
             .with_fallback_callback(|next_name| {
                 println!("... falling back to \"{}\"", next_name);
@@ -44,7 +55,23 @@ impl LlmClient {
             );
         }
 
-        Ok(Self { provider, debug })
+        let templates = prompts::defaults()
+            .load_overrides(&config)
+            .context("Failed to load prompt templates")?;
+
+        Ok(Self {
+            provider: Box::new(provider),
+            debug,
+            last_model: Mutex::new(None),
+            templates,
+        })
+    }
+
+    /// Check every response against `filters`, failing requests whose
+    /// response breaks them with `LlmError::ResponseRejected`
+    pub fn with_filters(mut self, filters: ResponseFilters) -> Self {
+        self.provider = llm_client::with_filters(self.provider, filters);
+        self
     }
 
     /// Send a completion request to the LLM with retry logic
@@ -71,6 +98,11 @@ impl LlmClient {
             match self.provider.complete(request.clone()).await {
                 Ok(response) => {
                     if self.debug {
+                        eprintln!(
+                            "Model: {} ({:.1}s)",
+                            response.model,
+                            response.latency.as_secs_f64()
+                        );
                         if let Some(usage) = &response.usage {
                             eprintln!(
                                 "Tokens: {} in, {} out",
@@ -78,6 +110,15 @@ impl LlmClient {
                             );
                         }
                     }
+                    if response.is_truncated()
+                        && let Some(reason) = &response.finish_reason
+                    {
+                        eprintln!(
+                            "Warning: response from {} was cut off ({}); output may be incomplete",
+                            response.model, reason
+                        );
+                    }
+                    *self.last_model.lock().unwrap() = Some(response.model);
                     return Ok(response.content);
                 }
                 Err(LlmError::ServerOverloaded { ref message }) => {
@@ -95,8 +136,15 @@ impl LlmClient {
                         tokio::time::sleep(backoff).await;
                     }
                 }
+                Err(e @ LlmError::ResponseRejected { .. }) => {
+                    // The caller asks the model to fix the response
+                    return Err(e.into());
+                }
                 Err(e) => {
                     // Non-retryable error (fallback already attempted by FallbackProvider)
+                    if let Some(hint) = e.remediation() {
+                        eprintln!("Hint: {}", hint);
+                    }
                     return Err(e).context("LLM request failed");
                 }
             }
@@ -115,12 +163,24 @@ impl LlmClient {
         self.provider.name()
     }
 
+    /// Prompt templates to render gc's prompts from
+    pub fn templates(&self) -> &PromptTemplates {
+        &self.templates
+    }
+
+    /// Model that answered the most recent successful request, as reported by the provider
+    pub fn last_model(&self) -> Option<String> {
+        self.last_model.lock().unwrap().clone()
+    }
+
     /// Create an LlmClient with an injected provider (for testing)
     #[cfg(test)]
-    pub fn with_provider(provider: FallbackProvider) -> Self {
+    pub fn with_provider(provider: llm_client::FallbackProvider) -> Self {
         Self {
-            provider,
+            provider: Box::new(provider),
             debug: false,
+            last_model: Mutex::new(None),
+            templates: prompts::defaults(),
         }
     }
 }
@@ -128,7 +188,7 @@ impl LlmClient {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use llm_client::MockProvider;
+    use llm_client::{DenyListFilter, FallbackProvider, MockProvider};
 
     #[tokio::test]
     async fn retries_on_server_overloaded_then_succeeds() {
@@ -153,6 +213,7 @@ mod tests {
         let result = client.complete("prompt", "system").await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "feat: add tests");
+        assert_eq!(client.last_model().as_deref(), Some("mock-model"));
     }
 
     #[tokio::test]
@@ -196,4 +257,25 @@ mod tests {
         let err = result.unwrap_err().to_string();
         assert!(err.contains("failed after 3 retries"));
     }
+
+    #[tokio::test]
+    async fn filters_reject_responses() {
+        let provider = MockProvider::always_succeeds("feat: add forbidden thing");
+
+        let chain = vec![(
+            "test".to_string(),
+            Box::new(provider) as Box<dyn LlmProvider>,
+        )];
+        let fallback = FallbackProvider::from_chain(chain);
+        let client = LlmClient::with_provider(fallback)
+            .with_filters(ResponseFilters::new().with_filter(DenyListFilter::new(&["forbidden"])));
+
+        let err = client.complete("prompt", "system").await.unwrap_err();
+        match err.downcast_ref::<LlmError>() {
+            Some(LlmError::ResponseRejected { content, .. }) => {
+                assert_eq!(content, "feat: add forbidden thing");
+            }
+            other => panic!("expected ResponseRejected, got {:?}", other),
+        }
+    }
 }
@@ -1,23 +1,32 @@
 // gc - Git commit with AI-generated conventional commit messages
 
+mod amend;
 mod config;
+mod hooks;
 mod llm;
+mod policy;
+mod preflight;
 mod prompts;
+mod push;
+mod split;
+mod symbols;
+mod template;
+mod trailers;
+mod wrap;
 
-use addr::parse_domain_name;
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use config::GcConfig;
-use email_address::EmailAddress;
 use git_conventional::Commit;
 use git2::Repository;
 use llm::LlmClient;
-use llm_client::{Config, ModelPreset};
+use llm_client::{Config, LlmError, ModelPreset, ResponseFilters};
+use policy::Policy;
+use preflight::Preflight;
 use std::collections::HashSet;
 use std::path::Path;
 use std::process::Command;
-use unicode_segmentation::UnicodeSegmentation;
-use url::Url;
+use template::CommitTemplate;
 
 #[derive(Parser, Debug)]
 #[command(name = "gc")]
@@ -36,6 +45,42 @@ struct Args {
     #[arg(long, default_value_t = false)]
     nopush: bool,
 
+    /// Push with --force-with-lease (for rebased branches)
+    #[arg(long, default_value_t = false)]
+    force_with_lease: bool,
+
+    /// Set the upstream to origin/<branch> without asking when the branch has none
+    #[arg(long, default_value_t = false)]
+    set_upstream: bool,
+
+    /// Skip the pre-push checks configured in gc.toml
+    #[arg(long, default_value_t = false)]
+    skip_checks: bool,
+
+    /// Skip pre-commit and commit-msg hooks (passed to git commit)
+    #[arg(long, default_value_t = false)]
+    no_verify: bool,
+
+    /// Amend the last commit, regenerating its message from its changes plus newly staged ones
+    #[arg(long, default_value_t = false)]
+    amend: bool,
+
+    /// Amend even if the last commit has already been pushed
+    #[arg(long, default_value_t = false, requires = "amend")]
+    force: bool,
+
+    /// Propose splitting unrelated changes into several commits, confirming the plan first
+    #[arg(long, default_value_t = false, conflicts_with = "amend")]
+    split: bool,
+
+    /// Show a summary of the staged changes and stop before calling the LLM
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+
+    /// Append Generated-by and Model trailers recording how the message was written
+    #[arg(long, default_value_t = false)]
+    trailer: bool,
+
     /// Additional context to include in the prompt
     #[arg(short, long)]
     context: Option<String>,
@@ -131,10 +176,12 @@ fn is_git_repo() -> bool {
 }
 
 /// Get staged diff with specific formatting flags (matches gc.sh behavior)
-fn get_staged_diff() -> Result<String> {
+///
+/// `base` diffs the index against that commit instead of HEAD (used when amending).
+fn get_staged_diff(base: Option<&str>) -> Result<String> {
     // Matches: git diff -U1 --staged --no-color --no-prefix --minimal --ignore-all-space --ignore-blank-lines
     // Then filters out: lines starting with "index", "---", "+++"
-    let diff = git(&[
+    let mut args = vec![
         "diff",
         "-U1",
         "--staged",
@@ -143,7 +190,9 @@ fn get_staged_diff() -> Result<String> {
         "--minimal",
         "--ignore-all-space",
         "--ignore-blank-lines",
-    ])?;
+    ];
+    args.extend(base);
+    let diff = git(&args)?;
 
     // Filter out metadata lines
     let filtered: Vec<&str> = diff
@@ -156,9 +205,11 @@ fn get_staged_diff() -> Result<String> {
     Ok(filtered.join("\n"))
 }
 
-/// Get file status for staged changes
-fn get_name_status() -> Result<String> {
-    git(&["diff", "--staged", "--name-status", "--no-color"])
+/// Get file status for staged changes, relative to `base` if given
+fn get_name_status(base: Option<&str>) -> Result<String> {
+    let mut args = vec!["diff", "--staged", "--name-status", "--no-color"];
+    args.extend(base);
+    git(&args)
 }
 
 fn get_status() -> Result<String> {
@@ -223,16 +274,6 @@ fn stage_all_changes() -> Result<()> {
     Ok(())
 }
 
-fn commit(message: &str) -> Result<()> {
-    git(&["commit", "-m", message])?;
-    Ok(())
-}
-
-fn push() -> Result<()> {
-    git(&["push"])?;
-    Ok(())
-}
-
 /// Extract filenames from deleted and renamed files in git name-status output.
 /// This parses output from `git diff --staged --name-status` to find:
 /// - Deleted files (status "D")
@@ -288,7 +329,7 @@ fn get_repo_filenames() -> Result<HashSet<String>> {
     // Also include deleted files and old names from renames in staged changes.
     // This prevents false URL detection when commit messages reference files
     // that were deleted or renamed.
-    if let Ok(name_status) = get_name_status() {
+    if let Ok(name_status) = get_name_status(None) {
         filenames.extend(extract_deleted_and_renamed_filenames(&name_status));
     }
 
@@ -323,6 +364,9 @@ fn prompt_for_large_commit_context(file_count: usize, estimated_tokens: usize) -
 // LLM interaction functions
 const MAX_RETRIES: usize = 3;
 
+/// Longest subject line gc accepts, in characters
+const MAX_SUBJECT_LENGTH: usize = 72;
+
 /// Parse LLM response into structured format
 /// Expected format:
 /// <observations>
@@ -381,7 +425,20 @@ async fn generate_commit_message(
             eprintln!("Attempt {}/{}", attempts, MAX_RETRIES);
         }
 
-        let response = llm.complete(prompt, system_prompt).await?;
+        let response = match llm.complete(prompt, system_prompt).await {
+            Ok(response) => response,
+            Err(e) => match rejected_response(&e) {
+                Some((violations, _)) if attempts < MAX_RETRIES => {
+                    if debug {
+                        eprintln!("Response rejected: {}, retrying...", violations.join(", "));
+                    }
+                    continue;
+                }
+                // The cleaning loop in generate_message asks the model to fix it
+                Some((_, content)) => content.to_string(),
+                None => return Err(e),
+            },
+        };
 
         if debug {
             eprintln!("Raw response:\n{}", response);
@@ -406,6 +463,41 @@ async fn generate_commit_message(
     }
 }
 
+/// The violations and content of a response the policy filter rejected, if
+/// that's why a request failed
+fn rejected_response(error: &anyhow::Error) -> Option<(&[String], &str)> {
+    match error.downcast_ref::<LlmError>()? {
+        LlmError::ResponseRejected {
+            violations,
+            content,
+        } => Some((violations, content)),
+        _ => None,
+    }
+}
+
+/// Complete a prompt that returns a commit message. A response the policy
+/// filter rejected is returned anyway, for the cleaning loop in
+/// generate_message to fix.
+async fn complete_message(
+    llm: &LlmClient,
+    prompt: &str,
+    system_prompt: &str,
+    debug: bool,
+) -> Result<String> {
+    match llm.complete(prompt, system_prompt).await {
+        Ok(response) => Ok(response),
+        Err(e) => match rejected_response(&e) {
+            Some((violations, content)) => {
+                if debug {
+                    eprintln!("Response rejected: {}", violations.join(", "));
+                }
+                Ok(content.to_string())
+            }
+            None => Err(e),
+        },
+    }
+}
+
 /// Request LLM to fix commit message issues
 async fn fix_commit_message(
     llm: &LlmClient,
@@ -414,83 +506,86 @@ async fn fix_commit_message(
     system_prompt: &str,
     debug: bool,
 ) -> Result<LlmResponse> {
-    let fix_prompt = prompts::fix_message_format(original_prompt, previous_response);
+    let fix_prompt =
+        prompts::fix_message_format(llm.templates(), original_prompt, previous_response)?;
     generate_commit_message(llm, &fix_prompt, system_prompt, debug).await
 }
 
-/// Request LLM to clean policy violations from message
-async fn clean_commit_message(
+/// Request LLM to shorten a subject line that is over the length limit
+async fn shorten_subject(
     llm: &LlmClient,
     message: &str,
     system_prompt: &str,
     debug: bool,
-) -> Result<LlmResponse> {
-    let clean_prompt = prompts::fix_message_content(message);
+) -> Result<String> {
+    let shorten_prompt = prompts::shorten_subject(llm.templates(), message, MAX_SUBJECT_LENGTH)?;
 
     if debug {
-        eprintln!("Cleaning prompt:\n{}", clean_prompt);
+        eprintln!("Shorten prompt:\n{}", shorten_prompt);
     }
 
-    let response = llm.complete(&clean_prompt, system_prompt).await?;
+    let response = complete_message(llm, &shorten_prompt, system_prompt, debug).await?;
 
     if debug {
-        eprintln!("Clean response:\n{}", response);
+        eprintln!("Shorten response:\n{}", response);
     }
 
-    let cleaned_message = response.trim().to_string();
-
-    Ok(LlmResponse {
-        message: cleaned_message.clone(),
-        raw_response: response,
-    })
+    Ok(response.trim().to_string())
 }
 
-// Validation functions
-/// Check for policy violations in commit message
-fn check_policy_violations(message: &str) -> Vec<String> {
-    let mut violations = Vec::new();
+/// Request LLM to add the sections and trailers the commit template requires
+async fn follow_template(
+    llm: &LlmClient,
+    message: &str,
+    template: &CommitTemplate,
+    problems: &[String],
+    debug: bool,
+) -> Result<String> {
+    let template_prompt =
+        prompts::follow_template(llm.templates(), message, &template.instructions(), problems)?;
 
-    if message.split_whitespace().any(EmailAddress::is_valid) {
-        violations.push("Contains email address".to_string());
+    if debug {
+        eprintln!("Template prompt:\n{}", template_prompt);
     }
 
-    let repo_filenames = get_repo_filenames().unwrap_or_default();
+    let response = complete_message(llm, &template_prompt, &prompts::SYSTEM_PROMPT, debug).await?;
 
-    if message.split_whitespace().any(|word| {
-        // Strip trailing period (end of sentence punctuation)
-        let word = word.strip_suffix('.').unwrap_or(word);
-
-        // Skip if word is an exact match for a filename in the repo
-        if repo_filenames.contains(word) {
-            return false;
-        }
+    if debug {
+        eprintln!("Template response:\n{}", response);
+    }
 
-        if let Ok(url) = Url::parse(word) {
-            return url.has_host();
-        }
+    Ok(response.trim().to_string())
+}
 
-        if word.contains('.') {
-            if let Ok(domain) = parse_domain_name(word) {
-                return domain.has_known_suffix();
-            }
-        }
+/// Request LLM to clean policy violations from message
+async fn clean_commit_message(
+    llm: &LlmClient,
+    message: &str,
+    violations: &[String],
+    system_prompt: &str,
+    debug: bool,
+) -> Result<LlmResponse> {
+    let clean_prompt = prompts::fix_message_content(llm.templates(), message, violations)?;
 
-        false
-    }) {
-        violations.push("Contains URL".to_string());
+    if debug {
+        eprintln!("Cleaning prompt:\n{}", clean_prompt);
     }
 
-    let has_emoji = message
-        .graphemes(true)
-        .any(|grapheme| emojis::get(grapheme).is_some());
+    let response = complete_message(llm, &clean_prompt, system_prompt, debug).await?;
 
-    if has_emoji {
-        violations.push("Contains emoji characters".to_string());
+    if debug {
+        eprintln!("Clean response:\n{}", response);
     }
 
-    violations
+    let cleaned_message = response.trim().to_string();
+
+    Ok(LlmResponse {
+        message: cleaned_message.clone(),
+        raw_response: response,
+    })
 }
 
+// Validation functions
 /// Validate conventional commit format using git-conventional crate
 fn validate_conventional_commit(message: &str) -> ValidationResult {
     match Commit::parse(message) {
@@ -501,14 +596,49 @@ fn validate_conventional_commit(message: &str) -> ValidationResult {
     }
 }
 
+/// Validate that the subject line fits within MAX_SUBJECT_LENGTH characters
+fn validate_subject_length(message: &str) -> ValidationResult {
+    let length = message.lines().next().unwrap_or_default().chars().count();
+    if length <= MAX_SUBJECT_LENGTH {
+        ValidationResult::Valid
+    } else {
+        ValidationResult::Invalid(vec![format!(
+            "Subject line is {} characters (limit is {})",
+            length, MAX_SUBJECT_LENGTH
+        )])
+    }
+}
+
+/// Run every message format check: Conventional Commits format and subject length
+fn validate_commit_message(message: &str) -> ValidationResult {
+    let errors: Vec<String> = [
+        validate_conventional_commit(message),
+        validate_subject_length(message),
+    ]
+    .iter()
+    .flat_map(ValidationResult::errors)
+    .collect();
+
+    if errors.is_empty() {
+        ValidationResult::Valid
+    } else {
+        ValidationResult::Invalid(errors)
+    }
+}
+
+/// Problems with a message that a commit hook rewrote after gc validated it
+fn validate_rewritten_message(message: &str, policy: &Policy) -> Vec<String> {
+    let mut problems = validate_commit_message(message).errors();
+    problems.extend(policy.check_committed(message));
+    problems
+}
+
 /// Handle config subcommands
 fn handle_config_command(action: &ConfigAction) -> Result<()> {
     match action {
         ConfigAction::SetDefault { preset } => {
             let mut config = Config::load()?;
-            // Verify preset exists
-            config.get_preset(preset)?;
-            config.defaults.insert("gc".to_string(), preset.clone());
+            config.set_default(preset, Some("gc"))?;
             config.save()?;
             println!("Default preset for gc set to: {}", preset);
         }
@@ -516,16 +646,8 @@ fn handle_config_command(action: &ConfigAction) -> Result<()> {
             let config = Config::load()?;
             let current_default = config.get_default_for_program("gc");
             println!("Available presets:");
-            for (name, preset) in &config.presets {
-                let default_marker = if name == current_default {
-                    " (default)"
-                } else {
-                    ""
-                };
-                println!(
-                    "  {} - {} / {}{}",
-                    name, preset.provider, preset.model, default_marker
-                );
+            for (name, preset) in config.sorted_presets() {
+                println!("  {}", preset.describe(name, name == current_default));
             }
         }
         ConfigAction::Show => {
@@ -541,17 +663,13 @@ fn handle_config_command(action: &ConfigAction) -> Result<()> {
             model,
         } => {
             let mut config = Config::load()?;
-            config.presets.insert(
-                name.clone(),
-                ModelPreset {
-                    provider: provider.clone(),
-                    model: model.clone(),
-                    fallback: None,
-                    api_key_env: None,
-                },
-            );
+            let replaced = config.add_preset(name, ModelPreset::new(provider, model))?;
             config.save()?;
-            println!("Added preset: {}", name);
+            if replaced {
+                println!("Replaced preset: {}", name);
+            } else {
+                println!("Added preset: {}", name);
+            }
         }
     }
     Ok(())
@@ -575,26 +693,38 @@ async fn main() -> Result<()> {
     // Load gc-specific config
     let gc_config = GcConfig::load().context("Failed to load gc config")?;
 
+    let amend_target = if args.amend {
+        let target = amend::AmendTarget::load()?;
+        target.check_pushed(args.force)?;
+        Some(target)
+    } else {
+        None
+    };
+
     // Initialize LLM client with selected preset
     let llm = LlmClient::new(args.model.as_deref(), args.debug)?;
 
-    // Check for changes and stage if needed
+    // Check for changes and stage if needed. Amending works without new
+    // changes, in which case only the message is regenerated.
     if args.staged {
         // Staged-only mode: check for already staged changes
-        let staged_files = get_name_status().context("Failed to check staged changes")?;
+        let staged_files = get_name_status(None).context("Failed to check staged changes")?;
 
-        if staged_files.trim().is_empty() {
+        if !staged_files.trim().is_empty() {
+            println!("Found staged changes, proceeding with commit");
+        } else if amend_target.is_some() {
+            println!("No staged changes, regenerating the last commit's message");
+        } else {
             println!(
                 "No staged changes detected. Use 'git add' to stage files first, or run without --staged to auto-stage all changes."
             );
             return Ok(());
         }
-        println!("Found staged changes, proceeding with commit");
     } else {
         // Normal mode: check for any changes, then stage all
         let status = get_status().context("Failed to check git status")?;
 
-        if status.trim().is_empty() {
+        if status.trim().is_empty() && amend_target.is_none() {
             println!("No changes detected.");
             return Ok(());
         }
@@ -602,9 +732,9 @@ async fn main() -> Result<()> {
         stage_all_changes().context("Failed to stage changes")?;
 
         // Verify we have staged changes after adding
-        let staged_files = get_name_status().context("Failed to verify staged changes")?;
+        let staged_files = get_name_status(None).context("Failed to verify staged changes")?;
 
-        if staged_files.trim().is_empty() {
+        if staged_files.trim().is_empty() && amend_target.is_none() {
             println!(
                 "No changes staged for commit (perhaps only untracked files were added and git config ignores them?)."
             );
@@ -612,16 +742,72 @@ async fn main() -> Result<()> {
         }
     }
 
+    // Built after staging so deleted and renamed files count as repo filenames
+    let policy = Policy::new(&gc_config.policy, get_repo_filenames().unwrap_or_default())?;
+    let llm = llm.with_filters(ResponseFilters::new().with_filter(policy.clone()));
+
     // Determine mode reference for user feedback
     let mode_ref = if args.staged {
         "staged changes"
     } else {
         "all changes"
     };
-    println!("Gathering context for {}", mode_ref);
+    if amend_target.is_some() {
+        println!("Gathering context for the last commit and {}", mode_ref);
+    } else {
+        println!("Gathering context for {}", mode_ref);
+    }
+
+    if args.dry_run {
+        let diff_base = amend_target.as_ref().map(|t| t.diff_base.as_str());
+        let git_diff = get_staged_diff(diff_base).context("Failed to get git diff")?;
+        let preflight = Preflight::load(
+            diff_base,
+            estimate_tokens(&git_diff),
+            gc_config.max_diff_tokens,
+        )
+        .context("Failed to summarize staged changes")?;
+        print!("{}", preflight.format());
+        println!("Dry run, nothing committed.");
+        return Ok(());
+    }
+
+    if args.split {
+        return run_split(&llm, &args, &gc_config, &policy).await;
+    }
+
+    let commit_message = generate_message(
+        &llm,
+        &args,
+        &gc_config,
+        &policy,
+        amend_target.as_ref(),
+        None,
+    )
+    .await?;
+
+    if !commit_with_hooks(&commit_message, &args, &policy)? {
+        return Ok(());
+    }
+
+    push_commits(&args, &gc_config, amend_target.as_ref())
+}
 
-    let git_diff = get_staged_diff().context("Failed to get git diff")?;
-    let git_name_status = get_name_status().context("Failed to get file status")?;
+/// Gather context for the staged changes, generate a commit message, and
+/// validate it against the conventional commit format and message policies
+///
+/// `extra_context` is added to the prompt after the user's context.
+async fn generate_message(
+    llm: &LlmClient,
+    args: &Args,
+    gc_config: &GcConfig,
+    policy: &Policy,
+    amend_target: Option<&amend::AmendTarget>,
+    extra_context: Option<&str>,
+) -> Result<String> {
+    let diff_base = amend_target.map(|t| t.diff_base.as_str());
+    let git_diff = get_staged_diff(diff_base).context("Failed to get git diff")?;
+    let git_name_status = get_name_status(diff_base).context("Failed to get file status")?;
     let current_branch = get_current_branch().context("Failed to get current branch")?;
     let main_branch = get_main_branch().context("Failed to determine main branch")?;
     let branch_commits = get_branch_commits(&current_branch, &main_branch)
@@ -633,12 +819,9 @@ async fn main() -> Result<()> {
     let use_summary_mode = estimated_tokens > gc_config.max_diff_tokens;
 
     if args.debug {
-        eprintln!(
-            "Diff size: {} chars, ~{} tokens (limit: {})",
-            git_diff.len(),
-            estimated_tokens,
-            gc_config.max_diff_tokens
-        );
+        let preflight = Preflight::load(diff_base, estimated_tokens, gc_config.max_diff_tokens)
+            .context("Failed to summarize staged changes")?;
+        eprint!("{}", preflight.format());
     }
 
     // Determine if user already provided context
@@ -654,6 +837,20 @@ async fn main() -> Result<()> {
         None
     };
 
+    // Only templates with sections or trailers to check are worth following
+    let template = CommitTemplate::load()?.filter(CommitTemplate::has_requirements);
+    if args.debug && template.is_some() {
+        eprintln!("Following the commit template from commit.template");
+    }
+
+    let changed_symbols =
+        symbols::format_changed_symbols(&symbols::extract_changed_symbols(&git_diff));
+    if args.debug
+        && let Some(changed_symbols) = &changed_symbols
+    {
+        eprint!("{}", changed_symbols);
+    }
+
     // Build context string
     let mut context = String::new();
 
@@ -679,6 +876,21 @@ async fn main() -> Result<()> {
         ));
     }
 
+    if let Some(target) = amend_target {
+        context.push_str(&format!(
+            "The last commit is being amended and needs a new message. Its current message is:\n{}\n\nThe changes below include that commit's changes plus any newly staged changes.\n\n---\n\n",
+            target.message
+        ));
+    }
+
+    if let Some(extra_context) = extra_context {
+        context.push_str(&format!("{}\n\n---\n\n", extra_context));
+    }
+
+    if let Some(template) = &template {
+        context.push_str(&format!("{}\n---\n\n", template.instructions()));
+    }
+
     context.push_str(&format!(
         "Current branch: {}\n\nCommits in {} since branching from {}:\n{}\n\n",
         current_branch, current_branch, main_branch, branch_commits
@@ -690,114 +902,302 @@ async fn main() -> Result<()> {
             "Changed files ({} files, diff too large to include):\n{}",
             file_count, git_name_status
         ));
+        if let Some(changed_symbols) = &changed_symbols {
+            context.push_str(&format!("\n\n{}", changed_symbols));
+        }
         if args.debug {
             eprintln!("Using summary mode (diff too large)");
         }
     } else {
         // Normal mode: include full diff
-        context.push_str(&format!(
-            "Changed files:\n{}\n\nStaged changes:\n{}",
-            git_name_status, git_diff
-        ));
+        context.push_str(&format!("Changed files:\n{}\n\n", git_name_status));
+        if let Some(changed_symbols) = &changed_symbols {
+            context.push_str(&format!("{}\n", changed_symbols));
+        }
+        context.push_str(&format!("Staged changes:\n{}", git_diff));
     }
 
     println!("Generating commit message with {}", llm.provider_name());
 
     // Generate commit message using appropriate prompt
     let prompt = if use_summary_mode {
-        prompts::generate_commit_prompt_summary_mode(&context)
+        prompts::generate_commit_prompt_summary_mode(llm.templates(), &context)?
     } else {
-        prompts::generate_commit_prompt(&context)
+        prompts::generate_commit_prompt(llm.templates(), &context)?
     };
 
     let mut llm_response =
-        generate_commit_message(&llm, &prompt, &prompts::SYSTEM_PROMPT, args.debug)
+        generate_commit_message(llm, &prompt, &prompts::SYSTEM_PROMPT, args.debug)
             .await
             .context("Failed to generate commit message")?;
 
     let mut commit_message = llm_response.message.clone();
 
-    let format_validation = validate_conventional_commit(&commit_message);
-    if !format_validation.is_valid() {
-        if args.debug {
-            eprintln!(
-                "Warning: Commit message format issues: {:?}",
-                format_validation.errors()
-            );
+    // Each fix can undo another (a cleaned message may no longer follow the
+    // format or the template), so start over until the final message passes
+    // every check
+    let mut final_attempts = 0;
+    loop {
+        let format_validation = validate_conventional_commit(&commit_message);
+        if !format_validation.is_valid() {
+            if args.debug {
+                eprintln!(
+                    "Warning: Commit message format issues: {:?}",
+                    format_validation.errors()
+                );
+            }
+            llm_response = fix_commit_message(
+                llm,
+                &prompt,
+                &llm_response.raw_response,
+                &prompts::SYSTEM_PROMPT,
+                args.debug,
+            )
+            .await
+            .context("Failed to fix commit message format")?;
+
+            commit_message = llm_response.message.clone();
         }
-        llm_response = fix_commit_message(
-            &llm,
-            &prompt,
-            &llm_response.raw_response,
-            &prompts::SYSTEM_PROMPT,
-            args.debug,
-        )
-        .await
-        .context("Failed to fix commit message format")?;
 
-        commit_message = llm_response.message.clone();
-    }
+        let mut shorten_attempts = 0;
+        while let ValidationResult::Invalid(errors) = validate_subject_length(&commit_message) {
+            shorten_attempts += 1;
+            if shorten_attempts > MAX_RETRIES {
+                eprintln!("Final message:\n{}", commit_message);
+                anyhow::bail!(
+                    "{} after {} attempts to shorten it",
+                    errors.join(", "),
+                    MAX_RETRIES
+                );
+            }
 
-    const MAX_CLEAN_ATTEMPTS: usize = 3;
-    let mut clean_attempts = 0;
+            if args.debug {
+                eprintln!("Warning: {}, asking for a shorter one", errors.join(", "));
+            }
+            commit_message =
+                shorten_subject(llm, &commit_message, &prompts::SYSTEM_PROMPT, args.debug)
+                    .await
+                    .context("Failed to shorten the subject line")?;
+        }
 
-    loop {
-        let violations = check_policy_violations(&commit_message);
+        if let Some(template) = &template {
+            let mut template_attempts = 0;
+            loop {
+                let problems = template.check(&commit_message);
+                if problems.is_empty() {
+                    break;
+                }
 
-        if violations.is_empty() {
-            break;
+                template_attempts += 1;
+                if template_attempts > MAX_RETRIES {
+                    eprintln!("Final message:\n{}", commit_message);
+                    anyhow::bail!(
+                        "Message doesn't follow the commit template after {} attempts: {}",
+                        MAX_RETRIES,
+                        problems.join(", ")
+                    );
+                }
+
+                if args.debug {
+                    eprintln!(
+                        "Warning: {}, asking the LLM to follow the template",
+                        problems.join(", ")
+                    );
+                }
+                commit_message =
+                    follow_template(llm, &commit_message, template, &problems, args.debug)
+                        .await
+                        .context("Failed to apply the commit template")?;
+            }
         }
 
-        clean_attempts += 1;
-        if clean_attempts > MAX_CLEAN_ATTEMPTS {
+        const MAX_CLEAN_ATTEMPTS: usize = 3;
+        let mut clean_attempts = 0;
+
+        loop {
+            let violations = policy.check(&commit_message);
+
+            if violations.is_empty() {
+                break;
+            }
+
+            clean_attempts += 1;
+            if clean_attempts > MAX_CLEAN_ATTEMPTS {
+                eprintln!(
+                    "Error: Message still contains policy violations after {} attempts. Cannot proceed.",
+                    MAX_CLEAN_ATTEMPTS
+                );
+                eprintln!("Final message:\n{}", commit_message);
+                anyhow::bail!(
+                    "Message validation failed after {} cleaning attempts",
+                    MAX_CLEAN_ATTEMPTS
+                );
+            }
+
             eprintln!(
-                "Error: Message still contains policy violations after {} attempts. Cannot proceed.",
-                MAX_CLEAN_ATTEMPTS
+                "Warning: Commit message contains policy violations: {}",
+                violations.join(", ")
             );
-            eprintln!("Final message:\n{}", commit_message);
-            anyhow::bail!(
-                "Message validation failed after {} cleaning attempts",
-                MAX_CLEAN_ATTEMPTS
+            eprintln!("{}", commit_message);
+            eprintln!();
+            eprintln!(
+                "Cleaning attempt {} of {}...",
+                clean_attempts, MAX_CLEAN_ATTEMPTS
             );
+
+            llm_response = clean_commit_message(
+                llm,
+                &commit_message,
+                &violations,
+                &prompts::SYSTEM_PROMPT,
+                args.debug,
+            )
+            .await
+            .context("Failed to clean commit message")?;
+
+            commit_message = llm_response.message.clone();
         }
 
-        eprintln!(
-            "Warning: Commit message contains policy violations: {}",
-            violations.join(", ")
-        );
-        eprintln!("{}", commit_message);
-        eprintln!();
-        eprintln!(
-            "Cleaning attempt {} of {}...",
-            clean_attempts, MAX_CLEAN_ATTEMPTS
-        );
+        if commit_message.trim().is_empty() {
+            anyhow::bail!("Final commit message is empty after validation. Exiting.");
+        }
+
+        commit_message = wrap::wrap_body(&commit_message, gc_config.body_width);
 
-        llm_response =
-            clean_commit_message(&llm, &commit_message, &prompts::SYSTEM_PROMPT, args.debug)
-                .await
-                .context("Failed to clean commit message")?;
+        let mut errors = validate_commit_message(&commit_message).errors();
+        if let Some(template) = &template {
+            errors.extend(template.check(&commit_message));
+        }
+        if errors.is_empty() {
+            break;
+        }
+
+        final_attempts += 1;
+        if final_attempts > MAX_RETRIES {
+            eprintln!("Final message:\n{}", commit_message);
+            anyhow::bail!(
+                "Final message fails validation after {} attempts: {}",
+                MAX_RETRIES,
+                errors.join(", ")
+            );
+        }
 
-        commit_message = llm_response.message.clone();
+        if args.debug {
+            eprintln!("Warning: {}, fixing the final message", errors.join(", "));
+        }
     }
 
-    if commit_message.trim().is_empty() {
-        anyhow::bail!("Final commit message is empty after validation. Exiting.");
+    if args.trailer || gc_config.trailer {
+        let model = llm
+            .last_model()
+            .unwrap_or_else(|| llm.provider_name().to_string());
+        commit_message = trailers::append(&commit_message, &trailers::provenance(&model));
     }
 
+    Ok(commit_message)
+}
+
+/// Commit through the repository's hooks, returning false if a hook rewrote
+/// the message into one that fails validation (the commit is kept but
+/// shouldn't be pushed)
+fn commit_with_hooks(commit_message: &str, args: &Args, policy: &Policy) -> Result<bool> {
     println!("--- commit ---");
     println!("{}", commit_message);
     println!("--------------");
 
-    commit(&commit_message).context("Failed to commit changes")?;
+    let hook_setup = hooks::HookSetup::detect().unwrap_or_default();
+    if args.debug && !hook_setup.managers.is_empty() {
+        let managers: Vec<String> = hook_setup.managers.iter().map(|m| m.to_string()).collect();
+        eprintln!("Hook managers: {}", managers.join(", "));
+    }
+    if !args.no_verify && !hook_setup.is_empty() {
+        println!("Running commit hooks: {}", hook_setup.describe());
+    }
+
+    if let Err(e) = hooks::commit(commit_message, args.no_verify, args.amend) {
+        eprintln!("Error: {}", e);
+        eprintln!();
+        if !args.no_verify && !hook_setup.is_empty() {
+            eprintln!(
+                "A commit hook rejected the commit. Fix the issue above and re-run gc, or skip hooks with --no-verify."
+            );
+        }
+        eprintln!("The generated message was not committed. To commit it yourself:");
+        let amend_flag = if args.amend { " --amend" } else { "" };
+        eprintln!("git commit{} -m {:?}", amend_flag, commit_message);
+        anyhow::bail!("Failed to commit changes");
+    }
+
+    // A commit-msg hook may have rewritten the message after it passed validation
+    if let Some(rewritten) = hooks::head_message()
+        .ok()
+        .and_then(|committed| hooks::rewritten_message(commit_message, &committed))
+    {
+        println!("Commit hook rewrote the message:");
+        println!("{}", rewritten);
+        println!("--------------");
+
+        let problems = validate_rewritten_message(&rewritten, policy);
+        if !problems.is_empty() {
+            eprintln!(
+                "Warning: The rewritten commit message fails validation: {}",
+                problems.join(", ")
+            );
+            eprintln!("Commit was successful but not pushed to remote.");
+            eprintln!("Fix the message with: git commit --amend");
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Run the pre-push checks and push, unless disabled
+fn push_commits(
+    args: &Args,
+    gc_config: &GcConfig,
+    amend_target: Option<&amend::AmendTarget>,
+) -> Result<()> {
+    let current_branch = get_current_branch().context("Failed to get current branch")?;
 
     if args.nopush {
         println!("Commit successful (skipped push due to --nopush flag)");
         return Ok(());
     }
 
-    // TODO: Capture remote info and provide better feedback
-    match push() {
-        Ok(_) => {
+    if !args.skip_checks
+        && let Err(e) = push::run_pre_push_checks(&gc_config.pre_push_checks)
+    {
+        eprintln!(
+            "Warning: {}. Commit was successful but not pushed to remote.",
+            e
+        );
+        eprintln!("Fix the issue and push manually with: git push");
+        return Ok(());
+    }
+
+    // The remote still has the commit that was amended, so a plain push would be rejected
+    let rewrote_pushed_commit = amend_target.is_some_and(|t| t.is_pushed());
+    if rewrote_pushed_commit && !args.force_with_lease {
+        println!("The amended commit was already pushed, pushing with --force-with-lease");
+    }
+
+    let push_options = push::PushOptions {
+        force_with_lease: args.force_with_lease || rewrote_pushed_commit,
+    };
+
+    let upstream_exists = push::upstream().is_some();
+    if !upstream_exists && !confirm_set_upstream(args, &current_branch)? {
+        println!(
+            "Commit successful (not pushed, {} has no upstream)",
+            current_branch
+        );
+        println!("Push it with: git push -u origin {}", current_branch);
+        return Ok(());
+    }
+
+    match push::push(&push_options, &current_branch, upstream_exists) {
+        Ok(upstream) => {
             // Get remote URL for better feedback
             if let Ok(remote_url) = git(&["remote", "get-url", "origin"]) {
                 let cleaned_url = remote_url
@@ -806,9 +1206,15 @@ async fn main() -> Result<()> {
                     .replace("git@", "")
                     .replace(".git", "")
                     .replace(":", "/");
-                println!("Pushed to {} {}", cleaned_url, current_branch);
+                println!(
+                    "Pushed to {} {} (tracking {})",
+                    cleaned_url, current_branch, upstream
+                );
             } else {
-                println!("Pushed to remote");
+                println!("Pushed to remote (tracking {})", upstream);
+            }
+            if !upstream_exists {
+                println!("Branch {} now tracks {}", current_branch, upstream);
             }
         }
         Err(e) => {
@@ -821,72 +1227,216 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Decide whether to push a branch that has no upstream yet, setting it to
+/// origin/<branch>: always with --set-upstream, otherwise only if the user
+/// confirms. Without a terminal to ask on, the push is skipped.
+fn confirm_set_upstream(args: &Args, branch: &str) -> Result<bool> {
+    use std::io::IsTerminal;
 
-    #[test]
-    fn test_url_detection() {
-        // Test message with HTTPS URL
-        let msg_with_https = "feat: see https://example.com for details";
-        let violations = check_policy_violations(msg_with_https);
-        assert!(violations.contains(&"Contains URL".to_string()));
+    if args.set_upstream {
+        println!(
+            "Branch {} has no upstream, setting it to origin/{}",
+            branch, branch
+        );
+        return Ok(true);
+    }
+    if !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    confirm(&format!(
+        "Branch {} has no upstream. Push and set it to origin/{}?",
+        branch, branch
+    ))
+}
+
+/// Ask for a yes/no confirmation on stderr
+fn confirm(prompt: &str) -> Result<bool> {
+    use std::io::{self, BufRead, Write};
 
-        // Test message with git URL
-        let msg_with_git = "feat: clone git://github.com/user/repo.git";
-        let violations = check_policy_violations(msg_with_git);
-        assert!(violations.contains(&"Contains URL".to_string()));
+    eprint!("{} [y/N] ", prompt);
+    io::stderr().flush()?;
 
-        // Test message without URL
-        let msg_without_url = "feat: add new feature";
-        let violations = check_policy_violations(msg_without_url);
-        assert!(!violations.contains(&"Contains URL".to_string()));
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    Ok(line.trim().eq_ignore_ascii_case("y"))
+}
 
-        // Test with domain-like text
-        let msg_with_domain = "fix: update example.com config";
-        let violations = check_policy_violations(msg_with_domain);
-        assert!(violations.contains(&"Contains URL".to_string()));
+/// Propose splitting the staged changes into several commits, then stage,
+/// generate a message for, and commit each group in turn
+async fn run_split(
+    llm: &LlmClient,
+    args: &Args,
+    gc_config: &GcConfig,
+    policy: &Policy,
+) -> Result<()> {
+    let staged =
+        split::parse_name_status(&get_name_status(None).context("Failed to get file status")?);
+    let groups = if staged.len() > 1 {
+        plan_split(llm, args, gc_config, &staged).await?
+    } else {
+        Vec::new()
+    };
+
+    if groups.len() < 2 {
+        println!("The changes belong in a single commit");
+        let commit_message = generate_message(llm, args, gc_config, policy, None, None).await?;
+        if !commit_with_hooks(&commit_message, args, policy)? {
+            return Ok(());
+        }
+        return push_commits(args, gc_config, None);
     }
 
-    #[test]
-    fn test_email_detection() {
-        // Test message with email
-        let msg_with_email = "feat: add user@example.com to contacts";
-        let violations = check_policy_violations(msg_with_email);
-        assert!(violations.contains(&"Contains email address".to_string()));
+    println!("Proposed commits:");
+    print!("{}", split::format_plan(&groups));
+    if !confirm(&format!("Create these {} commits?", groups.len()))? {
+        println!("Split cancelled; the changes are still staged.");
+        return Ok(());
+    }
 
-        // Test message without email
-        let msg_without_email = "feat: add new feature";
-        let violations = check_policy_violations(msg_without_email);
-        assert!(!violations.contains(&"Contains email address".to_string()));
+    let all_paths: Vec<&str> = groups.iter().flat_map(|g| g.paths()).collect();
 
-        // Test with various email formats
-        let msg_with_plus = "fix: contact john.doe+test@example.org";
-        let violations = check_policy_violations(msg_with_plus);
-        assert!(violations.contains(&"Contains email address".to_string()));
+    // Groups are staged with `git add`, which takes whole files, so a partially
+    // staged file would pick up its unstaged changes
+    let mut diff_args = vec!["diff", "--name-only", "--"];
+    diff_args.extend(&all_paths);
+    let partially_staged = git(&diff_args).context("Failed to check unstaged changes")?;
+    if !partially_staged.trim().is_empty() {
+        anyhow::bail!(
+            "--split stages whole files, but these files also have unstaged changes:\n{}Stage or stash those changes first.",
+            partially_staged
+        );
     }
 
-    #[test]
-    fn test_emoji_detection() {
-        // Test messages with emojis
-        let msg_with_emoji = "feat: add new feature 🎉";
-        let violations = check_policy_violations(msg_with_emoji);
-        assert!(violations.contains(&"Contains emoji characters".to_string()));
+    let mut reset_args = vec!["reset", "-q", "--"];
+    reset_args.extend(&all_paths);
+    git(&reset_args).context("Failed to unstage changes")?;
+
+    for (i, group) in groups.iter().enumerate() {
+        println!();
+        println!(
+            "Commit {} of {}: {}",
+            i + 1,
+            groups.len(),
+            group.description
+        );
+
+        let committed = commit_group(llm, args, gc_config, policy, group, i, groups.len()).await;
+        let error = match committed {
+            Ok(true) => continue,
+            Ok(false) => anyhow::anyhow!(
+                "Commit {} of {} was created, but a hook rewrote its message into one that fails validation",
+                i + 1,
+                groups.len()
+            ),
+            Err(e) => e,
+        };
+        if i + 1 < groups.len() {
+            eprintln!(
+                "Stopped at commit {} of {}. These commits were not created and their changes are unstaged:",
+                i + 1,
+                groups.len()
+            );
+            for (j, remaining) in groups.iter().enumerate().skip(i + 1) {
+                eprintln!("  {}. {}", j + 1, remaining.description);
+            }
+        }
+        return Err(error);
+    }
+
+    push_commits(args, gc_config, None)
+}
+
+/// Ask the LLM to group the staged files into commits
+async fn plan_split(
+    llm: &LlmClient,
+    args: &Args,
+    gc_config: &GcConfig,
+    staged: &[split::StagedFile],
+) -> Result<Vec<split::CommitGroup>> {
+    let git_diff = get_staged_diff(None).context("Failed to get git diff")?;
+    let git_name_status = get_name_status(None).context("Failed to get file status")?;
+
+    let mut context = String::new();
+    if !args.message.is_empty() {
+        context.push_str(&format!(
+            "The user described their changes as:\n{}\n\n---\n\n",
+            args.message.join(" ")
+        ));
+    }
+    if let Some(provided_context) = &args.context {
+        context.push_str(&format!(
+            "The user included this additional context about the work:\n{}\n\n---\n\n",
+            provided_context
+        ));
+    }
+    context.push_str(&format!("Changed files:\n{}\n\n", git_name_status));
+    if estimate_tokens(&git_diff) > gc_config.max_diff_tokens {
+        let changed_symbols =
+            symbols::format_changed_symbols(&symbols::extract_changed_symbols(&git_diff));
+        if let Some(changed_symbols) = changed_symbols {
+            context.push_str(&changed_symbols);
+        }
+    } else {
+        context.push_str(&format!("Staged changes:\n{}", git_diff));
+    }
 
-        // Test message without emojis
-        let msg_without_emoji = "feat: add new feature";
-        let violations = check_policy_violations(msg_without_emoji);
-        assert!(!violations.contains(&"Contains emoji characters".to_string()));
+    println!("Planning commits with {}", llm.provider_name());
+    let prompt = prompts::split_plan_prompt(llm.templates(), &context)?;
 
-        // Test with various emoji types
-        let msg_with_flag = "fix: update code 🇺🇸";
-        let violations = check_policy_violations(msg_with_flag);
-        assert!(violations.contains(&"Contains emoji characters".to_string()));
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let response = llm.complete(&prompt, &prompts::SYSTEM_PROMPT).await?;
+        if args.debug {
+            eprintln!("Raw plan response:\n{}", response);
+        }
 
-        let msg_with_symbol = "chore: cleanup ✅";
-        let violations = check_policy_violations(msg_with_symbol);
-        assert!(violations.contains(&"Contains emoji characters".to_string()));
+        match split::parse_plan(&response) {
+            Ok(planned) => return Ok(split::reconcile(planned, staged)),
+            Err(e) if attempts >= MAX_RETRIES => {
+                anyhow::bail!(
+                    "Failed to get a commit plan after {} attempts: {}",
+                    MAX_RETRIES,
+                    e
+                );
+            }
+            Err(e) => {
+                if args.debug {
+                    eprintln!("Plan parse error: {}, retrying...", e);
+                }
+            }
+        }
     }
+}
+
+/// Stage one group of a split and commit it, returning false if it shouldn't be pushed
+async fn commit_group(
+    llm: &LlmClient,
+    args: &Args,
+    gc_config: &GcConfig,
+    policy: &Policy,
+    group: &split::CommitGroup,
+    index: usize,
+    total: usize,
+) -> Result<bool> {
+    let mut add_args = vec!["add", "-A", "--"];
+    add_args.extend(group.paths());
+    git(&add_args).context("Failed to stage changes")?;
+
+    let split_context = format!(
+        "These changes were split out of a larger set of changes. This is commit {} of {} and covers: {}",
+        index + 1,
+        total,
+        group.description
+    );
+    let commit_message =
+        generate_message(llm, args, gc_config, policy, None, Some(&split_context)).await?;
+    commit_with_hooks(&commit_message, args, policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
     fn test_conventional_commit_validation() {
@@ -916,6 +1466,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_subject_length_validation() {
+        let at_limit = format!("feat: {}", "a".repeat(MAX_SUBJECT_LENGTH - 6));
+        assert!(validate_subject_length(&at_limit).is_valid());
+        // Only the subject line counts, and length is in characters, not bytes
+        assert!(
+            validate_subject_length(&format!("{}\n\n{}", at_limit, "b".repeat(200))).is_valid()
+        );
+        assert!(validate_subject_length(&format!("feat: {}", "é".repeat(60))).is_valid());
+
+        let result = validate_subject_length(&format!("{}a", at_limit));
+        assert_eq!(
+            result.errors(),
+            vec!["Subject line is 73 characters (limit is 72)"]
+        );
+    }
+
+    #[test]
+    fn test_commit_message_validation() {
+        assert!(validate_commit_message("fix: handle empty input").is_valid());
+
+        let errors = validate_commit_message(&format!("Update {}", "x".repeat(80))).errors();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("Conventional Commits format"));
+        assert_eq!(errors[1], "Subject line is 87 characters (limit is 72)");
+    }
+
+    #[test]
+    fn test_validate_rewritten_message() {
+        let policy = Policy::new(&Default::default(), HashSet::new()).unwrap();
+        assert!(validate_rewritten_message("feat: add login\n\nRefs: ABC-12", &policy).is_empty());
+
+        let problems = validate_rewritten_message("[ABC-12] feat: add login 🎉", &policy);
+        assert!(
+            problems
+                .iter()
+                .any(|p| p.contains("Conventional Commits format"))
+        );
+        assert!(problems.contains(&"Contains emoji characters".to_string()));
+    }
+
     #[test]
     fn test_extract_xml_tag_valid() {
         // Test extracting a valid tag
@@ -1077,78 +1668,6 @@ feat: add user authentication
         );
     }
 
-    #[test]
-    fn test_filename_excludes_url_detection() {
-        let msg_with_real_filename = "fix: update Cargo.toml dependencies";
-        let violations = check_policy_violations(msg_with_real_filename);
-        assert!(
-            !violations.contains(&"Contains URL".to_string()),
-            "Real filename should not be flagged as URL"
-        );
-
-        let msg_with_nonexistent_file = "fix: update example.com config";
-        let violations = check_policy_violations(msg_with_nonexistent_file);
-        assert!(
-            violations.contains(&"Contains URL".to_string()),
-            "Domain-like string that's not a filename should be flagged"
-        );
-    }
-
-    #[test]
-    fn test_filename_and_url_mixed() {
-        let msg = "fix: update Cargo.toml to use https://crates.io/new-crate";
-        let violations = check_policy_violations(msg);
-        assert!(
-            violations.contains(&"Contains URL".to_string()),
-            "Should flag actual URL even when message contains valid filenames"
-        );
-    }
-
-    #[test]
-    fn test_dotted_filenames() {
-        let msg = "fix: update main.rs formatting";
-        let violations = check_policy_violations(msg);
-        assert!(
-            !violations.contains(&"Contains URL".to_string()),
-            "Filename with dot should not be flagged as domain"
-        );
-    }
-
-    #[test]
-    fn test_trailing_period_handling() {
-        // Words ending with period (end of sentence) should not be flagged as URLs
-        let msg = "fix: resolve workspace root to enable execution from any directory. Previously relied on relative paths which only worked when executed from workspace root.";
-        let violations = check_policy_violations(msg);
-        assert!(
-            !violations.contains(&"Contains URL".to_string()),
-            "Words with trailing periods at end of sentence should not be flagged as URLs"
-        );
-
-        // But actual domains at end of sentence should still be caught
-        let msg_with_domain = "fix: see documentation at example.com.";
-        let violations = check_policy_violations(msg_with_domain);
-        assert!(
-            violations.contains(&"Contains URL".to_string()),
-            "Actual domain with trailing period should still be flagged"
-        );
-
-        // Multiple sentences with various words ending in periods
-        let msg_multiple = "feat: add new feature. Update configuration. Test everything.";
-        let violations = check_policy_violations(msg_multiple);
-        assert!(
-            !violations.contains(&"Contains URL".to_string()),
-            "Regular words ending sentences should not be flagged"
-        );
-
-        // Mixed: filename with trailing period should not be flagged
-        let msg_filename = "fix: update Cargo.toml.";
-        let violations = check_policy_violations(msg_filename);
-        assert!(
-            !violations.contains(&"Contains URL".to_string()),
-            "Filename with trailing period should not be flagged"
-        );
-    }
-
     #[test]
     fn test_extract_deleted_filenames() {
         // Test deleted file extraction
@@ -0,0 +1,387 @@
+// Commit message content policies
+
+use addr::parse_domain_name;
+use anyhow::{Result, anyhow};
+use email_address::EmailAddress;
+use llm_client::{RegexFilter, ResponseFilter};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use unicode_segmentation::UnicodeSegmentation;
+use url::Url;
+
+use crate::{extract_xml_tag, trailers};
+
+/// Policy settings from the `[policy]` table in gc.toml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyConfig {
+    /// Reject email addresses
+    #[serde(default = "enabled")]
+    pub email: bool,
+
+    /// Reject URLs and bare domain names
+    #[serde(default = "enabled")]
+    pub url: bool,
+
+    /// Reject emoji characters
+    #[serde(default = "enabled")]
+    pub emoji: bool,
+
+    /// Regular expressions that must not match anywhere in the message
+    #[serde(default)]
+    pub banned_patterns: Vec<String>,
+
+    /// Domains allowed in URLs and email addresses, including their subdomains
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+
+    /// Words that are never flagged as URLs or email addresses (e.g. "Node.js")
+    #[serde(default)]
+    pub allowed_words: Vec<String>,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl Default for PolicyConfig {
+    fn default() -> Self {
+        Self {
+            email: true,
+            url: true,
+            emoji: true,
+            banned_patterns: Vec::new(),
+            allowed_domains: Vec::new(),
+            allowed_words: Vec::new(),
+        }
+    }
+}
+
+/// Compiled policies, ready to check messages against
+#[derive(Debug, Clone)]
+pub struct Policy {
+    config: PolicyConfig,
+    banned: RegexFilter,
+    /// Words that are never flagged as URLs: allowed words plus repo filenames
+    known_words: HashSet<String>,
+}
+
+impl Policy {
+    /// Compile the policy. `repo_filenames` are filenames like `Cargo.toml`
+    /// that look like domains but shouldn't be flagged as URLs.
+    pub fn new(config: &PolicyConfig, repo_filenames: HashSet<String>) -> Result<Self> {
+        let banned = RegexFilter::new(&config.banned_patterns)
+            .map_err(|e| anyhow!("Invalid banned pattern in gc.toml: {}", e))?;
+
+        let mut known_words = repo_filenames;
+        known_words.extend(config.allowed_words.iter().cloned());
+
+        Ok(Self {
+            config: config.clone(),
+            banned,
+            known_words,
+        })
+    }
+
+    /// Check a commit message, returning a description of each violation
+    pub fn check(&self, message: &str) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if self.config.email && self.words(message).any(|word| self.is_email(word)) {
+            violations.push("Contains email address".to_string());
+        }
+
+        if self.config.url && self.words(message).any(|word| self.is_url(word)) {
+            violations.push("Contains URL".to_string());
+        }
+
+        if self.config.emoji
+            && message
+                .graphemes(true)
+                .any(|grapheme| emojis::get(grapheme).is_some())
+        {
+            violations.push("Contains emoji characters".to_string());
+        }
+
+        violations.extend(self.banned.check(message));
+        violations
+    }
+
+    /// Check a committed message, leaving out the provenance trailers
+    /// `--trailer` added to the trailer block ending it
+    pub fn check_committed(&self, message: &str) -> Vec<String> {
+        self.check(&trailers::strip(message))
+    }
+
+    /// Words that are candidates for the email and URL checks
+    fn words<'a>(&'a self, message: &'a str) -> impl Iterator<Item = &'a str> {
+        message
+            .split_whitespace()
+            // Strip trailing period (end of sentence punctuation)
+            .map(|word| word.strip_suffix('.').unwrap_or(word))
+            .filter(|word| !self.known_words.contains(*word))
+    }
+
+    fn is_email(&self, word: &str) -> bool {
+        match word.parse::<EmailAddress>() {
+            Ok(email) => !self.is_allowed_domain(email.domain()),
+            Err(_) => false,
+        }
+    }
+
+    fn is_url(&self, word: &str) -> bool {
+        if let Ok(url) = Url::parse(word) {
+            return url
+                .host_str()
+                .is_some_and(|host| !self.is_allowed_domain(host));
+        }
+
+        if word.contains('.')
+            && let Ok(domain) = parse_domain_name(word)
+        {
+            return domain.has_known_suffix() && !self.is_allowed_domain(word);
+        }
+
+        false
+    }
+
+    fn is_allowed_domain(&self, host: &str) -> bool {
+        let host = host.to_lowercase();
+        self.config.allowed_domains.iter().any(|allowed| {
+            let allowed = allowed.to_lowercase();
+            host == allowed || host.ends_with(&format!(".{}", allowed))
+        })
+    }
+}
+
+/// Rejects LLM responses whose commit message breaks the policy. A
+/// structured response is checked only in its `<commit_message>` section, as
+/// its observations may quote the diff, and a split plan holds no message.
+impl ResponseFilter for Policy {
+    fn check(&self, content: &str) -> Vec<String> {
+        if !content.contains("<observations>") {
+            return Policy::check(self, content);
+        }
+        extract_xml_tag(content, "commit_message")
+            .map_or_else(Vec::new, |message| Policy::check(self, &message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_policy() -> Policy {
+        let filenames = ["Cargo.toml", "main.rs"].map(String::from).into();
+        Policy::new(&PolicyConfig::default(), filenames).unwrap()
+    }
+
+    fn policy_from(toml_str: &str) -> Policy {
+        let config: PolicyConfig = toml::from_str(toml_str).unwrap();
+        Policy::new(&config, HashSet::new()).unwrap()
+    }
+
+    fn flags(policy: &Policy, message: &str, violation: &str) -> bool {
+        policy.check(message).contains(&violation.to_string())
+    }
+
+    #[test]
+    fn test_response_filter_checks_the_message() {
+        let policy = default_policy();
+        let filter: &dyn ResponseFilter = &policy;
+        assert!(
+            filter
+                .check("<observations>\nLinks to https://example.com\n</observations>\n<commit_message>\nfeat: add link\n</commit_message>")
+                .is_empty()
+        );
+        assert_eq!(
+            filter.check("<observations>\nx\n</observations>\n<commit_message>\nfeat: see https://example.com\n</commit_message>"),
+            vec!["Contains URL"]
+        );
+        assert!(
+            filter
+                .check("<observations>\nSee example.com\n</observations>\n<plan>\n</plan>")
+                .is_empty()
+        );
+        assert_eq!(
+            filter.check("feat: see https://example.com"),
+            vec!["Contains URL"]
+        );
+    }
+
+    #[test]
+    fn test_url_detection() {
+        let policy = default_policy();
+        assert!(flags(
+            &policy,
+            "feat: see https://example.com for details",
+            "Contains URL"
+        ));
+        assert!(flags(
+            &policy,
+            "feat: clone git://github.com/user/repo.git",
+            "Contains URL"
+        ));
+        assert!(!flags(&policy, "feat: add new feature", "Contains URL"));
+        // Domain-like text
+        assert!(flags(
+            &policy,
+            "fix: update example.com config",
+            "Contains URL"
+        ));
+    }
+
+    #[test]
+    fn test_email_detection() {
+        let policy = default_policy();
+        let email = "Contains email address";
+        assert!(flags(
+            &policy,
+            "feat: add user@example.com to contacts",
+            email
+        ));
+        assert!(!flags(&policy, "feat: add new feature", email));
+        assert!(flags(
+            &policy,
+            "fix: contact john.doe+test@example.org",
+            email
+        ));
+    }
+
+    #[test]
+    fn test_emoji_detection() {
+        let policy = default_policy();
+        let emoji = "Contains emoji characters";
+        assert!(flags(&policy, "feat: add new feature 🎉", emoji));
+        assert!(!flags(&policy, "feat: add new feature", emoji));
+        assert!(flags(&policy, "fix: update code 🇺🇸", emoji));
+        assert!(flags(&policy, "chore: cleanup ✅", emoji));
+    }
+
+    #[test]
+    fn test_filenames_are_not_urls() {
+        let policy = default_policy();
+        assert!(!flags(
+            &policy,
+            "fix: update Cargo.toml dependencies",
+            "Contains URL"
+        ));
+        assert!(!flags(
+            &policy,
+            "fix: update main.rs formatting",
+            "Contains URL"
+        ));
+        // Actual URLs are still flagged next to filenames
+        assert!(flags(
+            &policy,
+            "fix: update Cargo.toml to use https://crates.io/new-crate",
+            "Contains URL"
+        ));
+    }
+
+    #[test]
+    fn test_trailing_period_handling() {
+        let policy = default_policy();
+        let url = "Contains URL";
+
+        let msg = "fix: resolve workspace root to enable execution from any directory. Previously relied on relative paths which only worked when executed from workspace root.";
+        assert!(!flags(&policy, msg, url));
+        assert!(flags(
+            &policy,
+            "fix: see documentation at example.com.",
+            url
+        ));
+        assert!(!flags(
+            &policy,
+            "feat: add new feature. Update configuration. Test everything.",
+            url
+        ));
+        assert!(!flags(&policy, "fix: update Cargo.toml.", url));
+    }
+
+    #[test]
+    fn test_disabled_policies() {
+        let policy = policy_from("email = false\nurl = false\nemoji = false");
+        assert!(
+            policy
+                .check("feat: mail a@example.com about https://example.com 🎉")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_banned_patterns() {
+        let policy = policy_from(r#"banned_patterns = ["(?i)co-authored-by:", "JIRA-\\d+"]"#);
+        let violations = policy.check("feat: add login\n\nJIRA-42\nCo-Authored-By: someone");
+        assert_eq!(
+            violations,
+            vec![
+                "Matches banned pattern: (?i)co-authored-by:",
+                "Matches banned pattern: JIRA-\\d+"
+            ]
+        );
+        assert!(policy.check("feat: add login").is_empty());
+    }
+
+    #[test]
+    fn test_invalid_banned_pattern() {
+        let config = PolicyConfig {
+            banned_patterns: vec!["(unclosed".to_string()],
+            ..Default::default()
+        };
+        let err = Policy::new(&config, HashSet::new()).unwrap_err();
+        assert!(err.to_string().contains("(unclosed"));
+    }
+
+    #[test]
+    fn test_allowed_domains() {
+        let policy = policy_from(r#"allowed_domains = ["example.com"]"#);
+        assert!(
+            policy
+                .check("docs: link https://docs.example.com/guide")
+                .is_empty()
+        );
+        assert!(policy.check("fix: point at example.com").is_empty());
+        assert!(policy.check("chore: notify ops@example.com").is_empty());
+        // Other domains and lookalikes are still flagged
+        assert!(flags(
+            &policy,
+            "docs: link https://example.org",
+            "Contains URL"
+        ));
+        assert!(flags(&policy, "fix: use badexample.com", "Contains URL"));
+    }
+
+    #[test]
+    fn test_gc_trailers_are_not_checked() {
+        let policy = policy_from(r#"banned_patterns = ["(?i)generated"]"#);
+        let message =
+            "feat: add login\n\nGenerated-by: gc v1.3.0\nModel: anthropic/claude-3.5-sonnet";
+        assert!(policy.check_committed(message).is_empty());
+        assert!(flags(
+            &policy,
+            "feat: add generated client",
+            "Matches banned pattern: (?i)generated"
+        ));
+
+        // Generated messages get no exemption for trailer-like lines
+        let policy = default_policy();
+        let message = "feat: x\n\nModel: https://evil.example";
+        assert!(flags(&policy, message, "Contains URL"));
+        assert!(
+            policy
+                .check_committed("feat: x\n\nModel: https://evil.example\n\nBody")
+                .contains(&"Contains URL".to_string())
+        );
+    }
+
+    #[test]
+    fn test_allowed_words() {
+        let policy = policy_from(r#"allowed_words = ["Node.js", "socket.io"]"#);
+        assert!(
+            policy
+                .check("feat: upgrade Node.js and socket.io")
+                .is_empty()
+        );
+        assert!(flags(&policy, "feat: upgrade example.io", "Contains URL"));
+    }
+}
@@ -0,0 +1,208 @@
+// Pre-flight summary of the staged changes
+
+use anyhow::Result;
+use std::collections::BTreeSet;
+
+use crate::git;
+
+/// Top-level directories that say nothing about scope; the next path
+/// component is used instead
+const GENERIC_DIRS: &[&str] = &["src", "lib"];
+
+/// Lines changed in one staged file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStat {
+    pub path: String,
+    /// `None` for binary files, which git doesn't count lines for
+    pub insertions: Option<usize>,
+    pub deletions: Option<usize>,
+}
+
+/// What gc is about to describe, shown before the LLM is called
+#[derive(Debug, Clone)]
+pub struct Preflight {
+    pub files: Vec<FileStat>,
+    pub estimated_tokens: usize,
+    pub max_diff_tokens: usize,
+}
+
+impl Preflight {
+    /// Count the staged changes, relative to `base` if given
+    pub fn load(
+        base: Option<&str>,
+        estimated_tokens: usize,
+        max_diff_tokens: usize,
+    ) -> Result<Self> {
+        let mut args = vec!["diff", "--staged", "--numstat", "--no-color"];
+        args.extend(base);
+        Ok(Self {
+            files: parse_numstat(&git(&args)?),
+            estimated_tokens,
+            max_diff_tokens,
+        })
+    }
+
+    /// Scopes the changes likely belong to, from the files' leading directories
+    pub fn scopes(&self) -> Vec<&str> {
+        let scopes: BTreeSet<&str> = self
+            .files
+            .iter()
+            .filter_map(|file| scope(&file.path))
+            .collect();
+        scopes.into_iter().collect()
+    }
+
+    pub fn format(&self) -> String {
+        let width = self
+            .files
+            .iter()
+            .map(|file| file.path.len())
+            .max()
+            .unwrap_or(0)
+            .max("File".len());
+        let count = |n: Option<usize>| n.map_or("-".to_string(), |n| n.to_string());
+
+        let mut output = format!("{:<width$}  {:>6}  {:>6}\n", "File", "+", "-");
+        for file in &self.files {
+            output.push_str(&format!(
+                "{:<width$}  {:>6}  {:>6}\n",
+                file.path,
+                count(file.insertions),
+                count(file.deletions)
+            ));
+        }
+
+        let insertions: usize = self.files.iter().filter_map(|f| f.insertions).sum();
+        let deletions: usize = self.files.iter().filter_map(|f| f.deletions).sum();
+        output.push_str(&format!(
+            "{} file(s) changed, {} insertion(s)(+), {} deletion(s)(-)\n",
+            self.files.len(),
+            insertions,
+            deletions
+        ));
+
+        let scopes = self.scopes();
+        if !scopes.is_empty() {
+            output.push_str(&format!("Scopes: {}\n", scopes.join(", ")));
+        }
+
+        let mode = if self.estimated_tokens > self.max_diff_tokens {
+            ", summary mode: only the file list is sent"
+        } else {
+            ""
+        };
+        output.push_str(&format!(
+            "Diff size: ~{} tokens (limit: {}{})\n",
+            self.estimated_tokens, self.max_diff_tokens, mode
+        ));
+        output
+    }
+}
+
+/// The top-level directory of a path, or for `src/` and `lib/` the module
+/// below it. Files in the repository root have no scope.
+fn scope(path: &str) -> Option<&str> {
+    let (dir, rest) = path.split_once('/')?;
+    let scope = if GENERIC_DIRS.contains(&dir) {
+        let module = rest.split('/').next()?;
+        module.split('.').next()?
+    } else {
+        dir
+    };
+    // Renames are shown as "{old => new}"
+    (!scope.is_empty() && !scope.contains('{')).then_some(scope)
+}
+
+/// Parse `git diff --numstat` output: "<added>\t<deleted>\t<path>", with "-"
+/// counts for binary files
+fn parse_numstat(numstat: &str) -> Vec<FileStat> {
+    numstat
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            let insertions = parts.next()?.parse().ok();
+            let deletions = parts.next()?.parse().ok();
+            let path = parts.next()?.to_string();
+            Some(FileStat {
+                path,
+                insertions,
+                deletions,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preflight(numstat: &str) -> Preflight {
+        Preflight {
+            files: parse_numstat(numstat),
+            estimated_tokens: 120,
+            max_diff_tokens: 8000,
+        }
+    }
+
+    #[test]
+    fn test_parse_numstat() {
+        let files = parse_numstat("12\t3\tgc/src/main.rs\n-\t-\tlogo.png\n");
+        assert_eq!(
+            files,
+            vec![
+                FileStat {
+                    path: "gc/src/main.rs".to_string(),
+                    insertions: Some(12),
+                    deletions: Some(3),
+                },
+                FileStat {
+                    path: "logo.png".to_string(),
+                    insertions: None,
+                    deletions: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scopes() {
+        let preflight = preflight(
+            "1\t0\tgc/src/main.rs\n2\t0\tgc/README.md\n1\t1\tllm-client/src/lib.rs\n1\t0\tREADME.md\n",
+        );
+        assert_eq!(preflight.scopes(), vec!["gc", "llm-client"]);
+    }
+
+    #[test]
+    fn test_scope() {
+        assert_eq!(scope("gc/src/main.rs"), Some("gc"));
+        assert_eq!(scope("src/config.rs"), Some("config"));
+        assert_eq!(scope("src/providers/mock.rs"), Some("providers"));
+        assert_eq!(scope("README.md"), None);
+        assert_eq!(scope("{old => new}/lib.rs"), None);
+    }
+
+    #[test]
+    fn test_format() {
+        let output = preflight("12\t3\tsrc/main.rs\n-\t-\tlogo.png\n").format();
+        assert_eq!(
+            output,
+            "File              +       -\n\
+             src/main.rs      12       3\n\
+             logo.png          -       -\n\
+             2 file(s) changed, 12 insertion(s)(+), 3 deletion(s)(-)\n\
+             Scopes: main\n\
+             Diff size: ~120 tokens (limit: 8000)\n"
+        );
+    }
+
+    #[test]
+    fn test_format_large_diff() {
+        let mut preflight = preflight("1\t0\tREADME.md\n");
+        preflight.estimated_tokens = 9000;
+        let output = preflight.format();
+        assert!(output.ends_with(
+            "Diff size: ~9000 tokens (limit: 8000, summary mode: only the file list is sent)\n"
+        ));
+        assert!(!output.contains("Scopes"));
+    }
+}
@@ -1,14 +1,53 @@
 // LLM prompt templates
+//
+// Each prompt is registered as a named default in llm-client's
+// PromptTemplates, so users can override it in
+// ~/.config/cli-programs/prompts/<name>.toml or [templates.<name>] in
+// llm.toml without recompiling.
 
+use llm_client::{PromptTemplate, PromptTemplates, Result};
 use std::sync::LazyLock;
 
+/// Names the prompts are registered under
+pub const COMMIT: &str = "gc_commit";
+pub const COMMIT_SUMMARY: &str = "gc_commit_summary";
+pub const FIX_FORMAT: &str = "gc_fix_format";
+pub const CLEAN: &str = "gc_clean";
+pub const FOLLOW_TEMPLATE: &str = "gc_template";
+pub const SHORTEN: &str = "gc_shorten";
+pub const SPLIT_PLAN: &str = "gc_split_plan";
+
+/// gc's built-in prompts
+pub fn defaults() -> PromptTemplates {
+    [
+        (COMMIT, COMMIT_PROMPT),
+        (COMMIT_SUMMARY, COMMIT_SUMMARY_PROMPT),
+        (FIX_FORMAT, FIX_FORMAT_PROMPT),
+        (CLEAN, CLEAN_PROMPT),
+        (FOLLOW_TEMPLATE, FOLLOW_TEMPLATE_PROMPT),
+        (SHORTEN, SHORTEN_PROMPT),
+        (SPLIT_PLAN, SPLIT_PLAN_PROMPT),
+    ]
+    .into_iter()
+    .fold(PromptTemplates::new(), |templates, (name, prompt)| {
+        templates.with_default(name, PromptTemplate::new(None, prompt))
+    })
+}
+
+/// Render a named prompt
+fn render(templates: &PromptTemplates, name: &str, vars: &[(&str, &str)]) -> Result<String> {
+    Ok(templates.render(name, vars)?.prompt)
+}
+
 pub static SYSTEM_PROMPT: LazyLock<String> = LazyLock::new(|| {
     "You are an experienced software engineer that writes clear and concise Conventional Commit git commit messages.".to_string()
 });
 
-pub fn generate_commit_prompt(context: &str) -> String {
-    format!(
-        r#"Please write a clear message that describes the changes in this pull request.
+pub fn generate_commit_prompt(templates: &PromptTemplates, context: &str) -> Result<String> {
+    render(templates, COMMIT, &[("context", context)])
+}
+
+const COMMIT_PROMPT: &str = r#"Please write a clear message that describes the changes in this pull request.
 
 Requirements:
 - It needs to be a functionally descriptive message that will help engineers understand what is changing.
@@ -37,7 +76,7 @@ The key rules for a conventional commit formatted message:
 
 1. Start with a type
 2. Use a colon and space after type
-3. Provide a short, descriptive summary in the first line
+3. Provide a short, descriptive summary in the first line (72 characters or fewer)
 4. Optional body should be separated by a blank line
 5. Optional footers should be separated by a blank line
 
@@ -62,22 +101,32 @@ Some more context about what changed.
 
 Here are the code changes:
 
-{}
-"#,
-        context
+{{context}}
+"#;
+
+pub fn fix_message_format(
+    templates: &PromptTemplates,
+    original_prompt: &str,
+    previous_response: &str,
+) -> Result<String> {
+    render(
+        templates,
+        FIX_FORMAT,
+        &[
+            ("original_prompt", original_prompt),
+            ("previous_response", previous_response),
+        ],
     )
 }
 
-pub fn fix_message_format(original_prompt: &str, previous_response: &str) -> String {
-    format!(
-        r#"Please update your response. Here are the original instructions:
+const FIX_FORMAT_PROMPT: &str = r#"Please update your response. Here are the original instructions:
 
-{}
+{{original_prompt}}
 
 The previous response did not follow the required format. You MUST include both observation and commit_message sections.
 
 Previous response:
-{}
+{{previous_response}}
 
 Please submit a corrected version. As a reminder, it must follow this format:
 
@@ -90,16 +139,18 @@ commit-type: a functional description of the commit
 
 Additional context about the commit if needed
 </commit_message>
-"#,
-        original_prompt, previous_response
-    )
-}
+"#;
 
 /// Generate commit prompt for summary mode (when diff is too large)
 /// Uses file list and user-provided context instead of full diff
-pub fn generate_commit_prompt_summary_mode(context: &str) -> String {
-    format!(
-        r#"Please write a clear message that describes the changes in this pull request.
+pub fn generate_commit_prompt_summary_mode(
+    templates: &PromptTemplates,
+    context: &str,
+) -> Result<String> {
+    render(templates, COMMIT_SUMMARY, &[("context", context)])
+}
+
+const COMMIT_SUMMARY_PROMPT: &str = r#"Please write a clear message that describes the changes in this pull request.
 
 IMPORTANT: The diff was too large to include, so you are working with a file list and user-provided context instead of the actual code changes. Base your commit message on the available information.
 
@@ -131,7 +182,7 @@ The key rules for a conventional commit formatted message:
 
 1. Start with a type
 2. Use a colon and space after type
-3. Provide a short, descriptive summary in the first line
+3. Provide a short, descriptive summary in the first line (72 characters or fewer)
 4. Optional body should be separated by a blank line
 5. Optional footers should be separated by a blank line
 
@@ -156,15 +207,24 @@ Some more context about what changed.
 
 Here is the available information:
 
-{}
-"#,
-        context
+{{context}}
+"#;
+
+pub fn fix_message_content(
+    templates: &PromptTemplates,
+    message: &str,
+    violations: &[String],
+) -> Result<String> {
+    render(
+        templates,
+        CLEAN,
+        &[("violations", &violations.join("; ")), ("message", message)],
     )
 }
 
-pub fn fix_message_content(message: &str) -> String {
-    format!(
-        r#"Please update this commit message by removing all:
+const CLEAN_PROMPT: &str = r#"This commit message was rejected because it: {{violations}}
+
+Please update this commit message by removing all:
 
 - URLs (http/https links)
 - Email addresses
@@ -180,7 +240,133 @@ IMPORTANT: Return only the cleaned commit message. Do not add formatting (such a
 
 Commit message to clean:
 
-{}"#,
-        message
+{{message}}"#;
+
+/// Ask the LLM to add the parts of the repository's commit template that a
+/// message is missing
+pub fn follow_template(
+    templates: &PromptTemplates,
+    message: &str,
+    template_instructions: &str,
+    problems: &[String],
+) -> Result<String> {
+    render(
+        templates,
+        FOLLOW_TEMPLATE,
+        &[
+            ("problems", &problems.join("; ")),
+            ("template_instructions", template_instructions),
+            ("message", message),
+        ],
+    )
+}
+
+const FOLLOW_TEMPLATE_PROMPT: &str = r#"This commit message doesn't follow the repository's commit template: {{problems}}
+
+{{template_instructions}}
+Update the message so it has every required section and trailer. Keep the subject line and the rest of the content intact.
+IMPORTANT: Return only the updated commit message. Do not add formatting (such as code fences) or other explanations.
+
+Commit message to update:
+
+{{message}}"#;
+
+/// Ask the LLM to shorten a subject line that is over the length limit
+pub fn shorten_subject(
+    templates: &PromptTemplates,
+    message: &str,
+    max_length: usize,
+) -> Result<String> {
+    render(
+        templates,
+        SHORTEN,
+        &[
+            ("max_length", &max_length.to_string()),
+            ("message", message),
+        ],
     )
 }
+
+const SHORTEN_PROMPT: &str = r#"The first line of this commit message is too long. Rewrite it so it is at most {{max_length}} characters, keeping the conventional commit type and scope and the essential meaning. Move any detail that no longer fits into the body.
+
+Keep the rest of the message intact.
+IMPORTANT: Return only the updated commit message. Do not add formatting (such as code fences) or other explanations.
+
+Commit message to update:
+
+{{message}}"#;
+
+/// Ask the LLM to group staged files into separate commits (`--split`)
+pub fn split_plan_prompt(templates: &PromptTemplates, context: &str) -> Result<String> {
+    render(templates, SPLIT_PLAN, &[("context", context)])
+}
+
+const SPLIT_PLAN_PROMPT: &str = r#"The staged changes below may contain logically unrelated work. Group the changed files into the smallest number of commits where each commit is one coherent change (for example, a feature and its tests belong together, while an unrelated typo fix or dependency bump does not).
+
+Requirements:
+- Every changed file must appear in exactly one group.
+- Use the file paths exactly as listed (for renames, use the new path).
+- If all of the changes belong together, return a single group.
+- Order the groups so each commit makes sense on its own, e.g. refactors before the features that rely on them.
+
+Format your response like this:
+
+<observations>
+Notes on how the changes relate to each other
+</observations>
+<plan>
+<group>
+<description>One line describing this commit</description>
+<files>
+path/to/first/file
+path/to/second/file
+</files>
+</group>
+</plan>
+
+Here are the changes:
+
+{{context}}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_prompts_render() {
+        let templates = defaults();
+        let problems = vec!["missing Why:".to_string()];
+        let prompts = [
+            generate_commit_prompt(&templates, "DIFF").unwrap(),
+            generate_commit_prompt_summary_mode(&templates, "DIFF").unwrap(),
+            fix_message_format(&templates, "PROMPT", "RESPONSE").unwrap(),
+            fix_message_content(&templates, "MESSAGE", &problems).unwrap(),
+            follow_template(&templates, "MESSAGE", "Why:", &problems).unwrap(),
+            shorten_subject(&templates, "MESSAGE", 72).unwrap(),
+            split_plan_prompt(&templates, "DIFF").unwrap(),
+        ];
+
+        for prompt in &prompts {
+            assert!(
+                !prompt.contains("{{"),
+                "unrendered placeholder in {}",
+                prompt
+            );
+        }
+        assert!(prompts[2].contains("PROMPT") && prompts[2].contains("RESPONSE"));
+        assert!(prompts[5].contains("at most 72 characters"));
+    }
+
+    #[test]
+    fn test_prompts_can_be_overridden() {
+        let templates = defaults().with_default(
+            SHORTEN,
+            PromptTemplate::new(None, "Shorten to {{max_length}}: {{message}}"),
+        );
+        assert_eq!(
+            shorten_subject(&templates, "feat: x", 50).unwrap(),
+            "Shorten to 50: feat: x"
+        );
+    }
+}
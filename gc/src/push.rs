@@ -0,0 +1,110 @@
+// Pre-push checks and push handling
+
+use anyhow::{Context, Result};
+use std::process::Command;
+
+use crate::git;
+
+/// Options controlling how gc pushes after committing
+#[derive(Debug, Clone, Default)]
+pub struct PushOptions {
+    /// Use --force-with-lease (for rebased branches)
+    pub force_with_lease: bool,
+}
+
+/// The upstream the current branch tracks (e.g. `origin/main`), if any
+pub fn upstream() -> Option<String> {
+    git(&[
+        "rev-parse",
+        "--abbrev-ref",
+        "--symbolic-full-name",
+        "@{upstream}",
+    ])
+    .ok()
+    .map(|upstream| upstream.trim().to_string())
+    .filter(|upstream| !upstream.is_empty())
+}
+
+/// Build the arguments for `git push`.
+///
+/// When the branch has no upstream yet, pushes to origin and sets it as the
+/// upstream instead of letting git fail with "has no upstream branch".
+pub fn push_args(options: &PushOptions, branch: &str, upstream_exists: bool) -> Vec<String> {
+    let mut args = vec!["push".to_string()];
+
+    if options.force_with_lease {
+        args.push("--force-with-lease".to_string());
+    }
+
+    if !upstream_exists {
+        args.push("--set-upstream".to_string());
+        args.push("origin".to_string());
+        args.push(branch.to_string());
+    }
+
+    args
+}
+
+/// Push the current branch, setting its upstream to `origin/<branch>` when
+/// `upstream_exists` is false. Returns the upstream the branch tracks afterwards.
+pub fn push(options: &PushOptions, branch: &str, upstream_exists: bool) -> Result<String> {
+    let args = push_args(options, branch, upstream_exists);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    git(&args)?;
+    Ok(upstream().unwrap_or_else(|| format!("origin/{}", branch)))
+}
+
+/// Run each configured pre-push check through the shell, stopping at the first failure.
+///
+/// Check output is passed through to the terminal so failures are visible.
+pub fn run_pre_push_checks(checks: &[String]) -> Result<()> {
+    for check in checks {
+        println!("Running pre-push check: {}", check);
+
+        let status = Command::new("sh")
+            .args(["-c", check])
+            .status()
+            .with_context(|| format!("Failed to run pre-push check: {}", check))?;
+
+        if !status.success() {
+            anyhow::bail!("Pre-push check failed: {}", check);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_args_with_upstream() {
+        let args = push_args(&PushOptions::default(), "feature", true);
+        assert_eq!(args, vec!["push"]);
+    }
+
+    #[test]
+    fn test_push_args_without_upstream() {
+        let args = push_args(&PushOptions::default(), "feature", false);
+        assert_eq!(args, vec!["push", "--set-upstream", "origin", "feature"]);
+    }
+
+    #[test]
+    fn test_push_args_force_with_lease() {
+        let options = PushOptions {
+            force_with_lease: true,
+        };
+        let args = push_args(&options, "feature", true);
+        assert_eq!(args, vec!["push", "--force-with-lease"]);
+    }
+
+    #[test]
+    fn test_run_pre_push_checks() {
+        assert!(run_pre_push_checks(&[]).is_ok());
+        assert!(run_pre_push_checks(&["true".to_string()]).is_ok());
+
+        let err = run_pre_push_checks(&["true".to_string(), "exit 3".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("Pre-push check failed: exit 3"));
+    }
+}
@@ -0,0 +1,238 @@
+// Splitting staged changes into multiple commits
+
+use anyhow::Result;
+use std::collections::HashSet;
+
+use crate::extract_xml_tag;
+
+/// A changed file from `git diff --staged --name-status`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StagedFile {
+    pub path: String,
+    /// Original path for renames and copies, which must be staged with `path`
+    pub old_path: Option<String>,
+}
+
+impl StagedFile {
+    /// Paths to pass to `git add` to stage this change
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.path.as_str()).chain(self.old_path.as_deref())
+    }
+}
+
+/// One proposed commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitGroup {
+    pub description: String,
+    pub files: Vec<StagedFile>,
+}
+
+impl CommitGroup {
+    pub fn paths(&self) -> Vec<&str> {
+        self.files.iter().flat_map(StagedFile::paths).collect()
+    }
+}
+
+/// Parse `git diff --staged --name-status` output
+pub fn parse_name_status(name_status: &str) -> Vec<StagedFile> {
+    name_status
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split('\t').collect();
+            match parts.as_slice() {
+                [status, old, new] if status.starts_with('R') || status.starts_with('C') => {
+                    Some(StagedFile {
+                        path: new.to_string(),
+                        old_path: Some(old.to_string()),
+                    })
+                }
+                [_, path, ..] => Some(StagedFile {
+                    path: path.to_string(),
+                    old_path: None,
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Parse the `<plan>` section of the LLM's response into groups of paths
+pub fn parse_plan(response: &str) -> Result<Vec<(String, Vec<String>)>> {
+    let plan = extract_xml_tag(response, "plan")
+        .ok_or_else(|| anyhow::anyhow!("Response missing '<plan>' section"))?;
+
+    let mut groups = Vec::new();
+    let mut rest = plan.as_str();
+    while let Some(start) = rest.find("<group>") {
+        let after = &rest[start + "<group>".len()..];
+        let end = after.find("</group>").unwrap_or(after.len());
+        let group = &after[..end];
+        rest = &after[end..];
+
+        let description = extract_xml_tag(group, "description").unwrap_or_default();
+        let files: Vec<String> = extract_xml_tag(group, "files")
+            .unwrap_or_default()
+            .lines()
+            .map(|line| line.trim().trim_start_matches("- ").trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect();
+        if !files.is_empty() {
+            groups.push((description, files));
+        }
+    }
+
+    if groups.is_empty() {
+        anyhow::bail!("Plan contains no groups of files");
+    }
+    Ok(groups)
+}
+
+/// Match the planned paths against the staged files. Unknown paths and
+/// repeats are dropped, and staged files the plan left out get their own
+/// final group so nothing is lost.
+pub fn reconcile(planned: Vec<(String, Vec<String>)>, staged: &[StagedFile]) -> Vec<CommitGroup> {
+    let mut assigned: HashSet<&str> = HashSet::new();
+    let mut groups: Vec<CommitGroup> = planned
+        .into_iter()
+        .map(|(description, paths)| {
+            let files = paths
+                .iter()
+                .filter_map(|path| staged.iter().find(|f| f.path == *path))
+                .filter(|file| assigned.insert(file.path.as_str()))
+                .cloned()
+                .collect();
+            CommitGroup { description, files }
+        })
+        .filter(|group| !group.files.is_empty())
+        .collect();
+
+    let remaining: Vec<StagedFile> = staged
+        .iter()
+        .filter(|file| !assigned.contains(file.path.as_str()))
+        .cloned()
+        .collect();
+    if !remaining.is_empty() {
+        groups.push(CommitGroup {
+            description: "Remaining changes".to_string(),
+            files: remaining,
+        });
+    }
+
+    groups
+}
+
+/// The plan as shown to the user for confirmation
+pub fn format_plan(groups: &[CommitGroup]) -> String {
+    let mut output = String::new();
+    for (i, group) in groups.iter().enumerate() {
+        output.push_str(&format!("{}. {}\n", i + 1, group.description));
+        for file in &group.files {
+            match &file.old_path {
+                Some(old) => output.push_str(&format!("     {} -> {}\n", old, file.path)),
+                None => output.push_str(&format!("     {}\n", file.path)),
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str) -> StagedFile {
+        StagedFile {
+            path: path.to_string(),
+            old_path: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_name_status() {
+        let files = parse_name_status("M\tsrc/main.rs\nR087\told.rs\tnew.rs\nD\tgone.rs\n");
+        assert_eq!(
+            files,
+            vec![
+                file("src/main.rs"),
+                StagedFile {
+                    path: "new.rs".to_string(),
+                    old_path: Some("old.rs".to_string()),
+                },
+                file("gone.rs"),
+            ]
+        );
+        assert_eq!(
+            files[1].paths().collect::<Vec<_>>(),
+            vec!["new.rs", "old.rs"]
+        );
+    }
+
+    #[test]
+    fn test_parse_plan() {
+        let response = r#"<observations>Two unrelated changes</observations>
+<plan>
+<group>
+<description>Add login form</description>
+<files>
+src/login.rs
+- tests/login.rs
+</files>
+</group>
+<group>
+<description>Fix typo in README</description>
+<files>
+README.md
+</files>
+</group>
+</plan>"#;
+        let plan = parse_plan(response).unwrap();
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan[0].0, "Add login form");
+        assert_eq!(plan[0].1, vec!["src/login.rs", "tests/login.rs"]);
+        assert_eq!(plan[1].1, vec!["README.md"]);
+
+        assert!(parse_plan("no plan here").is_err());
+        assert!(parse_plan("<plan></plan>").is_err());
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let staged = vec![file("a.rs"), file("b.rs"), file("c.rs"), file("d.rs")];
+        let planned = vec![
+            (
+                "First".to_string(),
+                vec!["a.rs".to_string(), "unknown.rs".to_string()],
+            ),
+            (
+                "Second".to_string(),
+                vec!["b.rs".to_string(), "a.rs".to_string()],
+            ),
+            ("Empty".to_string(), vec!["unknown.rs".to_string()]),
+        ];
+
+        let groups = reconcile(planned, &staged);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].paths(), vec!["a.rs"]);
+        assert_eq!(groups[1].paths(), vec!["b.rs"]);
+        assert_eq!(groups[2].description, "Remaining changes");
+        assert_eq!(groups[2].paths(), vec!["c.rs", "d.rs"]);
+    }
+
+    #[test]
+    fn test_format_plan() {
+        let groups = vec![CommitGroup {
+            description: "Rename module".to_string(),
+            files: vec![
+                StagedFile {
+                    path: "new.rs".to_string(),
+                    old_path: Some("old.rs".to_string()),
+                },
+                file("lib.rs"),
+            ],
+        }];
+        assert_eq!(
+            format_plan(&groups),
+            "1. Rename module\n     old.rs -> new.rs\n     lib.rs\n"
+        );
+    }
+}
@@ -0,0 +1,277 @@
+// Changed symbol extraction
+//
+// Hunk headers in large files often point at an unrelated enclosing line, so
+// the diff alone can leave the LLM guessing which functions and types changed.
+// This scans each hunk's header and changed lines for definitions in common
+// languages and lists the names per file.
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Maximum number of symbols listed per file before the rest are summarized
+const MAX_SYMBOLS_PER_FILE: usize = 15;
+
+static RUST: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    patterns(&[
+        r#"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:(?:const|async|unsafe|extern\s+"[^"]*")\s+)*(?:fn|struct|enum|trait|type|mod|union)\s+([A-Za-z_]\w*)"#,
+        r"^\s*macro_rules!\s*([A-Za-z_]\w*)",
+        r"^\s*(?:unsafe\s+)?impl(?:<[^>]*>)?\s+(?:[\w:]+(?:<[^>]*>)?\s+for\s+)?([A-Za-z_]\w*)",
+    ])
+});
+
+static PYTHON: LazyLock<Vec<Regex>> =
+    LazyLock::new(|| patterns(&[r"^\s*(?:async\s+)?(?:def|class)\s+([A-Za-z_]\w*)"]));
+
+static JAVASCRIPT: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    patterns(&[
+        r"^\s*(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?(?:async\s+)?(?:function\*?|class|interface|type|enum)\s+([A-Za-z_$][\w$]*)",
+        r"^\s*(?:export\s+)?(?:const|let|var)\s+([A-Za-z_$][\w$]*)\s*(?::[^=]+)?=\s*(?:async\s+)?(?:function\b|\([^)]*\)\s*(?::[^=]+)?=>|[A-Za-z_$][\w$]*\s*=>)",
+    ])
+});
+
+static GO: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    patterns(&[
+        r"^func\s+(?:\([^)]*\)\s*)?([A-Za-z_]\w*)",
+        r"^\s*type\s+([A-Za-z_]\w*)",
+    ])
+});
+
+static JVM: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    patterns(&[
+        r"^\s*(?:(?:public|private|protected|internal|static|final|abstract|sealed|data|open|partial)\s+)*(?:class|interface|enum|record|object|struct)\s+([A-Za-z_]\w*)",
+        r"^\s*(?:(?:public|private|protected|internal|override|suspend|inline|open)\s+)*fun\s+(?:<[^>]*>\s*)?(?:[\w.]+\.)?([A-Za-z_]\w*)",
+        r"^\s*(?:(?:public|private|protected|internal|static|final|abstract|synchronized|override|async|virtual)\s+)+[\w<>\[\],.? ]+?\s+([A-Za-z_]\w*)\s*\(",
+    ])
+});
+
+static RUBY: LazyLock<Vec<Regex>> =
+    LazyLock::new(|| patterns(&[r"^\s*(?:def|class|module)\s+(?:self\.)?([A-Za-z_]\w*[?!]?)"]));
+
+static SHELL: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    patterns(&[
+        r"^\s*function\s+([A-Za-z_][\w-]*)",
+        r"^\s*([A-Za-z_][\w-]*)\s*\(\)",
+    ])
+});
+
+fn patterns(sources: &[&str]) -> Vec<Regex> {
+    sources
+        .iter()
+        .map(|source| Regex::new(source).expect("symbol pattern is valid"))
+        .collect()
+}
+
+/// Definition patterns for a file, chosen by extension
+fn patterns_for(path: &str) -> Option<&'static [Regex]> {
+    let extension = path.rsplit_once('.')?.1;
+    let patterns: &Vec<Regex> = match extension {
+        "rs" => &RUST,
+        "py" | "pyi" => &PYTHON,
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => &JAVASCRIPT,
+        "go" => &GO,
+        "java" | "kt" | "kts" | "scala" | "cs" | "swift" => &JVM,
+        "rb" => &RUBY,
+        "sh" | "bash" | "zsh" => &SHELL,
+        _ => return None,
+    };
+    Some(patterns)
+}
+
+/// Symbols touched in one file
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileSymbols {
+    pub path: String,
+    pub symbols: Vec<String>,
+}
+
+/// Path from a `diff --git` line produced with `--no-prefix`
+fn diff_path(header: &str) -> &str {
+    // Both sides are identical unless the file was renamed, so prefer an
+    // exact split in half; that keeps paths containing spaces intact
+    let mid = header.len() / 2;
+    // `get` fails when the midpoint falls inside a multi-byte character
+    if header.len() % 2 == 1
+        && let (Some(old), Some(new)) = (header.get(..mid), header.get(mid + 1..))
+        && old == new
+        && header.as_bytes()[mid] == b' '
+    {
+        return new;
+    }
+    header.rsplit(' ').next().unwrap_or(header)
+}
+
+/// First definition name found on a line of source
+fn symbol_on_line(line: &str, patterns: &[Regex]) -> Option<String> {
+    patterns
+        .iter()
+        .find_map(|pattern| pattern.captures(line))
+        .and_then(|captures| captures.get(1))
+        .map(|name| name.as_str().to_string())
+}
+
+/// Extract the functions and types touched by each file in a unified diff.
+///
+/// Names come from hunk header context (the enclosing definition git reports)
+/// and from definitions on added or removed lines. Files in languages without
+/// patterns, and files where nothing was recognized, are omitted.
+pub fn extract_changed_symbols(diff: &str) -> Vec<FileSymbols> {
+    let mut files: Vec<FileSymbols> = Vec::new();
+    let mut patterns: Option<&[Regex]> = None;
+
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            let path = diff_path(header);
+            patterns = patterns_for(path);
+            files.push(FileSymbols {
+                path: path.to_string(),
+                symbols: Vec::new(),
+            });
+            continue;
+        }
+
+        let Some(patterns) = patterns else {
+            continue;
+        };
+
+        let source = if let Some(hunk) = line.strip_prefix("@@") {
+            // "@@ -1,2 +1,3 @@ fn enclosing() {"
+            hunk.split_once("@@").map(|(_, context)| context.trim())
+        } else {
+            line.strip_prefix('+').or_else(|| line.strip_prefix('-'))
+        };
+
+        if let Some(source) = source
+            && let Some(symbol) = symbol_on_line(source, patterns)
+            && let Some(file) = files.last_mut()
+            && !file.symbols.contains(&symbol)
+        {
+            file.symbols.push(symbol);
+        }
+    }
+
+    files.retain(|file| !file.symbols.is_empty());
+    files
+}
+
+/// Render symbols as a "Changed symbols" section, or None if there are none
+pub fn format_changed_symbols(files: &[FileSymbols]) -> Option<String> {
+    if files.is_empty() {
+        return None;
+    }
+
+    let mut out = String::from("Changed symbols:\n");
+    for file in files {
+        let shown = file.symbols.len().min(MAX_SYMBOLS_PER_FILE);
+        out.push_str(&format!(
+            "- {}: {}",
+            file.path,
+            file.symbols[..shown].join(", ")
+        ));
+        if file.symbols.len() > shown {
+            out.push_str(&format!(" (+{} more)", file.symbols.len() - shown));
+        }
+        out.push('\n');
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git src/main.rs src/main.rs
+@@ -10,2 +10,3 @@ impl Config {
+     let x = 1;
++    let y = 2;
+@@ -40,1 +41,4 @@ fn main() {
++pub(crate) async fn load_settings(path: &Path) -> Result<()> {
++}
++pub struct Settings {
+diff --git web/app.ts web/app.ts
+@@ -1,1 +1,2 @@
++export const handleClick = async (event) => {
+-function oldHandler() {
+diff --git README.md README.md
+@@ -1,1 +1,1 @@
+-# Old
++# New";
+
+    #[test]
+    fn test_extract_changed_symbols() {
+        let files = extract_changed_symbols(DIFF);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "src/main.rs");
+        assert_eq!(
+            files[0].symbols,
+            vec!["Config", "main", "load_settings", "Settings"]
+        );
+        assert_eq!(files[1].path, "web/app.ts");
+        assert_eq!(files[1].symbols, vec!["handleClick", "oldHandler"]);
+    }
+
+    #[test]
+    fn test_extract_changed_symbols_dedupes() {
+        let diff =
+            "diff --git a.py a.py\n@@ -1 +1 @@ def run(self):\n-def run(self):\n+def run(self, x):";
+        let files = extract_changed_symbols(diff);
+        assert_eq!(files[0].symbols, vec!["run"]);
+    }
+
+    #[test]
+    fn test_language_patterns() {
+        let cases = [
+            ("x.rs", "impl<T> Display for Wrapper<T> {", "Wrapper"),
+            ("x.rs", "    pub fn new() -> Self {", "new"),
+            ("x.rs", "macro_rules! bail_out {", "bail_out"),
+            ("x.py", "    async def fetch(self):", "fetch"),
+            ("x.go", "func (s *Server) Start() error {", "Start"),
+            ("x.go", "type Server struct {", "Server"),
+            (
+                "X.java",
+                "    public static void main(String[] args) {",
+                "main",
+            ),
+            ("X.kt", "    override suspend fun load(): Int {", "load"),
+            ("x.rb", "  def self.call", "call"),
+            ("x.sh", "cleanup() {", "cleanup"),
+        ];
+        for (path, line, expected) in cases {
+            let patterns = patterns_for(path).unwrap();
+            assert_eq!(
+                symbol_on_line(line, patterns).as_deref(),
+                Some(expected),
+                "{}: {}",
+                path,
+                line
+            );
+        }
+        assert!(symbol_on_line("    let value = compute();", &RUST).is_none());
+        assert!(patterns_for("notes.md").is_none());
+        assert!(patterns_for("Makefile").is_none());
+    }
+
+    #[test]
+    fn test_diff_path() {
+        assert_eq!(diff_path("src/main.rs src/main.rs"), "src/main.rs");
+        assert_eq!(diff_path("my file.rs my file.rs"), "my file.rs");
+        assert_eq!(diff_path("old.rs new.rs"), "new.rs");
+        assert_eq!(diff_path("é é"), "é");
+        assert_eq!(diff_path("aé b"), "b");
+        assert_eq!(diff_path("ñ.rs ab.rs"), "ab.rs");
+    }
+
+    #[test]
+    fn test_format_changed_symbols() {
+        assert!(format_changed_symbols(&[]).is_none());
+
+        let files = vec![FileSymbols {
+            path: "big.rs".to_string(),
+            symbols: (0..17).map(|i| format!("f{}", i)).collect(),
+        }];
+        let formatted = format_changed_symbols(&files).unwrap();
+        assert!(formatted.starts_with("Changed symbols:\n- big.rs: f0, f1,"));
+        assert!(formatted.contains("f14 (+2 more)\n"));
+        assert!(!formatted.contains("f15"));
+    }
+}
@@ -0,0 +1,228 @@
+// Commit message templates (commit.template, usually a .gitmessage file)
+
+use anyhow::{Context, Result};
+use git2::{ErrorCode, Repository};
+use std::fs;
+
+/// The structure a repository's commit template asks for
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitTemplate {
+    /// The template as written, including its comments
+    text: String,
+    /// Body lines ending in a colon (e.g. "Why:") that the message must contain
+    pub sections: Vec<String>,
+    /// Trailer keys from the template's closing block (e.g. "Refs")
+    pub trailers: Vec<String>,
+}
+
+impl CommitTemplate {
+    /// Load the template configured in `commit.template` for the current
+    /// repository or user, if there is one
+    pub fn load() -> Result<Option<Self>> {
+        let Ok(repo) = Repository::discover(".") else {
+            return Ok(None);
+        };
+        let path = match repo.config()?.get_path("commit.template") {
+            Ok(path) => path,
+            Err(e) if e.code() == ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(e).context("Failed to read commit.template"),
+        };
+        let path = match repo.workdir() {
+            Some(workdir) if path.is_relative() => workdir.join(path),
+            _ => path,
+        };
+
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read commit template: {}", path.display()))?;
+        Ok(Some(Self::parse(&text)))
+    }
+
+    /// Find the section headings and required trailers in a template.
+    ///
+    /// Comment lines are ignored, as git strips them, and so is the first
+    /// line, which is a placeholder for the subject.
+    pub fn parse(text: &str) -> Self {
+        let lines: Vec<&str> = text
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.starts_with('#'))
+            .skip_while(|line| line.is_empty())
+            .skip(1)
+            .collect();
+
+        let paragraphs: Vec<Vec<&str>> = lines
+            .split(|line| line.is_empty())
+            .filter(|paragraph| !paragraph.is_empty())
+            .map(<[&str]>::to_vec)
+            .collect();
+
+        let trailer_block = paragraphs
+            .last()
+            .filter(|paragraph| paragraph.iter().all(|line| trailer_key(line).is_some()));
+        let trailers = trailer_block
+            .map(|paragraph| {
+                paragraph
+                    .iter()
+                    .filter_map(|line| trailer_key(line))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let body = match trailer_block {
+            Some(_) => &paragraphs[..paragraphs.len() - 1],
+            None => &paragraphs[..],
+        };
+        let sections = body
+            .iter()
+            .flatten()
+            .map(|line| line.trim())
+            .filter(|line| line.ends_with(':'))
+            .map(str::to_string)
+            .collect();
+
+        Self {
+            text: text.trim_end().to_string(),
+            sections,
+            trailers,
+        }
+    }
+
+    /// Whether the template asks for anything gc can check
+    pub fn has_requirements(&self) -> bool {
+        !self.sections.is_empty() || !self.trailers.is_empty()
+    }
+
+    /// Prompt text asking the LLM to follow the template
+    pub fn instructions(&self) -> String {
+        let mut text = format!(
+            "This repository has a commit message template. Follow its structure, but keep the Conventional Commit subject line. Lines starting with # are guidance and must not appear in the message.\n\nTemplate:\n{}\n",
+            self.text
+        );
+        if !self.sections.is_empty() {
+            text.push_str(&format!(
+                "\nThe body must contain these section headings, each on its own line, in this order: {}\n",
+                self.sections.join(", ")
+            ));
+        }
+        if !self.trailers.is_empty() {
+            text.push_str(&format!(
+                "\nThe message must end with these trailers, filled in from the branch name or the user's context when known: {}\n",
+                self.trailers
+                    .iter()
+                    .map(|key| format!("{}:", key))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        text
+    }
+
+    /// Describe each required section or trailer missing from a message,
+    /// and sections that aren't in the template's order
+    pub fn check(&self, message: &str) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        let mut positions = Vec::new();
+        for section in &self.sections {
+            match message
+                .lines()
+                .position(|line| line.trim().eq_ignore_ascii_case(section))
+            {
+                Some(position) => positions.push(position),
+                None => problems.push(format!("Missing section: {}", section)),
+            }
+        }
+        if !positions.is_sorted() {
+            problems.push(format!(
+                "Sections out of order (expected {})",
+                self.sections.join(", ")
+            ));
+        }
+
+        for key in &self.trailers {
+            if !message
+                .lines()
+                .any(|line| trailer_key(line).is_some_and(|k| k.eq_ignore_ascii_case(key)))
+            {
+                problems.push(format!("Missing trailer: {}", key));
+            }
+        }
+
+        problems
+    }
+}
+
+/// The key of a `Key: value` trailer line, which may have an empty value in a template
+fn trailer_key(line: &str) -> Option<&str> {
+    let (key, _) = line.split_once(':')?;
+    let valid = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    valid.then_some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "\
+# <type>(<scope>): <subject>
+<type>: <subject>
+
+# Why is this change needed?
+Why:
+
+# What does it change?
+What:
+- <details>
+
+Refs:
+Reviewed-by: <name>
+# Trailers above are required
+";
+
+    #[test]
+    fn test_parse() {
+        let template = CommitTemplate::parse(TEMPLATE);
+        assert_eq!(template.sections, vec!["Why:", "What:"]);
+        assert_eq!(template.trailers, vec!["Refs", "Reviewed-by"]);
+        assert!(template.has_requirements());
+    }
+
+    #[test]
+    fn test_parse_without_trailers() {
+        let template = CommitTemplate::parse("Summary\n\nDetails:\nmore text\n");
+        assert_eq!(template.sections, vec!["Details:"]);
+        assert!(template.trailers.is_empty());
+
+        let plain = CommitTemplate::parse("# Write a good message\n\n");
+        assert!(!plain.has_requirements());
+    }
+
+    #[test]
+    fn test_check() {
+        let template = CommitTemplate::parse(TEMPLATE);
+        let complete = "feat: add login\n\nWhy:\nUsers asked.\n\nWhat:\n- form\n\nRefs: APP-12\nReviewed-by: Sam";
+        assert!(template.check(complete).is_empty());
+
+        let partial = "feat: add login\n\nwhy:\nUsers asked.\n\nrefs: APP-12";
+        assert_eq!(
+            template.check(partial),
+            vec!["Missing section: What:", "Missing trailer: Reviewed-by"]
+        );
+
+        let reordered = "feat: add login\n\nWhat:\n- form\n\nWhy:\nUsers asked.\n\nRefs: APP-12\nReviewed-by: Sam";
+        assert_eq!(
+            template.check(reordered),
+            vec!["Sections out of order (expected Why:, What:)"]
+        );
+    }
+
+    #[test]
+    fn test_instructions() {
+        let instructions = CommitTemplate::parse(TEMPLATE).instructions();
+        assert!(instructions.contains("Template:\n# <type>(<scope>): <subject>"));
+        assert!(instructions.contains("in this order: Why:, What:"));
+        assert!(instructions.contains("trailers, filled in"));
+        assert!(instructions.contains("Refs:, Reviewed-by:"));
+    }
+}
@@ -0,0 +1,149 @@
+// Provenance trailers for generated commit messages
+
+/// Trailer keys gc adds with `--trailer`
+const GENERATED_BY: &str = "Generated-by";
+const MODEL: &str = "Model";
+
+/// Trailers recording that gc generated the message and which model wrote it
+pub fn provenance(model: &str) -> Vec<(&'static str, String)> {
+    vec![
+        (GENERATED_BY, format!("gc v{}", env!("CARGO_PKG_VERSION"))),
+        (MODEL, model.to_string()),
+    ]
+}
+
+/// Whether a message line is one of the trailers gc adds
+pub fn is_gc_trailer(line: &str) -> bool {
+    [GENERATED_BY, MODEL].iter().any(|key| {
+        line.strip_prefix(key)
+            .is_some_and(|rest| rest.starts_with(": "))
+    })
+}
+
+/// Split a message into its body and the trailer block ending it, if the
+/// last paragraph is one. A subject line alone is never a trailer block.
+fn split_trailer_block(message: &str) -> Option<(&str, &str)> {
+    let (body, last_paragraph) = message.rsplit_once("\n\n")?;
+    last_paragraph
+        .lines()
+        .all(is_trailer_line)
+        .then_some((body, last_paragraph))
+}
+
+/// The message without the trailers gc added. Only the trailer block ending
+/// the message is considered; trailer-like lines elsewhere are kept.
+pub fn strip(message: &str) -> String {
+    let message = message.trim_end();
+    let Some((body, block)) = split_trailer_block(message) else {
+        return message.to_string();
+    };
+    let kept: Vec<&str> = block.lines().filter(|line| !is_gc_trailer(line)).collect();
+    if kept.is_empty() {
+        body.trim_end().to_string()
+    } else {
+        format!("{}\n\n{}", body, kept.join("\n"))
+    }
+}
+
+/// Append trailers to a message, joining an existing trailer block if the
+/// message ends with one. gc trailers already in the message (e.g. when
+/// amending a commit that had them) are replaced.
+pub fn append(message: &str, trailers: &[(&str, String)]) -> String {
+    let message = strip(message);
+    let lines: Vec<String> = trailers
+        .iter()
+        .map(|(key, value)| format!("{}: {}", key, value))
+        .collect();
+
+    let separator = if split_trailer_block(&message).is_some() {
+        "\n"
+    } else {
+        "\n\n"
+    };
+
+    format!("{}{}{}", message, separator, lines.join("\n"))
+}
+
+/// Whether a line looks like a git trailer (`Token: value`)
+pub fn is_trailer_line(line: &str) -> bool {
+    line.split_once(": ").is_some_and(|(key, _)| {
+        !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trailers() -> Vec<(&'static str, String)> {
+        vec![
+            ("Generated-by", "gc v1.3.0".to_string()),
+            ("Model", "claude-sonnet-4".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_provenance() {
+        let trailers = provenance("sonnet");
+        assert_eq!(
+            trailers[0],
+            ("Generated-by", format!("gc v{}", env!("CARGO_PKG_VERSION")))
+        );
+        assert_eq!(trailers[1], ("Model", "sonnet".to_string()));
+    }
+
+    #[test]
+    fn test_append() {
+        assert_eq!(
+            append("feat: add login", &trailers()),
+            "feat: add login\n\nGenerated-by: gc v1.3.0\nModel: claude-sonnet-4"
+        );
+        assert_eq!(
+            append("feat: add login\n\nAdds a login form.\n", &trailers()),
+            "feat: add login\n\nAdds a login form.\n\nGenerated-by: gc v1.3.0\nModel: claude-sonnet-4"
+        );
+    }
+
+    #[test]
+    fn test_append_joins_existing_trailers() {
+        assert_eq!(
+            append("fix: handle timeouts\n\nRefs: ABC-12", &trailers()),
+            "fix: handle timeouts\n\nRefs: ABC-12\nGenerated-by: gc v1.3.0\nModel: claude-sonnet-4"
+        );
+        // A subject line alone is never treated as a trailer block
+        assert_eq!(
+            append("docs: update", &[("Model", "x".to_string())]),
+            "docs: update\n\nModel: x"
+        );
+    }
+
+    #[test]
+    fn test_append_replaces_gc_trailers() {
+        let amended = "feat: add login\n\nGenerated-by: gc v1.2.0\nModel: old-model";
+        assert_eq!(
+            append(amended, &trailers()),
+            "feat: add login\n\nGenerated-by: gc v1.3.0\nModel: claude-sonnet-4"
+        );
+    }
+
+    #[test]
+    fn test_strip_only_the_trailer_block() {
+        assert_eq!(
+            strip("feat: add login\n\nRefs: ABC-12\nModel: x\nGenerated-by: gc v1.3.0\n"),
+            "feat: add login\n\nRefs: ABC-12"
+        );
+        assert_eq!(
+            strip("feat: add login\n\nModel: https://example.com\n\nAdds a form."),
+            "feat: add login\n\nModel: https://example.com\n\nAdds a form."
+        );
+        assert_eq!(strip("Model: x"), "Model: x");
+    }
+
+    #[test]
+    fn test_is_gc_trailer() {
+        assert!(is_gc_trailer("Generated-by: gc v1.3.0"));
+        assert!(is_gc_trailer("Model: anthropic/claude-3.5-sonnet"));
+        assert!(!is_gc_trailer("Model changes are now cached"));
+        assert!(!is_gc_trailer("Refs: ABC-12"));
+    }
+}
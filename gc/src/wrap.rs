@@ -0,0 +1,153 @@
+// Commit message body wrapping
+
+use crate::trailers;
+
+/// Wrap body lines longer than `width` characters, leaving the subject line,
+/// code (fenced or indented), and a closing trailer block untouched. List
+/// items wrap with a hanging indent. A `width` of 0 disables wrapping.
+pub fn wrap_body(message: &str, width: usize) -> String {
+    let lines: Vec<&str> = message.lines().collect();
+    if width == 0 || lines.len() < 2 {
+        return message.to_string();
+    }
+
+    let trailers_start = trailer_block_start(&lines);
+    let mut output = vec![lines[0].to_string()];
+    let mut in_fence = false;
+
+    for (i, line) in lines.iter().enumerate().skip(1) {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            output.push(line.to_string());
+            continue;
+        }
+
+        let keep = in_fence
+            || i >= trailers_start
+            || line.chars().count() <= width
+            || line.starts_with("    ")
+            || line.starts_with('\t');
+        if keep {
+            output.push(line.to_string());
+        } else {
+            output.extend(wrap_line(line, width));
+        }
+    }
+
+    output.join("\n")
+}
+
+/// Index of the first line of the trailer block ending the message, or
+/// `lines.len()` if there isn't one
+fn trailer_block_start(lines: &[&str]) -> usize {
+    let Some(blank) = lines.iter().rposition(|line| line.trim().is_empty()) else {
+        return lines.len();
+    };
+    let last_paragraph = &lines[blank + 1..];
+    if !last_paragraph.is_empty()
+        && last_paragraph
+            .iter()
+            .all(|line| trailers::is_trailer_line(line))
+    {
+        blank + 1
+    } else {
+        lines.len()
+    }
+}
+
+/// Wrap one line at word boundaries, indenting continuation lines to line up
+/// with the text after any leading whitespace and list marker. Words longer
+/// than the width are kept whole.
+fn wrap_line(line: &str, width: usize) -> Vec<String> {
+    let prefix_len = line.len() - line.trim_start().len() + list_marker_len(line.trim_start());
+    let (prefix, text) = line.split_at(prefix_len);
+    let indent = " ".repeat(prefix.chars().count());
+
+    let mut wrapped = Vec::new();
+    let mut current = prefix.to_string();
+    let mut current_len = prefix.chars().count();
+    let mut has_words = false;
+
+    for word in text.split_whitespace() {
+        let word_len = word.chars().count();
+        if has_words && current_len + 1 + word_len > width {
+            wrapped.push(std::mem::replace(&mut current, indent.clone()));
+            current_len = indent.len();
+            has_words = false;
+        }
+        if has_words {
+            current.push(' ');
+            current_len += 1;
+        }
+        current.push_str(word);
+        current_len += word_len;
+        has_words = true;
+    }
+
+    wrapped.push(current);
+    wrapped
+}
+
+/// Length of a `- `, `* `, or `1. ` list marker at the start of `text`
+fn list_marker_len(text: &str) -> usize {
+    if text.starts_with("- ") || text.starts_with("* ") {
+        return 2;
+    }
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 && text[digits..].starts_with(". ") {
+        digits + 2
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_body() {
+        let message = "feat: add session timeout\n\nSessions now expire after thirty minutes of inactivity, and the timeout can be configured per deployment.";
+        assert_eq!(
+            wrap_body(message, 40),
+            "feat: add session timeout\n\nSessions now expire after thirty minutes\nof inactivity, and the timeout can be\nconfigured per deployment."
+        );
+        // Short lines and a width of 0 are left alone
+        assert_eq!(wrap_body(message, 0), message);
+        assert_eq!(
+            wrap_body("fix: typo\n\nShort body.", 40),
+            "fix: typo\n\nShort body."
+        );
+    }
+
+    #[test]
+    fn test_wrap_body_keeps_subject_and_trailers() {
+        let subject = "feat: a subject line that is longer than the wrapping width";
+        let trailer = "Reviewed-by: Someone With A Rather Long Name <someone@example.com>";
+        let message = format!("{}\n\nBody.\n\n{}", subject, trailer);
+        assert_eq!(wrap_body(&message, 30), message);
+    }
+
+    #[test]
+    fn test_wrap_body_list_items() {
+        let message = "refactor: split config loading\n\n- Move parsing into its own module so it can be tested in isolation\n12. Numbered items wrap with a hanging indent as well";
+        assert_eq!(
+            wrap_body(message, 36),
+            "refactor: split config loading\n\n- Move parsing into its own module\n  so it can be tested in isolation\n12. Numbered items wrap with a\n    hanging indent as well"
+        );
+    }
+
+    #[test]
+    fn test_wrap_body_keeps_code() {
+        let message = "fix: escape paths\n\n    let path = format!(\"{}/{}\", root.display(), name.to_lowercase());\n```\nsome --very --long --command --line --that --should --not --wrap\n```";
+        assert_eq!(wrap_body(message, 30), message);
+    }
+
+    #[test]
+    fn test_wrap_body_long_word() {
+        assert_eq!(
+            wrap_body("docs: note\n\nSee averyveryverylongidentifier here", 10),
+            "docs: note\n\nSee\naveryveryverylongidentifier\nhere"
+        );
+    }
+}
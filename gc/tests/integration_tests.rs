@@ -6,7 +6,7 @@ use predicates::prelude::*;
 use tempfile::TempDir;
 
 fn gc_cmd() -> Command {
-    cargo_bin_cmd!("gc").into()
+    cargo_bin_cmd!("gc")
 }
 
 #[test]
@@ -36,6 +36,71 @@ fn test_not_in_git_repo() {
         .stderr(predicate::str::contains("Not in a git repository"));
 }
 
+/// Run git in `dir` with a fixed identity
+fn git_in(dir: &std::path::Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .env("GIT_AUTHOR_NAME", "Test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+#[test]
+fn test_force_requires_amend() {
+    gc_cmd()
+        .arg("--force")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--amend"));
+}
+
+#[test]
+fn test_split_conflicts_with_amend() {
+    gc_cmd()
+        .args(["--split", "--amend"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_amend_refuses_pushed_commit() {
+    let temp_dir = TempDir::new().unwrap();
+    let remote = temp_dir.path().join("remote.git");
+    let repo = temp_dir.path().join("repo");
+    git_in(
+        temp_dir.path(),
+        &["init", "-q", "--bare", remote.to_str().unwrap()],
+    );
+    git_in(
+        temp_dir.path(),
+        &["init", "-q", "-b", "main", repo.to_str().unwrap()],
+    );
+    git_in(
+        &repo,
+        &["commit", "-q", "--allow-empty", "-m", "feat: first"],
+    );
+    git_in(
+        &repo,
+        &["remote", "add", "origin", remote.to_str().unwrap()],
+    );
+    git_in(&repo, &["push", "-q", "-u", "origin", "main"]);
+
+    gc_cmd()
+        .current_dir(&repo)
+        .arg("--amend")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "already been pushed (origin/main)",
+        ));
+}
+
 // TODO: Add more integration tests
 // - Test with staged changes
 // - Test with unstaged changes
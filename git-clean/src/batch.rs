@@ -0,0 +1,427 @@
+// Cleanup of many repositories in one run (--all-repos, --watched-repos)
+//
+// Each repository is fetched and inventoried first, so a single plan table
+// covers all of them and one confirmation deletes everything. The git helpers
+// work on the current directory, so the run changes into each repository.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::GitCleanConfig;
+use crate::interactive::{self, BranchLocation};
+use crate::inventory::Inventory;
+use crate::report::{Report, Summary};
+use crate::{OutputFormat, delete_local_branch_safe, delete_remote_branch, get_main_branch, git};
+
+/// A repository's inventory or report, or why it couldn't be processed
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum RepoEntry<T> {
+    Done(T),
+    Failed { repository: String, error: String },
+}
+
+/// Cleanup results for every repository, with totals
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchReport {
+    pub summary: Summary,
+    pub repositories: Vec<RepoEntry<Report>>,
+}
+
+/// Inventories of every repository, for a dry run
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchInventory {
+    pub repositories: Vec<RepoEntry<Inventory>>,
+}
+
+/// Git repositories directly inside `parent`, sorted by path. The paths are
+/// absolute, as the run changes directory between repositories.
+pub fn find_repos(parent: &Path) -> Result<Vec<PathBuf>> {
+    let parent = parent
+        .canonicalize()
+        .with_context(|| format!("Failed to read directory: {}", parent.display()))?;
+    let mut repos = Vec::new();
+    for entry in fs::read_dir(&parent)
+        .with_context(|| format!("Failed to read directory: {}", parent.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() && path.join(".git").exists() {
+            repos.push(path);
+        }
+    }
+    repos.sort();
+    Ok(repos)
+}
+
+/// The directories watched by track-changes
+#[derive(Debug, Deserialize)]
+struct TrackChangesConfig {
+    #[serde(default)]
+    directories: Vec<PathBuf>,
+}
+
+/// Git repositories in the track-changes watch list
+/// (~/.config/cli-programs/track-changes.toml)
+pub fn watched_repos() -> Result<Vec<PathBuf>> {
+    let path = GitCleanConfig::config_path()?.with_file_name("track-changes.toml");
+    if !path.exists() {
+        anyhow::bail!("No track-changes config found at {}", path.display());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let config: TrackChangesConfig =
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    Ok(config
+        .directories
+        .into_iter()
+        .filter(|dir| dir.join(".git").exists())
+        .collect())
+}
+
+/// Fetch, inventory, and (unless `dry_run`) clean every repository, then
+/// print a summary table or JSON. Fails after printing if any repository or
+/// deletion failed.
+pub fn run(
+    repos: &[PathBuf],
+    config: &GitCleanConfig,
+    dry_run: bool,
+    yes: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    if repos.is_empty() {
+        anyhow::bail!("No git repositories found");
+    }
+
+    let json = format == OutputFormat::Json;
+    let mut inventories = Vec::new();
+    for repo in repos {
+        if !json {
+            println!("Fetching {}...", repo.display());
+        }
+        inventories.push(match load_inventory(repo, config) {
+            Ok(inventory) => RepoEntry::Done(inventory),
+            Err(e) => failed(repo, &e),
+        });
+    }
+
+    if dry_run {
+        if json {
+            let output = BatchInventory {
+                repositories: inventories,
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            return check_failures(&output.repositories, 0);
+        }
+        println!();
+        print!("{}", format_plan_table(&inventories));
+        println!();
+        println!("Dry run, no branches deleted.");
+        return check_failures(&inventories, 0);
+    }
+
+    if !json {
+        println!();
+        print!("{}", format_plan_table(&inventories));
+        println!();
+
+        let counts: Vec<usize> = inventories
+            .iter()
+            .map(|entry| match entry {
+                RepoEntry::Done(inventory) => inventory.to_delete().count(),
+                RepoEntry::Failed { .. } => 0,
+            })
+            .collect();
+        let total: usize = counts.iter().sum();
+        if total == 0 {
+            println!("No merged branches to clean up.");
+            return check_failures(&inventories, 0);
+        }
+        if !yes
+            && !interactive::confirm(&format!(
+                "Delete {} branch(es) in {} repositories?",
+                total,
+                counts.iter().filter(|&&count| count > 0).count()
+            ))?
+        {
+            println!("Aborted, no branches deleted.");
+            return Ok(());
+        }
+    }
+
+    let mut summary = Summary::default();
+    let mut repositories = Vec::new();
+    for entry in inventories {
+        let entry = match entry {
+            RepoEntry::Done(inventory) => clean_repo(inventory),
+            RepoEntry::Failed { repository, error } => RepoEntry::Failed { repository, error },
+        };
+        if let RepoEntry::Done(report) = &entry {
+            summary.deleted += report.summary.deleted;
+            summary.skipped += report.summary.skipped;
+            summary.errors += report.summary.errors;
+        }
+        repositories.push(entry);
+    }
+    let report = BatchReport {
+        summary,
+        repositories,
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!();
+        print!("{}", format_result_table(&report));
+    }
+
+    check_failures(&report.repositories, report.summary.errors)
+}
+
+/// Change into `repo`, fetch, and list its branches
+fn load_inventory(repo: &Path, config: &GitCleanConfig) -> Result<Inventory> {
+    env::set_current_dir(repo).with_context(|| format!("Failed to enter {}", repo.display()))?;
+    let main_branch = get_main_branch().context("Failed to determine main branch")?;
+    git(&["fetch", "--prune"]).context("Failed to fetch and prune")?;
+    Inventory::load(&main_branch, config)
+}
+
+/// Delete the branches an inventory marks for deletion
+fn clean_repo(inventory: Inventory) -> RepoEntry<Report> {
+    let repository = inventory.repository.clone();
+    if let Err(e) = env::set_current_dir(&repository) {
+        return failed(Path::new(&repository), &e.into());
+    }
+    RepoEntry::Done(Report::run(inventory, |branch| match branch.location {
+        BranchLocation::Local => delete_local_branch_safe(&branch.name),
+        BranchLocation::Remote => delete_remote_branch(&branch.name),
+    }))
+}
+
+fn failed<T>(repo: &Path, error: &anyhow::Error) -> RepoEntry<T> {
+    RepoEntry::Failed {
+        repository: repo.display().to_string(),
+        error: format!("{:#}", error).trim().to_string(),
+    }
+}
+
+fn check_failures<T>(entries: &[RepoEntry<T>], errors: usize) -> Result<()> {
+    let failed = entries
+        .iter()
+        .filter(|entry| matches!(entry, RepoEntry::Failed { .. }))
+        .count();
+    if failed > 0 {
+        anyhow::bail!("{} repository(ies) could not be processed", failed);
+    }
+    if errors > 0 {
+        anyhow::bail!("{} branch(es) could not be deleted", errors);
+    }
+    Ok(())
+}
+
+/// Directory name of a repository, for the tables
+fn repo_name(repository: &str) -> String {
+    Path::new(repository)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| repository.to_string())
+}
+
+/// Width of the repository column
+fn name_width<T>(entries: &[RepoEntry<T>], repository: impl Fn(&T) -> &str) -> usize {
+    entries
+        .iter()
+        .map(|entry| match entry {
+            RepoEntry::Done(value) => repo_name(repository(value)).len(),
+            RepoEntry::Failed { repository, .. } => repo_name(repository).len(),
+        })
+        .chain(["Repository".len()])
+        .max()
+        .unwrap_or_default()
+}
+
+/// One row per repository with the number of local and remote branches a
+/// cleanup would delete
+fn format_plan_table(entries: &[RepoEntry<Inventory>]) -> String {
+    let width = name_width(entries, |inventory| &inventory.repository);
+    let mut table = format!(
+        "{:<width$}  {:<6}  {:>5}  {:>6}\n",
+        "Repository", "Main", "Local", "Remote"
+    );
+
+    for entry in entries {
+        match entry {
+            RepoEntry::Done(inventory) => {
+                let (local, remote): (Vec<_>, Vec<_>) = inventory
+                    .to_delete()
+                    .partition(|branch| branch.location == BranchLocation::Local);
+                table.push_str(&format!(
+                    "{:<width$}  {:<6}  {:>5}  {:>6}\n",
+                    repo_name(&inventory.repository),
+                    inventory.main_branch,
+                    local.len(),
+                    remote.len()
+                ));
+            }
+            RepoEntry::Failed { repository, error } => table.push_str(&format!(
+                "{:<width$}  error: {}\n",
+                repo_name(repository),
+                error
+            )),
+        }
+    }
+
+    table
+}
+
+/// One row per repository with what happened to its branches, and totals
+fn format_result_table(report: &BatchReport) -> String {
+    let width = name_width(&report.repositories, |report| &report.repository);
+    let row = |name: &str, summary: &Summary| {
+        format!(
+            "{:<width$}  {:>7}  {:>7}  {:>6}\n",
+            name, summary.deleted, summary.skipped, summary.errors
+        )
+    };
+
+    let mut table = format!(
+        "{:<width$}  {:>7}  {:>7}  {:>6}\n",
+        "Repository", "Deleted", "Skipped", "Errors"
+    );
+    for entry in &report.repositories {
+        match entry {
+            RepoEntry::Done(repo) => {
+                table.push_str(&row(&repo_name(&repo.repository), &repo.summary));
+            }
+            RepoEntry::Failed { repository, error } => table.push_str(&format!(
+                "{:<width$}  error: {}\n",
+                repo_name(repository),
+                error
+            )),
+        }
+    }
+    table.push_str(&row("Total", &report.summary));
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::{Action, BranchInfo};
+
+    fn branch(name: &str, location: BranchLocation, action: Action) -> BranchInfo {
+        BranchInfo {
+            name: name.to_string(),
+            location,
+            merged: action == Action::Delete,
+            last_commit: "2025-10-01T12:00:00+02:00".to_string(),
+            last_commit_age: "3 weeks ago".to_string(),
+            author: "Alice".to_string(),
+            action,
+            reason: (action == Action::Keep).then_some("unmerged"),
+        }
+    }
+
+    fn inventory() -> Inventory {
+        Inventory {
+            repository: "/src/app".to_string(),
+            main_branch: "main".to_string(),
+            branches: vec![
+                branch("feature", BranchLocation::Local, Action::Delete),
+                branch("wip", BranchLocation::Local, Action::Keep),
+                branch("feature", BranchLocation::Remote, Action::Delete),
+            ],
+        }
+    }
+
+    fn failure() -> RepoEntry<Inventory> {
+        RepoEntry::Failed {
+            repository: "/src/old-experiments".to_string(),
+            error: "Failed to determine main branch: Could not find main or master branch"
+                .to_string(),
+        }
+    }
+
+    #[test]
+    fn test_format_plan_table() {
+        let entries = vec![RepoEntry::Done(inventory()), failure()];
+        assert_eq!(
+            format_plan_table(&entries),
+            "Repository       Main    Local  Remote\n\
+             app              main        1       1\n\
+             old-experiments  error: Failed to determine main branch: Could not find main or master branch\n"
+        );
+    }
+
+    #[test]
+    fn test_format_result_table() {
+        let report = Report::run(inventory(), |branch| {
+            if branch.location == BranchLocation::Remote {
+                anyhow::bail!("remote rejected");
+            }
+            Ok(())
+        });
+        let batch = BatchReport {
+            summary: report.summary.clone(),
+            repositories: vec![RepoEntry::Done(report)],
+        };
+        assert_eq!(
+            format_result_table(&batch),
+            "Repository  Deleted  Skipped  Errors\n\
+             app               1        1       1\n\
+             Total             1        1       1\n"
+        );
+    }
+
+    #[test]
+    fn test_json_shape() {
+        let entries = vec![RepoEntry::Done(inventory()), failure()];
+        let json = serde_json::to_value(&entries).unwrap();
+        assert_eq!(json[0]["repository"], "/src/app");
+        assert_eq!(json[0]["branches"][0]["action"], "delete");
+        assert_eq!(json[1]["repository"], "/src/old-experiments");
+        assert!(json[1]["error"].as_str().unwrap().contains("main branch"));
+    }
+
+    #[test]
+    fn test_find_repos() {
+        let parent = env::temp_dir().join(format!("git-clean-batch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&parent);
+        for dir in ["b-repo/.git", "a-repo/.git", "not-a-repo"] {
+            fs::create_dir_all(parent.join(dir)).unwrap();
+        }
+        // A worktree or submodule has a .git file instead of a directory
+        fs::create_dir_all(parent.join("c-worktree")).unwrap();
+        fs::write(parent.join("c-worktree/.git"), "gitdir: /elsewhere").unwrap();
+
+        let repos = find_repos(&parent).unwrap();
+        let _ = fs::remove_dir_all(&parent);
+
+        let names: Vec<_> = repos
+            .iter()
+            .map(|repo| repo.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, vec!["a-repo", "b-repo", "c-worktree"]);
+    }
+
+    #[test]
+    fn test_watch_list_ignores_other_settings() {
+        let config: TrackChangesConfig = toml::from_str(
+            r#"
+            directories = ["/src/app", "/src/lib"]
+
+            [large_repo]
+            directories = ["/src/app"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.directories,
+            vec![PathBuf::from("/src/app"), PathBuf::from("/src/lib")]
+        );
+    }
+}
@@ -0,0 +1,206 @@
+// git-clean-specific configuration
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitCleanConfig {
+    /// Branches that are never deleted, locally or on origin. `*` matches any
+    /// run of characters (including `/`) and `?` any single character, so
+    /// "release/*" protects every release branch.
+    #[serde(default = "default_protected_branches")]
+    pub protected_branches: Vec<String>,
+
+    /// Only delete branches whose last commit is at least this many days old
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub older_than_days: Option<u64>,
+}
+
+fn default_protected_branches() -> Vec<String> {
+    ["main", "master", "develop"]
+        .iter()
+        .map(|branch| branch.to_string())
+        .collect()
+}
+
+impl Default for GitCleanConfig {
+    fn default() -> Self {
+        Self {
+            protected_branches: default_protected_branches(),
+            older_than_days: None,
+        }
+    }
+}
+
+impl GitCleanConfig {
+    /// Get the config file path: ~/.config/cli-programs/git-clean.toml
+    pub fn config_path() -> Result<PathBuf> {
+        let home = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE"))?;
+        Ok(PathBuf::from(home)
+            .join(".config")
+            .join("cli-programs")
+            .join("git-clean.toml"))
+    }
+
+    /// Load config from file, returning default if file doesn't exist
+    pub fn load() -> Result<Self> {
+        let path = Self::config_path()?;
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        let config: GitCleanConfig = toml::from_str(&content)?;
+        Ok(config)
+    }
+
+    /// Whether the branch matches one of the protected patterns
+    pub fn is_protected(&self, branch: &str) -> bool {
+        self.protected_branches
+            .iter()
+            .any(|pattern| glob_match(pattern, branch))
+    }
+
+    /// Whether the branch is protected, counting `main_branch`, which is kept
+    /// (locally and on origin) whatever the configured patterns are
+    pub fn is_protected_with_main(&self, branch: &str, main_branch: &str) -> bool {
+        branch == main_branch || self.is_protected(branch)
+    }
+
+    /// Whether a branch whose last commit was at `last_commit` (Unix time) is
+    /// too recent to delete
+    pub fn is_too_recent(&self, last_commit: i64) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        self.is_too_recent_at(last_commit, now)
+    }
+
+    fn is_too_recent_at(&self, last_commit: i64, now: i64) -> bool {
+        self.older_than_days.is_some_and(|days| {
+            let days = i64::try_from(days).unwrap_or(i64::MAX);
+            now - last_commit < days.saturating_mul(SECONDS_PER_DAY)
+        })
+    }
+}
+
+/// Match `text` against a pattern where `*` matches any run of characters
+/// and `?` matches exactly one
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it was tried at
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character
+                Some((star, start)) => {
+                    backtrack = Some((star, start + 1));
+                    p = star + 1;
+                    t = start + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = GitCleanConfig::default();
+        assert!(config.is_protected("main"));
+        assert!(config.is_protected("develop"));
+        assert!(!config.is_protected("feature/login"));
+        assert_eq!(config.older_than_days, None);
+        assert!(!config.is_too_recent(0));
+    }
+
+    #[test]
+    fn test_config_path() {
+        let path = GitCleanConfig::config_path().unwrap();
+        assert!(path.ends_with("cli-programs/git-clean.toml"));
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config: GitCleanConfig = toml::from_str(
+            "protected_branches = [\"main\", \"release/*\"]\nolder_than_days = 30\n",
+        )
+        .unwrap();
+        assert!(config.is_protected("release/2.0"));
+        assert!(!config.is_protected("master"));
+        assert_eq!(config.older_than_days, Some(30));
+
+        let config: GitCleanConfig = toml::from_str("older_than_days = 7\n").unwrap();
+        assert!(config.is_protected("master"));
+    }
+
+    #[test]
+    fn test_main_branch_always_protected() {
+        let config: GitCleanConfig =
+            toml::from_str("protected_branches = [\"release/*\"]\n").unwrap();
+        assert!(!config.is_protected("main"));
+        assert!(config.is_protected_with_main("main", "main"));
+        assert!(config.is_protected_with_main("release/2.0", "main"));
+        assert!(!config.is_protected_with_main("master", "main"));
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("main", "main"));
+        assert!(!glob_match("main", "main2"));
+        assert!(glob_match("release/*", "release/1.0"));
+        assert!(glob_match("release/*", "release/1.0/hotfix"));
+        assert!(!glob_match("release/*", "releases/1.0"));
+        assert!(glob_match("*-keep", "feature-keep"));
+        assert!(glob_match("*/wip*", "alice/wip-login"));
+        assert!(glob_match("v?.x", "v2.x"));
+        assert!(!glob_match("v?.x", "v10.x"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("a*b*c", "aXbYbZ"));
+    }
+
+    #[test]
+    fn test_is_too_recent() {
+        let config = GitCleanConfig {
+            older_than_days: Some(30),
+            ..Default::default()
+        };
+        let now = 100 * SECONDS_PER_DAY;
+        assert!(config.is_too_recent_at(now - 29 * SECONDS_PER_DAY, now));
+        assert!(!config.is_too_recent_at(now - 30 * SECONDS_PER_DAY, now));
+        assert!(!config.is_too_recent_at(now - 90 * SECONDS_PER_DAY, now));
+    }
+
+    #[test]
+    fn test_is_too_recent_with_huge_threshold() {
+        let config = GitCleanConfig {
+            older_than_days: Some(u64::MAX),
+            ..Default::default()
+        };
+        assert!(config.is_too_recent_at(0, 100 * SECONDS_PER_DAY));
+    }
+}
@@ -0,0 +1,270 @@
+// Cleanup of branches whose upstream is gone and of stale remote-tracking refs
+//
+// A branch merged with a squash or rebase never shows up in `--merged`, but
+// once the remote branch is deleted and `fetch --prune` removes its
+// remote-tracking ref, the local branch's configured upstream no longer
+// exists. That is what `git branch -vv` reports as "gone". Refs are read
+// through git2 rather than by parsing `git branch` output.
+
+use anyhow::{Context, Result};
+use git2::{BranchType, ErrorCode, Repository};
+
+use crate::config::GitCleanConfig;
+
+/// Local branches that track an upstream which no longer exists, skipping
+/// protected and recent branches and those in `skip` (current and worktree
+/// branches)
+pub fn gone_branches(
+    repo: &Repository,
+    config: &GitCleanConfig,
+    skip: &[String],
+) -> Result<Vec<String>> {
+    let mut branches = Vec::new();
+
+    for branch in repo.branches(Some(BranchType::Local))? {
+        let (branch, _) = branch?;
+        let Some(name) = branch.name()? else {
+            continue;
+        };
+        let Some(refname) = branch.get().name() else {
+            continue;
+        };
+
+        // Branches without an upstream were never pushed, so nothing is gone
+        let upstream = match repo.branch_upstream_name(refname) {
+            Ok(upstream) => upstream,
+            Err(e) if e.code() == ErrorCode::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let Some(upstream) = upstream.as_str() else {
+            continue;
+        };
+        if repo.find_reference(upstream).is_ok() {
+            continue;
+        }
+
+        if skip.iter().any(|s| s == name) || config.is_protected(name) {
+            continue;
+        }
+        let time = branch.get().peel_to_commit()?.committer().when().seconds();
+        if config.is_too_recent(time) {
+            continue;
+        }
+
+        branches.push(name.to_string());
+    }
+
+    branches.sort();
+    Ok(branches)
+}
+
+/// Remote-tracking refs left behind by a remote that is no longer configured,
+/// which `fetch --prune` never visits
+pub fn stale_remote_refs(repo: &Repository) -> Result<Vec<String>> {
+    let remotes = repo.remotes()?;
+    let prefixes: Vec<String> = remotes
+        .iter()
+        .flatten()
+        .map(|remote| format!("refs/remotes/{}/", remote))
+        .collect();
+
+    let mut refs = Vec::new();
+    for reference in repo.references_glob("refs/remotes/*")? {
+        let reference = reference?;
+        let Some(name) = reference.name() else {
+            continue;
+        };
+        if !prefixes.iter().any(|prefix| name.starts_with(prefix)) {
+            refs.push(name.to_string());
+        }
+    }
+
+    refs.sort();
+    Ok(refs)
+}
+
+/// Delete a local branch, whether or not it is merged
+pub fn delete_branch(repo: &Repository, name: &str) -> Result<()> {
+    repo.find_branch(name, BranchType::Local)?
+        .delete()
+        .with_context(|| format!("Failed to delete branch '{}'", name))
+}
+
+/// Delete a remote-tracking ref by its full name
+pub fn delete_ref(repo: &Repository, name: &str) -> Result<()> {
+    repo.find_reference(name)?
+        .delete()
+        .with_context(|| format!("Failed to delete ref '{}'", name))
+}
+
+/// Format gone branches and stale refs as two labeled groups, skipping empty ones
+pub fn format_plan(branches: &[String], refs: &[String]) -> String {
+    let mut plan = String::new();
+
+    if !branches.is_empty() {
+        plan.push_str(&format!(
+            "Local branches with a gone upstream ({}):\n",
+            branches.len()
+        ));
+        for branch in branches {
+            plan.push_str(&format!("  {}\n", branch));
+        }
+    }
+
+    if !refs.is_empty() {
+        plan.push_str(&format!("Stale remote-tracking refs ({}):\n", refs.len()));
+        for name in refs {
+            let short = name.strip_prefix("refs/remotes/").unwrap_or(name);
+            plan.push_str(&format!("  {}\n", short));
+        }
+    }
+
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use std::path::PathBuf;
+
+    /// A fresh repository with one commit on `main`, removed on drop
+    struct TestRepo {
+        path: PathBuf,
+        repo: Repository,
+    }
+
+    impl TestRepo {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "git-clean-gone-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&path);
+            let repo = Repository::init(&path).unwrap();
+            {
+                let sig = Signature::now("Test", "test@example.com").unwrap();
+                let tree_id = repo.index().unwrap().write_tree().unwrap();
+                let tree = repo.find_tree(tree_id).unwrap();
+                repo.commit(Some("refs/heads/main"), &sig, &sig, "init", &tree, &[])
+                    .unwrap();
+                repo.set_head("refs/heads/main").unwrap();
+            }
+            Self { path, repo }
+        }
+
+        fn branch(&self, name: &str) {
+            let head = self.repo.head().unwrap().peel_to_commit().unwrap();
+            self.repo.branch(name, &head, false).unwrap();
+        }
+
+        fn set_upstream(&self, branch: &str, remote: &str) {
+            let mut config = self.repo.config().unwrap();
+            config
+                .set_str(&format!("branch.{}.remote", branch), remote)
+                .unwrap();
+            config
+                .set_str(
+                    &format!("branch.{}.merge", branch),
+                    &format!("refs/heads/{}", branch),
+                )
+                .unwrap();
+        }
+
+        fn remote_ref(&self, name: &str) {
+            let head = self.repo.head().unwrap().target().unwrap();
+            self.repo
+                .reference(&format!("refs/remotes/{}", name), head, false, "test")
+                .unwrap();
+        }
+    }
+
+    impl Drop for TestRepo {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.path);
+        }
+    }
+
+    #[test]
+    fn test_gone_branches() {
+        let test = TestRepo::new("branches");
+        test.repo
+            .remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        for name in ["gone", "tracked", "local-only", "release-1"] {
+            test.branch(name);
+        }
+        test.set_upstream("gone", "origin");
+        test.set_upstream("tracked", "origin");
+        test.set_upstream("release-1", "origin");
+        test.remote_ref("origin/tracked");
+
+        let config = GitCleanConfig {
+            protected_branches: vec!["release-*".to_string()],
+            older_than_days: None,
+        };
+        let found = gone_branches(&test.repo, &config, &[]).unwrap();
+        assert_eq!(found, vec!["gone"]);
+
+        let skipped = gone_branches(&test.repo, &config, &["gone".to_string()]).unwrap();
+        assert!(skipped.is_empty());
+
+        let recent = GitCleanConfig {
+            older_than_days: Some(30),
+            ..config
+        };
+        assert!(gone_branches(&test.repo, &recent, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_stale_remote_refs_and_delete() {
+        let test = TestRepo::new("refs");
+        test.repo
+            .remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        test.remote_ref("origin/main");
+        test.remote_ref("upstream/main");
+        test.remote_ref("upstream/feature");
+
+        let stale = stale_remote_refs(&test.repo).unwrap();
+        assert_eq!(
+            stale,
+            vec![
+                "refs/remotes/upstream/feature",
+                "refs/remotes/upstream/main"
+            ]
+        );
+
+        delete_ref(&test.repo, &stale[0]).unwrap();
+        assert_eq!(stale_remote_refs(&test.repo).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_delete_branch() {
+        let test = TestRepo::new("delete");
+        test.branch("unmerged");
+        delete_branch(&test.repo, "unmerged").unwrap();
+        assert!(
+            test.repo
+                .find_branch("unmerged", BranchType::Local)
+                .is_err()
+        );
+        assert!(delete_branch(&test.repo, "missing").is_err());
+    }
+
+    #[test]
+    fn test_format_plan() {
+        let branches = vec!["squashed".to_string()];
+        let refs = vec!["refs/remotes/old/feature".to_string()];
+        assert_eq!(
+            format_plan(&branches, &refs),
+            "Local branches with a gone upstream (1):\n  squashed\n\
+             Stale remote-tracking refs (1):\n  old/feature\n"
+        );
+        assert_eq!(
+            format_plan(&branches, &[]),
+            "Local branches with a gone upstream (1):\n  squashed\n"
+        );
+    }
+}
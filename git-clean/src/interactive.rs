@@ -0,0 +1,313 @@
+// Interactive multi-select of branches to delete
+
+use anyhow::Result;
+use serde::Serialize;
+use std::io::{self, Write};
+
+use crate::git;
+
+/// Where a candidate branch lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BranchLocation {
+    Local,
+    Remote,
+}
+
+/// A merged branch that may be deleted, with metadata for display
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub name: String,
+    pub location: BranchLocation,
+    /// Relative age of the last commit (e.g. "3 weeks ago")
+    pub last_commit_age: String,
+    pub author: String,
+    /// Author of the merge commit that brought the branch into main,
+    /// or "fast-forward" when there was no merge commit
+    pub merged_by: String,
+    pub selected: bool,
+}
+
+impl Candidate {
+    /// Build a candidate by looking up its metadata from git
+    pub fn load(name: &str, location: BranchLocation, main_branch: &str) -> Self {
+        let (refname, target) = match location {
+            BranchLocation::Local => (name.to_string(), main_branch.to_string()),
+            BranchLocation::Remote => (
+                format!("origin/{}", name),
+                format!("origin/{}", main_branch),
+            ),
+        };
+
+        let info = git(&["log", "-1", "--format=%cr|%an", &refname]).unwrap_or_default();
+        let (age, author) = info
+            .trim()
+            .split_once('|')
+            .unwrap_or(("unknown", "unknown"));
+
+        let merged_by = git(&[
+            "log",
+            "--ancestry-path",
+            "--merges",
+            "--reverse",
+            "--format=%an",
+            &format!("{}..{}", refname, target),
+        ])
+        .ok()
+        .and_then(|out| out.lines().next().map(str::to_string))
+        .unwrap_or_else(|| "fast-forward".to_string());
+
+        Self {
+            name: name.to_string(),
+            location,
+            last_commit_age: age.to_string(),
+            author: author.to_string(),
+            merged_by,
+            selected: false,
+        }
+    }
+}
+
+/// Format a location for display
+pub fn format_location(location: BranchLocation) -> &'static str {
+    match location {
+        BranchLocation::Local => "local",
+        BranchLocation::Remote => "remote",
+    }
+}
+
+/// Display the candidate list with selection markers
+pub fn display_candidates(candidates: &[Candidate]) {
+    println!("\nMerged branches:");
+    println!("{:-<72}", "");
+
+    for (i, c) in candidates.iter().enumerate() {
+        let marker = if c.selected { "[x]" } else { "[ ]" };
+        println!(
+            "  {} {:>2}. {} ({}) - {}, by {}, merged by {}",
+            marker,
+            i + 1,
+            c.name,
+            format_location(c.location),
+            c.last_commit_age,
+            c.author,
+            c.merged_by
+        );
+    }
+
+    println!("{:-<72}", "");
+}
+
+/// Result of interpreting one line of selection input
+#[derive(Debug, PartialEq, Eq)]
+pub enum SelectionAction {
+    /// Selection changed; show the list again
+    Continue,
+    /// Delete the selected branches
+    Confirm,
+    /// Abort without deleting anything
+    Quit,
+    /// Input could not be understood
+    Invalid(String),
+}
+
+/// Apply a line of user input to the selection.
+///
+/// Accepts space- or comma-separated numbers and ranges (`1 3 5-7`) to toggle,
+/// `a` to select all, `n` to select none, `q` to quit, and an empty line to confirm.
+pub fn apply_input(candidates: &mut [Candidate], input: &str) -> SelectionAction {
+    let input = input.trim();
+
+    if input.is_empty() {
+        return SelectionAction::Confirm;
+    }
+    if input.eq_ignore_ascii_case("q") {
+        return SelectionAction::Quit;
+    }
+    if input.eq_ignore_ascii_case("a") {
+        candidates.iter_mut().for_each(|c| c.selected = true);
+        return SelectionAction::Continue;
+    }
+    if input.eq_ignore_ascii_case("n") {
+        candidates.iter_mut().for_each(|c| c.selected = false);
+        return SelectionAction::Continue;
+    }
+
+    let mut indices = Vec::new();
+    for token in input.split(|c: char| c == ',' || c.is_whitespace()) {
+        if token.is_empty() {
+            continue;
+        }
+        let range = match token.split_once('-') {
+            Some((start, end)) => start.parse::<usize>().ok().zip(end.parse::<usize>().ok()),
+            None => token.parse::<usize>().ok().map(|n| (n, n)),
+        };
+        match range {
+            Some((start, end)) if start >= 1 && start <= end && end <= candidates.len() => {
+                indices.extend(start..=end);
+            }
+            _ => return SelectionAction::Invalid(token.to_string()),
+        }
+    }
+
+    for n in indices {
+        let candidate = &mut candidates[n - 1];
+        candidate.selected = !candidate.selected;
+    }
+    SelectionAction::Continue
+}
+
+/// Run the selection loop until the user confirms or quits.
+///
+/// Returns `None` if the user quit, otherwise the selected candidates.
+pub fn select_candidates(mut candidates: Vec<Candidate>) -> Result<Option<Vec<Candidate>>> {
+    loop {
+        display_candidates(&candidates);
+
+        let selected = candidates.iter().filter(|c| c.selected).count();
+        print!(
+            "\nToggle (e.g. 1 3 5-7), 'a' all, 'n' none, Enter to delete {} selected, 'q' to quit: ",
+            selected
+        );
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        match apply_input(&mut candidates, &input) {
+            SelectionAction::Continue => {}
+            SelectionAction::Confirm => {
+                return Ok(Some(
+                    candidates.into_iter().filter(|c| c.selected).collect(),
+                ));
+            }
+            SelectionAction::Quit => return Ok(None),
+            SelectionAction::Invalid(token) => println!("Invalid selection: {}", token),
+        }
+    }
+}
+
+/// List the branches a cleanup will delete, local and remote separately
+pub fn format_plan(local: &[String], remote: &[String]) -> String {
+    let mut text = String::new();
+    for (heading, branches) in [
+        ("Local branches", local),
+        ("Remote branches on origin", remote),
+    ] {
+        if branches.is_empty() {
+            continue;
+        }
+        text.push_str(&format!("{} ({}):\n", heading, branches.len()));
+        for branch in branches {
+            text.push_str(&format!("  {}\n", branch));
+        }
+    }
+    text
+}
+
+/// Prompt for confirmation
+pub fn confirm(message: &str) -> Result<bool> {
+    print!("{} [y/N]: ", message);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let input = input.trim();
+
+    Ok(input.eq_ignore_ascii_case("y") || input.eq_ignore_ascii_case("yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(count: usize) -> Vec<Candidate> {
+        (0..count)
+            .map(|i| Candidate {
+                name: format!("feature-{}", i + 1),
+                location: BranchLocation::Local,
+                last_commit_age: "2 days ago".to_string(),
+                author: "Test".to_string(),
+                merged_by: "fast-forward".to_string(),
+                selected: false,
+            })
+            .collect()
+    }
+
+    fn selected_names(candidates: &[Candidate]) -> Vec<&str> {
+        candidates
+            .iter()
+            .filter(|c| c.selected)
+            .map(|c| c.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_toggle_numbers_and_ranges() {
+        let mut list = candidates(5);
+        assert_eq!(apply_input(&mut list, "1, 3-4"), SelectionAction::Continue);
+        assert_eq!(
+            selected_names(&list),
+            ["feature-1", "feature-3", "feature-4"]
+        );
+
+        // Toggling again deselects
+        assert_eq!(apply_input(&mut list, "3"), SelectionAction::Continue);
+        assert_eq!(selected_names(&list), ["feature-1", "feature-4"]);
+    }
+
+    #[test]
+    fn test_select_all_and_none() {
+        let mut list = candidates(3);
+        apply_input(&mut list, "a");
+        assert_eq!(selected_names(&list).len(), 3);
+        apply_input(&mut list, "N");
+        assert!(selected_names(&list).is_empty());
+    }
+
+    #[test]
+    fn test_confirm_and_quit() {
+        let mut list = candidates(2);
+        assert_eq!(apply_input(&mut list, "\n"), SelectionAction::Confirm);
+        assert_eq!(apply_input(&mut list, "q"), SelectionAction::Quit);
+    }
+
+    #[test]
+    fn test_invalid_input_leaves_selection_unchanged() {
+        let mut list = candidates(3);
+        assert_eq!(
+            apply_input(&mut list, "1 4"),
+            SelectionAction::Invalid("4".to_string())
+        );
+        assert!(selected_names(&list).is_empty());
+        assert_eq!(
+            apply_input(&mut list, "x"),
+            SelectionAction::Invalid("x".to_string())
+        );
+        assert_eq!(
+            apply_input(&mut list, "3-1"),
+            SelectionAction::Invalid("3-1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_plan() {
+        let local = vec!["feature-login".to_string(), "fix-typo".to_string()];
+        let remote = vec!["old-feature".to_string()];
+        assert_eq!(
+            format_plan(&local, &remote),
+            "Local branches (2):
+  feature-login
+  fix-typo
+Remote branches on origin (1):
+  old-feature
+"
+        );
+
+        // Empty groups are left out
+        assert_eq!(
+            format_plan(&[], &remote),
+            "Remote branches on origin (1):\n  old-feature\n"
+        );
+    }
+}
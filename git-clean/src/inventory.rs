@@ -0,0 +1,248 @@
+// Branch inventory for dry runs and JSON reports
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::config::GitCleanConfig;
+use crate::interactive::BranchLocation;
+use crate::{get_worktree_branches, git};
+
+/// What a normal run would do with a branch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Delete,
+    Keep,
+}
+
+/// One local or remote branch and what would happen to it
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchInfo {
+    pub name: String,
+    pub location: BranchLocation,
+    /// Merged into main (local) or origin/main (remote)
+    pub merged: bool,
+    /// Committer date of the last commit, ISO 8601
+    pub last_commit: String,
+    /// Relative age of the last commit (e.g. "3 weeks ago")
+    pub last_commit_age: String,
+    pub author: String,
+    pub action: Action,
+    /// Why the branch is kept
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<&'static str>,
+}
+
+/// Every branch in the repository with its candidate action
+#[derive(Debug, Clone, Serialize)]
+pub struct Inventory {
+    pub repository: String,
+    pub main_branch: String,
+    pub branches: Vec<BranchInfo>,
+}
+
+impl Inventory {
+    pub fn load(main_branch: &str, config: &GitCleanConfig) -> Result<Self> {
+        let repository = git(&["rev-parse", "--show-toplevel"])?.trim().to_string();
+        let current = git(&["symbolic-ref", "--short", "HEAD"])
+            .map(|b| b.trim().to_string())
+            .unwrap_or_default();
+        let worktrees = get_worktree_branches()?;
+        // Unlike the cleanup lists, these include protected and current branches
+        let merged_local = merged_refs(main_branch, "refs/heads")?;
+        let remote = list_branches("refs/remotes/origin")?;
+        // Repositories without an origin have nothing to compare against
+        let merged_remote = if remote.is_empty() {
+            Vec::new()
+        } else {
+            merged_refs(&format!("origin/{}", main_branch), "refs/remotes/origin")?
+        };
+
+        let mut branches = Vec::new();
+        for branch in list_branches("refs/heads")? {
+            let reason = if config.is_protected_with_main(&branch.name, main_branch) {
+                Some("protected")
+            } else if branch.name == current {
+                Some("current")
+            } else if worktrees.contains(&branch.name) {
+                Some("worktree")
+            } else {
+                branch.recent_reason(config)
+            };
+            let merged = merged_local.contains(&branch.name);
+            branches.push(branch.into_info(BranchLocation::Local, merged, reason));
+        }
+
+        for mut branch in remote {
+            let Some(name) = branch.name.strip_prefix("origin/") else {
+                continue;
+            };
+            if name == "HEAD" {
+                continue;
+            }
+            let merged = merged_remote.contains(&branch.name);
+            branch.name = name.to_string();
+            let reason = if config.is_protected_with_main(&branch.name, main_branch) {
+                Some("protected")
+            } else {
+                branch.recent_reason(config)
+            };
+            branches.push(branch.into_info(BranchLocation::Remote, merged, reason));
+        }
+
+        Ok(Self {
+            repository,
+            main_branch: main_branch.to_string(),
+            branches,
+        })
+    }
+
+    /// Branches a normal run would delete
+    pub fn to_delete(&self) -> impl Iterator<Item = &BranchInfo> {
+        self.branches.iter().filter(|b| b.action == Action::Delete)
+    }
+}
+
+/// Fields read from `git for-each-ref`
+struct RefEntry {
+    name: String,
+    last_commit: String,
+    last_commit_age: String,
+    /// Committer date of the last commit, Unix time
+    last_commit_time: i64,
+    author: String,
+}
+
+impl RefEntry {
+    /// "recent" if the last commit is newer than the configured age threshold
+    fn recent_reason(&self, config: &GitCleanConfig) -> Option<&'static str> {
+        config
+            .is_too_recent(self.last_commit_time)
+            .then_some("recent")
+    }
+
+    fn into_info(
+        self,
+        location: BranchLocation,
+        merged: bool,
+        reason: Option<&'static str>,
+    ) -> BranchInfo {
+        let reason = reason.or((!merged).then_some("unmerged"));
+        BranchInfo {
+            name: self.name,
+            location,
+            merged,
+            last_commit: self.last_commit,
+            last_commit_age: self.last_commit_age,
+            author: self.author,
+            action: if reason.is_none() {
+                Action::Delete
+            } else {
+                Action::Keep
+            },
+            reason,
+        }
+    }
+}
+
+fn list_branches(prefix: &str) -> Result<Vec<RefEntry>> {
+    let output = git(&[
+        "for-each-ref",
+        "--format=%(refname:short)|%(committerdate:iso-strict)|%(committerdate:relative)|%(committerdate:unix)|%(authorname)",
+        prefix,
+    ])?;
+    Ok(parse_refs(&output))
+}
+
+/// Short names of the refs under `prefix` that are merged into `target`
+fn merged_refs(target: &str, prefix: &str) -> Result<Vec<String>> {
+    let output = git(&[
+        "for-each-ref",
+        "--format=%(refname:short)",
+        "--merged",
+        target,
+        prefix,
+    ])?;
+    Ok(output.lines().map(str::to_string).collect())
+}
+
+fn parse_refs(output: &str) -> Vec<RefEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(5, '|');
+            Some(RefEntry {
+                name: fields.next()?.to_string(),
+                last_commit: fields.next()?.to_string(),
+                last_commit_age: fields.next()?.to_string(),
+                last_commit_time: fields.next()?.parse().ok()?,
+                author: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str) -> RefEntry {
+        parse_refs(&format!(
+            "{}|2025-10-01T12:00:00+02:00|3 weeks ago|1759312800|Alice",
+            name
+        ))
+        .pop()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_refs() {
+        let refs = parse_refs(
+            "feature|2025-10-01T12:00:00+02:00|3 weeks ago|1759312800|Alice\norigin/fix|2025-10-20T09:00:00Z|2 days ago|1760950800|Bob Smith\n",
+        );
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].name, "feature");
+        assert_eq!(refs[0].last_commit, "2025-10-01T12:00:00+02:00");
+        assert_eq!(refs[1].last_commit_age, "2 days ago");
+        assert_eq!(refs[1].last_commit_time, 1760950800);
+        assert_eq!(refs[1].author, "Bob Smith");
+    }
+
+    #[test]
+    fn test_actions() {
+        let merged = entry("feature").into_info(BranchLocation::Local, true, None);
+        assert_eq!(merged.action, Action::Delete);
+        assert_eq!(merged.reason, None);
+
+        let unmerged = entry("wip").into_info(BranchLocation::Local, false, None);
+        assert_eq!(unmerged.action, Action::Keep);
+        assert_eq!(unmerged.reason, Some("unmerged"));
+
+        // Protection wins over merge status
+        let protected = entry("main").into_info(BranchLocation::Remote, true, Some("protected"));
+        assert_eq!(protected.action, Action::Keep);
+        assert_eq!(protected.reason, Some("protected"));
+    }
+
+    #[test]
+    fn test_recent_reason() {
+        let mut config = GitCleanConfig::default();
+        assert_eq!(entry("feature").recent_reason(&config), None);
+
+        // The entry's last commit is from October 2025
+        config.older_than_days = Some(30);
+        assert_eq!(entry("feature").recent_reason(&config), None);
+        config.older_than_days = Some(365 * 100);
+        assert_eq!(entry("feature").recent_reason(&config), Some("recent"));
+    }
+
+    #[test]
+    fn test_json_shape() {
+        let info = entry("feature").into_info(BranchLocation::Remote, true, None);
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["location"], "remote");
+        assert_eq!(json["action"], "delete");
+        assert_eq!(json["merged"], true);
+        assert!(json.get("reason").is_none());
+    }
+}
@@ -1,8 +1,21 @@
 // git-clean - Clean up merged local and remote git branches
 
+mod batch;
+mod config;
+mod gone;
+mod interactive;
+mod inventory;
+mod report;
+
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use config::GitCleanConfig;
 use git2::Repository;
+use interactive::{BranchLocation, Candidate};
+use inventory::{BranchInfo, Inventory};
+use report::Report;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::process::Command;
 
 #[derive(Parser, Debug)]
@@ -10,7 +23,55 @@ use std::process::Command;
 #[command(about = "Clean up merged local and remote git branches", long_about = None)]
 #[command(version)]
 struct Args {
-    // Currently no arguments, but could add --dry-run, --yes, etc.
+    /// Choose which merged branches to delete from an interactive list
+    #[arg(short, long, conflicts_with = "dry_run")]
+    interactive: bool,
+
+    /// Delete without asking for confirmation first
+    #[arg(short, long, conflicts_with_all = ["dry_run", "interactive"])]
+    yes: bool,
+
+    /// Show which branches would be deleted without deleting anything
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+
+    /// Delete local branches whose upstream is gone (e.g. squash-merged and
+    /// deleted on the remote) and stale remote-tracking refs, instead of
+    /// merged branches
+    #[arg(long, conflicts_with_all = ["interactive", "format"])]
+    gone: bool,
+
+    /// Never delete branches matching this pattern (e.g. "release/*"); can be
+    /// repeated, and adds to `protected_branches` in git-clean.toml
+    #[arg(long, value_name = "GLOB")]
+    protect: Vec<String>,
+
+    /// Only delete branches whose last commit is at least this many days old
+    #[arg(long, value_name = "DAYS")]
+    older_than: Option<u64>,
+
+    /// Output format; JSON reports the inventory with --dry-run, and otherwise
+    /// what was deleted, skipped, or failed (deleting needs --yes)
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, conflicts_with = "interactive")]
+    format: OutputFormat,
+
+    /// Clean every git repository directly inside this directory, with one
+    /// confirmation and a summary table
+    #[arg(long, value_name = "PARENT_DIR", conflicts_with_all = ["interactive", "gone", "watched_repos"])]
+    all_repos: Option<PathBuf>,
+
+    /// Clean every repository in the track-changes watch list, like --all-repos
+    #[arg(long, conflicts_with_all = ["interactive", "gone"])]
+    watched_repos: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// Human-readable list of branches that would be deleted
+    Text,
+    /// Every branch with merge status, last activity, and candidate action,
+    /// or with its outcome after a cleanup
+    Json,
 }
 
 // =============================================================================
@@ -64,7 +125,7 @@ fn get_worktree_branches() -> Result<Vec<String>> {
 fn get_main_branch() -> Result<String> {
     // Check if main exists
     let main_check = Command::new("git")
-        .args(&["show-ref", "--verify", "--quiet", "refs/heads/main"])
+        .args(["show-ref", "--verify", "--quiet", "refs/heads/main"])
         .status()
         .context("Failed to check for main branch")?;
 
@@ -74,7 +135,7 @@ fn get_main_branch() -> Result<String> {
 
     // Check if master exists
     let master_check = Command::new("git")
-        .args(&["show-ref", "--verify", "--quiet", "refs/heads/master"])
+        .args(["show-ref", "--verify", "--quiet", "refs/heads/master"])
         .status()
         .context("Failed to check for master branch")?;
 
@@ -86,19 +147,37 @@ fn get_main_branch() -> Result<String> {
     anyhow::bail!("Could not find main or master branch")
 }
 
+/// Unix time of the last commit on each ref under `prefix`, keyed by short name
+fn last_commit_times(prefix: &str) -> Result<HashMap<String, i64>> {
+    let output = git(&[
+        "for-each-ref",
+        "--format=%(refname:short)|%(committerdate:unix)",
+        prefix,
+    ])?;
+
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let (name, time) = line.split_once('|')?;
+            Some((name.to_string(), time.parse().ok()?))
+        })
+        .collect())
+}
+
 /// Get list of local branches merged into main
-/// Excludes: current branch (*), main, master, develop
-fn get_merged_local_branches(main_branch: &str) -> Result<Vec<String>> {
+/// Excludes: current branch (*), protected branches, and branches with
+/// commits newer than the age threshold
+fn get_merged_local_branches(main_branch: &str, config: &GitCleanConfig) -> Result<Vec<String>> {
     let output = git(&["branch", "--merged", main_branch])?;
-
-    let protected_branches = ["main", "master", "develop"];
+    let times = last_commit_times("refs/heads")?;
 
     let branches: Vec<String> = output
         .lines()
         .map(|line| line.trim())
         .filter(|line| !line.starts_with('*')) // Exclude current branch
         .map(|line| line.trim_start_matches("* ").trim())
-        .filter(|branch| !protected_branches.contains(branch)) // Exclude protected branches
+        .filter(|branch| !config.is_protected_with_main(branch, main_branch)) // Exclude protected branches
+        .filter(|branch| !times.get(*branch).is_some_and(|t| config.is_too_recent(*t)))
         .map(|s| s.to_string())
         .collect();
 
@@ -106,8 +185,9 @@ fn get_merged_local_branches(main_branch: &str) -> Result<Vec<String>> {
 }
 
 /// Get list of remote branches merged into origin/main
-/// Excludes: HEAD, main, master, develop, origin/main, origin/master, origin/develop
-fn get_merged_remote_branches(main_branch: &str) -> Result<Vec<String>> {
+/// Excludes: HEAD, protected branches, and branches with commits newer than
+/// the age threshold
+fn get_merged_remote_branches(main_branch: &str, config: &GitCleanConfig) -> Result<Vec<String>> {
     // Check against origin/main to properly evaluate remote branch state
     let output = git(&[
         "branch",
@@ -116,17 +196,18 @@ fn get_merged_remote_branches(main_branch: &str) -> Result<Vec<String>> {
         &format!("origin/{}", main_branch),
     ])?;
 
-    let protected_branches = ["main", "master", "develop"];
+    let times = last_commit_times("refs/remotes/origin")?;
 
     let branches: Vec<String> = output
         .lines()
         .map(|line| line.trim())
         .filter(|line| !line.contains("HEAD")) // Exclude HEAD
+        .filter(|line| !times.get(*line).is_some_and(|t| config.is_too_recent(*t)))
         .filter_map(|line| {
             // Strip "origin/" prefix
             line.strip_prefix("origin/").map(|s| s.to_string())
         })
-        .filter(|branch| !protected_branches.contains(&branch.as_str())) // Exclude protected branches
+        .filter(|branch| !config.is_protected_with_main(branch, main_branch)) // Exclude protected branches
         .collect();
 
     Ok(branches)
@@ -152,74 +233,307 @@ fn delete_remote_branch(branch: &str) -> Result<()> {
 // Main Cleaning Logic
 // =============================================================================
 
-/// Clean up merged local branches
+/// Local branches merged into local main that can be deleted
 /// Evaluates against local main only - remote state is irrelevant
-fn clean_local_branches(main_branch: &str) -> Result<()> {
+fn get_local_candidates(main_branch: &str, config: &GitCleanConfig) -> Result<Vec<String>> {
     let worktree_branches = get_worktree_branches()?;
-    let merged_branches = get_merged_local_branches(main_branch)?;
 
-    for branch in merged_branches {
-        // Skip if branch is used by a worktree
-        if worktree_branches.contains(&branch.to_string()) {
-            continue;
-        }
+    // Skip branches used by a worktree
+    Ok(get_merged_local_branches(main_branch, config)?
+        .into_iter()
+        .filter(|branch| !worktree_branches.contains(branch))
+        .collect())
+}
 
+/// Clean up merged local branches
+fn clean_local_branches(branches: &[String]) {
+    for branch in branches {
         // Delete local branch merged to local main
-        if let Err(e) = delete_local_branch_safe(&branch) {
+        if let Err(e) = delete_local_branch_safe(branch) {
             eprintln!("Error deleting branch '{}': {}", branch, e);
         } else {
             println!("Deleted: {} (local)", branch);
         }
     }
-
-    Ok(())
 }
 
 /// Clean up merged remote branches
-fn clean_remote_branches(main_branch: &str) -> Result<()> {
-    let remote_merged_branches = get_merged_remote_branches(main_branch)?;
-
-    // Process each remote branch merged to origin/main
+fn clean_remote_branches(branches: &[String]) -> Result<()> {
     // Local branch state is irrelevant - remote cleanup is independent
-    for branch in remote_merged_branches {
-        delete_remote_branch(&branch)?;
+    for branch in branches {
+        delete_remote_branch(branch)?;
         println!("Deleted: {} (remote)", branch);
     }
 
     Ok(())
 }
 
+/// Show the branches a cleanup would delete and, unless `yes` is set, ask
+/// before deleting them
+fn clean(main_branch: &str, config: &GitCleanConfig, yes: bool) -> Result<()> {
+    let local =
+        get_local_candidates(main_branch, config).context("Failed to list local branches")?;
+    // Remote branches are evaluated against origin/main, independent of local state
+    let remote = get_merged_remote_branches(main_branch, config)
+        .context("Failed to list remote branches")?;
+
+    if local.is_empty() && remote.is_empty() {
+        println!("No merged branches to clean up.");
+        return Ok(());
+    }
+
+    println!("Merged branches to delete:");
+    print!("{}", interactive::format_plan(&local, &remote));
+    println!();
+
+    if !yes
+        && !interactive::confirm(&format!(
+            "Delete {} branch(es)?",
+            local.len() + remote.len()
+        ))?
+    {
+        println!("Aborted, no branches deleted.");
+        return Ok(());
+    }
+
+    clean_local_branches(&local);
+    clean_remote_branches(&remote).context("Failed to clean remote branches")?;
+
+    Ok(())
+}
+
+/// Let the user pick which merged branches to delete, then delete only those
+fn clean_interactive(main_branch: &str, config: &GitCleanConfig) -> Result<()> {
+    let mut candidates: Vec<Candidate> = get_local_candidates(main_branch, config)?
+        .iter()
+        .map(|branch| Candidate::load(branch, BranchLocation::Local, main_branch))
+        .collect();
+    candidates.extend(
+        get_merged_remote_branches(main_branch, config)?
+            .iter()
+            .map(|branch| Candidate::load(branch, BranchLocation::Remote, main_branch)),
+    );
+
+    if candidates.is_empty() {
+        println!("No merged branches to clean up.");
+        return Ok(());
+    }
+
+    let Some(selected) = interactive::select_candidates(candidates)? else {
+        println!("Aborted, no branches deleted.");
+        return Ok(());
+    };
+
+    if selected.is_empty() {
+        println!("No branches selected.");
+        return Ok(());
+    }
+
+    for candidate in selected {
+        let result = match candidate.location {
+            BranchLocation::Local => delete_local_branch_safe(&candidate.name),
+            BranchLocation::Remote => delete_remote_branch(&candidate.name),
+        };
+        match result {
+            Ok(()) => println!(
+                "Deleted: {} ({})",
+                candidate.name,
+                interactive::format_location(candidate.location)
+            ),
+            Err(e) => eprintln!("Error deleting branch '{}': {}", candidate.name, e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Show local branches whose upstream is gone and stale remote-tracking refs
+/// and, unless `yes` is set, ask before deleting them
+fn clean_gone(config: &GitCleanConfig, dry_run: bool, yes: bool) -> Result<()> {
+    let repo = Repository::open(".").context("Failed to open repository")?;
+    let skip = get_worktree_branches()?;
+
+    let branches = gone::gone_branches(&repo, config, &skip)
+        .context("Failed to list branches with a gone upstream")?;
+    let refs = gone::stale_remote_refs(&repo).context("Failed to list remote-tracking refs")?;
+
+    if branches.is_empty() && refs.is_empty() {
+        println!("No gone branches or stale refs to clean up.");
+        return Ok(());
+    }
+
+    if dry_run {
+        println!("Would delete:");
+        print!("{}", gone::format_plan(&branches, &refs));
+        return Ok(());
+    }
+
+    println!("Gone branches and stale refs to delete:");
+    print!("{}", gone::format_plan(&branches, &refs));
+    println!();
+
+    if !yes
+        && !interactive::confirm(&format!(
+            "Delete {} branch(es) and ref(s)?",
+            branches.len() + refs.len()
+        ))?
+    {
+        println!("Aborted, no branches deleted.");
+        return Ok(());
+    }
+
+    for branch in &branches {
+        match gone::delete_branch(&repo, branch) {
+            Ok(()) => println!("Deleted: {} (local)", branch),
+            Err(e) => eprintln!("Error deleting branch '{}': {:#}", branch, e),
+        }
+    }
+    for name in &refs {
+        gone::delete_ref(&repo, name)?;
+        let short = name.strip_prefix("refs/remotes/").unwrap_or(name);
+        println!("Deleted: {} (remote-tracking)", short);
+    }
+
+    Ok(())
+}
+
+/// Delete merged branches without asking and print a JSON report of every
+/// branch's outcome. Fails after printing the report if any deletion failed.
+fn clean_json(main_branch: &str, config: &GitCleanConfig) -> Result<()> {
+    let inventory = Inventory::load(main_branch, config)?;
+    let report = Report::run(inventory, |branch| match branch.location {
+        BranchLocation::Local => delete_local_branch_safe(&branch.name),
+        BranchLocation::Remote => delete_remote_branch(&branch.name),
+    });
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+
+    if report.summary.errors > 0 {
+        anyhow::bail!("{} branch(es) could not be deleted", report.summary.errors);
+    }
+    Ok(())
+}
+
+/// Report what a normal run would delete, without deleting anything
+fn dry_run(main_branch: &str, config: &GitCleanConfig, format: OutputFormat) -> Result<()> {
+    let inventory = Inventory::load(main_branch, config)?;
+
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&inventory)?);
+        return Ok(());
+    }
+
+    let (local, remote): (Vec<&BranchInfo>, Vec<&BranchInfo>) = inventory
+        .to_delete()
+        .partition(|branch| branch.location == BranchLocation::Local);
+    if local.is_empty() && remote.is_empty() {
+        println!("No merged branches to clean up.");
+        return Ok(());
+    }
+
+    let names = |branches: Vec<&BranchInfo>| -> Vec<String> {
+        branches.iter().map(|b| b.name.clone()).collect()
+    };
+    println!("Would delete:");
+    print!(
+        "{}",
+        interactive::format_plan(&names(local), &names(remote))
+    );
+
+    Ok(())
+}
+
 // =============================================================================
 // Main Entry Point
 // =============================================================================
 
 fn main() -> Result<()> {
-    let _args = Args::parse();
+    let args = Args::parse();
 
-    // Ensure we're in a git repository
-    if !is_git_repo() {
+    // Ensure we're in a git repository, unless cleaning several
+    let batch = args.all_repos.is_some() || args.watched_repos;
+    if !batch && !is_git_repo() {
         anyhow::bail!("Error: Not in a git repository");
     }
 
+    let mut config = GitCleanConfig::load().context("Failed to load git-clean config")?;
+    config.protected_branches.extend(args.protect);
+    if args.older_than.is_some() {
+        config.older_than_days = args.older_than;
+    }
+
+    // Keep stdout machine-readable for JSON reports
+    let json = args.format == OutputFormat::Json;
+    if json && !args.dry_run && !args.yes {
+        anyhow::bail!(
+            "--format json deletes branches without asking; pass --yes to confirm, or --dry-run for the inventory"
+        );
+    }
+
+    if let Some(parent) = &args.all_repos {
+        let repos = batch::find_repos(parent)?;
+        return batch::run(&repos, &config, args.dry_run, args.yes, args.format);
+    }
+    if args.watched_repos {
+        let repos = batch::watched_repos()?;
+        return batch::run(&repos, &config, args.dry_run, args.yes, args.format);
+    }
+
     // Detect main branch (main or master)
     let main_branch = get_main_branch().context("Failed to determine main branch")?;
 
-    if main_branch == "master" {
+    if main_branch == "master" && !json {
         println!("Using 'master' as main branch");
     }
 
-    // Fetch and prune remote references
-    println!("Fetching and pruning remote references...");
-    git(&["fetch", "--prune"]).context("Failed to fetch and prune")?;
+    // Fetch and prune remote references; gone mode prunes every remote so
+    // that only refs of unconfigured remotes are left stale
+    if !json {
+        println!("Fetching and pruning remote references...");
+    }
+    let fetch: &[&str] = if args.gone {
+        &["fetch", "--all", "--prune"]
+    } else {
+        &["fetch", "--prune"]
+    };
+    git(fetch).context("Failed to fetch and prune")?;
+
+    if json && args.dry_run {
+        return dry_run(&main_branch, &config, args.format);
+    }
+    if json {
+        return clean_json(&main_branch, &config);
+    }
 
     println!("Evaluating branches");
     println!();
 
-    // Clean local branches (includes handling of associated remotes)
-    clean_local_branches(&main_branch).context("Failed to clean local branches")?;
+    if args.gone {
+        clean_gone(&config, args.dry_run, args.yes)?;
+        println!();
+        if args.dry_run {
+            println!("Dry run, no branches deleted.");
+        } else {
+            println!("Done!");
+        }
+        return Ok(());
+    }
+
+    if args.dry_run {
+        dry_run(&main_branch, &config, args.format)?;
+        println!();
+        println!("Dry run, no branches deleted.");
+        return Ok(());
+    }
+
+    if args.interactive {
+        clean_interactive(&main_branch, &config)?;
+        println!();
+        println!("Done!");
+        return Ok(());
+    }
 
-    // Clean remote branches (independent of local branch state)
-    clean_remote_branches(&main_branch).context("Failed to clean remote branches")?;
+    clean(&main_branch, &config, args.yes)?;
 
     println!();
     println!("Done!");
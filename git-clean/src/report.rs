@@ -0,0 +1,150 @@
+// JSON report of a cleanup run, for cron jobs that aggregate results
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::interactive::BranchLocation;
+use crate::inventory::{Action, BranchInfo, Inventory};
+
+/// What happened to a branch during the run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Deleted,
+    Skipped,
+    Error,
+}
+
+/// One branch and what happened to it
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchResult {
+    pub name: String,
+    pub location: BranchLocation,
+    pub outcome: Outcome,
+    /// Why the branch was skipped (see the inventory's reasons)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<&'static str>,
+    /// Why the deletion failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Number of branches with each outcome
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    pub deleted: usize,
+    pub skipped: usize,
+    pub errors: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub repository: String,
+    pub main_branch: String,
+    pub summary: Summary,
+    pub branches: Vec<BranchResult>,
+}
+
+impl Report {
+    /// Delete every branch the inventory marks for deletion with `delete`,
+    /// recording the outcome of each branch
+    pub fn run(inventory: Inventory, mut delete: impl FnMut(&BranchInfo) -> Result<()>) -> Self {
+        let mut summary = Summary::default();
+        let mut branches = Vec::new();
+
+        for branch in &inventory.branches {
+            let (outcome, error) = match branch.action {
+                Action::Keep => (Outcome::Skipped, None),
+                Action::Delete => match delete(branch) {
+                    Ok(()) => (Outcome::Deleted, None),
+                    Err(e) => (Outcome::Error, Some(format!("{:#}", e).trim().to_string())),
+                },
+            };
+            match outcome {
+                Outcome::Deleted => summary.deleted += 1,
+                Outcome::Skipped => summary.skipped += 1,
+                Outcome::Error => summary.errors += 1,
+            }
+            branches.push(BranchResult {
+                name: branch.name.clone(),
+                location: branch.location,
+                outcome,
+                reason: branch.reason,
+                error,
+            });
+        }
+
+        Self {
+            repository: inventory.repository,
+            main_branch: inventory.main_branch,
+            summary,
+            branches,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn branch(name: &str, location: BranchLocation, reason: Option<&'static str>) -> BranchInfo {
+        BranchInfo {
+            name: name.to_string(),
+            location,
+            merged: reason != Some("unmerged"),
+            last_commit: "2025-10-01T12:00:00+02:00".to_string(),
+            last_commit_age: "3 weeks ago".to_string(),
+            author: "Alice".to_string(),
+            action: if reason.is_none() {
+                Action::Delete
+            } else {
+                Action::Keep
+            },
+            reason,
+        }
+    }
+
+    #[test]
+    fn test_run_records_outcomes() {
+        let inventory = Inventory {
+            repository: "/src/app".to_string(),
+            main_branch: "main".to_string(),
+            branches: vec![
+                branch("feature", BranchLocation::Local, None),
+                branch("main", BranchLocation::Local, Some("protected")),
+                branch("fix", BranchLocation::Remote, None),
+            ],
+        };
+
+        let mut deleted = Vec::new();
+        let report = Report::run(inventory, |branch| {
+            if branch.location == BranchLocation::Remote {
+                anyhow::bail!("git command failed: remote rejected\n");
+            }
+            deleted.push(branch.name.clone());
+            Ok(())
+        });
+
+        assert_eq!(deleted, vec!["feature"]);
+        assert_eq!(
+            report.summary,
+            Summary {
+                deleted: 1,
+                skipped: 1,
+                errors: 1
+            }
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["branches"][0]["outcome"], "deleted");
+        assert!(json["branches"][0].get("reason").is_none());
+        assert_eq!(json["branches"][1]["outcome"], "skipped");
+        assert_eq!(json["branches"][1]["reason"], "protected");
+        assert_eq!(json["branches"][2]["outcome"], "error");
+        assert_eq!(json["branches"][2]["location"], "remote");
+        assert_eq!(
+            json["branches"][2]["error"],
+            "git command failed: remote rejected"
+        );
+    }
+}
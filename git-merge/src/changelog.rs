@@ -0,0 +1,136 @@
+// Changelog enforcement
+//
+// Before merging, every CHANGELOG.md that covers a file changed on the feature
+// branch must itself have been changed on the branch and must still validate.
+// A changelog covers the files in its directory and below; when changelogs are
+// nested, a file is covered by the closest one.
+
+use anyhow::{Result, bail};
+use std::path::Path;
+
+use crate::git::git_output;
+
+const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+/// Directory containing a changelog, relative to the repository root ("" for the root)
+fn changelog_dir(changelog: &str) -> &str {
+    changelog
+        .strip_suffix(CHANGELOG_FILE)
+        .map(|dir| dir.trim_end_matches('/'))
+        .unwrap_or("")
+}
+
+/// Find the closest changelog covering the given file, if any
+fn covering_changelog<'a>(file: &str, changelogs: &'a [String]) -> Option<&'a String> {
+    changelogs
+        .iter()
+        .filter(|changelog| {
+            let dir = changelog_dir(changelog);
+            dir.is_empty()
+                || file
+                    .strip_prefix(dir)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+        .max_by_key(|changelog| changelog_dir(changelog).len())
+}
+
+/// Changelogs that cover at least one of the changed files, in first-seen order
+pub fn required_changelogs<'a>(changed: &[String], changelogs: &'a [String]) -> Vec<&'a String> {
+    let mut required: Vec<&String> = Vec::new();
+    for file in changed {
+        if let Some(changelog) = covering_changelog(file, changelogs)
+            && !required.contains(&changelog)
+        {
+            required.push(changelog);
+        }
+    }
+    required
+}
+
+/// Check that the feature branch updated, and did not break, each changelog covering its changes
+pub fn check_changelogs(feature_branch: &str, target_branch: &str) -> Result<()> {
+    let changelogs: Vec<String> = git_output(&["ls-tree", "-r", "--name-only", feature_branch])?
+        .lines()
+        .filter(|path| *path == CHANGELOG_FILE || path.ends_with("/CHANGELOG.md"))
+        .map(str::to_string)
+        .collect();
+
+    if changelogs.is_empty() {
+        return Ok(());
+    }
+
+    let range = format!("{}...{}", target_branch, feature_branch);
+    let changed: Vec<String> = git_output(&["diff", "--name-only", &range])?
+        .lines()
+        .map(str::to_string)
+        .collect();
+
+    let mut problems = Vec::new();
+    for changelog in required_changelogs(&changed, &changelogs) {
+        if !changed.contains(changelog) {
+            problems.push(format!(
+                "{} was not updated on '{}'",
+                changelog, feature_branch
+            ));
+            continue;
+        }
+
+        let content = git_output(&["show", &format!("{}:{}", feature_branch, changelog)])?;
+        if let Err(e) = changelog_validator::validate_content(&content, Path::new(changelog)) {
+            problems.push(e.to_string());
+        }
+    }
+
+    if !problems.is_empty() {
+        bail!(
+            "Changelog check failed:\n  - {}\nUpdate the changelog, or pass --skip-changelog to merge anyway.",
+            problems.join("\n  - ")
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_changelog_dir() {
+        assert_eq!(changelog_dir("CHANGELOG.md"), "");
+        assert_eq!(changelog_dir("gc/CHANGELOG.md"), "gc");
+        assert_eq!(changelog_dir("tools/gc/CHANGELOG.md"), "tools/gc");
+    }
+
+    #[test]
+    fn test_required_changelogs_uses_closest() {
+        let changelogs = strings(&["CHANGELOG.md", "gc/CHANGELOG.md", "sandy/CHANGELOG.md"]);
+        let changed = strings(&["gc/src/main.rs", "gc/README.md", "Cargo.toml"]);
+
+        let required = required_changelogs(&changed, &changelogs);
+        assert_eq!(required, vec!["gc/CHANGELOG.md", "CHANGELOG.md"]);
+    }
+
+    #[test]
+    fn test_required_changelogs_ignores_uncovered_files() {
+        let changelogs = strings(&["gc/CHANGELOG.md"]);
+        let changed = strings(&["Cargo.toml", "gcx/src/main.rs", "docs/gc/notes.md"]);
+
+        assert!(required_changelogs(&changed, &changelogs).is_empty());
+    }
+
+    #[test]
+    fn test_changelog_covers_itself() {
+        let changelogs = strings(&["gc/CHANGELOG.md"]);
+        let changed = strings(&["gc/CHANGELOG.md"]);
+
+        assert_eq!(
+            required_changelogs(&changed, &changelogs),
+            vec!["gc/CHANGELOG.md"]
+        );
+    }
+}
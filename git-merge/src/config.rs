@@ -0,0 +1,105 @@
+// git-merge-specific configuration
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitMergeConfig {
+    /// Delete the feature branch from origin after a successful merge
+    #[serde(default)]
+    pub delete_remote: bool,
+
+    /// Branches that are never deleted from origin
+    #[serde(default = "default_protected_branches")]
+    pub protected_branches: Vec<String>,
+
+    /// Shell commands to run on the merged result before pushing (e.g.
+    /// "cargo test"); a failure resets the target branch
+    #[serde(default)]
+    pub checks: Vec<String>,
+}
+
+fn default_protected_branches() -> Vec<String> {
+    ["main", "master", "develop"]
+        .iter()
+        .map(|branch| branch.to_string())
+        .collect()
+}
+
+impl Default for GitMergeConfig {
+    fn default() -> Self {
+        Self {
+            delete_remote: false,
+            protected_branches: default_protected_branches(),
+            checks: Vec::new(),
+        }
+    }
+}
+
+impl GitMergeConfig {
+    /// Get the config file path: ~/.config/cli-programs/git-merge.toml
+    pub fn config_path() -> Result<PathBuf> {
+        let home = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE"))?;
+        Ok(PathBuf::from(home)
+            .join(".config")
+            .join("cli-programs")
+            .join("git-merge.toml"))
+    }
+
+    /// Load config from file, returning default if file doesn't exist
+    pub fn load() -> Result<Self> {
+        let path = Self::config_path()?;
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        let config: GitMergeConfig = toml::from_str(&content)?;
+        Ok(config)
+    }
+
+    pub fn is_protected(&self, branch: &str) -> bool {
+        self.protected_branches.iter().any(|b| b == branch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = GitMergeConfig::default();
+        assert!(!config.delete_remote);
+        assert!(config.is_protected("main"));
+        assert!(config.is_protected("develop"));
+        assert!(!config.is_protected("feature/login"));
+        assert!(config.checks.is_empty());
+    }
+
+    #[test]
+    fn test_config_path() {
+        let path = GitMergeConfig::config_path().unwrap();
+        assert!(path.ends_with("cli-programs/git-merge.toml"));
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config: GitMergeConfig = toml::from_str("delete_remote = true\n").unwrap();
+        assert!(config.delete_remote);
+        assert!(config.is_protected("master"));
+
+        let config: GitMergeConfig =
+            toml::from_str("protected_branches = [\"release\"]\n").unwrap();
+        assert!(!config.delete_remote);
+        assert!(config.is_protected("release"));
+        assert!(!config.is_protected("main"));
+
+        let config: GitMergeConfig =
+            toml::from_str("checks = [\"cargo test\", \"cargo clippy\"]\n").unwrap();
+        assert_eq!(config.checks, vec!["cargo test", "cargo clippy"]);
+    }
+}
@@ -0,0 +1,22 @@
+// Running git and capturing its output
+
+use anyhow::{Context, Result, bail};
+use std::process::Command;
+
+/// Run git and return its trimmed stdout, failing on a non-zero exit
+pub fn git_output(args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run git command: git {}", args.join(" ")))?;
+
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
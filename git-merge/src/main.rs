@@ -1,7 +1,20 @@
 use anyhow::{Context, Result, bail};
 use clap::Parser;
+use std::path::PathBuf;
 use std::process::{Command, Stdio};
 
+mod changelog;
+mod config;
+mod git;
+mod summary;
+mod transaction;
+mod worktree;
+
+use config::GitMergeConfig;
+use summary::{MergeSummary, Outcome};
+use transaction::Transaction;
+use worktree::TempWorktree;
+
 /// Merge a feature branch into main with optional squash
 #[derive(Parser, Debug)]
 #[command(name = "git-merge")]
@@ -18,6 +31,42 @@ struct Args {
     /// Target branch name (defaults to 'main')
     #[arg(short = 't', long, default_value = "main")]
     target_branch: String,
+
+    /// Merge even if the branch did not update its CHANGELOG.md or it fails validation
+    #[arg(long)]
+    skip_changelog: bool,
+
+    /// Delete the feature branch from origin after a successful merge
+    #[arg(long, conflicts_with = "keep_remote")]
+    delete_remote: bool,
+
+    /// Keep the feature branch on origin, even if the config deletes it
+    #[arg(long)]
+    keep_remote: bool,
+
+    /// Command to run on the merged result before pushing (repeatable; replaces the configured checks)
+    #[arg(long = "check", value_name = "COMMAND")]
+    checks: Vec<String>,
+
+    /// Push without running the configured checks
+    #[arg(long, conflicts_with = "checks")]
+    skip_checks: bool,
+
+    /// Merge in a temporary worktree, leaving the current working tree untouched
+    #[arg(long)]
+    worktree: bool,
+
+    /// Also write the final summary to this file
+    #[arg(long, value_name = "PATH")]
+    summary_file: Option<PathBuf>,
+
+    /// Show a macOS notification when the merge completes
+    #[arg(long)]
+    notify: bool,
+
+    /// Print the commands that would run without pushing, checking out, merging, or deleting anything
+    #[arg(long)]
+    dry_run: bool,
 }
 
 fn main() {
@@ -34,27 +83,148 @@ fn run() -> Result<()> {
     check_git_installed()?;
     check_in_git_repo()?;
 
+    let config = GitMergeConfig::load().context("Failed to load git-merge config")?;
+
     // Determine feature branch
-    let feature_branch = determine_feature_branch(args.branch, &args.target_branch)?;
+    let feature_branch = determine_feature_branch(args.branch.clone(), &args.target_branch)?;
     println!("Feature branch: {}", feature_branch);
 
+    // Refuse to merge without a valid changelog update
+    if args.skip_changelog {
+        println!("Skipping changelog check.");
+    } else {
+        println!("Checking changelog updates...");
+        let result = changelog::check_changelogs(&feature_branch, &args.target_branch);
+        if args.dry_run
+            && let Err(e) = &result
+        {
+            println!("The merge would be refused: {}", e);
+            return Ok(());
+        }
+        result?;
+    }
+
+    let delete_remote = should_delete_remote(&feature_branch, &args, &config);
+    let checks = merge_checks(&args, &config);
+
+    if args.dry_run {
+        print_dry_run(&feature_branch, &args, &checks, delete_remote)?;
+        return Ok(());
+    }
+
+    let mut tx = Transaction::begin(&feature_branch, &args.target_branch, args.worktree)?;
+    let mut worktree = if args.worktree {
+        let worktree = TempWorktree::enter(&args.target_branch)?;
+        tx.complete(format!(
+            "Checked out '{}' in a temporary worktree",
+            args.target_branch
+        ));
+        Some(worktree)
+    } else {
+        None
+    };
+
+    let outcome = match merge(&feature_branch, &args, &checks, delete_remote, &mut tx) {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            // Roll back inside the worktree, before it is removed
+            tx.roll_back();
+            return Err(e);
+        }
+    };
+
+    println!("Merge process completed successfully.");
+    // Staged changes would be lost with the worktree
+    let kept_worktree = match &mut worktree {
+        Some(worktree) if !outcome.committed => {
+            worktree.keep();
+            Some(worktree.path())
+        }
+        _ => None,
+    };
+    let summary = MergeSummary::collect(
+        &feature_branch,
+        &args.target_branch,
+        args.squash,
+        &outcome,
+        config.is_protected(&feature_branch),
+        kept_worktree,
+    )?;
+    println!();
+    print!("{}", summary.render());
+
+    if let Some(path) = &args.summary_file {
+        summary.write(path)?;
+    }
+    if args.notify
+        && let Err(e) = summary.notify()
+    {
+        eprintln!("Warning: {}", e);
+    }
+
+    Ok(())
+}
+
+/// Whether to delete the feature branch from origin after merging. The flags
+/// override the config; protected branches are always kept.
+fn should_delete_remote(feature_branch: &str, args: &Args, config: &GitMergeConfig) -> bool {
+    if args.keep_remote || !(args.delete_remote || config.delete_remote) {
+        return false;
+    }
+    if config.is_protected(feature_branch) {
+        println!(
+            "'{}' is a protected branch, so it will be kept on origin.",
+            feature_branch
+        );
+        return false;
+    }
+    true
+}
+
+/// The commands to run on the merged result: those passed with `--check`,
+/// or else the configured ones
+fn merge_checks(args: &Args, config: &GitMergeConfig) -> Vec<String> {
+    if args.skip_checks {
+        Vec::new()
+    } else if !args.checks.is_empty() {
+        args.checks.clone()
+    } else {
+        config.checks.clone()
+    }
+}
+
+/// Push, update the target branch, merge, check, and push the result,
+/// recording each completed step in `tx`
+fn merge(
+    feature_branch: &str,
+    args: &Args,
+    checks: &[String],
+    delete_remote: bool,
+    tx: &mut Transaction,
+) -> Result<Outcome> {
     // Push feature branch to origin
     println!(
         "Ensuring remote 'origin' has the latest '{}'...",
         feature_branch
     );
-    push_branch(&feature_branch)?;
-
-    // Switch to main branch
-    println!("Checking out '{}'...", args.target_branch);
-    checkout_branch(&args.target_branch)?;
+    push_branch(feature_branch)?;
+    tx.complete(format!("Pushed '{}' to origin", feature_branch));
+
+    // Switch to main branch (a temporary worktree already has it checked out)
+    if !args.worktree {
+        println!("Checking out '{}'...", args.target_branch);
+        checkout_branch(&args.target_branch)?;
+        tx.complete(format!("Checked out '{}'", args.target_branch));
+    }
 
     // Update main branch
     println!("Fetching updates from origin...");
     run_git_command(&["fetch", "origin"])?;
+    tx.complete("Fetched from origin");
 
     println!("Pulling latest changes for '{}'...", args.target_branch);
     run_git_command(&["pull", "origin", &args.target_branch])?;
+    tx.complete(format!("Pulled '{}' from origin", args.target_branch));
 
     // Check for clean status
     if !is_git_status_clean()? {
@@ -65,19 +235,216 @@ fn run() -> Result<()> {
     }
 
     // Perform merge
-    if args.squash {
-        perform_squash_merge(&feature_branch, &args.target_branch)?;
+    let base_commit = get_current_commit()?;
+    let committed = if args.squash {
+        perform_squash_merge(feature_branch, &args.target_branch, checks, tx)?
     } else {
-        perform_simple_merge(&feature_branch)?;
-    }
+        perform_simple_merge(feature_branch, checks, tx)?
+    };
 
     println!("Pushing '{}' to origin...", args.target_branch);
     push_branch(&args.target_branch)?;
+    tx.complete(format!("Pushed '{}' to origin", args.target_branch));
+    tx.target_pushed();
 
-    println!("Merge process completed successfully.");
+    if delete_remote && committed {
+        delete_remote_branch(feature_branch);
+    }
+
+    Ok(Outcome {
+        base_commit,
+        committed,
+    })
+}
+
+/// Delete a branch from origin, warning instead of failing if it can't be
+/// deleted; the merge itself has already succeeded
+fn delete_remote_branch(branch: &str) {
+    println!("Deleting '{}' from origin...", branch);
+    let output = Command::new("git")
+        .args(["push", "origin", "--delete", branch])
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("remote ref does not exist") {
+                eprintln!(
+                    "Warning: '{}' is already gone from origin. Nothing to delete.",
+                    branch
+                );
+                // Drop the stale remote-tracking branch so it isn't reported as still there
+                let _ = Command::new("git")
+                    .args([
+                        "update-ref",
+                        "-d",
+                        &format!("refs/remotes/origin/{}", branch),
+                    ])
+                    .status();
+            } else {
+                eprintln!(
+                    "Warning: Failed to delete '{}' from origin: {}",
+                    branch,
+                    stderr.trim()
+                );
+            }
+        }
+        Err(e) => eprintln!("Warning: Failed to delete '{}' from origin: {}", branch, e),
+    }
+}
+
+/// Print the commands a merge would run, without running them
+fn print_dry_run(
+    feature_branch: &str,
+    args: &Args,
+    checks: &[String],
+    delete_remote: bool,
+) -> Result<()> {
+    let commits = count_commits(&args.target_branch, feature_branch)?;
+    let gc_available = args.squash && is_gc_available()?;
+    let steps = planned_steps(
+        feature_branch,
+        args,
+        checks,
+        commits,
+        gc_available,
+        delete_remote,
+    );
+
+    println!();
+    println!(
+        "Dry run: {} merge of '{}' into '{}' ({} commits not on the local '{}')",
+        if args.squash { "squash" } else { "simple" },
+        feature_branch,
+        args.target_branch,
+        commits,
+        args.target_branch
+    );
+    for (i, step) in steps.iter().enumerate() {
+        println!("  {}. {}", i + 1, step);
+    }
+    println!("Nothing was changed.");
     Ok(())
 }
 
+/// The commands a merge runs, in order, with notes where the outcome
+/// depends on the branch. `commits` is the number of feature branch commits
+/// not on the target branch; `gc_available` only matters for squash merges.
+fn planned_steps(
+    feature_branch: &str,
+    args: &Args,
+    checks: &[String],
+    commits: usize,
+    gc_available: bool,
+    delete_remote: bool,
+) -> Vec<String> {
+    let target_branch = &args.target_branch;
+    // Without gc, a squash merge with new commits is left staged and not committed
+    let merged = !args.squash || commits == 0 || gc_available;
+    // In a worktree, the feature branch is usually still checked out by the caller
+    let delete_local = |flag: &str| {
+        let mut step = format!("git branch {} {}", flag, feature_branch);
+        if args.worktree {
+            step.push_str(&format!(
+                " (skipped if '{}' is checked out in another worktree)",
+                feature_branch
+            ));
+        }
+        step
+    };
+    let run_checks = |steps: &mut Vec<String>| {
+        steps.extend(checks.iter().map(|check| {
+            format!(
+                "{} (a failure resets '{}' and stops the merge)",
+                check, target_branch
+            )
+        }));
+    };
+
+    let mut steps = if args.worktree {
+        vec![
+            format!("git worktree add <temp dir> {}", target_branch),
+            format!("git push origin {}", feature_branch),
+        ]
+    } else {
+        vec![
+            format!("git push origin {}", feature_branch),
+            format!("git checkout {}", target_branch),
+        ]
+    };
+    steps.push("git fetch origin".to_string());
+    steps.push(format!("git pull origin {}", target_branch));
+
+    if !args.squash {
+        steps.push(format!("git merge {}", feature_branch));
+        run_checks(&mut steps);
+        steps.push(delete_local("-d"));
+    } else if commits == 0 {
+        steps.push(format!(
+            "git merge --squash {} (no new commits, so the commit is skipped)",
+            feature_branch
+        ));
+        steps.push(delete_local("-D"));
+    } else if gc_available {
+        steps.push(format!("git merge --squash {}", feature_branch));
+        if checks.is_empty() {
+            steps.push(format!(
+                "gc --context <history of {} commits from '{}'> (generates the message, commits, and pushes)",
+                commits, feature_branch
+            ));
+        } else {
+            steps.push(format!(
+                "gc --context <history of {} commits from '{}'> --nopush (generates the message and commits)",
+                commits, feature_branch
+            ));
+            run_checks(&mut steps);
+        }
+        steps.push(delete_local("-D"));
+    } else {
+        steps.push(format!(
+            "git merge --squash {} (gc not found in PATH, so the changes are left staged for a manual commit and '{}' is kept)",
+            feature_branch, feature_branch
+        ));
+    }
+
+    steps.push(format!("git push origin {}", target_branch));
+    if delete_remote && merged {
+        steps.push(format!("git push origin --delete {}", feature_branch));
+    }
+    if args.worktree {
+        steps.push(if merged {
+            "git worktree remove --force <temp dir>".to_string()
+        } else {
+            "The worktree is kept for committing the staged changes".to_string()
+        });
+    }
+    steps
+}
+
+/// Number of commits on `feature_branch` that are not on `target_branch`
+fn count_commits(target_branch: &str, feature_branch: &str) -> Result<usize> {
+    let output = Command::new("git")
+        .args([
+            "rev-list",
+            "--count",
+            &format!("{}..{}", target_branch, feature_branch),
+        ])
+        .output()
+        .context("Failed to count commits")?;
+
+    if !output.status.success() {
+        bail!(
+            "Failed to compare '{}' with '{}': {}",
+            feature_branch,
+            target_branch,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8(output.stdout)?.trim().parse()?)
+}
+
 fn check_git_installed() -> Result<()> {
     Command::new("git")
         .arg("--version")
@@ -183,7 +550,13 @@ fn is_git_status_clean() -> Result<bool> {
     Ok(output.stdout.is_empty())
 }
 
-fn perform_simple_merge(feature_branch: &str) -> Result<()> {
+/// Merge the feature branch into the current branch and delete it locally.
+/// Returns whether the merge was completed.
+fn perform_simple_merge(
+    feature_branch: &str,
+    checks: &[String],
+    tx: &mut Transaction,
+) -> Result<bool> {
     println!(
         "Performing simple merge of '{}' into current branch...",
         feature_branch
@@ -195,27 +568,51 @@ fn perform_simple_merge(feature_branch: &str) -> Result<()> {
         .context("Failed to merge branch")?;
 
     if !status.success() {
-        bail!("Merge failed. Please resolve conflicts manually and complete the merge.");
+        bail!(
+            "Merging '{}' failed. Resolve the conflicts on '{}' and run git-merge again.",
+            feature_branch,
+            feature_branch
+        );
     }
+    tx.complete(format!("Merged '{}'", feature_branch));
+    run_checks(checks, tx)?;
 
     // Delete the feature branch after successful merge
+    if let Some(location) = worktree::checked_out_at(feature_branch)? {
+        println!(
+            "Keeping local branch '{}': it is checked out in {}",
+            feature_branch,
+            location.display()
+        );
+        return Ok(true);
+    }
     println!("Deleting local branch '{}'...", feature_branch);
     let status = Command::new("git")
         .args(["branch", "-d", feature_branch])
         .status()
         .context("Failed to delete branch")?;
 
-    if !status.success() {
+    if status.success() {
+        tx.complete(format!("Deleted local branch '{}'", feature_branch));
+    } else {
         eprintln!(
             "Warning: Failed to delete local branch '{}'. You may need to delete it manually.",
             feature_branch
         );
     }
 
-    Ok(())
+    Ok(true)
 }
 
-fn perform_squash_merge(feature_branch: &str, main_branch: &str) -> Result<()> {
+/// Squash merge the feature branch and commit it with gc. Returns whether
+/// the merge was completed, which it isn't if gc is missing and the changes
+/// are left staged.
+fn perform_squash_merge(
+    feature_branch: &str,
+    main_branch: &str,
+    checks: &[String],
+    tx: &mut Transaction,
+) -> Result<bool> {
     // Get feature branch history
     println!("Gathering commit history from '{}'...", feature_branch);
     let output = Command::new("git")
@@ -251,12 +648,14 @@ fn perform_squash_merge(feature_branch: &str, main_branch: &str) -> Result<()> {
         let has_conflicts = check_for_conflicts()?;
         if has_conflicts {
             bail!(
-                "Merge conflict detected after 'git merge --squash'. Resolve conflicts, then run 'gc' manually."
+                "Merge conflict detected after 'git merge --squash'. Resolve the conflicts on '{}' and run git-merge again.",
+                feature_branch
             );
         } else {
             bail!("git merge --squash failed for an unknown reason.");
         }
     }
+    tx.complete(format!("Squash merged '{}'", feature_branch));
 
     // Check if squash merge resulted in any changes
     if is_git_status_clean()? {
@@ -270,7 +669,7 @@ fn perform_squash_merge(feature_branch: &str, main_branch: &str) -> Result<()> {
         if !is_gc_available()? {
             eprintln!("Warning: 'gc' command not found. Changes are staged.");
             eprintln!("Please commit manually or install 'gc' from this workspace.");
-            return Ok(());
+            return Ok(false);
         }
 
         // Generate commit message and commit using gc
@@ -283,14 +682,19 @@ fn perform_squash_merge(feature_branch: &str, main_branch: &str) -> Result<()> {
         let last_commit_before = get_current_commit()?;
         println!("Last commit before gc: {}", last_commit_before);
 
-        // Run gc with context
+        // Run gc with context. With checks to run, gc only commits so that a
+        // failing check can still undo the merge.
+        let mut gc_args = vec!["--context", &context_msg];
+        if !checks.is_empty() {
+            gc_args.push("--nopush");
+        }
         let status = Command::new("gc")
-            .args(["--context", &context_msg])
+            .args(&gc_args)
             .status()
             .context("Failed to run gc")?;
 
         if !status.success() {
-            bail!("'gc' failed. The squashed changes are staged. Please commit manually.");
+            bail!("'gc' failed to commit the squashed changes.");
         }
 
         let last_commit_after = get_current_commit()?;
@@ -298,27 +702,65 @@ fn perform_squash_merge(feature_branch: &str, main_branch: &str) -> Result<()> {
 
         if last_commit_before == last_commit_after {
             bail!(
-                "'gc' script completed, but no new commit was created on '{}'. The squashed changes are likely still staged. Please investigate and commit manually.",
+                "'gc' script completed, but no new commit was created on '{}'.",
                 main_branch
             );
         }
         println!("New commit successfully created by gc.");
+        if checks.is_empty() {
+            // gc pushes the commit it creates
+            tx.complete(format!(
+                "Committed {} with gc and pushed it",
+                last_commit_after
+            ));
+            tx.target_pushed();
+        } else {
+            tx.complete(format!("Committed {} with gc", last_commit_after));
+            run_checks(checks, tx)?;
+        }
     }
 
     // Clean up local branch
+    if let Some(location) = worktree::checked_out_at(feature_branch)? {
+        println!(
+            "Keeping local branch '{}': it is checked out in {}",
+            feature_branch,
+            location.display()
+        );
+        return Ok(true);
+    }
     println!("Force deleting local branch '{}'...", feature_branch);
     let status = Command::new("git")
         .args(["branch", "-D", feature_branch])
         .status()
         .context("Failed to delete branch")?;
 
-    if !status.success() {
+    if status.success() {
+        tx.complete(format!("Deleted local branch '{}'", feature_branch));
+    } else {
         eprintln!(
             "Warning: Failed to force delete local branch '{}'. Check permissions or other issues.",
             feature_branch
         );
     }
 
+    Ok(true)
+}
+
+/// Run each check on the merged result, failing on the first one that fails
+fn run_checks(checks: &[String], tx: &mut Transaction) -> Result<()> {
+    for check in checks {
+        println!("Running check: {}", check);
+        let status = Command::new("sh")
+            .args(["-c", check])
+            .status()
+            .with_context(|| format!("Failed to run check: {}", check))?;
+
+        if !status.success() {
+            bail!("Check failed on the merged result: {}", check);
+        }
+        tx.complete(format!("Check passed: {}", check));
+    }
     Ok(())
 }
 
@@ -362,3 +804,109 @@ fn get_current_commit() -> Result<String> {
 
     Ok(String::from_utf8(output.stdout)?.trim().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(flags: &[&str]) -> Args {
+        Args::parse_from(["git-merge"].iter().chain(flags))
+    }
+
+    #[test]
+    fn test_planned_steps_simple_merge() {
+        let steps = planned_steps("feature", &args(&[]), &[], 2, false, false);
+        assert_eq!(
+            steps,
+            vec![
+                "git push origin feature",
+                "git checkout main",
+                "git fetch origin",
+                "git pull origin main",
+                "git merge feature",
+                "git branch -d feature",
+                "git push origin main",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_planned_steps_squash_merge() {
+        let args = args(&["--squash", "-t", "develop"]);
+        let steps = planned_steps("feature", &args, &[], 3, true, false);
+        assert_eq!(steps[4], "git merge --squash feature");
+        assert!(steps[5].starts_with("gc --context <history of 3 commits from 'feature'>"));
+        assert_eq!(steps[6], "git branch -D feature");
+        assert_eq!(steps[7], "git push origin develop");
+    }
+
+    #[test]
+    fn test_planned_steps_squash_without_gc_or_commits() {
+        let squash = args(&["--squash"]);
+        let steps = planned_steps("feature", &squash, &[], 3, false, true);
+        assert_eq!(steps.len(), 6);
+        assert!(steps[4].contains("gc not found in PATH"));
+        assert!(!steps.iter().any(|s| s.contains("git branch")));
+        // The branch is kept, so it isn't deleted from origin either
+        assert!(!steps.iter().any(|s| s.contains("--delete")));
+
+        let steps = planned_steps("feature", &squash, &[], 0, true, false);
+        assert!(steps[4].contains("commit is skipped"));
+        assert!(!steps.iter().any(|s| s.starts_with("gc ")));
+        assert_eq!(steps[5], "git branch -D feature");
+    }
+
+    #[test]
+    fn test_planned_steps_delete_remote() {
+        let steps = planned_steps("feature", &args(&[]), &[], 2, false, true);
+        assert_eq!(steps.len(), 8);
+        assert_eq!(steps[6], "git push origin main");
+        assert_eq!(steps[7], "git push origin --delete feature");
+    }
+
+    #[test]
+    fn test_planned_steps_worktree() {
+        let steps = planned_steps("feature", &args(&["--worktree"]), &[], 2, false, false);
+        assert_eq!(steps[0], "git worktree add <temp dir> main");
+        assert_eq!(steps[1], "git push origin feature");
+        assert!(!steps.iter().any(|s| s.starts_with("git checkout")));
+        assert!(steps[5].starts_with("git branch -d feature (skipped if 'feature' is checked out"));
+        assert_eq!(steps[7], "git worktree remove --force <temp dir>");
+
+        let args = args(&["--worktree", "--squash"]);
+        let steps = planned_steps("feature", &args, &[], 3, false, false);
+        assert!(steps.last().unwrap().starts_with("The worktree is kept"));
+    }
+
+    #[test]
+    fn test_planned_steps_checks() {
+        let checks = vec!["cargo test".to_string()];
+        let steps = planned_steps("feature", &args(&[]), &checks, 2, false, false);
+        assert_eq!(steps[4], "git merge feature");
+        assert!(steps[5].starts_with("cargo test (a failure resets 'main'"));
+        assert_eq!(steps[6], "git branch -d feature");
+
+        // gc must not push before the checks have passed
+        let steps = planned_steps("feature", &args(&["--squash"]), &checks, 3, true, false);
+        assert!(steps[5].contains("--nopush"));
+        assert!(steps[6].starts_with("cargo test"));
+        assert_eq!(steps[8], "git push origin main");
+    }
+
+    #[test]
+    fn test_merge_checks() {
+        let config = GitMergeConfig {
+            checks: vec!["cargo test".to_string()],
+            ..GitMergeConfig::default()
+        };
+        assert_eq!(merge_checks(&args(&[]), &config), vec!["cargo test"]);
+        assert_eq!(
+            merge_checks(
+                &args(&["--check", "make lint", "--check", "make test"]),
+                &config
+            ),
+            vec!["make lint", "make test"]
+        );
+        assert!(merge_checks(&args(&["--skip-checks"]), &config).is_empty());
+    }
+}
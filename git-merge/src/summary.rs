@@ -0,0 +1,269 @@
+// Summary of a completed merge
+//
+// The progress output of a merge is long and interleaved with git's own, so
+// the end result (what was merged, what was deleted, and what is left to do)
+// is collected from the repository once the merge is done and reported in one
+// place.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::git::git_output;
+use crate::transaction::resolve;
+
+/// What the merge steps report back
+#[derive(Debug)]
+pub struct Outcome {
+    /// The target branch's commit just before merging
+    pub base_commit: String,
+    /// Whether the merge was committed (false if a squash merge was left staged)
+    pub committed: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MergeSummary {
+    pub feature_branch: String,
+    pub target_branch: String,
+    pub squash: bool,
+    /// The commit the merge created, if it created one
+    pub commit: Option<String>,
+    pub files_changed: usize,
+    pub deleted_locally: bool,
+    pub deleted_remotely: bool,
+    /// The worktree the merge ran in, if it was kept
+    pub worktree: Option<PathBuf>,
+    pub follow_ups: Vec<String>,
+}
+
+impl MergeSummary {
+    /// Collect the summary from the repository after the merge. `protected`
+    /// says whether the feature branch is expected to stay on origin;
+    /// `worktree` is the temporary worktree the merge ran in, if it is kept.
+    pub fn collect(
+        feature_branch: &str,
+        target_branch: &str,
+        squash: bool,
+        outcome: &Outcome,
+        protected: bool,
+        worktree: Option<&Path>,
+    ) -> Result<Self> {
+        let head = git_output(&["rev-parse", "HEAD"])?;
+        let changed_files = if outcome.committed {
+            git_output(&["diff", "--name-only", &outcome.base_commit, &head])?
+        } else {
+            git_output(&["diff", "--cached", "--name-only"])?
+        };
+
+        let mut summary = Self {
+            feature_branch: feature_branch.to_string(),
+            target_branch: target_branch.to_string(),
+            squash,
+            commit: (head != outcome.base_commit).then_some(head),
+            files_changed: changed_files.lines().count(),
+            deleted_locally: resolve(&format!("refs/heads/{}", feature_branch)).is_none(),
+            deleted_remotely: resolve(&format!("refs/remotes/origin/{}", feature_branch)).is_none(),
+            worktree: worktree.map(Path::to_path_buf),
+            follow_ups: Vec::new(),
+        };
+        summary.follow_ups = summary.follow_ups(outcome.committed, protected);
+        Ok(summary)
+    }
+
+    /// What is left to do by hand
+    fn follow_ups(&self, committed: bool, protected: bool) -> Vec<String> {
+        let mut follow_ups = Vec::new();
+
+        if !committed {
+            follow_ups.push(format!(
+                "Commit the staged changes on '{}' and push it: git commit && git push origin {}",
+                self.target_branch, self.target_branch
+            ));
+            if let Some(worktree) = &self.worktree {
+                follow_ups[0].insert_str(0, &format!("In {}: ", worktree.display()));
+                follow_ups.push(format!(
+                    "Remove the worktree once pushed: git worktree remove {}",
+                    worktree.display()
+                ));
+            }
+        }
+        if !self.deleted_locally {
+            follow_ups.push(format!(
+                "Delete the local branch{}: git branch -D {}",
+                if committed { "" } else { " once committed" },
+                self.feature_branch
+            ));
+        }
+        if !self.deleted_remotely && !protected {
+            follow_ups.push(format!(
+                "Delete the branch from origin{}: git push origin --delete {}",
+                if committed { "" } else { " once committed" },
+                self.feature_branch
+            ));
+        }
+
+        follow_ups
+    }
+
+    fn strategy(&self) -> &'static str {
+        if self.squash { "squash" } else { "simple" }
+    }
+
+    /// The summary as printed and written to `--summary-file`
+    pub fn render(&self) -> String {
+        let mut text = format!(
+            "Merged '{}' into '{}' ({} merge)\n",
+            self.feature_branch,
+            self.target_branch,
+            self.strategy()
+        );
+        text.push_str(&format!(
+            "  Commit:        {}\n",
+            self.commit.as_deref().unwrap_or("none")
+        ));
+        text.push_str(&format!("  Files changed: {}\n", self.files_changed));
+        text.push_str(&format!(
+            "  Local branch:  {}\n",
+            if self.deleted_locally {
+                "deleted"
+            } else {
+                "kept"
+            }
+        ));
+        text.push_str(&format!(
+            "  Remote branch: {}\n",
+            if self.deleted_remotely {
+                "deleted"
+            } else {
+                "still on origin"
+            }
+        ));
+
+        if self.follow_ups.is_empty() {
+            text.push_str("Nothing left to do.\n");
+        } else {
+            text.push_str("Follow-up:\n");
+            for follow_up in &self.follow_ups {
+                text.push_str(&format!("  - {}\n", follow_up));
+            }
+        }
+
+        text
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.render())
+            .with_context(|| format!("Failed to write merge summary: {}", path.display()))
+    }
+
+    /// Show a macOS notification with the outcome
+    pub fn notify(&self) -> Result<()> {
+        let mut message = format!("{} into {}", self.feature_branch, self.target_branch);
+        if !self.follow_ups.is_empty() {
+            message.push_str(&format!(
+                " ({} follow-up{})",
+                self.follow_ups.len(),
+                if self.follow_ups.len() == 1 { "" } else { "s" }
+            ));
+        }
+
+        let applescript = format!(
+            r#"display notification "{}" with title "git-merge" subtitle "{} merge complete""#,
+            message.replace('"', "\\\""),
+            self.strategy()
+        );
+
+        let output = Command::new("osascript")
+            .arg("-e")
+            .arg(&applescript)
+            .output()
+            .context("Failed to execute osascript command")?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "Failed to show notification: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary() -> MergeSummary {
+        MergeSummary {
+            feature_branch: "feature".to_string(),
+            target_branch: "main".to_string(),
+            squash: true,
+            commit: Some("abc1234".to_string()),
+            files_changed: 3,
+            deleted_locally: true,
+            deleted_remotely: true,
+            worktree: None,
+            follow_ups: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_render_complete_merge() {
+        assert_eq!(
+            summary().render(),
+            "Merged 'feature' into 'main' (squash merge)
+  Commit:        abc1234
+  Files changed: 3
+  Local branch:  deleted
+  Remote branch: deleted
+Nothing left to do.
+"
+        );
+    }
+
+    #[test]
+    fn test_follow_ups_for_remote_branch() {
+        let summary = MergeSummary {
+            deleted_remotely: false,
+            ..summary()
+        };
+        assert_eq!(
+            summary.follow_ups(true, false),
+            vec!["Delete the branch from origin: git push origin --delete feature"]
+        );
+        // Protected branches are meant to stay on origin
+        assert!(summary.follow_ups(true, true).is_empty());
+    }
+
+    #[test]
+    fn test_follow_ups_for_staged_squash() {
+        let mut summary = MergeSummary {
+            commit: None,
+            deleted_locally: false,
+            deleted_remotely: false,
+            ..summary()
+        };
+        summary.follow_ups = summary.follow_ups(false, false);
+        assert_eq!(summary.follow_ups.len(), 3);
+        assert!(summary.follow_ups[0].starts_with("Commit the staged changes on 'main'"));
+        assert!(summary.follow_ups[1].contains("once committed: git branch -D feature"));
+
+        let in_worktree = MergeSummary {
+            worktree: Some(PathBuf::from("/tmp/git-merge-main-42")),
+            ..summary
+        };
+        let follow_ups = in_worktree.follow_ups(false, false);
+        assert_eq!(follow_ups.len(), 4);
+        assert!(follow_ups[0].starts_with("In /tmp/git-merge-main-42: Commit the staged changes"));
+        assert_eq!(
+            follow_ups[1],
+            "Remove the worktree once pushed: git worktree remove /tmp/git-merge-main-42"
+        );
+
+        let text = in_worktree.render();
+        assert!(text.contains("  Commit:        none\n"));
+        assert!(text.contains("  Remote branch: still on origin\n"));
+        assert!(text.contains("Follow-up:\n  - Commit the staged changes"));
+    }
+}
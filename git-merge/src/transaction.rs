@@ -0,0 +1,264 @@
+// Restoring the starting state after a failed merge
+//
+// Before the first step of a merge, the starting branch and the commits of
+// the feature and target branches are recorded. Each step is recorded as it
+// completes; if a later step fails, the target branch is reset to its recorded
+// commit (unless the merge already reached origin), a deleted feature branch
+// is recreated, and the starting branch is checked out again. When the merge
+// runs in a temporary worktree, the caller's checkout never changes, so there
+// is no starting branch to return to.
+
+use anyhow::{Context, Result};
+
+use crate::git::git_output;
+
+/// Commit a ref points to, or None if it doesn't exist
+pub fn resolve(reference: &str) -> Option<String> {
+    git_output(&["rev-parse", "--verify", "--quiet", reference]).ok()
+}
+
+fn short(commit: &str) -> &str {
+    &commit[..commit.len().min(7)]
+}
+
+/// One action taken to restore the starting state
+#[derive(Debug, PartialEq)]
+enum Restore {
+    /// Reset the (checked out) target branch, aborting any merge in progress
+    ResetTarget(String),
+    /// Recreate the deleted feature branch at its recorded commit
+    RecreateFeature,
+    /// Check out the starting branch (or commit, if HEAD was detached)
+    CheckoutStart(String),
+}
+
+/// The state of a merge in progress
+#[derive(Debug)]
+pub struct Transaction {
+    /// Branch checked out when git-merge started, or the commit if detached;
+    /// None when merging in a temporary worktree
+    start: Option<String>,
+    feature_branch: String,
+    feature_commit: String,
+    target_branch: String,
+    /// The local target branch's commit, or origin's if there is no local branch yet
+    target_commit: Option<String>,
+    completed: Vec<String>,
+    /// Whether a completed step pushed the target branch to origin
+    target_pushed: bool,
+}
+
+impl Transaction {
+    /// Record the starting state before anything is changed
+    pub fn begin(feature_branch: &str, target_branch: &str, in_worktree: bool) -> Result<Self> {
+        let start = if in_worktree {
+            None
+        } else {
+            let branch = git_output(&["rev-parse", "--abbrev-ref", "HEAD"])?;
+            Some(if branch == "HEAD" {
+                git_output(&["rev-parse", "HEAD"])?
+            } else {
+                branch
+            })
+        };
+
+        let feature_commit = resolve(&format!("refs/heads/{}", feature_branch))
+            .with_context(|| format!("Branch '{}' does not exist", feature_branch))?;
+        let target_commit = resolve(&format!("refs/heads/{}", target_branch))
+            .or_else(|| resolve(&format!("refs/remotes/origin/{}", target_branch)));
+
+        Ok(Self {
+            start,
+            feature_branch: feature_branch.to_string(),
+            feature_commit,
+            target_branch: target_branch.to_string(),
+            target_commit,
+            completed: Vec::new(),
+            target_pushed: false,
+        })
+    }
+
+    /// Record a step that completed
+    pub fn complete(&mut self, step: impl Into<String>) {
+        self.completed.push(step.into());
+    }
+
+    /// Record that the target branch reached origin, after which the merge
+    /// is no longer undone locally
+    pub fn target_pushed(&mut self) {
+        self.target_pushed = true;
+    }
+
+    /// Report the completed steps and restore the starting state as far as
+    /// possible. Problems are reported rather than returned, so that the
+    /// original error stays the one git-merge exits with.
+    pub fn roll_back(&self) {
+        eprintln!();
+        if self.completed.is_empty() {
+            eprintln!("The merge failed before any step completed.");
+        } else {
+            eprintln!("The merge failed after these steps completed:");
+            for (i, step) in self.completed.iter().enumerate() {
+                eprintln!("  {}. {}", i + 1, step);
+            }
+        }
+
+        let current = git_output(&["rev-parse", "--abbrev-ref", "HEAD"]).unwrap_or_default();
+        let feature_exists = resolve(&format!("refs/heads/{}", self.feature_branch)).is_some();
+        let actions = self.plan(&current, feature_exists);
+        if actions.is_empty() && !self.target_pushed {
+            return;
+        }
+
+        eprintln!("Restoring the starting state...");
+        if self.target_pushed {
+            eprintln!(
+                "  '{}' was already pushed to origin, so the merge is kept",
+                self.target_branch
+            );
+        }
+        for action in actions {
+            match self.apply(&action) {
+                Ok(done) => eprintln!("  {}", done),
+                Err(e) => eprintln!("  Warning: {}", e),
+            }
+        }
+    }
+
+    /// The actions that restore the starting state, given the branch now
+    /// checked out and whether the feature branch still exists
+    fn plan(&self, current: &str, feature_exists: bool) -> Vec<Restore> {
+        let mut actions = Vec::new();
+
+        if current == self.target_branch
+            && !self.target_pushed
+            && let Some(commit) = &self.target_commit
+        {
+            actions.push(Restore::ResetTarget(commit.clone()));
+        }
+        if !feature_exists {
+            actions.push(Restore::RecreateFeature);
+        }
+        if let Some(start) = &self.start
+            && current != start
+        {
+            actions.push(Restore::CheckoutStart(start.clone()));
+        }
+
+        actions
+    }
+
+    fn apply(&self, action: &Restore) -> Result<String> {
+        match action {
+            Restore::ResetTarget(commit) => {
+                git_output(&["reset", "--merge", commit])
+                    .with_context(|| format!("Could not reset '{}'", self.target_branch))?;
+                Ok(format!(
+                    "Reset '{}' to {}",
+                    self.target_branch,
+                    short(commit)
+                ))
+            }
+            Restore::RecreateFeature => {
+                git_output(&["branch", &self.feature_branch, &self.feature_commit])
+                    .with_context(|| format!("Could not recreate '{}'", self.feature_branch))?;
+                Ok(format!(
+                    "Recreated '{}' at {}",
+                    self.feature_branch,
+                    short(&self.feature_commit)
+                ))
+            }
+            Restore::CheckoutStart(start) => {
+                git_output(&["checkout", start])
+                    .with_context(|| format!("Could not check out '{}'", start))?;
+                Ok(format!("Checked out '{}'", start))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(start: &str) -> Transaction {
+        Transaction {
+            start: Some(start.to_string()),
+            feature_branch: "feature".to_string(),
+            feature_commit: "f00d".to_string(),
+            target_branch: "main".to_string(),
+            target_commit: Some("beef".to_string()),
+            completed: Vec::new(),
+            target_pushed: false,
+        }
+    }
+
+    #[test]
+    fn test_plan_before_leaving_start() {
+        // Pushing the feature branch failed: nothing to undo
+        assert!(transaction("feature").plan("feature", true).is_empty());
+    }
+
+    #[test]
+    fn test_plan_after_failed_merge() {
+        assert_eq!(
+            transaction("feature").plan("main", true),
+            vec![
+                Restore::ResetTarget("beef".to_string()),
+                Restore::CheckoutStart("feature".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_after_branch_deleted() {
+        // Pushing the target failed after the feature branch was deleted
+        assert_eq!(
+            transaction("feature").plan("main", false),
+            vec![
+                Restore::ResetTarget("beef".to_string()),
+                Restore::RecreateFeature,
+                Restore::CheckoutStart("feature".to_string())
+            ]
+        );
+
+        // Started on the target branch, so there is nothing to check out
+        assert_eq!(
+            transaction("main").plan("main", true),
+            vec![Restore::ResetTarget("beef".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_plan_keeps_pushed_merge() {
+        let mut tx = transaction("feature");
+        tx.target_pushed();
+        assert_eq!(
+            tx.plan("main", true),
+            vec![Restore::CheckoutStart("feature".to_string())]
+        );
+
+        let mut tx = transaction("feature");
+        tx.target_commit = None;
+        assert_eq!(
+            tx.plan("main", true),
+            vec![Restore::CheckoutStart("feature".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_plan_in_worktree() {
+        // The worktree is on the target branch and the caller's checkout never moved
+        let mut tx = transaction("feature");
+        tx.start = None;
+        assert_eq!(
+            tx.plan("main", false),
+            vec![
+                Restore::ResetTarget("beef".to_string()),
+                Restore::RecreateFeature
+            ]
+        );
+        tx.target_pushed();
+        assert_eq!(tx.plan("main", true), Vec::new());
+    }
+}
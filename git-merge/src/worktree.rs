@@ -0,0 +1,156 @@
+// Merging in a temporary linked worktree
+//
+// With --worktree, the target branch is checked out in a new worktree under
+// the temp directory and the merge runs there, so the caller's working tree
+// (and any uncommitted work in it) is never touched. The worktree is removed
+// again when the merge finishes, whether it succeeded or not, unless it holds
+// a squash merge that was left staged.
+
+use anyhow::{Context, Result, bail};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::git::git_output;
+
+/// A linked worktree that is the current directory until dropped
+#[derive(Debug)]
+pub struct TempWorktree {
+    path: PathBuf,
+    original_dir: PathBuf,
+    keep: bool,
+}
+
+impl TempWorktree {
+    /// Check out `branch` in a new worktree and change into it
+    pub fn enter(branch: &str) -> Result<Self> {
+        if let Some(location) = checked_out_at(branch)? {
+            bail!(
+                "'{}' is already checked out in {}. Merge there instead of using --worktree.",
+                branch,
+                location.display()
+            );
+        }
+
+        let path = env::temp_dir().join(format!(
+            "git-merge-{}-{}",
+            branch.replace('/', "-"),
+            std::process::id()
+        ));
+        let original_dir = env::current_dir().context("Failed to get current directory")?;
+
+        println!(
+            "Checking out '{}' in a temporary worktree at {}...",
+            branch,
+            path.display()
+        );
+        git_output(&[
+            "worktree",
+            "add",
+            "--quiet",
+            &path.to_string_lossy(),
+            branch,
+        ])
+        .with_context(|| format!("Failed to create a worktree for '{}'", branch))?;
+        env::set_current_dir(&path)
+            .with_context(|| format!("Failed to change into {}", path.display()))?;
+
+        Ok(Self {
+            path,
+            original_dir,
+            keep: false,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Leave the worktree in place when dropped, for changes that still need
+    /// committing
+    pub fn keep(&mut self) {
+        self.keep = true;
+    }
+}
+
+impl Drop for TempWorktree {
+    fn drop(&mut self) {
+        if let Err(e) = env::set_current_dir(&self.original_dir) {
+            eprintln!(
+                "Warning: Failed to return to {}: {}",
+                self.original_dir.display(),
+                e
+            );
+            return;
+        }
+        if self.keep {
+            return;
+        }
+
+        let status = Command::new("git")
+            .args(["worktree", "remove", "--force"])
+            .arg(&self.path)
+            .status();
+        if !matches!(status, Ok(s) if s.success()) {
+            eprintln!(
+                "Warning: Failed to remove the worktree at {}. Remove it with: git worktree remove --force {}",
+                self.path.display(),
+                self.path.display()
+            );
+        }
+    }
+}
+
+/// The worktree `branch` is checked out in, if any
+pub fn checked_out_at(branch: &str) -> Result<Option<PathBuf>> {
+    let list = git_output(&["worktree", "list", "--porcelain"])?;
+    Ok(find_checkout(&list, branch))
+}
+
+/// Find the worktree with `branch` checked out in `git worktree list
+/// --porcelain` output
+fn find_checkout(list: &str, branch: &str) -> Option<PathBuf> {
+    let wanted = format!("branch refs/heads/{}", branch);
+    let mut worktree = None;
+    for line in list.lines() {
+        if let Some(path) = line.strip_prefix("worktree ") {
+            worktree = Some(PathBuf::from(path));
+        } else if line == wanted {
+            return worktree;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIST: &str = "worktree /work/repo
+HEAD 1111111111111111111111111111111111111111
+branch refs/heads/feature/login
+
+worktree /tmp/git-merge-main-42
+HEAD 2222222222222222222222222222222222222222
+branch refs/heads/main
+
+worktree /work/detached
+HEAD 3333333333333333333333333333333333333333
+detached
+";
+
+    #[test]
+    fn test_find_checkout() {
+        assert_eq!(
+            find_checkout(LIST, "feature/login"),
+            Some(PathBuf::from("/work/repo"))
+        );
+        assert_eq!(
+            find_checkout(LIST, "main"),
+            Some(PathBuf::from("/tmp/git-merge-main-42"))
+        );
+        // Prefixes of a checked out branch don't match
+        assert_eq!(find_checkout(LIST, "feature"), None);
+        assert_eq!(find_checkout(LIST, "develop"), None);
+    }
+}
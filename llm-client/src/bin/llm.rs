@@ -0,0 +1,304 @@
+// llm - manage the LLM configuration shared by every tool in the workspace
+
+use anyhow::{Context, Result, bail};
+use clap::{Parser, Subcommand};
+use llm_client::{Config, LlmRequest, ModelPreset, get_provider, with_rate_limits};
+
+const TEST_PROMPT: &str = "Reply with the single word OK.";
+
+#[derive(Parser, Debug)]
+#[command(name = "llm")]
+#[command(about = "Manage the LLM presets shared by the cli-programs tools")]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Configuration management (~/.config/cli-programs/llm.toml)
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// List presets, per-tool defaults, and profiles
+    List,
+    /// Add a preset, replacing any preset with the same name
+    AddPreset {
+        /// Preset name
+        name: String,
+        /// Provider (claude-cli, anthropic, openrouter, cerebras, lm-studio)
+        #[arg(short, long)]
+        provider: String,
+        /// Model identifier
+        #[arg(short = 'M', long)]
+        model: String,
+        /// Preset to try if this one fails
+        #[arg(long)]
+        fallback: Option<String>,
+        /// Task profile this preset can serve (repeatable)
+        #[arg(long = "tag", value_name = "PROFILE")]
+        tags: Vec<String>,
+    },
+    /// Set the default preset for every tool, or for one with --program
+    SetDefault {
+        /// Name of the preset to use as default
+        preset: String,
+        /// Tool to set the default for (e.g. ask, gc)
+        #[arg(long)]
+        program: Option<String>,
+    },
+    /// Check the config and that every preset's provider can be set up
+    Doctor,
+    /// Send a short prompt with a preset and report the latency
+    Test {
+        /// Preset to test (defaults to the default preset)
+        preset: Option<String>,
+        /// Prompt to send
+        #[arg(long, default_value = TEST_PROMPT)]
+        prompt: String,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Commands::Config { action } => match action {
+            ConfigAction::List => list(&Config::load()?),
+            ConfigAction::AddPreset {
+                name,
+                provider,
+                model,
+                fallback,
+                tags,
+            } => add_preset(name, provider, model, fallback, tags),
+            ConfigAction::SetDefault { preset, program } => {
+                let mut config = Config::load()?;
+                let message = set_default(&mut config, &preset, program.as_deref())?;
+                config.save()?;
+                println!("{}", message);
+                Ok(())
+            }
+            ConfigAction::Doctor => doctor(),
+            ConfigAction::Test { preset, prompt } => test(preset.as_deref(), &prompt).await,
+        },
+    }
+}
+
+/// Print the presets, per-tool defaults, and profiles in the config
+fn list(config: &Config) -> Result<()> {
+    println!("Config file: {}", Config::config_path()?.display());
+
+    println!("\nPresets:");
+    for (name, preset) in config.sorted_presets() {
+        println!(
+            "  {}",
+            preset.describe(name, *name == config.default_preset)
+        );
+    }
+
+    if !config.defaults.is_empty() {
+        println!("\nTool defaults:");
+        for (program, preset) in sorted(&config.defaults) {
+            println!("  {} -> {}", program, preset);
+        }
+    }
+
+    if !config.profiles.is_empty() {
+        println!("\nProfiles:");
+        for (profile, preset) in sorted(&config.profiles) {
+            println!("  {} -> {}", profile, preset);
+        }
+    }
+
+    Ok(())
+}
+
+fn sorted<V>(map: &std::collections::HashMap<String, V>) -> Vec<(&String, &V)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by_key(|(key, _)| *key);
+    entries
+}
+
+fn add_preset(
+    name: String,
+    provider: String,
+    model: String,
+    fallback: Option<String>,
+    tags: Vec<String>,
+) -> Result<()> {
+    let mut config = Config::load()?;
+    let replaced = config.add_preset(
+        &name,
+        ModelPreset {
+            fallback,
+            tags,
+            ..ModelPreset::new(provider, model)
+        },
+    )?;
+    config.save()?;
+
+    if replaced {
+        println!("Replaced preset: {}", name);
+    } else {
+        println!("Added preset: {}", name);
+    }
+    Ok(())
+}
+
+/// Make `preset` the default for `program`, or for every tool without a
+/// default of its own. Returns a message describing the change.
+fn set_default(config: &mut Config, preset: &str, program: Option<&str>) -> Result<String> {
+    config.set_default(preset, program)?;
+    Ok(match program {
+        Some(program) => format!("Default preset for {} set to: {}", program, preset),
+        None => format!("Default preset set to: {}", preset),
+    })
+}
+
+/// Check the config file and every preset, printing each result.
+/// Fails if any preset can't be used.
+fn doctor() -> Result<()> {
+    let path = Config::config_path()?;
+    // Parsed here rather than with Config::load so its warnings aren't
+    // printed twice
+    let config: Config = if path.exists() {
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: Config = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        println!(
+            "  [ok  ] {} ({} presets)",
+            path.display(),
+            config.presets.len()
+        );
+        config
+    } else {
+        println!(
+            "  [ok  ] {} doesn't exist; using the defaults",
+            path.display()
+        );
+        Config::default()
+    };
+
+    let mut failures = 0;
+    let mut warnings = 0;
+
+    for warning in config.validate() {
+        println!("  [WARN] {}", warning);
+        warnings += 1;
+    }
+
+    let mut defaults = vec![("default_preset", &config.default_preset)];
+    defaults.extend(
+        sorted(&config.defaults)
+            .into_iter()
+            .map(|(program, preset)| (program.as_str(), preset)),
+    );
+    for (program, preset) in defaults {
+        if !config.presets.contains_key(preset) {
+            println!("  [FAIL] {} uses unknown preset {}", program, preset);
+            failures += 1;
+        }
+    }
+
+    for (name, preset) in config.sorted_presets() {
+        let provider_config = config.get_provider_config(&preset.provider);
+        match get_provider(preset, provider_config) {
+            Ok(_) => println!(
+                "  [ok  ] preset {} ({} / {})",
+                name, preset.provider, preset.model
+            ),
+            Err(e) => {
+                println!("  [FAIL] preset {}: {}", name, e);
+                if let Some(fix) = e.remediation() {
+                    println!("         fix: {}", fix);
+                }
+                failures += 1;
+            }
+        }
+    }
+
+    println!();
+    if failures > 0 {
+        bail!("{} problem(s) found, {} warning(s)", failures, warnings);
+    }
+    if warnings > 0 {
+        println!("No problems found, {} warning(s)", warnings);
+    } else {
+        println!("No problems found");
+    }
+    Ok(())
+}
+
+/// Send a prompt with a preset and report the model, latency, and tokens
+async fn test(preset_name: Option<&str>, prompt: &str) -> Result<()> {
+    let config = Config::load()?;
+    let preset_name = preset_name.unwrap_or(&config.default_preset);
+    let preset = config.get_preset(preset_name)?;
+    let provider = get_provider(preset, config.get_provider_config(&preset.provider))?;
+    let provider = with_rate_limits(&config, &preset.provider, provider)?;
+
+    println!(
+        "Testing {} ({} / {})...",
+        preset_name, preset.provider, preset.model
+    );
+    let request = LlmRequest {
+        prompt: prompt.to_string(),
+        system_prompt: None,
+        max_tokens: Some(64),
+        temperature: None,
+        files: Vec::new(),
+        json_schema: None,
+    };
+    let response = match provider.complete(request).await {
+        Ok(response) => response,
+        Err(e) => {
+            if let Some(hint) = e.remediation() {
+                eprintln!("Hint: {}", hint);
+            }
+            return Err(e).context(format!("Preset {} failed", preset_name));
+        }
+    };
+
+    println!(
+        "OK: {} answered in {:.1}s",
+        response.model,
+        response.latency.as_secs_f64()
+    );
+    if let Some(usage) = &response.usage {
+        println!(
+            "Tokens: {} in, {} out",
+            usage.input_tokens, usage.output_tokens
+        );
+    }
+    println!("Response: {}", response.content.trim());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_default_message() {
+        let mut config = Config::default();
+        config.presets.insert(
+            "haiku".to_string(),
+            ModelPreset::new("anthropic", "claude-haiku-4-5"),
+        );
+
+        let message = set_default(&mut config, "haiku", Some("gc")).unwrap();
+        assert_eq!(message, "Default preset for gc set to: haiku");
+        let message = set_default(&mut config, "haiku", None).unwrap();
+        assert_eq!(message, "Default preset set to: haiku");
+        assert!(set_default(&mut config, "missing", None).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! Provider capability metadata
+//!
+//! Describes what each provider implementation can handle so presets can be
+//! checked against it when the config is loaded, rather than failing at runtime.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::providers::ProviderKind;
+
+/// A feature a preset may depend on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Separate system prompt
+    SystemPrompt,
+    /// Structured output constrained by a JSON schema
+    JsonSchema,
+    /// File attachments (images, audio)
+    Files,
+    /// Streaming responses
+    Streaming,
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::SystemPrompt => "system prompts",
+            Self::JsonSchema => "JSON schema output",
+            Self::Files => "attachments",
+            Self::Streaming => "streaming",
+        };
+        f.write_str(name)
+    }
+}
+
+/// What a provider implementation supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProviderCapabilities {
+    /// Maximum context window in tokens, if fixed for the provider
+    /// (`None` means it depends on the model)
+    pub max_context_tokens: Option<u32>,
+    pub system_prompt: bool,
+    pub json_schema: bool,
+    pub files: bool,
+    pub streaming: bool,
+}
+
+impl ProviderCapabilities {
+    /// Check whether a single capability is supported
+    pub fn supports(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::SystemPrompt => self.system_prompt,
+            Capability::JsonSchema => self.json_schema,
+            Capability::Files => self.files,
+            Capability::Streaming => self.streaming,
+        }
+    }
+}
+
+impl ProviderKind {
+    /// Get the capabilities of this provider's implementation
+    pub fn capabilities(&self) -> ProviderCapabilities {
+        match self {
+            Self::ClaudeCli => ProviderCapabilities {
+                max_context_tokens: Some(200_000),
+                system_prompt: true,
+                json_schema: false,
+                files: false,
+                streaming: false,
+            },
+            Self::Anthropic => ProviderCapabilities {
+                max_context_tokens: Some(200_000),
+                system_prompt: true,
+                json_schema: false,
+                files: false,
+                streaming: false,
+            },
+            Self::OpenRouter => ProviderCapabilities {
+                max_context_tokens: None,
+                system_prompt: true,
+                json_schema: true,
+                files: true,
+                streaming: false,
+            },
+            Self::Cerebras => ProviderCapabilities {
+                max_context_tokens: Some(131_072),
+                system_prompt: true,
+                json_schema: true,
+                files: false,
+                streaming: false,
+            },
+            Self::LmStudio => ProviderCapabilities {
+                max_context_tokens: None,
+                system_prompt: true,
+                json_schema: true,
+                files: true,
+                streaming: false,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supports() {
+        let caps = ProviderKind::Cerebras.capabilities();
+        assert!(caps.supports(Capability::SystemPrompt));
+        assert!(caps.supports(Capability::JsonSchema));
+        assert!(!caps.supports(Capability::Files));
+    }
+
+    #[test]
+    fn test_capability_deserialize() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            requires: Vec<Capability>,
+        }
+        let parsed: Wrapper = toml::from_str(r#"requires = ["files", "json_schema"]"#).unwrap();
+        assert_eq!(
+            parsed.requires,
+            vec![Capability::Files, Capability::JsonSchema]
+        );
+    }
+}
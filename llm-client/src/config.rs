@@ -1,8 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::str::FromStr;
 
+use crate::capabilities::Capability;
 use crate::error::{LlmError, Result};
+use crate::fallback::{FallbackProvider, get_provider_with_fallback};
+use crate::providers::ProviderKind;
+use crate::ratelimit::RateLimit;
+use crate::template::PromptTemplate;
 
 /// Main configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +28,19 @@ pub struct Config {
     /// Provider-specific configuration
     #[serde(default)]
     pub providers: HashMap<String, ProviderConfig>,
+
+    /// Limits shared by all providers, across every tool using this config
+    #[serde(default, skip_serializing_if = "RateLimit::is_unlimited")]
+    pub rate_limit: RateLimit,
+
+    /// Task profiles pinned to a preset (profile name -> preset name), taking
+    /// precedence over preset `tags`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub profiles: HashMap<String, String>,
+
+    /// Prompt template overrides (template name -> template)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub templates: HashMap<String, PromptTemplate>,
 }
 
 fn default_preset() -> String {
@@ -45,6 +64,61 @@ pub struct ModelPreset {
     /// If not set, uses the provider's default env var
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub api_key_env: Option<String>,
+
+    /// Features this preset relies on (e.g. `["files", "json_schema"]`),
+    /// checked against the provider's capabilities when the config is loaded
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<Capability>,
+
+    /// Context window this preset needs, checked against the provider's maximum
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_tokens: Option<u32>,
+
+    /// Task profiles this preset can serve (e.g. `["cheap-fast"]`), see
+    /// [`Config::preset_for_profile`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl ModelPreset {
+    /// A preset for `model` on `provider`, with no other settings
+    pub fn new(provider: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            provider: provider.into(),
+            model: model.into(),
+            fallback: None,
+            api_key_env: None,
+            requires: Vec::new(),
+            context_tokens: None,
+            tags: Vec::new(),
+        }
+    }
+
+    /// One line describing the preset, e.g. "haiku - anthropic / claude-haiku-4-5 (default)"
+    pub fn describe(&self, name: &str, is_default: bool) -> String {
+        let mut line = format!("{} - {} / {}", name, self.provider, self.model);
+        if is_default {
+            line.push_str(" (default)");
+        }
+        if let Some(fallback) = &self.fallback {
+            line.push_str(&format!(" [fallback: {}]", fallback));
+        }
+        if !self.tags.is_empty() {
+            line.push_str(&format!(" [tags: {}]", self.tags.join(", ")));
+        }
+        line
+    }
+}
+
+/// Well-known task profiles. Tags are free-form, but tools should request
+/// one of these so a single preset tag covers every tool.
+pub mod profiles {
+    /// Quick, inexpensive requests such as commit messages and short answers
+    pub const CHEAP_FAST: &str = "cheap-fast";
+    /// Requests where output quality matters more than speed or cost
+    pub const HIGH_QUALITY: &str = "high-quality";
+    /// Requests with large inputs, such as long diffs or documents
+    pub const LONG_CONTEXT: &str = "long-context";
 }
 
 /// Provider-specific configuration
@@ -61,6 +135,10 @@ pub struct ProviderConfig {
     /// Custom base URL (for API providers)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub base_url: Option<String>,
+
+    /// Limits for this provider, across every tool using this config
+    #[serde(default, skip_serializing_if = "RateLimit::is_unlimited")]
+    pub rate_limit: RateLimit,
 }
 
 impl Config {
@@ -74,9 +152,84 @@ impl Config {
 
         let content = std::fs::read_to_string(&config_path)?;
         let config: Config = toml::from_str(&content)?;
+
+        for warning in config.validate() {
+            eprintln!("Warning: {}", warning);
+        }
+
         Ok(config)
     }
 
+    /// Check presets against their providers' capabilities.
+    ///
+    /// Returns human-readable warnings for presets that reference unknown
+    /// providers or fallbacks, or require features their provider lacks.
+    pub fn validate(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        let mut names: Vec<&String> = self.presets.keys().collect();
+        names.sort();
+
+        for name in names {
+            let preset = &self.presets[name];
+
+            if let Some(fallback) = &preset.fallback
+                && !self.presets.contains_key(fallback)
+            {
+                warnings.push(format!(
+                    "preset {} falls back to unknown preset {}",
+                    name, fallback
+                ));
+            }
+
+            let kind = match ProviderKind::from_str(&preset.provider) {
+                Ok(kind) => kind,
+                Err(_) => {
+                    warnings.push(format!(
+                        "preset {} uses unknown provider {}",
+                        name, preset.provider
+                    ));
+                    continue;
+                }
+            };
+            let caps = kind.capabilities();
+
+            for capability in &preset.requires {
+                if !caps.supports(*capability) {
+                    warnings.push(format!(
+                        "preset {} uses {} but provider {} doesn't support {}",
+                        name,
+                        capability_noun(*capability),
+                        preset.provider,
+                        capability
+                    ));
+                }
+            }
+
+            if let (Some(needed), Some(max)) = (preset.context_tokens, caps.max_context_tokens)
+                && needed > max
+            {
+                warnings.push(format!(
+                    "preset {} needs {} context tokens but provider {} supports at most {}",
+                    name, needed, preset.provider, max
+                ));
+            }
+        }
+
+        let mut profiles: Vec<(&String, &String)> = self.profiles.iter().collect();
+        profiles.sort();
+        for (profile, preset) in profiles {
+            if !self.presets.contains_key(preset) {
+                warnings.push(format!(
+                    "profile {} uses unknown preset {}",
+                    profile, preset
+                ));
+            }
+        }
+
+        warnings
+    }
+
     /// Save configuration to the default location
     pub fn save(&self) -> Result<()> {
         let config_path = Self::config_path()?;
@@ -105,6 +258,40 @@ impl Config {
             .ok_or_else(|| LlmError::InvalidPreset(name.to_string()))
     }
 
+    /// Presets sorted by name
+    pub fn sorted_presets(&self) -> Vec<(&String, &ModelPreset)> {
+        let mut presets: Vec<_> = self.presets.iter().collect();
+        presets.sort_by_key(|(name, _)| *name);
+        presets
+    }
+
+    /// Add a preset, replacing any preset with the same name. Fails if its
+    /// provider or fallback preset doesn't exist. Returns whether a preset
+    /// was replaced.
+    pub fn add_preset(&mut self, name: &str, preset: ModelPreset) -> Result<bool> {
+        ProviderKind::from_str(&preset.provider)?;
+        if let Some(fallback) = &preset.fallback
+            && fallback != name
+        {
+            self.get_preset(fallback)?;
+        }
+        Ok(self.presets.insert(name.to_string(), preset).is_some())
+    }
+
+    /// Make `preset` the default for `program`, or for every tool without a
+    /// default of its own
+    pub fn set_default(&mut self, preset: &str, program: Option<&str>) -> Result<()> {
+        self.get_preset(preset)?;
+        match program {
+            Some(program) => {
+                self.defaults
+                    .insert(program.to_string(), preset.to_string());
+            }
+            None => self.default_preset = preset.to_string(),
+        }
+        Ok(())
+    }
+
     /// Get the default preset name for a specific program
     ///
     /// Falls back to `default_preset` if no program-specific default is set.
@@ -115,12 +302,65 @@ impl Config {
             .unwrap_or(&self.default_preset)
     }
 
+    /// Get the preset name serving a task profile
+    ///
+    /// Uses the `[profiles]` table if it names a preset for `profile`, then
+    /// presets tagged with it (preferring `default_preset`, otherwise the first
+    /// by name). Falls back to `default_preset` when no preset serves the
+    /// profile, so tools keep working with configs that don't use tags.
+    pub fn preset_for_profile(&self, profile: &str) -> &str {
+        if let Some(preset) = self.profiles.get(profile) {
+            return preset;
+        }
+
+        let tagged = |name: &String| {
+            self.presets[name]
+                .tags
+                .iter()
+                .any(|tag| tag.as_str() == profile)
+        };
+        if self.presets.contains_key(&self.default_preset) && tagged(&self.default_preset) {
+            return &self.default_preset;
+        }
+
+        self.presets
+            .keys()
+            .filter(|name| tagged(name))
+            .min()
+            .map(String::as_str)
+            .unwrap_or(&self.default_preset)
+    }
+
+    /// Build the provider chain for a task profile, letting the config decide
+    /// which preset serves it
+    ///
+    /// ```no_run
+    /// use llm_client::{Config, profiles};
+    ///
+    /// let config = Config::load()?;
+    /// let provider = config.for_profile(profiles::CHEAP_FAST)?;
+    /// # Ok::<(), llm_client::LlmError>(())
+    /// ```
+    pub fn for_profile(&self, profile: &str) -> Result<FallbackProvider> {
+        get_provider_with_fallback(self, self.preset_for_profile(profile))
+    }
+
     /// Get provider config by provider name
     pub fn get_provider_config(&self, provider: &str) -> Option<&ProviderConfig> {
         self.providers.get(provider)
     }
 }
 
+/// Short name of a capability as it appears in preset `requires` lists
+fn capability_noun(capability: Capability) -> &'static str {
+    match capability {
+        Capability::SystemPrompt => "system_prompt",
+        Capability::JsonSchema => "json_schema",
+        Capability::Files => "files",
+        Capability::Streaming => "streaming",
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         let mut presets = HashMap::new();
@@ -133,6 +373,9 @@ impl Default for Config {
                 model: "sonnet".to_string(),
                 fallback: None,
                 api_key_env: None,
+                requires: Vec::new(),
+                context_tokens: None,
+                tags: Vec::new(),
             },
         );
 
@@ -141,6 +384,9 @@ impl Default for Config {
             defaults: HashMap::new(),
             presets,
             providers: HashMap::new(),
+            rate_limit: RateLimit::default(),
+            profiles: HashMap::new(),
+            templates: HashMap::new(),
         }
     }
 }
@@ -160,6 +406,56 @@ mod tests {
         assert_eq!(preset.model, "sonnet");
     }
 
+    #[test]
+    fn test_describe_preset() {
+        let mut haiku = ModelPreset::new("anthropic", "claude-haiku-4-5");
+        assert_eq!(
+            haiku.describe("haiku", true),
+            "haiku - anthropic / claude-haiku-4-5 (default)"
+        );
+
+        haiku.fallback = Some("claude-cli".to_string());
+        haiku.tags = vec!["cheap-fast".to_string(), "long-context".to_string()];
+        assert_eq!(
+            haiku.describe("haiku", false),
+            "haiku - anthropic / claude-haiku-4-5 [fallback: claude-cli] [tags: cheap-fast, long-context]"
+        );
+    }
+
+    #[test]
+    fn test_add_preset() {
+        let mut config = Config::default();
+        let haiku = ModelPreset::new("anthropic", "claude-haiku-4-5");
+        assert!(!config.add_preset("haiku", haiku.clone()).unwrap());
+        assert!(config.add_preset("haiku", haiku).unwrap());
+
+        let unknown_provider = ModelPreset::new("nope", "model");
+        assert!(config.add_preset("bad", unknown_provider).is_err());
+
+        let mut missing_fallback = ModelPreset::new("anthropic", "claude-sonnet-4-5");
+        missing_fallback.fallback = Some("missing".to_string());
+        assert!(config.add_preset("sonnet", missing_fallback).is_err());
+        assert!(!config.presets.contains_key("sonnet"));
+    }
+
+    #[test]
+    fn test_set_default() {
+        let mut config = Config::default();
+        config.presets.insert(
+            "haiku".to_string(),
+            ModelPreset::new("anthropic", "claude-haiku-4-5"),
+        );
+
+        config.set_default("haiku", Some("gc")).unwrap();
+        assert_eq!(config.get_default_for_program("gc"), "haiku");
+        assert_eq!(config.default_preset, "claude-cli");
+
+        config.set_default("haiku", None).unwrap();
+        assert_eq!(config.default_preset, "haiku");
+
+        assert!(config.set_default("missing", None).is_err());
+    }
+
     #[test]
     fn test_invalid_preset() {
         let config = Config::default();
@@ -207,4 +503,134 @@ mod tests {
         // Unknown program should still fall back
         assert_eq!(config.get_default_for_program("bookname"), "claude-cli");
     }
+
+    #[test]
+    fn test_parse_rate_limits() {
+        let config: Config = toml::from_str(
+            r#"
+[rate_limit]
+max_concurrent = 4
+
+[providers.anthropic.rate_limit]
+requests_per_minute = 50
+max_concurrent = 2
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.rate_limit.max_concurrent, Some(4));
+        assert_eq!(config.rate_limit.requests_per_minute, None);
+        let anthropic = config.get_provider_config("anthropic").unwrap();
+        assert_eq!(anthropic.rate_limit.requests_per_minute, Some(50));
+        assert_eq!(anthropic.rate_limit.max_concurrent, Some(2));
+
+        let toml_str = toml::to_string_pretty(&Config::default()).unwrap();
+        assert!(!toml_str.contains("rate_limit"));
+    }
+
+    #[test]
+    fn test_default_config_validates_cleanly() {
+        assert!(Config::default().validate().is_empty());
+    }
+
+    #[test]
+    fn test_validate_unsupported_capability() {
+        let config: Config = toml::from_str(
+            r#"
+[presets.fast-vision]
+provider = "cerebras"
+model = "llama-3.3-70b"
+requires = ["files", "json_schema"]
+"#,
+        )
+        .unwrap();
+
+        let warnings = config.validate();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0],
+            "preset fast-vision uses files but provider cerebras doesn't support attachments"
+        );
+    }
+
+    #[test]
+    fn test_validate_context_and_unknowns() {
+        let config: Config = toml::from_str(
+            r#"
+[presets.big]
+provider = "anthropic"
+model = "claude-sonnet-4"
+context_tokens = 500000
+fallback = "missing"
+
+[presets.odd]
+provider = "nope"
+model = "x"
+"#,
+        )
+        .unwrap();
+
+        let warnings = config.validate();
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].contains("falls back to unknown preset missing"));
+        assert!(warnings[1].contains("supports at most 200000"));
+        assert!(warnings[2].contains("unknown provider nope"));
+    }
+
+    #[test]
+    fn test_preset_for_profile() {
+        let mut config: Config = toml::from_str(
+            r#"
+default_preset = "sonnet"
+
+[presets.sonnet]
+provider = "anthropic"
+model = "claude-sonnet-4"
+tags = ["high-quality", "long-context"]
+
+[presets.llama]
+provider = "cerebras"
+model = "llama-3.3-70b"
+tags = ["cheap-fast"]
+
+[presets.haiku]
+provider = "anthropic"
+model = "claude-haiku-4"
+tags = ["cheap-fast"]
+"#,
+        )
+        .unwrap();
+
+        // First tagged preset by name
+        assert_eq!(config.preset_for_profile(profiles::CHEAP_FAST), "haiku");
+        assert_eq!(config.preset_for_profile(profiles::HIGH_QUALITY), "sonnet");
+        // Nothing tagged: the default preset
+        assert_eq!(config.preset_for_profile("vision"), "sonnet");
+
+        // The default preset wins among tagged presets
+        config.default_preset = "llama".to_string();
+        assert_eq!(config.preset_for_profile(profiles::CHEAP_FAST), "llama");
+
+        // An explicit [profiles] entry wins over tags
+        config
+            .profiles
+            .insert("cheap-fast".to_string(), "sonnet".to_string());
+        assert_eq!(config.preset_for_profile(profiles::CHEAP_FAST), "sonnet");
+    }
+
+    #[test]
+    fn test_validate_unknown_profile_preset() {
+        let config: Config = toml::from_str(
+            r#"
+[profiles]
+cheap-fast = "missing"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.validate(),
+            vec!["profile cheap-fast uses unknown preset missing"]
+        );
+    }
 }
@@ -16,6 +16,25 @@ pub enum LlmError {
     #[error("Server overloaded (HTTP 503): {message}")]
     ServerOverloaded { message: String },
 
+    #[error("Authentication failed for {provider}: {message}")]
+    AuthFailed { provider: String, message: String },
+
+    #[error("Prompt is too large for the model's context window{}", context_details(.limit, .actual))]
+    ContextTooLarge {
+        limit: Option<u64>,
+        actual: Option<u64>,
+    },
+
+    #[error("Content filtered by provider: {message}")]
+    ContentFiltered { message: String },
+
+    #[error("Response rejected by filter: {}", .violations.join(", "))]
+    ResponseRejected {
+        violations: Vec<String>,
+        /// The rejected response, for asking the model to fix it
+        content: String,
+    },
+
     #[error("API error{}: {message}", status_code.map(|c| format!(" (HTTP {})", c)).unwrap_or_default())]
     ApiError {
         message: String,
@@ -25,6 +44,9 @@ pub enum LlmError {
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    #[error("Prompt template error: {0}")]
+    TemplateError(String),
+
     #[error("Invalid model preset: {0}")]
     InvalidPreset(String),
 
@@ -42,3 +64,303 @@ pub enum LlmError {
 }
 
 pub type Result<T> = std::result::Result<T, LlmError>;
+
+fn context_details(limit: &Option<u64>, actual: &Option<u64>) -> String {
+    match (*actual, *limit) {
+        (Some(actual), Some(limit)) => format!(" ({} tokens, limit {})", actual, limit),
+        (None, Some(limit)) => format!(" (limit {} tokens)", limit),
+        (Some(actual), None) => format!(" ({} tokens)", actual),
+        (None, None) => String::new(),
+    }
+}
+
+/// Format a token count compactly, e.g. 200000 -> "200k"
+fn format_tokens(tokens: u64) -> String {
+    if tokens >= 1000 && tokens.is_multiple_of(1000) {
+        format!("{}k", tokens / 1000)
+    } else {
+        tokens.to_string()
+    }
+}
+
+/// Extract the token counts from a context overflow message.
+///
+/// Providers word these differently ("prompt is too long: 208477 tokens >
+/// 200000 maximum", "maximum context length is 128000 tokens. However, your
+/// messages resulted in 130000 tokens"), but when exactly two numbers appear
+/// the smaller one is the limit and the larger the prompt size.
+fn parse_token_counts(message: &str) -> (Option<u64>, Option<u64>) {
+    let numbers: Vec<u64> = message
+        .split(|c: char| !c.is_ascii_digit() && c != ',')
+        .filter_map(|n| n.replace(',', "").parse().ok())
+        .collect();
+
+    match numbers.as_slice() {
+        [a, b] => (Some(*a.min(b)), Some(*a.max(b))),
+        _ => (None, None),
+    }
+}
+
+fn is_context_overflow(message: &str, kind: Option<&str>) -> bool {
+    let message = message.to_lowercase();
+    matches!(kind, Some("context_length_exceeded" | "request_too_large"))
+        || message.contains("prompt is too long")
+        || message.contains("context length")
+        || message.contains("context window")
+        || message.contains("too many tokens")
+}
+
+fn is_content_filter(message: &str, kind: Option<&str>) -> bool {
+    let message = message.to_lowercase();
+    matches!(kind, Some("content_filter" | "content_policy_violation"))
+        || message.contains("content management policy")
+        || message.contains("content filter")
+        || message.contains("content policy")
+}
+
+impl LlmError {
+    /// Classify a failed HTTP response from an API provider.
+    ///
+    /// `kind` is the provider's machine-readable error type or code, when the
+    /// error body includes one.
+    pub(crate) fn from_http_response(
+        provider: &str,
+        status: u16,
+        kind: Option<&str>,
+        message: String,
+        retry_after: Option<u64>,
+    ) -> Self {
+        match status {
+            401 | 403 => Self::AuthFailed {
+                provider: provider.to_string(),
+                message,
+            },
+            429 => Self::RateLimited { retry_after },
+            // 529 is Anthropic's "overloaded" status
+            503 | 529 => Self::ServerOverloaded { message },
+            _ => Self::from_error_message(provider, kind, &message).unwrap_or(Self::ApiError {
+                message,
+                status_code: Some(status),
+            }),
+        }
+    }
+
+    /// Recognize context overflow, content filtering, auth, and rate limit
+    /// failures from an error message when there is no status code to go on
+    pub(crate) fn from_error_message(
+        provider: &str,
+        kind: Option<&str>,
+        message: &str,
+    ) -> Option<Self> {
+        let lower = message.to_lowercase();
+        if is_context_overflow(message, kind) {
+            let (limit, actual) = parse_token_counts(message);
+            Some(Self::ContextTooLarge { limit, actual })
+        } else if is_content_filter(message, kind) {
+            Some(Self::ContentFiltered {
+                message: message.to_string(),
+            })
+        } else if lower.contains("invalid api key")
+            || lower.contains("authentication")
+            || lower.contains("/login")
+        {
+            Some(Self::AuthFailed {
+                provider: provider.to_string(),
+                message: message.to_string(),
+            })
+        } else if lower.contains("rate limit") || lower.contains("usage limit") {
+            Some(Self::RateLimited { retry_after: None })
+        } else {
+            None
+        }
+    }
+
+    /// Actionable guidance for the user, for errors where there is something
+    /// better to do than retry the same request
+    pub fn remediation(&self) -> Option<String> {
+        match self {
+            Self::MissingApiKey { env_var, .. } => Some(format!(
+                "export {} or set api_key for this provider in ~/.config/cli-programs/llm.toml",
+                env_var
+            )),
+            Self::RateLimited {
+                retry_after: Some(seconds),
+            } => Some(format!(
+                "wait {} seconds before retrying, or use a preset on another provider",
+                seconds
+            )),
+            Self::RateLimited { retry_after: None } => Some(
+                "wait a minute before retrying, or use a preset on another provider".to_string(),
+            ),
+            Self::ServerOverloaded { .. } => Some(
+                "the provider is overloaded; retry later or add a `fallback` preset in llm.toml"
+                    .to_string(),
+            ),
+            Self::AuthFailed { provider, .. } if provider == "Claude CLI" => {
+                Some("run `claude` and use /login to sign in again".to_string())
+            }
+            Self::AuthFailed { provider, .. } => Some(format!(
+                "check the {} API key in your environment or llm.toml; it may be invalid or revoked",
+                provider
+            )),
+            Self::ContextTooLarge {
+                limit: Some(limit), ..
+            } => Some(format!(
+                "prompt exceeds {} tokens; send less input (e.g. summarize or split it) or use a preset with a larger context window",
+                format_tokens(*limit)
+            )),
+            Self::ContextTooLarge { limit: None, .. } => Some(
+                "send less input (e.g. summarize or split it) or use a preset with a larger context window"
+                    .to_string(),
+            ),
+            Self::ContentFiltered { .. } => Some(
+                "the provider's content filter blocked this request; rephrase it or use a preset on another provider"
+                    .to_string(),
+            ),
+            Self::ResponseRejected { .. } => Some(
+                "the response broke this tool's content policy; retry, or rephrase the request"
+                    .to_string(),
+            ),
+            Self::InvalidPreset(name) => Some(format!(
+                "preset '{}' is not defined; check the [presets] section of ~/.config/cli-programs/llm.toml",
+                name
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_token_counts() {
+        assert_eq!(
+            parse_token_counts("prompt is too long: 208477 tokens > 200000 maximum"),
+            (Some(200_000), Some(208_477))
+        );
+        assert_eq!(
+            parse_token_counts(
+                "This model's maximum context length is 128,000 tokens. \
+                 However, your messages resulted in 130,512 tokens."
+            ),
+            (Some(128_000), Some(130_512))
+        );
+        assert_eq!(parse_token_counts("context window exceeded"), (None, None));
+        assert_eq!(parse_token_counts("198000 + 4096 > 200000"), (None, None));
+    }
+
+    #[test]
+    fn test_from_http_response_status_codes() {
+        assert!(matches!(
+            LlmError::from_http_response("Anthropic API", 401, None, "bad key".into(), None),
+            LlmError::AuthFailed { .. }
+        ));
+        assert!(matches!(
+            LlmError::from_http_response("OpenRouter", 429, None, "slow down".into(), Some(30)),
+            LlmError::RateLimited {
+                retry_after: Some(30)
+            }
+        ));
+        assert!(matches!(
+            LlmError::from_http_response("Anthropic API", 529, None, "Overloaded".into(), None),
+            LlmError::ServerOverloaded { .. }
+        ));
+        assert!(matches!(
+            LlmError::from_http_response("Cerebras", 500, None, "boom".into(), None),
+            LlmError::ApiError {
+                status_code: Some(500),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_from_http_response_context_too_large() {
+        let err = LlmError::from_http_response(
+            "Anthropic API",
+            400,
+            Some("invalid_request_error"),
+            "prompt is too long: 208477 tokens > 200000 maximum".into(),
+            None,
+        );
+        assert!(matches!(
+            err,
+            LlmError::ContextTooLarge {
+                limit: Some(200_000),
+                actual: Some(208_477)
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Prompt is too large for the model's context window (208477 tokens, limit 200000)"
+        );
+        assert!(
+            err.remediation()
+                .unwrap()
+                .starts_with("prompt exceeds 200k tokens")
+        );
+
+        let err = LlmError::from_http_response(
+            "OpenRouter",
+            400,
+            Some("context_length_exceeded"),
+            "too long".into(),
+            None,
+        );
+        assert!(matches!(err, LlmError::ContextTooLarge { limit: None, .. }));
+    }
+
+    #[test]
+    fn test_from_http_response_content_filter() {
+        let err = LlmError::from_http_response(
+            "OpenRouter",
+            400,
+            Some("content_filter"),
+            "The response was filtered".into(),
+            None,
+        );
+        assert!(matches!(err, LlmError::ContentFiltered { .. }));
+    }
+
+    #[test]
+    fn test_from_error_message_claude_cli() {
+        assert!(matches!(
+            LlmError::from_error_message("Claude CLI", None, "Prompt is too long"),
+            Some(LlmError::ContextTooLarge { .. })
+        ));
+        let auth =
+            LlmError::from_error_message("Claude CLI", None, "Invalid API key · Please run /login")
+                .unwrap();
+        assert!(auth.remediation().unwrap().contains("/login"));
+        assert!(matches!(
+            LlmError::from_error_message("Claude CLI", None, "Claude usage limit reached"),
+            Some(LlmError::RateLimited { retry_after: None })
+        ));
+        assert!(LlmError::from_error_message("Claude CLI", None, "something else").is_none());
+    }
+
+    #[test]
+    fn test_remediation() {
+        let err = LlmError::RateLimited {
+            retry_after: Some(20),
+        };
+        assert!(err.remediation().unwrap().contains("20 seconds"));
+
+        let err = LlmError::MissingApiKey {
+            provider: "OpenRouter".into(),
+            env_var: "OPENROUTER_API_KEY".into(),
+        };
+        assert!(err.remediation().unwrap().contains("OPENROUTER_API_KEY"));
+
+        assert!(LlmError::ConfigError("x".into()).remediation().is_none());
+    }
+
+    #[test]
+    fn test_format_tokens() {
+        assert_eq!(format_tokens(200_000), "200k");
+        assert_eq!(format_tokens(131_072), "131072");
+        assert_eq!(format_tokens(512), "512");
+    }
+}
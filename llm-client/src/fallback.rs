@@ -1,7 +1,8 @@
 //! Fallback provider chain support
 //!
 //! Enables configuring multiple providers in a fallback chain,
-//! where if one provider fails, the next one is tried.
+//! where if one provider fails, the next one is tried. Responses record
+//! which preset served them in `LlmResponse::served_by`.
 
 use async_trait::async_trait;
 use std::collections::HashSet;
@@ -9,8 +10,9 @@ use std::sync::Arc;
 
 use crate::config::Config;
 use crate::error::{LlmError, Result};
-use crate::provider::{LlmProvider, LlmRequest, LlmResponse};
+use crate::provider::{LlmProvider, LlmRequest, LlmResponse, ServedBy};
 use crate::providers::get_provider;
+use crate::ratelimit::with_rate_limits;
 
 /// Callback invoked when the fallback chain advances to the next preset.
 /// Receives the name of the preset about to be tried.
@@ -28,6 +30,8 @@ pub struct FallbackProvider {
     debug: bool,
     /// Optional callback invoked with the next preset name on each fallback transition
     on_fallback: Option<FallbackCallback>,
+    /// Fail with the primary preset's error instead of falling back
+    strict: bool,
 }
 
 impl std::fmt::Debug for FallbackProvider {
@@ -37,6 +41,7 @@ impl std::fmt::Debug for FallbackProvider {
             .field("preset_names", &self.chain.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>())
             .field("debug", &self.debug)
             .field("has_fallback_callback", &self.on_fallback.is_some())
+            .field("strict", &self.strict)
             .finish()
     }
 }
@@ -44,7 +49,7 @@ impl std::fmt::Debug for FallbackProvider {
 impl FallbackProvider {
     /// Create a new FallbackProvider with the given chain
     fn new(chain: Vec<(String, Box<dyn LlmProvider>)>) -> Self {
-        Self { chain, debug: false, on_fallback: None }
+        Self { chain, debug: false, on_fallback: None, strict: false }
     }
 
     /// Create a FallbackProvider directly from a chain of providers.
@@ -71,6 +76,22 @@ impl FallbackProvider {
         self
     }
 
+    /// Fail with the primary preset's error instead of falling back, for
+    /// quality-sensitive tasks where a cheaper model's answer isn't wanted
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// The presets a request may be sent to: only the primary one when strict
+    fn active_chain(&self) -> &[(String, Box<dyn LlmProvider>)] {
+        if self.strict {
+            &self.chain[..self.chain.len().min(1)]
+        } else {
+            &self.chain
+        }
+    }
+
     /// Get the name of the primary provider
     pub fn primary_name(&self) -> &str {
         self.chain
@@ -89,10 +110,18 @@ impl FallbackProvider {
 impl LlmProvider for FallbackProvider {
     async fn complete(&self, request: LlmRequest) -> Result<LlmResponse> {
         let mut last_error = None;
+        let chain = self.active_chain();
 
-        for (i, (preset_name, provider)) in self.chain.iter().enumerate() {
+        for (i, (preset_name, provider)) in chain.iter().enumerate() {
             match provider.complete(request.clone()).await {
-                Ok(response) => return Ok(response),
+                Ok(mut response) => {
+                    response.served_by = Some(ServedBy {
+                        preset: preset_name.clone(),
+                        provider: provider.name(),
+                        fallbacks: i,
+                    });
+                    return Ok(response);
+                }
                 Err(e) => {
                     if self.debug {
                         eprintln!(
@@ -102,8 +131,8 @@ impl LlmProvider for FallbackProvider {
                     }
 
                     // If there's a next provider, log and continue
-                    if i + 1 < self.chain.len() {
-                        let next_name = &self.chain[i + 1].0;
+                    if i + 1 < chain.len() {
+                        let next_name = &chain[i + 1].0;
                         if let Some(cb) = &self.on_fallback {
                             cb(next_name);
                         }
@@ -133,7 +162,7 @@ impl LlmProvider for FallbackProvider {
 
     fn is_available(&self) -> Result<()> {
         // Check if at least one provider is available
-        for (_, provider) in &self.chain {
+        for (_, provider) in self.active_chain() {
             if provider.is_available().is_ok() {
                 return Ok(());
             }
@@ -185,6 +214,7 @@ pub fn get_provider_with_fallback(config: &Config, preset_name: &str) -> Result<
         // Create the provider, skipping if API key is missing
         match get_provider(preset, provider_config) {
             Ok(provider) => {
+                let provider = with_rate_limits(config, &preset.provider, provider)?;
                 chain.push((name.clone(), provider));
             }
             Err(LlmError::MissingApiKey { provider, env_var }) => {
@@ -194,6 +224,10 @@ pub fn get_provider_with_fallback(config: &Config, preset_name: &str) -> Result<
                 );
                 // Continue to next preset in chain
             }
+            Err(LlmError::ProviderUnavailable(reason)) => {
+                // e.g. the Claude CLI isn't installed on this machine
+                eprintln!("Warning: Skipping '{}' - {}", name, reason);
+            }
             Err(e) => {
                 // Other errors (config errors, invalid provider, etc.) should still fail
                 return Err(e);
@@ -216,7 +250,7 @@ pub fn get_provider_with_fallback(config: &Config, preset_name: &str) -> Result<
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::ModelPreset;
+    use crate::config::{ModelPreset, ProviderConfig};
     use crate::providers::MockProvider;
     use std::collections::HashMap;
 
@@ -230,6 +264,9 @@ mod tests {
                 model: "sonnet".to_string(),
                 fallback: Some("fallback1".to_string()),
                 api_key_env: None,
+                requires: Vec::new(),
+                context_tokens: None,
+                tags: Vec::new(),
             },
         );
 
@@ -240,6 +277,9 @@ mod tests {
                 model: "sonnet".to_string(),
                 fallback: Some("fallback2".to_string()),
                 api_key_env: None,
+                requires: Vec::new(),
+                context_tokens: None,
+                tags: Vec::new(),
             },
         );
 
@@ -250,6 +290,9 @@ mod tests {
                 model: "sonnet".to_string(),
                 fallback: None,
                 api_key_env: None,
+                requires: Vec::new(),
+                context_tokens: None,
+                tags: Vec::new(),
             },
         );
 
@@ -258,6 +301,9 @@ mod tests {
             defaults: HashMap::new(),
             presets,
             providers: HashMap::new(),
+            rate_limit: Default::default(),
+            profiles: HashMap::new(),
+            templates: HashMap::new(),
         }
     }
 
@@ -271,6 +317,9 @@ mod tests {
                 model: "sonnet".to_string(),
                 fallback: Some("b".to_string()),
                 api_key_env: None,
+                requires: Vec::new(),
+                context_tokens: None,
+                tags: Vec::new(),
             },
         );
 
@@ -281,6 +330,9 @@ mod tests {
                 model: "sonnet".to_string(),
                 fallback: Some("a".to_string()), // Cycle back to 'a'
                 api_key_env: None,
+                requires: Vec::new(),
+                context_tokens: None,
+                tags: Vec::new(),
             },
         );
 
@@ -289,6 +341,9 @@ mod tests {
             defaults: HashMap::new(),
             presets,
             providers: HashMap::new(),
+            rate_limit: Default::default(),
+            profiles: HashMap::new(),
+            templates: HashMap::new(),
         }
     }
 
@@ -312,6 +367,39 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_unavailable_provider_is_skipped() {
+        let mut config = test_config();
+        config.presets.get_mut("fallback1").unwrap().fallback = None;
+        config.presets.get_mut("fallback1").unwrap().provider = "lm-studio".to_string();
+        config.providers.insert(
+            "claude-cli".to_string(),
+            ProviderConfig {
+                cli_path: Some("/nonexistent/claude".into()),
+                ..Default::default()
+            },
+        );
+
+        let provider = get_provider_with_fallback(&config, "primary").unwrap();
+        assert_eq!(provider.chain_len(), 1);
+        assert_eq!(provider.primary_name(), "fallback1");
+    }
+
+    #[test]
+    fn test_all_providers_unavailable() {
+        let mut config = test_config();
+        config.providers.insert(
+            "claude-cli".to_string(),
+            ProviderConfig {
+                cli_path: Some("/nonexistent/claude".into()),
+                ..Default::default()
+            },
+        );
+
+        let result = get_provider_with_fallback(&config, "primary");
+        assert!(result.unwrap_err().to_string().contains("No providers"));
+    }
+
     #[tokio::test]
     async fn test_fallback_provider_success_on_first() {
         let chain = vec![(
@@ -331,7 +419,10 @@ mod tests {
 
         let result = provider.complete(request).await;
         assert!(result.is_ok());
-        assert_eq!(result.unwrap().content, "response");
+        let response = result.unwrap();
+        assert_eq!(response.content, "response");
+        assert!(!response.fell_back());
+        assert_eq!(response.served_by.unwrap().preset, "primary");
     }
 
     #[tokio::test]
@@ -362,7 +453,47 @@ mod tests {
 
         let result = provider.complete(request).await;
         assert!(result.is_ok());
-        assert_eq!(result.unwrap().content, "fallback response");
+        let response = result.unwrap();
+        assert_eq!(response.content, "fallback response");
+        assert!(response.fell_back());
+        assert_eq!(
+            response.served_by,
+            Some(ServedBy {
+                preset: "fallback".to_string(),
+                provider: "mock",
+                fallbacks: 1,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_strict_provider_does_not_fall_back() {
+        let chain = vec![
+            (
+                "primary".to_string(),
+                Box::new(MockProvider::always_fails(LlmError::ApiError {
+                    message: "primary failed".to_string(),
+                    status_code: Some(500),
+                })) as Box<dyn LlmProvider>,
+            ),
+            (
+                "fallback".to_string(),
+                Box::new(MockProvider::always_succeeds("cheaper response")) as Box<dyn LlmProvider>,
+            ),
+        ];
+
+        let provider = FallbackProvider::new(chain).with_strict(true);
+        let request = LlmRequest {
+            prompt: "test".to_string(),
+            system_prompt: None,
+            max_tokens: None,
+            temperature: None,
+            files: vec![],
+            json_schema: None,
+        };
+
+        let err = provider.complete(request).await.unwrap_err().to_string();
+        assert!(err.contains("primary failed"));
     }
 
     #[test]
@@ -379,6 +510,9 @@ mod tests {
                 model: "claude-sonnet-4-20250514".to_string(),
                 fallback: Some("fallback".to_string()),
                 api_key_env: Some("__LLM_CLIENT_TEST_NONEXISTENT_KEY_12345__".to_string()),
+                requires: Vec::new(),
+                context_tokens: None,
+                tags: Vec::new(),
             },
         );
 
@@ -390,6 +524,9 @@ mod tests {
                 model: "sonnet".to_string(),
                 fallback: None,
                 api_key_env: None,
+                requires: Vec::new(),
+                context_tokens: None,
+                tags: Vec::new(),
             },
         );
 
@@ -398,6 +535,9 @@ mod tests {
             defaults: HashMap::new(),
             presets,
             providers: HashMap::new(),
+            rate_limit: Default::default(),
+            profiles: HashMap::new(),
+            templates: HashMap::new(),
         };
 
         // Should succeed by skipping anthropic and using claude-cli
@@ -422,6 +562,9 @@ mod tests {
                 model: "claude-sonnet-4-20250514".to_string(),
                 fallback: Some("fallback".to_string()),
                 api_key_env: Some("__LLM_CLIENT_TEST_NONEXISTENT_KEY_A__".to_string()),
+                requires: Vec::new(),
+                context_tokens: None,
+                tags: Vec::new(),
             },
         );
 
@@ -432,6 +575,9 @@ mod tests {
                 model: "llama-4-scout".to_string(),
                 fallback: None,
                 api_key_env: Some("__LLM_CLIENT_TEST_NONEXISTENT_KEY_B__".to_string()),
+                requires: Vec::new(),
+                context_tokens: None,
+                tags: Vec::new(),
             },
         );
 
@@ -440,6 +586,9 @@ mod tests {
             defaults: HashMap::new(),
             presets,
             providers: HashMap::new(),
+            rate_limit: Default::default(),
+            profiles: HashMap::new(),
+            templates: HashMap::new(),
         };
 
         // Should fail because all providers in chain are missing API keys
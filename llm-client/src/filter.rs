@@ -0,0 +1,300 @@
+//! Response filters for content policies
+//!
+//! Tools that must keep certain content out of model output (gc rejects
+//! emails and URLs in commit messages, for example) register filters on a
+//! [`FilteredProvider`], so every completion is checked the same way no matter
+//! which provider served it. A filter returns a description of each violation
+//! it finds; any violation fails the request with
+//! [`LlmError::ResponseRejected`], which carries the rejected content so the
+//! caller can ask the model to fix it.
+
+use async_trait::async_trait;
+use regex::Regex;
+
+use crate::error::{LlmError, Result};
+use crate::provider::{LlmProvider, LlmRequest, LlmResponse};
+
+/// Checks response content against a policy
+pub trait ResponseFilter: Send + Sync {
+    /// Describe each violation in `content`; empty if it is acceptable
+    fn check(&self, content: &str) -> Vec<String>;
+}
+
+/// Closures work as filters, for one-off checks
+impl<F> ResponseFilter for F
+where
+    F: Fn(&str) -> Vec<String> + Send + Sync,
+{
+    fn check(&self, content: &str) -> Vec<String> {
+        self(content)
+    }
+}
+
+/// Rejects content matching any of a set of regular expressions
+#[derive(Debug, Clone)]
+pub struct RegexFilter {
+    /// Each pattern with what it catches, reported instead of the pattern
+    patterns: Vec<(Regex, Option<String>)>,
+}
+
+impl RegexFilter {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| Ok((compile(pattern.as_ref())?, None)))
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Patterns paired with a description of what they catch, which is what
+    /// a match reports
+    pub fn described<S: AsRef<str>, D: AsRef<str>>(patterns: &[(S, D)]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|(pattern, description)| {
+                Ok((
+                    compile(pattern.as_ref())?,
+                    Some(description.as_ref().to_string()),
+                ))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+}
+
+fn compile(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern)
+        .map_err(|e| LlmError::ConfigError(format!("Invalid filter pattern {}: {}", pattern, e)))
+}
+
+impl ResponseFilter for RegexFilter {
+    fn check(&self, content: &str) -> Vec<String> {
+        self.patterns
+            .iter()
+            .filter(|(pattern, _)| pattern.is_match(content))
+            .map(|(pattern, description)| match description {
+                Some(description) => description.clone(),
+                None => format!("Matches banned pattern: {}", pattern),
+            })
+            .collect()
+    }
+}
+
+/// Rejects content containing any of a list of terms, ignoring case
+#[derive(Debug, Clone)]
+pub struct DenyListFilter {
+    terms: Vec<String>,
+}
+
+impl DenyListFilter {
+    pub fn new<S: AsRef<str>>(terms: &[S]) -> Self {
+        Self {
+            terms: terms.iter().map(|t| t.as_ref().to_string()).collect(),
+        }
+    }
+}
+
+impl ResponseFilter for DenyListFilter {
+    fn check(&self, content: &str) -> Vec<String> {
+        let content = content.to_lowercase();
+        self.terms
+            .iter()
+            .filter(|term| content.contains(&term.to_lowercase()))
+            .map(|term| format!("Contains denied term: {}", term))
+            .collect()
+    }
+}
+
+/// An ordered set of filters, checked together
+#[derive(Default)]
+pub struct ResponseFilters {
+    filters: Vec<Box<dyn ResponseFilter>>,
+}
+
+impl ResponseFilters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a filter; violations are reported in registration order
+    pub fn with_filter(mut self, filter: impl ResponseFilter + 'static) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// Every violation found by any filter
+    pub fn check(&self, content: &str) -> Vec<String> {
+        self.filters
+            .iter()
+            .flat_map(|filter| filter.check(content))
+            .collect()
+    }
+}
+
+/// A provider that checks every response against its filters
+pub struct FilteredProvider {
+    inner: Box<dyn LlmProvider>,
+    filters: ResponseFilters,
+}
+
+impl FilteredProvider {
+    pub fn new(inner: Box<dyn LlmProvider>, filters: ResponseFilters) -> Self {
+        Self { inner, filters }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for FilteredProvider {
+    async fn complete(&self, request: LlmRequest) -> Result<LlmResponse> {
+        let response = self.inner.complete(request).await?;
+        let violations = self.filters.check(&response.content);
+        if violations.is_empty() {
+            Ok(response)
+        } else {
+            Err(LlmError::ResponseRejected {
+                violations,
+                content: response.content,
+            })
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn is_available(&self) -> Result<()> {
+        self.inner.is_available()
+    }
+}
+
+/// Apply filters to a provider.
+///
+/// Returns the provider unchanged if there are none.
+pub fn with_filters(
+    provider: Box<dyn LlmProvider>,
+    filters: ResponseFilters,
+) -> Box<dyn LlmProvider> {
+    if filters.is_empty() {
+        provider
+    } else {
+        Box::new(FilteredProvider::new(provider, filters))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::MockProvider;
+
+    fn request() -> LlmRequest {
+        LlmRequest {
+            prompt: "hi".to_string(),
+            system_prompt: None,
+            max_tokens: None,
+            temperature: None,
+            files: vec![],
+            json_schema: None,
+        }
+    }
+
+    #[test]
+    fn test_regex_filter() {
+        let filter = RegexFilter::new(&["(?i)co-authored-by:", r"JIRA-\d+"]).unwrap();
+        assert_eq!(
+            filter.check("fix: JIRA-12\n\nCo-Authored-By: someone"),
+            vec![
+                "Matches banned pattern: (?i)co-authored-by:",
+                r"Matches banned pattern: JIRA-\d+"
+            ]
+        );
+        assert!(filter.check("fix: typo").is_empty());
+    }
+
+    #[test]
+    fn test_described_regex_filter() {
+        let filter = RegexFilter::described(&[
+            (r"\bsudo\b", "runs with root privileges"),
+            (r"\brm\s+-rf\b", "removes files recursively"),
+        ])
+        .unwrap();
+        assert_eq!(
+            filter.check("sudo rm -rf /tmp/x"),
+            vec!["runs with root privileges", "removes files recursively"]
+        );
+        assert!(filter.check("ls").is_empty());
+        assert!(RegexFilter::described(&[("(unclosed", "broken")]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_regex() {
+        let err = RegexFilter::new(&["(unclosed"]).unwrap_err();
+        assert!(matches!(err, LlmError::ConfigError(_)));
+        assert!(err.to_string().contains("(unclosed"));
+    }
+
+    #[test]
+    fn test_deny_list_ignores_case() {
+        let filter = DenyListFilter::new(&["rm -rf", "mkfs"]);
+        assert_eq!(
+            filter.check("sudo RM -RF /"),
+            vec!["Contains denied term: rm -rf"]
+        );
+        assert!(filter.check("ls -la").is_empty());
+    }
+
+    #[test]
+    fn test_filters_combine_in_order() {
+        let filters = ResponseFilters::new()
+            .with_filter(DenyListFilter::new(&["secret"]))
+            .with_filter(|content: &str| {
+                if content.len() > 10 {
+                    vec!["Too long".to_string()]
+                } else {
+                    vec![]
+                }
+            });
+        assert_eq!(
+            filters.check("the secret word"),
+            vec!["Contains denied term: secret", "Too long"]
+        );
+        assert!(filters.check("ok").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_filtered_provider_rejects() {
+        let filters = ResponseFilters::new().with_filter(DenyListFilter::new(&["forbidden"]));
+        let provider = FilteredProvider::new(
+            Box::new(MockProvider::always_succeeds("a forbidden answer")),
+            filters,
+        );
+
+        match provider.complete(request()).await {
+            Err(LlmError::ResponseRejected {
+                violations,
+                content,
+            }) => {
+                assert_eq!(violations, vec!["Contains denied term: forbidden"]);
+                assert_eq!(content, "a forbidden answer");
+            }
+            other => panic!(
+                "expected ResponseRejected, got {:?}",
+                other.map(|r| r.content)
+            ),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filtered_provider_passes_clean_response() {
+        let provider = with_filters(
+            Box::new(MockProvider::always_succeeds("fine").with_name("mock")),
+            ResponseFilters::new().with_filter(DenyListFilter::new(&["forbidden"])),
+        );
+        let response = provider.complete(request()).await.unwrap();
+        assert_eq!(response.content, "fine");
+        assert_eq!(provider.name(), "mock");
+    }
+}
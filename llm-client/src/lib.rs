@@ -6,14 +6,26 @@
 //! - OpenRouter (multi-model access)
 //! - Cerebras (fast Llama inference)
 
+pub mod capabilities;
 pub mod config;
 pub mod error;
 pub mod fallback;
+pub mod filter;
 pub mod provider;
 pub mod providers;
+pub mod ratelimit;
+pub mod template;
 
-pub use config::{Config, ModelPreset, ProviderConfig};
+pub use capabilities::{Capability, ProviderCapabilities};
+pub use config::{Config, ModelPreset, ProviderConfig, profiles};
 pub use error::{LlmError, Result};
 pub use fallback::{FallbackProvider, get_provider_with_fallback};
-pub use provider::{FileAttachment, LlmProvider, LlmRequest, LlmResponse, TokenUsage};
+pub use filter::{
+    DenyListFilter, FilteredProvider, RegexFilter, ResponseFilter, ResponseFilters, with_filters,
+};
+pub use provider::{
+    FileAttachment, FinishReason, LlmProvider, LlmRequest, LlmResponse, ServedBy, TokenUsage,
+};
 pub use providers::{MockProvider, ProviderKind, get_provider};
+pub use ratelimit::{RateLimit, RateLimitedProvider, RateLimiter, with_rate_limits};
+pub use template::{PromptTemplate, PromptTemplates, TemplatedClient};
@@ -1,5 +1,7 @@
 use async_trait::async_trait;
 use serde_json::Value;
+use std::fmt;
+use std::time::Duration;
 
 use crate::error::Result;
 
@@ -29,8 +31,95 @@ pub struct LlmRequest {
 #[derive(Debug, Clone)]
 pub struct LlmResponse {
     pub content: String,
+    /// Model that produced the response, as reported by the provider when
+    /// available (falls back to the requested model)
     pub model: String,
     pub usage: Option<TokenUsage>,
+    /// Why generation stopped, when the provider reports it
+    pub finish_reason: Option<FinishReason>,
+    /// Wall-clock time for the request
+    pub latency: Duration,
+    /// The preset that served the request, set by `FallbackProvider`
+    pub served_by: Option<ServedBy>,
+}
+
+impl LlmResponse {
+    /// Whether the response was cut off before the model finished
+    pub fn is_truncated(&self) -> bool {
+        matches!(
+            self.finish_reason,
+            Some(FinishReason::Length | FinishReason::ContentFilter)
+        )
+    }
+
+    /// Whether a fallback preset served the request instead of the primary one
+    pub fn fell_back(&self) -> bool {
+        self.served_by
+            .as_ref()
+            .is_some_and(|served_by| served_by.fallbacks > 0)
+    }
+}
+
+/// Which preset of a fallback chain served a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServedBy {
+    /// Name of the preset
+    pub preset: String,
+    /// Display name of the preset's provider
+    pub provider: &'static str,
+    /// Number of presets that failed before this one
+    pub fallbacks: usize,
+}
+
+/// Why the model stopped generating
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FinishReason {
+    /// Natural end of the response or a stop sequence
+    Stop,
+    /// Hit the max token limit; the content is cut off
+    Length,
+    /// Stopped by the provider's content filter
+    ContentFilter,
+    /// Stopped to call a tool
+    ToolUse,
+    /// Any other provider-specific reason
+    Other(String),
+}
+
+impl FinishReason {
+    /// Map an OpenAI-compatible `finish_reason`
+    pub fn from_openai(reason: &str) -> Self {
+        match reason {
+            "stop" => Self::Stop,
+            "length" => Self::Length,
+            "content_filter" => Self::ContentFilter,
+            "tool_calls" | "function_call" => Self::ToolUse,
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    /// Map an Anthropic `stop_reason`
+    pub fn from_anthropic(reason: &str) -> Self {
+        match reason {
+            "end_turn" | "stop_sequence" => Self::Stop,
+            "max_tokens" => Self::Length,
+            "refusal" => Self::ContentFilter,
+            "tool_use" => Self::ToolUse,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for FinishReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stop => write!(f, "stop"),
+            Self::Length => write!(f, "length"),
+            Self::ContentFilter => write!(f, "content filter"),
+            Self::ToolUse => write!(f, "tool use"),
+            Self::Other(reason) => write!(f, "{}", reason),
+        }
+    }
 }
 
 /// Token usage information
@@ -52,3 +141,82 @@ pub trait LlmProvider: Send + Sync {
     /// Check if the provider is available (API key set, CLI installed, etc.)
     fn is_available(&self) -> Result<()>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(finish_reason: Option<FinishReason>) -> LlmResponse {
+        LlmResponse {
+            content: "text".to_string(),
+            model: "model".to_string(),
+            usage: None,
+            finish_reason,
+            latency: Duration::ZERO,
+            served_by: None,
+        }
+    }
+
+    #[test]
+    fn test_finish_reason_from_openai() {
+        assert_eq!(FinishReason::from_openai("stop"), FinishReason::Stop);
+        assert_eq!(FinishReason::from_openai("length"), FinishReason::Length);
+        assert_eq!(
+            FinishReason::from_openai("content_filter"),
+            FinishReason::ContentFilter
+        );
+        assert_eq!(
+            FinishReason::from_openai("tool_calls"),
+            FinishReason::ToolUse
+        );
+        assert_eq!(
+            FinishReason::from_openai("eos"),
+            FinishReason::Other("eos".to_string())
+        );
+    }
+
+    #[test]
+    fn test_finish_reason_from_anthropic() {
+        assert_eq!(FinishReason::from_anthropic("end_turn"), FinishReason::Stop);
+        assert_eq!(
+            FinishReason::from_anthropic("max_tokens"),
+            FinishReason::Length
+        );
+        assert_eq!(
+            FinishReason::from_anthropic("refusal"),
+            FinishReason::ContentFilter
+        );
+        assert_eq!(
+            FinishReason::from_anthropic("tool_use"),
+            FinishReason::ToolUse
+        );
+    }
+
+    #[test]
+    fn test_is_truncated() {
+        assert!(response(Some(FinishReason::Length)).is_truncated());
+        assert!(response(Some(FinishReason::ContentFilter)).is_truncated());
+        assert!(!response(Some(FinishReason::Stop)).is_truncated());
+        assert!(!response(None).is_truncated());
+    }
+
+    #[test]
+    fn test_fell_back() {
+        let mut response = response(None);
+        assert!(!response.fell_back());
+
+        let served_by = ServedBy {
+            preset: "primary".to_string(),
+            provider: "Claude CLI",
+            fallbacks: 0,
+        };
+        response.served_by = Some(served_by.clone());
+        assert!(!response.fell_back());
+
+        response.served_by = Some(ServedBy {
+            fallbacks: 2,
+            ..served_by
+        });
+        assert!(response.fell_back());
+    }
+}
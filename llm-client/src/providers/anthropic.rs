@@ -5,9 +5,11 @@
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::time::Instant;
 
 use crate::error::{LlmError, Result};
-use crate::provider::{LlmProvider, LlmRequest, LlmResponse, TokenUsage};
+use crate::provider::{FinishReason, LlmProvider, LlmRequest, LlmResponse, TokenUsage};
+use crate::providers::retry_after_secs;
 
 const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1/messages";
 const ANTHROPIC_VERSION: &str = "2023-06-01";
@@ -52,6 +54,10 @@ struct Message {
 #[derive(Debug, Deserialize)]
 struct MessagesResponse {
     content: Vec<ContentBlock>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    stop_reason: Option<String>,
     usage: ResponseUsage,
 }
 
@@ -73,6 +79,9 @@ struct ErrorResponse {
 
 #[derive(Debug, Deserialize)]
 struct ApiError {
+    /// e.g. "invalid_request_error", "rate_limit_error", "overloaded_error"
+    #[serde(rename = "type", default)]
+    kind: Option<String>,
     message: String,
 }
 
@@ -91,6 +100,7 @@ impl LlmProvider for AnthropicProvider {
             messages,
         };
 
+        let started = Instant::now();
         let response = self
             .client
             .post(ANTHROPIC_API_URL)
@@ -107,23 +117,20 @@ impl LlmProvider for AnthropicProvider {
 
         let status = response.status();
         if !status.is_success() {
+            let retry_after = retry_after_secs(&response);
             let error_text = response.text().await.unwrap_or_default();
-            let message =
-                if let Ok(error_response) = serde_json::from_str::<ErrorResponse>(&error_text) {
-                    error_response.error.message
-                } else {
-                    error_text
-                };
-
-            // Handle 503 (server overloaded) separately for retry logic
-            if status.as_u16() == 503 {
-                return Err(LlmError::ServerOverloaded { message });
-            }
-
-            return Err(LlmError::ApiError {
+            let (kind, message) = match serde_json::from_str::<ErrorResponse>(&error_text) {
+                Ok(error_response) => (error_response.error.kind, error_response.error.message),
+                Err(_) => (None, error_text),
+            };
+
+            return Err(LlmError::from_http_response(
+                self.name(),
+                status.as_u16(),
+                kind.as_deref(),
                 message,
-                status_code: Some(status.as_u16()),
-            });
+                retry_after,
+            ));
         }
 
         let api_response: MessagesResponse =
@@ -138,6 +145,16 @@ impl LlmProvider for AnthropicProvider {
             .map(|c| c.text.clone())
             .unwrap_or_default();
 
+        let finish_reason = api_response
+            .stop_reason
+            .as_deref()
+            .map(FinishReason::from_anthropic);
+        if content.is_empty() && finish_reason == Some(FinishReason::ContentFilter) {
+            return Err(LlmError::ContentFiltered {
+                message: "the model declined to respond".to_string(),
+            });
+        }
+
         let usage = Some(TokenUsage {
             input_tokens: api_response.usage.input_tokens,
             output_tokens: api_response.usage.output_tokens,
@@ -145,8 +162,11 @@ impl LlmProvider for AnthropicProvider {
 
         Ok(LlmResponse {
             content,
-            model: self.model.clone(),
+            model: api_response.model.unwrap_or_else(|| self.model.clone()),
             usage,
+            finish_reason,
+            latency: started.elapsed(),
+            served_by: None,
         })
     }
 
@@ -3,11 +3,16 @@
 //! Uses the installed Claude Code CLI as a subprocess.
 
 use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Instant;
 use tokio::process::Command;
 
 use crate::error::{LlmError, Result};
-use crate::provider::{LlmProvider, LlmRequest, LlmResponse};
+use crate::provider::{FinishReason, LlmProvider, LlmRequest, LlmResponse, TokenUsage};
+
+const PROVIDER_NAME: &str = "Claude CLI";
 
 /// Provider that uses the Claude CLI (subprocess)
 pub struct ClaudeCliProvider {
@@ -47,6 +52,97 @@ impl ClaudeCliProvider {
     }
 }
 
+/// Result object printed by `claude --print --output-format json`
+#[derive(Debug, Deserialize)]
+struct CliResult {
+    #[serde(default)]
+    subtype: Option<String>,
+    #[serde(default)]
+    is_error: bool,
+    #[serde(default)]
+    result: Option<String>,
+    #[serde(default)]
+    usage: Option<CliUsage>,
+    /// Per-model usage, keyed by the model ID actually used
+    #[serde(default, rename = "modelUsage")]
+    model_usage: HashMap<String, CliModelUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CliUsage {
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    cache_creation_input_tokens: u32,
+    #[serde(default)]
+    cache_read_input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct CliModelUsage {
+    #[serde(default, rename = "outputTokens")]
+    output_tokens: u64,
+}
+
+/// Content and metadata parsed from the CLI's JSON output
+#[derive(Debug, PartialEq)]
+struct ParsedOutput {
+    content: String,
+    model: Option<String>,
+    usage: Option<(u32, u32)>,
+    finish_reason: Option<FinishReason>,
+}
+
+/// Map a CLI failure message to a structured error where it is recognizable
+fn cli_error(message: String) -> LlmError {
+    LlmError::from_error_message(PROVIDER_NAME, None, &message)
+        .unwrap_or(LlmError::ClaudeCliError(message))
+}
+
+/// Parse JSON output from the CLI, falling back to treating stdout as plain
+/// text (older CLI versions, or output that isn't a result object)
+fn parse_cli_output(stdout: &str) -> Result<ParsedOutput> {
+    let Ok(result) = serde_json::from_str::<CliResult>(stdout.trim()) else {
+        return Ok(ParsedOutput {
+            content: stdout.trim().to_string(),
+            model: None,
+            usage: None,
+            finish_reason: None,
+        });
+    };
+
+    if result.is_error {
+        let message = result
+            .result
+            .unwrap_or_else(|| result.subtype.unwrap_or_else(|| "unknown error".into()));
+        return Err(cli_error(message));
+    }
+
+    // The main model does the most output; helper models may also appear
+    let model = result
+        .model_usage
+        .iter()
+        .max_by_key(|(_, usage)| usage.output_tokens)
+        .map(|(model, _)| model.clone());
+
+    Ok(ParsedOutput {
+        content: result.result.unwrap_or_default().trim().to_string(),
+        model,
+        usage: result.usage.map(|u| {
+            (
+                u.input_tokens + u.cache_creation_input_tokens + u.cache_read_input_tokens,
+                u.output_tokens,
+            )
+        }),
+        finish_reason: result.subtype.map(|subtype| match subtype.as_str() {
+            "success" => FinishReason::Stop,
+            _ => FinishReason::Other(subtype),
+        }),
+    })
+}
+
 #[async_trait]
 impl LlmProvider for ClaudeCliProvider {
     async fn complete(&self, request: LlmRequest) -> Result<LlmResponse> {
@@ -58,35 +154,43 @@ impl LlmProvider for ClaudeCliProvider {
             cmd.args(["--system-prompt", system]);
         }
 
-        cmd.args(["--print", &request.prompt]);
+        cmd.args(["--output-format", "json", "--print", &request.prompt]);
 
+        let started = Instant::now();
         let output = cmd
             .output()
             .await
             .map_err(|e| LlmError::ClaudeCliError(format!("Failed to execute: {}", e)))?;
 
         if !output.status.success() {
+            // With --output-format json, the CLI reports most failures as an
+            // error result on stdout; parsing it returns the structured error
+            parse_cli_output(&String::from_utf8_lossy(&output.stdout))?;
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(LlmError::ClaudeCliError(format!(
-                "Command failed: {}",
-                stderr
-            )));
+            return Err(cli_error(format!("Command failed: {}", stderr.trim())));
         }
 
-        let content = String::from_utf8(output.stdout)
-            .map_err(|e| LlmError::ClaudeCliError(format!("Invalid UTF-8: {}", e)))?
-            .trim()
-            .to_string();
+        let stdout = String::from_utf8(output.stdout)
+            .map_err(|e| LlmError::ClaudeCliError(format!("Invalid UTF-8: {}", e)))?;
+        let parsed = parse_cli_output(&stdout)?;
 
         Ok(LlmResponse {
-            content,
-            model: self.model.clone(),
-            usage: None,
+            content: parsed.content,
+            model: parsed.model.unwrap_or_else(|| self.model.clone()),
+            usage: parsed
+                .usage
+                .map(|(input_tokens, output_tokens)| TokenUsage {
+                    input_tokens,
+                    output_tokens,
+                }),
+            finish_reason: parsed.finish_reason,
+            latency: started.elapsed(),
+            served_by: None,
         })
     }
 
     fn name(&self) -> &'static str {
-        "Claude CLI"
+        PROVIDER_NAME
     }
 
     fn is_available(&self) -> Result<()> {
@@ -94,3 +198,56 @@ impl LlmProvider for ClaudeCliProvider {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cli_output_json() {
+        let stdout = r#"{"type":"result","subtype":"success","is_error":false,"duration_ms":2100,
+            "result":"  Hello there  ","session_id":"abc",
+            "usage":{"input_tokens":10,"cache_creation_input_tokens":200,"cache_read_input_tokens":5,"output_tokens":42},
+            "modelUsage":{"claude-haiku-4-5":{"outputTokens":3},"claude-sonnet-4-5":{"outputTokens":42}}}"#;
+
+        let parsed = parse_cli_output(stdout).unwrap();
+        assert_eq!(parsed.content, "Hello there");
+        assert_eq!(parsed.model.as_deref(), Some("claude-sonnet-4-5"));
+        assert_eq!(parsed.usage, Some((215, 42)));
+        assert_eq!(parsed.finish_reason, Some(FinishReason::Stop));
+    }
+
+    #[test]
+    fn test_parse_cli_output_plain_text_fallback() {
+        let parsed = parse_cli_output("  just text\n").unwrap();
+        assert_eq!(parsed.content, "just text");
+        assert!(parsed.model.is_none());
+        assert!(parsed.usage.is_none());
+        assert!(parsed.finish_reason.is_none());
+    }
+
+    #[test]
+    fn test_parse_cli_output_error() {
+        let stdout = r#"{"type":"result","subtype":"error_during_execution","is_error":true}"#;
+        let err = parse_cli_output(stdout).unwrap_err();
+        assert!(err.to_string().contains("error_during_execution"));
+    }
+
+    #[test]
+    fn test_parse_cli_output_error_is_classified() {
+        let stdout = r#"{"type":"result","subtype":"success","is_error":true,"result":"Prompt is too long"}"#;
+        let err = parse_cli_output(stdout).unwrap_err();
+        assert!(matches!(err, LlmError::ContextTooLarge { .. }));
+    }
+
+    #[test]
+    fn test_parse_cli_output_other_subtype() {
+        let stdout = r#"{"subtype":"error_max_turns","is_error":false,"result":"partial"}"#;
+        let parsed = parse_cli_output(stdout).unwrap();
+        assert_eq!(parsed.content, "partial");
+        assert_eq!(
+            parsed.finish_reason,
+            Some(FinishReason::Other("error_max_turns".to_string()))
+        );
+    }
+}
@@ -6,9 +6,10 @@
 use async_trait::async_trait;
 use std::sync::Mutex;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 use crate::error::{LlmError, Result};
-use crate::provider::{LlmProvider, LlmRequest, LlmResponse};
+use crate::provider::{FinishReason, LlmProvider, LlmRequest, LlmResponse};
 
 /// A mock provider for testing retry and fallback behavior
 pub struct MockProvider {
@@ -90,6 +91,9 @@ impl LlmProvider for MockProvider {
             content: self.success_response.clone(),
             model: "mock-model".to_string(),
             usage: None,
+            finish_reason: Some(FinishReason::Stop),
+            latency: Duration::ZERO,
+            served_by: None,
         })
     }
 
@@ -115,6 +119,24 @@ fn clone_error(err: &LlmError) -> LlmError {
         LlmError::RateLimited { retry_after } => LlmError::RateLimited {
             retry_after: *retry_after,
         },
+        LlmError::AuthFailed { provider, message } => LlmError::AuthFailed {
+            provider: provider.clone(),
+            message: message.clone(),
+        },
+        LlmError::ContextTooLarge { limit, actual } => LlmError::ContextTooLarge {
+            limit: *limit,
+            actual: *actual,
+        },
+        LlmError::ContentFiltered { message } => LlmError::ContentFiltered {
+            message: message.clone(),
+        },
+        LlmError::ResponseRejected {
+            violations,
+            content,
+        } => LlmError::ResponseRejected {
+            violations: violations.clone(),
+            content: content.clone(),
+        },
         LlmError::ApiError {
             message,
             status_code,
@@ -124,6 +146,7 @@ fn clone_error(err: &LlmError) -> LlmError {
         },
         LlmError::ProviderUnavailable(s) => LlmError::ProviderUnavailable(s.clone()),
         LlmError::ConfigError(s) => LlmError::ConfigError(s.clone()),
+        LlmError::TemplateError(s) => LlmError::TemplateError(s.clone()),
         LlmError::InvalidPreset(s) => LlmError::InvalidPreset(s.clone()),
         LlmError::ClaudeCliError(s) => LlmError::ClaudeCliError(s.clone()),
         // For Io and Toml errors, we create a generic error since they can't be cloned
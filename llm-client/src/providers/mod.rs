@@ -10,6 +10,8 @@ pub use claude_cli::ClaudeCliProvider;
 pub use mock::MockProvider;
 pub use openai_compatible::OpenAICompatibleProvider;
 
+use std::str::FromStr;
+
 use crate::config::{ModelPreset, ProviderConfig};
 use crate::error::{LlmError, Result};
 use crate::provider::LlmProvider;
@@ -24,9 +26,11 @@ pub enum ProviderKind {
     LmStudio,
 }
 
-impl ProviderKind {
+impl FromStr for ProviderKind {
+    type Err = LlmError;
+
     /// Parse provider kind from string
-    pub fn from_str(s: &str) -> Result<Self> {
+    fn from_str(s: &str) -> Result<Self> {
         match s.to_lowercase().as_str() {
             "claude-cli" | "claude_cli" | "claudecli" => Ok(Self::ClaudeCli),
             "anthropic" => Ok(Self::Anthropic),
@@ -36,7 +40,9 @@ impl ProviderKind {
             _ => Err(LlmError::ConfigError(format!("Unknown provider: {}", s))),
         }
     }
+}
 
+impl ProviderKind {
     /// Get the environment variable name for this provider's API key
     pub fn env_var(&self) -> Option<&'static str> {
         match self {
@@ -95,6 +101,18 @@ pub fn get_provider(
     }
 }
 
+/// Seconds from a `Retry-After` header, if the server sent one in that form
+pub(crate) fn retry_after_secs(response: &reqwest::Response) -> Option<u64> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
 /// Get API key from config or environment variable
 fn get_api_key(
     config: Option<&ProviderConfig>,
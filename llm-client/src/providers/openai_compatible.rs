@@ -10,9 +10,13 @@ use async_trait::async_trait;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::time::Instant;
 
 use crate::error::{LlmError, Result};
-use crate::provider::{FileAttachment, LlmProvider, LlmRequest, LlmResponse, TokenUsage};
+use crate::provider::{
+    FileAttachment, FinishReason, LlmProvider, LlmRequest, LlmResponse, TokenUsage,
+};
+use crate::providers::retry_after_secs;
 
 /// Provider for OpenAI-compatible APIs
 pub struct OpenAICompatibleProvider {
@@ -135,17 +139,23 @@ struct InputAudioData {
 #[derive(Debug, Deserialize)]
 struct ChatCompletionResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    model: Option<String>,
     usage: Option<Usage>,
 }
 
 #[derive(Debug, Deserialize)]
 struct Choice {
     message: ResponseMessage,
+    #[serde(default)]
+    finish_reason: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct ResponseMessage {
-    content: String,
+    /// Null when the response was blocked by a content filter
+    #[serde(default)]
+    content: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -162,6 +172,21 @@ struct ErrorResponse {
 #[derive(Debug, Deserialize)]
 struct ApiError {
     message: String,
+    /// e.g. "context_length_exceeded"; some providers send a number here
+    #[serde(default)]
+    code: Option<serde_json::Value>,
+    #[serde(rename = "type", default)]
+    kind: Option<String>,
+}
+
+impl ApiError {
+    /// The most specific machine-readable error identifier available
+    fn kind(&self) -> Option<&str> {
+        self.code
+            .as_ref()
+            .and_then(|code| code.as_str())
+            .or(self.kind.as_deref())
+    }
 }
 
 /// Check if a MIME type is an audio type
@@ -268,6 +293,7 @@ impl LlmProvider for OpenAICompatibleProvider {
             request_builder = request_builder.header("Authorization", format!("Bearer {}", api_key));
         }
 
+        let started = Instant::now();
         let response = request_builder
             .json(&chat_request)
             .send()
@@ -279,23 +305,23 @@ impl LlmProvider for OpenAICompatibleProvider {
 
         let status = response.status();
         if !status.is_success() {
+            let retry_after = retry_after_secs(&response);
             let error_text = response.text().await.unwrap_or_default();
-            let message =
-                if let Ok(error_response) = serde_json::from_str::<ErrorResponse>(&error_text) {
-                    error_response.error.message
-                } else {
-                    error_text
-                };
-
-            // Handle 503 (server overloaded) separately for retry logic
-            if status.as_u16() == 503 {
-                return Err(LlmError::ServerOverloaded { message });
-            }
-
-            return Err(LlmError::ApiError {
+            let (kind, message) = match serde_json::from_str::<ErrorResponse>(&error_text) {
+                Ok(error_response) => (
+                    error_response.error.kind().map(str::to_string),
+                    error_response.error.message,
+                ),
+                Err(_) => (None, error_text),
+            };
+
+            return Err(LlmError::from_http_response(
+                self.name,
+                status.as_u16(),
+                kind.as_deref(),
                 message,
-                status_code: Some(status.as_u16()),
-            });
+                retry_after,
+            ));
         }
 
         let chat_response: ChatCompletionResponse =
@@ -304,11 +330,18 @@ impl LlmProvider for OpenAICompatibleProvider {
                 status_code: None,
             })?;
 
-        let content = chat_response
-            .choices
-            .first()
-            .map(|c| c.message.content.clone())
+        let choice = chat_response.choices.first();
+        let content = choice
+            .and_then(|c| c.message.content.clone())
             .unwrap_or_default();
+        let finish_reason = choice
+            .and_then(|c| c.finish_reason.as_deref())
+            .map(FinishReason::from_openai);
+        if content.is_empty() && finish_reason == Some(FinishReason::ContentFilter) {
+            return Err(LlmError::ContentFiltered {
+                message: "the response was blocked by the provider's content filter".to_string(),
+            });
+        }
 
         let usage = chat_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
@@ -317,8 +350,11 @@ impl LlmProvider for OpenAICompatibleProvider {
 
         Ok(LlmResponse {
             content,
-            model: self.model.clone(),
+            model: chat_response.model.unwrap_or_else(|| self.model.clone()),
             usage,
+            finish_reason,
+            latency: started.elapsed(),
+            served_by: None,
         })
     }
 
@@ -0,0 +1,402 @@
+//! Rate limits shared across processes
+//!
+//! Each tool builds its own provider, so limits can't be tracked in memory:
+//! gc and ask running at the same time would each get the full allowance.
+//! Instead, state lives in `~/.config/cli-programs/ratelimit/`:
+//!
+//! - `requests_per_minute` is a token bucket in `<key>.bucket`, updated under
+//!   an exclusive file lock
+//! - `max_concurrent` is a set of `<key>.slot-N` lock files; a request holds
+//!   one for as long as it runs, and the OS releases it if the process dies
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+use crate::error::{LlmError, Result};
+use crate::provider::{LlmProvider, LlmRequest, LlmResponse};
+
+/// How often to retry while every concurrency slot is taken
+const SLOT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Key for the limit shared by every provider
+const GLOBAL_KEY: &str = "global";
+
+/// Limits for one provider, or for all providers combined
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Requests started per minute, with bursts of up to this many
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<u32>,
+
+    /// Requests in flight at once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<u32>,
+}
+
+impl RateLimit {
+    pub fn is_unlimited(&self) -> bool {
+        self.requests_per_minute.is_none() && self.max_concurrent.is_none()
+    }
+}
+
+/// Token bucket state as stored on disk
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bucket {
+    tokens: f64,
+    /// Milliseconds since the Unix epoch
+    updated: u64,
+}
+
+impl Bucket {
+    fn parse(content: &str) -> Option<Self> {
+        let (tokens, updated) = content.trim().split_once(' ')?;
+        Some(Self {
+            tokens: tokens.parse().ok()?,
+            updated: updated.parse().ok()?,
+        })
+    }
+
+    /// Refill for the time elapsed since the last update, then take a token.
+    ///
+    /// Returns the updated bucket, and how long to wait if no token was available.
+    fn take(self, per_minute: u32, now: u64) -> (Self, Option<Duration>) {
+        let capacity = f64::from(per_minute);
+        let per_ms = capacity / 60_000.0;
+        let elapsed = now.saturating_sub(self.updated) as f64;
+        let tokens = (self.tokens + elapsed * per_ms).min(capacity);
+
+        if tokens >= 1.0 {
+            let bucket = Self {
+                tokens: tokens - 1.0,
+                updated: now,
+            };
+            (bucket, None)
+        } else {
+            let wait = ((1.0 - tokens) / per_ms).ceil() as u64;
+            let bucket = Self {
+                tokens,
+                updated: now,
+            };
+            (bucket, Some(Duration::from_millis(wait)))
+        }
+    }
+}
+
+/// Held while a request runs; dropping it frees the concurrency slot
+#[derive(Debug)]
+pub struct Permit {
+    _slot: Option<File>,
+}
+
+/// Enforces one [`RateLimit`] across every process using the same state directory
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    dir: PathBuf,
+    key: String,
+    limit: RateLimit,
+}
+
+impl RateLimiter {
+    pub fn new(dir: impl Into<PathBuf>, key: &str, limit: RateLimit) -> Self {
+        Self {
+            dir: dir.into(),
+            key: key.to_string(),
+            limit,
+        }
+    }
+
+    /// Directory holding the shared limiter state: ~/.config/cli-programs/ratelimit
+    pub fn state_dir() -> Result<PathBuf> {
+        let home =
+            std::env::var("HOME").map_err(|_| LlmError::ConfigError("HOME not set".into()))?;
+        Ok(PathBuf::from(home).join(".config/cli-programs/ratelimit"))
+    }
+
+    /// Wait for a concurrency slot, then for a token
+    pub async fn acquire(&self) -> Result<Permit> {
+        fs::create_dir_all(&self.dir)?;
+
+        let slot = match self.limit.max_concurrent {
+            Some(max) => Some(self.acquire_slot(max).await?),
+            None => None,
+        };
+
+        if let Some(per_minute) = self.limit.requests_per_minute {
+            while let Some(wait) = self.take_token(per_minute)? {
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        Ok(Permit { _slot: slot })
+    }
+
+    async fn acquire_slot(&self, max: u32) -> Result<File> {
+        loop {
+            for n in 0..max.max(1) {
+                let path = self.dir.join(format!("{}.slot-{}", self.key, n));
+                let file = OpenOptions::new()
+                    .create(true)
+                    .truncate(false)
+                    .write(true)
+                    .open(&path)?;
+                match file.try_lock() {
+                    Ok(()) => return Ok(file),
+                    Err(TryLockError::WouldBlock) => continue,
+                    Err(TryLockError::Error(e)) => return Err(e.into()),
+                }
+            }
+            tokio::time::sleep(SLOT_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Take a token from the bucket, or return how long until one is available
+    fn take_token(&self, per_minute: u32) -> Result<Option<Duration>> {
+        let path = self.dir.join(format!("{}.bucket", self.key));
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(&path)?;
+        file.lock()?;
+
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        let now = now_millis();
+        // A new or unreadable bucket starts full
+        let bucket = Bucket::parse(&content).unwrap_or(Bucket {
+            tokens: f64::from(per_minute),
+            updated: now,
+        });
+
+        let (bucket, wait) = bucket.take(per_minute, now);
+        write_bucket(&mut file, bucket)?;
+        Ok(wait)
+    }
+}
+
+fn write_bucket(file: &mut File, bucket: Bucket) -> Result<()> {
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    write!(file, "{} {}", bucket.tokens, bucket.updated)?;
+    Ok(())
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// A provider that waits for its rate limits before each request
+pub struct RateLimitedProvider {
+    inner: Box<dyn LlmProvider>,
+    limiters: Vec<RateLimiter>,
+}
+
+impl RateLimitedProvider {
+    pub fn new(inner: Box<dyn LlmProvider>, limiters: Vec<RateLimiter>) -> Self {
+        Self { inner, limiters }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for RateLimitedProvider {
+    async fn complete(&self, request: LlmRequest) -> Result<LlmResponse> {
+        let mut permits = Vec::with_capacity(self.limiters.len());
+        for limiter in &self.limiters {
+            permits.push(limiter.acquire().await?);
+        }
+        self.inner.complete(request).await
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn is_available(&self) -> Result<()> {
+        self.inner.is_available()
+    }
+}
+
+/// Apply the global limit and the provider's own limit from the config.
+///
+/// Returns the provider unchanged if neither is set.
+pub fn with_rate_limits(
+    config: &Config,
+    provider_name: &str,
+    provider: Box<dyn LlmProvider>,
+) -> Result<Box<dyn LlmProvider>> {
+    let provider_limit = config
+        .get_provider_config(provider_name)
+        .map(|c| c.rate_limit)
+        .unwrap_or_default();
+    if config.rate_limit.is_unlimited() && provider_limit.is_unlimited() {
+        return Ok(provider);
+    }
+
+    let dir = RateLimiter::state_dir()?;
+    Ok(Box::new(RateLimitedProvider::new(
+        provider,
+        limiters(&dir, config.rate_limit, provider_name, provider_limit),
+    )))
+}
+
+/// Limiters in acquisition order: the provider's own, then the global one
+fn limiters(
+    dir: &Path,
+    global: RateLimit,
+    provider_name: &str,
+    provider_limit: RateLimit,
+) -> Vec<RateLimiter> {
+    let mut limiters = Vec::new();
+    if !provider_limit.is_unlimited() {
+        limiters.push(RateLimiter::new(dir, provider_name, provider_limit));
+    }
+    if !global.is_unlimited() {
+        limiters.push(RateLimiter::new(dir, GLOBAL_KEY, global));
+    }
+    limiters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::MockProvider;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "llm-client-ratelimit-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let empty = Bucket {
+            tokens: 0.0,
+            updated: 0,
+        };
+
+        // 60/min is one token per second
+        let (bucket, wait) = empty.take(60, 500);
+        assert_eq!(wait, Some(Duration::from_millis(500)));
+        assert_eq!(bucket.updated, 500);
+
+        let (bucket, wait) = bucket.take(60, 1000);
+        assert_eq!(wait, None);
+        assert!(bucket.tokens.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_bucket_caps_at_capacity() {
+        let old = Bucket {
+            tokens: 1.0,
+            updated: 0,
+        };
+        let (bucket, wait) = old.take(10, 3_600_000);
+        assert_eq!(wait, None);
+        assert_eq!(bucket.tokens, 9.0);
+    }
+
+    #[test]
+    fn test_bucket_parse() {
+        let bucket = Bucket::parse("2.5 1700000000000\n").unwrap();
+        assert_eq!(bucket.tokens, 2.5);
+        assert_eq!(bucket.updated, 1_700_000_000_000);
+        assert_eq!(Bucket::parse("garbage"), None);
+    }
+
+    #[test]
+    fn test_limiters_order() {
+        let dir = Path::new("/tmp");
+        let limit = RateLimit {
+            max_concurrent: Some(1),
+            ..Default::default()
+        };
+
+        let keys: Vec<String> = limiters(dir, limit, "anthropic", limit)
+            .into_iter()
+            .map(|l| l.key)
+            .collect();
+        assert_eq!(keys, vec!["anthropic", "global"]);
+        assert!(limiters(dir, RateLimit::default(), "anthropic", RateLimit::default()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tokens_shared_between_limiters() {
+        let dir = temp_dir("tokens");
+        let limit = RateLimit {
+            requests_per_minute: Some(2),
+            ..Default::default()
+        };
+        // Two limiters on the same files act like two processes
+        let a = RateLimiter::new(&dir, "test", limit);
+        let b = RateLimiter::new(&dir, "test", limit);
+        fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(a.take_token(2).unwrap(), None);
+        assert_eq!(b.take_token(2).unwrap(), None);
+        assert!(a.take_token(2).unwrap().is_some());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_slots() {
+        let dir = temp_dir("slots");
+        let limit = RateLimit {
+            max_concurrent: Some(1),
+            ..Default::default()
+        };
+        let limiter = RateLimiter::new(&dir, "test", limit);
+
+        let permit = limiter.acquire().await.unwrap();
+        let blocked = tokio::time::timeout(Duration::from_millis(250), limiter.acquire()).await;
+        assert!(blocked.is_err(), "second request should wait for the slot");
+
+        drop(permit);
+        let reacquired = tokio::time::timeout(Duration::from_millis(500), limiter.acquire()).await;
+        assert!(reacquired.is_ok());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_provider_forwards() {
+        let dir = temp_dir("provider");
+        let limit = RateLimit {
+            max_concurrent: Some(2),
+            requests_per_minute: Some(10),
+        };
+        let provider = RateLimitedProvider::new(
+            Box::new(MockProvider::always_succeeds("ok")),
+            vec![RateLimiter::new(&dir, "mock", limit)],
+        );
+
+        let response = provider
+            .complete(LlmRequest {
+                prompt: "hi".to_string(),
+                system_prompt: None,
+                max_tokens: None,
+                temperature: None,
+                files: vec![],
+                json_schema: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.content, "ok");
+        assert_eq!(provider.name(), "mock");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
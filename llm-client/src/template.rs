@@ -0,0 +1,341 @@
+//! Prompt templates with named variables
+//!
+//! Tools register their built-in prompts as defaults, and users can override
+//! any of them without recompiling:
+//!
+//! 1. Built-in defaults registered with [`PromptTemplates::with_default`]
+//! 2. Template files in `~/.config/cli-programs/prompts/<name>.toml`
+//! 3. `[templates.<name>]` tables in `llm.toml`
+//!
+//! Later sources win. Placeholders are written `{{name}}`; rendering fails if a
+//! placeholder has no value, so a typo in an edited template is caught
+//! instead of being sent to the model.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::error::{LlmError, Result};
+use crate::provider::{LlmProvider, LlmRequest, LlmResponse};
+
+/// A prompt with `{{name}}` placeholders and an optional system prompt
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    /// System prompt, which may also contain placeholders
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+
+    /// User prompt
+    #[serde(default)]
+    pub prompt: String,
+
+    /// Read the prompt from this file instead, relative to the prompts
+    /// directory unless absolute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+}
+
+impl PromptTemplate {
+    pub fn new(system: Option<&str>, prompt: &str) -> Self {
+        Self {
+            system: system.map(String::from),
+            prompt: prompt.to_string(),
+            file: None,
+        }
+    }
+
+    /// Render the prompt and system prompt into a request
+    pub fn render(&self, vars: &[(&str, &str)]) -> Result<LlmRequest> {
+        Ok(LlmRequest {
+            prompt: render(&self.prompt, vars)?,
+            system_prompt: self
+                .system
+                .as_deref()
+                .map(|system| render(system, vars))
+                .transpose()?,
+            max_tokens: None,
+            temperature: None,
+            files: vec![],
+            json_schema: None,
+        })
+    }
+
+    /// Replace `prompt` with the contents of `file`, if set
+    fn resolve_file(mut self, dir: &Path) -> Result<Self> {
+        if let Some(file) = self.file.take() {
+            let path = dir.join(file);
+            self.prompt = std::fs::read_to_string(&path).map_err(|e| {
+                LlmError::TemplateError(format!(
+                    "failed to read prompt file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+        }
+        Ok(self)
+    }
+}
+
+/// Substitute `{{name}}` placeholders in `text`
+pub fn render(text: &str, vars: &[(&str, &str)]) -> Result<String> {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or_else(|| {
+            LlmError::TemplateError(format!("unclosed placeholder: {{{{{}", truncate(after)))
+        })?;
+
+        let name = after[..end].trim();
+        let value = vars
+            .iter()
+            .find(|(var, _)| *var == name)
+            .map(|(_, value)| *value)
+            .ok_or_else(|| LlmError::TemplateError(format!("no value for {{{{{}}}}}", name)))?;
+        output.push_str(value);
+        rest = &after[end + 2..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn truncate(text: &str) -> &str {
+    let line = text.lines().next().unwrap_or_default();
+    match line.char_indices().nth(20) {
+        Some((i, _)) => &line[..i],
+        None => line,
+    }
+}
+
+/// Named prompt templates, layered from built-in defaults and user overrides
+#[derive(Debug, Clone, Default)]
+pub struct PromptTemplates {
+    templates: HashMap<String, PromptTemplate>,
+}
+
+impl PromptTemplates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a built-in template
+    pub fn with_default(mut self, name: &str, template: PromptTemplate) -> Self {
+        self.templates.insert(name.to_string(), template);
+        self
+    }
+
+    /// Directory holding template files: `~/.config/cli-programs/prompts`
+    pub fn templates_dir() -> Result<PathBuf> {
+        let config_path = Config::config_path()?;
+        Ok(config_path
+            .parent()
+            .map(|dir| dir.join("prompts"))
+            .unwrap_or_else(|| PathBuf::from("prompts")))
+    }
+
+    /// Apply user overrides from the prompts directory and `llm.toml`
+    pub fn load_overrides(self, config: &Config) -> Result<Self> {
+        self.load_overrides_from(config, &Self::templates_dir()?)
+    }
+
+    /// Apply user overrides from `dir` and the `[templates]` in `config`
+    pub fn load_overrides_from(mut self, config: &Config, dir: &Path) -> Result<Self> {
+        if dir.is_dir() {
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.extension().is_none_or(|ext| ext != "toml") {
+                    continue;
+                }
+                let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+                let template: PromptTemplate = toml::from_str(&std::fs::read_to_string(&path)?)
+                    .map_err(|e| {
+                        LlmError::TemplateError(format!("invalid {}: {}", path.display(), e))
+                    })?;
+                self.templates
+                    .insert(name.to_string(), template.resolve_file(dir)?);
+            }
+        }
+
+        for (name, template) in &config.templates {
+            self.templates
+                .insert(name.clone(), template.clone().resolve_file(dir)?);
+        }
+
+        Ok(self)
+    }
+
+    pub fn get(&self, name: &str) -> Result<&PromptTemplate> {
+        self.templates
+            .get(name)
+            .ok_or_else(|| LlmError::TemplateError(format!("unknown template: {}", name)))
+    }
+
+    /// Render a named template into a request
+    pub fn render(&self, name: &str, vars: &[(&str, &str)]) -> Result<LlmRequest> {
+        self.get(name)?.render(vars).map_err(|e| match e {
+            LlmError::TemplateError(message) => {
+                LlmError::TemplateError(format!("{} (template {})", message, name))
+            }
+            other => other,
+        })
+    }
+
+    /// Names of all templates, sorted
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.templates.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+}
+
+/// Wraps a provider with a set of templates so requests can be made by
+/// template name
+pub struct TemplatedClient<P> {
+    provider: P,
+    templates: PromptTemplates,
+}
+
+impl<P: LlmProvider> TemplatedClient<P> {
+    pub fn new(provider: P, templates: PromptTemplates) -> Self {
+        Self {
+            provider,
+            templates,
+        }
+    }
+
+    /// Render the named template with `vars` and send it
+    pub async fn render_and_complete(
+        &self,
+        name: &str,
+        vars: &[(&str, &str)],
+    ) -> Result<LlmResponse> {
+        let request = self.templates.render(name, vars)?;
+        self.provider.complete(request).await
+    }
+
+    pub fn templates(&self) -> &PromptTemplates {
+        &self.templates
+    }
+
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+}
+
+#[async_trait]
+impl<P: LlmProvider> LlmProvider for TemplatedClient<P> {
+    async fn complete(&self, request: LlmRequest) -> Result<LlmResponse> {
+        self.provider.complete(request).await
+    }
+
+    fn name(&self) -> &'static str {
+        self.provider.name()
+    }
+
+    fn is_available(&self) -> Result<()> {
+        self.provider.is_available()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::MockProvider;
+
+    #[test]
+    fn test_render() {
+        let text = "Branch {{branch}}:\n{{ diff }}\n{{branch}}";
+        let rendered = render(text, &[("branch", "main"), ("diff", "+ x")]).unwrap();
+        assert_eq!(rendered, "Branch main:\n+ x\nmain");
+
+        // Values are inserted as-is, not rendered again
+        assert_eq!(render("{{a}}", &[("a", "{{b}}")]).unwrap(), "{{b}}");
+        assert_eq!(render("no placeholders", &[]).unwrap(), "no placeholders");
+    }
+
+    #[test]
+    fn test_render_errors() {
+        let err = render("Hello {{name}}", &[]).unwrap_err();
+        assert!(err.to_string().contains("no value for {{name}}"));
+
+        let err = render("Hello {{name", &[("name", "x")]).unwrap_err();
+        assert!(err.to_string().contains("unclosed placeholder"));
+    }
+
+    #[test]
+    fn test_render_template() {
+        let template = PromptTemplate::new(Some("You write {{kind}}."), "Describe:\n{{diff}}");
+        let request = template
+            .render(&[("kind", "commit messages"), ("diff", "+ x")])
+            .unwrap();
+        assert_eq!(request.prompt, "Describe:\n+ x");
+        assert_eq!(
+            request.system_prompt.as_deref(),
+            Some("You write commit messages.")
+        );
+    }
+
+    #[test]
+    fn test_overrides() {
+        let dir = std::env::temp_dir().join(format!("llm-client-templates-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("summary.toml"),
+            "system = \"Be brief.\"\nprompt = \"Summarize {{text}}\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("review.txt"), "Review {{diff}}").unwrap();
+        std::fs::write(dir.join("notes.md"), "not a template").unwrap();
+
+        let config: Config = toml::from_str(
+            r#"
+[templates.review]
+file = "review.txt"
+"#,
+        )
+        .unwrap();
+
+        let templates = PromptTemplates::new()
+            .with_default("summary", PromptTemplate::new(None, "built-in"))
+            .with_default("commit", PromptTemplate::new(None, "Commit {{diff}}"))
+            .load_overrides_from(&config, &dir)
+            .unwrap();
+
+        assert_eq!(templates.names(), vec!["commit", "review", "summary"]);
+        assert_eq!(
+            templates.get("summary").unwrap().prompt,
+            "Summarize {{text}}"
+        );
+        assert_eq!(templates.get("review").unwrap().prompt, "Review {{diff}}");
+        assert_eq!(templates.get("commit").unwrap().prompt, "Commit {{diff}}");
+
+        let err = templates.render("review", &[]).unwrap_err();
+        assert!(err.to_string().contains("(template review)"));
+        assert!(templates.get("missing").is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_render_and_complete() {
+        let templates = PromptTemplates::new()
+            .with_default("greet", PromptTemplate::new(None, "Hello {{name}}"));
+        let client = TemplatedClient::new(MockProvider::always_succeeds("hi there"), templates);
+
+        let response = client
+            .render_and_complete("greet", &[("name", "Ada")])
+            .await
+            .unwrap();
+        assert_eq!(response.content, "hi there");
+
+        assert!(client.render_and_complete("greet", &[]).await.is_err());
+    }
+}
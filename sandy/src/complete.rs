@@ -0,0 +1,103 @@
+//! Dynamic shell completion of sandbox and template names
+//!
+//! Shells call back into sandy (`COMPLETE=<shell> sandy ...`) to complete
+//! arguments, so candidates come from the current state file and the
+//! templates directory.
+
+use clap_complete::CompletionCandidate;
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::state::State;
+
+/// Candidates for a sandbox name argument, read from the state file.
+///
+/// Errors are swallowed: a missing or unreadable state file just means there
+/// is nothing to complete.
+pub fn sandbox_candidates() -> Vec<CompletionCandidate> {
+    State::load()
+        .map(|state| candidates(&state))
+        .unwrap_or_default()
+}
+
+/// Candidates for a `--template` argument: the named templates that have a
+/// Dockerfile
+pub fn template_candidates() -> Vec<CompletionCandidate> {
+    Config::template_names()
+        .unwrap_or_default()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// One candidate per sandbox: its display name (`repo` or `repo:name`), or
+/// its state key when several repositories share a name, with the path as
+/// help text
+fn candidates(state: &State) -> Vec<CompletionCandidate> {
+    let mut name_counts: HashMap<String, usize> = HashMap::new();
+    for info in state.sandboxes.values() {
+        *name_counts.entry(info.display_name()).or_default() += 1;
+    }
+
+    let mut sandboxes: Vec<_> = state.sandboxes.iter().collect();
+    sandboxes.sort_by(|a, b| a.0.cmp(b.0));
+
+    sandboxes
+        .into_iter()
+        .map(|(key, info)| {
+            let name = info.display_name();
+            let path = info.path.display().to_string();
+            if name_counts[&name] > 1 {
+                CompletionCandidate::new(key)
+            } else {
+                CompletionCandidate::new(name).help(Some(path.into()))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn values(state: &State) -> Vec<String> {
+        candidates(state)
+            .iter()
+            .map(|c| c.get_value().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_candidates_use_repo_names() {
+        let mut state = State::default();
+        state.add_sandbox(PathBuf::from("/work/api"), None, "claude");
+        state.add_sandbox(PathBuf::from("/work/web"), None, "claude");
+
+        assert_eq!(values(&state), vec!["api", "web"]);
+    }
+
+    #[test]
+    fn test_candidates_use_paths_for_shared_names() {
+        let mut state = State::default();
+        state.add_sandbox(PathBuf::from("/work/api"), None, "claude");
+        state.add_sandbox(PathBuf::from("/forks/api"), None, "claude");
+        state.add_sandbox(PathBuf::from("/work/web"), None, "claude");
+
+        assert_eq!(values(&state), vec!["/forks/api", "/work/api", "web"]);
+    }
+
+    #[test]
+    fn test_candidates_include_named_sandboxes() {
+        let mut state = State::default();
+        state.add_sandbox(PathBuf::from("/work/api"), None, "claude");
+        state.add_sandbox(PathBuf::from("/work/api"), Some("auth"), "claude");
+
+        assert_eq!(values(&state), vec!["api", "api:auth"]);
+    }
+
+    #[test]
+    fn test_candidates_empty_state() {
+        assert!(candidates(&State::default()).is_empty());
+    }
+}
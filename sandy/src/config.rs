@@ -2,7 +2,7 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Mount {
@@ -12,6 +12,19 @@ pub struct Mount {
     pub readonly: bool,
 }
 
+/// A BuildKit secret exposed to `RUN --mount=type=secret,id=<id>` during template builds.
+/// Exactly one of `env` or `src` must be set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildSecret {
+    pub id: String,
+    /// Host environment variable holding the secret
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<String>,
+    /// Host file holding the secret (tilde and env vars are expanded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub src: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     /// Custom Docker template image name
@@ -33,6 +46,39 @@ pub struct Config {
     /// Additional volume mounts
     #[serde(default)]
     pub mounts: Vec<Mount>,
+
+    /// Build arguments passed to `docker build` when building the template
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub build_args: HashMap<String, String>,
+
+    /// BuildKit secrets available while building the template
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub build_secrets: Vec<BuildSecret>,
+
+    /// Repositories mounted alongside a repository when its sandbox is created,
+    /// keyed by that repository's path
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub linked_repos: HashMap<String, Vec<String>>,
+
+    /// Named Dockerfile template for new sandboxes of a repository, keyed by
+    /// that repository's path
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub repo_templates: HashMap<String, String>,
+
+    /// Directory for the git worktrees of `sandy new --branch` sandboxes
+    /// (defaults to `sandy/worktrees` in the config directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktrees_dir: Option<String>,
+
+    /// Directory `sandy clone` clones repositories into (defaults to
+    /// `sandy/projects` in the config directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub projects_dir: Option<String>,
+
+    /// Stop running sandboxes whose last agent session ended this many minutes
+    /// ago, checked by `sandy list` and `sandy resume`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_stop_minutes: Option<u64>,
 }
 
 fn default_binary_dirs() -> Vec<String> {
@@ -67,6 +113,13 @@ impl Default for Config {
                     readonly: false,
                 },
             ],
+            build_args: HashMap::new(),
+            build_secrets: Vec::new(),
+            linked_repos: HashMap::new(),
+            repo_templates: HashMap::new(),
+            worktrees_dir: None,
+            projects_dir: None,
+            auto_stop_minutes: None,
         }
     }
 }
@@ -137,6 +190,99 @@ impl Config {
             shellexpand::full(path).with_context(|| format!("Failed to expand path: {}", path))?;
         Ok(PathBuf::from(expanded.as_ref()))
     }
+
+    /// Directory holding the worktrees of branch sandboxes, expanded
+    pub fn worktrees_dir(&self) -> Result<PathBuf> {
+        match &self.worktrees_dir {
+            Some(dir) => Self::expand_path(dir),
+            None => Ok(Self::config_dir()?.join("sandy").join("worktrees")),
+        }
+    }
+
+    /// Directory `sandy clone` clones into, expanded
+    pub fn projects_dir(&self) -> Result<PathBuf> {
+        match &self.projects_dir {
+            Some(dir) => Self::expand_path(dir),
+            None => Ok(Self::config_dir()?.join("sandy").join("projects")),
+        }
+    }
+
+    /// Directory holding the named Dockerfile templates, one subdirectory each
+    pub fn templates_dir() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join("sandy").join("templates"))
+    }
+
+    /// Names of the templates in [`Config::templates_dir`] that have a Dockerfile
+    pub fn template_names() -> Result<Vec<String>> {
+        let dir = Self::templates_dir()?;
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.join("Dockerfile").is_file()
+                && let Some(name) = path.file_name()
+            {
+                names.push(name.to_string_lossy().into_owned());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Repositories configured to be mounted alongside `workspace`, expanded
+    pub fn linked_repos_for(&self, workspace: &Path) -> Result<Vec<PathBuf>> {
+        for (repo, linked) in &self.linked_repos {
+            if is_repo(repo, workspace)? {
+                return linked.iter().map(|path| Self::expand_path(path)).collect();
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    /// Named template configured for new sandboxes of `workspace`
+    pub fn template_for(&self, workspace: &Path) -> Result<Option<String>> {
+        for (repo, template) in &self.repo_templates {
+            if is_repo(repo, workspace)? {
+                if let Err(e) = check_template_name(template) {
+                    anyhow::bail!("Invalid repo_templates entry for {}: {}", repo, e);
+                }
+                return Ok(Some(template.clone()));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Check a template name, which becomes a directory name and part of the
+/// image name (which Docker requires to be lowercase)
+pub fn check_template_name(name: &str) -> std::result::Result<(), String> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        && chars.all(|c| {
+            c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_' || c == '.'
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "'{}' must start with a lowercase letter or digit and may only contain \
+             lowercase letters, digits, '-', '_', and '.'",
+            name
+        ))
+    }
+}
+
+/// Whether a repository path from the config refers to `workspace`
+fn is_repo(repo: &str, workspace: &Path) -> Result<bool> {
+    let repo = Config::expand_path(repo)?;
+    Ok(repo.canonicalize().unwrap_or(repo) == workspace)
 }
 
 #[cfg(test)]
@@ -200,9 +346,11 @@ mod tests {
 
     #[test]
     fn test_config_serialization_roundtrip() {
-        let mut config = Config::default();
-        config.template_image = Some("my-template".to_string());
-        config.binary_dirs = vec!["/usr/bin".to_string(), "~/.cargo/bin".to_string()];
+        let mut config = Config {
+            template_image: Some("my-template".to_string()),
+            binary_dirs: vec!["/usr/bin".to_string(), "~/.cargo/bin".to_string()],
+            ..Default::default()
+        };
         config.env.insert("MY_VAR".to_string(), "value".to_string());
 
         let serialized = toml::to_string_pretty(&config).unwrap();
@@ -276,6 +424,137 @@ mod tests {
         assert!(!config.mounts[0].readonly);
     }
 
+    #[test]
+    fn test_config_with_build_args_and_secrets() {
+        let toml_str = r#"
+            [build_args]
+            NODE_VERSION = "22"
+
+            [[build_secrets]]
+            id = "npm_token"
+            env = "NPM_TOKEN"
+
+            [[build_secrets]]
+            id = "pip_conf"
+            src = "~/.config/pip/pip.conf"
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config.build_args.get("NODE_VERSION"),
+            Some(&"22".to_string())
+        );
+        assert_eq!(config.build_secrets.len(), 2);
+        assert_eq!(config.build_secrets[0].env.as_deref(), Some("NPM_TOKEN"));
+        assert!(config.build_secrets[0].src.is_none());
+        assert_eq!(
+            config.build_secrets[1].src.as_deref(),
+            Some("~/.config/pip/pip.conf")
+        );
+    }
+
+    #[test]
+    fn test_build_settings_omitted_when_empty() {
+        let serialized = toml::to_string_pretty(&Config::default()).unwrap();
+        assert!(!serialized.contains("build_args"));
+        assert!(!serialized.contains("build_secrets"));
+    }
+
+    #[test]
+    fn test_linked_repos_for() {
+        let toml_str = r#"
+            [linked_repos]
+            "/code/app" = ["/code/shared", "$HOME/lib"]
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let home = env::var("HOME").unwrap();
+        assert_eq!(
+            config.linked_repos_for(Path::new("/code/app")).unwrap(),
+            vec![
+                PathBuf::from("/code/shared"),
+                PathBuf::from(home).join("lib")
+            ]
+        );
+        assert!(
+            config
+                .linked_repos_for(Path::new("/code/other"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_linked_repos_omitted_when_empty() {
+        let serialized = toml::to_string_pretty(&Config::default()).unwrap();
+        assert!(!serialized.contains("linked_repos"));
+    }
+
+    #[test]
+    fn test_template_for() {
+        let toml_str = r#"
+            [repo_templates]
+            "/code/app" = "rust"
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config.template_for(Path::new("/code/app")).unwrap(),
+            Some("rust".to_string())
+        );
+        assert_eq!(config.template_for(Path::new("/code/other")).unwrap(), None);
+
+        let serialized = toml::to_string_pretty(&Config::default()).unwrap();
+        assert!(!serialized.contains("repo_templates"));
+    }
+
+    #[test]
+    fn test_check_template_name() {
+        assert!(check_template_name("rust").is_ok());
+        assert!(check_template_name("py3.12_slim-dev").is_ok());
+        assert!(check_template_name("1x").is_ok());
+        for name in ["", ".", "..", "-x", "_x", ".hidden", "Rust", "a/b"] {
+            assert!(check_template_name(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_template_for_rejects_invalid_names() {
+        let config: Config = toml::from_str(
+            r#"
+            [repo_templates]
+            "/code/app" = ".."
+        "#,
+        )
+        .unwrap();
+        let err = config.template_for(Path::new("/code/app")).unwrap_err();
+        assert!(err.to_string().contains("repo_templates"));
+    }
+
+    #[test]
+    fn test_worktrees_dir() {
+        let config: Config = toml::from_str(r#"worktrees_dir = "/tmp/sandboxes""#).unwrap();
+        assert_eq!(
+            config.worktrees_dir().unwrap(),
+            PathBuf::from("/tmp/sandboxes")
+        );
+
+        let default = Config::default().worktrees_dir().unwrap();
+        assert!(default.ends_with("sandy/worktrees"));
+    }
+
+    #[test]
+    fn test_projects_dir() {
+        let config: Config = toml::from_str(r#"projects_dir = "/tmp/projects""#).unwrap();
+        assert_eq!(
+            config.projects_dir().unwrap(),
+            PathBuf::from("/tmp/projects")
+        );
+
+        let default = Config::default().projects_dir().unwrap();
+        assert!(default.ends_with("sandy/projects"));
+    }
+
     #[test]
     fn test_config_with_env_vars() {
         let toml_str = r#"
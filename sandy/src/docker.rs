@@ -2,15 +2,28 @@ use anyhow::{Context, Result, bail};
 use chrono::Local;
 use sha2::{Digest, Sha256};
 use std::fs;
+use std::io::IsTerminal;
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::time::Duration;
 
-use crate::config::Config;
+use crate::config::{BuildSecret, Config};
+use crate::run::{RunOutcome, exit_code, headless_tool_args, wait_with_timeout};
 use crate::state::{
-    load_default_template_hash, load_template_digest, load_template_hash,
-    save_default_template_hash, save_template_digest, save_template_hash,
+    SandboxInfo, State, TemplateBuild, load_default_template_hash, load_template_digest,
+    load_template_hash, sandbox_key, save_default_template_hash, save_template_digest,
+    save_template_hash,
 };
+use crate::worktree::get_git_common_dir;
+
+/// Label on every template image sandy builds, set to the template name
+/// (`default` for the default template), so `sandy prune` can find old builds
+pub const TEMPLATE_LABEL: &str = "sandy.template";
+
+/// Label on every sandbox container sandy creates, so `sandy prune` only
+/// considers containers that are sandy's
+pub const SANDBOX_LABEL: &str = "sandy.sandbox";
 
 /// Status of a sandbox container
 #[derive(Debug, Clone, PartialEq)]
@@ -20,15 +33,19 @@ pub enum SandboxStatus {
     NotFound,
 }
 
-/// Get the sandbox container name for a workspace path
+/// Get the sandbox container name for a workspace path and optional sandbox name
 ///
-/// Uses format: `sandy-{dirname}-{short_hash}` for readability while maintaining uniqueness.
+/// Uses format: `sandy-{dirname}-{short_hash}` for readability while maintaining uniqueness,
+/// or `sandy-{dirname}-{name}-{short_hash}` for a named sandbox.
 /// The dirname is sanitized to meet Docker container naming requirements.
-fn get_container_name(workspace: &Path) -> String {
-    let dirname = workspace
+pub fn get_container_name(workspace: &Path, name: Option<&str>) -> String {
+    let mut dirname = workspace
         .file_name()
         .map(|n| n.to_string_lossy().into_owned())
         .unwrap_or_else(|| "workspace".to_string());
+    if let Some(name) = name {
+        dirname = format!("{}-{}", dirname, name);
+    }
 
     // Sanitize for Docker container name requirements: [a-zA-Z0-9][a-zA-Z0-9_.-]*
     let sanitized: String = dirname
@@ -44,7 +61,7 @@ fn get_container_name(workspace: &Path) -> String {
 
     // Add short hash for uniqueness (handles same-name directories in different paths)
     let mut hasher = Sha256::new();
-    hasher.update(workspace.to_string_lossy().as_bytes());
+    hasher.update(sandbox_key(workspace, name).as_bytes());
     let hash = hex::encode(hasher.finalize());
 
     format!("sandy-{}-{}", sanitized.to_lowercase(), &hash[..6])
@@ -124,11 +141,19 @@ pub fn hash_content(content: &str) -> Result<String> {
     Ok(hex::encode(hasher.finalize()))
 }
 
-/// Check if template needs to be rebuilt
-pub fn template_needs_rebuild(dockerfile_path: &Path) -> Result<bool> {
+/// Check if template needs to be rebuilt, for the default template or the
+/// named one
+pub fn template_needs_rebuild(dockerfile_path: &Path, template: Option<&str>) -> Result<bool> {
     let current_hash = hash_dockerfile(dockerfile_path)?;
 
-    match load_template_hash()? {
+    let stored_hash = match template {
+        Some(name) => State::load()?
+            .templates
+            .get(name)
+            .map(|build| build.dockerfile_hash.clone()),
+        None => load_template_hash()?,
+    };
+    match stored_hash {
         Some(stored_hash) => Ok(current_hash != stored_hash),
         None => Ok(true),
     }
@@ -343,24 +368,81 @@ pub fn prepare_template_assets(dockerfile_dir: &Path, config: &Config) -> Result
     Ok(())
 }
 
-/// Build the custom template image
-pub fn build_template(dockerfile_path: &Path, image_name: &str, config: &Config) -> Result<()> {
-    build_template_impl(dockerfile_path, image_name, config, false)
+/// Format a BuildKit `--secret` value for a configured secret
+fn secret_spec(secret: &BuildSecret) -> Result<String> {
+    match (&secret.env, &secret.src) {
+        (Some(env), None) => Ok(format!("id={},env={}", secret.id, env)),
+        (None, Some(src)) => {
+            let path = Config::expand_path(src)?;
+            if !path.exists() {
+                bail!(
+                    "Build secret '{}' file not found: {}",
+                    secret.id,
+                    path.display()
+                );
+            }
+            Ok(format!("id={},src={}", secret.id, path.display()))
+        }
+        _ => bail!(
+            "Build secret '{}' must set exactly one of 'env' or 'src'",
+            secret.id
+        ),
+    }
+}
+
+/// `docker build` arguments for configured build args and secrets
+fn build_arg_flags(config: &Config) -> Result<Vec<String>> {
+    let mut flags = Vec::new();
+
+    let mut build_args: Vec<_> = config.build_args.iter().collect();
+    build_args.sort();
+    for (key, value) in build_args {
+        flags.push("--build-arg".to_string());
+        flags.push(format!("{}={}", key, Config::expand_env(value)?));
+    }
+
+    for secret in &config.build_secrets {
+        if let Some(env) = &secret.env
+            && std::env::var_os(env).is_none()
+        {
+            bail!(
+                "Build secret '{}' reads ${}, which is not set",
+                secret.id,
+                env
+            );
+        }
+        flags.push("--secret".to_string());
+        flags.push(secret_spec(secret)?);
+    }
+
+    Ok(flags)
 }
 
-/// Build the custom template image, optionally ignoring Docker's build cache
+/// Build the custom template image from the default template or the named one
+pub fn build_template(
+    dockerfile_path: &Path,
+    image_name: &str,
+    config: &Config,
+    template: Option<&str>,
+) -> Result<()> {
+    build_template_impl(dockerfile_path, image_name, config, template, false)
+}
+
+/// Build the custom template image, ignoring Docker's build cache
 pub fn build_template_no_cache(
     dockerfile_path: &Path,
     image_name: &str,
     config: &Config,
+    template: Option<&str>,
 ) -> Result<()> {
-    build_template_impl(dockerfile_path, image_name, config, true)
+    build_template_impl(dockerfile_path, image_name, config, template, true)
 }
 
 fn build_template_impl(
     dockerfile_path: &Path,
     image_name: &str,
     config: &Config,
+    template: Option<&str>,
     no_cache: bool,
 ) -> Result<()> {
     let dockerfile_dir = dockerfile_path.parent().unwrap_or(Path::new("."));
@@ -379,11 +461,21 @@ fn build_template_impl(
 
     let mut cmd = Command::new("docker");
     cmd.args(["build", "-t", image_name]);
+    cmd.args([
+        "--label",
+        &format!("{}={}", TEMPLATE_LABEL, template.unwrap_or("default")),
+    ]);
 
     if no_cache {
         cmd.args(["--no-cache", "--pull"]);
     }
 
+    // Secrets are only mounted into RUN steps, never written to image layers
+    cmd.args(build_arg_flags(config)?);
+    if !config.build_secrets.is_empty() {
+        cmd.env("DOCKER_BUILDKIT", "1");
+    }
+
     cmd.args([
         "-f",
         &dockerfile_path.to_string_lossy(),
@@ -400,13 +492,26 @@ fn build_template_impl(
         bail!("Failed to build template image");
     }
 
-    // Save the Dockerfile hash after successful build
+    // Save the Dockerfile hash and the image digest (for use with docker
+    // sandbox) after successful build
     let hash = hash_dockerfile(dockerfile_path)?;
-    save_template_hash(&hash)?;
-
-    // Get and save the image digest for use with docker sandbox
     let digest = get_image_digest(image_name)?;
-    save_template_digest(&digest)?;
+    match template {
+        Some(name) => State::update(|state| {
+            state.templates.insert(
+                name.to_string(),
+                TemplateBuild {
+                    dockerfile_hash: hash,
+                    image_digest: digest.clone(),
+                },
+            );
+            Ok(())
+        })?,
+        None => {
+            save_template_hash(&hash)?;
+            save_template_digest(&digest)?;
+        }
+    }
 
     println!("Template image built successfully: {}", image_name);
     println!("Image digest: {}", digest);
@@ -414,8 +519,8 @@ fn build_template_impl(
 }
 
 /// Get the status of a sandbox
-pub fn sandbox_status(workspace: &Path) -> Result<SandboxStatus> {
-    let container_name = get_container_name(workspace);
+pub fn sandbox_status(sandbox: &SandboxInfo) -> Result<SandboxStatus> {
+    let container_name = sandbox.container_name();
 
     let output = Command::new("docker")
         .args([
@@ -451,11 +556,99 @@ fn get_tool_command(tool: &str) -> Vec<&str> {
     }
 }
 
-/// Start a new sandbox with the given configuration and CLI tool
-pub fn start_sandbox(workspace: &Path, config: &Config, tool: &str) -> Result<()> {
+/// Get the host directory that serves as the writable workspace of a read-only sandbox
+pub fn scratch_dir(sandbox: &SandboxInfo) -> Result<PathBuf> {
+    Ok(Config::config_dir()?
+        .join("sandy")
+        .join("scratch")
+        .join(sandbox.container_name()))
+}
+
+/// Extra CLI tool arguments for a read-only sandbox, so the agent can find the
+/// repository even though it starts in the scratch directory
+fn read_only_tool_args(tool: &str, workspace: &Path) -> Vec<String> {
+    let repo = workspace.display().to_string();
+    match tool {
+        "claude" => vec![
+            "--add-dir".to_string(),
+            repo.clone(),
+            "--append-system-prompt".to_string(),
+            format!(
+                "The repository is mounted read-only at {}. \
+                 Write any notes, reports, or patches to the current directory instead.",
+                repo
+            ),
+        ],
+        _ => vec![],
+    }
+}
+
+/// Extra CLI tool arguments giving the agent access to the other repositories
+/// of a multi-repository sandbox
+fn extra_repo_tool_args(tool: &str, repos: &[PathBuf]) -> Vec<String> {
+    match tool {
+        "claude" => repos
+            .iter()
+            .flat_map(|repo| ["--add-dir".to_string(), repo.display().to_string()])
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// CLI tool arguments that depend on how the sandbox's repositories are mounted
+fn sandbox_tool_args(sandbox: &SandboxInfo, tool: &str) -> Vec<String> {
+    let mut args = extra_repo_tool_args(tool, &sandbox.extra_repos);
+    if sandbox.read_only {
+        args.extend(read_only_tool_args(tool, &sandbox.path));
+    }
+    args
+}
+
+/// Build the `docker sandbox run` command for a sandbox, up to the CLI tool.
+///
+/// For a read-only sandbox, the repository is bind-mounted read-only at its usual
+/// path and the sandbox workspace is a per-sandbox scratch directory on the host.
+/// Extra repositories are mounted at their host paths, read-only along with the
+/// main one. A branch sandbox works in its worktree, with the repository's git
+/// directory mounted too so git inside the container can follow the worktree's
+/// `.git` file. Returns the command and the working directory inside the sandbox.
+fn sandbox_command(sandbox: &SandboxInfo, config: &Config) -> Result<(Command, PathBuf)> {
+    let workspace = sandbox.workspace();
     let mut cmd = Command::new("docker");
     cmd.args(["sandbox", "run"]);
 
+    if let Some(worktree) = &sandbox.worktree {
+        if !worktree.is_dir() {
+            bail!(
+                "Worktree for '{}' is missing: {}. Remove the sandbox with 'sandy remove'.",
+                sandbox.display_name(),
+                worktree.display()
+            );
+        }
+        let git_dir = get_git_common_dir(worktree)?.display().to_string();
+        cmd.args(["-v", &format!("{}:{}", git_dir, git_dir)]);
+    }
+
+    let workdir = if sandbox.read_only {
+        let scratch = scratch_dir(sandbox)?;
+        fs::create_dir_all(&scratch).with_context(|| {
+            format!("Failed to create scratch directory: {}", scratch.display())
+        })?;
+        let repo = workspace.display().to_string();
+        cmd.args(["-v", &format!("{}:{}:ro", repo, repo)]);
+        cmd.args(["-e", &format!("SANDY_READ_ONLY_REPO={}", repo)]);
+        scratch
+    } else {
+        workspace.to_path_buf()
+    };
+
+    // Mount the other repositories side by side with the workspace
+    let flag = if sandbox.read_only { ":ro" } else { "" };
+    for repo in &sandbox.extra_repos {
+        let repo = repo.display().to_string();
+        cmd.args(["-v", &format!("{}:{}{}", repo, repo, flag)]);
+    }
+
     // Mount configured volumes
     for mount in &config.mounts {
         let source = Config::expand_path(&mount.source)?;
@@ -479,7 +672,19 @@ pub fn start_sandbox(workspace: &Path, config: &Config, tool: &str) -> Result<()
 
     // Use the stored image digest for the template (bypasses Docker Sandbox's cache)
     // Fall back to template_image name if no digest is stored (first run before build)
-    if let Some(digest) = load_template_digest()? {
+    if let Some(name) = &sandbox.template {
+        let digest = State::load()?
+            .templates
+            .get(name)
+            .map(|build| build.image_digest.clone())
+            .with_context(|| {
+                format!(
+                    "Template '{}' has not been built. Run 'sandy build --template {}'.",
+                    name, name
+                )
+            })?;
+        cmd.args(["--template", &digest]);
+    } else if let Some(digest) = load_template_digest()? {
         cmd.args(["--template", &digest]);
     } else if let Some(ref template) = config.template_image {
         cmd.args(["--template", template]);
@@ -488,12 +693,21 @@ pub fn start_sandbox(workspace: &Path, config: &Config, tool: &str) -> Result<()
     // Use sandbox credentials - auth persists across sandboxes in Docker volume
     cmd.args(["--credentials=sandbox"]);
 
-    // Name the container for tracking
-    let container_name = get_container_name(workspace);
+    // Name and label the container for tracking
+    let container_name = sandbox.container_name();
     cmd.args(["--name", &container_name]);
+    cmd.args(["--label", SANDBOX_LABEL]);
 
     // Workspace
-    cmd.args(["-w", &workspace.display().to_string()]);
+    cmd.args(["-w", &workdir.display().to_string()]);
+
+    Ok((cmd, workdir))
+}
+
+/// Start a new sandbox with the given configuration and CLI tool, returning
+/// the tool's exit code once the session ends
+pub fn start_sandbox(sandbox: &SandboxInfo, config: &Config, tool: &str) -> Result<i32> {
+    let (mut cmd, workdir) = sandbox_command(sandbox, config)?;
 
     // CLI tool command
     let tool_cmd = get_tool_command(tool);
@@ -503,8 +717,21 @@ pub fn start_sandbox(workspace: &Path, config: &Config, tool: &str) -> Result<()
     } else {
         cmd.args(tool_cmd);
     }
+    cmd.args(sandbox_tool_args(sandbox, tool));
 
-    println!("Starting sandbox for: {}", workspace.display());
+    println!("Starting sandbox for: {}", sandbox.workspace().display());
+    if let Some(branch) = &sandbox.branch {
+        println!("Branch: {}", branch);
+    }
+    for repo in &sandbox.extra_repos {
+        println!("Also mounting: {}", repo.display());
+    }
+    if sandbox.read_only {
+        println!(
+            "Repository is read-only; agent output goes to: {}",
+            workdir.display()
+        );
+    }
 
     let status = cmd
         .stdout(Stdio::inherit())
@@ -513,16 +740,106 @@ pub fn start_sandbox(workspace: &Path, config: &Config, tool: &str) -> Result<()
         .status()
         .context("Failed to start sandbox")?;
 
-    if !status.success() {
-        bail!("Sandbox exited with error");
+    Ok(exit_code(status))
+}
+
+/// Run the CLI tool non-interactively with a task prompt, writing its
+/// stdout and stderr to `log`. The container is stopped if `timeout` passes.
+pub fn run_sandbox_task(
+    sandbox: &SandboxInfo,
+    config: &Config,
+    tool: &str,
+    prompt: &str,
+    log: &Path,
+    timeout: Option<Duration>,
+) -> Result<RunOutcome> {
+    let (mut cmd, _) = sandbox_command(sandbox, config)?;
+    cmd.args(headless_tool_args(tool, prompt));
+    cmd.args(sandbox_tool_args(sandbox, tool));
+
+    if let Some(parent) = log.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
     }
+    let file =
+        fs::File::create(log).with_context(|| format!("Failed to create {}", log.display()))?;
+
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(file.try_clone()?)
+        .stderr(file)
+        .spawn()
+        .context("Failed to start sandbox")?;
 
-    Ok(())
+    let outcome = wait_with_timeout(&mut child, timeout)?;
+    if outcome == RunOutcome::TimedOut {
+        let _ = child.kill();
+        let _ = child.wait();
+        stop_sandbox(sandbox)?;
+    }
+
+    Ok(outcome)
+}
+
+/// Run a one-off command in a sandbox's container with `docker exec`,
+/// streaming its output, and return its exit code. A stopped container is
+/// started for the command and stopped again afterwards.
+pub fn exec_in_sandbox(sandbox: &SandboxInfo, command: &[String]) -> Result<i32> {
+    let container_name = sandbox.container_name();
+
+    let status = sandbox_status(sandbox)?;
+    match status {
+        SandboxStatus::Running => {}
+        SandboxStatus::Stopped => {
+            let output = Command::new("docker")
+                .args(["start", &container_name])
+                .output()
+                .context("Failed to start sandbox")?;
+            if !output.status.success() {
+                bail!(
+                    "Failed to start sandbox: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+            }
+        }
+        SandboxStatus::NotFound => bail!(
+            "The container for '{}' doesn't exist. Run 'sandy resume' to recreate it.",
+            sandbox.display_name()
+        ),
+    }
+
+    // The agent's working directory: the scratch directory of a read-only
+    // sandbox, otherwise the workspace (mounted at its host path)
+    let workdir = if sandbox.read_only {
+        scratch_dir(sandbox)?
+    } else {
+        sandbox.workspace().to_path_buf()
+    };
+
+    let mut cmd = Command::new("docker");
+    cmd.arg("exec");
+    // Keep stdin open so input can be piped in, and allocate a terminal only
+    // when attached to one, so output can be redirected to a file
+    if std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+        cmd.arg("-it");
+    } else {
+        cmd.arg("-i");
+    }
+    cmd.args(["-w", &workdir.display().to_string(), &container_name]);
+    cmd.args(command);
+
+    let result = cmd.status().context("Failed to run docker exec");
+
+    if status == SandboxStatus::Stopped {
+        stop_sandbox(sandbox)?;
+    }
+
+    Ok(exit_code(result?))
 }
 
 /// Stop a running sandbox
-pub fn stop_sandbox(workspace: &Path) -> Result<()> {
-    let container_name = get_container_name(workspace);
+pub fn stop_sandbox(sandbox: &SandboxInfo) -> Result<()> {
+    let container_name = sandbox.container_name();
 
     let output = Command::new("docker")
         .args(["stop", &container_name])
@@ -539,12 +856,23 @@ pub fn stop_sandbox(workspace: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Delete the scratch directory of a read-only sandbox, if there is one
+pub fn remove_scratch_dir(sandbox: &SandboxInfo) -> Result<()> {
+    let scratch = scratch_dir(sandbox)?;
+    if scratch.exists() {
+        fs::remove_dir_all(&scratch).with_context(|| {
+            format!("Failed to remove scratch directory: {}", scratch.display())
+        })?;
+    }
+    Ok(())
+}
+
 /// Remove a sandbox container
-pub fn remove_sandbox(workspace: &Path) -> Result<()> {
-    let container_name = get_container_name(workspace);
+pub fn remove_sandbox(sandbox: &SandboxInfo) -> Result<()> {
+    let container_name = sandbox.container_name();
 
     // Stop first if running
-    let _ = stop_sandbox(workspace);
+    let _ = stop_sandbox(sandbox);
 
     let output = Command::new("docker")
         .args(["rm", "-f", &container_name])
@@ -597,16 +925,112 @@ mod tests {
     #[test]
     fn test_get_container_name_deterministic() {
         let path = Path::new("/test/workspace");
-        let name1 = get_container_name(path);
-        let name2 = get_container_name(path);
+        let name1 = get_container_name(path, None);
+        let name2 = get_container_name(path, None);
 
         assert_eq!(name1, name2);
     }
 
+    #[test]
+    fn test_build_arg_flags_sorted_and_expanded() {
+        let mut config = Config::default();
+        config
+            .build_args
+            .insert("NODE_VERSION".to_string(), "22".to_string());
+        config
+            .build_args
+            .insert("BASE_HOME".to_string(), "$HOME".to_string());
+
+        let flags = build_arg_flags(&config).unwrap();
+        let home = std::env::var("HOME").unwrap();
+        assert_eq!(
+            flags,
+            vec![
+                "--build-arg".to_string(),
+                format!("BASE_HOME={}", home),
+                "--build-arg".to_string(),
+                "NODE_VERSION=22".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_secret_spec() {
+        let temp_dir = TempDir::new().unwrap();
+        let secret_file = temp_dir.path().join("npmrc");
+        fs::write(&secret_file, "token").unwrap();
+
+        let from_env = BuildSecret {
+            id: "npm".to_string(),
+            env: Some("NPM_TOKEN".to_string()),
+            src: None,
+        };
+        assert_eq!(secret_spec(&from_env).unwrap(), "id=npm,env=NPM_TOKEN");
+
+        let from_file = BuildSecret {
+            id: "npmrc".to_string(),
+            env: None,
+            src: Some(secret_file.display().to_string()),
+        };
+        assert_eq!(
+            secret_spec(&from_file).unwrap(),
+            format!("id=npmrc,src={}", secret_file.display())
+        );
+    }
+
+    #[test]
+    fn test_secret_spec_rejects_invalid() {
+        let both = BuildSecret {
+            id: "x".to_string(),
+            env: Some("X".to_string()),
+            src: Some("/x".to_string()),
+        };
+        assert!(secret_spec(&both).is_err());
+
+        let neither = BuildSecret {
+            id: "x".to_string(),
+            env: None,
+            src: None,
+        };
+        assert!(secret_spec(&neither).is_err());
+
+        let missing = BuildSecret {
+            id: "x".to_string(),
+            env: None,
+            src: Some("/nonexistent/sandy-secret".to_string()),
+        };
+        assert!(secret_spec(&missing).is_err());
+    }
+
+    #[test]
+    fn test_read_only_tool_args_claude() {
+        let args = read_only_tool_args("claude", Path::new("/code/repo"));
+        assert_eq!(args[0], "--add-dir");
+        assert_eq!(args[1], "/code/repo");
+        assert_eq!(args[2], "--append-system-prompt");
+        assert!(args[3].contains("read-only at /code/repo"));
+    }
+
+    #[test]
+    fn test_read_only_tool_args_other_tools() {
+        assert!(read_only_tool_args("gemini", Path::new("/code/repo")).is_empty());
+        assert!(read_only_tool_args("custom-tool", Path::new("/code/repo")).is_empty());
+    }
+
+    #[test]
+    fn test_extra_repo_tool_args() {
+        let repos = vec![PathBuf::from("/code/shared"), PathBuf::from("/code/proto")];
+        assert_eq!(
+            extra_repo_tool_args("claude", &repos),
+            vec!["--add-dir", "/code/shared", "--add-dir", "/code/proto"]
+        );
+        assert!(extra_repo_tool_args("codex", &repos).is_empty());
+    }
+
     #[test]
     fn test_get_container_name_format() {
         let path = Path::new("/test/workspace");
-        let name = get_container_name(path);
+        let name = get_container_name(path, None);
 
         // Format: sandy-{dirname}-{6 char hash}
         assert!(name.starts_with("sandy-workspace-"));
@@ -622,27 +1046,38 @@ mod tests {
         let path1 = Path::new("/test/workspace1");
         let path2 = Path::new("/test/workspace2");
 
-        let name1 = get_container_name(path1);
-        let name2 = get_container_name(path2);
+        let name1 = get_container_name(path1, None);
+        let name2 = get_container_name(path2, None);
 
         assert_ne!(name1, name2);
     }
 
+    #[test]
+    fn test_get_container_name_named_sandbox() {
+        let path = Path::new("/test/workspace");
+        let default = get_container_name(path, None);
+        let named = get_container_name(path, Some("feature-x"));
+
+        assert!(named.starts_with("sandy-workspace-feature-x-"));
+        assert_ne!(named, default);
+        assert_ne!(named, get_container_name(path, Some("other")));
+    }
+
     #[test]
     fn test_get_container_name_special_characters() {
         // Test with valid special chars (dashes, underscores)
         let path = Path::new("/test/workspace/and-dashes_underscores");
-        let name = get_container_name(path);
+        let name = get_container_name(path, None);
         assert!(name.starts_with("sandy-and-dashes_underscores-"));
 
         // Test with spaces (should be replaced with dashes)
         let path_with_spaces = Path::new("/test/my project name");
-        let name_spaces = get_container_name(path_with_spaces);
+        let name_spaces = get_container_name(path_with_spaces, None);
         assert!(name_spaces.starts_with("sandy-my-project-name-"));
 
         // Test with dots (allowed in Docker container names)
         let path_with_dots = Path::new("/test/my.project.name");
-        let name_dots = get_container_name(path_with_dots);
+        let name_dots = get_container_name(path_with_dots, None);
         assert!(name_dots.starts_with("sandy-my.project.name-"));
 
         // Verify hash suffix is valid hex
@@ -757,8 +1192,10 @@ mod tests {
         let non_exec_path = bin_dir.join("not-executable");
         fs::write(&non_exec_path, "data").unwrap();
 
-        let mut config = Config::default();
-        config.binary_dirs = vec![bin_dir.to_string_lossy().to_string()];
+        let config = Config {
+            binary_dirs: vec![bin_dir.to_string_lossy().to_string()],
+            ..Default::default()
+        };
 
         let dockerfile_dir = temp_dir.path().join("docker");
         fs::create_dir(&dockerfile_dir).unwrap();
@@ -781,8 +1218,10 @@ mod tests {
         let subdir = bin_dir.join("subdir");
         fs::create_dir(&subdir).unwrap();
 
-        let mut config = Config::default();
-        config.binary_dirs = vec![bin_dir.to_string_lossy().to_string()];
+        let config = Config {
+            binary_dirs: vec![bin_dir.to_string_lossy().to_string()],
+            ..Default::default()
+        };
 
         let dockerfile_dir = temp_dir.path().join("docker");
         fs::create_dir(&dockerfile_dir).unwrap();
@@ -806,8 +1245,10 @@ mod tests {
         let old_file = assets_bin.join("old-binary");
         fs::write(&old_file, "old content").unwrap();
 
-        let mut config = Config::default();
-        config.binary_dirs = vec![];
+        let config = Config {
+            binary_dirs: vec![],
+            ..Default::default()
+        };
 
         let result = prepare_template_assets(&dockerfile_dir, &config);
         assert!(result.is_ok());
@@ -819,8 +1260,10 @@ mod tests {
     #[test]
     fn test_prepare_template_assets_nonexistent_binary_dir() {
         let temp_dir = TempDir::new().unwrap();
-        let mut config = Config::default();
-        config.binary_dirs = vec!["/nonexistent/path/12345".to_string()];
+        let config = Config {
+            binary_dirs: vec!["/nonexistent/path/12345".to_string()],
+            ..Default::default()
+        };
 
         let result = prepare_template_assets(temp_dir.path(), &config);
         // Should succeed but skip the nonexistent directory
@@ -835,8 +1278,10 @@ mod tests {
         let file_path = temp_dir.path().join("not-a-dir");
         fs::write(&file_path, "content").unwrap();
 
-        let mut config = Config::default();
-        config.binary_dirs = vec![file_path.to_string_lossy().to_string()];
+        let config = Config {
+            binary_dirs: vec![file_path.to_string_lossy().to_string()],
+            ..Default::default()
+        };
 
         let dockerfile_dir = temp_dir.path().join("docker");
         fs::create_dir(&dockerfile_dir).unwrap();
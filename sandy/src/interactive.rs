@@ -1,15 +1,19 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
 use std::io::{self, Write};
+use std::path::PathBuf;
 
 use crate::docker::{SandboxStatus, sandbox_status};
-use crate::state::{SandboxInfo, State};
-use crate::worktree::get_repo_name;
+use crate::run::format_duration;
+use crate::state::{SandboxInfo, Session, State};
+use crate::usage::format_age;
 
 /// Display entry for interactive selection
+#[derive(Debug, Clone)]
 pub struct SelectionEntry {
     /// Canonical path key (used for state lookup)
     pub key: String,
-    /// Display name (derived from repo directory name)
+    /// Display name (see [`SandboxInfo::display_name`])
     pub name: String,
     pub info: SandboxInfo,
     pub status: SandboxStatus,
@@ -20,8 +24,8 @@ pub fn get_sandbox_entries(state: &State) -> Result<Vec<SelectionEntry>> {
     let mut entries = Vec::new();
 
     for (key, info) in &state.sandboxes {
-        let status = sandbox_status(&info.path).unwrap_or(SandboxStatus::NotFound);
-        let name = get_repo_name(&info.path);
+        let status = sandbox_status(info).unwrap_or(SandboxStatus::NotFound);
+        let name = info.display_name();
         entries.push(SelectionEntry {
             key: key.clone(),
             name,
@@ -31,11 +35,47 @@ pub fn get_sandbox_entries(state: &State) -> Result<Vec<SelectionEntry>> {
     }
 
     // Sort by creation time (most recent first)
-    entries.sort_by(|a, b| b.info.created_at.cmp(&a.info.created_at));
+    entries.sort_by_key(|e| std::cmp::Reverse(e.info.created_at));
 
     Ok(entries)
 }
 
+/// Find the sandbox named on the command line, by state key, repository path,
+/// display name, or sandbox name
+pub fn find_entry<'a>(entries: &'a [SelectionEntry], name: &str) -> Result<&'a SelectionEntry> {
+    if let Some(entry) = entries.iter().find(|e| e.key == name) {
+        return Ok(entry);
+    }
+
+    // A path selects among the repository's sandboxes, or a branch sandbox by
+    // its worktree
+    let path = std::fs::canonicalize(name).unwrap_or_else(|_| PathBuf::from(name));
+    let mut matches: Vec<&SelectionEntry> =
+        entries.iter().filter(|e| e.info.is_for(&path)).collect();
+    if matches.is_empty() {
+        matches = entries
+            .iter()
+            .filter(|e| e.name == name || e.info.name.as_deref() == Some(name))
+            .collect();
+    }
+
+    match matches.as_slice() {
+        [entry] => Ok(entry),
+        [] => bail!(
+            "No sandbox named '{}'. Run 'sandy list' to see your sandboxes.",
+            name
+        ),
+        _ => {
+            let keys: Vec<String> = matches.iter().map(|e| format!("  {}", e.key)).collect();
+            bail!(
+                "Several sandboxes are named '{}'; pass one of these instead:\n{}",
+                name,
+                keys.join("\n")
+            )
+        }
+    }
+}
+
 /// Format a status for display
 fn format_status(status: &SandboxStatus) -> &'static str {
     match status {
@@ -45,6 +85,19 @@ fn format_status(status: &SandboxStatus) -> &'static str {
     }
 }
 
+/// Summarize a finished session, e.g. "codex, 42m, exit 0, 2 hours ago"
+fn format_session(session: &Session, now: DateTime<Utc>) -> String {
+    let mode = if session.headless { " (run)" } else { "" };
+    format!(
+        "{}{}, {}, exit {}, {}",
+        session.tool,
+        mode,
+        format_duration(session.duration_secs),
+        session.exit_code,
+        format_age(now, session.ended_at())
+    )
+}
+
 /// Display the list of sandboxes
 pub fn display_sandbox_list(entries: &[SelectionEntry]) {
     if entries.is_empty() {
@@ -52,18 +105,37 @@ pub fn display_sandbox_list(entries: &[SelectionEntry]) {
         return;
     }
 
+    let now = Utc::now();
+
     println!("\nAvailable sandboxes:");
     println!("{:-<60}", "");
 
     for (i, entry) in entries.iter().enumerate() {
         let status = format_status(&entry.status);
+        let mut mode = if entry.info.read_only {
+            " [read-only]".to_string()
+        } else if let Some(branch) = &entry.info.branch {
+            format!(" [branch {}]", branch)
+        } else {
+            String::new()
+        };
+        if let Some(template) = &entry.info.template {
+            mode.push_str(&format!(" [template {}]", template));
+        }
         println!(
-            "  {}. {} {} - {}",
+            "  {}. {} {}{} - {}",
             i + 1,
             entry.name,
             status,
-            entry.info.path.display()
+            mode,
+            entry.info.workspace().display()
         );
+        for repo in &entry.info.extra_repos {
+            println!("       + {}", repo.display());
+        }
+        if let Some(session) = entry.info.sessions.last() {
+            println!("       last session: {}", format_session(session, now));
+        }
     }
 
     println!("{:-<60}", "");
@@ -123,8 +195,16 @@ mod tests {
                 path.to_string_lossy().to_string(),
                 SandboxInfo {
                     path,
+                    name: None,
                     created_at: Utc::now() - chrono::Duration::hours(i as i64),
                     tool: Some("claude".to_string()),
+                    last_used_at: None,
+                    read_only: false,
+                    extra_repos: Vec::new(),
+                    worktree: None,
+                    branch: None,
+                    template: None,
+                    sessions: Vec::new(),
                 },
             );
         }
@@ -138,8 +218,16 @@ mod tests {
             name: "repo".to_string(),
             info: SandboxInfo {
                 path: PathBuf::from("/test/repo"),
+                name: None,
                 created_at: Utc::now(),
                 tool: Some("claude".to_string()),
+                last_used_at: None,
+                read_only: false,
+                extra_repos: Vec::new(),
+                worktree: None,
+                branch: None,
+                template: None,
+                sessions: Vec::new(),
             },
             status: SandboxStatus::Running,
         };
@@ -167,6 +255,29 @@ mod tests {
         assert_eq!(formatted, "[no container]");
     }
 
+    #[test]
+    fn test_format_session() {
+        let now = Utc::now();
+        let mut session = Session {
+            tool: "codex".to_string(),
+            started_at: now - chrono::Duration::minutes(42 + 120),
+            duration_secs: 42 * 60,
+            exit_code: 0,
+            headless: false,
+        };
+        assert_eq!(
+            format_session(&session, now),
+            "codex, 42m, exit 0, 2 hours ago"
+        );
+
+        session.headless = true;
+        session.exit_code = 124;
+        assert_eq!(
+            format_session(&session, now),
+            "codex (run), 42m, exit 124, 2 hours ago"
+        );
+    }
+
     #[test]
     fn test_get_sandbox_entries_empty_state() {
         let state = State::default();
@@ -177,7 +288,7 @@ mod tests {
     #[test]
     fn test_get_sandbox_entries_single() {
         let mut state = State::default();
-        state.add_sandbox(PathBuf::from("/test/my-repo"), "claude");
+        state.add_sandbox(PathBuf::from("/test/my-repo"), None, "claude");
 
         let entries = get_sandbox_entries(&state).unwrap();
 
@@ -206,16 +317,32 @@ mod tests {
             "/older".to_string(),
             SandboxInfo {
                 path: PathBuf::from("/older"),
+                name: None,
                 created_at: older_time,
                 tool: Some("claude".to_string()),
+                last_used_at: None,
+                read_only: false,
+                extra_repos: Vec::new(),
+                worktree: None,
+                branch: None,
+                template: None,
+                sessions: Vec::new(),
             },
         );
         state.sandboxes.insert(
             "/newer".to_string(),
             SandboxInfo {
                 path: PathBuf::from("/newer"),
+                name: None,
                 created_at: newer_time,
                 tool: Some("gemini".to_string()),
+                last_used_at: None,
+                read_only: false,
+                extra_repos: Vec::new(),
+                worktree: None,
+                branch: None,
+                template: None,
+                sessions: Vec::new(),
             },
         );
 
@@ -231,6 +358,7 @@ mod tests {
         let mut state = State::default();
         state.add_sandbox(
             PathBuf::from("/home/user/projects/awesome-project"),
+            None,
             "claude",
         );
 
@@ -255,8 +383,16 @@ mod tests {
                 name: "repo1".to_string(),
                 info: SandboxInfo {
                     path: PathBuf::from("/test/repo1"),
+                    name: None,
                     created_at: Utc::now(),
                     tool: Some("claude".to_string()),
+                    last_used_at: None,
+                    read_only: false,
+                    extra_repos: Vec::new(),
+                    worktree: None,
+                    branch: None,
+                    template: None,
+                    sessions: Vec::new(),
                 },
                 status: SandboxStatus::Running,
             },
@@ -265,8 +401,16 @@ mod tests {
                 name: "repo2".to_string(),
                 info: SandboxInfo {
                     path: PathBuf::from("/test/repo2"),
+                    name: None,
                     created_at: Utc::now(),
                     tool: Some("gemini".to_string()),
+                    last_used_at: None,
+                    read_only: false,
+                    extra_repos: Vec::new(),
+                    worktree: None,
+                    branch: None,
+                    template: None,
+                    sessions: Vec::new(),
                 },
                 status: SandboxStatus::Stopped,
             },
@@ -288,8 +432,16 @@ mod tests {
                 name: "test".to_string(),
                 info: SandboxInfo {
                     path: PathBuf::from("/test"),
+                    name: None,
                     created_at: Utc::now(),
                     tool: Some("claude".to_string()),
+                    last_used_at: None,
+                    read_only: false,
+                    extra_repos: Vec::new(),
+                    worktree: None,
+                    branch: None,
+                    template: None,
+                    sessions: Vec::new(),
                 },
                 status: status.clone(),
             };
@@ -298,4 +450,56 @@ mod tests {
             assert_eq!(entry.status, status);
         }
     }
+
+    #[test]
+    fn test_find_entry() {
+        let mut state = State::default();
+        state.add_sandbox(PathBuf::from("/work/api"), None, "claude");
+        state.add_sandbox(PathBuf::from("/forks/api"), None, "claude");
+        state.add_sandbox(PathBuf::from("/work/web"), None, "claude");
+        let entries = get_sandbox_entries(&state).unwrap();
+
+        assert_eq!(find_entry(&entries, "web").unwrap().key, "/work/web");
+        assert_eq!(
+            find_entry(&entries, "/forks/api").unwrap().key,
+            "/forks/api"
+        );
+
+        let err = find_entry(&entries, "api").unwrap_err().to_string();
+        assert!(err.contains("Several sandboxes are named 'api'"));
+        assert!(err.contains("/work/api") && err.contains("/forks/api"));
+
+        let err = find_entry(&entries, "missing").unwrap_err().to_string();
+        assert!(err.contains("No sandbox named 'missing'"));
+    }
+
+    #[test]
+    fn test_find_named_entry() {
+        let mut state = State::default();
+        state.add_sandbox(PathBuf::from("/work/api"), None, "claude");
+        state.add_sandbox(PathBuf::from("/work/api"), Some("auth"), "claude");
+        state.add_sandbox(PathBuf::from("/work/api"), Some("billing"), "claude");
+        state.add_sandbox(PathBuf::from("/work/web"), Some("a"), "claude");
+        state.add_sandbox(PathBuf::from("/work/web"), Some("b"), "claude");
+        let entries = get_sandbox_entries(&state).unwrap();
+
+        assert_eq!(find_entry(&entries, "api").unwrap().key, "/work/api");
+        assert_eq!(
+            find_entry(&entries, "api:auth").unwrap().key,
+            "/work/api#auth"
+        );
+        assert_eq!(
+            find_entry(&entries, "billing").unwrap().key,
+            "/work/api#billing"
+        );
+        assert_eq!(
+            find_entry(&entries, "/work/api#billing").unwrap().key,
+            "/work/api#billing"
+        );
+
+        // The repository path is the default sandbox's key, if there is one
+        assert_eq!(find_entry(&entries, "/work/api").unwrap().key, "/work/api");
+        let err = find_entry(&entries, "/work/web").unwrap_err().to_string();
+        assert!(err.contains("/work/web#a") && err.contains("/work/web#b"));
+    }
 }
@@ -1,24 +1,41 @@
+mod complete;
 mod config;
 mod docker;
 mod interactive;
+mod prune;
+mod run;
 mod state;
+mod usage;
 mod worktree;
 
 use anyhow::{Context, Result, bail};
-use clap::{Parser, Subcommand};
+use chrono::Utc;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::{ArgValueCandidates, CompleteEnv};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use config::Config;
 use docker::{
-    DefaultTemplateStatus, backup_dockerfile, build_template, build_template_no_cache,
-    check_default_template_status, check_docker, check_docker_sandbox, new_default_available,
-    remove_sandbox, start_sandbox, template_exists, template_needs_rebuild,
+    DefaultTemplateStatus, SandboxStatus, backup_dockerfile, build_template,
+    build_template_no_cache, check_default_template_status, check_docker, check_docker_sandbox,
+    exec_in_sandbox, new_default_available, remove_sandbox, remove_scratch_dir, run_sandbox_task,
+    start_sandbox, stop_sandbox, template_exists, template_needs_rebuild,
     update_dockerfile_from_default,
 };
-use interactive::{confirm, display_sandbox_list, get_sandbox_entries, prompt_selection};
-use state::State;
-use worktree::{get_repo_name, get_workspace_path};
+use interactive::{
+    SelectionEntry, confirm, display_sandbox_list, find_entry, get_sandbox_entries,
+    prompt_selection,
+};
+use prune::{Orphans, remove_container, remove_image};
+use run::{RunOutcome, default_log_path, parse_timeout, resolve_repo};
+use state::{SandboxInfo, Session, State, display_name, sandbox_key};
+use usage::{collect_usage, display_usage, format_age, volume_sizes};
+use worktree::{
+    add_worktree, clone_repo, get_repo_name, get_workspace_path, remove_worktree,
+    repo_name_from_url, resolve_extra_repos,
+};
 
 /// Default template image name used when no custom template is configured
 const DEFAULT_TEMPLATE_IMAGE: &str = "sandy-dev";
@@ -39,18 +56,124 @@ enum Commands {
         /// CLI tool to run (claude, gemini, codex). Defaults to config value.
         #[arg(long, short)]
         tool: Option<String>,
+        /// Mount the repository read-only; the agent works in a scratch directory
+        #[arg(long)]
+        read_only: bool,
+        /// Another repository to mount alongside this one (repeatable). Defaults to
+        /// linked_repos in sandy.toml.
+        #[arg(long = "repo", value_name = "PATH")]
+        repos: Vec<PathBuf>,
+        /// Name for another sandbox of this repository (e.g. one per feature)
+        #[arg(long, value_parser = parse_sandbox_name)]
+        name: Option<String>,
+        /// Work on this branch in a new git worktree instead of the repository
+        /// itself (the branch is created if needed). Names the sandbox after the
+        /// branch unless --name is given.
+        #[arg(long, conflicts_with = "read_only")]
+        branch: Option<String>,
+        /// Named Dockerfile template to build the sandbox from. Defaults to
+        /// repo_templates in sandy.toml, then the default template.
+        #[arg(long, value_parser = parse_template_name,
+              add = ArgValueCandidates::new(complete::template_candidates))]
+        template: Option<String>,
+    },
+    /// Clone a repository into the projects directory and start a sandbox for it
+    Clone {
+        /// Repository URL, as accepted by git clone
+        url: String,
+        /// Where to clone to (defaults to projects_dir in sandy.toml)
+        #[arg(long, value_name = "PATH")]
+        dir: Option<PathBuf>,
+        /// CLI tool to run (claude, gemini, codex). Defaults to config value.
+        #[arg(long, short)]
+        tool: Option<String>,
+        /// Mount the repository read-only; the agent works in a scratch directory
+        #[arg(long)]
+        read_only: bool,
+        /// Named Dockerfile template to build the sandbox from
+        #[arg(long, value_parser = parse_template_name,
+              add = ArgValueCandidates::new(complete::template_candidates))]
+        template: Option<String>,
+    },
+    /// Run an agent non-interactively on a task, creating or reusing the sandbox
+    Run {
+        /// Repository to run in (defaults to the current directory)
+        #[arg(long)]
+        repo: Option<PathBuf>,
+        /// CLI tool to run (claude, gemini, codex). Defaults to the sandbox's tool, then config.
+        #[arg(long, short)]
+        tool: Option<String>,
+        /// Task prompt for the agent
+        #[arg(long, short)]
+        command: String,
+        /// Stop the agent after this long (e.g. 90s, 30m, 1h30m)
+        #[arg(long, value_parser = parse_timeout)]
+        timeout: Option<Duration>,
+        /// File to write the agent's output to (defaults to a log under the sandy config directory)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Mount the repository read-only when creating the sandbox
+        #[arg(long)]
+        read_only: bool,
     },
     /// Resume an existing sandbox (interactive selection)
-    Resume,
+    Resume {
+        /// Sandbox to resume, by repository name or path (defaults to the current
+        /// repository's sandbox, then asks)
+        #[arg(add = ArgValueCandidates::new(complete::sandbox_candidates))]
+        name: Option<String>,
+        /// CLI tool to run (claude, gemini, codex), remembered for later resumes.
+        /// Defaults to the sandbox's tool.
+        #[arg(long, short)]
+        tool: Option<String>,
+    },
+    /// Run a one-off command in a sandbox without starting the agent
+    Exec {
+        /// Sandbox to run in, by repository name or path (defaults to the current
+        /// repository's sandbox, then asks)
+        #[arg(add = ArgValueCandidates::new(complete::sandbox_candidates))]
+        name: Option<String>,
+        /// Command and arguments, after `--`
+        #[arg(last = true, required = true, value_name = "COMMAND")]
+        command: Vec<String>,
+    },
     /// List all sandbox environments
     List,
+    /// Stop a running sandbox without removing it (interactive selection)
+    Stop {
+        /// Sandbox to stop, by repository name or path (asks when omitted)
+        #[arg(add = ArgValueCandidates::new(complete::sandbox_candidates))]
+        name: Option<String>,
+    },
     /// Remove a sandbox environment (interactive selection)
-    Remove,
+    Remove {
+        /// Sandbox to remove, by repository name or path (asks when omitted)
+        #[arg(add = ArgValueCandidates::new(complete::sandbox_candidates))]
+        name: Option<String>,
+    },
+    /// Show disk usage and last-used time for each sandbox
+    Usage,
+    /// Remove containers, images, and sandboxes that no longer belong to anything
+    Prune {
+        /// Remove without asking for confirmation
+        #[arg(long, short)]
+        force: bool,
+        /// List what would be removed without removing it
+        #[arg(long, conflicts_with = "force")]
+        dry_run: bool,
+    },
     /// Build or rebuild the sandbox template image
     Build {
         /// Force a complete rebuild, ignoring Docker's build cache
         #[arg(long, short)]
         force: bool,
+        /// Build argument for the Dockerfile (KEY=VALUE), overriding build_args in sandy.toml
+        #[arg(long = "build-arg", value_name = "KEY=VALUE", value_parser = parse_build_arg)]
+        build_args: Vec<(String, String)>,
+        /// Build this named template instead of the default one
+        #[arg(long, value_parser = parse_template_name,
+              add = ArgValueCandidates::new(complete::template_candidates))]
+        template: Option<String>,
     },
     /// Update the Dockerfile template to the latest embedded default
     Update {
@@ -82,20 +205,113 @@ enum ConfigAction {
         value: String,
     },
     /// Show Dockerfile path and contents
-    Dockerfile,
+    Dockerfile {
+        /// Show this named template instead of the default one
+        #[arg(long, value_parser = parse_template_name,
+              add = ArgValueCandidates::new(complete::template_candidates))]
+        template: Option<String>,
+    },
     /// Create or reset the Dockerfile template for customization
-    CreateDockerfile,
+    CreateDockerfile {
+        /// Create a named template (e.g. rust, python), starting from the default Dockerfile
+        #[arg(long, value_parser = parse_template_name)]
+        template: Option<String>,
+    },
+    /// List the named Dockerfile templates
+    Templates,
+}
+
+/// Parse a `--build-arg KEY=VALUE` value
+fn parse_build_arg(s: &str) -> std::result::Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got '{}'", s)),
+    }
+}
+
+/// Parse a `--name` value, which becomes part of the container name
+fn parse_sandbox_name(s: &str) -> std::result::Result<String, String> {
+    if !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+    {
+        Ok(s.to_string())
+    } else {
+        Err(format!(
+            "'{}' may only contain letters, digits, '-', '_', and '.'",
+            s
+        ))
+    }
+}
+
+/// Parse a `--template` value
+fn parse_template_name(s: &str) -> std::result::Result<String, String> {
+    config::check_template_name(s).map(|()| s.to_string())
+}
+
+/// Default sandbox name for a branch, with characters `--name` doesn't
+/// allow (such as the `/` in `feature/login`) replaced by `-`
+fn branch_sandbox_name(branch: &str) -> String {
+    branch
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
 }
 
 fn main() -> Result<()> {
+    // Answers shell completion requests (COMPLETE=<shell>) and exits
+    CompleteEnv::with_factory(Cli::command).complete();
+
     let cli = Cli::parse();
 
     match cli.command {
-        Some(Commands::New { tool }) => cmd_new(tool),
-        Some(Commands::Resume) => cmd_resume(),
+        Some(Commands::New {
+            tool,
+            read_only,
+            repos,
+            name,
+            branch,
+            template,
+        }) => cmd_new(tool, read_only, repos, name, branch, template),
+        Some(Commands::Clone {
+            url,
+            dir,
+            tool,
+            read_only,
+            template,
+        }) => cmd_clone(&url, dir, tool, read_only, template),
+        Some(Commands::Run {
+            repo,
+            tool,
+            command,
+            timeout,
+            output,
+            read_only,
+        }) => {
+            let code = cmd_run(repo, tool, &command, timeout, output, read_only)?;
+            std::process::exit(code);
+        }
+        Some(Commands::Resume { name, tool }) => cmd_resume(name, tool),
+        Some(Commands::Exec { name, command }) => {
+            let code = cmd_exec(name, &command)?;
+            std::process::exit(code);
+        }
         Some(Commands::List) => cmd_list(),
-        Some(Commands::Remove) => cmd_remove(),
-        Some(Commands::Build { force }) => cmd_build(force),
+        Some(Commands::Stop { name }) => cmd_stop(name),
+        Some(Commands::Remove { name }) => cmd_remove(name),
+        Some(Commands::Usage) => cmd_usage(),
+        Some(Commands::Prune { force, dry_run }) => cmd_prune(force, dry_run),
+        Some(Commands::Build {
+            force,
+            build_args,
+            template,
+        }) => cmd_build(force, build_args, template),
         Some(Commands::Update { force }) => cmd_update(force),
         Some(Commands::Config { action }) => cmd_config(action),
         None => cmd_interactive(),
@@ -107,10 +323,13 @@ fn main() -> Result<()> {
 fn current_workspace_has_sandbox() -> Option<String> {
     let cwd = env::current_dir().ok()?;
     let workspace_path = get_workspace_path(&cwd);
-    let workspace_key = workspace_path.to_string_lossy().to_string();
     let state = State::load().ok()?;
 
-    if state.sandboxes.contains_key(&workspace_key) {
+    if state
+        .sandboxes
+        .values()
+        .any(|info| info.is_for(&workspace_path))
+    {
         Some(get_repo_name(&workspace_path))
     } else {
         None
@@ -151,9 +370,9 @@ fn cmd_interactive() -> Result<()> {
         match input {
             "1" => {
                 if sandbox_exists.is_some() {
-                    return cmd_resume();
+                    return cmd_resume(None, None);
                 } else {
-                    return cmd_new(None);
+                    return cmd_new(None, false, Vec::new(), None, None, None);
                 }
             }
             "2" => {
@@ -164,7 +383,7 @@ fn cmd_interactive() -> Result<()> {
                 println!();
             }
             "4" | "remove" | "rm" => {
-                return cmd_remove();
+                return cmd_remove(None);
             }
             "5" | "config" | "c" => {
                 cmd_config(ConfigAction::Show)?;
@@ -180,33 +399,172 @@ fn cmd_interactive() -> Result<()> {
     }
 }
 
-fn cmd_new(tool_override: Option<String>) -> Result<()> {
+fn cmd_new(
+    tool_override: Option<String>,
+    read_only: bool,
+    repos: Vec<PathBuf>,
+    name: Option<String>,
+    branch: Option<String>,
+    template: Option<String>,
+) -> Result<()> {
+    // Get current workspace (git root or current directory)
+    let cwd = env::current_dir().context("Failed to get current directory")?;
+    let workspace_path = get_workspace_path(&cwd);
+    new_sandbox(
+        workspace_path,
+        tool_override,
+        read_only,
+        repos,
+        name,
+        branch,
+        template,
+    )
+}
+
+/// Clone a repository and create a sandbox for it
+fn cmd_clone(
+    url: &str,
+    dir: Option<PathBuf>,
+    tool_override: Option<String>,
+    read_only: bool,
+    template: Option<String>,
+) -> Result<()> {
+    // Fail before cloning rather than after
+    check_docker()?;
+    check_docker_sandbox()?;
+
+    let dir = match dir {
+        Some(dir) => dir,
+        None => {
+            let name = repo_name_from_url(url).with_context(|| {
+                format!("Can't tell the repository name from '{}'; pass --dir", url)
+            })?;
+            Config::load()?.projects_dir()?.join(name)
+        }
+    };
+    if dir.exists() {
+        bail!(
+            "{} already exists. Pass --dir to clone elsewhere, or run 'sandy new' in it.",
+            dir.display()
+        );
+    }
+
+    println!("Cloning {} into {}...", url, dir.display());
+    clone_repo(url, &dir)?;
+    let workspace_path = dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", dir.display()))?;
+
+    new_sandbox(
+        workspace_path,
+        tool_override,
+        read_only,
+        Vec::new(),
+        None,
+        None,
+        template,
+    )
+}
+
+/// Create and start a sandbox for `workspace_path`
+fn new_sandbox(
+    workspace_path: PathBuf,
+    tool_override: Option<String>,
+    read_only: bool,
+    repos: Vec<PathBuf>,
+    name: Option<String>,
+    branch: Option<String>,
+    template: Option<String>,
+) -> Result<()> {
     // Check Docker availability
     check_docker()?;
     check_docker_sandbox()?;
 
     // Load configuration
     let mut config = Config::load()?;
-    let mut state = State::load()?;
 
     // Resolve tool: flag overrides config default
     let tool = tool_override.unwrap_or_else(|| config.default_tool.clone());
 
-    // Get current workspace (git root or current directory)
-    let cwd = env::current_dir().context("Failed to get current directory")?;
-    let workspace_path = get_workspace_path(&cwd);
-    let workspace_key = workspace_path.to_string_lossy().to_string();
-    let workspace_name = get_repo_name(&workspace_path);
+    let name = name.or_else(|| branch.as_deref().map(branch_sandbox_name));
+    let workspace_key = sandbox_key(&workspace_path, name.as_deref());
+    let workspace_name = display_name(&workspace_path, name.as_deref());
 
     // Check if sandbox already exists for this workspace
-    if state.sandboxes.contains_key(&workspace_key) {
+    check_no_sandbox(&State::load()?, &workspace_key, &workspace_name)?;
+
+    // Repositories from the command line replace those linked in the config
+    let repos = if repos.is_empty() {
+        config.linked_repos_for(&workspace_path)?
+    } else {
+        repos
+    };
+    let extra_repos = resolve_extra_repos(&workspace_path, &repos)?;
+    let worktrees_dir = config.worktrees_dir()?;
+    let template = match template {
+        Some(template) => Some(template),
+        None => config.template_for(&workspace_path)?,
+    };
+
+    ensure_template(&mut config, template.as_deref())?;
+
+    // Save state with tool info, checking again in case another sandy
+    // created the sandbox while the template was building
+    let info = State::update(|state| {
+        check_no_sandbox(state, &workspace_key, &workspace_name)?;
+        let info = state.add_sandbox(workspace_path.clone(), name.as_deref(), &tool);
+        info.read_only = read_only;
+        info.extra_repos = extra_repos;
+        info.template = template;
+        if let Some(branch) = &branch {
+            let worktree = worktrees_dir.join(info.container_name());
+            add_worktree(&workspace_path, &worktree, branch)?;
+            println!(
+                "Created worktree for branch '{}' at: {}",
+                branch,
+                worktree.display()
+            );
+            info.worktree = Some(worktree);
+            info.branch = Some(branch.clone());
+        }
+        Ok(info.clone())
+    })?;
+
+    println!("Starting sandbox for '{}' with {}...", workspace_name, tool);
+
+    // Start the sandbox
+    run_session(&info, &config, &tool)?;
+
+    Ok(())
+}
+
+/// Repositories linked to `workspace` in the config, resolved for mounting
+fn linked_repos(config: &Config, workspace: &Path) -> Result<Vec<PathBuf>> {
+    resolve_extra_repos(workspace, &config.linked_repos_for(workspace)?)
+}
+
+fn check_no_sandbox(state: &State, workspace_key: &str, workspace_name: &str) -> Result<()> {
+    if state.sandboxes.contains_key(workspace_key) {
         bail!(
-            "Sandbox already exists for '{}'. Use 'sandy resume' to continue.",
+            "Sandbox already exists for '{}'. Use 'sandy resume' to continue, or 'sandy new --name <name>' for another one.",
             workspace_name
         );
     }
+    Ok(())
+}
+
+/// Create, update, and build the template image as needed before starting a
+/// sandbox. A named template is only built, as it has no embedded default.
+fn ensure_template(config: &mut Config, template: Option<&str>) -> Result<()> {
+    if let Some(name) = template {
+        let (dockerfile, image) = named_template(config, name)?;
+        if !template_exists(&image)? || template_needs_rebuild(&dockerfile, Some(name))? {
+            println!("Building sandbox template '{}'...", name);
+            build_template(&dockerfile, &image, config, Some(name))?;
+        }
+        return Ok(());
+    }
 
-    // Handle template - auto-create, update, and build as needed
     let template_name = config
         .template_image
         .clone()
@@ -226,21 +584,21 @@ fn cmd_new(tool_override: Option<String>) -> Result<()> {
                 "Created default Dockerfile at: {}",
                 template_dockerfile.display()
             );
-            build_template(&template_dockerfile, &template_name, &config)?;
+            build_template(&template_dockerfile, &template_name, config, None)?;
         }
         DefaultTemplateStatus::NeedsUpdate => {
             // Embedded default has changed - update user's Dockerfile and rebuild
             println!("Updating sandbox template to latest version...");
             update_dockerfile_from_default(&template_dockerfile, DEFAULT_DOCKERFILE)?;
             println!("Updated Dockerfile at: {}", template_dockerfile.display());
-            build_template(&template_dockerfile, &template_name, &config)?;
+            build_template(&template_dockerfile, &template_name, config, None)?;
         }
         DefaultTemplateStatus::UpToDate | DefaultTemplateStatus::Customized => {
             // Dockerfile is current or customized - only rebuild if needed
-            let needs_build = !image_exists || template_needs_rebuild(&template_dockerfile)?;
+            let needs_build = !image_exists || template_needs_rebuild(&template_dockerfile, None)?;
             if needs_build {
                 println!("Building sandbox template...");
-                build_template(&template_dockerfile, &template_name, &config)?;
+                build_template(&template_dockerfile, &template_name, config, None)?;
             }
         }
     }
@@ -251,15 +609,6 @@ fn cmd_new(tool_override: Option<String>) -> Result<()> {
         config.save()?;
     }
 
-    // Save state with tool info
-    state.add_sandbox(workspace_path.clone(), &tool);
-    state.save()?;
-
-    println!("Starting sandbox for '{}' with {}...", workspace_name, tool);
-
-    // Start the sandbox
-    start_sandbox(&workspace_path, &config, &tool)?;
-
     Ok(())
 }
 
@@ -270,7 +619,7 @@ fn cmd_rebuild_and_new(tool_override: Option<String>) -> Result<()> {
 
     // Load configuration
     let mut config = Config::load()?;
-    let mut state = State::load()?;
+    let state = State::load()?;
 
     // Resolve tool: flag overrides config default
     let tool = tool_override.unwrap_or_else(|| config.default_tool.clone());
@@ -281,188 +630,596 @@ fn cmd_rebuild_and_new(tool_override: Option<String>) -> Result<()> {
     let workspace_key = workspace_path.to_string_lossy().to_string();
     let workspace_name = get_repo_name(&workspace_path);
 
-    // Remove existing sandbox if present
-    if state.sandboxes.contains_key(&workspace_key) {
+    // Remove existing sandbox if present, keeping its read-only mode, extra
+    // repositories, and scratch directory
+    let existing = state.sandboxes.get(&workspace_key);
+    let read_only = existing.is_some_and(|info| info.read_only);
+    let extra_repos = match existing {
+        Some(info) => info.extra_repos.clone(),
+        None => linked_repos(&config, &workspace_path)?,
+    };
+    let template = match existing {
+        Some(info) => info.template.clone(),
+        None => config.template_for(&workspace_path)?,
+    };
+    if let Some(info) = existing {
         println!("Removing existing sandbox for '{}'...", workspace_name);
-        let _ = remove_sandbox(&workspace_path);
-        state.remove_sandbox(&workspace_key);
-        state.save()?;
+        let _ = remove_sandbox(info);
+        State::update(|state| {
+            state.remove_sandbox(&workspace_key);
+            Ok(())
+        })?;
     }
 
     // Get or create template name
-    let template_name = config
-        .template_image
-        .clone()
-        .unwrap_or_else(|| DEFAULT_TEMPLATE_IMAGE.to_string());
-    let template_dockerfile = get_template_dockerfile()?;
-
-    // Check template status and handle updates
-    let template_status = check_default_template_status(&template_dockerfile, DEFAULT_DOCKERFILE)?;
-
-    match template_status {
-        DefaultTemplateStatus::NeedsCreation => {
-            println!("Creating default Dockerfile...");
-            update_dockerfile_from_default(&template_dockerfile, DEFAULT_DOCKERFILE)?;
-            println!(
-                "Created default Dockerfile at: {}",
-                template_dockerfile.display()
-            );
+    let (template_dockerfile, template_name) = match &template {
+        Some(name) => named_template(&config, name)?,
+        None => {
+            let dockerfile = get_template_dockerfile()?;
+            refresh_default_dockerfile(&dockerfile)?;
+            (dockerfile, template_image_name(&config, None))
         }
-        DefaultTemplateStatus::NeedsUpdate => {
-            println!("Updating Dockerfile to latest default...");
-            update_dockerfile_from_default(&template_dockerfile, DEFAULT_DOCKERFILE)?;
-            println!("Updated Dockerfile at: {}", template_dockerfile.display());
-        }
-        DefaultTemplateStatus::UpToDate => {
-            // Nothing to do, Dockerfile is current
-        }
-        DefaultTemplateStatus::Customized => {
-            // Check if new default is available and warn
-            if new_default_available(DEFAULT_DOCKERFILE)? {
-                println!("Note: A new default Dockerfile template is available.");
-                println!(
-                    "Your Dockerfile has been customized, so it was not updated automatically."
-                );
-                println!(
-                    "Run 'sandy update --force' to update (your current file will be backed up)."
-                );
-                println!();
-            }
-        }
-    }
+    };
 
     // Force rebuild template (no cache)
     println!("Rebuilding template (ignoring Docker cache)...");
-    build_template_no_cache(&template_dockerfile, &template_name, &config)?;
-
-    // Update config with template_image if not already set
-    if config.template_image.is_none() {
+    build_template_no_cache(
+        &template_dockerfile,
+        &template_name,
+        &config,
+        template.as_deref(),
+    )?;
+
+    // Update config with template_image if not already set (named templates
+    // derive their image name from it)
+    if template.is_none() && config.template_image.is_none() {
         config.template_image = Some(template_name);
         config.save()?;
     }
 
     // Save state with tool info
-    state.add_sandbox(workspace_path.clone(), &tool);
-    state.save()?;
+    let info = State::update(|state| {
+        let info = state.add_sandbox(workspace_path.clone(), None, &tool);
+        info.read_only = read_only;
+        info.extra_repos = extra_repos;
+        info.template = template;
+        Ok(info.clone())
+    })?;
 
     println!("Starting sandbox for '{}' with {}...", workspace_name, tool);
 
     // Start the sandbox
-    start_sandbox(&workspace_path, &config, &tool)?;
+    run_session(&info, &config, &tool)?;
 
     Ok(())
 }
 
-fn cmd_resume() -> Result<()> {
+fn cmd_resume(name: Option<String>, tool_override: Option<String>) -> Result<()> {
     check_docker()?;
     check_docker_sandbox()?;
 
     let config = Config::load()?;
     let state = State::load()?;
 
-    // Try to auto-select sandbox for current working directory
-    if let Ok(cwd) = env::current_dir() {
+    let mut entries = get_sandbox_entries(&state)?;
+    stop_idle_sandboxes(&config, &mut entries);
+
+    // Try to auto-select among the sandboxes for the current working directory
+    if name.is_none()
+        && let Ok(cwd) = env::current_dir()
+    {
         let workspace_path = get_workspace_path(&cwd);
-        let workspace_key = workspace_path.to_string_lossy().to_string();
-        if let Some(info) = state.sandboxes.get(&workspace_key) {
-            let repo_name = get_repo_name(&info.path);
-            // Use stored tool, or fall back to config default for legacy sandboxes
-            let tool = info
-                .tool
-                .clone()
-                .unwrap_or_else(|| config.default_tool.clone());
-            println!("Resuming sandbox '{}' with {}...", repo_name, tool);
-            start_sandbox(&info.path, &config, &tool)?;
-            return Ok(());
+        let local: Vec<SelectionEntry> = entries
+            .iter()
+            .filter(|e| e.info.is_for(&workspace_path))
+            .cloned()
+            .collect();
+        match local.as_slice() {
+            [] => {}
+            [entry] => return resume_entry(entry, &config, tool_override),
+            _ => {
+                return match prompt_selection(&local)? {
+                    Some(entry) => resume_entry(entry, &config, tool_override),
+                    None => Ok(()),
+                };
+            }
         }
     }
 
     // Fall back to interactive selection
-    let entries = get_sandbox_entries(&state)?;
     if entries.is_empty() {
         println!("No sandboxes found. Create one with 'sandy new'");
         return Ok(());
     }
 
-    let entry = match prompt_selection(&entries)? {
-        Some(e) => e,
-        None => return Ok(()),
+    let entry = match &name {
+        Some(name) => find_entry(&entries, name)?,
+        None => match prompt_selection(&entries)? {
+            Some(e) => e,
+            None => return Ok(()),
+        },
     };
 
-    // Use stored tool, or fall back to config default for legacy sandboxes
-    let tool = entry
-        .info
-        .tool
+    resume_entry(entry, &config, tool_override)
+}
+
+fn resume_entry(
+    entry: &SelectionEntry,
+    config: &Config,
+    tool_override: Option<String>,
+) -> Result<()> {
+    // Use the flag, then the stored tool, then the config default for legacy sandboxes
+    let tool = tool_override
         .clone()
+        .or_else(|| entry.info.tool.clone())
         .unwrap_or_else(|| config.default_tool.clone());
 
+    State::update(|state| {
+        if let Some(tool) = &tool_override {
+            state.set_tool(&entry.key, tool);
+        }
+        state.touch_sandbox(&entry.key);
+        Ok(())
+    })?;
+
     // Docker Sandbox handles reconnection automatically - just call run again
     println!("Resuming sandbox '{}' with {}...", entry.name, tool);
-    start_sandbox(&entry.info.path, &config, &tool)?;
+    run_session(&entry.info, config, &tool)?;
 
     Ok(())
 }
 
+/// Run the sandbox's CLI tool interactively and record how the session ended
+fn run_session(info: &SandboxInfo, config: &Config, tool: &str) -> Result<()> {
+    let started_at = Utc::now();
+    let code = start_sandbox(info, config, tool)?;
+    record_session(info, Session::ended(tool, started_at, code, false))?;
+
+    if code != 0 {
+        bail!("Sandbox exited with error");
+    }
+    Ok(())
+}
+
+/// Save a finished session in the sandbox's history
+fn record_session(info: &SandboxInfo, session: Session) -> Result<()> {
+    State::update(|state| {
+        state.record_session(&info.key(), session);
+        Ok(())
+    })
+}
+
+/// Run an agent headlessly and return its exit status
+fn cmd_run(
+    repo: Option<PathBuf>,
+    tool_override: Option<String>,
+    prompt: &str,
+    timeout: Option<Duration>,
+    output: Option<PathBuf>,
+    read_only: bool,
+) -> Result<i32> {
+    check_docker()?;
+    check_docker_sandbox()?;
+
+    let mut config = Config::load()?;
+    let state = State::load()?;
+
+    let workspace_path = get_workspace_path(&resolve_repo(repo)?);
+    let workspace_key = workspace_path.to_string_lossy().to_string();
+    let workspace_name = get_repo_name(&workspace_path);
+
+    // Reuse an existing sandbox with its mode; otherwise create a new one
+    let existing = state.sandboxes.get(&workspace_key);
+    let (tool, read_only) = match existing {
+        Some(info) => {
+            let tool = tool_override
+                .or_else(|| info.tool.clone())
+                .unwrap_or_else(|| config.default_tool.clone());
+            if read_only && !info.read_only {
+                bail!(
+                    "Sandbox for '{}' already exists with a writable repository; remove it to run read-only",
+                    workspace_name
+                );
+            }
+            (tool, info.read_only)
+        }
+        None => (
+            tool_override.unwrap_or_else(|| config.default_tool.clone()),
+            read_only,
+        ),
+    };
+    let extra_repos = match existing {
+        Some(_) => Vec::new(),
+        None => linked_repos(&config, &workspace_path)?,
+    };
+    let template = match existing {
+        Some(info) => info.template.clone(),
+        None => config.template_for(&workspace_path)?,
+    };
+    let is_new = existing.is_none();
+
+    ensure_template(&mut config, template.as_deref())?;
+
+    let info = State::update(|state| {
+        if is_new && !state.sandboxes.contains_key(&workspace_key) {
+            let info = state.add_sandbox(workspace_path.clone(), None, &tool);
+            info.read_only = read_only;
+            info.extra_repos = extra_repos;
+            info.template = template;
+        }
+        state.touch_sandbox(&workspace_key);
+        state
+            .sandboxes
+            .get(&workspace_key)
+            .cloned()
+            .context("Sandbox disappeared from state")
+    })?;
+
+    let log = match output {
+        Some(path) => path,
+        None => default_log_path(&info)?,
+    };
+
+    eprintln!(
+        "Running {} in sandbox '{}' (output: {})",
+        tool,
+        workspace_name,
+        log.display()
+    );
+
+    let started_at = Utc::now();
+    let outcome = run_sandbox_task(&info, &config, &tool, prompt, &log, timeout)?;
+    record_session(
+        &info,
+        Session::ended(&tool, started_at, outcome.exit_code(), true),
+    )?;
+
+    match outcome {
+        RunOutcome::TimedOut => eprintln!(
+            "Timed out after {}s; sandbox stopped",
+            timeout.unwrap_or_default().as_secs()
+        ),
+        RunOutcome::Exited(code) => eprintln!("{} exited with status {}", tool, code),
+    }
+
+    Ok(outcome.exit_code())
+}
+
 fn cmd_list() -> Result<()> {
+    let config = Config::load()?;
     let state = State::load()?;
-    let entries = get_sandbox_entries(&state)?;
+    let mut entries = get_sandbox_entries(&state)?;
+    stop_idle_sandboxes(&config, &mut entries);
 
     display_sandbox_list(&entries);
 
     Ok(())
 }
 
-fn cmd_remove() -> Result<()> {
-    let mut state = State::load()?;
+fn cmd_usage() -> Result<()> {
+    let state = State::load()?;
+    let entries = get_sandbox_entries(&state)?;
+    if entries.is_empty() {
+        println!("No sandboxes found.");
+        return Ok(());
+    }
+
+    check_docker()?;
+
+    let usages = collect_usage(entries)?;
+    // Volume sizes are best-effort; `docker system df -v` can be slow or unavailable
+    let volumes = volume_sizes().unwrap_or_default();
+    display_usage(&usages, &volumes);
+
+    Ok(())
+}
+
+fn cmd_prune(force: bool, dry_run: bool) -> Result<()> {
+    check_docker()?;
+
+    let config = Config::load()?;
+    let state = State::load()?;
+    let entries = get_sandbox_entries(&state)?;
+    let orphans = Orphans::find(
+        entries,
+        &state.templates,
+        &Config::template_names()?,
+        |name| template_image_name(&config, Some(name)),
+    )?;
+
+    if orphans.is_empty() {
+        println!("Nothing to prune.");
+        return Ok(());
+    }
+
+    print!("{}", orphans.format());
+    if dry_run {
+        println!("Dry run, nothing removed.");
+        return Ok(());
+    }
+    if !force && !confirm("Remove these?")? {
+        return Ok(());
+    }
+
+    // Containers go first, so the images they were created from can be removed
+    let mut failures = 0;
+    let mut report = |what: &str, result: Result<()>| {
+        if let Err(e) = result {
+            eprintln!("Failed to remove {}: {}", what, e);
+            failures += 1;
+        }
+    };
+    for entry in &orphans.sandboxes {
+        report(&entry.name, remove_sandbox(&entry.info));
+        if entry.info.read_only {
+            report(&entry.name, remove_scratch_dir(&entry.info));
+        }
+        // git can't remove the worktree without its repository
+        if let Some(worktree) = &entry.info.worktree
+            && worktree.exists()
+        {
+            report(
+                &worktree.display().to_string(),
+                std::fs::remove_dir_all(worktree).map_err(Into::into),
+            );
+        }
+    }
+    for container in &orphans.containers {
+        report(container, remove_container(container));
+    }
+    for (_, image) in &orphans.templates {
+        report(image, remove_image(image));
+    }
+    for image in &orphans.images {
+        report(&image.id, remove_image(&image.id));
+    }
+
+    State::update(|state| {
+        for entry in &orphans.sandboxes {
+            state.remove_sandbox(&entry.key);
+        }
+        for (name, _) in &orphans.templates {
+            state.templates.remove(name);
+        }
+        Ok(())
+    })?;
+
+    if failures > 0 {
+        bail!("{} item(s) could not be removed", failures);
+    }
+    println!("Pruned.");
+    Ok(())
+}
+
+/// Run a command in a sandbox and return its exit status
+fn cmd_exec(name: Option<String>, command: &[String]) -> Result<i32> {
+    check_docker()?;
+
+    let state = State::load()?;
+    let entries = get_sandbox_entries(&state)?;
+    if entries.is_empty() {
+        bail!("No sandboxes found. Create one with 'sandy new'");
+    }
+
+    let entry = match &name {
+        Some(name) => find_entry(&entries, name)?.clone(),
+        None => {
+            // Prefer the sandboxes for the current working directory
+            let cwd = env::current_dir().context("Failed to get current directory")?;
+            let workspace_path = get_workspace_path(&cwd);
+            let local: Vec<SelectionEntry> = entries
+                .iter()
+                .filter(|e| e.info.is_for(&workspace_path))
+                .cloned()
+                .collect();
+            let candidates = if local.is_empty() { &entries } else { &local };
+            let selected = match candidates.as_slice() {
+                [entry] => Some(entry),
+                _ => prompt_selection(candidates)?,
+            };
+            match selected {
+                Some(entry) => entry.clone(),
+                None => return Ok(0),
+            }
+        }
+    };
+
+    exec_in_sandbox(&entry.info, command)
+}
+
+fn cmd_stop(name: Option<String>) -> Result<()> {
+    let state = State::load()?;
+
+    let entries = get_sandbox_entries(&state)?;
+    let entry = match &name {
+        Some(name) => find_entry(&entries, name)?,
+        // Interactive selection among the running sandboxes
+        None => {
+            let running: Vec<SelectionEntry> = entries
+                .into_iter()
+                .filter(|e| e.status == SandboxStatus::Running)
+                .collect();
+            if running.is_empty() {
+                println!("No running sandboxes.");
+                return Ok(());
+            }
+            return match prompt_selection(&running)? {
+                Some(entry) => stop_entry(entry),
+                None => Ok(()),
+            };
+        }
+    };
+
+    stop_entry(entry)
+}
+
+fn stop_entry(entry: &SelectionEntry) -> Result<()> {
+    if entry.status != SandboxStatus::Running {
+        println!("Sandbox '{}' is not running.", entry.name);
+        return Ok(());
+    }
+
+    stop_sandbox(&entry.info)?;
+    println!("Sandbox '{}' stopped.", entry.name);
+
+    Ok(())
+}
+
+/// Stop running sandboxes whose last session ended more than
+/// `auto_stop_minutes` ago, marking them stopped in `entries`
+fn stop_idle_sandboxes(config: &Config, entries: &mut [SelectionEntry]) {
+    let Some(minutes) = config.auto_stop_minutes else {
+        return;
+    };
+    let now = Utc::now();
+    let cutoff = now - chrono::Duration::minutes(minutes as i64);
+
+    for entry in entries {
+        if entry.status != SandboxStatus::Running {
+            continue;
+        }
+        let Some(idle_since) = entry.info.idle_since() else {
+            continue;
+        };
+        if idle_since > cutoff {
+            continue;
+        }
+        match stop_sandbox(&entry.info) {
+            Ok(()) => {
+                println!(
+                    "Stopped idle sandbox '{}' (last session ended {})",
+                    entry.name,
+                    format_age(now, idle_since)
+                );
+                entry.status = SandboxStatus::Stopped;
+            }
+            Err(e) => eprintln!(
+                "Warning: failed to stop idle sandbox '{}': {}",
+                entry.name, e
+            ),
+        }
+    }
+}
+
+fn cmd_remove(name: Option<String>) -> Result<()> {
+    let state = State::load()?;
 
-    // Interactive selection
     let entries = get_sandbox_entries(&state)?;
     if entries.is_empty() {
         println!("No sandboxes found.");
         return Ok(());
     }
 
-    let entry = match prompt_selection(&entries)? {
-        Some(e) => e,
-        None => return Ok(()),
+    let entry = match &name {
+        Some(name) => find_entry(&entries, name)?,
+        // Interactive selection
+        None => match prompt_selection(&entries)? {
+            Some(e) => e,
+            None => return Ok(()),
+        },
     };
 
-    if !confirm(&format!("Remove sandbox for '{}'?", entry.name))? {
+    let prompt = if entry.info.read_only {
+        format!(
+            "Remove sandbox for '{}'? Its scratch directory will be deleted.",
+            entry.name
+        )
+    } else if let Some(worktree) = &entry.info.worktree {
+        format!(
+            "Remove sandbox for '{}'? Its worktree at {} will be deleted, including uncommitted changes (the branch is kept).",
+            entry.name,
+            worktree.display()
+        )
+    } else {
+        format!("Remove sandbox for '{}'?", entry.name)
+    };
+    if !confirm(&prompt)? {
         return Ok(());
     }
 
     // Remove Docker sandbox
     println!("Removing sandbox container...");
-    let _ = remove_sandbox(&entry.info.path);
+    let _ = remove_sandbox(&entry.info);
+    if entry.info.read_only {
+        remove_scratch_dir(&entry.info)?;
+    }
+    if let Some(worktree) = &entry.info.worktree {
+        println!("Removing worktree...");
+        remove_worktree(&entry.info.path, worktree)?;
+    }
 
     // Remove from state
-    state.remove_sandbox(&entry.key);
-    state.save()?;
+    State::update(|state| {
+        state.remove_sandbox(&entry.key);
+        Ok(())
+    })?;
 
     println!("Sandbox '{}' removed.", entry.name);
 
     Ok(())
 }
 
-fn cmd_build(force: bool) -> Result<()> {
+fn cmd_build(
+    force: bool,
+    build_args: Vec<(String, String)>,
+    template: Option<String>,
+) -> Result<()> {
     check_docker()?;
 
     let mut config = Config::load()?;
 
+    // Command-line build args apply to this build only and are not saved
+    let mut build_config = config.clone();
+    build_config.build_args.extend(build_args);
+
     // Get or create template name
-    let template_name = config
-        .template_image
-        .clone()
-        .unwrap_or_else(|| DEFAULT_TEMPLATE_IMAGE.to_string());
-    let template_dockerfile = get_template_dockerfile()?;
+    let (template_dockerfile, template_name) = match &template {
+        Some(name) => named_template(&config, name)?,
+        None => {
+            let dockerfile = get_template_dockerfile()?;
+            refresh_default_dockerfile(&dockerfile)?;
+            (dockerfile, template_image_name(&config, None))
+        }
+    };
 
-    // Check template status and handle updates
-    let template_status = check_default_template_status(&template_dockerfile, DEFAULT_DOCKERFILE)?;
+    // Build the template
+    if force {
+        println!("Force rebuilding template (ignoring Docker cache)...");
+        build_template_no_cache(
+            &template_dockerfile,
+            &template_name,
+            &build_config,
+            template.as_deref(),
+        )?;
+    } else {
+        println!("Building template...");
+        build_template(
+            &template_dockerfile,
+            &template_name,
+            &build_config,
+            template.as_deref(),
+        )?;
+    }
+
+    // Update config with template_image if not already set (named templates
+    // derive their image name from it)
+    if template.is_none() && config.template_image.is_none() {
+        config.template_image = Some(template_name);
+        config.save()?;
+    }
+
+    Ok(())
+}
+
+/// Create or update the default Dockerfile from the embedded default, or
+/// note that a newer default is available for a customized one
+fn refresh_default_dockerfile(template_dockerfile: &Path) -> Result<()> {
+    let template_status = check_default_template_status(template_dockerfile, DEFAULT_DOCKERFILE)?;
 
     match template_status {
         DefaultTemplateStatus::NeedsCreation => {
             println!("Creating default Dockerfile...");
-            update_dockerfile_from_default(&template_dockerfile, DEFAULT_DOCKERFILE)?;
+            update_dockerfile_from_default(template_dockerfile, DEFAULT_DOCKERFILE)?;
             println!(
                 "Created default Dockerfile at: {}",
                 template_dockerfile.display()
@@ -470,7 +1227,7 @@ fn cmd_build(force: bool) -> Result<()> {
         }
         DefaultTemplateStatus::NeedsUpdate => {
             println!("Updating Dockerfile to latest default...");
-            update_dockerfile_from_default(&template_dockerfile, DEFAULT_DOCKERFILE)?;
+            update_dockerfile_from_default(template_dockerfile, DEFAULT_DOCKERFILE)?;
             println!("Updated Dockerfile at: {}", template_dockerfile.display());
         }
         DefaultTemplateStatus::UpToDate => {
@@ -491,21 +1248,6 @@ fn cmd_build(force: bool) -> Result<()> {
         }
     }
 
-    // Build the template
-    if force {
-        println!("Force rebuilding template (ignoring Docker cache)...");
-        build_template_no_cache(&template_dockerfile, &template_name, &config)?;
-    } else {
-        println!("Building template...");
-        build_template(&template_dockerfile, &template_name, &config)?;
-    }
-
-    // Update config with template_image if not already set
-    if config.template_image.is_none() {
-        config.template_image = Some(template_name);
-        config.save()?;
-    }
-
     Ok(())
 }
 
@@ -560,8 +1302,8 @@ fn cmd_config(action: ConfigAction) -> Result<()> {
             println!("{:-<60}", "");
             println!("{}", toml_str);
         }
-        ConfigAction::Dockerfile => {
-            let dockerfile_path = get_template_dockerfile()?;
+        ConfigAction::Dockerfile { template } => {
+            let dockerfile_path = template_dockerfile(template.as_deref())?;
             println!("Dockerfile path: {}", dockerfile_path.display());
             println!("{:-<60}", "");
 
@@ -572,10 +1314,20 @@ fn cmd_config(action: ConfigAction) -> Result<()> {
             } else {
                 println!("(Dockerfile does not exist yet)");
                 println!();
-                println!(
-                    "A default Dockerfile will be created automatically when you run 'sandy new'."
-                );
-                println!("To create it now for customization, run: sandy config create-dockerfile");
+                match template {
+                    Some(name) => println!(
+                        "To create it, run: sandy config create-dockerfile --template {}",
+                        name
+                    ),
+                    None => {
+                        println!(
+                            "A default Dockerfile will be created automatically when you run 'sandy new'."
+                        );
+                        println!(
+                            "To create it now for customization, run: sandy config create-dockerfile"
+                        );
+                    }
+                }
             }
         }
         ConfigAction::Set { key, value } => {
@@ -583,8 +1335,16 @@ fn cmd_config(action: ConfigAction) -> Result<()> {
 
             match key.as_str() {
                 "template_image" => config.template_image = Some(value),
+                "worktrees_dir" => config.worktrees_dir = Some(value),
+                "projects_dir" => config.projects_dir = Some(value),
+                "auto_stop_minutes" => {
+                    let minutes = value
+                        .parse()
+                        .with_context(|| format!("Invalid number of minutes: {}", value))?;
+                    config.auto_stop_minutes = Some(minutes);
+                }
                 _ => bail!(
-                    "Unknown configuration key: {}. Valid keys: template_image",
+                    "Unknown configuration key: {}. Valid keys: template_image, worktrees_dir, projects_dir, auto_stop_minutes",
                     key
                 ),
             }
@@ -592,8 +1352,8 @@ fn cmd_config(action: ConfigAction) -> Result<()> {
             config.save()?;
             println!("Configuration updated.");
         }
-        ConfigAction::CreateDockerfile => {
-            let template_path = get_template_dockerfile()?;
+        ConfigAction::CreateDockerfile { template } => {
+            let template_path = template_dockerfile(template.as_deref())?;
 
             if template_path.exists() && !confirm("Template Dockerfile already exists. Overwrite?")?
             {
@@ -612,7 +1372,39 @@ fn cmd_config(action: ConfigAction) -> Result<()> {
                 template_path.display()
             );
             println!("\nEdit this file to customize your sandbox environment.");
-            println!("Changes will be automatically built on your next 'sandy new'.");
+            match template {
+                Some(name) => println!(
+                    "Use it with 'sandy new --template {}' or repo_templates in sandy.toml.",
+                    name
+                ),
+                None => {
+                    println!("Changes will be automatically built on your next 'sandy new'.")
+                }
+            }
+        }
+        ConfigAction::Templates => {
+            let names = Config::template_names()?;
+            if names.is_empty() {
+                println!("No named templates found.");
+                println!("Create one with: sandy config create-dockerfile --template <name>");
+                return Ok(());
+            }
+
+            let config = Config::load()?;
+            let state = State::load()?;
+            println!("Templates in: {}", Config::templates_dir()?.display());
+            for name in names {
+                let built = match state.templates.get(&name) {
+                    Some(_) => "built",
+                    None => "not built yet",
+                };
+                println!(
+                    "  {} ({}, image {})",
+                    name,
+                    built,
+                    template_image_name(&config, Some(&name))
+                );
+            }
         }
     }
 
@@ -624,5 +1416,40 @@ fn get_template_dockerfile() -> Result<PathBuf> {
     Ok(Config::config_dir()?.join("sandy").join("Dockerfile"))
 }
 
+/// Get the path to the default template's Dockerfile or a named template's
+fn template_dockerfile(template: Option<&str>) -> Result<PathBuf> {
+    match template {
+        Some(name) => Ok(Config::templates_dir()?.join(name).join("Dockerfile")),
+        None => get_template_dockerfile(),
+    }
+}
+
+/// Image name of the default template, or of a named template
+/// (`<template_image>-<name>`)
+fn template_image_name(config: &Config, template: Option<&str>) -> String {
+    let base = config
+        .template_image
+        .as_deref()
+        .unwrap_or(DEFAULT_TEMPLATE_IMAGE);
+    match template {
+        Some(name) => format!("{}-{}", base, name),
+        None => base.to_string(),
+    }
+}
+
+/// Dockerfile path and image name of a named template, which must exist
+fn named_template(config: &Config, name: &str) -> Result<(PathBuf, String)> {
+    let dockerfile = template_dockerfile(Some(name))?;
+    if !dockerfile.exists() {
+        bail!(
+            "Template '{}' not found at {}. Create it with 'sandy config create-dockerfile --template {}'.",
+            name,
+            dockerfile.display(),
+            name
+        );
+    }
+    Ok((dockerfile, template_image_name(config, Some(name))))
+}
+
 /// Default Dockerfile template loaded from template/Dockerfile at compile time
 const DEFAULT_DOCKERFILE: &str = include_str!("../template/Dockerfile");
@@ -0,0 +1,238 @@
+use anyhow::{Context, Result, bail};
+use std::collections::{HashMap, HashSet};
+use std::process::Command;
+
+use crate::docker::{SANDBOX_LABEL, TEMPLATE_LABEL};
+use crate::interactive::SelectionEntry;
+use crate::state::TemplateBuild;
+
+/// An image left untagged when its template was rebuilt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DanglingImage {
+    pub id: String,
+    pub created: String,
+    pub size: String,
+}
+
+/// Docker resources and state entries that no longer belong to anything
+#[derive(Debug, Default)]
+pub struct Orphans {
+    /// Sandboxes whose repository has been deleted
+    pub sandboxes: Vec<SelectionEntry>,
+    /// Containers sandy created that have no sandbox in the state file
+    pub containers: Vec<String>,
+    /// Named templates whose Dockerfile has been deleted, with their image name
+    pub templates: Vec<(String, String)>,
+    /// Template images replaced by a rebuild
+    pub images: Vec<DanglingImage>,
+}
+
+impl Orphans {
+    /// Find orphans among the sandboxes in the state file and what Docker has.
+    ///
+    /// `template_names` are the templates that still have a Dockerfile, and
+    /// `template_image` gives the image name of a named template.
+    pub fn find(
+        entries: Vec<SelectionEntry>,
+        templates: &HashMap<String, TemplateBuild>,
+        template_names: &[String],
+        template_image: impl Fn(&str) -> String,
+    ) -> Result<Self> {
+        let known: HashSet<String> = entries.iter().map(|e| e.info.container_name()).collect();
+        let containers = unknown_containers(sandy_containers()?, &known);
+
+        let sandboxes = entries
+            .into_iter()
+            .filter(|entry| !entry.info.path.exists())
+            .collect();
+
+        let mut templates: Vec<(String, String)> = templates
+            .keys()
+            .filter(|name| !template_names.contains(name))
+            .map(|name| (name.clone(), template_image(name)))
+            .collect();
+        templates.sort();
+
+        Ok(Self {
+            sandboxes,
+            containers,
+            templates,
+            images: dangling_images()?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sandboxes.is_empty()
+            && self.containers.is_empty()
+            && self.templates.is_empty()
+            && self.images.is_empty()
+    }
+
+    pub fn format(&self) -> String {
+        let mut output = String::new();
+        if !self.sandboxes.is_empty() {
+            output.push_str(&format!(
+                "Sandboxes whose repository no longer exists ({}):\n",
+                self.sandboxes.len()
+            ));
+            for entry in &self.sandboxes {
+                output.push_str(&format!(
+                    "  {} ({})\n",
+                    entry.name,
+                    entry.info.path.display()
+                ));
+            }
+        }
+        if !self.containers.is_empty() {
+            output.push_str(&format!(
+                "Containers with no sandbox ({}):\n",
+                self.containers.len()
+            ));
+            for container in &self.containers {
+                output.push_str(&format!("  {}\n", container));
+            }
+        }
+        if !self.templates.is_empty() {
+            output.push_str(&format!(
+                "Templates whose Dockerfile was deleted ({}):\n",
+                self.templates.len()
+            ));
+            for (name, image) in &self.templates {
+                output.push_str(&format!("  {} ({})\n", name, image));
+            }
+        }
+        if !self.images.is_empty() {
+            output.push_str(&format!(
+                "Template images replaced by a rebuild ({}):\n",
+                self.images.len()
+            ));
+            for image in &self.images {
+                output.push_str(&format!(
+                    "  {}  {}  {}\n",
+                    image.id, image.created, image.size
+                ));
+            }
+        }
+        output
+    }
+}
+
+/// Sandy's containers that aren't in `known`, sorted
+fn unknown_containers(containers: Vec<String>, known: &HashSet<String>) -> Vec<String> {
+    let mut unknown: Vec<String> = containers
+        .into_iter()
+        .filter(|name| !known.contains(name))
+        .collect();
+    unknown.sort();
+    unknown
+}
+
+/// Names of every container, running or not, that sandy created
+fn sandy_containers() -> Result<Vec<String>> {
+    let output = docker(&[
+        "ps",
+        "-a",
+        "--filter",
+        &format!("label={}", SANDBOX_LABEL),
+        "--format",
+        "{{.Names}}",
+    ])?;
+    Ok(output.lines().map(|line| line.trim().to_string()).collect())
+}
+
+/// Untagged images built by sandy
+fn dangling_images() -> Result<Vec<DanglingImage>> {
+    let output = docker(&[
+        "images",
+        "--filter",
+        "dangling=true",
+        "--filter",
+        &format!("label={}", TEMPLATE_LABEL),
+        "--format",
+        "{{.ID}}\t{{.CreatedSince}}\t{{.Size}}",
+    ])?;
+    Ok(parse_images(&output))
+}
+
+fn parse_images(output: &str) -> Vec<DanglingImage> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            Some(DanglingImage {
+                id: parts.next()?.to_string(),
+                created: parts.next()?.to_string(),
+                size: parts.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Remove a container, whether or not it is running
+pub fn remove_container(name: &str) -> Result<()> {
+    docker(&["rm", "-f", name]).map(|_| ())
+}
+
+/// Remove an image by ID or name
+pub fn remove_image(image: &str) -> Result<()> {
+    docker(&["rmi", image]).map(|_| ())
+}
+
+fn docker(args: &[&str]) -> Result<String> {
+    let output = Command::new("docker")
+        .args(args)
+        .output()
+        .context("Failed to run docker")?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_containers() {
+        let known: HashSet<String> = ["sandy-app-abc123".to_string()].into();
+        let containers = vec![
+            "sandy-old-def456".to_string(),
+            "sandy-app-abc123".to_string(),
+            "sandy-gone-000111".to_string(),
+        ];
+        assert_eq!(
+            unknown_containers(containers, &known),
+            vec!["sandy-gone-000111", "sandy-old-def456"]
+        );
+    }
+
+    #[test]
+    fn test_parse_images() {
+        let images = parse_images("1a2b3c4d5e6f\t3 weeks ago\t1.2GB\nbad line\n");
+        assert_eq!(
+            images,
+            vec![DanglingImage {
+                id: "1a2b3c4d5e6f".to_string(),
+                created: "3 weeks ago".to_string(),
+                size: "1.2GB".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        let orphans = Orphans {
+            containers: vec!["sandy-old-def456".to_string()],
+            templates: vec![("rust".to_string(), "sandy-dev-rust".to_string())],
+            ..Default::default()
+        };
+        assert_eq!(
+            orphans.format(),
+            "Containers with no sandbox (1):\n  sandy-old-def456\n\
+             Templates whose Dockerfile was deleted (1):\n  rust (sandy-dev-rust)\n"
+        );
+        assert!(!orphans.is_empty());
+        assert!(Orphans::default().is_empty());
+    }
+}
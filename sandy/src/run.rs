@@ -0,0 +1,213 @@
+use anyhow::{Context, Result, bail};
+use chrono::Local;
+use std::path::PathBuf;
+use std::process::Child;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::state::SandboxInfo;
+
+/// How often to check whether a headless run has finished
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Exit status reported when a run is stopped by `--timeout`, matching timeout(1)
+pub const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// How a headless run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// The agent exited with this status (signals are reported as 128 + signal)
+    Exited(i32),
+    TimedOut,
+}
+
+impl RunOutcome {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Exited(code) => code,
+            Self::TimedOut => TIMEOUT_EXIT_CODE,
+        }
+    }
+}
+
+/// Parse a timeout such as `90s`, `30m`, `2h`, or `1h30m`; a bare number is seconds
+pub fn parse_timeout(s: &str) -> std::result::Result<Duration, String> {
+    let invalid = || format!("invalid timeout '{}' (expected e.g. 90s, 30m, 1h30m)", s);
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(invalid());
+    }
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            _ => return Err(invalid()),
+        };
+        let value: u64 = digits.parse().map_err(|_| invalid())?;
+        total += value * unit;
+        digits.clear();
+    }
+    if !digits.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(Duration::from_secs(total))
+}
+
+/// Format a duration the way `--timeout` accepts it, e.g. `42m` or `1h5m`
+pub fn format_duration(secs: u64) -> String {
+    let (hours, minutes) = (secs / 3600, secs % 3600 / 60);
+    match (hours, minutes) {
+        (0, 0) => format!("{}s", secs),
+        (0, _) => format!("{}m", minutes),
+        (_, 0) => format!("{}h", hours),
+        _ => format!("{}h{}m", hours, minutes),
+    }
+}
+
+/// Tool command line for running a single task without a terminal
+pub fn headless_tool_args(tool: &str, prompt: &str) -> Vec<String> {
+    let args: &[&str] = match tool {
+        "claude" => &["claude", "--dangerously-skip-permissions", "-p"],
+        "gemini" => &["gemini", "--yolo", "-p"],
+        "codex" => &["codex", "exec", "--full-auto"],
+        // Custom tools get the prompt as their only argument
+        _ => &[],
+    };
+
+    let mut cmd: Vec<String> = if args.is_empty() {
+        vec![tool.to_string()]
+    } else {
+        args.iter().map(|a| a.to_string()).collect()
+    };
+    cmd.push(prompt.to_string());
+    cmd
+}
+
+/// Default output file for a run: `<config>/sandy/runs/<container>-<timestamp>.log`
+pub fn default_log_path(sandbox: &SandboxInfo) -> Result<PathBuf> {
+    Ok(Config::config_dir()?
+        .join("sandy")
+        .join("runs")
+        .join(format!(
+            "{}-{}.log",
+            sandbox.container_name(),
+            Local::now().format("%Y%m%d-%H%M%S")
+        )))
+}
+
+/// Wait for a child process, giving up once `timeout` has passed
+pub fn wait_with_timeout(child: &mut Child, timeout: Option<Duration>) -> Result<RunOutcome> {
+    let Some(timeout) = timeout else {
+        let status = child.wait().context("Failed to wait for sandbox")?;
+        return Ok(RunOutcome::Exited(exit_code(status)));
+    };
+
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait().context("Failed to wait for sandbox")? {
+            return Ok(RunOutcome::Exited(exit_code(status)));
+        }
+        if start.elapsed() >= timeout {
+            return Ok(RunOutcome::TimedOut);
+        }
+        std::thread::sleep(POLL_INTERVAL.min(timeout.saturating_sub(start.elapsed())));
+    }
+}
+
+/// Exit code of a finished process, with signals reported as 128 + signal
+pub fn exit_code(status: std::process::ExitStatus) -> i32 {
+    use std::os::unix::process::ExitStatusExt;
+
+    match (status.code(), status.signal()) {
+        (Some(code), _) => code,
+        (None, Some(signal)) => 128 + signal,
+        (None, None) => 1,
+    }
+}
+
+/// Resolve `--repo` (or the current directory) to an existing directory
+pub fn resolve_repo(repo: Option<PathBuf>) -> Result<PathBuf> {
+    let path = match repo {
+        Some(path) => path,
+        None => std::env::current_dir().context("Failed to get current directory")?,
+    };
+    if !path.is_dir() {
+        bail!("Repository path is not a directory: {}", path.display());
+    }
+    path.canonicalize()
+        .with_context(|| format!("Failed to resolve {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_timeout("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_timeout("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_timeout("2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(parse_timeout("1h30m"), Ok(Duration::from_secs(5400)));
+        assert!(parse_timeout("").is_err());
+        assert!(parse_timeout("30").is_ok());
+        assert!(parse_timeout("m").is_err());
+        assert!(parse_timeout("30x").is_err());
+        assert!(parse_timeout("1h30").is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(59), "59s");
+        assert_eq!(format_duration(42 * 60 + 10), "42m");
+        assert_eq!(format_duration(3600), "1h");
+        assert_eq!(format_duration(3900), "1h5m");
+        assert_eq!(
+            parse_timeout(&format_duration(3900)),
+            Ok(Duration::from_secs(3900))
+        );
+    }
+
+    #[test]
+    fn test_headless_tool_args() {
+        assert_eq!(
+            headless_tool_args("codex", "fix the tests"),
+            vec!["codex", "exec", "--full-auto", "fix the tests"]
+        );
+        assert_eq!(
+            headless_tool_args("claude", "task"),
+            vec!["claude", "--dangerously-skip-permissions", "-p", "task"]
+        );
+        assert_eq!(headless_tool_args("aider", "task"), vec!["aider", "task"]);
+    }
+
+    #[test]
+    fn test_wait_with_timeout() {
+        let mut quick = Command::new("sh").args(["-c", "exit 3"]).spawn().unwrap();
+        assert_eq!(
+            wait_with_timeout(&mut quick, Some(Duration::from_secs(5))).unwrap(),
+            RunOutcome::Exited(3)
+        );
+
+        let mut slow = Command::new("sleep").arg("5").spawn().unwrap();
+        let outcome = wait_with_timeout(&mut slow, Some(Duration::from_millis(100))).unwrap();
+        assert_eq!(outcome, RunOutcome::TimedOut);
+        assert_eq!(outcome.exit_code(), TIMEOUT_EXIT_CODE);
+        slow.kill().unwrap();
+        slow.wait().unwrap();
+    }
+}
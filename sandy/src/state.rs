@@ -2,79 +2,300 @@ use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use crate::config::Config;
+use crate::docker::get_container_name;
+use crate::worktree::get_repo_name;
+
+/// State file written by versions before the rename from `sandbox` to sandy
+const LEGACY_STATE_FILE: &str = "sandbox-state.json";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SandboxInfo {
     /// Full path to the repository
     pub path: PathBuf,
+    /// Name telling this sandbox apart from others for the same repository;
+    /// None for the repository's default sandbox
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     /// When the sandbox was created
     pub created_at: DateTime<Utc>,
     /// CLI tool used for this sandbox (claude, gemini, codex)
     #[serde(default)]
     pub tool: Option<String>,
+    /// When the sandbox was last started or resumed through sandy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// Repository is mounted read-only, with a scratch directory as the workspace
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+    /// Further repositories mounted beside `path`, at their host paths
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_repos: Vec<PathBuf>,
+    /// Linked git worktree mounted as the workspace instead of `path`, for a
+    /// sandbox created with `sandy new --branch`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree: Option<PathBuf>,
+    /// Branch checked out in `worktree`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Named Dockerfile template the sandbox was created from; None for the
+    /// default template
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// The most recent agent sessions, oldest first (see [`MAX_SESSIONS`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sessions: Vec<Session>,
+}
+
+/// Number of agent sessions kept per sandbox
+pub const MAX_SESSIONS: usize = 10;
+
+/// How one run of a sandbox's CLI tool ended
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub tool: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_secs: u64,
+    /// Exit status of the tool (signals are reported as 128 + signal)
+    pub exit_code: i32,
+    /// Run with `sandy run` rather than interactively
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub headless: bool,
 }
 
+impl Session {
+    /// A session that started at `started_at` and has just ended
+    pub fn ended(tool: &str, started_at: DateTime<Utc>, exit_code: i32, headless: bool) -> Self {
+        let duration = Utc::now().signed_duration_since(started_at);
+        Self {
+            tool: tool.to_string(),
+            started_at,
+            duration_secs: duration.num_seconds().max(0) as u64,
+            exit_code,
+            headless,
+        }
+    }
+
+    pub fn ended_at(&self) -> DateTime<Utc> {
+        self.started_at + chrono::Duration::seconds(self.duration_secs as i64)
+    }
+}
+
+impl SandboxInfo {
+    /// State key of the sandbox (see [`sandbox_key`])
+    pub fn key(&self) -> String {
+        sandbox_key(&self.path, self.name.as_deref())
+    }
+
+    /// Name of the sandbox's Docker container
+    pub fn container_name(&self) -> String {
+        get_container_name(&self.path, self.name.as_deref())
+    }
+
+    /// Directory the agent works in: the sandbox's worktree, if it has one,
+    /// otherwise the repository
+    pub fn workspace(&self) -> &Path {
+        self.worktree.as_deref().unwrap_or(&self.path)
+    }
+
+    /// When the sandbox's last agent session ended, or None while a session
+    /// started since then may still be running (or none has ended yet)
+    pub fn idle_since(&self) -> Option<DateTime<Utc>> {
+        let ended = self.sessions.last()?.ended_at();
+        let started = self.last_used_at.unwrap_or(self.created_at);
+        (started <= ended).then_some(ended)
+    }
+
+    /// Whether `workspace` is the sandbox's repository or its worktree
+    pub fn is_for(&self, workspace: &Path) -> bool {
+        self.path == workspace || self.worktree.as_deref() == Some(workspace)
+    }
+
+    /// Name shown to the user (see [`display_name`])
+    pub fn display_name(&self) -> String {
+        display_name(&self.path, self.name.as_deref())
+    }
+}
+
+/// Name of a sandbox as shown to the user: the repository directory name,
+/// followed by `:<name>` for a named sandbox
+pub fn display_name(repo_path: &Path, name: Option<&str>) -> String {
+    let repo = get_repo_name(repo_path);
+    match name {
+        Some(name) => format!("{}:{}", repo, name),
+        None => repo,
+    }
+}
+
+/// State key of a sandbox: the repository path, followed by `#<name>` for a
+/// named sandbox, so a repository's default sandbox keeps its original key
+pub fn sandbox_key(repo_path: &Path, name: Option<&str>) -> String {
+    let path = repo_path.to_string_lossy();
+    match name {
+        Some(name) => format!("{}#{}", path, name),
+        None => path.to_string(),
+    }
+}
+
+/// The last successful build of a named template
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateBuild {
+    /// Hash of the template's Dockerfile when it was built
+    pub dockerfile_hash: String,
+    /// Docker image digest, used to start sandboxes from this exact image
+    pub image_digest: String,
+}
+
+/// Version of the state file format written by this build of sandy
+const SCHEMA_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct State {
-    /// Map of canonical repo path to sandbox info
+    /// State file format version; 0 for files written before it was recorded
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Map of sandbox key (see [`sandbox_key`]) to sandbox info; a repository
+    /// can have a default sandbox and any number of named ones
     /// Alias "worktrees" for backwards compatibility with pre-v0.2.0 state files
     #[serde(alias = "worktrees")]
     pub sandboxes: HashMap<String, SandboxInfo>,
+    /// Last build of each named template, keyed by template name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub templates: HashMap<String, TemplateBuild>,
+    /// Fields this version doesn't know about, kept so that saving a state
+    /// file written by a newer sandy doesn't drop them
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl State {
     /// Get the state file path
+    ///
+    /// A state file left by sandy's predecessor, `sandbox`, is moved into
+    /// place the first time the path is needed.
     pub fn state_path() -> Result<PathBuf> {
-        Ok(Config::config_dir()?.join("sandy-state.json"))
+        let dir = Config::config_dir()?;
+        let path = dir.join("sandy-state.json");
+        adopt_legacy_state(&dir.join(LEGACY_STATE_FILE), &path)?;
+        Ok(path)
     }
 
-    /// Load state from file
+    /// Load a snapshot of the state
+    ///
+    /// Saves replace the file atomically, so this never sees a partial write.
+    /// Use [`State::update`] to change the state.
     pub fn load() -> Result<Self> {
-        let path = Self::state_path()?;
-
-        if path.exists() {
-            let content = fs::read_to_string(&path)
-                .with_context(|| format!("Failed to read state file: {}", path.display()))?;
-            let state: State = serde_json::from_str(&content)
-                .with_context(|| format!("Failed to parse state file: {}", path.display()))?;
-            Ok(state)
-        } else {
-            Ok(State::default())
-        }
+        Self::load_from(&Self::state_path()?)
     }
 
-    /// Save state to file
-    pub fn save(&self) -> Result<()> {
-        let path = Self::state_path()?;
-        let dir = path.parent().unwrap();
+    /// Load the state, apply `f`, and save it, holding an exclusive lock
+    /// throughout so concurrent sandy commands can't lose each other's changes
+    ///
+    /// Nothing is saved if `f` returns an error.
+    pub fn update<T>(f: impl FnOnce(&mut State) -> Result<T>) -> Result<T> {
+        Self::update_at(&Self::state_path()?, f)
+    }
 
-        if !dir.exists() {
-            fs::create_dir_all(dir)
-                .with_context(|| format!("Failed to create state directory: {}", dir.display()))?;
+    fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(State::default());
         }
 
-        let content = serde_json::to_string_pretty(self).context("Failed to serialize state")?;
-        fs::write(&path, content)
-            .with_context(|| format!("Failed to write state file: {}", path.display()))?;
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read state file: {}", path.display()))?;
+        let mut state: State = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse state file: {}", path.display()))?;
+        state.migrate();
+        Ok(state)
+    }
+
+    fn update_at<T>(path: &Path, f: impl FnOnce(&mut State) -> Result<T>) -> Result<T> {
+        let _lock = lock_state_file(path)?;
+        let mut state = Self::load_from(path)?;
+        let result = f(&mut state)?;
+        state.save_to(path)?;
+        Ok(result)
+    }
+
+    /// Bring a state file written by an older sandy up to the current format
+    ///
+    /// Files from a newer sandy keep their version number, and their unknown
+    /// fields are written back untouched.
+    fn migrate(&mut self) {
+        // 0 -> 1: unversioned files need no changes; the pre-v0.2.0
+        // "worktrees" key is handled by the serde alias
+        self.schema_version = self.schema_version.max(SCHEMA_VERSION);
+    }
+
+    /// Write the state to a temporary file and rename it into place
+    fn save_to(&self, path: &Path) -> Result<()> {
+        let mut state = self.clone();
+        state.migrate();
+        let content = serde_json::to_string_pretty(&state).context("Failed to serialize state")?;
+
+        let temp_path = path.with_extension("json.tmp");
+        let mut file = File::create(&temp_path)
+            .with_context(|| format!("Failed to write state file: {}", temp_path.display()))?;
+        file.write_all(content.as_bytes())
+            .and_then(|()| file.sync_all())
+            .with_context(|| format!("Failed to write state file: {}", temp_path.display()))?;
+        fs::rename(&temp_path, path)
+            .with_context(|| format!("Failed to replace state file: {}", path.display()))?;
 
         Ok(())
     }
 
-    /// Add a sandbox to the state (keyed by canonical repo path)
-    pub fn add_sandbox(&mut self, repo_path: PathBuf, tool: &str) {
-        let key = repo_path.to_string_lossy().to_string();
-        self.sandboxes.insert(
-            key,
-            SandboxInfo {
+    /// Add a sandbox to the state (keyed by canonical repo path and name)
+    pub fn add_sandbox(
+        &mut self,
+        repo_path: PathBuf,
+        name: Option<&str>,
+        tool: &str,
+    ) -> &mut SandboxInfo {
+        let key = sandbox_key(&repo_path, name);
+        self.sandboxes
+            .entry(key)
+            .insert_entry(SandboxInfo {
                 path: repo_path,
+                name: name.map(str::to_string),
                 created_at: Utc::now(),
                 tool: Some(tool.to_string()),
-            },
-        );
+                last_used_at: None,
+                read_only: false,
+                extra_repos: Vec::new(),
+                worktree: None,
+                branch: None,
+                template: None,
+                sessions: Vec::new(),
+            })
+            .into_mut()
+    }
+
+    /// Record that a sandbox was just started or resumed
+    pub fn touch_sandbox(&mut self, key: &str) {
+        if let Some(info) = self.sandboxes.get_mut(key) {
+            info.last_used_at = Some(Utc::now());
+        }
+    }
+
+    /// Change the CLI tool a sandbox runs on later resumes
+    pub fn set_tool(&mut self, key: &str, tool: &str) {
+        if let Some(info) = self.sandboxes.get_mut(key) {
+            info.tool = Some(tool.to_string());
+        }
+    }
+
+    /// Record a finished agent session, dropping the oldest beyond [`MAX_SESSIONS`]
+    pub fn record_session(&mut self, key: &str, session: Session) {
+        if let Some(info) = self.sandboxes.get_mut(key) {
+            info.sessions.push(session);
+            let excess = info.sessions.len().saturating_sub(MAX_SESSIONS);
+            info.sessions.drain(..excess);
+        }
     }
 
     /// Remove a sandbox from the state
@@ -83,6 +304,30 @@ impl State {
     }
 }
 
+/// Take an exclusive advisory lock on `<state file>.lock`, creating the
+/// directory if needed. The lock is released when the returned file is dropped.
+///
+/// A separate lock file is used because saving renames a new file over the
+/// state file, which would leave other processes holding a lock on the old one.
+fn lock_state_file(path: &Path) -> Result<File> {
+    let dir = path.parent().unwrap();
+    if !dir.exists() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create state directory: {}", dir.display()))?;
+    }
+
+    let lock_path = path.with_extension("json.lock");
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open state lock: {}", lock_path.display()))?;
+    file.lock()
+        .with_context(|| format!("Failed to lock state file: {}", lock_path.display()))?;
+    Ok(file)
+}
+
 /// Get the template hash file path (tracks user's Dockerfile hash after build)
 pub fn template_hash_path() -> Result<PathBuf> {
     Ok(Config::config_dir()?.join("sandy-template.hash"))
@@ -182,6 +427,27 @@ pub fn save_template_digest(digest: &str) -> Result<()> {
     Ok(())
 }
 
+/// Move a `sandbox-state.json` to `path` if there is no sandy state yet
+fn adopt_legacy_state(legacy: &Path, path: &Path) -> Result<()> {
+    if path.exists() || !legacy.exists() {
+        return Ok(());
+    }
+    match fs::rename(legacy, path) {
+        Ok(()) => {
+            eprintln!(
+                "Migrated sandbox state from {} to {}",
+                legacy.display(),
+                path.display()
+            );
+            Ok(())
+        }
+        // Another sandy command got there first
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e)
+            .with_context(|| format!("Failed to move {} to {}", legacy.display(), path.display())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,7 +458,7 @@ mod tests {
         let mut state = State::default();
         let path = PathBuf::from("/test/repo");
 
-        state.add_sandbox(path.clone(), "claude");
+        state.add_sandbox(path.clone(), None, "claude");
 
         assert_eq!(state.sandboxes.len(), 1);
         let key = path.to_string_lossy().to_string();
@@ -209,8 +475,8 @@ mod tests {
         let path1 = PathBuf::from("/test/repo1");
         let path2 = PathBuf::from("/test/repo2");
 
-        state.add_sandbox(path1.clone(), "claude");
-        state.add_sandbox(path2.clone(), "gemini");
+        state.add_sandbox(path1.clone(), None, "claude");
+        state.add_sandbox(path2.clone(), None, "gemini");
 
         assert_eq!(state.sandboxes.len(), 2);
         assert!(
@@ -230,7 +496,7 @@ mod tests {
         let mut state = State::default();
         let path = PathBuf::from("/test/repo");
 
-        state.add_sandbox(path.clone(), "claude");
+        state.add_sandbox(path.clone(), None, "claude");
         let first_time = state
             .sandboxes
             .get(&path.to_string_lossy().to_string())
@@ -240,7 +506,7 @@ mod tests {
         // Small delay to ensure different timestamp
         std::thread::sleep(std::time::Duration::from_millis(10));
 
-        state.add_sandbox(path.clone(), "gemini");
+        state.add_sandbox(path.clone(), None, "gemini");
         let info = state
             .sandboxes
             .get(&path.to_string_lossy().to_string())
@@ -251,15 +517,46 @@ mod tests {
         assert_eq!(info.tool, Some("gemini".to_string()));
     }
 
+    #[test]
+    fn test_touch_sandbox() {
+        let mut state = State::default();
+        let path = PathBuf::from("/test/repo");
+        state.add_sandbox(path.clone(), None, "claude");
+        let key = path.to_string_lossy().to_string();
+
+        assert!(state.sandboxes[&key].last_used_at.is_none());
+        state.touch_sandbox(&key);
+        assert!(state.sandboxes[&key].last_used_at.is_some());
+
+        // Unknown keys are ignored
+        state.touch_sandbox("/test/missing");
+        assert_eq!(state.sandboxes.len(), 1);
+    }
+
+    #[test]
+    fn test_set_tool() {
+        let mut state = State::default();
+        let path = PathBuf::from("/test/repo");
+        state.add_sandbox(path.clone(), None, "claude");
+        let key = path.to_string_lossy().to_string();
+
+        state.set_tool(&key, "codex");
+        assert_eq!(state.sandboxes[&key].tool.as_deref(), Some("codex"));
+
+        // Unknown keys are ignored
+        state.set_tool("/test/missing", "gemini");
+        assert_eq!(state.sandboxes.len(), 1);
+    }
+
     #[test]
     fn test_remove_sandbox() {
         let mut state = State::default();
         let path = PathBuf::from("/test/repo");
 
-        state.add_sandbox(path.clone(), "claude");
+        state.add_sandbox(path.clone(), None, "claude");
         assert_eq!(state.sandboxes.len(), 1);
 
-        let removed = state.remove_sandbox(&path.to_string_lossy().to_string());
+        let removed = state.remove_sandbox(&path.to_string_lossy());
         assert!(removed.is_some());
         assert_eq!(removed.unwrap().path, path);
         assert!(state.sandboxes.is_empty());
@@ -276,8 +573,16 @@ mod tests {
     fn test_sandbox_info_serialization() {
         let info = SandboxInfo {
             path: PathBuf::from("/test/path"),
+            name: None,
             created_at: Utc::now(),
             tool: Some("gemini".to_string()),
+            last_used_at: None,
+            read_only: false,
+            extra_repos: Vec::new(),
+            worktree: None,
+            branch: None,
+            template: None,
+            sessions: Vec::new(),
         };
 
         let serialized = serde_json::to_string(&info).unwrap();
@@ -288,11 +593,163 @@ mod tests {
         assert_eq!(deserialized.tool, info.tool);
     }
 
+    #[test]
+    fn test_read_only_flag_roundtrip() {
+        let mut state = State::default();
+        state
+            .add_sandbox(PathBuf::from("/repo1"), None, "claude")
+            .read_only = true;
+        state.add_sandbox(PathBuf::from("/repo2"), None, "claude");
+
+        let serialized = serde_json::to_string(&state).unwrap();
+        let deserialized: State = serde_json::from_str(&serialized).unwrap();
+
+        assert!(deserialized.sandboxes["/repo1"].read_only);
+        assert!(!deserialized.sandboxes["/repo2"].read_only);
+        // Writable sandboxes keep the pre-existing state file format
+        assert_eq!(serialized.matches("read_only").count(), 1);
+    }
+
+    #[test]
+    fn test_named_sandboxes_share_a_repository() {
+        let mut state = State::default();
+        let path = PathBuf::from("/work/api");
+        state.add_sandbox(path.clone(), None, "claude");
+        state.add_sandbox(path.clone(), Some("auth"), "codex");
+
+        assert_eq!(state.sandboxes.len(), 2);
+        let named = &state.sandboxes["/work/api#auth"];
+        assert_eq!(named.path, path);
+        assert_eq!(named.name.as_deref(), Some("auth"));
+        assert_eq!(named.display_name(), "api:auth");
+        assert_eq!(state.sandboxes["/work/api"].display_name(), "api");
+
+        // The default sandbox keeps the pre-existing state file format
+        let serialized = serde_json::to_string(&state).unwrap();
+        assert_eq!(serialized.matches("\"name\"").count(), 1);
+        let deserialized: State = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.sandboxes["/work/api#auth"].name, named.name);
+    }
+
+    #[test]
+    fn test_extra_repos_roundtrip() {
+        let mut state = State::default();
+        state
+            .add_sandbox(PathBuf::from("/code/app"), None, "claude")
+            .extra_repos = vec![PathBuf::from("/code/shared")];
+        state.add_sandbox(PathBuf::from("/code/other"), None, "claude");
+
+        let serialized = serde_json::to_string(&state).unwrap();
+        let deserialized: State = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(
+            deserialized.sandboxes["/code/app"].extra_repos,
+            vec![PathBuf::from("/code/shared")]
+        );
+        assert!(deserialized.sandboxes["/code/other"].extra_repos.is_empty());
+        // Single-repository sandboxes keep the pre-existing state file format
+        assert_eq!(serialized.matches("extra_repos").count(), 1);
+    }
+
+    #[test]
+    fn test_worktree_workspace() {
+        let mut state = State::default();
+        let info = state.add_sandbox(PathBuf::from("/code/app"), Some("login"), "claude");
+        assert_eq!(info.workspace(), Path::new("/code/app"));
+
+        info.worktree = Some(PathBuf::from("/sandboxes/app-login"));
+        info.branch = Some("feature/login".to_string());
+        assert_eq!(info.workspace(), Path::new("/sandboxes/app-login"));
+
+        let serialized = serde_json::to_string(&state).unwrap();
+        let deserialized: State = serde_json::from_str(&serialized).unwrap();
+        let info = &deserialized.sandboxes["/code/app#login"];
+        assert_eq!(info.branch.as_deref(), Some("feature/login"));
+        assert_eq!(info.workspace(), Path::new("/sandboxes/app-login"));
+    }
+
+    #[test]
+    fn test_record_session_keeps_most_recent() {
+        let mut state = State::default();
+        let key = state
+            .add_sandbox(PathBuf::from("/code/app"), None, "codex")
+            .key();
+        assert_eq!(key, "/code/app");
+
+        let started_at = Utc::now() - chrono::Duration::minutes(42);
+        for exit_code in 0..MAX_SESSIONS as i32 + 2 {
+            state.record_session(&key, Session::ended("codex", started_at, exit_code, false));
+        }
+        state.record_session(
+            "/code/missing",
+            Session::ended("codex", started_at, 0, false),
+        );
+
+        let sessions = &state.sandboxes[&key].sessions;
+        assert_eq!(sessions.len(), MAX_SESSIONS);
+        assert_eq!(sessions[0].exit_code, 2);
+        assert_eq!(sessions.last().unwrap().exit_code, MAX_SESSIONS as i32 + 1);
+        assert!((2519..=2521).contains(&sessions[0].duration_secs));
+
+        let serialized = serde_json::to_string(&state).unwrap();
+        assert!(!serialized.contains("headless"));
+        let deserialized: State = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.sandboxes[&key].sessions, *sessions);
+    }
+
+    #[test]
+    fn test_idle_since() {
+        let mut state = State::default();
+        let info = state.add_sandbox(PathBuf::from("/code/app"), None, "claude");
+        assert_eq!(info.idle_since(), None);
+
+        let started_at = Utc::now() - chrono::Duration::hours(3);
+        info.sessions
+            .push(Session::ended("claude", started_at, 0, false));
+        let ended = info.sessions[0].ended_at();
+        assert_eq!(info.idle_since(), Some(ended));
+
+        // Resumed since the last session ended, so a session may be running
+        info.last_used_at = Some(ended + chrono::Duration::minutes(1));
+        assert_eq!(info.idle_since(), None);
+    }
+
+    #[test]
+    fn test_template_builds_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("sandy-state.json");
+
+        State::update_at(&state_path, |state| {
+            let info = state.add_sandbox(PathBuf::from("/test/repo"), None, "claude");
+            info.template = Some("rust".to_string());
+            state.templates.insert(
+                "rust".to_string(),
+                TemplateBuild {
+                    dockerfile_hash: "abc".to_string(),
+                    image_digest: "sha256:def".to_string(),
+                },
+            );
+            Ok(())
+        })
+        .unwrap();
+
+        let state = State::load_from(&state_path).unwrap();
+        assert_eq!(
+            state.sandboxes["/test/repo"].template.as_deref(),
+            Some("rust")
+        );
+        assert_eq!(state.templates["rust"].image_digest, "sha256:def");
+
+        // Nothing is written for the default template
+        let json = serde_json::to_string(&State::default()).unwrap();
+        assert!(!json.contains("templates"));
+    }
+
     #[test]
     fn test_state_serialization_roundtrip() {
         let mut state = State::default();
-        state.add_sandbox(PathBuf::from("/repo1"), "claude");
-        state.add_sandbox(PathBuf::from("/repo2"), "gemini");
+        state.add_sandbox(PathBuf::from("/repo1"), None, "claude");
+        state.add_sandbox(PathBuf::from("/repo2"), None, "gemini");
 
         let serialized = serde_json::to_string_pretty(&state).unwrap();
         let deserialized: State = serde_json::from_str(&serialized).unwrap();
@@ -312,7 +769,7 @@ mod tests {
 
         // Create and save state
         let mut state = State::default();
-        state.add_sandbox(PathBuf::from("/test/repo"), "claude");
+        state.add_sandbox(PathBuf::from("/test/repo"), None, "claude");
 
         let content = serde_json::to_string_pretty(&state).unwrap();
         fs::write(&state_path, &content).unwrap();
@@ -329,12 +786,124 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_adopt_legacy_state() {
+        let temp_dir = TempDir::new().unwrap();
+        let legacy = temp_dir.path().join(LEGACY_STATE_FILE);
+        let path = temp_dir.path().join("sandy-state.json");
+        fs::write(
+            &legacy,
+            r#"{"sandboxes":{"/test/repo":{"path":"/test/repo","created_at":"2025-11-20T10:00:00Z","tool":"codex"}}}"#,
+        )
+        .unwrap();
+
+        adopt_legacy_state(&legacy, &path).unwrap();
+        assert!(!legacy.exists());
+        let state = State::load_from(&path).unwrap();
+        assert_eq!(state.sandboxes["/test/repo"].tool.as_deref(), Some("codex"));
+
+        // An existing sandy state file is never replaced
+        fs::write(&legacy, r#"{"sandboxes":{}}"#).unwrap();
+        adopt_legacy_state(&legacy, &path).unwrap();
+        assert!(legacy.exists());
+        assert_eq!(State::load_from(&path).unwrap().sandboxes.len(), 1);
+    }
+
+    #[test]
+    fn test_update_saves_atomically() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("sandy-state.json");
+
+        State::update_at(&state_path, |state| {
+            state.add_sandbox(PathBuf::from("/test/repo"), None, "claude");
+            Ok(())
+        })
+        .unwrap();
+
+        let state = State::load_from(&state_path).unwrap();
+        assert_eq!(state.schema_version, SCHEMA_VERSION);
+        assert!(state.sandboxes.contains_key("/test/repo"));
+        assert!(!state_path.with_extension("json.tmp").exists());
+
+        // A failed update leaves the file untouched
+        let result: Result<()> = State::update_at(&state_path, |state| {
+            state.remove_sandbox("/test/repo");
+            anyhow::bail!("aborted")
+        });
+        assert!(result.is_err());
+        assert_eq!(State::load_from(&state_path).unwrap().sandboxes.len(), 1);
+    }
+
+    #[test]
+    fn test_concurrent_updates_keep_every_entry() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("sandy-state.json");
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let state_path = state_path.clone();
+                std::thread::spawn(move || {
+                    State::update_at(&state_path, |state| {
+                        state.add_sandbox(PathBuf::from(format!("/repo{}", i)), None, "claude");
+                        // Widen the window between load and save
+                        std::thread::sleep(std::time::Duration::from_millis(5));
+                        Ok(())
+                    })
+                    .unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(State::load_from(&state_path).unwrap().sandboxes.len(), 8);
+    }
+
+    #[test]
+    fn test_unversioned_state_is_migrated() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("sandy-state.json");
+        fs::write(
+            &state_path,
+            r#"{"worktrees": {"/test/repo": {"path": "/test/repo", "created_at": "2024-01-01T00:00:00Z"}}}"#,
+        )
+        .unwrap();
+
+        let state = State::load_from(&state_path).unwrap();
+        assert_eq!(state.schema_version, SCHEMA_VERSION);
+        assert!(state.sandboxes.contains_key("/test/repo"));
+    }
+
+    #[test]
+    fn test_newer_state_keeps_unknown_fields() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("sandy-state.json");
+        fs::write(
+            &state_path,
+            r#"{"schema_version": 7, "sandboxes": {}, "groups": {"web": ["/repo"]}}"#,
+        )
+        .unwrap();
+
+        State::update_at(&state_path, |state| {
+            state.add_sandbox(PathBuf::from("/repo"), None, "claude");
+            Ok(())
+        })
+        .unwrap();
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&state_path).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], 7);
+        assert_eq!(saved["groups"]["web"][0], "/repo");
+        assert!(saved["sandboxes"]["/repo"].is_object());
+    }
+
     #[test]
     fn test_state_with_special_characters_in_path() {
         let mut state = State::default();
         let path = PathBuf::from("/test/repo with spaces/and-dashes_underscores");
 
-        state.add_sandbox(path.clone(), "claude");
+        state.add_sandbox(path.clone(), None, "claude");
 
         let serialized = serde_json::to_string(&state).unwrap();
         let deserialized: State = serde_json::from_str(&serialized).unwrap();
@@ -348,7 +917,7 @@ mod tests {
     fn test_sandbox_info_created_at_is_current() {
         let before = Utc::now();
         let mut state = State::default();
-        state.add_sandbox(PathBuf::from("/test"), "claude");
+        state.add_sandbox(PathBuf::from("/test"), None, "claude");
         let after = Utc::now();
 
         let info = state.sandboxes.get("/test").unwrap();
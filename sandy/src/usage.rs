@@ -0,0 +1,540 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::process::Command;
+
+use crate::interactive::SelectionEntry;
+use crate::state::SandboxInfo;
+
+/// Volume used by `--credentials=sandbox`, shared by every sandbox
+pub const CREDENTIALS_VOLUME: &str = "docker-claude-sandbox-data";
+
+/// Disk usage of a single sandbox container, from `docker container inspect --size`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerUsage {
+    /// Size of the container's writable layer
+    pub writable_bytes: u64,
+    /// ID of the image the container was created from
+    pub image_id: String,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Named volumes mounted into the container
+    pub volumes: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InspectEntry {
+    image: String,
+    #[serde(default)]
+    size_rw: Option<u64>,
+    state: InspectState,
+    #[serde(default)]
+    mounts: Vec<InspectMount>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InspectState {
+    #[serde(default)]
+    started_at: String,
+    #[serde(default)]
+    finished_at: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InspectMount {
+    #[serde(rename = "Type")]
+    kind: String,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+/// Usage for one sandbox, combining state with what Docker reports
+pub struct SandboxUsage {
+    pub name: String,
+    pub info: SandboxInfo,
+    /// None when the container no longer exists
+    pub container: Option<ContainerUsage>,
+    /// Size of the container's image, if known
+    pub image_bytes: Option<u64>,
+    /// Number of sandboxes sharing the same image
+    pub image_shared_by: usize,
+}
+
+impl SandboxUsage {
+    /// The sandbox's share of its image size, split evenly between sandboxes using it
+    pub fn attributed_image_bytes(&self) -> u64 {
+        match self.image_bytes {
+            Some(bytes) if self.image_shared_by > 0 => bytes / self.image_shared_by as u64,
+            _ => 0,
+        }
+    }
+
+    /// Writable layer plus the attributed share of the image
+    pub fn total_bytes(&self) -> u64 {
+        self.container.as_ref().map_or(0, |c| c.writable_bytes) + self.attributed_image_bytes()
+    }
+
+    /// Most recent of the state's last-used time, creation time, and the
+    /// container's start/finish times
+    pub fn last_used(&self) -> DateTime<Utc> {
+        let mut times = vec![Some(self.info.created_at), self.info.last_used_at];
+        if let Some(container) = &self.container {
+            times.push(container.started_at);
+            times.push(container.finished_at);
+        }
+        times
+            .into_iter()
+            .flatten()
+            .max()
+            .unwrap_or(self.info.created_at)
+    }
+}
+
+/// Parse the output of `docker container inspect --size`
+pub fn parse_container_inspect(json: &str) -> Result<Option<ContainerUsage>> {
+    let entries: Vec<InspectEntry> =
+        serde_json::from_str(json).context("Failed to parse docker inspect output")?;
+
+    Ok(entries.into_iter().next().map(|entry| ContainerUsage {
+        writable_bytes: entry.size_rw.unwrap_or(0),
+        image_id: entry.image,
+        started_at: parse_docker_time(&entry.state.started_at),
+        finished_at: parse_docker_time(&entry.state.finished_at),
+        volumes: entry
+            .mounts
+            .into_iter()
+            .filter(|m| m.kind == "volume")
+            .filter_map(|m| m.name)
+            .collect(),
+    }))
+}
+
+/// Parse a Docker timestamp, treating Docker's zero time as unset
+fn parse_docker_time(value: &str) -> Option<DateTime<Utc>> {
+    if value.is_empty() || value.starts_with("0001-01-01") {
+        return None;
+    }
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Parse volume sizes from the "Local Volumes space usage" table of `docker system df -v`
+pub fn parse_volume_sizes(output: &str) -> HashMap<String, String> {
+    let mut sizes = HashMap::new();
+    let mut lines = output
+        .lines()
+        .skip_while(|l| !l.starts_with("Local Volumes space usage"))
+        .skip(1)
+        .skip_while(|l| l.trim().is_empty());
+
+    // Header row: VOLUME NAME   LINKS   SIZE
+    if !lines.next().is_some_and(|l| l.starts_with("VOLUME NAME")) {
+        return sizes;
+    }
+
+    for line in lines.take_while(|l| !l.trim().is_empty()) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if let (Some(name), Some(size)) = (fields.first(), fields.last())
+            && fields.len() >= 2
+        {
+            sizes.insert(name.to_string(), size.to_string());
+        }
+    }
+
+    sizes
+}
+
+/// Inspect a sandbox container, returning None if it doesn't exist
+fn inspect_container(info: &SandboxInfo) -> Result<Option<ContainerUsage>> {
+    let container_name = info.container_name();
+
+    let output = Command::new("docker")
+        .args(["container", "inspect", "--size", &container_name])
+        .output()
+        .context("Failed to inspect sandbox container")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("No such container") || stderr.contains("no such container") {
+            return Ok(None);
+        }
+        bail!("Failed to inspect {}: {}", container_name, stderr.trim());
+    }
+
+    parse_container_inspect(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Get the size of an image in bytes
+fn image_size(image_id: &str) -> Result<u64> {
+    let output = Command::new("docker")
+        .args(["image", "inspect", image_id, "--format", "{{.Size}}"])
+        .output()
+        .context("Failed to inspect image")?;
+
+    if !output.status.success() {
+        bail!(
+            "Failed to get size of image '{}': {}",
+            image_id,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid size for image '{}'", image_id))
+}
+
+/// Get the sizes of all local volumes (as reported by Docker, e.g. "45.2MB")
+pub fn volume_sizes() -> Result<HashMap<String, String>> {
+    let output = Command::new("docker")
+        .args(["system", "df", "-v"])
+        .output()
+        .context("Failed to get Docker disk usage")?;
+
+    if !output.status.success() {
+        bail!(
+            "Failed to get Docker disk usage: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(parse_volume_sizes(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Collect usage for every sandbox, largest first
+pub fn collect_usage(entries: Vec<SelectionEntry>) -> Result<Vec<SandboxUsage>> {
+    let mut usages = Vec::new();
+    for entry in entries {
+        let container = inspect_container(&entry.info)?;
+        usages.push(SandboxUsage {
+            name: entry.name,
+            info: entry.info,
+            container,
+            image_bytes: None,
+            image_shared_by: 0,
+        });
+    }
+
+    let mut image_counts: HashMap<String, usize> = HashMap::new();
+    for usage in &usages {
+        if let Some(container) = &usage.container {
+            *image_counts.entry(container.image_id.clone()).or_default() += 1;
+        }
+    }
+
+    let mut image_sizes: HashMap<String, Option<u64>> = HashMap::new();
+    for image_id in image_counts.keys() {
+        image_sizes.insert(image_id.clone(), image_size(image_id).ok());
+    }
+
+    for usage in &mut usages {
+        if let Some(container) = &usage.container {
+            usage.image_bytes = image_sizes.get(&container.image_id).copied().flatten();
+            usage.image_shared_by = image_counts[&container.image_id];
+        }
+    }
+
+    usages.sort_by_key(|u| std::cmp::Reverse(u.total_bytes()));
+    Ok(usages)
+}
+
+/// Format a byte count using Docker's decimal units
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "kB", "MB", "GB", "TB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{}B", bytes)
+    } else {
+        format!("{:.1}{}", value, UNITS[unit])
+    }
+}
+
+/// Describe how long ago a time was, e.g. "3 days ago"
+pub fn format_age(now: DateTime<Utc>, then: DateTime<Utc>) -> String {
+    let elapsed = now.signed_duration_since(then);
+    let (count, unit) = if elapsed.num_days() > 0 {
+        (elapsed.num_days(), "day")
+    } else if elapsed.num_hours() > 0 {
+        (elapsed.num_hours(), "hour")
+    } else if elapsed.num_minutes() > 0 {
+        (elapsed.num_minutes(), "minute")
+    } else {
+        return "just now".to_string();
+    };
+
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{} {}{} ago", count, unit, plural)
+}
+
+/// Print the usage report
+pub fn display_usage(usages: &[SandboxUsage], volumes: &HashMap<String, String>) {
+    let now = Utc::now();
+
+    println!("\nSandbox disk usage (largest first):");
+    println!("{:-<60}", "");
+
+    for usage in usages {
+        println!("  {} - {}", usage.name, usage.info.path.display());
+
+        match &usage.container {
+            Some(container) => {
+                println!(
+                    "    Container: {} writable",
+                    format_bytes(container.writable_bytes)
+                );
+                let short_id = container
+                    .image_id
+                    .trim_start_matches("sha256:")
+                    .get(..12)
+                    .unwrap_or(&container.image_id);
+                match usage.image_bytes {
+                    Some(bytes) if usage.image_shared_by > 1 => println!(
+                        "    Image:     {} ({}, shared by {} sandboxes, {} attributed)",
+                        format_bytes(bytes),
+                        short_id,
+                        usage.image_shared_by,
+                        format_bytes(usage.attributed_image_bytes())
+                    ),
+                    Some(bytes) => {
+                        println!("    Image:     {} ({})", format_bytes(bytes), short_id)
+                    }
+                    None => println!("    Image:     unknown ({})", short_id),
+                }
+            }
+            None => println!("    Container: not found"),
+        }
+
+        let last_used = usage.last_used();
+        println!(
+            "    Last used: {} ({})",
+            last_used.format("%Y-%m-%d %H:%M"),
+            format_age(now, last_used)
+        );
+    }
+
+    println!("{:-<60}", "");
+
+    let writable: u64 = usages
+        .iter()
+        .filter_map(|u| u.container.as_ref())
+        .map(|c| c.writable_bytes)
+        .sum();
+    let mut images: HashMap<&str, u64> = HashMap::new();
+    for usage in usages {
+        if let (Some(container), Some(bytes)) = (&usage.container, usage.image_bytes) {
+            images.insert(&container.image_id, bytes);
+        }
+    }
+    println!("  Containers (writable): {}", format_bytes(writable));
+    println!(
+        "  Images ({} unique):     {}",
+        images.len(),
+        format_bytes(images.values().sum())
+    );
+    match volumes.get(CREDENTIALS_VOLUME) {
+        Some(size) => println!("  Credentials volume:    {} ({})", size, CREDENTIALS_VOLUME),
+        None => println!(
+            "  Credentials volume:    not found ({})",
+            CREDENTIALS_VOLUME
+        ),
+    }
+
+    let mut other_volumes: Vec<&String> = usages
+        .iter()
+        .filter_map(|u| u.container.as_ref())
+        .flat_map(|c| &c.volumes)
+        .filter(|v| v.as_str() != CREDENTIALS_VOLUME)
+        .collect();
+    other_volumes.sort();
+    other_volumes.dedup();
+    for volume in other_volumes {
+        let size = volumes.get(volume).map_or("unknown", String::as_str);
+        println!("  Volume {}: {}", volume, size);
+    }
+
+    println!("{:-<60}", "");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::path::PathBuf;
+
+    const INSPECT_OUTPUT: &str = r#"[
+        {
+            "Id": "abc123",
+            "Image": "sha256:0123456789abcdef0123",
+            "SizeRw": 52428800,
+            "SizeRootFs": 1252428800,
+            "State": {
+                "Status": "exited",
+                "StartedAt": "2026-01-20T10:00:00.123456789Z",
+                "FinishedAt": "2026-01-20T12:30:00Z"
+            },
+            "Mounts": [
+                {"Type": "volume", "Name": "docker-claude-sandbox-data", "Destination": "/data"},
+                {"Type": "bind", "Source": "/Users/me/.claude", "Destination": "/home/agent/.claude"}
+            ]
+        }
+    ]"#;
+
+    fn usage(
+        container: Option<ContainerUsage>,
+        image_bytes: Option<u64>,
+        shared: usize,
+    ) -> SandboxUsage {
+        SandboxUsage {
+            name: "repo".to_string(),
+            info: SandboxInfo {
+                path: PathBuf::from("/test/repo"),
+                name: None,
+                created_at: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+                tool: Some("claude".to_string()),
+                last_used_at: None,
+                read_only: false,
+                extra_repos: Vec::new(),
+                worktree: None,
+                branch: None,
+                template: None,
+                sessions: Vec::new(),
+            },
+            container,
+            image_bytes,
+            image_shared_by: shared,
+        }
+    }
+
+    #[test]
+    fn test_parse_container_inspect() {
+        let container = parse_container_inspect(INSPECT_OUTPUT).unwrap().unwrap();
+
+        assert_eq!(container.writable_bytes, 52_428_800);
+        assert_eq!(container.image_id, "sha256:0123456789abcdef0123");
+        assert_eq!(
+            container.finished_at,
+            Some(Utc.with_ymd_and_hms(2026, 1, 20, 12, 30, 0).unwrap())
+        );
+        assert!(container.started_at.is_some());
+        assert_eq!(container.volumes, vec!["docker-claude-sandbox-data"]);
+    }
+
+    #[test]
+    fn test_parse_container_inspect_zero_times_and_missing_size() {
+        let json = r#"[{
+            "Image": "sha256:abc",
+            "State": {"StartedAt": "0001-01-01T00:00:00Z", "FinishedAt": "0001-01-01T00:00:00Z"}
+        }]"#;
+        let container = parse_container_inspect(json).unwrap().unwrap();
+
+        assert_eq!(container.writable_bytes, 0);
+        assert!(container.started_at.is_none());
+        assert!(container.finished_at.is_none());
+        assert!(container.volumes.is_empty());
+    }
+
+    #[test]
+    fn test_parse_container_inspect_empty() {
+        assert!(parse_container_inspect("[]").unwrap().is_none());
+        assert!(parse_container_inspect("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_volume_sizes() {
+        let output = "\
+Images space usage:
+
+REPOSITORY   TAG       IMAGE ID       CREATED       SIZE      SHARED SIZE   UNIQUE SIZE   CONTAINERS
+sandy-dev    latest    0123456789ab   2 days ago    1.2GB     0B            1.2GB         2
+
+Local Volumes space usage:
+
+VOLUME NAME                  LINKS     SIZE
+docker-claude-sandbox-data   2         45.2MB
+other-volume                 0         0B
+
+Build cache usage: 0B
+";
+        let sizes = parse_volume_sizes(output);
+
+        assert_eq!(sizes.len(), 2);
+        assert_eq!(sizes["docker-claude-sandbox-data"], "45.2MB");
+        assert_eq!(sizes["other-volume"], "0B");
+    }
+
+    #[test]
+    fn test_parse_volume_sizes_no_volumes_section() {
+        assert!(parse_volume_sizes("Images space usage:\n").is_empty());
+    }
+
+    #[test]
+    fn test_attributed_image_bytes() {
+        let container = parse_container_inspect(INSPECT_OUTPUT).unwrap();
+
+        let shared = usage(container.clone(), Some(1_200_000_000), 3);
+        assert_eq!(shared.attributed_image_bytes(), 400_000_000);
+        assert_eq!(shared.total_bytes(), 52_428_800 + 400_000_000);
+
+        let unknown = usage(container, None, 1);
+        assert_eq!(unknown.attributed_image_bytes(), 0);
+
+        let missing = usage(None, None, 0);
+        assert_eq!(missing.total_bytes(), 0);
+    }
+
+    #[test]
+    fn test_last_used_prefers_most_recent() {
+        let container = parse_container_inspect(INSPECT_OUTPUT).unwrap();
+        let with_container = usage(container, None, 1);
+        assert_eq!(
+            with_container.last_used(),
+            Utc.with_ymd_and_hms(2026, 1, 20, 12, 30, 0).unwrap()
+        );
+
+        let mut from_state = usage(None, None, 0);
+        assert_eq!(from_state.last_used(), from_state.info.created_at);
+        let resumed = Utc.with_ymd_and_hms(2026, 2, 1, 9, 0, 0).unwrap();
+        from_state.info.last_used_at = Some(resumed);
+        assert_eq!(from_state.last_used(), resumed);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0B");
+        assert_eq!(format_bytes(999), "999B");
+        assert_eq!(format_bytes(1_500), "1.5kB");
+        assert_eq!(format_bytes(52_428_800), "52.4MB");
+        assert_eq!(format_bytes(100_000_000_000), "100.0GB");
+    }
+
+    #[test]
+    fn test_format_age() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 20, 12, 0, 0).unwrap();
+        assert_eq!(format_age(now, now), "just now");
+        assert_eq!(
+            format_age(now, now - chrono::Duration::minutes(1)),
+            "1 minute ago"
+        );
+        assert_eq!(
+            format_age(now, now - chrono::Duration::hours(5)),
+            "5 hours ago"
+        );
+        assert_eq!(
+            format_age(now, now - chrono::Duration::days(3)),
+            "3 days ago"
+        );
+    }
+}
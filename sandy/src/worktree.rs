@@ -34,12 +34,174 @@ pub fn get_repo_name(repo_path: &Path) -> String {
         .unwrap_or_else(|| "repo".to_string())
 }
 
+/// Directory name for a clone of `url`, as `git clone` would pick it:
+/// `https://github.com/user/repo.git` and `git@github.com:user/repo` give `repo`
+pub fn repo_name_from_url(url: &str) -> Option<String> {
+    let url = url.trim_end_matches('/');
+    let name = url.rsplit(['/', ':']).next()?;
+    let name = name.strip_suffix(".git").unwrap_or(name);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Clone `url` into `path`, which must not exist yet
+pub fn clone_repo(url: &str, path: &Path) -> Result<()> {
+    if path.exists() {
+        bail!("{} already exists", path.display());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    // `--` so a URL starting with '-' isn't taken as an option
+    let status = Command::new("git")
+        .args(["clone", "--", url])
+        .arg(path)
+        .status()
+        .context("Failed to execute git clone")?;
+    if !status.success() {
+        bail!("Failed to clone {}", url);
+    }
+    Ok(())
+}
+
+/// Resolve repositories to mount alongside `workspace` to their git roots,
+/// dropping duplicates and the workspace itself
+pub fn resolve_extra_repos(workspace: &Path, repos: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut resolved: Vec<PathBuf> = Vec::new();
+    for repo in repos {
+        if !repo.is_dir() {
+            bail!("Repository path is not a directory: {}", repo.display());
+        }
+        let path = repo
+            .canonicalize()
+            .with_context(|| format!("Failed to resolve {}", repo.display()))?;
+        let path = get_workspace_path(&path);
+        if path != workspace && !resolved.contains(&path) {
+            resolved.push(path);
+        }
+    }
+    Ok(resolved)
+}
+
+/// Create a linked worktree of `repo` at `path` with `branch` checked out,
+/// creating the branch from the current HEAD if it doesn't exist yet
+pub fn add_worktree(repo: &Path, path: &Path, branch: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    let exists = Command::new("git")
+        .args(["show-ref", "--verify", "--quiet"])
+        .arg(format!("refs/heads/{}", branch))
+        .current_dir(repo)
+        .status()
+        .context("Failed to execute git show-ref")?
+        .success();
+
+    let mut cmd = Command::new("git");
+    cmd.args(["worktree", "add"]).current_dir(repo);
+    if exists {
+        cmd.arg(path).arg(branch);
+    } else {
+        cmd.args(["-b", branch]).arg(path);
+    }
+    let output = cmd.output().context("Failed to execute git worktree add")?;
+
+    if !output.status.success() {
+        bail!(
+            "Failed to create worktree for branch '{}': {}",
+            branch,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Remove a linked worktree of `repo`, discarding uncommitted changes in it.
+/// The branch is kept. A worktree that was already deleted is pruned instead.
+pub fn remove_worktree(repo: &Path, path: &Path) -> Result<()> {
+    let mut cmd = Command::new("git");
+    if path.exists() {
+        cmd.args(["worktree", "remove", "--force"]).arg(path);
+    } else {
+        cmd.args(["worktree", "prune"]);
+    }
+    let output = cmd
+        .current_dir(repo)
+        .output()
+        .context("Failed to execute git worktree")?;
+
+    if !output.status.success() {
+        bail!(
+            "Failed to remove worktree {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Get the git directory shared by a repository and its linked worktrees,
+/// which a worktree's `.git` file points into
+pub fn get_git_common_dir(repo: &Path) -> Result<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--path-format=absolute", "--git-common-dir"])
+        .current_dir(repo)
+        .output()
+        .context("Failed to execute git rev-parse")?;
+
+    if !output.status.success() {
+        bail!(
+            "Not a git repository: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(PathBuf::from(dir))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::process::Command;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_repo_name_from_url() {
+        for url in [
+            "https://github.com/user/repo.git",
+            "https://github.com/user/repo/",
+            "git@github.com:user/repo.git",
+            "git@host:repo",
+            "/srv/git/repo.git",
+        ] {
+            assert_eq!(repo_name_from_url(url).as_deref(), Some("repo"), "{}", url);
+        }
+        assert_eq!(repo_name_from_url(""), None);
+    }
+
+    #[test]
+    fn test_clone_repo() {
+        let temp = TempDir::new().unwrap();
+        let origin = temp.path().join("origin");
+        std::fs::create_dir(&origin).unwrap();
+        Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(&origin)
+            .status()
+            .unwrap();
+
+        let dest = temp.path().join("projects").join("origin");
+        clone_repo(&origin.to_string_lossy(), &dest).unwrap();
+        assert!(dest.join(".git").is_dir());
+
+        let err = clone_repo(&origin.to_string_lossy(), &dest).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+    }
+
     #[test]
     fn test_get_repo_name_simple() {
         let path = Path::new("/home/user/projects/my-repo");
@@ -153,4 +315,64 @@ mod tests {
         let result = get_repo_root(Path::new("/nonexistent/path/12345"));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_resolve_extra_repos() {
+        let temp_dir = TempDir::new().unwrap();
+        let app = temp_dir.path().canonicalize().unwrap().join("app");
+        let shared = temp_dir.path().canonicalize().unwrap().join("shared");
+        std::fs::create_dir_all(&app).unwrap();
+        std::fs::create_dir_all(&shared).unwrap();
+
+        let repos = vec![shared.clone(), app.clone(), shared.join(".")];
+        let resolved = resolve_extra_repos(&app, &repos).unwrap();
+        assert_eq!(resolved, vec![shared]);
+
+        let missing = vec![temp_dir.path().join("missing")];
+        assert!(resolve_extra_repos(&app, &missing).is_err());
+    }
+
+    /// Create a repository with one commit, or None if git is unavailable
+    fn init_repo_with_commit(path: &Path) -> Option<()> {
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(path)
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+        };
+        git(&["init", "-b", "main"])?;
+        git(&["commit", "--allow-empty", "-m", "init"])?;
+        Some(())
+    }
+
+    #[test]
+    fn test_add_and_remove_worktree() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().canonicalize().unwrap().join("app");
+        std::fs::create_dir_all(&repo).unwrap();
+        if init_repo_with_commit(&repo).is_none() {
+            return;
+        }
+
+        let worktree = temp_dir.path().join("worktrees").join("app-login");
+        add_worktree(&repo, &worktree, "feature/login").unwrap();
+        assert!(worktree.join(".git").is_file());
+        assert_eq!(get_git_common_dir(&worktree).unwrap(), repo.join(".git"));
+
+        // The branch is checked out there, so it can't be used twice
+        let other = temp_dir.path().join("worktrees").join("other");
+        assert!(add_worktree(&repo, &other, "feature/login").is_err());
+
+        remove_worktree(&repo, &worktree).unwrap();
+        assert!(!worktree.exists());
+
+        // An existing branch is checked out rather than created
+        add_worktree(&repo, &worktree, "feature/login").unwrap();
+        std::fs::remove_dir_all(&worktree).unwrap();
+        remove_worktree(&repo, &worktree).unwrap();
+        add_worktree(&repo, &other, "feature/login").unwrap();
+    }
 }
@@ -7,7 +7,7 @@ use std::process::Command as StdCommand;
 use tempfile::TempDir;
 
 fn sandy_cmd() -> Command {
-    cargo_bin_cmd!("sandy").into()
+    cargo_bin_cmd!("sandy")
 }
 
 fn create_git_repo(path: &std::path::Path) -> bool {
@@ -60,9 +60,113 @@ fn test_help_shows_subcommands() {
         .stdout(predicate::str::contains("resume"))
         .stdout(predicate::str::contains("list"))
         .stdout(predicate::str::contains("remove"))
+        .stdout(predicate::str::contains("usage"))
         .stdout(predicate::str::contains("config"));
 }
 
+#[test]
+fn test_new_help_shows_read_only() {
+    sandy_cmd()
+        .args(["new", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--read-only"));
+}
+
+#[test]
+fn test_resume_help_shows_tool() {
+    sandy_cmd()
+        .args(["resume", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--tool"));
+}
+
+#[test]
+fn test_build_help_shows_build_arg() {
+    sandy_cmd()
+        .args(["build", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--build-arg"));
+}
+
+#[test]
+fn test_build_rejects_malformed_build_arg() {
+    sandy_cmd()
+        .args(["build", "--build-arg", "NOEQUALS"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected KEY=VALUE"));
+}
+
+#[test]
+fn test_run_help_shows_options() {
+    sandy_cmd()
+        .args(["run", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--repo"))
+        .stdout(predicate::str::contains("--command"))
+        .stdout(predicate::str::contains("--timeout"));
+}
+
+#[test]
+fn test_run_rejects_invalid_timeout() {
+    sandy_cmd()
+        .args(["run", "--command", "fix the tests", "--timeout", "soon"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid timeout 'soon'"));
+}
+
+#[test]
+fn test_exec_requires_command() {
+    sandy_cmd()
+        .args(["exec", "my-app"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("<COMMAND>"));
+}
+
+#[test]
+fn test_exec_help_shows_separator() {
+    sandy_cmd()
+        .args(["exec", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "sandy exec [NAME] -- <COMMAND>...",
+        ));
+}
+
+#[test]
+fn test_prune_force_conflicts_with_dry_run() {
+    sandy_cmd()
+        .args(["prune", "--force", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_clone_requires_url() {
+    sandy_cmd()
+        .arg("clone")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("<URL>"));
+}
+
+#[test]
+fn test_run_requires_command() {
+    sandy_cmd()
+        .arg("run")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--command"));
+}
+
 // ============================================================================
 // List Command Tests
 // ============================================================================
@@ -84,6 +188,40 @@ fn test_list_with_no_sandboxes() {
         .stdout(predicate::str::contains("No sandboxes found"));
 }
 
+#[test]
+fn test_stop_with_no_running_sandboxes() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = setup_test_config(&temp_dir);
+
+    // The sandbox has no container, so it isn't running
+    let state_path = config_dir.join("sandy-state.json");
+    let state_content = r#"{
+        "sandboxes": {
+            "/test/my-project": {
+                "path": "/test/my-project",
+                "created_at": "2024-01-01T00:00:00Z"
+            }
+        }
+    }"#;
+    fs::write(&state_path, state_content).unwrap();
+
+    sandy_cmd()
+        .arg("stop")
+        .env("HOME", temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No running sandboxes"));
+
+    sandy_cmd()
+        .args(["stop", "my-project"])
+        .env("HOME", temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Sandbox 'my-project' is not running",
+        ));
+}
+
 #[test]
 fn test_list_with_sandboxes() {
     let temp_dir = TempDir::new().unwrap();
@@ -209,6 +347,58 @@ fn test_config_set_template_image() {
     assert!(content.contains("my-custom-image"));
 }
 
+#[test]
+fn test_config_set_worktrees_dir() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = setup_test_config(&temp_dir);
+
+    let config_path = config_dir.join("sandy.toml");
+    fs::write(&config_path, "binary_dirs = []\n").unwrap();
+
+    sandy_cmd()
+        .args(["config", "set", "worktrees_dir", "~/sandboxes"])
+        .env("HOME", temp_dir.path())
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(&config_path).unwrap();
+    assert!(content.contains("worktrees_dir = \"~/sandboxes\""));
+}
+
+#[test]
+fn test_config_set_auto_stop_minutes() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = setup_test_config(&temp_dir);
+
+    let config_path = config_dir.join("sandy.toml");
+    fs::write(&config_path, "binary_dirs = []\n").unwrap();
+
+    sandy_cmd()
+        .args(["config", "set", "auto_stop_minutes", "soon"])
+        .env("HOME", temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid number of minutes"));
+
+    sandy_cmd()
+        .args(["config", "set", "auto_stop_minutes", "90"])
+        .env("HOME", temp_dir.path())
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(&config_path).unwrap();
+    assert!(content.contains("auto_stop_minutes = 90"));
+}
+
+#[test]
+fn test_new_branch_conflicts_with_read_only() {
+    sandy_cmd()
+        .args(["new", "--branch", "feature/login", "--read-only"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
 #[test]
 fn test_config_set_invalid_key() {
     let temp_dir = TempDir::new().unwrap();
@@ -245,6 +435,65 @@ fn test_config_create_dockerfile() {
     assert!(dockerfile_path.exists());
 }
 
+#[test]
+fn test_config_create_named_template() {
+    let temp_dir = TempDir::new().unwrap();
+    setup_test_config(&temp_dir);
+
+    sandy_cmd()
+        .args(["config", "templates"])
+        .env("HOME", temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No named templates found"));
+
+    sandy_cmd()
+        .args(["config", "create-dockerfile", "--template", "rust"])
+        .env("HOME", temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("sandy new --template rust"));
+
+    let dockerfile_path = temp_dir
+        .path()
+        .join(".config")
+        .join("cli-programs")
+        .join("sandy")
+        .join("templates")
+        .join("rust")
+        .join("Dockerfile");
+    assert!(dockerfile_path.exists());
+
+    sandy_cmd()
+        .args(["config", "templates"])
+        .env("HOME", temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "rust (not built yet, image sandy-dev-rust)",
+        ));
+
+    // Named templates complete from the templates directory
+    sandy_cmd()
+        .args(["--", "sandy", "new", "--template", "r"])
+        .env("HOME", temp_dir.path())
+        .env("COMPLETE", "fish")
+        .assert()
+        .success()
+        .stdout("rust\n");
+}
+
+#[test]
+fn test_template_name_must_be_lowercase() {
+    sandy_cmd()
+        .args(["new", "--template", "Rust"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "may only contain lowercase letters",
+        ));
+}
+
 // ============================================================================
 // New Command Tests
 // ============================================================================
@@ -350,6 +599,84 @@ fn test_remove_with_no_sandboxes() {
         .stdout(predicate::str::contains("No sandboxes found"));
 }
 
+#[test]
+fn test_remove_unknown_name() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = setup_test_config(&temp_dir);
+
+    let state_path = config_dir.join("sandy-state.json");
+    let state_content = r#"{
+        "sandboxes": {
+            "/test/my-project": {
+                "path": "/test/my-project",
+                "created_at": "2024-01-01T00:00:00Z"
+            }
+        }
+    }"#;
+    fs::write(&state_path, state_content).unwrap();
+
+    sandy_cmd()
+        .args(["remove", "other-project"])
+        .env("HOME", temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No sandbox named 'other-project'"));
+}
+
+// ============================================================================
+// Shell Completion Tests
+// ============================================================================
+
+#[test]
+fn test_completes_sandbox_names() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = setup_test_config(&temp_dir);
+
+    let state_path = config_dir.join("sandy-state.json");
+    let state_content = r#"{
+        "sandboxes": {
+            "/test/project-a": {
+                "path": "/test/project-a",
+                "created_at": "2024-01-01T00:00:00Z"
+            },
+            "/test/other": {
+                "path": "/test/other",
+                "created_at": "2024-01-02T00:00:00Z"
+            }
+        }
+    }"#;
+    fs::write(&state_path, state_content).unwrap();
+
+    sandy_cmd()
+        .args(["--", "sandy", "resume", "proj"])
+        .env("HOME", temp_dir.path())
+        .env("COMPLETE", "fish")
+        .assert()
+        .success()
+        .stdout("project-a\t/test/project-a\n");
+}
+
+// ============================================================================
+// Usage Command Tests
+// ============================================================================
+
+#[test]
+fn test_usage_with_no_sandboxes() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = setup_test_config(&temp_dir);
+
+    // Create empty state file
+    let state_path = config_dir.join("sandy-state.json");
+    fs::write(&state_path, r#"{"sandboxes":{}}"#).unwrap();
+
+    sandy_cmd()
+        .arg("usage")
+        .env("HOME", temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No sandboxes found"));
+}
+
 // ============================================================================
 // Resume Command Tests
 // ============================================================================
@@ -0,0 +1,257 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::EncryptedBackup;
+use crate::git;
+
+/// A snapshot written to the backup directory
+pub struct Snapshot {
+    /// Short hash of the snapshot commit
+    pub commit: String,
+    /// The encrypted bundle
+    pub path: PathBuf,
+}
+
+/// Where the encrypted snapshots of a watched directory are written:
+/// `<backup_dir>/<directory name>`
+pub fn snapshot_dir(backup: &EncryptedBackup) -> PathBuf {
+    let name = backup
+        .path
+        .file_name()
+        .map_or("repository".into(), |n| n.to_string_lossy());
+    backup.backup_dir.join(name.as_ref())
+}
+
+/// Check that the `age` binary is installed
+pub fn age_available() -> bool {
+    Command::new("age")
+        .arg("--version")
+        .output()
+        .is_ok_and(|o| o.status.success())
+}
+
+/// Snapshot the directory's current contents into a new encrypted bundle,
+/// unless nothing changed since the last snapshot.
+///
+/// The snapshot is committed to a temporary repository that is removed
+/// afterwards, so no plaintext history is kept on this machine. Only the
+/// hash of the last snapshot's tree is remembered, to detect changes.
+pub fn snapshot(backup: &EncryptedBackup) -> Result<Option<Snapshot>> {
+    let git_dir =
+        std::env::temp_dir().join(format!("track-changes-snapshot-{}.git", std::process::id()));
+    let _ = fs::remove_dir_all(&git_dir);
+    let result = snapshot_with(backup, &git_dir);
+    let _ = fs::remove_dir_all(&git_dir);
+    result
+}
+
+fn snapshot_with(backup: &EncryptedBackup, git_dir: &Path) -> Result<Option<Snapshot>> {
+    git::init_snapshot_repo(git_dir)?;
+    let (tree, commit) = git::snapshot(git_dir, &backup.path)?;
+
+    let mut trees = SnapshotTrees::load();
+    if trees.0.get(&backup.path) == Some(&tree) {
+        return Ok(None);
+    }
+
+    let dir = snapshot_dir(backup);
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create backup directory: {}", dir.display()))?;
+    let path = dir.join(format!(
+        "{}.bundle.age",
+        Local::now().format("%Y-%m-%dT%H%M%S")
+    ));
+    encrypt(&git::bundle(git_dir)?, &backup.recipient, &path)?;
+
+    trees.0.insert(backup.path.clone(), tree);
+    trees.save()?;
+    Ok(Some(Snapshot { commit, path }))
+}
+
+/// Tree hash of the last snapshot of each directory
+struct SnapshotTrees(HashMap<PathBuf, String>);
+
+impl SnapshotTrees {
+    /// ~/.local/share/track-changes/snapshots.json
+    fn path() -> Result<PathBuf> {
+        let home = dirs::home_dir().context("Could not determine home directory")?;
+        Ok(home
+            .join(".local")
+            .join("share")
+            .join("track-changes")
+            .join("snapshots.json"))
+    }
+
+    /// Load the hashes, starting fresh if the file is missing or unreadable
+    fn load() -> Self {
+        let trees = Self::path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self(trees)
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(&self.0)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Encrypt `data` to the recipient and write it to `path`
+fn encrypt(data: &[u8], recipient: &str, path: &Path) -> Result<()> {
+    // Encrypt to a temporary file and rename it, so an interrupted run never
+    // leaves a truncated snapshot
+    let tmp = path.with_extension("age.tmp");
+    let mut child = Command::new("age")
+        .arg("--encrypt")
+        .args(["--recipient", recipient])
+        .arg("--output")
+        .arg(&tmp)
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run age (is it installed?)")?;
+    child
+        .stdin
+        .take()
+        .context("Failed to open age stdin")?
+        .write_all(data)
+        .context("Failed to write to age stdin")?;
+    let output = child.wait_with_output().context("Failed to run age")?;
+    if !output.status.success() {
+        let _ = fs::remove_file(&tmp);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("age failed: {}", stderr.trim());
+    }
+
+    fs::rename(&tmp, path).with_context(|| format!("Failed to write snapshot: {}", path.display()))
+}
+
+/// Encrypted snapshots to restore from `source`: the `.bundle.age` files in a
+/// snapshot directory, oldest first, or `source` itself if it's a file
+fn snapshots_in(source: &Path) -> Result<Vec<PathBuf>> {
+    if !source.is_dir() {
+        return Ok(vec![source.to_path_buf()]);
+    }
+
+    let mut snapshots: Vec<PathBuf> = fs::read_dir(source)
+        .with_context(|| format!("Failed to read {}", source.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.to_string_lossy().ends_with(".bundle.age"))
+        .collect();
+    // Snapshot names are timestamps, so they sort oldest first
+    snapshots.sort();
+    if snapshots.is_empty() {
+        anyhow::bail!("No snapshots (*.bundle.age) in {}", source.display());
+    }
+    Ok(snapshots)
+}
+
+/// Decrypt snapshots with an age identity file and replay them, oldest
+/// first, into a new repository at `target`. Returns how many were restored.
+pub fn restore(source: &Path, identity: &Path, target: &Path) -> Result<usize> {
+    if target.exists() {
+        anyhow::bail!("{} already exists", target.display());
+    }
+    let snapshots = snapshots_in(source)?;
+
+    git::init_repo(target)?;
+    let result = replay(&snapshots, identity, target);
+    if result.is_err() {
+        let _ = fs::remove_dir_all(target);
+    }
+    result.map(|()| snapshots.len())
+}
+
+fn replay(snapshots: &[PathBuf], identity: &Path, target: &Path) -> Result<()> {
+    // Decrypted bundles only ever live inside the restored repository
+    let decrypted = target.join(".git").join("snapshot.bundle");
+    let mut head: Option<String> = None;
+
+    for snapshot in snapshots {
+        // age reads passphrases for encrypted identities from the terminal
+        let status = Command::new("age")
+            .arg("--decrypt")
+            .arg("--identity")
+            .arg(identity)
+            .arg("--output")
+            .arg(&decrypted)
+            .arg(snapshot)
+            .status()
+            .context("Failed to run age (is it installed?)")?;
+        if !status.success() {
+            anyhow::bail!("Failed to decrypt {}", snapshot.display());
+        }
+
+        let commit = git::replay_bundle(target, &decrypted, head.as_deref());
+        let _ = fs::remove_file(&decrypted);
+        head = Some(commit?);
+    }
+
+    if let Some(head) = head {
+        git::checkout_commit(target, &head)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_dir() {
+        let backup = EncryptedBackup {
+            path: PathBuf::from("/track-changes-test/private-notes"),
+            recipient: "age1examplerecipient".to_string(),
+            backup_dir: PathBuf::from("/track-changes-test/backups"),
+        };
+        assert_eq!(
+            snapshot_dir(&backup),
+            PathBuf::from("/track-changes-test/backups/private-notes")
+        );
+    }
+
+    #[test]
+    fn test_snapshots_in() {
+        let dir =
+            std::env::temp_dir().join(format!("track-changes-snapshots-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "2026-10-12T090000.bundle.age",
+            "2026-10-11T230000.bundle.age",
+            "2026-10-12T100000.bundle.age.tmp",
+        ] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        let snapshots = snapshots_in(&dir).unwrap();
+        let single = snapshots_in(&snapshots[1]).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            snapshots,
+            vec![
+                dir.join("2026-10-11T230000.bundle.age"),
+                dir.join("2026-10-12T090000.bundle.age")
+            ]
+        );
+        assert_eq!(single, vec![dir.join("2026-10-12T090000.bundle.age")]);
+    }
+
+    #[test]
+    fn test_restore_refuses_existing_target() {
+        let dir = std::env::temp_dir();
+        let err = restore(Path::new("notes"), Path::new("key.txt"), &dir).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+    }
+}
@@ -3,11 +3,93 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::quiet::{DirectoryQuietHours, QuietWindow};
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
     /// List of directories to watch for changes
     #[serde(default)]
     pub directories: Vec<PathBuf>,
+    /// Settings for large repositories
+    #[serde(default)]
+    pub large_repo: LargeRepoConfig,
+    /// Windows in which scheduled runs don't auto-commit
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub quiet_hours: Vec<QuietWindow>,
+    /// Per-directory quiet hours, replacing `quiet_hours` for that directory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub directory_quiet_hours: Vec<DirectoryQuietHours>,
+    /// Watched directories whose history is only kept as encrypted snapshots
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub encrypted_backups: Vec<EncryptedBackup>,
+}
+
+/// A watched directory that keeps no local git history. Each change is
+/// written as a git bundle snapshot encrypted with `age` instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncryptedBackup {
+    /// Watched directory
+    pub path: PathBuf,
+    /// age recipient (public key, e.g. "age1...") snapshots are encrypted to
+    pub recipient: String,
+    /// Directory the snapshots are written to, under the directory's name
+    pub backup_dir: PathBuf,
+}
+
+/// How `git status` reports untracked files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UntrackedFiles {
+    /// Ignore untracked files (`-uno`)
+    No,
+    /// Show untracked files, collapsing untracked directories (`-unormal`)
+    #[default]
+    Normal,
+    /// Show every untracked file (`-uall`)
+    All,
+}
+
+impl UntrackedFiles {
+    /// The `git status` flag for this mode
+    pub fn flag(self) -> &'static str {
+        match self {
+            Self::No => "-uno",
+            Self::Normal => "-unormal",
+            Self::All => "-uall",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LargeRepoConfig {
+    /// Watched directories that use the fast path: status limited by
+    /// `untracked`, and staging of only the changed paths via a pathspec file
+    #[serde(default)]
+    pub directories: Vec<PathBuf>,
+    /// Untracked file handling for fast-path directories
+    #[serde(default = "default_untracked")]
+    pub untracked: UntrackedFiles,
+    /// Skip a repository while another git process holds its index.lock
+    #[serde(default = "default_true")]
+    pub skip_if_busy: bool,
+}
+
+fn default_untracked() -> UntrackedFiles {
+    UntrackedFiles::No
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for LargeRepoConfig {
+    fn default() -> Self {
+        Self {
+            directories: Vec::new(),
+            untracked: default_untracked(),
+            skip_if_busy: default_true(),
+        }
+    }
 }
 
 impl Config {
@@ -66,14 +148,205 @@ impl Config {
         Ok(true)
     }
 
+    /// Whether a watched directory is configured to use the large-repo fast path
+    pub fn is_fast_path(&self, path: &Path) -> bool {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.large_repo
+            .directories
+            .iter()
+            .any(|d| resolve_path(d) == canonical)
+    }
+
+    /// Untracked file handling for a watched directory
+    pub fn untracked_for(&self, path: &Path) -> UntrackedFiles {
+        if self.is_fast_path(path) {
+            self.large_repo.untracked
+        } else {
+            UntrackedFiles::Normal
+        }
+    }
+
+    /// Quiet hours that apply to a watched directory
+    pub fn quiet_hours_for(&self, path: &Path) -> &[QuietWindow] {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.directory_quiet_hours
+            .iter()
+            .find(|d| resolve_path(&d.path) == canonical)
+            .map_or(&self.quiet_hours, |d| &d.windows)
+    }
+
+    /// Encrypted snapshot settings for a watched directory, if it has any,
+    /// with its configured paths resolved
+    pub fn encrypted_backup_for(&self, path: &Path) -> Option<EncryptedBackup> {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.encrypted_backups
+            .iter()
+            .find(|b| resolve_path(&b.path) == canonical)
+            .map(|b| EncryptedBackup {
+                path: canonical,
+                recipient: b.recipient.clone(),
+                backup_dir: resolve_path(&b.backup_dir),
+            })
+    }
+
     /// Remove a directory from the watch list
     pub fn remove_directory(&mut self, path: &Path) -> Result<bool> {
-        let canonical = path
-            .canonicalize()
-            .unwrap_or_else(|_| path.to_path_buf());
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
 
         let initial_len = self.directories.len();
         self.directories.retain(|d| d != &canonical);
         Ok(self.directories.len() < initial_len)
     }
 }
+
+/// Resolve a path from the config file: expand a leading `~` and
+/// canonicalize it like watched directories, if it exists
+fn resolve_path(path: &Path) -> PathBuf {
+    let expanded = match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    };
+    expanded.canonicalize().unwrap_or(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_without_large_repo_section() {
+        let config: Config =
+            toml::from_str(r#"directories = ["/track-changes-test/notes"]"#).unwrap();
+        assert_eq!(
+            config.directories,
+            vec![PathBuf::from("/track-changes-test/notes")]
+        );
+        assert!(config.large_repo.directories.is_empty());
+        assert_eq!(config.large_repo.untracked, UntrackedFiles::No);
+        assert!(config.large_repo.skip_if_busy);
+    }
+
+    #[test]
+    fn test_parse_large_repo_section() {
+        let config: Config = toml::from_str(
+            r#"
+            directories = ["/track-changes-test/monorepo"]
+
+            [large_repo]
+            directories = ["/track-changes-test/monorepo"]
+            untracked = "all"
+            skip_if_busy = false
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.large_repo.untracked, UntrackedFiles::All);
+        assert!(!config.large_repo.skip_if_busy);
+        assert!(config.is_fast_path(Path::new("/track-changes-test/monorepo")));
+        assert!(!config.is_fast_path(Path::new("/track-changes-test/notes")));
+        assert_eq!(
+            config.untracked_for(Path::new("/track-changes-test/monorepo")),
+            UntrackedFiles::All
+        );
+        assert_eq!(
+            config.untracked_for(Path::new("/track-changes-test/notes")),
+            UntrackedFiles::Normal
+        );
+    }
+
+    #[test]
+    fn test_quiet_hours_for() {
+        let config: Config = toml::from_str(
+            r#"
+            [[quiet_hours]]
+            days = ["mon", "tue", "wed", "thu", "fri"]
+            start = "09:00"
+            end = "18:00"
+
+            [[directory_quiet_hours]]
+            path = "/track-changes-test/notes"
+            windows = []
+
+            [[directory_quiet_hours]]
+            path = "/track-changes-test/journal"
+            windows = [{ start = "22:00", end = "07:00" }]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.quiet_hours_for(Path::new("/track-changes-test/wiki")),
+            config.quiet_hours.as_slice()
+        );
+        assert!(
+            config
+                .quiet_hours_for(Path::new("/track-changes-test/notes"))
+                .is_empty()
+        );
+        assert_eq!(
+            config
+                .quiet_hours_for(Path::new("/track-changes-test/journal"))
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_configured_paths_expand_home() {
+        let home = dirs::home_dir().unwrap();
+        let config: Config = toml::from_str(
+            r#"
+            [[directory_quiet_hours]]
+            path = "~/track-changes-test-notes"
+            windows = []
+
+            [[encrypted_backups]]
+            path = "~/track-changes-test-private"
+            recipient = "age1examplerecipient"
+            backup_dir = "~/track-changes-test-backups"
+            "#,
+        )
+        .unwrap();
+        assert!(
+            config
+                .quiet_hours_for(&home.join("track-changes-test-notes"))
+                .is_empty()
+        );
+        let backup = config
+            .encrypted_backup_for(&home.join("track-changes-test-private"))
+            .unwrap();
+        assert_eq!(backup.path, home.join("track-changes-test-private"));
+        assert_eq!(backup.backup_dir, home.join("track-changes-test-backups"));
+    }
+
+    #[test]
+    fn test_encrypted_backup_for() {
+        let config: Config = toml::from_str(
+            r#"
+            [[encrypted_backups]]
+            path = "/track-changes-test/private"
+            recipient = "age1examplerecipient"
+            backup_dir = "/track-changes-test/backups"
+            "#,
+        )
+        .unwrap();
+        let backup = config
+            .encrypted_backup_for(Path::new("/track-changes-test/private"))
+            .unwrap();
+        assert_eq!(backup.recipient, "age1examplerecipient");
+        assert_eq!(
+            backup.backup_dir,
+            PathBuf::from("/track-changes-test/backups")
+        );
+        assert!(
+            config
+                .encrypted_backup_for(Path::new("/track-changes-test/notes"))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_untracked_flags() {
+        assert_eq!(UntrackedFiles::No.flag(), "-uno");
+        assert_eq!(UntrackedFiles::Normal.flag(), "-unormal");
+        assert_eq!(UntrackedFiles::All.flag(), "-uall");
+    }
+}
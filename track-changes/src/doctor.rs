@@ -0,0 +1,356 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local};
+use std::fs::{self, OpenOptions};
+use std::path::Path;
+
+use crate::backup;
+use crate::config::Config;
+use crate::git;
+use crate::launchd;
+use crate::log::CommitLog;
+
+/// How long after the last scheduled run before the job looks stalled
+/// (the plist runs hourly, so this allows for one missed run)
+const STALE_AFTER_HOURS: i64 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Level {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// Result of one health check, with a suggested fix for problems
+#[derive(Debug)]
+struct Check {
+    level: Level,
+    message: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(message: impl Into<String>) -> Self {
+        Self {
+            level: Level::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            level: Level::Warn,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            level: Level::Fail,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn print(&self) {
+        let tag = match self.level {
+            Level::Ok => "ok  ",
+            Level::Warn => "WARN",
+            Level::Fail => "FAIL",
+        };
+        println!("  [{}] {}", tag, self.message);
+        if let Some(fix) = &self.fix {
+            println!("         fix: {}", fix);
+        }
+    }
+}
+
+/// Run all health checks and print the results.
+/// Fails if any check found a problem that stops scheduled commits.
+pub fn run() -> Result<()> {
+    let mut checks = Vec::new();
+
+    println!("Config:");
+    check_config(&mut checks);
+    print_new(&checks, 0);
+
+    let start = checks.len();
+    println!("\nScheduler:");
+    check_scheduler(&mut checks)?;
+    print_new(&checks, start);
+
+    let start = checks.len();
+    println!("\nLogs:");
+    check_logs(&mut checks)?;
+    print_new(&checks, start);
+
+    let failures = checks.iter().filter(|c| c.level == Level::Fail).count();
+    let warnings = checks.iter().filter(|c| c.level == Level::Warn).count();
+
+    println!();
+    if failures > 0 {
+        anyhow::bail!("{} problem(s) found, {} warning(s)", failures, warnings);
+    }
+    if warnings > 0 {
+        println!("No problems found, {} warning(s)", warnings);
+    } else {
+        println!("No problems found");
+    }
+
+    Ok(())
+}
+
+fn print_new(checks: &[Check], start: usize) {
+    for check in &checks[start..] {
+        check.print();
+    }
+}
+
+/// Config parses, and every watched directory is a git repository
+fn check_config(checks: &mut Vec<Check>) {
+    let path = match Config::config_path() {
+        Ok(path) => path,
+        Err(e) => {
+            checks.push(Check::fail(e.to_string(), "set $HOME"));
+            return;
+        }
+    };
+
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            checks.push(Check::fail(
+                format!("{:#}", e),
+                format!("fix the TOML syntax in {}", path.display()),
+            ));
+            return;
+        }
+    };
+
+    if config.directories.is_empty() {
+        checks.push(Check::warn(
+            "no directories are being watched",
+            "track-changes add <directory>",
+        ));
+        return;
+    }
+
+    checks.push(Check::ok(format!(
+        "{} ({} watched directories)",
+        path.display(),
+        config.directories.len()
+    )));
+
+    for dir in &config.directories {
+        if !dir.exists() {
+            checks.push(Check::warn(
+                format!("{} does not exist", dir.display()),
+                format!("track-changes remove {}", dir.display()),
+            ));
+        } else if config.encrypted_backup_for(dir).is_some() {
+            if git::is_git_repo(dir) {
+                checks.push(Check::fail(
+                    format!(
+                        "{} is configured for encrypted snapshots but keeps plaintext git history",
+                        dir.display()
+                    ),
+                    format!("move {} out of the way", dir.join(".git").display()),
+                ));
+            }
+        } else if !git::is_git_repo(dir) {
+            checks.push(Check::warn(
+                format!("{} is not a git repository and is skipped", dir.display()),
+                format!("git init {}, or track-changes remove it", dir.display()),
+            ));
+        }
+    }
+
+    if !config.encrypted_backups.is_empty() && !backup::age_available() {
+        checks.push(Check::fail(
+            format!(
+                "age is not installed, so {} directory(ies) can't be snapshotted",
+                config.encrypted_backups.len()
+            ),
+            "brew install age",
+        ));
+    }
+}
+
+/// The plist is installed, points at the installed binary, is loaded, and its
+/// last run succeeded
+fn check_scheduler(checks: &mut Vec<Check>) -> Result<()> {
+    let plist_path = launchd::plist_path()?;
+    let Ok(plist) = fs::read_to_string(&plist_path) else {
+        checks.push(Check::fail(
+            format!("launch agent not installed ({})", plist_path.display()),
+            "track-changes install",
+        ));
+        return Ok(());
+    };
+    checks.push(Check::ok(format!("plist: {}", plist_path.display())));
+
+    check_binary(checks, &plist)?;
+
+    let status = match launchd::service_status() {
+        Ok(Some(status)) => status,
+        Ok(None) => {
+            checks.push(Check::fail(
+                "launch agent is installed but not loaded",
+                format!("launchctl load {}", plist_path.display()),
+            ));
+            return Ok(());
+        }
+        Err(e) => {
+            checks.push(Check::fail(
+                format!("could not query launchd: {:#}", e),
+                "the scheduler requires macOS's launchctl",
+            ));
+            return Ok(());
+        }
+    };
+
+    let state = status.state.as_deref().unwrap_or("unknown");
+    match status.runs {
+        Some(0) | None => checks.push(Check::warn(
+            format!("loaded ({}), but launchd has not run it yet", state),
+            format!("launchctl kickstart gui/$(id -u)/{}", launchd::PLIST_LABEL),
+        )),
+        Some(runs) => checks.push(Check::ok(format!("loaded ({}), {} run(s)", state, runs))),
+    }
+
+    match status.last_exit_code {
+        Some(0) => checks.push(Check::ok("last run exited successfully")),
+        Some(code) => checks.push(Check::fail(
+            format!("last run exited with status {}", code),
+            format!(
+                "check {} for the error",
+                launchd::log_dir()?.join("launchd-stderr.log").display()
+            ),
+        )),
+        None => {}
+    }
+
+    Ok(())
+}
+
+/// The plist runs the installed binary, and it exists
+fn check_binary(checks: &mut Vec<Check>, plist: &str) -> Result<()> {
+    let expected = launchd::binary_path()?;
+    let Some(program) = launchd::plist_program(plist) else {
+        checks.push(Check::fail(
+            "plist has no ProgramArguments",
+            "track-changes install to regenerate it",
+        ));
+        return Ok(());
+    };
+
+    if Path::new(&program) != expected {
+        checks.push(Check::fail(
+            format!("plist runs {}, expected {}", program, expected.display()),
+            "track-changes install to regenerate it",
+        ));
+    } else if !expected.exists() {
+        checks.push(Check::fail(
+            format!("binary not found: {}", expected.display()),
+            "reinstall with cargo run -p update-cli-programs --release",
+        ));
+    } else {
+        checks.push(Check::ok(format!("binary: {}", program)));
+    }
+
+    // Running a different build than the scheduled one makes `doctor` and
+    // manual runs behave differently from the hourly job
+    if let Ok(current) = std::env::current_exe()
+        && let (Ok(current), Ok(scheduled)) = (current.canonicalize(), expected.canonicalize())
+        && current != scheduled
+    {
+        checks.push(Check::warn(
+            format!(
+                "this is {}, but launchd runs {}",
+                current.display(),
+                scheduled.display()
+            ),
+            "install this build to ~/.local/bin so both match",
+        ));
+    }
+
+    Ok(())
+}
+
+/// The commit log is writable, and the job has run recently
+fn check_logs(checks: &mut Vec<Check>) -> Result<()> {
+    let log_path = CommitLog::log_path()?;
+    match log_path.parent() {
+        Some(dir) if !dir.exists() => checks.push(Check::warn(
+            format!("{} does not exist yet", dir.display()),
+            "it is created on the first commit; run track-changes now to create it",
+        )),
+        _ => match OpenOptions::new().create(true).append(true).open(&log_path) {
+            Ok(_) => checks.push(Check::ok(format!("writable: {}", log_path.display()))),
+            Err(e) => checks.push(Check::fail(
+                format!("cannot write {}: {}", log_path.display(), e),
+                format!(
+                    "check the ownership and permissions of {}",
+                    log_path.display()
+                ),
+            )),
+        },
+    }
+
+    let stdout_log = launchd::log_dir()?.join("launchd-stdout.log");
+    let last_run: Option<DateTime<Local>> = fs::metadata(&stdout_log)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(DateTime::from);
+
+    match last_run {
+        Some(time) if is_stale(time, Local::now()) => checks.push(Check::warn(
+            format!(
+                "last scheduled run: {} (more than {} hours ago)",
+                time.format("%Y-%m-%d %H:%M:%S"),
+                STALE_AFTER_HOURS
+            ),
+            "check that the launch agent is loaded, and that the Mac has been awake",
+        )),
+        Some(time) => checks.push(Check::ok(format!(
+            "last scheduled run: {}",
+            time.format("%Y-%m-%d %H:%M:%S")
+        ))),
+        None => checks.push(Check::warn(
+            format!(
+                "no scheduled runs logged ({} missing)",
+                stdout_log.display()
+            ),
+            "track-changes install, then wait for the first hourly run",
+        )),
+    }
+
+    if let Some(entry) = CommitLog::read_recent(1)?.first() {
+        checks.push(Check::ok(format!(
+            "last auto-commit: {} in {}",
+            entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            entry.directory.display()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Whether the last run is old enough to suggest the job stopped running
+fn is_stale(last_run: DateTime<Local>, now: DateTime<Local>) -> bool {
+    now - last_run > Duration::hours(STALE_AFTER_HOURS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_stale() {
+        let now = Local::now();
+        assert!(!is_stale(now - Duration::minutes(30), now));
+        assert!(!is_stale(now - Duration::minutes(119), now));
+        assert!(is_stale(now - Duration::hours(3), now));
+    }
+}
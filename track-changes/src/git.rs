@@ -1,13 +1,26 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Local};
+use std::collections::HashSet;
+use std::io::Write;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
+
+use crate::config::UntrackedFiles;
+
+/// Build a git command in the specified directory.
+///
+/// LFS smudging is disabled so auto-commits never download LFS content.
+fn git_command(args: &[&str], working_dir: &Path) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.args(args)
+        .current_dir(working_dir)
+        .env("GIT_LFS_SKIP_SMUDGE", "1");
+    cmd
+}
 
 /// Execute a git command in the specified directory and return the output
 fn git(args: &[&str], working_dir: &Path) -> Result<String> {
-    let output = Command::new("git")
-        .args(args)
-        .current_dir(working_dir)
+    let output = git_command(args, working_dir)
         .output()
         .context("Failed to execute git command")?;
 
@@ -19,6 +32,34 @@ fn git(args: &[&str], working_dir: &Path) -> Result<String> {
     String::from_utf8(output.stdout).context("Git output was not valid UTF-8")
 }
 
+/// Execute a git command with the given bytes on stdin and return the output
+fn git_with_stdin(args: &[&str], working_dir: &Path, input: &[u8]) -> Result<Vec<u8>> {
+    let mut child = git_command(args, working_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to execute git command")?;
+
+    child
+        .stdin
+        .take()
+        .context("Failed to open git stdin")?
+        .write_all(input)
+        .context("Failed to write to git stdin")?;
+
+    let output = child
+        .wait_with_output()
+        .context("Failed to execute git command")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git command failed: {}", stderr.trim());
+    }
+
+    Ok(output.stdout)
+}
+
 /// Check if directory is a git repository
 pub fn is_git_repo(path: &Path) -> bool {
     git2::Repository::open(path).is_ok()
@@ -30,31 +71,282 @@ pub fn init_repo(path: &Path) -> Result<()> {
     Ok(())
 }
 
-/// Get list of changed files from git status --porcelain
-pub fn get_changed_files(path: &Path) -> Result<Vec<String>> {
-    let status = git(&["status", "--porcelain"], path)?;
-    Ok(status
-        .lines()
-        .filter(|s| !s.is_empty())
-        .map(|s| s.to_string())
+/// Check if another git process is using the repository (index.lock exists)
+pub fn is_busy(path: &Path) -> bool {
+    git2::Repository::open(path)
+        .map(|repo| repo.path().join("index.lock").exists())
+        .unwrap_or(false)
+}
+
+/// A single entry from `git status --porcelain`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusEntry {
+    /// Two-character status code, e.g. " M", "??", "R "
+    pub code: String,
+    pub path: String,
+    /// Original path for renames and copies
+    pub orig_path: Option<String>,
+}
+
+impl StatusEntry {
+    /// Paths that must be staged to record this change
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.path.as_str()).chain(self.orig_path.as_deref())
+    }
+}
+
+impl std::fmt::Display for StatusEntry {
+    /// Formats like `git status --porcelain` without `-z`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.orig_path {
+            Some(orig) => write!(f, "{} {} -> {}", self.code, orig, self.path),
+            None => write!(f, "{} {}", self.code, self.path),
+        }
+    }
+}
+
+/// Parse `git status --porcelain -z` output
+pub fn parse_porcelain_z(output: &str) -> Vec<StatusEntry> {
+    let mut entries = Vec::new();
+    let mut records = output.split('\0').filter(|r| !r.is_empty());
+
+    while let Some(record) = records.next() {
+        if record.len() < 4 {
+            continue;
+        }
+        let (code, path) = record.split_at(3);
+        let code = code[..2].to_string();
+        let orig_path = if code.contains('R') || code.contains('C') {
+            records.next().map(str::to_string)
+        } else {
+            None
+        };
+        entries.push(StatusEntry {
+            code,
+            path: path.to_string(),
+            orig_path,
+        });
+    }
+
+    entries
+}
+
+/// Get changed files from `git status --porcelain`
+pub fn status(path: &Path, untracked: UntrackedFiles) -> Result<Vec<StatusEntry>> {
+    let output = git(&["status", "--porcelain", "-z", untracked.flag()], path)?;
+    Ok(parse_porcelain_z(&output))
+}
+
+/// Get list of changed files, formatted like `git status --porcelain`
+pub fn get_changed_files(path: &Path, untracked: UntrackedFiles) -> Result<Vec<String>> {
+    Ok(status(path, untracked)?
+        .iter()
+        .map(StatusEntry::to_string)
         .collect())
 }
 
-/// Stage all changes and commit with timestamp
-/// Returns the commit hash on success
-pub fn commit_with_timestamp(path: &Path) -> Result<String> {
-    // Stage all changes
+/// Check if the repository's .gitattributes routes any patterns through LFS
+pub fn uses_lfs(path: &Path) -> bool {
+    std::fs::read_to_string(path.join(".gitattributes"))
+        .map(|content| content.contains("filter=lfs"))
+        .unwrap_or(false)
+}
+
+/// Check if the git-lfs extension is installed
+pub fn lfs_available() -> bool {
+    Command::new("git")
+        .args(["lfs", "version"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+/// Return the subset of paths that are LFS-tracked according to .gitattributes
+pub fn lfs_tracked(path: &Path, paths: &[&str]) -> Result<HashSet<String>> {
+    if paths.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let input: Vec<u8> = paths
+        .iter()
+        .flat_map(|p| p.bytes().chain(std::iter::once(0)))
+        .collect();
+    let output = git_with_stdin(&["check-attr", "-z", "--stdin", "filter"], path, &input)?;
+
+    Ok(parse_check_attr_z(&String::from_utf8_lossy(&output), "lfs"))
+}
+
+/// Parse `git check-attr -z` output (`path\0attr\0value\0` triples), returning
+/// the paths whose attribute has the given value
+fn parse_check_attr_z(output: &str, value: &str) -> HashSet<String> {
+    let fields: Vec<&str> = output.split('\0').collect();
+    fields
+        .chunks(3)
+        .filter(|chunk| chunk.len() == 3 && chunk[2] == value)
+        .map(|chunk| chunk[0].to_string())
+        .collect()
+}
+
+/// Stage all changes
+pub fn stage_all(path: &Path) -> Result<()> {
     git(&["add", "-A"], path)?;
+    Ok(())
+}
 
-    // Create commit message with ISO timestamp
+/// Stage only the given paths, passed to git as a NUL-separated pathspec file
+/// so large change sets don't hit argument length limits
+pub fn stage_paths(path: &Path, paths: &[&str]) -> Result<()> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    let input: Vec<u8> = paths
+        .iter()
+        .flat_map(|p| p.bytes().chain(std::iter::once(0)))
+        .collect();
+    git_with_stdin(
+        &[
+            "--literal-pathspecs",
+            "add",
+            "-A",
+            "--pathspec-from-file=-",
+            "--pathspec-file-nul",
+        ],
+        path,
+        &input,
+    )?;
+    Ok(())
+}
+
+/// Commit message with an ISO timestamp
+fn auto_commit_message() -> String {
     let timestamp = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%:z");
-    let message = format!("Auto-commit: {}", timestamp);
+    format!("Auto-commit: {}", timestamp)
+}
+
+/// Commit staged changes with a timestamp message.
+/// Returns the commit hash, or None if nothing was staged.
+pub fn commit_staged(path: &Path) -> Result<Option<String>> {
+    let has_staged = !git_command(&["diff", "--cached", "--quiet"], path)
+        .status()
+        .context("Failed to execute git command")?
+        .success();
+    if !has_staged {
+        return Ok(None);
+    }
 
-    git(&["commit", "-m", &message], path)?;
+    git(&["commit", "-m", &auto_commit_message()], path)?;
 
     // Get the commit hash
     let hash = git(&["rev-parse", "--short", "HEAD"], path)?;
-    Ok(hash.trim().to_string())
+    Ok(Some(hash.trim().to_string()))
+}
+
+/// Bundle every ref of the repository, returning the bundle's bytes
+pub fn bundle(path: &Path) -> Result<Vec<u8>> {
+    let output = git_command(&["bundle", "create", "-", "--all"], path)
+        .output()
+        .context("Failed to execute git command")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git command failed: {}", stderr.trim());
+    }
+
+    Ok(output.stdout)
+}
+
+/// Execute a git command with a git directory kept outside the work tree
+fn git_separate(git_dir: &Path, work_tree: &Path, args: &[&str]) -> Result<String> {
+    let (Some(git_dir), Some(work_tree_str)) = (git_dir.to_str(), work_tree.to_str()) else {
+        anyhow::bail!("Paths must be valid UTF-8");
+    };
+    let mut full_args = vec!["--git-dir", git_dir, "--work-tree", work_tree_str];
+    full_args.extend(args);
+    git(&full_args, work_tree)
+}
+
+/// Create an empty bare repository to hold a snapshot
+pub fn init_snapshot_repo(git_dir: &Path) -> Result<()> {
+    git2::Repository::init_bare(git_dir).context("Failed to initialize snapshot repository")?;
+    Ok(())
+}
+
+/// Commit the current contents of `work_tree` to the snapshot repository at
+/// `git_dir`, returning the tree and short commit hashes
+pub fn snapshot(git_dir: &Path, work_tree: &Path) -> Result<(String, String)> {
+    git_separate(git_dir, work_tree, &["add", "-A"])?;
+    let tree = git_separate(git_dir, work_tree, &["write-tree"])?;
+    let tree = tree.trim();
+    let commit = git_separate(
+        git_dir,
+        work_tree,
+        &["commit-tree", tree, "-m", &auto_commit_message()],
+    )?;
+    let commit = commit.trim();
+    git_separate(git_dir, work_tree, &["update-ref", "HEAD", commit])?;
+
+    let short = git_separate(git_dir, work_tree, &["rev-parse", "--short", commit])?;
+    Ok((tree.to_string(), short.trim().to_string()))
+}
+
+/// Fetch the snapshot in a bundle into `repo` and commit its tree on top of
+/// `parent`, keeping the snapshot's message, author, and date. Returns the
+/// new commit's hash.
+pub fn replay_bundle(repo: &Path, bundle: &Path, parent: Option<&str>) -> Result<String> {
+    let Some(bundle) = bundle.to_str() else {
+        anyhow::bail!("Paths must be valid UTF-8");
+    };
+    git(&["fetch", "--quiet", bundle, "HEAD"], repo)?;
+
+    let info = git(
+        &[
+            "log",
+            "-1",
+            "--format=%an%x1f%ae%x1f%aI%x1f%B",
+            "FETCH_HEAD",
+        ],
+        repo,
+    )?;
+    let mut fields = info.splitn(4, '\x1f');
+    let (Some(name), Some(email), Some(date), Some(message)) =
+        (fields.next(), fields.next(), fields.next(), fields.next())
+    else {
+        anyhow::bail!("Failed to read the snapshot commit in {}", bundle);
+    };
+
+    let mut args = vec!["commit-tree", "FETCH_HEAD^{tree}", "-m", message.trim_end()];
+    if let Some(parent) = parent {
+        args.extend(["-p", parent]);
+    }
+    let output = git_command(&args, repo)
+        .env("GIT_AUTHOR_NAME", name)
+        .env("GIT_AUTHOR_EMAIL", email)
+        .env("GIT_AUTHOR_DATE", date)
+        .env("GIT_COMMITTER_NAME", name)
+        .env("GIT_COMMITTER_EMAIL", email)
+        .env("GIT_COMMITTER_DATE", date)
+        .output()
+        .context("Failed to execute git command")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git command failed: {}", stderr.trim());
+    }
+
+    Ok(String::from_utf8(output.stdout)
+        .context("Git output was not valid UTF-8")?
+        .trim()
+        .to_string())
+}
+
+/// Point the current branch at `commit` and check it out
+pub fn checkout_commit(repo: &Path, commit: &str) -> Result<()> {
+    git(&["update-ref", "HEAD", commit], repo)?;
+    git(&["reset", "--hard", "--quiet"], repo)?;
+    Ok(())
 }
 
 /// Get the latest commit timestamp for a directory
@@ -72,3 +364,218 @@ pub fn get_last_commit_time(path: &Path) -> Result<Option<DateTime<Local>>> {
         Err(_) => Ok(None), // No commits yet or other error
     }
 }
+
+/// Git's well-known empty tree, used as the diff base when the history starts
+/// inside the summarized period
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// A commit from `git log`, with the files it changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggedCommit {
+    pub hash: String,
+    pub timestamp: DateTime<Local>,
+    pub subject: String,
+    /// Changed files from `--name-status`, e.g. "M\tnotes/todo.md"
+    pub files: Vec<String>,
+}
+
+/// Get the commits made since `since`, oldest first
+pub fn log_since(path: &Path, since: DateTime<Local>) -> Result<Vec<LoggedCommit>> {
+    let since = since.to_rfc3339();
+    let output = match git(
+        &[
+            "log",
+            "--reverse",
+            "--no-renames",
+            &format!("--since={}", since),
+            "--format=%x1e%h%x1f%aI%x1f%s",
+            "--name-status",
+        ],
+        path,
+    ) {
+        Ok(output) => output,
+        // No commits yet
+        Err(_) if get_last_commit_time(path)?.is_none() => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    parse_log(&output)
+}
+
+/// Parse `git log --format=%x1e%h%x1f%aI%x1f%s --name-status` output
+fn parse_log(output: &str) -> Result<Vec<LoggedCommit>> {
+    let mut commits = Vec::new();
+
+    for record in output.split('\x1e').filter(|r| !r.trim().is_empty()) {
+        let mut lines = record.lines();
+        let header = lines.next().unwrap_or_default();
+        let mut fields = header.splitn(3, '\x1f');
+        let (Some(hash), Some(timestamp), Some(subject)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let timestamp = DateTime::parse_from_rfc3339(timestamp)
+            .context("Failed to parse commit timestamp")?
+            .with_timezone(&Local);
+
+        commits.push(LoggedCommit {
+            hash: hash.to_string(),
+            timestamp,
+            subject: subject.to_string(),
+            files: lines
+                .filter(|line| !line.trim().is_empty())
+                .map(str::to_string)
+                .collect(),
+        });
+    }
+
+    Ok(commits)
+}
+
+/// Get the combined diff from just before `oldest` to HEAD
+pub fn diff_from(path: &Path, oldest: &str) -> Result<String> {
+    let parent = format!("{}^", oldest);
+    let base = match git(&["rev-parse", "--verify", "--quiet", &parent], path) {
+        Ok(hash) => hash.trim().to_string(),
+        Err(_) => EMPTY_TREE.to_string(),
+    };
+    git(&["diff", "--no-color", &base, "HEAD"], path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_porcelain_z() {
+        let output = " M src/main.rs\0?? new file.txt\0R  renamed.rs\0original.rs\0D  gone.rs\0";
+        let entries = parse_porcelain_z(output);
+
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].code, " M");
+        assert_eq!(entries[0].path, "src/main.rs");
+        assert_eq!(entries[1].path, "new file.txt");
+        assert_eq!(entries[2].code, "R ");
+        assert_eq!(entries[2].path, "renamed.rs");
+        assert_eq!(entries[2].orig_path.as_deref(), Some("original.rs"));
+        assert_eq!(entries[3].code, "D ");
+    }
+
+    #[test]
+    fn test_status_entry_display_and_paths() {
+        let entries = parse_porcelain_z(" M a.txt\0R  b.txt\0c.txt\0");
+        assert_eq!(entries[0].to_string(), " M a.txt");
+        assert_eq!(entries[1].to_string(), "R  c.txt -> b.txt");
+        assert_eq!(
+            entries[1].paths().collect::<Vec<_>>(),
+            vec!["b.txt", "c.txt"]
+        );
+    }
+
+    #[test]
+    fn test_parse_check_attr_z() {
+        let output = "big.psd\0filter\0lfs\0notes.md\0filter\0unspecified\0";
+        let tracked = parse_check_attr_z(output, "lfs");
+        assert_eq!(tracked, HashSet::from(["big.psd".to_string()]));
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let output = "\x1ea1b2c3d\x1f2026-10-12T09:00:00+02:00\x1fAuto-commit: 2026-10-12T09:00:00+02:00\n\nM\tnotes/todo.md\nA\tnotes/new.md\n\x1ee4f5a6b\x1f2026-10-13T10:00:00+02:00\x1fAuto-commit: 2026-10-13T10:00:00+02:00\n\nD\told.md\n";
+        let commits = parse_log(output).unwrap();
+
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].hash, "a1b2c3d");
+        assert_eq!(commits[0].subject, "Auto-commit: 2026-10-12T09:00:00+02:00");
+        assert_eq!(
+            commits[0].files,
+            vec!["M\tnotes/todo.md", "A\tnotes/new.md"]
+        );
+        assert_eq!(commits[1].files, vec!["D\told.md"]);
+        assert!(commits[0].timestamp < commits[1].timestamp);
+
+        assert!(parse_log("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_stage_paths_and_commit() {
+        let dir = std::env::temp_dir().join(format!("track-changes-git-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        init_repo(&dir).unwrap();
+        git(&["config", "user.email", "test@example.com"], &dir).unwrap();
+        git(&["config", "user.name", "Test"], &dir).unwrap();
+
+        std::fs::write(dir.join("keep.txt"), "a").unwrap();
+        std::fs::write(dir.join(":odd name.txt"), "b").unwrap();
+        std::fs::write(dir.join("skip.txt"), "c").unwrap();
+
+        let entries = status(&dir, UntrackedFiles::All).unwrap();
+        let no_untracked = status(&dir, UntrackedFiles::No).unwrap();
+
+        stage_paths(&dir, &["keep.txt", ":odd name.txt"]).unwrap();
+        let hash = commit_staged(&dir).unwrap();
+        let nothing_staged = commit_staged(&dir).unwrap();
+        let remaining = status(&dir, UntrackedFiles::All).unwrap();
+        let logged = log_since(&dir, Local::now() - chrono::Duration::hours(1)).unwrap();
+        let diff = diff_from(&dir, &logged[0].hash).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert!(no_untracked.is_empty());
+        assert!(hash.is_some());
+        assert!(nothing_staged.is_none());
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].path, "skip.txt");
+        assert_eq!(logged.len(), 1);
+        assert_eq!(Some(&logged[0].hash), hash.as_ref());
+        assert_eq!(logged[0].files.len(), 2);
+        // The root commit is diffed against the empty tree
+        assert!(diff.contains("+++ b/keep.txt"));
+    }
+
+    #[test]
+    fn test_snapshot_and_replay() {
+        let dir =
+            std::env::temp_dir().join(format!("track-changes-snapshot-{}", std::process::id()));
+        let notes = dir.join("notes");
+        let git_dir = dir.join("snapshot.git");
+        let restored = dir.join("restored");
+        std::fs::create_dir_all(&notes).unwrap();
+
+        // Each snapshot goes through a fresh repository, as in backup::snapshot
+        let mut bundles = Vec::new();
+        let mut trees = Vec::new();
+        for (i, content) in ["- [ ] call", "- [x] call"].iter().enumerate() {
+            init_snapshot_repo(&git_dir).unwrap();
+            git(&["config", "user.email", "test@example.com"], &git_dir).unwrap();
+            git(&["config", "user.name", "Test"], &git_dir).unwrap();
+            std::fs::write(notes.join("todo.md"), content).unwrap();
+            let (tree, _) = snapshot(&git_dir, &notes).unwrap();
+
+            let bundle_path = dir.join(format!("{}.bundle", i));
+            std::fs::write(&bundle_path, bundle(&git_dir).unwrap()).unwrap();
+            std::fs::remove_dir_all(&git_dir).unwrap();
+            bundles.push(bundle_path);
+            trees.push(tree);
+        }
+
+        init_repo(&restored).unwrap();
+        let first = replay_bundle(&restored, &bundles[0], None).unwrap();
+        let second = replay_bundle(&restored, &bundles[1], Some(&first)).unwrap();
+        checkout_commit(&restored, &second).unwrap();
+
+        let count = git(&["rev-list", "--count", "HEAD"], &restored).unwrap();
+        let tree = git(&["rev-parse", "HEAD^{tree}"], &restored).unwrap();
+        let subject = git(&["log", "-1", "--format=%s"], &restored).unwrap();
+        let content = std::fs::read_to_string(restored.join("todo.md")).unwrap();
+        let notes_has_git = notes.join(".git").exists();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_ne!(trees[0], trees[1]);
+        assert_eq!(count.trim(), "2");
+        assert_eq!(tree.trim(), trees[1]);
+        assert!(subject.starts_with("Auto-commit: "));
+        assert_eq!(content, "- [x] call");
+        assert!(!notes_has_git);
+    }
+}
@@ -3,7 +3,7 @@ use std::fs;
 use std::path::PathBuf;
 use std::process::Command;
 
-const PLIST_LABEL: &str = "com.cli-programs.track-changes";
+pub const PLIST_LABEL: &str = "com.cli-programs.track-changes";
 
 /// Get the plist file path
 pub fn plist_path() -> Result<PathBuf> {
@@ -14,11 +14,22 @@ pub fn plist_path() -> Result<PathBuf> {
         .join(format!("{}.plist", PLIST_LABEL)))
 }
 
+/// Path of the binary the plist runs
+pub fn binary_path() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("Could not determine home directory")?;
+    Ok(home.join(".local").join("bin").join("track-changes"))
+}
+
+/// Directory for launchd's stdout/stderr logs
+pub fn log_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("Could not determine home directory")?;
+    Ok(home.join(".local").join("share").join("track-changes"))
+}
+
 /// Generate the launchd plist content
 pub fn generate_plist() -> Result<String> {
-    let home = dirs::home_dir().context("Could not determine home directory")?;
-    let binary_path = home.join(".local").join("bin").join("track-changes");
-    let log_dir = home.join(".local").join("share").join("track-changes");
+    let binary_path = binary_path()?;
+    let log_dir = log_dir()?;
 
     let plist = format!(
         r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -74,8 +85,7 @@ pub fn install() -> Result<()> {
     }
 
     // Ensure log directory exists
-    let home = dirs::home_dir().context("Could not determine home directory")?;
-    let log_dir = home.join(".local").join("share").join("track-changes");
+    let log_dir = log_dir()?;
     fs::create_dir_all(&log_dir)
         .with_context(|| format!("Failed to create log directory: {}", log_dir.display()))?;
 
@@ -131,3 +141,115 @@ pub fn is_installed() -> Result<bool> {
     let path = plist_path()?;
     Ok(path.exists())
 }
+
+/// The program path from an installed plist's ProgramArguments
+pub fn plist_program(plist: &str) -> Option<String> {
+    let (_, rest) = plist.split_once("<key>ProgramArguments</key>")?;
+    let (_, rest) = rest.split_once("<string>")?;
+    let (program, _) = rest.split_once("</string>")?;
+    Some(program.trim().to_string())
+}
+
+/// Job state reported by `launchctl print`
+#[derive(Debug, Default, PartialEq)]
+pub struct ServiceStatus {
+    /// e.g. "running" or "not running"
+    pub state: Option<String>,
+    /// Number of times launchd has started the job
+    pub runs: Option<u64>,
+    /// Exit code of the most recent run, if it has exited
+    pub last_exit_code: Option<i64>,
+}
+
+/// Parse the `key = value` lines of `launchctl print` output
+pub fn parse_service_status(output: &str) -> ServiceStatus {
+    let mut status = ServiceStatus::default();
+
+    for line in output.lines() {
+        let Some((key, value)) = line.trim().split_once(" = ") else {
+            continue;
+        };
+        match key {
+            "state" if status.state.is_none() => status.state = Some(value.to_string()),
+            "runs" => status.runs = value.parse().ok(),
+            // "(never exited)" before the first run completes
+            "last exit code" => status.last_exit_code = value.parse().ok(),
+            _ => {}
+        }
+    }
+
+    status
+}
+
+/// Query launchd for the job. Returns None if the job is not loaded.
+pub fn service_status() -> Result<Option<ServiceStatus>> {
+    let uid = Command::new("id")
+        .arg("-u")
+        .output()
+        .context("Failed to run id -u")?;
+    let uid = String::from_utf8_lossy(&uid.stdout).trim().to_string();
+
+    let output = Command::new("launchctl")
+        .args(["print", &format!("gui/{}/{}", uid, PLIST_LABEL)])
+        .output()
+        .context("Failed to run launchctl print")?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    Ok(Some(parse_service_status(&String::from_utf8_lossy(
+        &output.stdout,
+    ))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plist_program() {
+        let plist = generate_plist().unwrap();
+        assert_eq!(
+            plist_program(&plist),
+            Some(binary_path().unwrap().display().to_string())
+        );
+        assert_eq!(plist_program("<plist></plist>"), None);
+    }
+
+    #[test]
+    fn test_parse_service_status() {
+        let output = "gui/501/com.cli-programs.track-changes = {
+	active count = 0
+	path = /Users/me/Library/LaunchAgents/com.cli-programs.track-changes.plist
+	state = not running
+
+	program = /Users/me/.local/bin/track-changes
+	runs = 42
+	last exit code = 1
+
+	spawn type = daemon (3)
+	jetsam properties = {
+		state = ignored
+	}
+}
+";
+        assert_eq!(
+            parse_service_status(output),
+            ServiceStatus {
+                state: Some("not running".to_string()),
+                runs: Some(42),
+                last_exit_code: Some(1),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_service_status_never_exited() {
+        let status = parse_service_status(
+            "\tstate = running\n\truns = 1\n\tlast exit code = (never exited)\n",
+        );
+        assert_eq!(status.runs, Some(1));
+        assert_eq!(status.last_exit_code, None);
+    }
+}
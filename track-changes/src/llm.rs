@@ -0,0 +1,69 @@
+//! LLM client wrapper for track-changes
+//!
+//! Provides a simplified interface to the llm-client crate.
+
+use anyhow::{Context, Result};
+use llm_client::{Config, LlmProvider, LlmRequest, get_provider, with_rate_limits};
+
+/// Wrapper around LLM providers for track-changes
+pub struct LlmClient {
+    provider: Box<dyn LlmProvider>,
+}
+
+impl LlmClient {
+    /// Create a new LLM client
+    ///
+    /// If preset_name is None, uses the default preset from config.
+    pub fn new(preset_name: Option<&str>) -> Result<Self> {
+        let config = Config::load().context("Failed to load LLM configuration")?;
+
+        let preset_name =
+            preset_name.unwrap_or_else(|| config.get_default_for_program("track-changes"));
+        let preset = config
+            .get_preset(preset_name)
+            .context(format!("Unknown preset: {}", preset_name))?;
+
+        let provider_config = config.get_provider_config(&preset.provider);
+        let provider = get_provider(preset, provider_config).context(format!(
+            "Failed to initialize provider '{}' for preset '{}'",
+            preset.provider, preset_name
+        ))?;
+        let provider = with_rate_limits(&config, &preset.provider, provider)
+            .context("Failed to set up rate limits")?;
+
+        Ok(Self { provider })
+    }
+
+    /// Send a completion request to the LLM
+    pub async fn complete(&self, prompt: &str, system_prompt: &str) -> Result<String> {
+        let request = LlmRequest {
+            prompt: prompt.to_string(),
+            system_prompt: Some(system_prompt.to_string()),
+            max_tokens: None,
+            temperature: None,
+            files: vec![],
+            json_schema: None,
+        };
+
+        let response = match self.provider.complete(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                if let Some(hint) = e.remediation() {
+                    eprintln!("Hint: {}", hint);
+                }
+                return Err(e).context("LLM request failed");
+            }
+        };
+
+        if response.is_truncated()
+            && let Some(reason) = &response.finish_reason
+        {
+            eprintln!(
+                "Warning: response from {} was cut off ({}); output may be incomplete",
+                response.model, reason
+            );
+        }
+
+        Ok(response.content)
+    }
+}
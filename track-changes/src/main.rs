@@ -1,15 +1,20 @@
+mod backup;
 mod config;
+mod doctor;
 mod git;
 mod launchd;
+mod llm;
 mod log;
+mod quiet;
+mod summary;
 
 use anyhow::{Context, Result};
 use chrono::Local;
 use clap::{Parser, Subcommand};
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use config::Config;
+use config::{Config, EncryptedBackup, UntrackedFiles};
 use log::{CommitLog, LogEntry};
 
 #[derive(Parser, Debug)]
@@ -42,27 +47,57 @@ enum Commands {
     /// List all watched directories with status
     List,
     /// Commit changes in all watched directories now
-    Now,
+    Now {
+        /// Commit even in directories that are in their quiet hours
+        #[arg(long)]
+        force: bool,
+    },
     /// Install launchd plist for hourly runs
     Install,
     /// Remove launchd plist
     Uninstall,
+    /// Check the launchd job, binary, logs, and config for problems
+    Doctor,
     /// Show recent commit log
     Log {
         /// Number of entries to show
         #[arg(short, long, default_value = "20")]
         count: usize,
     },
+    /// Summarize what changed in a directory with an LLM, followed by the commit list
+    #[command(after_help = "Tip: Use \".\" for the current directory")]
+    Summarize {
+        /// Directory to summarize
+        #[arg(default_value = ".")]
+        directory: PathBuf,
+        /// How far back to look (e.g. 7d, 2w, 12h)
+        #[arg(long, default_value = "7d", value_parser = summary::parse_since)]
+        since: chrono::Duration,
+        /// Model preset to use (overrides default from llm-client config)
+        #[arg(short, long)]
+        model: Option<String>,
+    },
+    /// Restore a directory's history from its encrypted snapshots
+    Restore {
+        /// Snapshot directory (<backup_dir>/<name>), or a single .bundle.age snapshot
+        snapshots: PathBuf,
+        /// Directory to restore the history into (must not exist)
+        target: PathBuf,
+        /// age identity file holding the key the snapshots were encrypted to
+        #[arg(short, long)]
+        identity: PathBuf,
+    },
 }
 
-fn main() -> Result<()> {
+#[tokio::main]
+async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match (&cli.dir, &cli.command) {
         // --dir <directory>: Add to watch list AND run commit check
         (Some(dir), None) => {
             cmd_add_directory(dir)?;
-            run_commit_for_directory(dir)?;
+            run_commit_for_directory(dir, &Config::load()?)?;
         }
         // No args: Show help
         (None, None) => {
@@ -74,15 +109,40 @@ fn main() -> Result<()> {
             let newly_added = cmd_add_directory(directory)?;
             if newly_added {
                 // Trigger initial commit for newly added directories
-                run_commit_for_directory(directory)?;
+                run_commit_for_directory(directory, &Config::load()?)?;
             }
         }
         (None, Some(Commands::Remove { directory })) => cmd_remove_directory(directory)?,
         (None, Some(Commands::List)) => cmd_list()?,
-        (None, Some(Commands::Now)) => cmd_run_all()?,
+        (None, Some(Commands::Now { force })) => cmd_run_all(*force)?,
         (None, Some(Commands::Install)) => launchd::install()?,
         (None, Some(Commands::Uninstall)) => launchd::uninstall()?,
+        (None, Some(Commands::Doctor)) => doctor::run()?,
         (None, Some(Commands::Log { count })) => cmd_show_log(*count)?,
+        (
+            None,
+            Some(Commands::Summarize {
+                directory,
+                since,
+                model,
+            }),
+        ) => summary::run(directory, *since, model.as_deref()).await?,
+        (
+            None,
+            Some(Commands::Restore {
+                snapshots,
+                target,
+                identity,
+            }),
+        ) => {
+            let count = backup::restore(snapshots, identity, target)?;
+            println!(
+                "Restored {} snapshot(s) from {} into {}",
+                count,
+                snapshots.display(),
+                target.display()
+            );
+        }
         // Error: --dir with subcommand
         (Some(_), Some(_)) => {
             anyhow::bail!("Cannot use --dir with a subcommand");
@@ -108,9 +168,19 @@ fn prompt_yes_no(prompt: &str) -> Result<bool> {
 
 /// Add a directory to the watch list
 /// Returns Ok(true) if the directory was newly added, Ok(false) if already watching
-fn cmd_add_directory(path: &PathBuf) -> Result<bool> {
-    // Validate it's a git repo, offer to initialize if not
-    if !git::is_git_repo(path) {
+fn cmd_add_directory(path: &Path) -> Result<bool> {
+    let mut config = Config::load()?;
+
+    // Directories with encrypted snapshots must not keep local history
+    if config.encrypted_backup_for(path).is_some() {
+        if git::is_git_repo(path) {
+            anyhow::bail!(
+                "{} is configured for encrypted snapshots but has a local git repository, which keeps plaintext history",
+                path.display()
+            );
+        }
+    } else if !git::is_git_repo(path) {
+        // Validate it's a git repo, offer to initialize if not
         println!("Not a git repository: {}", path.display());
 
         if prompt_yes_no("Initialize a git repository here?")? {
@@ -121,7 +191,6 @@ fn cmd_add_directory(path: &PathBuf) -> Result<bool> {
         }
     }
 
-    let mut config = Config::load()?;
     let added = config.add_directory(path)?;
 
     if added {
@@ -135,7 +204,7 @@ fn cmd_add_directory(path: &PathBuf) -> Result<bool> {
 }
 
 /// Remove a directory from the watch list
-fn cmd_remove_directory(path: &PathBuf) -> Result<()> {
+fn cmd_remove_directory(path: &Path) -> Result<()> {
     let mut config = Config::load()?;
     let removed = config.remove_directory(path)?;
 
@@ -172,14 +241,33 @@ fn cmd_list() -> Result<()> {
             continue;
         }
 
+        if let Some(backup) = config.encrypted_backup_for(dir) {
+            println!(
+                "    Mode: encrypted snapshots in {}",
+                backup::snapshot_dir(&backup).display()
+            );
+            println!();
+            continue;
+        }
+
         if !git::is_git_repo(dir) {
             println!("    Status: NOT a git repo (will be skipped)");
             println!();
             continue;
         }
 
+        if config.is_fast_path(dir) {
+            println!("    Mode: large-repo fast path");
+        }
+
+        if let Some(window) =
+            quiet::active_window(config.quiet_hours_for(dir), Local::now().naive_local())
+        {
+            println!("    Quiet hours: auto-commits paused until {}", window.end);
+        }
+
         // Check for changes
-        match git::get_changed_files(dir) {
+        match git::get_changed_files(dir, config.untracked_for(dir)) {
             Ok(files) => {
                 if files.is_empty() {
                     println!("    Status: no pending changes");
@@ -217,9 +305,11 @@ fn cmd_list() -> Result<()> {
     Ok(())
 }
 
-/// Run commit check on all watched directories
-fn cmd_run_all() -> Result<()> {
+/// Run commit check on all watched directories, skipping those in their
+/// quiet hours unless `force` is set
+fn cmd_run_all(force: bool) -> Result<()> {
     let config = Config::load()?;
+    let now = Local::now().naive_local();
 
     if config.directories.is_empty() {
         println!("No directories being watched.");
@@ -227,7 +317,10 @@ fn cmd_run_all() -> Result<()> {
         return Ok(());
     }
 
-    println!("Processing {} directory(ies)...\n", config.directories.len());
+    println!(
+        "Processing {} directory(ies)...\n",
+        config.directories.len()
+    );
 
     let mut committed = 0;
     let mut skipped = 0;
@@ -242,13 +335,19 @@ fn cmd_run_all() -> Result<()> {
             continue;
         }
 
-        if !git::is_git_repo(dir) {
+        if config.encrypted_backup_for(dir).is_none() && !git::is_git_repo(dir) {
             println!(" - not a git repo, skipping");
             skipped += 1;
             continue;
         }
 
-        match run_commit_for_directory(dir) {
+        if !force && let Some(window) = quiet::active_window(config.quiet_hours_for(dir), now) {
+            println!(" - quiet hours until {}, skipping", window.end);
+            skipped += 1;
+            continue;
+        }
+
+        match run_commit_for_directory(dir, &config) {
             Ok(true) => committed += 1,
             Ok(false) => {} // No changes, already printed
             Err(e) => {
@@ -269,24 +368,71 @@ fn cmd_run_all() -> Result<()> {
 
 /// Run commit check for a single directory
 /// Returns Ok(true) if a commit was made, Ok(false) if no changes
-fn run_commit_for_directory(path: &PathBuf) -> Result<bool> {
+fn run_commit_for_directory(path: &Path, config: &Config) -> Result<bool> {
+    if let Some(backup) = config.encrypted_backup_for(path) {
+        return run_snapshot_for_directory(path, &backup);
+    }
+
+    // Another git process (editor, IDE, manual commit) is working in the repo
+    if config.large_repo.skip_if_busy && git::is_busy(path) {
+        println!(" - busy (index.lock exists), skipping");
+        return Ok(false);
+    }
+
+    // Without git-lfs, adding LFS-tracked files would commit their full
+    // contents instead of pointers, so leave them out of the commit
+    let exclude_lfs = git::uses_lfs(path) && !git::lfs_available();
+    let fast_path = config.is_fast_path(path);
+
+    let mut untracked = config.untracked_for(path);
+    if exclude_lfs && untracked == UntrackedFiles::Normal {
+        // List individual files so LFS files in new directories can be excluded
+        untracked = UntrackedFiles::All;
+    }
+
     // Check for changes
-    let files = git::get_changed_files(path)?;
+    let mut entries = git::status(path, untracked)?;
+
+    if exclude_lfs {
+        let paths: Vec<&str> = entries.iter().flat_map(|e| e.paths()).collect();
+        let lfs = git::lfs_tracked(path, &paths)?;
+        if !lfs.is_empty() {
+            eprintln!(
+                " - warning: git-lfs is not installed, skipping {} LFS-tracked file(s)",
+                lfs.len()
+            );
+            entries.retain(|e| !e.paths().any(|p| lfs.contains(p)));
+        }
+    }
 
-    if files.is_empty() {
+    if entries.is_empty() {
         println!(" - no changes");
         return Ok(false);
     }
 
+    // Stage the changes
+    if fast_path || exclude_lfs {
+        let paths: Vec<&str> = entries.iter().flat_map(|e| e.paths()).collect();
+        git::stage_paths(path, &paths)
+    } else {
+        git::stage_all(path)
+    }
+    .with_context(|| format!("Failed to stage changes in {}", path.display()))?;
+
     // Commit the changes
-    let hash = git::commit_with_timestamp(path)
-        .with_context(|| format!("Failed to commit in {}", path.display()))?;
+    let Some(hash) = git::commit_staged(path)
+        .with_context(|| format!("Failed to commit in {}", path.display()))?
+    else {
+        println!(" - no changes");
+        return Ok(false);
+    };
 
+    let files: Vec<String> = entries.iter().map(|e| e.to_string()).collect();
     println!(" - committed: {} ({} file(s))", hash, files.len());
 
     // Log the commit
     let entry = LogEntry {
-        directory: path.clone(),
+        directory: path.to_path_buf(),
         timestamp: Local::now(),
         files_changed: files,
         commit_hash: hash,
@@ -299,6 +445,40 @@ fn run_commit_for_directory(path: &PathBuf) -> Result<bool> {
     Ok(true)
 }
 
+/// Write an encrypted snapshot of a directory that keeps no local history
+/// Returns Ok(true) if a snapshot was written, Ok(false) if no changes
+fn run_snapshot_for_directory(path: &Path, backup: &EncryptedBackup) -> Result<bool> {
+    if git::is_git_repo(path) {
+        anyhow::bail!(
+            "configured for encrypted snapshots but has a local git repository; move its .git directory out of the way"
+        );
+    }
+
+    let Some(snapshot) = backup::snapshot(backup)? else {
+        println!(" - no changes");
+        return Ok(false);
+    };
+    println!(
+        " - encrypted snapshot: {} ({})",
+        snapshot.commit,
+        snapshot.path.display()
+    );
+
+    // File names stay out of the log, which is plaintext
+    let entry = LogEntry {
+        directory: path.to_path_buf(),
+        timestamp: Local::now(),
+        files_changed: Vec::new(),
+        commit_hash: snapshot.commit,
+    };
+
+    if let Err(e) = CommitLog::append(&entry) {
+        eprintln!("Warning: failed to write log entry: {}", e);
+    }
+
+    Ok(true)
+}
+
 /// Show recent commit log entries
 fn cmd_show_log(count: usize) -> Result<()> {
     let entries = CommitLog::read_recent(count)?;
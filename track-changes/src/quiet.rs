@@ -0,0 +1,170 @@
+// Quiet hours: time windows in which scheduled runs don't auto-commit
+//
+// Snapshots taken while a file is being edited capture half-finished work, so
+// a window like 09:00-18:00 on workdays can pause auto-commits while the user
+// is at the keyboard. A window whose end is before its start runs past
+// midnight, and its days are the days it starts on.
+
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+
+/// A time of day written as `HH:MM`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ClockTime(NaiveTime);
+
+impl TryFrom<String> for ClockTime {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        NaiveTime::parse_from_str(&value, "%H:%M")
+            .map(Self)
+            .map_err(|_| format!("invalid time '{}', expected HH:MM", value))
+    }
+}
+
+impl From<ClockTime> for String {
+    fn from(time: ClockTime) -> Self {
+        time.to_string()
+    }
+}
+
+impl fmt::Display for ClockTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.format("%H:%M"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuietWindow {
+    /// Days the window starts on (e.g. ["mon", "tue"]); every day when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<Weekday>,
+    pub start: ClockTime,
+    pub end: ClockTime,
+}
+
+impl QuietWindow {
+    fn starts_on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    /// Whether `now` falls inside the window
+    pub fn contains(&self, now: NaiveDateTime) -> bool {
+        let time = now.time();
+        let day = now.weekday();
+        let (start, end) = (self.start.0, self.end.0);
+
+        if start <= end {
+            self.starts_on(day) && time >= start && time < end
+        } else {
+            (self.starts_on(day) && time >= start) || (self.starts_on(day.pred()) && time < end)
+        }
+    }
+}
+
+/// Quiet hours for one watched directory, replacing the global ones
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DirectoryQuietHours {
+    pub path: PathBuf,
+    /// Windows for this directory; empty to auto-commit at any time
+    #[serde(default)]
+    pub windows: Vec<QuietWindow>,
+}
+
+/// The window containing `now`, if any
+pub fn active_window(windows: &[QuietWindow], now: NaiveDateTime) -> Option<&QuietWindow> {
+    windows.iter().find(|window| window.contains(now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2026-10-12 is a Monday
+        NaiveDate::from_ymd_opt(2026, 10, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn window(toml: &str) -> QuietWindow {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_workday_window() {
+        let window = window(
+            r#"
+            days = ["mon", "tue", "wed", "thu", "fri"]
+            start = "09:00"
+            end = "18:00"
+            "#,
+        );
+        assert!(window.contains(at(12, 9, 0)));
+        assert!(window.contains(at(16, 17, 59)));
+        assert!(!window.contains(at(12, 8, 59)));
+        assert!(!window.contains(at(12, 18, 0)));
+        // Saturday
+        assert!(!window.contains(at(17, 12, 0)));
+    }
+
+    #[test]
+    fn test_window_past_midnight() {
+        let window = window(
+            r#"
+            days = ["fri"]
+            start = "22:00"
+            end = "07:00"
+            "#,
+        );
+        assert!(window.contains(at(16, 23, 0)));
+        // Saturday morning belongs to Friday's window
+        assert!(window.contains(at(17, 6, 30)));
+        assert!(!window.contains(at(17, 7, 0)));
+        // Friday morning belongs to Thursday, which isn't listed
+        assert!(!window.contains(at(16, 6, 30)));
+    }
+
+    #[test]
+    fn test_every_day_when_days_omitted() {
+        let windows = vec![window(
+            r#"
+            start = "12:00"
+            end = "13:00"
+            "#,
+        )];
+        assert!(active_window(&windows, at(18, 12, 30)).is_some());
+        assert!(active_window(&windows, at(18, 13, 30)).is_none());
+    }
+
+    #[test]
+    fn test_invalid_time() {
+        let result: Result<QuietWindow, _> = toml::from_str(
+            r#"
+            start = "9am"
+            end = "18:00"
+            "#,
+        );
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("invalid time '9am', expected HH:MM"));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let window = window(
+            r#"
+            days = ["sat"]
+            start = "08:30"
+            end = "12:00"
+            "#,
+        );
+        let serialized = toml::to_string(&window).unwrap();
+        assert!(serialized.contains("start = \"08:30\""));
+        assert_eq!(toml::from_str::<QuietWindow>(&serialized).unwrap(), window);
+    }
+}
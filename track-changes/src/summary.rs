@@ -0,0 +1,206 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local};
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::git::{self, LoggedCommit};
+use crate::llm::LlmClient;
+
+/// Diffs beyond this many bytes are cut off before being sent to the LLM
+const MAX_DIFF_BYTES: usize = 60_000;
+
+const SYSTEM_PROMPT: &str = "You summarize changes to a directory of notes or documents \
+that is auto-committed hourly. Commit messages are only timestamps, so describe what \
+changed from the diff itself.";
+
+/// Parse a period like `7d`, `2w`, `12h`, or `30m`
+pub fn parse_since(s: &str) -> std::result::Result<Duration, String> {
+    let invalid = || format!("invalid period '{}' (expected e.g. 7d, 2w, 12h)", s);
+    let s = s.trim();
+    let unit_start = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let (value, unit) = s.split_at(unit_start);
+    let value: i64 = value.parse().map_err(|_| invalid())?;
+
+    let period = match unit {
+        "m" => Duration::try_minutes(value),
+        "h" => Duration::try_hours(value),
+        "d" => Duration::try_days(value),
+        "w" => Duration::try_weeks(value),
+        _ => return Err(invalid()),
+    };
+    // Also rejects periods reaching back before the earliest representable date
+    period
+        .filter(|period| Local::now().checked_sub_signed(*period).is_some())
+        .ok_or_else(|| format!("period '{}' is too long", s))
+}
+
+/// Summarize the commits in `path` over the last `period`
+pub async fn run(path: &Path, period: Duration, preset: Option<&str>) -> Result<()> {
+    if !git::is_git_repo(path) {
+        anyhow::bail!("Not a git repository: {}", path.display());
+    }
+
+    let since = Local::now()
+        .checked_sub_signed(period)
+        .context("Period is too long")?;
+    let commits = git::log_since(path, since)?;
+    let Some(oldest) = commits.first() else {
+        println!(
+            "No commits in {} since {}.",
+            path.display(),
+            since.format("%Y-%m-%d %H:%M")
+        );
+        return Ok(());
+    };
+
+    let diff = git::diff_from(path, &oldest.hash)
+        .with_context(|| format!("Failed to diff {}", path.display()))?;
+
+    eprintln!(
+        "Summarizing {} commit(s) in {}...",
+        commits.len(),
+        path.display()
+    );
+    let llm = LlmClient::new(preset)?;
+    let summary = llm
+        .complete(&build_prompt(path, since, &commits, &diff), SYSTEM_PROMPT)
+        .await?;
+
+    println!("{}", summary.trim());
+    println!();
+    print!("{}", format_commit_list(&commits));
+
+    Ok(())
+}
+
+fn build_prompt(
+    path: &Path,
+    since: DateTime<Local>,
+    commits: &[LoggedCommit],
+    diff: &str,
+) -> String {
+    let mut prompt = format!(
+        "Write a short narrative summary of what changed in {} since {}, for a weekly review. \
+Group related changes by topic rather than by commit, mention notable additions, edits, \
+and deletions, and skip trivial whitespace or formatting changes. Write plain prose with \
+at most a few short paragraphs or bullet lists.\n\n",
+        path.display(),
+        since.format("%A %Y-%m-%d")
+    );
+
+    let _ = writeln!(prompt, "<commits>");
+    prompt.push_str(&format_commit_list(commits));
+    let _ = writeln!(prompt, "</commits>\n");
+
+    let _ = writeln!(prompt, "<diff>");
+    let shown = truncate_diff(diff);
+    prompt.push_str(shown);
+    if shown.len() < diff.len() {
+        let _ = writeln!(
+            prompt,
+            "\n[diff truncated: {} of {} bytes shown]",
+            shown.len(),
+            diff.len()
+        );
+    }
+    let _ = writeln!(prompt, "</diff>");
+
+    prompt
+}
+
+/// Cut the diff at a line boundary within the size limit
+fn truncate_diff(diff: &str) -> &str {
+    if diff.len() <= MAX_DIFF_BYTES {
+        return diff;
+    }
+    let mut end = MAX_DIFF_BYTES;
+    while !diff.is_char_boundary(end) {
+        end -= 1;
+    }
+    match diff[..end].rfind('\n') {
+        Some(newline) => &diff[..=newline],
+        None => &diff[..end],
+    }
+}
+
+/// The raw commit list, one line per commit followed by its changed files
+fn format_commit_list(commits: &[LoggedCommit]) -> String {
+    let mut list = format!("Commits ({}):\n", commits.len());
+    for commit in commits {
+        let _ = writeln!(
+            list,
+            "{}  {}  {}",
+            commit.timestamp.format("%Y-%m-%d %H:%M"),
+            commit.hash,
+            commit.subject
+        );
+        for file in &commit.files {
+            let _ = writeln!(list, "  {}", file.replace('\t', " "));
+        }
+    }
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(hash: &str, files: &[&str]) -> LoggedCommit {
+        LoggedCommit {
+            hash: hash.to_string(),
+            timestamp: DateTime::parse_from_rfc3339("2026-10-12T09:00:00+00:00")
+                .unwrap()
+                .with_timezone(&Local),
+            subject: "Auto-commit: 2026-10-12T09:00:00+00:00".to_string(),
+            files: files.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("7d"), Ok(Duration::days(7)));
+        assert_eq!(parse_since("2w"), Ok(Duration::weeks(2)));
+        assert_eq!(parse_since("12h"), Ok(Duration::hours(12)));
+        assert_eq!(parse_since("30m"), Ok(Duration::minutes(30)));
+        assert!(parse_since("7").is_err());
+        assert!(parse_since("d").is_err());
+        assert!(parse_since("7y").is_err());
+        assert!(parse_since("").is_err());
+        assert_eq!(
+            parse_since("99999999999999d"),
+            Err("period '99999999999999d' is too long".to_string())
+        );
+        assert!(parse_since("9999999999w").is_err());
+    }
+
+    #[test]
+    fn test_format_commit_list() {
+        let list = format_commit_list(&[
+            commit("a1b2c3d", &["M\tnotes/todo.md"]),
+            commit("e4f5a6b", &["A\tnotes/new.md", "D\told.md"]),
+        ]);
+        assert!(list.starts_with("Commits (2):\n"));
+        assert!(list.contains("  a1b2c3d  Auto-commit:"));
+        assert!(list.contains("\n  M notes/todo.md\n"));
+        assert!(list.ends_with("  D old.md\n"));
+    }
+
+    #[test]
+    fn test_truncate_diff() {
+        let short = "+ line\n";
+        assert_eq!(truncate_diff(short), short);
+
+        let long = "+ a line of text\n".repeat(MAX_DIFF_BYTES / 10);
+        let truncated = truncate_diff(&long);
+        assert!(truncated.len() <= MAX_DIFF_BYTES);
+        assert!(truncated.ends_with('\n'));
+
+        let prompt = build_prompt(
+            Path::new("/notes"),
+            Local::now(),
+            &[commit("a1b2c3d", &[])],
+            &long,
+        );
+        assert!(prompt.contains("[diff truncated:"));
+    }
+}
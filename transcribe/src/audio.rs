@@ -12,12 +12,20 @@ pub struct AudioInfo {
     pub sample_rate: u32,
     pub channels: u32,
     pub codec: String,
+    /// Length of the recording in milliseconds, when ffprobe reports it
+    pub duration_ms: Option<u64>,
 }
 
 impl AudioInfo {
     /// Check if audio needs conversion to meet whisper requirements
-    pub fn needs_conversion(&self) -> bool {
-        self.sample_rate != REQUIRED_SAMPLE_RATE || self.channels != 1
+    /// (16kHz with the given channel count; whisper normally wants mono)
+    pub fn needs_conversion(&self, channels: u32) -> bool {
+        self.sample_rate != REQUIRED_SAMPLE_RATE || self.channels != channels
+    }
+
+    /// Whisper's `--diarize` tells speakers apart by stereo channel
+    pub fn is_stereo(&self) -> bool {
+        self.channels == 2
     }
 
     /// Get a human-readable description of issues
@@ -49,6 +57,7 @@ pub fn check_audio_format(path: &Path) -> Result<AudioInfo> {
             "-print_format",
             "json",
             "-show_streams",
+            "-show_format",
             "-select_streams",
             "a:0",
         ])
@@ -91,16 +100,53 @@ pub fn check_audio_format(path: &Path) -> Result<AudioInfo> {
         .unwrap_or("unknown")
         .to_string();
 
+    let duration_ms = json["format"]["duration"]
+        .as_str()
+        .or_else(|| stream["duration"].as_str())
+        .and_then(|duration| duration.parse::<f64>().ok())
+        .map(|secs| (secs * 1000.0) as u64);
+
     Ok(AudioInfo {
         sample_rate,
         channels,
         codec,
+        duration_ms,
     })
 }
 
-/// Convert audio file to whisper-compatible format (16kHz mono PCM WAV)
+/// Cut `length_ms` of audio starting at `start_ms` out of `input` into a
+/// whisper-compatible 16kHz mono PCM WAV file at `output`
+pub fn extract_segment(input: &Path, start_ms: u64, length_ms: u64, output: &Path) -> Result<()> {
+    let seconds = |ms: u64| format!("{:.3}", ms as f64 / 1000.0);
+
+    let output = Command::new("ffmpeg")
+        .args(["-ss", &seconds(start_ms), "-t", &seconds(length_ms), "-i"])
+        .arg(input)
+        .args([
+            "-ar",
+            "16000", // 16kHz sample rate
+            "-ac",
+            "1",
+            "-c:a",
+            "pcm_s16le", // 16-bit PCM
+            "-y",        // Overwrite output
+        ])
+        .arg(output)
+        .output()
+        .context("Failed to run ffmpeg. Is ffmpeg installed?")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("ffmpeg failed to extract audio: {}", stderr);
+    }
+
+    Ok(())
+}
+
+/// Convert audio file to whisper-compatible format (16kHz PCM WAV, mono unless
+/// keeping stereo for diarization)
 /// Returns a temporary file that will be deleted when dropped
-pub fn convert_audio(input: &Path) -> Result<NamedTempFile> {
+pub fn convert_audio(input: &Path, channels: u32) -> Result<NamedTempFile> {
     let temp_file = NamedTempFile::with_suffix(".wav").context("Failed to create temp file")?;
 
     let output = Command::new("ffmpeg")
@@ -110,7 +156,7 @@ pub fn convert_audio(input: &Path) -> Result<NamedTempFile> {
             "-ar",
             "16000", // 16kHz sample rate
             "-ac",
-            "1", // Mono
+            &channels.to_string(),
             "-c:a",
             "pcm_s16le", // 16-bit PCM
             "-y",        // Overwrite output
@@ -137,22 +183,27 @@ mod tests {
             sample_rate: 16000,
             channels: 1,
             codec: "pcm_s16le".to_string(),
+            duration_ms: None,
         };
-        assert!(!info.needs_conversion());
+        assert!(!info.needs_conversion(1));
 
         let info = AudioInfo {
             sample_rate: 44100,
             channels: 1,
             codec: "pcm_s16le".to_string(),
+            duration_ms: None,
         };
-        assert!(info.needs_conversion());
+        assert!(info.needs_conversion(1));
 
         let info = AudioInfo {
             sample_rate: 16000,
             channels: 2,
             codec: "pcm_s16le".to_string(),
+            duration_ms: None,
         };
-        assert!(info.needs_conversion());
+        assert!(info.needs_conversion(1));
+        assert!(!info.needs_conversion(2));
+        assert!(info.is_stereo());
     }
 
     #[test]
@@ -161,6 +212,7 @@ mod tests {
             sample_rate: 44100,
             channels: 2,
             codec: "pcm_s16le".to_string(),
+            duration_ms: None,
         };
         let issues = info.issues();
         assert_eq!(issues.len(), 2);
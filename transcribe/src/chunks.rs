@@ -0,0 +1,278 @@
+// Chunked transcription of long recordings
+//
+// A multi-hour recording is cut into overlapping chunks that are transcribed
+// one at a time, so progress can be reported and an interrupted run picks up
+// after the last finished chunk. Each chunk's text is saved in a work
+// directory keyed by the input file and the whisper options, which is
+// deleted once the whole transcript has been written.
+
+use anyhow::{Context, Result, bail};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// How far each chunk runs into the next, so words cut at a boundary are
+/// heard whole in one of them
+pub const OVERLAP_MS: u64 = 5_000;
+
+/// Words at the end of the previous chunk searched for a repeat
+const MAX_OVERLAP_WORDS: usize = 40;
+
+/// Words at the start of a chunk that may be skipped before the repeat, as
+/// whisper often garbles a word cut off at the chunk's start
+const MAX_SKIPPED_WORDS: usize = 3;
+
+/// Shortest repeat that counts as overlap, so a single common word like
+/// "the" doesn't get dropped
+const MIN_OVERLAP_WORDS: usize = 2;
+
+/// A slice of the recording, in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chunk {
+    pub index: usize,
+    pub start_ms: u64,
+    pub length_ms: u64,
+}
+
+impl Chunk {
+    pub fn end_ms(&self) -> u64 {
+        self.start_ms + self.length_ms
+    }
+}
+
+/// Chunk length in milliseconds for a number of minutes, rejecting 0 and
+/// lengths too long to plan chunks with
+pub fn chunk_ms(minutes: u64) -> Result<u64> {
+    if minutes == 0 {
+        bail!("Chunk length must be at least 1 minute");
+    }
+    minutes
+        .checked_mul(60_000)
+        .filter(|ms| ms.checked_add(OVERLAP_MS).is_some())
+        .with_context(|| format!("Chunk length of {} minutes is too long", minutes))
+}
+
+/// Split a recording into chunks of `chunk_ms`, each running `OVERLAP_MS`
+/// into the next one
+pub fn plan(duration_ms: u64, chunk_ms: u64) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut start_ms = 0;
+    while start_ms < duration_ms {
+        let length_ms = (chunk_ms + OVERLAP_MS).min(duration_ms - start_ms);
+        chunks.push(Chunk {
+            index: chunks.len(),
+            start_ms,
+            length_ms,
+        });
+        start_ms += chunk_ms;
+    }
+    chunks
+}
+
+/// Whether a recording is long enough to be split into several chunks
+pub fn needs_chunking(duration_ms: u64, chunk_ms: u64) -> bool {
+    chunk_ms > 0 && duration_ms > chunk_ms + OVERLAP_MS
+}
+
+/// Saved chunk transcripts for one input file and set of options
+pub struct WorkDir {
+    path: PathBuf,
+}
+
+impl WorkDir {
+    /// Get the work directories' parent: ~/.cache/cli-programs/transcribe
+    pub fn base_dir() -> Result<PathBuf> {
+        let home = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE"))?;
+        Ok(PathBuf::from(home)
+            .join(".cache")
+            .join("cli-programs")
+            .join("transcribe"))
+    }
+
+    /// Open (or create) the work directory for `input` transcribed with
+    /// `settings`. A changed file or different settings get a fresh directory.
+    pub fn open(input: &Path, settings: &[String]) -> Result<Self> {
+        let path = Self::base_dir()?.join(work_key(input, settings)?);
+        fs::create_dir_all(&path)
+            .with_context(|| format!("Failed to create work directory: {}", path.display()))?;
+        Ok(Self { path })
+    }
+
+    fn transcript_path(&self, chunk: &Chunk) -> PathBuf {
+        self.path.join(format!("chunk-{:04}.txt", chunk.index))
+    }
+
+    /// Where to write a chunk's audio while it is transcribed
+    pub fn audio_path(&self, chunk: &Chunk) -> PathBuf {
+        self.path.join(format!("chunk-{:04}.wav", chunk.index))
+    }
+
+    /// The saved transcript of a finished chunk
+    pub fn transcript(&self, chunk: &Chunk) -> Option<String> {
+        fs::read_to_string(self.transcript_path(chunk)).ok()
+    }
+
+    /// Save a chunk's transcript, via a rename so that an interrupted write
+    /// never looks finished
+    pub fn save(&self, chunk: &Chunk, text: &str) -> Result<()> {
+        let path = self.transcript_path(chunk);
+        let temp = path.with_extension("txt.tmp");
+        fs::write(&temp, text).with_context(|| format!("Failed to write {}", temp.display()))?;
+        fs::rename(&temp, &path).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Delete the work directory once the transcript is complete
+    pub fn remove(self) -> Result<()> {
+        fs::remove_dir_all(&self.path)
+            .with_context(|| format!("Failed to remove {}", self.path.display()))
+    }
+}
+
+/// Directory name identifying an input file (by path, size, and modification
+/// time) and the options it is transcribed with
+fn work_key(input: &Path, settings: &[String]) -> Result<String> {
+    let path = input
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", input.display()))?;
+    let metadata =
+        fs::metadata(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_secs());
+
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    modified.hash(&mut hasher);
+    settings.hash(&mut hasher);
+
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Ok(format!("{}-{:016x}", name, hasher.finish()))
+}
+
+/// Lowercase letters and digits of a word, so punctuation and case that
+/// whisper picks differently in two chunks still match
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Drop the start of `next` that repeats the end of `previous`: the longest
+/// run of words ending `previous` that appears at (or a few words into) the
+/// start of `next`. `next` is returned unchanged when nothing repeats.
+pub fn strip_overlap<'a>(previous: &str, next: &'a str) -> &'a str {
+    let previous: Vec<String> = previous.split_whitespace().map(normalize).collect();
+    let tail = &previous[previous.len().saturating_sub(MAX_OVERLAP_WORDS)..];
+    let words: Vec<&str> = next.split_whitespace().collect();
+    let normalized: Vec<String> = words.iter().map(|word| normalize(word)).collect();
+
+    for len in (MIN_OVERLAP_WORDS..=tail.len().min(words.len())).rev() {
+        let repeat = &tail[tail.len() - len..];
+        for skip in 0..=MAX_SKIPPED_WORDS {
+            if skip + len > words.len() {
+                break;
+            }
+            if normalized[skip..skip + len] == *repeat {
+                let last = words[skip + len - 1];
+                let end = last.as_ptr() as usize - next.as_ptr() as usize + last.len();
+                return next[end..].trim_start();
+            }
+        }
+    }
+
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan() {
+        let chunks = plan(25 * 60_000, 10 * 60_000);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].start_ms, 0);
+        assert_eq!(chunks[0].end_ms(), 10 * 60_000 + OVERLAP_MS);
+        assert_eq!(chunks[1].start_ms, 10 * 60_000);
+        assert_eq!(chunks[2].index, 2);
+        assert_eq!(chunks[2].end_ms(), 25 * 60_000);
+    }
+
+    #[test]
+    fn test_chunk_ms() {
+        assert_eq!(chunk_ms(10).unwrap(), 600_000);
+        assert!(chunk_ms(0).unwrap_err().to_string().contains("at least 1"));
+        assert!(
+            chunk_ms(u64::MAX)
+                .unwrap_err()
+                .to_string()
+                .contains("too long")
+        );
+    }
+
+    #[test]
+    fn test_needs_chunking() {
+        assert!(!needs_chunking(10 * 60_000 + OVERLAP_MS, 10 * 60_000));
+        assert!(needs_chunking(11 * 60_000, 10 * 60_000));
+        assert!(!needs_chunking(3 * 3_600_000, 0));
+    }
+
+    #[test]
+    fn test_strip_overlap() {
+        let previous = "We agreed to ship the release on Friday, after the review.";
+        let next = "on friday after the review. Next item is hiring.";
+        assert_eq!(strip_overlap(previous, next), "Next item is hiring.");
+    }
+
+    #[test]
+    fn test_strip_overlap_skips_garbled_first_words() {
+        let previous = "so the plan is to migrate the database first";
+        let next = "grate the database first\nand then the API.";
+        assert_eq!(strip_overlap(previous, next), "and then the API.");
+    }
+
+    #[test]
+    fn test_strip_overlap_without_repeat() {
+        let previous = "That covers the budget.";
+        let next = "The next topic is travel.";
+        assert_eq!(strip_overlap(previous, next), next);
+        // A single shared word isn't treated as overlap
+        assert_eq!(strip_overlap("Look at the", "the cat"), "the cat");
+        assert_eq!(strip_overlap("", next), next);
+    }
+
+    #[test]
+    fn test_work_dir_saves_transcripts() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("talk.wav");
+        fs::write(&input, b"audio").unwrap();
+
+        let key = work_key(&input, &["medium".to_string()]).unwrap();
+        assert!(key.starts_with("talk-"));
+        assert_ne!(key, work_key(&input, &["large-turbo".to_string()]).unwrap());
+
+        let work = WorkDir {
+            path: dir.path().join(key),
+        };
+        fs::create_dir_all(&work.path).unwrap();
+        let chunk = plan(60_000, 20_000)[1];
+        assert_eq!(work.transcript(&chunk), None);
+        work.save(&chunk, "Hello there.\n").unwrap();
+        assert_eq!(work.transcript(&chunk).as_deref(), Some("Hello there.\n"));
+        assert!(work.audio_path(&chunk).ends_with("chunk-0001.wav"));
+
+        let path = work.path.clone();
+        work.remove().unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -1,5 +1,6 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -16,6 +17,60 @@ pub struct Config {
     /// Default model to use: "medium" or "large-turbo"
     #[serde(default = "default_model")]
     pub default_model: String,
+
+    /// Transcribe recordings longer than this many minutes in chunks of that
+    /// length, with progress and resume (off when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_minutes: Option<u64>,
+
+    /// Named whisper option sets, selected with `--profile`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, WhisperProfile>,
+}
+
+/// whisper-cli tuning options, e.g. a "fast" or "accurate" profile
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WhisperProfile {
+    /// Number of threads (`--threads`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<u32>,
+
+    /// Beam search size (`--beam-size`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub beam_size: Option<u32>,
+
+    /// Number of best candidates to keep (`--best-of`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best_of: Option<u32>,
+
+    /// Sampling temperature (`--temperature`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    /// Any other whisper-cli arguments, passed as-is
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+}
+
+impl WhisperProfile {
+    /// whisper-cli arguments for this profile
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(threads) = self.threads {
+            args.extend(["--threads".to_string(), threads.to_string()]);
+        }
+        if let Some(beam_size) = self.beam_size {
+            args.extend(["--beam-size".to_string(), beam_size.to_string()]);
+        }
+        if let Some(best_of) = self.best_of {
+            args.extend(["--best-of".to_string(), best_of.to_string()]);
+        }
+        if let Some(temperature) = self.temperature {
+            args.extend(["--temperature".to_string(), temperature.to_string()]);
+        }
+        args.extend(self.args.iter().cloned());
+        args
+    }
 }
 
 fn default_whisper_cli_path() -> String {
@@ -38,6 +93,8 @@ impl Default for Config {
             whisper_cli_path: default_whisper_cli_path(),
             models_dir: default_models_dir(),
             default_model: default_model(),
+            chunk_minutes: None,
+            profiles: BTreeMap::new(),
         }
     }
 }
@@ -88,6 +145,31 @@ impl Config {
         };
         PathBuf::from(&self.models_dir).join(model_file)
     }
+
+    /// User-supplied whisper-cli arguments: the selected profile's options
+    /// followed by `--whisper-arg` passthrough, so passthrough wins on conflicts
+    pub fn whisper_args(
+        &self,
+        profile: Option<&str>,
+        passthrough: &[String],
+    ) -> Result<Vec<String>> {
+        let mut args = match profile {
+            Some(name) => match self.profiles.get(name) {
+                Some(profile) => profile.to_args(),
+                None if self.profiles.is_empty() => {
+                    bail!("Unknown profile: {}. No profiles are configured", name)
+                }
+                None => bail!(
+                    "Unknown profile: {}. Available profiles: {}",
+                    name,
+                    self.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+                ),
+            },
+            None => Vec::new(),
+        };
+        args.extend(passthrough.iter().cloned());
+        Ok(args)
+    }
 }
 
 #[cfg(test)]
@@ -100,6 +182,7 @@ mod tests {
         assert_eq!(config.default_model, "medium");
         assert!(config.whisper_cli_path.contains("whisper-cli"));
         assert!(config.models_dir.contains("models"));
+        assert_eq!(config.chunk_minutes, None);
     }
 
     #[test]
@@ -108,6 +191,8 @@ mod tests {
             whisper_cli_path: "/usr/bin/whisper-cli".to_string(),
             models_dir: "/models".to_string(),
             default_model: "medium".to_string(),
+            chunk_minutes: None,
+            profiles: BTreeMap::new(),
         };
 
         assert_eq!(
@@ -126,10 +211,71 @@ mod tests {
 whisper_cli_path = "/custom/path/whisper-cli"
 models_dir = "/custom/models"
 default_model = "large-turbo"
+chunk_minutes = 15
 "#;
         let config: Config = toml::from_str(toml_str).unwrap();
         assert_eq!(config.whisper_cli_path, "/custom/path/whisper-cli");
         assert_eq!(config.models_dir, "/custom/models");
         assert_eq!(config.default_model, "large-turbo");
+        assert_eq!(config.chunk_minutes, Some(15));
+    }
+
+    #[test]
+    fn test_parse_profiles() {
+        let toml_str = r#"
+[profiles.fast]
+threads = 8
+beam_size = 1
+
+[profiles.accurate]
+beam_size = 5
+best_of = 5
+temperature = 0.0
+args = ["--entropy-thold", "2.6"]
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.default_model, "medium");
+        assert_eq!(
+            config.profiles["fast"].to_args(),
+            vec!["--threads", "8", "--beam-size", "1"]
+        );
+        assert_eq!(
+            config.profiles["accurate"].to_args(),
+            vec![
+                "--beam-size",
+                "5",
+                "--best-of",
+                "5",
+                "--temperature",
+                "0",
+                "--entropy-thold",
+                "2.6"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_whisper_args() {
+        let mut config = Config::default();
+        config.profiles.insert(
+            "fast".to_string(),
+            WhisperProfile {
+                threads: Some(8),
+                ..Default::default()
+            },
+        );
+
+        let passthrough = vec!["--language".to_string(), "de".to_string()];
+        assert_eq!(
+            config.whisper_args(Some("fast"), &passthrough).unwrap(),
+            vec!["--threads", "8", "--language", "de"]
+        );
+        assert_eq!(
+            config.whisper_args(None, &passthrough).unwrap(),
+            passthrough
+        );
+
+        let err = config.whisper_args(Some("slow"), &[]).unwrap_err();
+        assert!(err.to_string().contains("Available profiles: fast"));
     }
 }
@@ -1,11 +1,16 @@
 mod audio;
+mod chunks;
 mod config;
+mod llm;
+mod meeting;
 
 use anyhow::{bail, Context, Result};
 use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use config::Config;
-use std::path::PathBuf;
+use llm::LlmClient;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use tempfile::NamedTempFile;
 
 #[derive(Parser, Debug)]
 #[command(name = "transcribe")]
@@ -16,11 +21,24 @@ struct Args {
     file: Option<PathBuf>,
 
     /// Model to use for transcription
-    #[arg(short, long, value_enum)]
+    #[arg(short, long, value_enum, global = true)]
     model: Option<Model>,
 
+    /// Named whisper option profile from the config (e.g. "fast", "accurate")
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Extra argument passed to whisper-cli as-is (repeatable, applied after the profile)
+    #[arg(long = "whisper-arg", value_name = "ARG", global = true, allow_hyphen_values = true)]
+    whisper_args: Vec<String>,
+
+    /// Transcribe recordings longer than this many minutes in chunks of that
+    /// length, resuming after an interruption (defaults to config; off if unset)
+    #[arg(long, value_name = "MINUTES")]
+    chunk_minutes: Option<u64>,
+
     /// Show debug output
-    #[arg(long)]
+    #[arg(long, global = true)]
     debug: bool,
 
     #[command(subcommand)]
@@ -46,6 +64,26 @@ impl Model {
 
 #[derive(Subcommand, Debug)]
 enum Commands {
+    /// Transcribe a meeting recording into timestamped notes
+    #[command(
+        long_about = "Transcribe a meeting recording into timestamped notes.\n\n\
+        Segments keep their timestamps. Stereo recordings (one speaker per channel) are \
+        diarized, and consecutive segments from the same speaker are merged.\n\n\
+        With --summarize, the transcript is sent to an LLM (via llm-client) and a summary \
+        with decisions and action items is added to the notes."
+    )]
+    Meeting {
+        /// Meeting recording to transcribe
+        file: PathBuf,
+
+        /// Add a summary with decisions and action items using an LLM
+        #[arg(short, long)]
+        summarize: bool,
+
+        /// LLM preset for the summary (overrides default from llm-client config)
+        #[arg(short, long, requires = "summarize")]
+        preset: Option<String>,
+    },
     /// Manage configuration
     Config {
         #[command(subcommand)]
@@ -59,19 +97,39 @@ enum ConfigAction {
     Show,
     /// Set a configuration value
     Set {
-        /// Configuration key (whisper_cli_path, models_dir, default_model)
+        /// Configuration key (whisper_cli_path, models_dir, default_model, chunk_minutes)
         key: String,
         /// Value to set
         value: String,
     },
 }
 
-fn main() -> Result<()> {
+#[tokio::main]
+async fn main() -> Result<()> {
     let args = Args::parse();
 
-    // Handle config subcommands
-    if let Some(Commands::Config { action }) = args.command {
-        return handle_config_command(action);
+    match args.command {
+        // Handle config subcommands
+        Some(Commands::Config { action }) => return handle_config_command(action),
+        Some(Commands::Meeting {
+            file,
+            summarize,
+            preset,
+        }) => {
+            let config = Config::load().context("Failed to load configuration")?;
+            let whisper_args = config.whisper_args(args.profile.as_deref(), &args.whisper_args)?;
+            return run_meeting(
+                &config,
+                &file,
+                args.model,
+                &whisper_args,
+                summarize,
+                preset.as_deref(),
+                args.debug,
+            )
+            .await;
+        }
+        None => {}
     }
 
     // Show help if no file argument provided
@@ -82,6 +140,7 @@ fn main() -> Result<()> {
 
     // Load config
     let config = Config::load().context("Failed to load configuration")?;
+    let whisper_args = config.whisper_args(args.profile.as_deref(), &args.whisper_args)?;
 
     // Get the input file (safe to unwrap since we checked above)
     let input_file = args.file.unwrap();
@@ -90,9 +149,121 @@ fn main() -> Result<()> {
         bail!("Input file not found: {}", input_file.display());
     }
 
+    let model_path = resolve_model(&config, args.model)?;
+    let audio_info =
+        audio::check_audio_format(&input_file).context("Failed to analyze audio file")?;
+
+    if let Some(minutes) = args.chunk_minutes.or(config.chunk_minutes) {
+        let chunk_ms = chunks::chunk_ms(minutes)?;
+        if let Some(duration_ms) = audio_info.duration_ms
+            && chunks::needs_chunking(duration_ms, chunk_ms)
+        {
+            return transcribe_chunked(
+                &config,
+                &model_path,
+                &input_file,
+                duration_ms,
+                chunk_ms,
+                &whisper_args,
+                args.debug,
+            );
+        }
+    }
+
+    let audio = prepare_audio(&input_file, &audio_info, false, args.debug)?;
+
+    if args.debug {
+        eprintln!("Using model: {}", model_path.display());
+        eprintln!("Transcribing: {}", audio.path.display());
+    }
+
+    let stdout = run_whisper(
+        &config,
+        &model_path,
+        &audio.path,
+        &[
+            "--no-timestamps",
+            "-nt", // No timestamps in output
+        ],
+        &whisper_args,
+    )?;
+
+    // whisper-cli outputs some metadata lines before the transcription
+    // The actual transcription starts after the model loading messages
+    let transcription = extract_transcription(&stdout);
+    print!("{}", transcription);
+
+    Ok(())
+}
+
+/// Transcribe a meeting with timestamps and speakers, optionally adding LLM notes
+async fn run_meeting(
+    config: &Config,
+    input_file: &Path,
+    model: Option<Model>,
+    whisper_args: &[String],
+    summarize: bool,
+    preset: Option<&str>,
+    debug: bool,
+) -> Result<()> {
+    if !input_file.exists() {
+        bail!("Input file not found: {}", input_file.display());
+    }
+
+    let model_path = resolve_model(config, model)?;
+
+    // Keep stereo recordings as two channels so whisper can diarize them
+    let audio_info =
+        audio::check_audio_format(input_file).context("Failed to analyze audio file")?;
+    let audio = prepare_audio(input_file, &audio_info, true, debug)?;
+
+    if debug {
+        eprintln!("Using model: {}", model_path.display());
+        eprintln!("Transcribing: {}", audio.path.display());
+        if !audio.stereo {
+            eprintln!("Mono recording: speakers will not be labeled");
+        }
+    }
+
+    let diarize_args: &[&str] = if audio.stereo { &["--diarize"] } else { &[] };
+    let stdout = run_whisper(config, &model_path, &audio.path, diarize_args, whisper_args)?;
+
+    let segments = meeting::merge_segments(meeting::parse_segments(&stdout));
+    if segments.is_empty() {
+        bail!("whisper-cli produced no transcript segments");
+    }
+    let transcript = meeting::format_transcript(&segments);
+
+    let summary = if summarize {
+        eprintln!("Summarizing meeting...");
+        let llm = LlmClient::new(preset, debug)?;
+        Some(
+            llm.complete(
+                &meeting::build_summary_prompt(&transcript),
+                meeting::SUMMARY_SYSTEM_PROMPT,
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+
+    let title = input_file
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| input_file.display().to_string());
+    print!(
+        "{}",
+        meeting::format_notes(&title, &transcript, summary.as_deref())
+    );
+
+    Ok(())
+}
+
+/// Resolve the model file and check that whisper-cli and the model exist
+fn resolve_model(config: &Config, model: Option<Model>) -> Result<PathBuf> {
     // Determine which model to use
-    let model_name = args
-        .model
+    let model_name = model
         .map(|m| m.as_str().to_string())
         .unwrap_or_else(|| config.default_model.clone());
 
@@ -113,44 +284,190 @@ fn main() -> Result<()> {
         );
     }
 
-    // Check audio format
-    let audio_info = audio::check_audio_format(&input_file)
-        .context("Failed to analyze audio file")?;
+    Ok(model_path)
+}
 
-    if args.debug {
+/// Audio ready for whisper-cli
+struct PreparedAudio {
+    path: PathBuf,
+    /// Kept as two channels for diarization
+    stereo: bool,
+    /// Converted copy of the input, deleted when dropped
+    _temp_file: Option<NamedTempFile>,
+}
+
+/// Convert the input audio to 16kHz mono if needed.
+/// With `keep_stereo`, stereo input stays stereo so whisper can diarize it.
+fn prepare_audio(
+    input_file: &Path,
+    audio_info: &audio::AudioInfo,
+    keep_stereo: bool,
+    debug: bool,
+) -> Result<PreparedAudio> {
+    if debug {
         eprintln!(
             "Audio: {} Hz, {} channel(s), codec: {}",
             audio_info.sample_rate, audio_info.channels, audio_info.codec
         );
     }
 
+    let stereo = keep_stereo && audio_info.is_stereo();
+    let channels = if stereo { 2 } else { 1 };
+
     // Convert if needed
-    let (transcription_file, _temp_file) = if audio_info.needs_conversion() {
-        let issues = audio_info.issues().join(", ");
-        eprintln!("Converting audio ({})...", issues);
+    if !audio_info.needs_conversion(channels) {
+        return Ok(PreparedAudio {
+            path: input_file.to_path_buf(),
+            stereo,
+            _temp_file: None,
+        });
+    }
 
-        let temp = audio::convert_audio(&input_file).context("Failed to convert audio")?;
-        let path = temp.path().to_path_buf();
-        (path, Some(temp))
+    let issues = if stereo {
+        format!("sample rate is {} Hz", audio_info.sample_rate)
     } else {
-        (input_file.clone(), None)
+        audio_info.issues().join(", ")
     };
+    eprintln!("Converting audio ({})...", issues);
+
+    let temp = audio::convert_audio(input_file, channels).context("Failed to convert audio")?;
+    Ok(PreparedAudio {
+        path: temp.path().to_path_buf(),
+        stereo,
+        _temp_file: Some(temp),
+    })
+}
 
-    if args.debug {
+/// Transcribe a long recording chunk by chunk, printing each chunk's text as
+/// it is done. Finished chunks are saved, so running the same command again
+/// after an interruption starts at the first unfinished chunk.
+fn transcribe_chunked(
+    config: &Config,
+    model_path: &Path,
+    input_file: &Path,
+    duration_ms: u64,
+    chunk_ms: u64,
+    whisper_args: &[String],
+    debug: bool,
+) -> Result<()> {
+    let chunks = chunks::plan(duration_ms, chunk_ms);
+
+    if debug {
         eprintln!("Using model: {}", model_path.display());
-        eprintln!("Transcribing: {}", transcription_file.display());
+        eprintln!(
+            "Transcribing: {} in {} chunks",
+            input_file.display(),
+            chunks.len()
+        );
+    }
+
+    let mut settings = vec![model_path.display().to_string(), chunk_ms.to_string()];
+    settings.extend(whisper_args.iter().cloned());
+    let work = chunks::WorkDir::open(input_file, &settings)?;
+
+    let done = chunks
+        .iter()
+        .filter(|chunk| work.transcript(chunk).is_some())
+        .count();
+    if done > 0 {
+        eprintln!(
+            "Resuming: {} of {} chunks already transcribed",
+            done,
+            chunks.len()
+        );
+    }
+
+    let mut previous = String::new();
+    for chunk in &chunks {
+        let text = match work.transcript(chunk) {
+            Some(text) => text,
+            None => {
+                eprintln!(
+                    "Transcribing chunk {}/{} ({} - {})...",
+                    chunk.index + 1,
+                    chunks.len(),
+                    meeting::format_timestamp(chunk.start_ms),
+                    meeting::format_timestamp(chunk.end_ms())
+                );
+                let text = transcribe_chunk(
+                    config,
+                    model_path,
+                    input_file,
+                    &work,
+                    chunk,
+                    whisper_args,
+                    debug,
+                )
+                .with_context(|| {
+                    format!(
+                        "Failed on chunk {}/{}; run the same command again to resume",
+                        chunk.index + 1,
+                        chunks.len()
+                    )
+                })?;
+                work.save(chunk, &text)?;
+                text
+            }
+        };
+
+        let new_text = chunks::strip_overlap(&previous, &text).trim_end();
+        if !new_text.is_empty() {
+            println!("{}", new_text);
+        }
+        previous = text;
+    }
+
+    work.remove()
+}
+
+/// Cut one chunk out of the recording and transcribe it
+fn transcribe_chunk(
+    config: &Config,
+    model_path: &Path,
+    input_file: &Path,
+    work: &chunks::WorkDir,
+    chunk: &chunks::Chunk,
+    whisper_args: &[String],
+    debug: bool,
+) -> Result<String> {
+    let audio_path = work.audio_path(chunk);
+    audio::extract_segment(input_file, chunk.start_ms, chunk.length_ms, &audio_path)?;
+
+    if debug {
+        eprintln!("Transcribing: {}", audio_path.display());
     }
 
-    // Run whisper-cli
+    let stdout = run_whisper(
+        config,
+        model_path,
+        &audio_path,
+        &["--no-timestamps", "-nt"],
+        whisper_args,
+    );
+    let _ = std::fs::remove_file(&audio_path);
+
+    Ok(extract_transcription(&stdout?))
+}
+
+/// Run whisper-cli on a prepared audio file and return its stdout.
+/// `user_args` (profile and `--whisper-arg` options) come last so they can
+/// override the built-in ones.
+fn run_whisper(
+    config: &Config,
+    model_path: &Path,
+    audio_file: &Path,
+    extra_args: &[&str],
+    user_args: &[String],
+) -> Result<String> {
     let output = Command::new(&config.whisper_cli_path)
         .args([
             "-f",
-            transcription_file.to_str().context("Invalid file path")?,
+            audio_file.to_str().context("Invalid file path")?,
             "-m",
             model_path.to_str().context("Invalid model path")?,
-            "--no-timestamps",
-            "-nt", // No timestamps in output
         ])
+        .args(extra_args)
+        .args(user_args)
         .output()
         .context("Failed to run whisper-cli")?;
 
@@ -159,15 +476,7 @@ fn main() -> Result<()> {
         bail!("whisper-cli failed: {}", stderr);
     }
 
-    // Parse and print the transcription
-    let stdout = String::from_utf8(output.stdout).context("Invalid UTF-8 in whisper output")?;
-
-    // whisper-cli outputs some metadata lines before the transcription
-    // The actual transcription starts after the model loading messages
-    let transcription = extract_transcription(&stdout);
-    print!("{}", transcription);
-
-    Ok(())
+    String::from_utf8(output.stdout).context("Invalid UTF-8 in whisper output")
 }
 
 /// Extract the transcription text from whisper-cli output
@@ -216,6 +525,13 @@ fn handle_config_command(action: ConfigAction) -> Result<()> {
             println!("whisper_cli_path = \"{}\"", config.whisper_cli_path);
             println!("models_dir = \"{}\"", config.models_dir);
             println!("default_model = \"{}\"", config.default_model);
+            match config.chunk_minutes {
+                Some(minutes) => println!("chunk_minutes = {}", minutes),
+                None => println!("chunk_minutes = off"),
+            }
+            for (name, profile) in &config.profiles {
+                println!("profile {}: {}", name, profile.to_args().join(" "));
+            }
 
             // Show status of paths
             println!();
@@ -257,8 +573,16 @@ fn handle_config_command(action: ConfigAction) -> Result<()> {
                     }
                     config.default_model = value;
                 }
+                "chunk_minutes" if value == "off" => config.chunk_minutes = None,
+                "chunk_minutes" => {
+                    let minutes = value
+                        .parse()
+                        .with_context(|| format!("Invalid number of minutes: {}", value))?;
+                    chunks::chunk_ms(minutes)?;
+                    config.chunk_minutes = Some(minutes);
+                }
                 _ => bail!(
-                    "Unknown config key: {}. Valid keys: whisper_cli_path, models_dir, default_model",
+                    "Unknown config key: {}. Valid keys: whisper_cli_path, models_dir, default_model, chunk_minutes",
                     key
                 ),
             }
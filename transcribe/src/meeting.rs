@@ -0,0 +1,247 @@
+//! Meeting mode: timestamped, speaker-grouped transcripts with optional notes
+//!
+//! whisper-cli prints one line per segment, e.g.
+//! `[00:00:01.000 --> 00:00:04.500]  (speaker 0) Let's get started.`
+//! The speaker tag is only present when `--diarize` is used on stereo audio.
+
+/// A span of speech from the transcript
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    /// Start time in milliseconds
+    pub start_ms: u64,
+    /// End time in milliseconds
+    pub end_ms: u64,
+    /// Speaker label from whisper's diarization, e.g. "0"
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+/// System prompt for the summarization pass
+pub const SUMMARY_SYSTEM_PROMPT: &str = "You turn meeting transcripts into concise notes. \
+Respond in Markdown with exactly these sections:\n\
+### Summary\nA short paragraph describing what the meeting covered.\n\
+### Decisions\nA bulleted list of decisions that were made.\n\
+### Action Items\nA bulleted list of action items, naming the owner when the transcript makes it clear.\n\
+Write \"None\" under a section if the transcript contains nothing for it. \
+Do not invent details that are not in the transcript.";
+
+/// Parse a whisper timestamp like "01:02:03.456" into milliseconds
+fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let (hms, millis) = timestamp.trim().split_once('.')?;
+    let mut parts = hms.split(':').map(|p| p.parse::<u64>().ok());
+    let (hours, minutes, seconds) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(((hours * 60 + minutes) * 60 + seconds) * 1000 + millis.parse::<u64>().ok()?)
+}
+
+/// Format milliseconds as "HH:MM:SS"
+pub fn format_timestamp(ms: u64) -> String {
+    let seconds = ms / 1000;
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60
+    )
+}
+
+/// Parse whisper-cli segment lines, ignoring status output, empty segments,
+/// and non-speech markers like `[BLANK_AUDIO]`
+pub fn parse_segments(output: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+
+    for line in output.lines() {
+        let Some(rest) = line.trim_start().strip_prefix('[') else {
+            continue;
+        };
+        let Some((range, text)) = rest.split_once(']') else {
+            continue;
+        };
+        let Some((start, end)) = range.split_once("-->") else {
+            continue;
+        };
+        let (Some(start_ms), Some(end_ms)) = (parse_timestamp(start), parse_timestamp(end)) else {
+            continue;
+        };
+
+        let mut text = text.trim();
+        let mut speaker = None;
+        if let Some(tagged) = text.strip_prefix("(speaker ")
+            && let Some((label, remainder)) = tagged.split_once(')')
+        {
+            if label != "?" {
+                speaker = Some(label.to_string());
+            }
+            text = remainder.trim();
+        }
+
+        if text.is_empty() || (text.starts_with('[') && text.ends_with(']')) {
+            continue;
+        }
+
+        segments.push(Segment {
+            start_ms,
+            end_ms,
+            speaker,
+            text: text.to_string(),
+        });
+    }
+
+    segments
+}
+
+/// Merge consecutive segments from the same known speaker into one
+pub fn merge_segments(segments: Vec<Segment>) -> Vec<Segment> {
+    let mut merged: Vec<Segment> = Vec::new();
+
+    for segment in segments {
+        if let Some(last) = merged.last_mut()
+            && segment.speaker.is_some()
+            && last.speaker == segment.speaker
+        {
+            last.end_ms = segment.end_ms;
+            last.text.push(' ');
+            last.text.push_str(&segment.text);
+            continue;
+        }
+        merged.push(segment);
+    }
+
+    merged
+}
+
+/// Render segments as one "[HH:MM:SS] Speaker N: text" line per segment
+pub fn format_transcript(segments: &[Segment]) -> String {
+    let mut out = String::new();
+    for segment in segments {
+        out.push_str(&format!("[{}] ", format_timestamp(segment.start_ms)));
+        if let Some(speaker) = &segment.speaker {
+            out.push_str(&format!("Speaker {}: ", speaker));
+        }
+        out.push_str(&segment.text);
+        out.push('\n');
+    }
+    out
+}
+
+/// Build the user prompt for the summarization pass
+pub fn build_summary_prompt(transcript: &str) -> String {
+    format!(
+        "Summarize this meeting transcript. Lines are prefixed with timestamps \
+         and, when known, the speaker.\n\n{}",
+        transcript
+    )
+}
+
+/// Assemble the final meeting notes document
+pub fn format_notes(title: &str, transcript: &str, summary: Option<&str>) -> String {
+    let mut out = format!("# Meeting: {}\n\n", title);
+    if let Some(summary) = summary {
+        out.push_str("## Notes\n\n");
+        out.push_str(summary.trim());
+        out.push_str("\n\n");
+    }
+    out.push_str("## Transcript\n\n");
+    out.push_str(transcript);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = "whisper_init_from_file_with_params_no_state: loading model
+main: processing 'meeting.wav' (1000 samples, 62.5 sec), 4 threads
+
+[00:00:00.000 --> 00:00:03.200]  (speaker 0) Let's get started.
+[00:00:03.200 --> 00:00:06.000]  (speaker 0) First item is the release.
+[00:00:06.000 --> 00:00:09.500]  (speaker 1) We ship on Friday.
+[00:00:09.500 --> 00:00:10.000]  (speaker ?) [BLANK_AUDIO]
+[00:01:02.000 --> 00:01:05.000]  (speaker ?) Sounds good.
+
+whisper_print_timings:     total time =  1234.56 ms
+";
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("00:00:00.000"), Some(0));
+        assert_eq!(parse_timestamp(" 01:02:03.456 "), Some(3_723_456));
+        assert_eq!(parse_timestamp("02:03.456"), None);
+        assert_eq!(parse_timestamp("garbage"), None);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "00:00:00");
+        assert_eq!(format_timestamp(3_723_456), "01:02:03");
+    }
+
+    #[test]
+    fn test_parse_segments() {
+        let segments = parse_segments(OUTPUT);
+        assert_eq!(segments.len(), 4);
+        assert_eq!(segments[0].start_ms, 0);
+        assert_eq!(segments[0].end_ms, 3200);
+        assert_eq!(segments[0].speaker.as_deref(), Some("0"));
+        assert_eq!(segments[0].text, "Let's get started.");
+        assert_eq!(segments[2].speaker.as_deref(), Some("1"));
+        assert_eq!(segments[3].speaker, None);
+        assert_eq!(segments[3].start_ms, 62_000);
+    }
+
+    #[test]
+    fn test_parse_segments_without_diarization() {
+        let segments = parse_segments("[00:00:00.000 --> 00:00:02.000]   Hello there.\n");
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].speaker, None);
+        assert_eq!(segments[0].text, "Hello there.");
+    }
+
+    #[test]
+    fn test_merge_segments() {
+        let merged = merge_segments(parse_segments(OUTPUT));
+        assert_eq!(merged.len(), 3);
+        assert_eq!(
+            merged[0].text,
+            "Let's get started. First item is the release."
+        );
+        assert_eq!(merged[0].end_ms, 6000);
+        assert_eq!(merged[1].text, "We ship on Friday.");
+    }
+
+    #[test]
+    fn test_merge_segments_keeps_unknown_speakers_separate() {
+        let segments = parse_segments(
+            "[00:00:00.000 --> 00:00:01.000]  One.\n[00:00:01.000 --> 00:00:02.000]  Two.\n",
+        );
+        assert_eq!(merge_segments(segments).len(), 2);
+    }
+
+    #[test]
+    fn test_format_transcript() {
+        let transcript = format_transcript(&merge_segments(parse_segments(OUTPUT)));
+        let lines: Vec<&str> = transcript.lines().collect();
+        assert_eq!(
+            lines[0],
+            "[00:00:00] Speaker 0: Let's get started. First item is the release."
+        );
+        assert_eq!(lines[1], "[00:00:06] Speaker 1: We ship on Friday.");
+        assert_eq!(lines[2], "[00:01:02] Sounds good.");
+    }
+
+    #[test]
+    fn test_format_notes() {
+        let notes = format_notes(
+            "standup.m4a",
+            "[00:00:00] Hi.\n",
+            Some("### Summary\nShort.\n"),
+        );
+        assert!(notes.starts_with("# Meeting: standup.m4a\n\n## Notes\n\n### Summary"));
+        assert!(notes.ends_with("## Transcript\n\n[00:00:00] Hi.\n"));
+
+        let plain = format_notes("standup.m4a", "[00:00:00] Hi.\n", None);
+        assert!(!plain.contains("## Notes"));
+    }
+}
@@ -0,0 +1,79 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Deserialize)]
+struct MemberToml {
+    #[serde(default)]
+    bin: Vec<BinTarget>,
+}
+
+#[derive(Deserialize)]
+struct BinTarget {
+    name: String,
+}
+
+/// Names of the binaries a workspace member builds: its `[[bin]]` targets,
+/// `src/main.rs` under the member's name, and each file in `src/bin/`.
+/// A library-only member builds none.
+pub fn member_binaries(member_dir: &Path, member: &str) -> Vec<String> {
+    let mut binaries: Vec<String> = fs::read_to_string(member_dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| toml::from_str::<MemberToml>(&content).ok())
+        .map(|toml| toml.bin.into_iter().map(|bin| bin.name).collect())
+        .unwrap_or_default();
+
+    if member_dir.join("src").join("main.rs").exists() {
+        binaries.push(member.to_string());
+    }
+
+    if let Ok(entries) = fs::read_dir(member_dir.join("src").join("bin")) {
+        let mut extra: Vec<String> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+            .collect();
+        extra.sort();
+        binaries.extend(extra);
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    binaries.retain(|name| seen.insert(name.clone()));
+    binaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_member_binaries() {
+        let dir = std::env::temp_dir().join(format!("update-cli-programs-{}", std::process::id()));
+        let member = |name: &str, toml: &str, files: &[&str]| {
+            let member_dir = dir.join(name);
+            fs::create_dir_all(member_dir.join("src").join("bin")).unwrap();
+            fs::write(member_dir.join("Cargo.toml"), toml).unwrap();
+            for file in files {
+                fs::write(member_dir.join("src").join(file), "").unwrap();
+            }
+            member_binaries(&member_dir, name)
+        };
+
+        let tool = member(
+            "tool",
+            "[package]\nname = \"tool\"\n\n[[bin]]\nname = \"tool\"\npath = \"src/main.rs\"\n",
+            &["main.rs"],
+        );
+        let library = member(
+            "library",
+            "[package]\nname = \"library\"\n",
+            &["lib.rs", "bin/helper.rs", "bin/admin.rs", "bin/notes.txt"],
+        );
+        let plain = member("plain", "[package]\nname = \"plain\"\n", &["main.rs"]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(tool, vec!["tool"]);
+        assert_eq!(library, vec!["admin", "helper"]);
+        assert_eq!(plain, vec!["plain"]);
+    }
+}
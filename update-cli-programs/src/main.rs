@@ -1,5 +1,11 @@
+mod binaries;
+mod manifest;
+mod path_check;
+
 use anyhow::{Context, Result};
-use clap::Parser;
+use chrono::Utc;
+use clap::{Parser, Subcommand};
+use manifest::{InstalledTool, Manifest};
 use serde::Deserialize;
 use std::fs;
 use std::os::unix::fs::PermissionsExt;
@@ -17,6 +23,18 @@ struct Cli {
     /// Target directory (defaults to ~/.local/bin)
     #[arg(short, long)]
     target: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Restore the previously installed binary of a tool
+    Rollback {
+        /// Name of the tool to roll back
+        tool: String,
+    },
 }
 
 #[derive(Deserialize)]
@@ -24,6 +42,16 @@ struct WorkspaceToml {
     workspace: Workspace,
 }
 
+#[derive(Deserialize)]
+struct PackageToml {
+    package: Package,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    version: String,
+}
+
 #[derive(Deserialize)]
 struct Workspace {
     members: Vec<String>,
@@ -32,6 +60,10 @@ struct Workspace {
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    if let Some(Commands::Rollback { tool }) = &cli.command {
+        return rollback(tool);
+    }
+
     let workspace_root = Path::new(WORKSPACE_ROOT)
         .parent()
         .context("Failed to determine workspace root")?;
@@ -70,7 +102,7 @@ fn main() -> Result<()> {
     println!("Building Rust tools...");
 
     let build_status = Command::new("cargo")
-        .args(&["build", "--release", "--workspace"])
+        .args(["build", "--release", "--workspace"])
         .current_dir(workspace_root)
         .status()
         .context("Failed to run cargo build")?;
@@ -81,8 +113,23 @@ fn main() -> Result<()> {
 
     println!("\nInstalling programs:");
 
+    let mut manifest = Manifest::load().context("Failed to load install manifest")?;
+    let git_commit = workspace_commit(workspace_root);
+    let built_at = Utc::now().to_rfc3339();
+
+    // Members can build several binaries (e.g. llm-client's `llm`), or none
+    let binaries: Vec<(&String, String)> = programs
+        .iter()
+        .flat_map(|member| {
+            binaries::member_binaries(&workspace_root.join(member), member)
+                .into_iter()
+                .map(move |binary| (member, binary))
+        })
+        .collect();
+    let mut installed = Vec::new();
+
     // Install each program
-    for program in &programs {
+    for (member, program) in &binaries {
         let binary_path = workspace_root.join("target").join("release").join(program);
 
         if !binary_path.exists() {
@@ -91,37 +138,152 @@ fn main() -> Result<()> {
 
         let target_path = target_dir.join(program);
 
-        // Remove old binary first to invalidate macOS code signature cache.
-        // If we overwrite in-place, macOS may cache the old signature and kill
-        // the new binary with "zsh: killed" until reboot.
-        if target_path.exists() {
-            fs::remove_file(&target_path)
-                .with_context(|| format!("Failed to remove old {}", target_path.display()))?;
-        }
-
-        // Copy new binary
-        fs::copy(&binary_path, &target_path)
-            .with_context(|| format!("Failed to copy {} to {}", program, target_path.display()))?;
-
-        // Make executable
-        let mut perms = fs::metadata(&target_path)?.permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(&target_path, perms)
-            .with_context(|| format!("Failed to set permissions on {}", target_path.display()))?;
+        // Keep the old binary so it can be restored with `rollback`
+        let backed_up = if target_path.exists() {
+            let backup_path = Manifest::backup_path(program)?;
+            if let Some(parent) = backup_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&target_path, &backup_path)
+                .with_context(|| format!("Failed to back up {}", target_path.display()))?;
+            true
+        } else {
+            false
+        };
+
+        install_binary(&binary_path, &target_path)?;
+
+        let version = package_version(&workspace_root.join(member)).unwrap_or_default();
+        manifest.record_install(
+            program,
+            InstalledTool {
+                version,
+                git_commit: git_commit.clone(),
+                built_at: built_at.clone(),
+                sha256: manifest::hash_file(&target_path)?,
+                path: target_path.clone(),
+                previous: None,
+            },
+            backed_up,
+        );
 
         println!("  - {}", program);
+        installed.push(program.clone());
     }
 
+    manifest.save().context("Failed to save install manifest")?;
+
     println!("\nPrograms installed to {}", target_dir.display());
 
+    path_check::check(&target_dir, Path::new(&home), &installed)?;
+
     // Check for ask shell integration if ask was installed
-    if programs.contains(&"ask".to_string()) {
+    if installed.contains(&"ask".to_string()) {
         check_ask_shell_integration(&home);
     }
 
     Ok(())
 }
 
+/// Copy a binary into place and make it executable
+fn install_binary(source: &Path, target_path: &Path) -> Result<()> {
+    // Remove old binary first to invalidate macOS code signature cache.
+    // If we overwrite in-place, macOS may cache the old signature and kill
+    // the new binary with "zsh: killed" until reboot.
+    if target_path.exists() {
+        fs::remove_file(target_path)
+            .with_context(|| format!("Failed to remove old {}", target_path.display()))?;
+    }
+
+    // Copy new binary
+    fs::copy(source, target_path).with_context(|| {
+        format!(
+            "Failed to copy {} to {}",
+            source.display(),
+            target_path.display()
+        )
+    })?;
+
+    // Make executable
+    let mut perms = fs::metadata(target_path)?.permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(target_path, perms)
+        .with_context(|| format!("Failed to set permissions on {}", target_path.display()))?;
+
+    Ok(())
+}
+
+/// Restore a tool's retained backup, keeping the current binary as the new backup
+fn rollback(tool: &str) -> Result<()> {
+    let mut manifest = Manifest::load().context("Failed to load install manifest")?;
+    let entry = manifest
+        .tools
+        .get(tool)
+        .with_context(|| format!("{} is not in the install manifest", tool))?;
+    let Some(previous) = entry.previous.as_deref() else {
+        anyhow::bail!("No previous version of {} to roll back to", tool);
+    };
+
+    let backup_path = Manifest::backup_path(tool)?;
+    if !backup_path.exists() {
+        anyhow::bail!("Backup binary missing: {}", backup_path.display());
+    }
+    if manifest::hash_file(&backup_path)? != previous.sha256 {
+        anyhow::bail!(
+            "Backup binary {} does not match the recorded hash",
+            backup_path.display()
+        );
+    }
+
+    let target_path = entry.path.clone();
+    let swap_path = backup_path.with_extension("swap");
+    fs::copy(&target_path, &swap_path)
+        .with_context(|| format!("Failed to back up {}", target_path.display()))?;
+    install_binary(&backup_path, &target_path)?;
+    fs::rename(&swap_path, &backup_path)
+        .with_context(|| format!("Failed to update backup {}", backup_path.display()))?;
+
+    manifest.record_rollback(tool)?;
+    manifest.save().context("Failed to save install manifest")?;
+
+    let restored = &manifest.tools[tool];
+    println!(
+        "Rolled back {} to {} ({})",
+        tool,
+        restored.version,
+        restored.git_commit.as_deref().unwrap_or("unknown commit")
+    );
+    Ok(())
+}
+
+/// Read the package version from a member's Cargo.toml
+fn package_version(member_dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(member_dir.join("Cargo.toml")).ok()?;
+    let package: PackageToml = toml::from_str(&content).ok()?;
+    Some(package.package.version)
+}
+
+/// Current workspace commit, suffixed with `-dirty` if there are uncommitted changes
+fn workspace_commit(workspace_root: &Path) -> Option<String> {
+    let run = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(workspace_root)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    };
+
+    let commit = run(&["rev-parse", "--short", "HEAD"])?;
+    let dirty = run(&["status", "--porcelain"]).is_some_and(|s| !s.is_empty());
+    Some(if dirty {
+        format!("{}-dirty", commit)
+    } else {
+        commit
+    })
+}
+
 /// Check if the ask shell integration is set up
 fn check_ask_shell_integration(home: &str) {
     let shell = std::env::var("SHELL").unwrap_or_default();
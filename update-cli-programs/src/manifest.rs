@@ -0,0 +1,195 @@
+// Manifest of installed tools and retained backups for rollback
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Record of a single installed binary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstalledTool {
+    /// Package version from the tool's Cargo.toml
+    pub version: String,
+    /// Workspace git commit the binary was built from (suffixed with `-dirty`
+    /// when the working tree had uncommitted changes)
+    pub git_commit: Option<String>,
+    /// RFC 3339 timestamp of the build/install
+    pub built_at: String,
+    /// SHA-256 of the installed binary
+    pub sha256: String,
+    /// Where the binary was installed
+    pub path: PathBuf,
+    /// The install this one replaced, if a backup was retained
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous: Option<Box<InstalledTool>>,
+}
+
+/// All installed tools, keyed by name
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(default)]
+    pub tools: BTreeMap<String, InstalledTool>,
+}
+
+impl Manifest {
+    /// Directory holding the manifest and backups: ~/.config/cli-programs
+    pub fn config_dir() -> Result<PathBuf> {
+        let home = std::env::var("HOME").context("HOME environment variable not set")?;
+        Ok(PathBuf::from(home).join(".config").join("cli-programs"))
+    }
+
+    /// Path of the manifest file
+    pub fn path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join("installed.json"))
+    }
+
+    /// Path of the retained backup binary for a tool
+    pub fn backup_path(tool: &str) -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join("backups").join(tool))
+    }
+
+    /// Load the manifest, returning an empty one if it doesn't exist yet
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid manifest {}", path.display()))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path()?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Record a new install, keeping the replaced entry (without its own history)
+    /// as the rollback target when a backup binary was retained
+    pub fn record_install(&mut self, tool: &str, mut entry: InstalledTool, backed_up: bool) {
+        if backed_up && let Some(mut old) = self.tools.remove(tool) {
+            old.previous = None;
+            entry.previous = Some(Box::new(old));
+        }
+        self.tools.insert(tool.to_string(), entry);
+    }
+
+    /// Swap a tool's current and previous entries after a rollback, so rolling
+    /// back again returns to the newer build
+    pub fn record_rollback(&mut self, tool: &str) -> Result<()> {
+        let current = self
+            .tools
+            .remove(tool)
+            .with_context(|| format!("{} is not in the install manifest", tool))?;
+        let Some(previous) = current.previous.clone() else {
+            self.tools.insert(tool.to_string(), current);
+            anyhow::bail!("No previous version of {} recorded", tool);
+        };
+
+        let mut restored = *previous;
+        let mut replaced = current;
+        replaced.previous = None;
+        restored.previous = Some(Box::new(replaced));
+        self.tools.insert(tool.to_string(), restored);
+        Ok(())
+    }
+}
+
+/// SHA-256 of a file as lowercase hex
+pub fn hash_file(path: &Path) -> Result<String> {
+    let data = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(hex::encode(Sha256::digest(&data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(version: &str) -> InstalledTool {
+        InstalledTool {
+            version: version.to_string(),
+            git_commit: Some("abc123".to_string()),
+            built_at: "2025-10-20T10:00:00Z".to_string(),
+            sha256: format!("hash-{}", version),
+            path: PathBuf::from("/home/user/.local/bin/gc"),
+            previous: None,
+        }
+    }
+
+    #[test]
+    fn test_record_install_keeps_one_previous() {
+        let mut manifest = Manifest::default();
+        manifest.record_install("gc", entry("1.0.0"), false);
+        manifest.record_install("gc", entry("1.1.0"), true);
+        manifest.record_install("gc", entry("1.2.0"), true);
+
+        let current = &manifest.tools["gc"];
+        assert_eq!(current.version, "1.2.0");
+        let previous = current.previous.as_ref().unwrap();
+        assert_eq!(previous.version, "1.1.0");
+        assert!(previous.previous.is_none());
+    }
+
+    #[test]
+    fn test_record_install_without_backup_drops_history() {
+        let mut manifest = Manifest::default();
+        manifest.record_install("gc", entry("1.0.0"), false);
+        manifest.record_install("gc", entry("1.1.0"), false);
+        assert!(manifest.tools["gc"].previous.is_none());
+    }
+
+    #[test]
+    fn test_record_rollback_swaps_entries() {
+        let mut manifest = Manifest::default();
+        manifest.record_install("gc", entry("1.0.0"), false);
+        manifest.record_install("gc", entry("1.1.0"), true);
+
+        manifest.record_rollback("gc").unwrap();
+        assert_eq!(manifest.tools["gc"].version, "1.0.0");
+        assert_eq!(
+            manifest.tools["gc"].previous.as_ref().unwrap().version,
+            "1.1.0"
+        );
+
+        assert!(manifest.record_rollback("missing").is_err());
+    }
+
+    #[test]
+    fn test_manifest_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("ucp-manifest-{}", std::process::id()));
+        let path = dir.join("installed.json");
+
+        let mut manifest = Manifest::default();
+        manifest.record_install("ask", entry("1.3.0"), false);
+        manifest.save_to(&path).unwrap();
+
+        let loaded = Manifest::load_from(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded.tools["ask"], manifest.tools["ask"]);
+    }
+
+    #[test]
+    fn test_hash_file() {
+        let path = std::env::temp_dir().join(format!("ucp-hash-{}", std::process::id()));
+        fs::write(&path, b"hello").unwrap();
+        let hash = hash_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            hash,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+}
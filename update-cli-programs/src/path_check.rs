@@ -0,0 +1,270 @@
+use anyhow::Result;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// Check that `target_dir` is on PATH, warn about binaries that shadow the
+/// installed ones, and offer to add an export line to the shell rc file
+pub fn check(target_dir: &Path, home: &Path, programs: &[String]) -> Result<()> {
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+    let entries: Vec<PathBuf> = std::env::split_paths(&path_var).collect();
+
+    let shadowed = find_shadowed(programs, target_dir, &entries);
+    if !shadowed.is_empty() {
+        println!();
+        println!(
+            "Warning: these commands run a different binary than the one in {}:",
+            target_dir.display()
+        );
+        for (program, found) in &shadowed {
+            println!("  {} -> {}", program, found.display());
+        }
+        println!(
+            "Remove them or move {} earlier in PATH.",
+            target_dir.display()
+        );
+    }
+
+    let Some((shell_name, rc_files)) = shell_rc_files(home) else {
+        if !contains_dir(&entries, target_dir) {
+            println!();
+            println!("Note: {} is not on your PATH.", target_dir.display());
+        }
+        return Ok(());
+    };
+
+    let configured = rc_files.iter().any(|rc_file| {
+        fs::read_to_string(rc_file).is_ok_and(|content| rc_adds_dir(&content, target_dir, home))
+    });
+    if configured {
+        return Ok(());
+    }
+    // Set up somewhere we don't look (e.g. a system profile or a plugin manager)
+    if contains_dir(&entries, target_dir) {
+        return Ok(());
+    }
+
+    let rc_file = &rc_files[0];
+    let line = export_line(target_dir, home);
+    println!();
+    println!(
+        "{} is not on your PATH, and your {} config doesn't add it. Add this to {}:",
+        target_dir.display(),
+        shell_name,
+        rc_file.display()
+    );
+    println!();
+    println!("  {}", line);
+
+    if !io::stdin().is_terminal() {
+        return Ok(());
+    }
+
+    println!();
+    eprint!("Add it automatically? [y/N] ");
+    io::stderr().flush().ok();
+
+    let mut response = String::new();
+    io::stdin().lock().read_line(&mut response)?;
+
+    if response.trim().eq_ignore_ascii_case("y") {
+        let mut file = OpenOptions::new().append(true).create(true).open(rc_file)?;
+        writeln!(file, "\n# Added by update-cli-programs\n{}", line)?;
+        println!("\nAdded! Run this to activate:");
+        println!("  source {}", rc_file.display());
+    } else {
+        println!("Skipped.");
+    }
+
+    Ok(())
+}
+
+/// The user's shell and the rc files that may set PATH, the interactive
+/// rc file (where an export line is added) first
+fn shell_rc_files(home: &Path) -> Option<(&'static str, Vec<PathBuf>)> {
+    let shell = std::env::var("SHELL").ok()?;
+    let shell_name = Path::new(&shell).file_name()?.to_str()?;
+
+    match shell_name {
+        "zsh" => Some((
+            "zsh",
+            [".zshrc", ".zshenv", ".zprofile"]
+                .iter()
+                .map(|f| home.join(f))
+                .collect(),
+        )),
+        "bash" => Some((
+            "bash",
+            [".bashrc", ".bash_profile", ".profile"]
+                .iter()
+                .map(|f| home.join(f))
+                .collect(),
+        )),
+        _ => None,
+    }
+}
+
+/// Check if an rc file has an uncommented line adding `dir` to PATH
+fn rc_adds_dir(content: &str, dir: &Path, home: &Path) -> bool {
+    let spellings = dir_spellings(dir, home);
+    content
+        .lines()
+        .map(str::trim)
+        // `PATH=...`, `export PATH=...`, or zsh's `path=(... $path)`
+        .filter(|line| !line.starts_with('#') && line.to_lowercase().contains("path"))
+        .any(|line| spellings.iter().any(|s| line.contains(s.as_str())))
+}
+
+/// Ways `dir` may be written in an rc file
+fn dir_spellings(dir: &Path, home: &Path) -> Vec<String> {
+    let mut spellings = vec![dir.display().to_string()];
+    if let Ok(relative) = dir.strip_prefix(home) {
+        let relative = relative.display();
+        spellings.push(format!("$HOME/{}", relative));
+        spellings.push(format!("${{HOME}}/{}", relative));
+        spellings.push(format!("~/{}", relative));
+    }
+    spellings
+}
+
+/// The export line to add, written relative to `$HOME` when possible
+fn export_line(dir: &Path, home: &Path) -> String {
+    let dir = match dir.strip_prefix(home) {
+        Ok(relative) => format!("$HOME/{}", relative.display()),
+        Err(_) => dir.display().to_string(),
+    };
+    format!("export PATH=\"{}:$PATH\"", dir)
+}
+
+fn contains_dir(entries: &[PathBuf], dir: &Path) -> bool {
+    entries.iter().any(|entry| same_dir(entry, dir))
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    a == b
+        || matches!(
+            (a.canonicalize(), b.canonicalize()),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+/// Installed programs that resolve to another binary because it comes earlier
+/// in PATH (or because `target_dir` isn't on PATH at all)
+fn find_shadowed(
+    programs: &[String],
+    target_dir: &Path,
+    entries: &[PathBuf],
+) -> Vec<(String, PathBuf)> {
+    let earlier: Vec<&PathBuf> = entries
+        .iter()
+        .take_while(|entry| !same_dir(entry, target_dir))
+        .collect();
+
+    programs
+        .iter()
+        .filter(|program| target_dir.join(program).exists())
+        .filter_map(|program| {
+            earlier
+                .iter()
+                .map(|entry| entry.join(program))
+                .find(|candidate| is_executable(candidate))
+                .map(|found| (program.clone(), found))
+        })
+        .collect()
+}
+
+fn is_executable(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rc_adds_dir() {
+        let home = Path::new("/home/user");
+        let dir = Path::new("/home/user/.local/bin");
+
+        assert!(rc_adds_dir(
+            "export PATH=\"$HOME/.local/bin:$PATH\"",
+            dir,
+            home
+        ));
+        assert!(rc_adds_dir("path=(~/.local/bin $path)", dir, home));
+        assert!(rc_adds_dir("PATH=~/.local/bin:$PATH", dir, home));
+        assert!(rc_adds_dir(
+            "export PATH=${HOME}/.local/bin:$PATH",
+            dir,
+            home
+        ));
+        assert!(rc_adds_dir(
+            "export PATH=/home/user/.local/bin:$PATH",
+            dir,
+            home
+        ));
+        // Commented out or unrelated lines don't count
+        assert!(!rc_adds_dir(
+            "# export PATH=\"$HOME/.local/bin:$PATH\"",
+            dir,
+            home
+        ));
+        assert!(!rc_adds_dir("alias ll='ls -l ~/.local/bin'", dir, home));
+        assert!(!rc_adds_dir("export PATH=\"$HOME/bin:$PATH\"", dir, home));
+    }
+
+    #[test]
+    fn test_export_line() {
+        let home = Path::new("/home/user");
+        assert_eq!(
+            export_line(Path::new("/home/user/.local/bin"), home),
+            "export PATH=\"$HOME/.local/bin:$PATH\""
+        );
+        assert_eq!(
+            export_line(Path::new("/opt/tools/bin"), home),
+            "export PATH=\"/opt/tools/bin:$PATH\""
+        );
+    }
+
+    #[test]
+    fn test_find_shadowed() {
+        let root =
+            std::env::temp_dir().join(format!("update-cli-programs-path-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let (before, target, after) =
+            (root.join("before"), root.join("target"), root.join("after"));
+        for dir in [&before, &target, &after] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        let make_executable = |path: PathBuf| {
+            fs::write(&path, "").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        };
+        for program in ["gc", "ask", "sandy"] {
+            make_executable(target.join(program));
+        }
+        make_executable(before.join("gc"));
+        make_executable(after.join("ask"));
+        // Not executable, so it doesn't shadow anything
+        fs::write(before.join("sandy"), "").unwrap();
+
+        let programs: Vec<String> = ["gc", "ask", "sandy", "missing"].map(String::from).to_vec();
+        let on_path = find_shadowed(
+            &programs,
+            &target,
+            &[before.clone(), target.clone(), after.clone()],
+        );
+        let off_path = find_shadowed(&programs, &target, &[before.clone(), after.clone()]);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(on_path, vec![("gc".to_string(), before.join("gc"))]);
+        assert_eq!(
+            off_path,
+            vec![
+                ("gc".to_string(), before.join("gc")),
+                ("ask".to_string(), after.join("ask"))
+            ]
+        );
+    }
+}